@@ -0,0 +1,50 @@
+/*
+ * C bindings for the dominoes engine
+ *
+ * Every seat is external, so the front end makes every move, either by choosing one of the legal moves or by asking the AI to
+ * choose. Moves and states are passed as JSON. Strings returned by these functions belong to the caller, who must free them with
+ * dominoes_string_free. A function that fails returns NULL, and the reason can be read with dominoes_last_error.
+ */
+
+#ifndef DOMINOES_H
+#define DOMINOES_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A game */
+typedef struct DominoesGame DominoesGame;
+
+/* Creates a game for 2 to 4 players and deals the hands. The same seed and the same moves always produce the same game. */
+DominoesGame *dominoes_game_new(size_t num_players, uint64_t seed);
+
+/* Frees a game */
+void dominoes_game_free(DominoesGame *game);
+
+/* Returns the legal moves of the player whose turn it is, as a JSON array, e.g. [{"kind":"play","tile":[6,6],"end":null}] */
+char *dominoes_legal_moves(const DominoesGame *game);
+
+/* Makes a move, given as len bytes of JSON in the form returned by dominoes_legal_moves. Returns the events as a JSON array. */
+char *dominoes_apply_move(DominoesGame *game, const uint8_t *data, size_t len);
+
+/* Lets the AI make a move. spec chooses the computer player, e.g. "mcts:hard" or "greedy", or is NULL for the default AI. */
+char *dominoes_ai_move(DominoesGame *game, const char *spec);
+
+/* Returns the state of the game as a JSON object */
+char *dominoes_state_json(const DominoesGame *game);
+
+/* Returns the message of the last error on this thread, or NULL. The message must not be freed. */
+const char *dominoes_last_error(void);
+
+/* Frees a string returned by one of these functions */
+void dominoes_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DOMINOES_H */
@@ -0,0 +1,315 @@
+//! C bindings for the engine
+//!
+//! This crate exposes the engine through a C ABI, so that it can back mobile apps and front ends written in other languages. The
+//! declarations are in `include/dominoes.h`. As with the JavaScript bindings (see the `dominoes-wasm` crate), every seat is
+//! external, so the front end makes every move, either by choosing one of the legal moves or by asking the AI to choose. Moves
+//! and states are passed as JSON (see `engine::view`).
+//!
+//! Strings returned by these functions belong to the caller, who must free them with `dominoes_string_free`. A function that
+//! fails returns null, and the reason can be read with `dominoes_last_error`.
+//!
+//! # Examples
+//! ```c
+//! DominoesGame *game = dominoes_game_new(2, 42);
+//! char *events = dominoes_apply_move(game, (const uint8_t *)"{\"kind\":\"draw\"}", 15);
+//! if (events == NULL) {
+//!     printf("%s\n", dominoes_last_error());
+//! }
+//! dominoes_string_free(events);
+//! dominoes_game_free(game);
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use dominoes_state::Action;
+use engine::{Game, GameEvent, Move, Seat, StateView};
+use player::PlayerSpec;
+use rules::Configuration;
+
+thread_local! {
+    // The message of the last error on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// A game played through the C bindings
+pub struct DominoesGame {
+    game: Game,
+}
+
+/// Creates a game and deals the hands
+///
+/// # Arguments
+/// * `num_players` - The number of players (2 to 4)
+/// * `seed` - Determines the deal. The same seed and the same moves always produce the same game.
+///
+/// # Returns
+/// The game, waiting for the first player's move, or null if the number of players is not supported. The game must be freed with
+/// `dominoes_game_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn dominoes_game_new(num_players: usize, seed: u64) -> *mut DominoesGame {
+    let result = catch(|| {
+        if !(2..=4).contains(&num_players) {
+            return Err(format!("Unsupported number of players: {num_players}"));
+        }
+        let configuration = Configuration::new(
+            num_players,
+            Configuration::DEFAULT_VARIATION,
+            Configuration::DEFAULT_SET_ID,
+            Configuration::default_starting_hand_size(num_players, Configuration::DEFAULT_VARIATION),
+        );
+        let seats = (0..num_players).map(|_| Seat::External).collect();
+        let mut game = DominoesGame { game: Game::with_seed(&configuration, seats, seed) };
+        game.advance(Vec::new());
+        Ok(game)
+    });
+    result.map_or(ptr::null_mut(), |game| Box::into_raw(Box::new(game)))
+}
+
+/// Frees a game
+///
+/// # Safety
+/// `game` must be null or a game returned by `dominoes_game_new` that has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dominoes_game_free(game: *mut DominoesGame) {
+    if !game.is_null() {
+        // SAFETY: The caller guarantees that the game came from Box::into_raw in dominoes_game_new and is freed only once
+        drop(unsafe { Box::from_raw(game) });
+    }
+}
+
+/// Returns the legal moves of the player whose turn it is
+///
+/// # Returns
+/// A JSON array of moves, e.g. `[{"kind":"play","tile":[6,6],"end":null},{"kind":"draw"}]`, which is empty if the game is over,
+/// or null if `game` is null
+///
+/// # Safety
+/// `game` must be null or a valid game returned by `dominoes_game_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dominoes_legal_moves(game: *const DominoesGame) -> *mut c_char {
+    // SAFETY: The caller guarantees that the pointer is null or valid
+    let game = unsafe { game.as_ref() };
+    to_c_string(catch(|| {
+        let game = game.ok_or("The game is null")?;
+        let moves: Vec<Move> = game.legal_actions().iter().map(Move::from).collect();
+        Ok(serde_json::to_string(&moves).expect("A move can always be serialized"))
+    }))
+}
+
+/// Makes a move for the player whose turn it is
+///
+/// # Arguments
+/// * `game` - The game
+/// * `data` - The move as UTF-8 JSON, in the form returned by `dominoes_legal_moves`
+/// * `len` - The number of bytes in `data`
+///
+/// # Returns
+/// A JSON array of the events that occurred (see `engine::GameEvent`), or null if the move cannot be read or is not legal
+///
+/// # Safety
+/// `game` must be null or a valid game returned by `dominoes_game_new`, and `data` must be null or point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dominoes_apply_move(game: *mut DominoesGame, data: *const u8, len: usize) -> *mut c_char {
+    // SAFETY: The caller guarantees that the pointers are null or valid
+    let (game, data) = unsafe { (game.as_mut(), (!data.is_null()).then(|| slice::from_raw_parts(data, len))) };
+    to_c_string(catch(|| {
+        let game = game.ok_or("The game is null")?;
+        let data = data.ok_or("The move is null")?;
+        let requested: Move = serde_json::from_slice(data).map_err(|e| format!("The move is not valid: {e}"))?;
+        let action = requested.to_action(&game.game).ok_or("The move is not legal")?;
+        game.submit(action)
+    }))
+}
+
+/// Lets the AI make a move for the player whose turn it is
+///
+/// # Arguments
+/// * `game` - The game
+/// * `spec` - The computer player that chooses the move, e.g. `mcts:hard` or `greedy` (see `player::player_factory`), or null
+///   for the default AI
+///
+/// # Returns
+/// A JSON array of the events that occurred (see `engine::GameEvent`), or null if the game is over or the player is not a
+/// computer player
+///
+/// # Safety
+/// `game` must be null or a valid game returned by `dominoes_game_new`, and `spec` must be null or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dominoes_ai_move(game: *mut DominoesGame, spec: *const c_char) -> *mut c_char {
+    // SAFETY: The caller guarantees that the pointers are null or valid
+    let (game, spec) = unsafe { (game.as_mut(), (!spec.is_null()).then(|| CStr::from_ptr(spec))) };
+    to_c_string(catch(|| {
+        let game = game.ok_or("The game is null")?;
+        let spec: PlayerSpec = match spec {
+            Some(spec) => spec.to_str().map_err(|_| "The player is not UTF-8")?.parse()?,
+            None => "mcts".parse()?,
+        };
+        let action = game.game.choose_action(&spec).map_err(|e| e.to_string())?;
+        game.submit(action)
+    }))
+}
+
+/// Returns the state of the game
+///
+/// # Returns
+/// A JSON object with the layout, the hands of all players, the number of tiles in the boneyard, whose turn it is, and the result
+/// once the game is over (see `engine::StateView`), or null if `game` is null
+///
+/// # Safety
+/// `game` must be null or a valid game returned by `dominoes_game_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dominoes_state_json(game: *const DominoesGame) -> *mut c_char {
+    // SAFETY: The caller guarantees that the pointer is null or valid
+    let game = unsafe { game.as_ref() };
+    to_c_string(catch(|| {
+        let game = game.ok_or("The game is null")?;
+        Ok(serde_json::to_string(&StateView::new(&game.game)).expect("The state can always be serialized"))
+    }))
+}
+
+/// Returns the message of the last error on this thread
+///
+/// # Returns
+/// The message, or null if no function has failed on this thread. The message belongs to the library and is valid until the next
+/// call to a function that fails.
+#[unsafe(no_mangle)]
+pub extern "C" fn dominoes_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| error.as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Frees a string returned by one of these functions
+///
+/// # Safety
+/// `s` must be null or a string returned by one of these functions that has not been freed. It must not be the message returned
+/// by `dominoes_last_error`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dominoes_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: The caller guarantees that the string came from CString::into_raw and is freed only once
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+impl DominoesGame {
+    // Returns the legal actions of the player whose turn it is
+    fn legal_actions(&self) -> Vec<Action> {
+        self.game.legal_actions(self.game.state().whose_turn)
+    }
+
+    // Submits an action, and returns the events that occurred until the next move is needed as JSON
+    fn submit(&mut self, action: Action) -> Result<String, String> {
+        let events = self.game.submit(action).map_err(|e| e.to_string())?;
+        let events = self.advance(events);
+        Ok(serde_json::to_string(&events).expect("An event can always be serialized"))
+    }
+
+    // Steps the game until it needs a move or is over, and returns all of the events
+    fn advance(&mut self, mut events: Vec<GameEvent>) -> Vec<GameEvent> {
+        while !self.game.is_over() && !matches!(events.last(), Some(GameEvent::NeedsInput { .. })) {
+            events.extend(self.game.step());
+        }
+        events
+    }
+}
+
+// Runs the body of a function, recording the error if it fails. A panic is reported as an error, since it must not unwind into C.
+fn catch<T>(body: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    let result = panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Unknown error".to_string());
+        Err(format!("Internal error: {message}"))
+    });
+    result
+        .map_err(|message| {
+            let message = CString::new(message.replace('\0', " ")).expect("NUL characters have been replaced");
+            LAST_ERROR.set(Some(message));
+        })
+        .ok()
+}
+
+// Converts the result of a function to a string that belongs to the caller, or null if there is no result
+fn to_c_string(result: Option<String>) -> *mut c_char {
+    result.map_or(ptr::null_mut(), |s| CString::new(s).expect("JSON has no NUL characters").into_raw())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Takes a string returned by the bindings
+    fn take(s: *mut c_char) -> Option<String> {
+        (!s.is_null()).then(|| {
+            let string = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+            unsafe { dominoes_string_free(s) };
+            string
+        })
+    }
+
+    // Returns the last error
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(dominoes_last_error()) }.to_str().unwrap().to_string()
+    }
+
+    // Applies a move given as JSON
+    fn apply(game: *mut DominoesGame, json: &str) -> Option<String> {
+        take(unsafe { dominoes_apply_move(game, json.as_ptr(), json.len()) })
+    }
+
+    #[test]
+    fn test_ffi_plays_a_game() {
+        let game = dominoes_game_new(2, 42);
+        assert!(!game.is_null());
+        for _ in 0..100 {
+            let state: serde_json::Value = serde_json::from_str(&take(unsafe { dominoes_state_json(game) }).unwrap()).unwrap();
+            if state["game_is_over"] == true {
+                break;
+            }
+            let moves: Vec<serde_json::Value> =
+                serde_json::from_str(&take(unsafe { dominoes_legal_moves(game) }).unwrap()).unwrap();
+            let events = apply(game, &moves[0].to_string()).unwrap();
+            assert!(events.starts_with('['));
+        }
+        assert_eq!(take(unsafe { dominoes_legal_moves(game) }).unwrap(), "[]");
+        unsafe { dominoes_game_free(game) };
+    }
+
+    #[test]
+    fn test_ffi_ai_move() {
+        let game = dominoes_game_new(2, 7);
+        let events = take(unsafe { dominoes_ai_move(game, c"greedy".as_ptr()) }).unwrap();
+        assert!(events.contains("tile_played") || events.contains("tile_drawn"), "{events}");
+
+        assert!(take(unsafe { dominoes_ai_move(game, c"human".as_ptr()) }).is_none());
+        assert_eq!(last_error(), "A human player cannot choose an action for the AI");
+        assert!(take(unsafe { dominoes_ai_move(game, c"robot".as_ptr()) }).is_none());
+        unsafe { dominoes_game_free(game) };
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        assert!(dominoes_game_new(1, 0).is_null());
+        assert_eq!(last_error(), "Unsupported number of players: 1");
+
+        let game = dominoes_game_new(2, 42);
+        assert!(apply(game, "{\"kind\":").is_none());
+        assert!(last_error().starts_with("The move is not valid"));
+        assert!(apply(game, r#"{"kind":"play","tile":[99,99],"end":null}"#).is_none());
+        assert!(last_error().starts_with("The move is not valid"));
+        assert!(apply(game, r#"{"kind":"play","tile":[0,0],"end":3}"#).is_none());
+        assert_eq!(last_error(), "The move is not legal");
+        assert!(unsafe { dominoes_apply_move(game, ptr::null(), 0) }.is_null());
+        assert_eq!(last_error(), "The move is null");
+        unsafe { dominoes_game_free(game) };
+
+        assert!(unsafe { dominoes_state_json(ptr::null()) }.is_null());
+        assert_eq!(last_error(), "The game is null");
+        unsafe { dominoes_game_free(ptr::null_mut()) };
+    }
+}
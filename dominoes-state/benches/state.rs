@@ -0,0 +1,72 @@
+//! Benchmarks of the game state
+//!
+//! Run with `cargo bench -p dominoes-state`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use hidden_game_player::State;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use dominoes_state::{Action, DominoesState, ZHash};
+use rules::{Configuration, Tile};
+
+// Returns a state in the middle of a game. Tiles are drawn from the boneyard, and each one is played on the first end that it
+// matches until the given number have been played.
+fn mid_game(configuration: &Configuration, num_played: usize) -> DominoesState {
+    let mut state = DominoesState::with_rng(configuration, &mut ChaCha8Rng::seed_from_u64(1));
+    let mut played = 0;
+    while played < num_played {
+        let tile = state.draw_tile().expect("The boneyard should have enough tiles");
+        if let Some(end) = playable_end(&state, tile) {
+            state.play_tile_unchecked(tile, end);
+            played += 1;
+        }
+    }
+    state
+}
+
+// Returns the end that a tile can be played on, or None if it cannot be played
+fn playable_end(state: &DominoesState, tile: Tile) -> Option<Option<u8>> {
+    if state.layout.is_empty() {
+        return tile.is_double().then_some(None);
+    }
+    let (a, b) = tile.as_tuple();
+    [a, b].into_iter().find(|&end| state.can_play_tile(&tile, Some(end))).map(Some)
+}
+
+fn bench_zhash(c: &mut Criterion) {
+    let configuration = Configuration::default();
+    let state = mid_game(&configuration, 8);
+
+    c.bench_function("ZHash::from_state", |b| b.iter(|| ZHash::from_state(black_box(&state.layout), 0)));
+    c.bench_function("ZHash update", |b| {
+        b.iter(|| {
+            let mut hash = black_box(state.fingerprint);
+            hash.add_tile(u8::from(Tile::from((3, 6)))).change_end_count(6, 1, 0).change_end_count(3, 0, 1).turn();
+            hash
+        })
+    });
+}
+
+fn bench_apply(c: &mut Criterion) {
+    let configuration = Configuration::default();
+    let state = mid_game(&configuration, 8);
+    let player_id = state.whose_turn;
+    let tile = *state.boneyard.peek().expect("The boneyard should not be empty");
+    let (play_tile, end) = state
+        .boneyard
+        .remaining()
+        .iter()
+        .find_map(|&tile| playable_end(&state, tile).map(|end| (tile, end)))
+        .expect("A tile in the boneyard should be playable");
+
+    let play = Action::play_unchecked(player_id, play_tile, end);
+    let draw = Action::draw(player_id, tile);
+    c.bench_function("DominoesState::apply play", |b| b.iter(|| black_box(&state).apply(&play)));
+    c.bench_function("DominoesState::apply draw", |b| b.iter(|| black_box(&state).apply(&draw)));
+}
+
+criterion_group!(benches, bench_zhash, bench_apply);
+criterion_main!(benches);
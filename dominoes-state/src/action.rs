@@ -4,7 +4,8 @@
 
 use std::fmt::Display;
 
-use rules::{self, Tile};
+use crate::{DominoesError, DominoesState, Hand};
+use rules::{self, DrawRule, Tile, Variation};
 
 /// Represents an action taken by a player
 ///
@@ -20,12 +21,13 @@ use rules::{self, Tile};
 /// let draw_action = Action::draw(0, Tile::from((1, 2)));
 ///
 /// // Player plays a tile
-/// let play_action = Action::play(1, Tile::from((3, 4)), Some(3));
+/// let play_action = Action::play_unchecked(1, Tile::from((3, 4)), Some(3));
 ///
 /// // Player passes their turn
 /// let pass_action = Action::pass(0);
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Action {
     /// The ID of the player who took this action
     pub player_id: u8,
@@ -111,18 +113,52 @@ impl Action {
 
     /// Creates an action representing playing a tile without drawing
     ///
-    /// This is used when a player plays a tile from their hand without needing to draw from the boneyard first.
+    /// This is used when a player plays a tile from their hand without needing to draw from the boneyard first. It does not check
+    /// whether the tile can be played on the end (see `validate`).
     ///
     /// # Arguments
     /// * `player_id` - The ID of the player playing the tile
     /// * `tile` - The tile being played on the layout
+    /// * `end` - The open end that the tile is played on, or `None` for the first tile
     ///
     /// # Returns
     /// A new Action representing a play-only turn
     ///
+    /// # Errors
+    /// * `DominoesError::NotCanonical` if the tile's ordinal does not stand for a tile
+    /// * `DominoesError::InvalidMove` if the end does not match either side of the tile
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesError};
+    /// # use rules::Tile;
+    ///
+    /// let tile = Tile::from((3, 6));
+    /// let action = Action::play(0, tile, Some(6)).unwrap();
+    /// assert_eq!(action.tile_drawn, None);
+    /// assert_eq!(action.tile_played, Some((tile, Some(6))));
+    /// assert_eq!(Action::play(0, tile, Some(5)), Err(DominoesError::InvalidMove { tile, end: Some(5) }));
+    /// ```
+    pub fn play(player_id: u8, tile: Tile, end: Option<u8>) -> Result<Self, DominoesError> {
+        let (a, b) = Tile::checked_new(tile.ordinal).ok_or(DominoesError::NotCanonical(tile.ordinal))?.as_tuple();
+        if end.is_some_and(|end| end != a && end != b) {
+            return Err(DominoesError::InvalidMove { tile, end });
+        }
+        Ok(Self::play_unchecked(player_id, tile, end))
+    }
+
+    /// Creates an action representing playing a tile without drawing, without checking it
+    ///
+    /// This is the panicking version of `play`, for tiles and ends that are known to be valid, such as those of the legal
+    /// actions generated by the search.
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player playing the tile
+    /// * `tile` - The tile being played on the layout
+    /// * `end` - The open end that the tile is played on, or `None` for the first tile
+    ///
     /// # Panics
-    /// This function will panic if the tile is not a double and no end value is provided.
-    /// This function will also panic if the provided end does not match either side of the tile.
+    /// Panics if the provided end does not match either side of the tile
     ///
     /// # Examples
     /// ```rust
@@ -130,13 +166,11 @@ impl Action {
     /// # use rules::Tile;
     ///
     /// let tile = Tile::from((6, 6));
-    /// let action = Action::play(0, tile, Some(6));
-    /// assert_eq!(action.tile_drawn, None);
+    /// let action = Action::play_unchecked(0, tile, Some(6));
     /// assert_eq!(action.tile_played, Some((tile, Some(6))));
     /// ```
-    pub fn play(player_id: u8, tile: Tile, end: Option<u8>) -> Self {
+    pub fn play_unchecked(player_id: u8, tile: Tile, end: Option<u8>) -> Self {
         assert!(end.is_none() || end == Some(tile.as_tuple().0) || end == Some(tile.as_tuple().1));
-        assert!(end.is_some() || tile.is_double());
         Self {
             player_id,
             tile_drawn: None,
@@ -214,12 +248,177 @@ impl Action {
     /// # use dominoes_state::Action;
     /// # use rules::Tile;
     /// let tile = Tile::from((6, 6));
-    /// let action = Action::play(0, tile, Some(6));
+    /// let action = Action::play_unchecked(0, tile, Some(6));
     /// assert!(action.is_play());
     /// ```
     pub fn is_play(&self) -> bool {
         self.tile_played.is_some()
     }
+
+    /// Checks if the action ends the player's turn
+    ///
+    /// A player who draws a tile continues until they play or pass, and in Mexican Train, a player who plays a double on a train
+    /// plays again.
+    ///
+    /// # Arguments
+    /// * `variation` - The variation being played
+    ///
+    /// # Returns
+    /// `true` if the next player's turn begins, `false` if the same player must act again
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Action;
+    /// # use rules::{Tile, Variation};
+    ///
+    /// assert!(!Action::draw(0, Tile::from((1, 2))).ends_turn(Variation::Traditional));
+    /// assert!(!Action::play_unchecked(0, Tile::from((3, 3)), Some(3)).ends_turn(Variation::MexicanTrain));
+    /// assert!(Action::pass(0).ends_turn(Variation::MexicanTrain));
+    /// ```
+    pub fn ends_turn(&self, variation: Variation) -> bool {
+        match variation {
+            Variation::Traditional => self.tile_drawn.is_none(),
+            Variation::MexicanTrain => match self.tile_played {
+                Some((tile, Some(_))) => !tile.is_double(),
+                Some((_, None)) => true,
+                None => self.tile_drawn.is_none(),
+            },
+            _ => self.tile_played.is_some() || self.tile_drawn.is_none(),
+        }
+    }
+
+    /// Checks if two actions are the same choice
+    ///
+    /// A player cannot choose which tile they draw, so two draws by the same player are the same choice even if they draw
+    /// different tiles, e.g. in different sampled deals of the hidden tiles.
+    ///
+    /// # Returns
+    /// `true` if the actions are equal apart from the tile drawn, `false` otherwise
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Action;
+    /// # use rules::Tile;
+    ///
+    /// let draw = Action::draw(0, Tile::from((1, 2)));
+    /// assert!(draw.is_same_choice(&Action::draw(0, Tile::from((3, 4)))));
+    /// assert!(!draw.is_same_choice(&Action::draw(1, Tile::from((1, 2)))));
+    /// assert!(!draw.is_same_choice(&Action::pass(0)));
+    /// ```
+    pub fn is_same_choice(&self, other: &Action) -> bool {
+        self.player_id == other.player_id && self.is_draw() == other.is_draw() && self.tile_played == other.tile_played
+    }
+
+    /// Returns the action drawing from the boneyard of a state instead, if it is a draw
+    ///
+    /// A search of sampled deals draws from a sampled boneyard, so a draw that it chooses must be changed to draw the tile that
+    /// is really next before it is applied to the state.
+    ///
+    /// # Arguments
+    /// * `state` - The state that the action is to be applied to
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesState};
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let state = DominoesState::new(&Configuration::default());
+    /// let next = *state.boneyard.peek().unwrap();
+    /// assert_eq!(Action::draw(0, Tile::from((1, 2))).drawing_from(&state), Action::draw(0, next));
+    /// assert_eq!(Action::pass(0).drawing_from(&state), Action::pass(0));
+    /// ```
+    pub fn drawing_from(mut self, state: &DominoesState) -> Self {
+        if self.is_draw() {
+            self.tile_drawn = state.boneyard.peek().copied();
+        }
+        self
+    }
+
+    /// Checks whether this action is legal for the given state and the acting player's hand
+    ///
+    /// The check follows the same rules that the players follow: a player may only act on their own turn, must play a tile
+    /// from their hand that fits the layout, may only draw when they have no playable tile, and may only pass when they have no
+    /// playable tile and cannot draw. A drawn tile must be the next tile in the boneyard. Depending on the rule for drawing, a
+    /// player may never draw, or may draw only one tile and pass if they cannot play it (see `DominoesState::can_draw`).
+    ///
+    /// # Arguments
+    /// * `state` - The current (authoritative) game state
+    /// * `hand` - The hand of the player taking this action
+    ///
+    /// # Returns
+    /// `Ok(())` if the action is legal, or the reason it is not
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesState, Hand};
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let state = DominoesState::new(&config);
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((6, 6)));
+    ///
+    /// assert!(Action::play_unchecked(0, Tile::from((6, 6)), None).validate(&state, &hand).is_ok());
+    /// assert!(Action::play_unchecked(0, Tile::from((5, 5)), None).validate(&state, &hand).is_err());
+    /// ```
+    pub fn validate(&self, state: &DominoesState, hand: &Hand) -> Result<(), DominoesError> {
+        if state.game_is_over {
+            return Err(DominoesError::GameOver);
+        }
+        if self.player_id != state.whose_turn {
+            return Err(DominoesError::WrongTurn(self.player_id));
+        }
+
+        let has_playable_tile = hand.tiles().iter().any(|tile| state.can_play_tile(tile, None));
+
+        if let Some(tile) = self.tile_drawn {
+            if has_playable_tile {
+                return Err(DominoesError::DrawWithPlayableTile);
+            }
+            match state.draw_rule() {
+                DrawRule::NoDraw => return Err(DominoesError::NoDrawing),
+                DrawRule::DrawOne if state.has_drawn() => return Err(DominoesError::AlreadyDrawn),
+                _ => {}
+            }
+            match state.boneyard.peek() {
+                Some(next) if *next == tile => {}
+                Some(_) => return Err(DominoesError::NotNextTile(tile)),
+                None => return Err(DominoesError::EmptyBoneyard),
+            }
+        }
+
+        if let Some((tile, end)) = self.tile_played {
+            if !hand.contains(&tile) && self.tile_drawn != Some(tile) {
+                return Err(DominoesError::NotInHand(tile));
+            }
+            if state.layout.is_empty() {
+                if end.is_some() {
+                    return Err(DominoesError::EndOfFirstTile);
+                }
+                if let Some(required) = state.opening().required_tile(hand.tiles())
+                    && tile != required
+                {
+                    return Err(DominoesError::WrongFirstTile { tile, required });
+                }
+            } else if end.is_none() {
+                return Err(DominoesError::NoEnd(tile));
+            }
+            if !state.is_end_in_set(end) || !state.can_play_tile(&tile, end) {
+                return Err(DominoesError::InvalidMove { tile, end });
+            }
+        }
+
+        if self.is_pass() {
+            if has_playable_tile {
+                return Err(DominoesError::PassWithPlayableTile);
+            }
+            if state.can_draw() {
+                return Err(DominoesError::PassWithTilesToDraw);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Display for Action {
@@ -247,6 +446,9 @@ impl Display for Action {
 /// The History struct maintains a chronological record of all actions taken by all players during a game. This can be used for
 /// game replay, analysis, or implementing undo functionality.
 ///
+/// The fingerprint of the state after each action can be recorded along with it (see `add_action_with_fingerprint`). Replaying the
+/// actions and comparing the fingerprints detects any difference between the recorded game and the replayed one.
+///
 /// # Examples
 /// ```rust
 /// # use dominoes_state::{History, Action};
@@ -257,10 +459,17 @@ impl Display for Action {
 ///
 /// assert_eq!(history.get_actions().len(), 2);
 /// ```
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct History {
     /// Vector storing all actions in chronological order
     actions: Vec<Action>,
+    /// The fingerprint of the state after each of the first actions. The chain ends at the first action recorded without one.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    fingerprints: Vec<u64>,
+    /// The player who went first, if it was recorded (see `set_first_player`)
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    first_player: Option<u8>,
 }
 
 impl History {
@@ -277,12 +486,12 @@ impl History {
     /// assert!(history.get_actions().is_empty());
     /// ```
     pub fn new() -> Self {
-        Self { actions: Vec::new() }
+        Self { actions: Vec::new(), fingerprints: Vec::new(), first_player: None }
     }
 
     /// Adds an action to the game history
     ///
-    /// Actions are added in chronological order and cannot be removed once added.
+    /// Actions are added in chronological order. Only the last action can be removed (see `pop_action`).
     ///
     /// # Arguments
     /// * `action` - The action to add to the history
@@ -300,6 +509,112 @@ impl History {
         self.actions.push(action);
     }
 
+    /// Adds an action to the game history along with the fingerprint of the state after it
+    ///
+    /// The fingerprint is only kept if every earlier action has one, so the fingerprints always form an unbroken chain from the
+    /// start of the game.
+    ///
+    /// # Arguments
+    /// * `action` - The action to add to the history
+    /// * `fingerprint` - The fingerprint of the state after the action
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{History, Action};
+    ///
+    /// let mut history = History::new();
+    /// history.add_action_with_fingerprint(Action::pass(0), 17);
+    /// history.add_action_with_fingerprint(Action::pass(1), 42);
+    /// assert_eq!(history.fingerprints(), &[17, 42]);
+    /// assert!(history.has_fingerprints());
+    /// ```
+    pub fn add_action_with_fingerprint(&mut self, action: Action, fingerprint: u64) {
+        if self.has_fingerprints() {
+            self.fingerprints.push(fingerprint);
+        }
+        self.actions.push(action);
+    }
+
+    /// Removes and returns the last action taken
+    ///
+    /// This is used to take back actions.
+    ///
+    /// # Returns
+    /// The last action, or `None` if the history is empty
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{History, Action};
+    ///
+    /// let mut history = History::new();
+    /// history.add_action(Action::pass(0));
+    /// history.add_action(Action::pass(1));
+    ///
+    /// assert_eq!(history.pop_action(), Some(Action::pass(1)));
+    /// assert_eq!(history.get_actions().len(), 1);
+    /// ```
+    pub fn pop_action(&mut self) -> Option<Action> {
+        let action = self.actions.pop();
+        self.fingerprints.truncate(self.actions.len());
+        action
+    }
+
+    /// Returns the fingerprints of the states after the actions, in chronological order
+    ///
+    /// There is a fingerprint for each action up to the first one that was added without one (see `add_action_with_fingerprint`).
+    pub fn fingerprints(&self) -> &[u64] {
+        &self.fingerprints
+    }
+
+    /// Records the player who goes first
+    ///
+    /// The first player is decided by the opening rule once the hands have been dealt, before any action is taken (see
+    /// `rules::Opening`).
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the first player
+    pub fn set_first_player(&mut self, player_id: u8) {
+        self.first_player = Some(player_id);
+    }
+
+    /// Returns the player who went first
+    ///
+    /// # Returns
+    /// The recorded first player, or if none was recorded, the player who took the first action, or `None` if there are no
+    /// actions either
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{History, Action};
+    ///
+    /// let mut history = History::new();
+    /// assert_eq!(history.first_player(), None);
+    /// history.add_action(Action::pass(1));
+    /// assert_eq!(history.first_player(), Some(1));
+    /// history.set_first_player(0);
+    /// assert_eq!(history.first_player(), Some(0));
+    /// ```
+    pub fn first_player(&self) -> Option<u8> {
+        self.first_player.or_else(|| self.actions.first().map(|action| action.player_id))
+    }
+
+    /// Returns `true` if every action has a fingerprint
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{History, Action};
+    ///
+    /// let mut history = History::new();
+    /// assert!(history.has_fingerprints());
+    /// history.add_action(Action::pass(0));
+    /// history.add_action_with_fingerprint(Action::pass(1), 42);
+    /// assert!(!history.has_fingerprints());
+    /// assert!(history.fingerprints().is_empty());
+    /// ```
+    pub fn has_fingerprints(&self) -> bool {
+        self.fingerprints.len() == self.actions.len()
+    }
+
     /// Gets all actions taken during the game
     ///
     /// Returns a reference to the complete list of actions in chronological order.
@@ -354,7 +669,7 @@ impl History {
     /// * `player_id` - The ID of the player whose actions to retrieve
     ///
     /// # Returns
-    /// A vector of references to the player's actions
+    /// A vector of references to the player's actions. Use `player_actions` to avoid allocating the vector.
     ///
     /// # Examples
     /// ```rust
@@ -369,7 +684,27 @@ impl History {
     /// println!("Player 0 took {} actions", player_actions.len());
     /// ```
     pub fn get_player_actions(&self, player_id: u8) -> Vec<&Action> {
-        self.actions.iter().filter(|action| action.player_id == player_id).collect()
+        self.player_actions(player_id).collect()
+    }
+
+    /// Returns an iterator over all actions taken by a specific player, in chronological order
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player whose actions to retrieve
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{History, Action};
+    ///
+    /// let mut history = History::new();
+    /// history.add_action(Action::pass(0));
+    /// history.add_action(Action::pass(1));
+    /// history.add_action(Action::pass(0));
+    ///
+    /// assert_eq!(history.player_actions(0).count(), 2);
+    /// ```
+    pub fn player_actions(&self, player_id: u8) -> impl Iterator<Item = &Action> {
+        self.actions.iter().filter(move |action| action.player_id == player_id)
     }
 
     /// Gets all actions that follow the last action by the specified player
@@ -381,7 +716,8 @@ impl History {
     /// * `player_id` - The ID of the player to check
     ///
     /// # Returns
-    /// A vector of references to actions that occurred after the player's last action
+    /// A vector of references to actions that occurred after the player's last action. Use `actions_after_player` to avoid
+    /// allocating the vector.
     ///
     /// # Examples
     /// ```rust
@@ -399,13 +735,33 @@ impl History {
     /// }
     /// ```
     pub fn get_actions_after_player(&self, player_id: u8) -> Vec<&Action> {
+        self.actions_after_player(player_id).iter().collect()
+    }
+
+    /// Returns the actions that follow the last action by the specified player
+    ///
+    /// The slice is empty if the player has no actions or if the player's last action is the final action in the history.
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player to check
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{History, Action};
+    ///
+    /// let mut history = History::new();
+    /// history.add_action(Action::pass(0));
+    /// history.add_action(Action::pass(1));
+    /// history.add_action(Action::pass(1));
+    ///
+    /// assert_eq!(history.actions_after_player(0), &[Action::pass(1), Action::pass(1)]);
+    /// assert!(history.actions_after_player(2).is_empty());
+    /// ```
+    pub fn actions_after_player(&self, player_id: u8) -> &[Action] {
         // Find the index of the last action by the specified player
-        if let Some(last_player_index) = self.actions.iter().rposition(|action| action.player_id == player_id) {
-            // Return all actions after that index
-            self.actions.iter().skip(last_player_index + 1).collect()
-        } else {
-            // Player has no actions, return empty vector
-            Vec::new()
+        match self.actions.iter().rposition(|action| action.player_id == player_id) {
+            Some(last_player_index) => &self.actions[last_player_index + 1..],
+            None => &[],
         }
     }
 }
@@ -414,7 +770,7 @@ impl History {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rules::Tile;
+    use rules::{Configuration, Tile, Variation};
 
     // Tests for Action struct
     #[test]
@@ -457,13 +813,22 @@ mod tests {
     fn test_action_play_tile() {
         let tile = Tile::from((2, 3));
         let end = Some(2);
-        let action = Action::play(1, tile, end);
+        let action = Action::play_unchecked(1, tile, end);
 
         assert_eq!(action.player_id, 1);
         assert_eq!(action.tile_drawn, None);
         assert_eq!(action.tile_played, Some((tile, end)));
     }
 
+    #[test]
+    fn test_action_play_checked() {
+        let tile = Tile::from((2, 3));
+        assert_eq!(Action::play(1, tile, Some(3)), Ok(Action::play_unchecked(1, tile, Some(3))));
+        assert_eq!(Action::play(1, tile, None), Ok(Action::play_unchecked(1, tile, None)));
+        assert_eq!(Action::play(1, tile, Some(4)), Err(DominoesError::InvalidMove { tile, end: Some(4) }));
+        assert_eq!(Action::play(1, Tile::new(253), None), Err(DominoesError::NotCanonical(253)));
+    }
+
     #[test]
     fn test_action_pass() {
         let action = Action::pass(0);
@@ -473,11 +838,107 @@ mod tests {
         assert_eq!(action.tile_played, None);
     }
 
+    #[test]
+    fn test_action_validate_play() {
+        let configuration = rules::Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((6, 6)));
+        hand.add_tile(Tile::from((4, 6)));
+
+        // Wrong player
+        assert_eq!(Action::play_unchecked(1, Tile::from((6, 6)), None).validate(&state, &hand), Err(DominoesError::WrongTurn(1)));
+        // Not in hand
+        assert_eq!(
+            Action::play_unchecked(0, Tile::from((5, 5)), None).validate(&state, &hand),
+            Err(DominoesError::NotInHand(Tile::from((5, 5))))
+        );
+        // The first tile is not played on an end
+        let action = Action::play_unchecked(0, Tile::from((4, 6)), Some(6));
+        assert_eq!(action.validate(&state, &hand), Err(DominoesError::EndOfFirstTile));
+        assert!(Action::play_unchecked(0, Tile::from((6, 6)), None).validate(&state, &hand).is_ok());
+
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        hand.remove_tile(&Tile::from((6, 6)));
+        assert!(Action::play_unchecked(0, Tile::from((4, 6)), Some(6)).validate(&state, &hand).is_ok());
+        assert_eq!(
+            Action::play_unchecked(0, Tile::from((4, 6)), Some(4)).validate(&state, &hand),
+            Err(DominoesError::InvalidMove { tile: Tile::from((4, 6)), end: Some(4) })
+        );
+
+        // An end that is not in the set cannot be played on
+        hand.add_tile(Tile::from((9, 9)));
+        assert_eq!(
+            Action::play_unchecked(0, Tile::from((9, 9)), Some(9)).validate(&state, &hand),
+            Err(DominoesError::InvalidMove { tile: Tile::from((9, 9)), end: Some(9) })
+        );
+
+        // Nothing is legal once the game is over
+        state.mark_game_over(None);
+        assert_eq!(Action::play_unchecked(0, Tile::from((4, 6)), Some(6)).validate(&state, &hand), Err(DominoesError::GameOver));
+    }
+
+    #[test]
+    fn test_action_validate_first_tile() {
+        let mut hand = Hand::new();
+        for tile in [(3, 3), (2, 5), (6, 6)] {
+            hand.add_tile(Tile::from(tile));
+        }
+        let first_tile = |variation, tile| {
+            let state = DominoesState::new(&Configuration::new(2, variation, 6, 7));
+            Action::play_unchecked(0, Tile::from(tile), None).validate(&state, &hand)
+        };
+
+        // Traditional requires the highest double and Bergen the lowest, while All Fives allows any tile
+        assert!(first_tile(Variation::Traditional, (6, 6)).is_ok());
+        assert_eq!(
+            first_tile(Variation::Traditional, (3, 3)),
+            Err(DominoesError::WrongFirstTile { tile: Tile::from((3, 3)), required: Tile::from((6, 6)) })
+        );
+        assert!(first_tile(Variation::Bergen, (3, 3)).is_ok());
+        assert!(first_tile(Variation::Bergen, (6, 6)).is_err());
+        assert!(first_tile(Variation::AllFives, (2, 5)).is_ok());
+        assert!(first_tile(Variation::AllFives, (3, 3)).is_ok());
+        assert!(first_tile(Variation::Traditional, (2, 5)).is_err());
+    }
+
+    #[test]
+    fn test_action_validate_draw_and_pass() {
+        let configuration = rules::Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.boneyard = rules::Boneyard::with(vec![Tile::from((0, 1))]);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((2, 3)));
+
+        // No playable tile, so the player must draw the next tile rather than pass
+        assert_eq!(Action::pass(0).validate(&state, &hand), Err(DominoesError::PassWithTilesToDraw));
+        assert_eq!(
+            Action::draw(0, Tile::from((1, 1))).validate(&state, &hand),
+            Err(DominoesError::NotNextTile(Tile::from((1, 1))))
+        );
+        assert!(Action::draw(0, Tile::from((0, 1))).validate(&state, &hand).is_ok());
+
+        // Once the boneyard is empty, passing is allowed
+        state.draw_tile();
+        assert_eq!(Action::draw(0, Tile::from((0, 1))).validate(&state, &hand), Err(DominoesError::EmptyBoneyard));
+        assert!(Action::pass(0).validate(&state, &hand).is_ok());
+
+        // A player with a playable tile may neither draw nor pass
+        hand.add_tile(Tile::from((3, 6)));
+        assert_eq!(Action::pass(0).validate(&state, &hand), Err(DominoesError::PassWithPlayableTile));
+        assert_eq!(
+            Action::draw(0, Tile::from((0, 1))).validate(&state, &hand),
+            Err(DominoesError::DrawWithPlayableTile)
+        );
+    }
+
     #[test]
     fn test_action_clone() {
         let tile = Tile::from((1, 1));
         let end = Some(1);
-        let action1 = Action::play(1, tile, end);
+        let action1 = Action::play_unchecked(1, tile, end);
         let action2 = action1.clone();
 
         assert_eq!(action1.player_id, action2.player_id);
@@ -497,6 +958,32 @@ mod tests {
         assert!(debug_string.contains("tile_played"));
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_action_serde() {
+        let mut history = History::new();
+        history.add_action(Action::draw(0, Tile::from((4, 5))));
+        history.add_action(Action::play_unchecked(1, Tile::from((3, 6)), Some(6)));
+        history.add_action(Action::pass(0));
+
+        let json = serde_json::to_string(&history).unwrap();
+        let restored: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_actions(), history.get_actions());
+        assert!(!json.contains("fingerprints"));
+
+        let mut history = History::new();
+        history.add_action_with_fingerprint(Action::pass(0), 7);
+        let json = serde_json::to_string(&history).unwrap();
+        let restored: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.fingerprints(), &[7]);
+        assert!(!json.contains("first_player"));
+
+        history.set_first_player(1);
+        let json = serde_json::to_string(&history).unwrap();
+        let restored: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.first_player(), Some(1));
+    }
+
     // Tests for History struct
     #[test]
     fn test_history_default() {
@@ -555,6 +1042,42 @@ mod tests {
         assert_eq!(history.get_last_action().unwrap().player_id, 1);
     }
 
+    #[test]
+    fn test_history_pop_action() {
+        let mut history = History::default();
+        assert_eq!(history.pop_action(), None);
+
+        history.add_action(Action::pass(0));
+        history.add_action(Action::draw(1, Tile::from((2, 3))));
+        assert_eq!(history.pop_action(), Some(Action::draw(1, Tile::from((2, 3)))));
+        assert_eq!(history.get_last_action(), Some(&Action::pass(0)));
+        assert_eq!(history.pop_action(), Some(Action::pass(0)));
+        assert!(history.get_actions().is_empty());
+    }
+
+    #[test]
+    fn test_history_fingerprints() {
+        let mut history = History::new();
+        history.add_action_with_fingerprint(Action::pass(0), 1);
+        history.add_action_with_fingerprint(Action::pass(1), 2);
+        assert_eq!(history.fingerprints(), &[1, 2]);
+
+        // An action without a fingerprint ends the chain
+        history.add_action(Action::pass(0));
+        history.add_action_with_fingerprint(Action::pass(1), 4);
+        assert_eq!(history.fingerprints(), &[1, 2]);
+        assert!(!history.has_fingerprints());
+
+        // Taking the actions back restores it
+        history.pop_action();
+        history.pop_action();
+        assert!(history.has_fingerprints());
+        history.pop_action();
+        assert_eq!(history.fingerprints(), &[1]);
+        history.add_action_with_fingerprint(Action::pass(1), 5);
+        assert_eq!(history.fingerprints(), &[1, 5]);
+    }
+
     #[test]
     fn test_history_get_player_actions() {
         let mut history = History::default();
@@ -562,7 +1085,7 @@ mod tests {
         let tile2 = Tile::from((3, 4));
 
         // Add actions for different players
-        history.add_action(Action::play(0, tile1, Some(1)));
+        history.add_action(Action::play_unchecked(0, tile1, Some(1)));
         history.add_action(Action::draw(1, tile2));
         history.add_action(Action::pass(0));
         history.add_action(Action::pass(1));
@@ -599,10 +1122,10 @@ mod tests {
         let tile3 = Tile::from((3, 3));
 
         // Add sequence of actions: P0, P1, P0, P1, P0
-        history.add_action(Action::play(0, tile1, None));  // index 0
+        history.add_action(Action::play_unchecked(0, tile1, None));  // index 0
         history.add_action(Action::draw(1, tile2));   // index 1
         history.add_action(Action::pass(0));              // index 2
-        history.add_action(Action::play(1, tile3, None));  // index 3
+        history.add_action(Action::play_unchecked(1, tile3, None));  // index 3
         history.add_action(Action::pass(0));              // index 4
 
         // Get actions after player 0's last action (should be empty)
@@ -633,7 +1156,7 @@ mod tests {
         let tile2 = Tile::from((3, 4));
 
         // Sequence: P0, P1, P0, P1
-        history.add_action(Action::play(0, tile1, Some(1)));  // index 0
+        history.add_action(Action::play_unchecked(0, tile1, Some(1)));  // index 0
         history.add_action(Action::draw(1, tile2));   // index 1 - last P1 action
         history.add_action(Action::pass(0));              // index 2
         history.add_action(Action::pass(0));              // index 3
@@ -659,7 +1182,7 @@ mod tests {
     fn test_history_single_action() {
         let mut history = History::default();
         let tile = Tile::from((6, 6));
-        let action = Action::play(1, tile, Some(6));
+        let action = Action::play_unchecked(1, tile, Some(6));
 
         history.add_action(action);
 
@@ -672,9 +1195,9 @@ mod tests {
     #[test]
     fn test_action_equality() {
         let tile = Tile::from((2, 3));
-        let action1 = Action::play(0, tile, Some(2));
-        let action2 = Action::play(0, tile, Some(2));
-        let action3 = Action::play(1, tile, Some(2));
+        let action1 = Action::play_unchecked(0, tile, Some(2));
+        let action2 = Action::play_unchecked(0, tile, Some(2));
+        let action3 = Action::play_unchecked(1, tile, Some(2));
 
         assert_eq!(action1, action2);
         assert_ne!(action1, action3);
@@ -708,7 +1231,7 @@ mod tests {
 
             // Only play with None end if it's a double, otherwise use a valid end
             let end = if tile.is_double() { None } else { Some(tile.as_tuple().0) };
-            let play_action = Action::play(1, tile, end);
+            let play_action = Action::play_unchecked(1, tile, end);
             assert_eq!(play_action.tile_played, Some((tile, end)));
         }
     }
@@ -719,7 +1242,7 @@ mod tests {
         let tile1 = Tile::from((1, 1));
         let tile2 = Tile::from((2, 2));
 
-        let action1 = Action::play(0, tile1, None);
+        let action1 = Action::play_unchecked(0, tile1, None);
         let action2 = Action::draw(1, tile2);
         let action3 = Action::pass(0);
 
@@ -808,7 +1331,7 @@ mod tests {
 
         // Add different types of actions
         history.add_action(Action::draw(0, tile));
-        history.add_action(Action::play(1, tile, Some(4)));
+        history.add_action(Action::play_unchecked(1, tile, Some(4)));
         history.add_action(Action::pass(0));
         history.add_action(Action::new(1, Some(tile), Some((tile, Some(4)))));
 
@@ -829,7 +1352,7 @@ mod tests {
         let tile2 = Tile::from((2, 2));
 
         // Test that get_last_action always returns the most recent
-        history.add_action(Action::play(0, tile1, Some(1)));
+        history.add_action(Action::play_unchecked(0, tile1, Some(1)));
         assert_eq!(history.get_last_action().unwrap().tile_played, Some((tile1, Some(1))));
 
         history.add_action(Action::draw(1, tile2));
@@ -3,12 +3,15 @@
 //! This module defines the `DominoesState` struct which implements the `State` trait from the `hidden_game_player` crate.
 //! It encapsulates the current state of a dominoes game, including the layout, boneyard, player turns, and game status.
 
-use crate::{Action, ZHash};
+use crate::{Action, DominoesError, Hand, Muggins, Trains, ZHash, lowest_hand, scorer};
 use hidden_game_player::{PlayerId, State};
-use rules::{Boneyard, Configuration, Layout, Tile};
+use rand::Rng;
+use rules::metrics::{self, Counter};
+use rules::{Boneyard, Configuration, DrawRule, Layout, Opening, Tile, Variation};
 
 /// A concrete implementation of hidden_game_player::State for dominoes games
-#[derive(Debug, Clone)]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DominoesState {
     /// The layout
     pub layout: Layout,
@@ -16,7 +19,8 @@ pub struct DominoesState {
     pub boneyard: Boneyard,
     /// Whose turn is next (player ID)
     pub whose_turn: u8,
-    /// State fingerprint
+    /// Fingerprint of the layout. The fingerprint of the state (see `State::fingerprint`) also includes whose turn it is, the
+    /// consecutive passes, the number of tiles in the boneyard, and the scores.
     pub fingerprint: ZHash,
     /// Number of consecutive passes (typically if consecutive_passes == self.configuration.num_players, everyone has passed)
     pub consecutive_passes: u8,
@@ -24,13 +28,108 @@ pub struct DominoesState {
     pub game_is_over: bool,
     /// Player ID of the winner, or None if the game is still ongoing
     pub winner: Option<u8>,
+    /// The variation, which determines whether plays score (see `scorer`)
+    variation: Variation,
+    /// The rule for drawing from the boneyard, or `None` for the rule of the variation. It is not serialized if it is `None`, so
+    /// older states are unchanged.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    draw_rule: Option<DrawRule>,
+    /// Whether the player whose turn it is has drawn a tile since the last tile was played or the last pass
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    drawn: bool,
+    /// Whether a tile had been drawn before each tile played and each pass when a player may draw only one tile, so that `undo`
+    /// can restore it
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    drawn_before_actions: Vec<bool>,
+    /// The points scored by each player during play, by player ID
+    scores: Vec<u32>,
+    /// The number of consecutive passes before each tile played, so that `undo` can restore them
+    passes_before_plays: Vec<u8>,
+    /// The hands of the players, by player ID. They are empty until they are dealt (see `deal`).
+    hands: Vec<Hand>,
+    /// The number of tiles dealt to each player
+    starting_hand_size: usize,
+    /// Whether the hands have been dealt, so that `apply` and `undo` keep track of them
+    dealt: bool,
+    /// The trains in Mexican Train, or `None` in the other variations. It is not serialized if it is `None`, so older states are
+    /// unchanged.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    trains: Option<Trains>,
+    /// The trains before each tile played and each pass in Mexican Train, so that `undo` can restore them
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    trains_before_actions: Vec<Trains>,
+    /// The points to be claimed under the muggins rule, or `None` if the rule is not used. It is not serialized if it is `None`,
+    /// so older states are unchanged.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    muggins: Option<Muggins>,
+    /// Whether the players play as two teams of partners (see `Configuration::partners`). It is not serialized if it is `false`,
+    /// so older states are unchanged.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    partners: bool,
+}
+
+// Cloned states are counted, since the search clones a state for every node it expands
+impl Clone for DominoesState {
+    fn clone(&self) -> Self {
+        metrics::increment(Counter::StatesCloned);
+        Self {
+            layout: self.layout.clone(),
+            boneyard: self.boneyard.clone(),
+            whose_turn: self.whose_turn,
+            fingerprint: self.fingerprint,
+            consecutive_passes: self.consecutive_passes,
+            game_is_over: self.game_is_over,
+            winner: self.winner,
+            variation: self.variation,
+            draw_rule: self.draw_rule,
+            drawn: self.drawn,
+            drawn_before_actions: self.drawn_before_actions.clone(),
+            scores: self.scores.clone(),
+            passes_before_plays: self.passes_before_plays.clone(),
+            hands: self.hands.clone(),
+            starting_hand_size: self.starting_hand_size,
+            dealt: self.dealt,
+            trains: self.trains.clone(),
+            trains_before_actions: self.trains_before_actions.clone(),
+            muggins: self.muggins.clone(),
+            partners: self.partners,
+        }
+    }
 }
 
 impl State for DominoesState {
     type Action = Action;
 
     fn fingerprint(&self) -> u64 {
-        self.fingerprint.into()
+        // The turn, passes, and boneyard are public fields that may be changed directly, so they are hashed here rather than
+        // kept up to date in the fingerprint. So are the trains and their markers, which only Mexican Train has, and whether the
+        // player has drawn where a player may draw only one tile, since they decide which actions are legal. The scores and the
+        // points that can still be claimed are hashed here too, since they are changed by claims and by the game loop.
+        let mut fingerprint = self.fingerprint;
+        fingerprint
+            .change_turn(0, self.whose_turn)
+            .change_passes(0, self.consecutive_passes)
+            .change_boneyard_size(0, self.boneyard.count());
+        if let Some(trains) = &self.trains {
+            for player_id in (0..self.num_players()).filter(|&player_id| trains.is_marked(player_id)) {
+                fingerprint.toggle_marker(player_id);
+            }
+            for train in 0..=trains.mexican_train() {
+                if let Some(node) = trains.end(train) {
+                    fingerprint.toggle_train_end(train, self.layout.nodes[node].tile.ordinal);
+                }
+            }
+        }
+        if self.drawn && self.draw_rule() == DrawRule::DrawOne {
+            fingerprint.toggle_drawn();
+        }
+        for (player_id, &score) in self.scores.iter().enumerate() {
+            fingerprint.change_score(player_id as u8, 0, score);
+        }
+        if let Some((player_id, points)) = self.unclaimed_points() {
+            fingerprint.toggle_unclaimed(player_id, points);
+        }
+        fingerprint.into()
     }
 
     fn whose_turn(&self) -> u8 {
@@ -41,8 +140,19 @@ impl State for DominoesState {
         self.game_is_over
     }
 
+    /// Returns the state after an action
+    ///
+    /// A drawn tile is drawn from the boneyard, a played tile is played on the layout, and a pass is counted. If the hands have
+    /// been dealt (see `deal`), the drawn tile is added to the player's hand and the played tile is removed from it, and a player
+    /// who plays their last tile wins. The turn passes to the next player, except that a player who only draws a tile continues
+    /// until they play or pass, and in Mexican Train, a player who plays a double plays again. If the game is blocked, it is over
+    /// (see `check_blocked_game`).
+    ///
+    /// # Panics
+    /// Panics if the tile drawn is not the next tile in the boneyard, or if the tile played cannot be played. In debug builds,
+    /// also panics if it is not the player's turn. Use `DominoesState::try_apply` for actions that come from untrusted input.
     fn apply(&self, action: &Self::Action) -> Self {
-        assert!(false, "apply() is not yet implemented for DominoesState");
+        debug_assert_eq!(action.player_id, self.whose_turn, "It is not player {}'s turn", action.player_id);
         let mut new_state = self.clone();
         if action.tile_drawn.is_some() {
             let drawn_tile = new_state.draw_tile();
@@ -52,7 +162,28 @@ impl State for DominoesState {
             );
         }
         if let Some((tile, end)) = action.tile_played {
-            new_state.play_tile(tile, end);
+            new_state.play_tile_unchecked(tile, end);
+        }
+        if action.is_pass() {
+            new_state.pass();
+        }
+
+        if new_state.dealt {
+            let hand = &mut new_state.hands[action.player_id as usize];
+            if let Some(tile) = action.tile_drawn {
+                hand.add_tile(tile);
+            }
+            if let Some((tile, _)) = action.tile_played {
+                hand.remove_tile(&tile);
+                if hand.is_empty() {
+                    new_state.mark_game_over(Some(action.player_id));
+                    return new_state;
+                }
+            }
+        }
+
+        if !new_state.check_blocked_game() && action.ends_turn(new_state.variation) {
+            new_state.advance_turn();
         }
         new_state
     }
@@ -74,14 +205,96 @@ impl DominoesState {
     /// assert!(!state.game_is_over);
     /// ```
     pub fn new(configuration: &Configuration) -> Self {
+        Self::with_boneyard(configuration, Boneyard::new(configuration))
+    }
+
+    /// Creates a new dominoes game state whose boneyard is shuffled with the given random number generator
+    ///
+    /// # Arguments
+    /// * `configuration` - Game configuration containing players, variation, and domino set
+    /// * `rng` - The random number generator used to shuffle the boneyard
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    /// use rand::SeedableRng;
+    /// use rand_chacha::ChaCha8Rng;
+    ///
+    /// let config = Configuration::default();
+    /// let a = DominoesState::with_rng(&config, &mut ChaCha8Rng::seed_from_u64(7));
+    /// let b = DominoesState::with_rng(&config, &mut ChaCha8Rng::seed_from_u64(7));
+    /// assert_eq!(a.boneyard.peek(), b.boneyard.peek());
+    /// ```
+    pub fn with_rng<R: Rng + ?Sized>(configuration: &Configuration, rng: &mut R) -> Self {
+        Self::with_boneyard(configuration, Boneyard::with_rng(configuration, rng))
+    }
+
+    /// Creates a dominoes game state for a position that is already in progress
+    ///
+    /// This is used to set up positions for tests, puzzles, and analysis. The fingerprint is computed to be the same as if the
+    /// tiles in the layout had been played one at a time. In Mexican Train, the lines attached to the engine are assigned to the
+    /// trains in order (see `Trains::with_layout`).
+    ///
+    /// # Arguments
+    /// * `configuration` - Game configuration containing players, variation, and domino set
+    /// * `layout` - The tiles that have been played
+    /// * `boneyard` - The tiles that have not been drawn
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Boneyard, Configuration, Layout, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// let state = DominoesState::with_position(&config, layout, Boneyard::with(vec![Tile::from((0, 1))]));
+    /// assert_eq!(state.layout.open_count(6), 2);
+    /// assert_eq!(state.boneyard.count(), 1);
+    /// ```
+    pub fn with_position(configuration: &Configuration, layout: Layout, boneyard: Boneyard) -> Self {
+        let mut state = Self::with_boneyard(configuration, boneyard);
+        for node in &layout.nodes {
+            state.fingerprint.add_tile(node.tile.into());
+        }
+        for (end, &count) in layout.end_counts.iter().enumerate() {
+            if count > 0 {
+                state.fingerprint.change_end_count(end as u8, 0, count);
+            }
+        }
+        if state.trains.is_some() {
+            state.trains = Some(Trains::with_layout(configuration.num_players(), &layout));
+        }
+        state.layout = layout;
+        state.check_fingerprint();
+        state
+    }
+
+    // Creates a new state with the given boneyard
+    fn with_boneyard(configuration: &Configuration, boneyard: Boneyard) -> Self {
+        metrics::increment(Counter::StatesCreated);
         Self {
             layout: Layout::new(configuration),
-            boneyard: Boneyard::new(configuration),
+            boneyard,
             whose_turn: PlayerId::ALICE as u8,
             fingerprint: ZHash::default(),
             consecutive_passes: 0,
             game_is_over: false,
             winner: None,
+            variation: configuration.variation(),
+            draw_rule: Some(configuration.draw_rule()).filter(|&rule| rule != configuration.variation().draw_rule()),
+            drawn: false,
+            drawn_before_actions: Vec::new(),
+            scores: vec![0; configuration.num_players()],
+            passes_before_plays: Vec::new(),
+            hands: vec![Hand::new(); configuration.num_players()],
+            starting_hand_size: configuration.starting_hand_size(),
+            dealt: false,
+            trains: (configuration.variation() == Variation::MexicanTrain).then(|| Trains::new(configuration.num_players())),
+            trains_before_actions: Vec::new(),
+            muggins: configuration.muggins().then(Muggins::default),
+            partners: configuration.partners(),
         }
     }
 
@@ -94,8 +307,11 @@ impl DominoesState {
 
     /// Checks if a tile can be played on the current layout
     ///
-    /// Validates whether the specified tile can be legally placed on the layout. For empty layouts, only doubles can be played.
-    /// For non-empty layouts, the tile must match at least one open end.
+    /// Validates whether the specified tile can be legally placed on the layout. For empty layouts, only doubles can be played if
+    /// the opening rule of the variation requires one (see `opening`). The rule may also require a specific double, which depends
+    /// on the player's hand, so it is checked by `Action::validate`. For non-empty layouts, the tile must match at least one open
+    /// end. In Mexican Train, the end must be on a train that the player whose turn it is may play on (see `Trains`). In Chicken
+    /// Foot, a double must receive all of its tiles, e.g. three for a double played on a line, before any other end may be used.
     ///
     /// # Arguments
     /// * `tile` - The tile to check for playability
@@ -120,7 +336,21 @@ impl DominoesState {
     /// }
     /// ```
     pub fn can_play_tile(&self, tile: &Tile, end: Option<u8>) -> bool {
-        if !self.layout.is_empty() {
+        if let Some(trains) = &self.trains
+            && !self.layout.is_empty()
+        {
+            let (a, b) = tile.as_tuple();
+            let playable = |end| trains.train_for(&self.layout, self.whose_turn, end).is_some();
+            match end {
+                Some(end) => (a == end || b == end) && playable(end),
+                None => playable(a) || playable(b),
+            }
+        } else if let Some(double) = self.unfilled_double() {
+            // Only the open ends of the double may be used
+            let (a, b) = tile.as_tuple();
+            let value = self.layout.nodes[double].tile.as_tuple().0;
+            (a == value || b == value) && end.is_none_or(|end| end == value)
+        } else if !self.layout.is_empty() {
             let (a, b) = tile.as_tuple();
             if let Some(end) = end {
                 // If an end is specified, it must match that end and the end must be open somewhere
@@ -130,40 +360,123 @@ impl DominoesState {
                 self.layout.end_counts[a as usize] > 0 || self.layout.end_counts[b as usize] > 0
             }
         } else {
-            // If the layout is empty, a double can be played
-            // TODO: Handle different variations of the game here
+            // If the layout is empty, the tile must be allowed by the opening rule
             assert!(
                 end.is_none(),
                 "An end was specified for an empty layout. Something is wrong."
             );
-            tile.is_double()
+            tile.is_double() || !self.opening().requires_double()
         }
     }
 
     /// Draws and returns a tile from the boneyard
     ///
-    /// Removes and returns a random tile from the boneyard. Returns `None` if the boneyard is empty.
+    /// Removes and returns a random tile from the boneyard. Returns `None` if the boneyard is empty. Once the first tile has been
+    /// played, a player who may draw only one tile may not draw again until they play or pass (see `can_draw`).
     ///
     /// # Returns
     /// `Some(Tile)` if a tile was successfully drawn, `None` if boneyard is empty
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(player_id = self.whose_turn)))]
     pub fn draw_tile(&mut self) -> Option<Tile> {
         // Note: Unlike playing a tile, drawing does not reset the consecutive passes counter because a pass could still occur
         // afterward if the boneyard is empty.
+        let tile = self.boneyard.draw();
+        if tile.is_some()
+            && let Some(muggins) = &mut self.muggins
+        {
+            muggins.expire();
+        }
+        // The tiles dealt before the first tile is played are not drawn by a player
+        if tile.is_some() && !self.layout.is_empty() {
+            self.drawn = true;
+        }
+        self.check_fingerprint();
+        tile
+    }
+
+    /// Returns the rule for drawing from the boneyard (see `Configuration::draw_rule`)
+    pub fn draw_rule(&self) -> DrawRule {
+        self.draw_rule.unwrap_or(self.variation.draw_rule())
+    }
 
-        // FIXME: This currently does not change the fingerprint, but it probably should
-        self.boneyard.draw()
+    /// Returns `true` if the player whose turn it is has drawn a tile and has not played or passed since
+    pub fn has_drawn(&self) -> bool {
+        self.drawn
     }
 
-    /// Plays a tile on the layout
+    /// Returns `true` if the player whose turn it is may draw a tile when they cannot play
+    ///
+    /// A player may draw while the boneyard has tiles, as allowed by the rule for drawing (see `Configuration::draw_rule`). In a
+    /// Block game, a player never draws, and where a player draws only one tile, a player who has drawn and still cannot play
+    /// must pass.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Configuration, DrawRule, Tile, Variation};
+    ///
+    /// let mut state = DominoesState::new(&Configuration::new(2, Variation::MexicanTrain, 12, 15));
+    /// state.play_tile_unchecked(Tile::from((12, 12)), None);
+    /// assert!(state.can_draw());
+    /// state.draw_tile();
+    /// assert!(!state.can_draw());
+    ///
+    /// let block = DominoesState::new(&Configuration::default().with_draw_rule(DrawRule::NoDraw));
+    /// assert!(!block.can_draw());
+    /// ```
+    pub fn can_draw(&self) -> bool {
+        match self.draw_rule() {
+            DrawRule::NoDraw => false,
+            DrawRule::DrawUntilPlayable => !self.boneyard.is_empty(),
+            DrawRule::DrawOne => !self.boneyard.is_empty() && !self.drawn,
+        }
+    }
+
+    /// Plays a tile on the layout, or returns an error if it cannot be played
     ///
     /// Places the specified tile on the layout at the given open end. Updates the layout, fingerprint, and open ends accordingly.
+    /// In Mexican Train, the tile is played on the train chosen by `Trains::train_for`, and in Chicken Foot, on the double that
+    /// has not received all of its tiles, if any. The state is unchanged if the tile cannot be played.
+    ///
+    /// # Arguments
+    /// * `tile` - The tile to place on the layout
+    /// * `end` - The specific end to attach to. Must be `None` for empty layouts
+    ///
+    /// # Errors
+    /// * `DominoesError::NotCanonical` if the tile's ordinal does not stand for a tile
+    /// * `DominoesError::EndOfFirstTile` if an end is given for the first tile
+    /// * `DominoesError::NoEnd` if no end is given for any other tile
+    /// * `DominoesError::InvalidMove` if the tile cannot be played on the end
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesError, DominoesState};
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let mut state = DominoesState::new(&config);
+    /// assert_eq!(state.play_tile(Tile::from((6, 6)), None), Ok(()));
+    /// let tile = Tile::from((1, 2));
+    /// assert_eq!(state.play_tile(tile, Some(6)), Err(DominoesError::InvalidMove { tile, end: Some(6) }));
+    /// assert_eq!(state.layout.nodes.len(), 1);
+    /// ```
+    pub fn play_tile(&mut self, tile: Tile, end: Option<u8>) -> Result<(), DominoesError> {
+        self.check_play(tile, end)?;
+        self.play_tile_unchecked(tile, end);
+        Ok(())
+    }
+
+    /// Plays a tile on the layout, without checking it
+    ///
+    /// This is the panicking version of `play_tile`, for tiles and ends that are known to be legal, such as those of the legal
+    /// actions generated by the search.
     ///
     /// # Arguments
     /// * `tile` - The tile to place on the layout
     /// * `end` - The specific end to attach to. Must be `None` for empty layouts
     ///
     /// # Panics
-    /// Panics if the tile cannot be legally played.
+    /// Panics if the tile cannot be legally played
     ///
     /// # Examples
     /// ```rust
@@ -174,26 +487,45 @@ impl DominoesState {
     /// let mut state = DominoesState::new(&config);
     /// let tile = Tile::from((1, 2));
     /// if state.can_play_tile(&tile, None) {
-    ///     state.play_tile(tile, None);
+    ///     state.play_tile_unchecked(tile, None);
     /// }
     /// ```
-    pub fn play_tile(&mut self, tile: Tile, end: Option<u8>) {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, fields(player_id = self.whose_turn, %tile, ?end))
+    )]
+    pub fn play_tile_unchecked(&mut self, tile: Tile, end: Option<u8>) {
         assert!(
             self.can_play_tile(&tile, end),
             "Tile {tile} cannot be played on the layout"
         );
 
+        if let Some(trains) = &self.trains {
+            self.trains_before_actions.push(trains.clone());
+        }
+        self.end_drawing();
+
         if let Some(matched_end) = end {
-            // Find the index of a matching open end
-            let (parent_index, _) = self
-                .layout
-                .open
-                .iter_all()
-                .find(|(_, values)| values.contains(&matched_end))
-                .expect("No matching open end found");
+            // Find the index of a matching open end, which is the end of a train in Mexican Train
+            let train = self.trains.as_ref().map(|trains| {
+                let train = trains.train_for(&self.layout, self.whose_turn, matched_end).expect("No matching train found");
+                (train, trains.end(train).unwrap_or(0))
+            });
+            let parent_index = match train {
+                Some((_, end)) => end,
+                None => match self.unfilled_double() {
+                    Some(double) => double,
+                    // The open ends are not kept in order, so the oldest matching end is taken for the play to be the same
+                    // every time, since the end that a tile is played on can change the points that it scores
+                    None => self.layout.nodes_with_open_end(matched_end).min().expect("No matching open end found"),
+                },
+            };
 
             // Place the tile in the layout
-            let (new_end, new_end_change) = self.layout.attach(tile, Some(*parent_index));
+            let (new_end, new_end_change) = self.layout.attach_unchecked(tile, Some(parent_index));
+            if let (Some(trains), Some((train, _))) = (&mut self.trains, train) {
+                trains.extend(train, self.layout.nodes.len() - 1, self.whose_turn);
+            }
 
             // Update the fingerprint for the new tile
             self.fingerprint.add_tile(tile.into());
@@ -212,24 +544,406 @@ impl DominoesState {
                 new_end_count,
             );
         } else {
-            // If no end specified, the tile must be a double and the layout must be empty
+            // If no end specified, the layout must be empty
             assert!(
                 self.layout.is_empty(),
                 "Layout is not empty; must specify an end to play on"
             );
 
             // Place the tile in the layout
-            let (new_end, new_end_change) = self.layout.attach(tile, None);
+            self.layout.attach_unchecked(tile, None);
 
             // Update the fingerprint for the new tile
             self.fingerprint.add_tile(tile.into());
 
-            // Update the fingerprint for the new end count
-            assert!(self.layout.open_count(new_end) == new_end_change);
-            self.fingerprint
-                .change_end_count(new_end, 0, new_end_change);
+            // Update the fingerprint for the new end counts. Both ends of the first tile are open.
+            self.change_first_tile_end_counts(tile);
         }
+        self.passes_before_plays.push(self.consecutive_passes);
         self.update_consecutive_passes(false); // Reset consecutive passes because a tile was played
+
+        let points = self.points_scored();
+        let scored = match &mut self.muggins {
+            Some(muggins) => muggins.play(self.whose_turn, points.unwrap_or(0)),
+            None => points,
+        };
+        if let Some(points) = scored {
+            self.add_score(self.whose_turn, points);
+        }
+        self.check_fingerprint();
+    }
+
+    // Returns the reason that a tile cannot be played on an end, if any (see `play_tile`)
+    fn check_play(&self, tile: Tile, end: Option<u8>) -> Result<(), DominoesError> {
+        if Tile::checked_new(tile.ordinal).is_none() {
+            return Err(DominoesError::NotCanonical(tile.ordinal));
+        }
+        match end {
+            Some(_) if self.layout.is_empty() => Err(DominoesError::EndOfFirstTile),
+            None if !self.layout.is_empty() => Err(DominoesError::NoEnd(tile)),
+            _ if !self.is_end_in_set(end) || !self.can_play_tile(&tile, end) => Err(DominoesError::InvalidMove { tile, end }),
+            _ => Ok(()),
+        }
+    }
+
+    // Returns true if the value of an end, if any, is in the set. `can_play_tile` assumes that it is.
+    pub(crate) fn is_end_in_set(&self, end: Option<u8>) -> bool {
+        end.is_none_or(|end| (end as usize) < self.layout.end_counts.len())
+    }
+
+    /// Returns the state after an action, or an error if the action cannot be applied
+    ///
+    /// This is the checked version of `apply`, for actions that come from untrusted input. It checks that the action is taken by
+    /// the player whose turn it is, that a drawn tile is the next tile in the boneyard, that a played tile can be played, and, if
+    /// the hands have been dealt, that the player holds it. Unlike `Action::validate`, it does not check whether the player should
+    /// have drawn or passed instead. The search uses `apply`, which does not check anything.
+    ///
+    /// # Arguments
+    /// * `action` - The action to apply
+    ///
+    /// # Errors
+    /// * `DominoesError::GameOver` if the game is over
+    /// * `DominoesError::WrongTurn` if it is not the player's turn
+    /// * `DominoesError::EmptyBoneyard` or `DominoesError::NotNextTile` if the tile cannot be drawn
+    /// * `DominoesError::NotInHand` if the hands have been dealt and the player does not hold the tile played
+    /// * Any of the errors returned by `play_tile` if the tile cannot be played
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesError, DominoesState};
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let state = DominoesState::new(&config);
+    /// let action = Action::play_unchecked(1, Tile::from((6, 6)), None);
+    /// assert_eq!(state.try_apply(&action).unwrap_err(), DominoesError::WrongTurn(1));
+    ///
+    /// let state = state.try_apply(&Action::play_unchecked(0, Tile::from((6, 6)), None)).unwrap();
+    /// assert_eq!(state.whose_turn, 1);
+    /// ```
+    pub fn try_apply(&self, action: &Action) -> Result<Self, DominoesError> {
+        if self.game_is_over {
+            return Err(DominoesError::GameOver);
+        }
+        if action.player_id != self.whose_turn {
+            return Err(DominoesError::WrongTurn(action.player_id));
+        }
+        if let Some(tile) = action.tile_drawn {
+            match self.boneyard.peek() {
+                Some(next) if *next == tile => {}
+                Some(_) => return Err(DominoesError::NotNextTile(tile)),
+                None => return Err(DominoesError::EmptyBoneyard),
+            }
+        }
+        if let Some((tile, end)) = action.tile_played {
+            if self.dealt && !self.hands[action.player_id as usize].contains(&tile) && action.tile_drawn != Some(tile) {
+                return Err(DominoesError::NotInHand(tile));
+            }
+            self.check_play(tile, end)?;
+        }
+        Ok(self.apply(action))
+    }
+
+    /// Deals the starting hands from the boneyard
+    ///
+    /// Each player in turn draws the number of tiles given by the configuration. From then on, `apply` and `undo` keep track of
+    /// the hands, so a simulation does not need to. The engine does not deal the hands into the state that it shows the players,
+    /// because a player must not see the other hands.
+    ///
+    /// # Panics
+    /// Panics if the hands have already been dealt, or if the boneyard does not have enough tiles
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let config = Configuration::default();
+    /// let mut state = DominoesState::new(&config);
+    /// state.deal();
+    /// assert_eq!(state.tiles_remaining(0), config.starting_hand_size());
+    /// assert_eq!(state.boneyard.count(), config.set_size() - 2 * config.starting_hand_size());
+    /// ```
+    pub fn deal(&mut self) {
+        assert!(!self.dealt, "The hands have already been dealt");
+        let hands = self.boneyard.deal(self.hands.len(), self.starting_hand_size);
+        self.hands = hands.into_iter().map(Hand::from).collect();
+        self.dealt = true;
+    }
+
+    /// Sets the hands of the players, e.g. hands sampled for a simulation
+    ///
+    /// From then on, `apply` and `undo` keep track of the hands as if they had been dealt (see `deal`). The boneyard is not
+    /// changed.
+    ///
+    /// # Arguments
+    /// * `hands` - The hands, by player ID
+    ///
+    /// # Panics
+    /// Panics if there is not a hand for every player
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesState, Hand};
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((2, 3)));
+    /// state.set_hands(vec![hand, Hand::new()]);
+    /// assert!(state.is_dealt());
+    /// assert_eq!(state.tiles_remaining(0), 1);
+    /// ```
+    pub fn set_hands(&mut self, hands: Vec<Hand>) {
+        assert_eq!(hands.len(), self.hands.len(), "There must be a hand for every player");
+        self.hands = hands;
+        self.dealt = true;
+    }
+
+    /// Returns true if the state keeps track of the hands (see `deal` and `set_hands`)
+    pub fn is_dealt(&self) -> bool {
+        self.dealt
+    }
+
+    /// Returns the number of tiles dealt to each player at the start of a round
+    pub fn starting_hand_size(&self) -> usize {
+        self.starting_hand_size
+    }
+
+    /// Returns a copy of the state without the information that is hidden from the players
+    ///
+    /// The state is serialized with the order of the boneyard and the hands, so that a game can be restored exactly. A game that
+    /// is saved for someone who is playing it should not reveal them, so the copy keeps only the tiles remaining in the boneyard,
+    /// in sorted order, and no hands. Tiles that were drawn earlier can no longer be taken back, and the boneyard should be
+    /// shuffled before drawing from it again.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.deal();
+    /// let public = state.without_hidden_info();
+    /// assert!(!public.is_dealt());
+    /// assert_eq!(public.boneyard.count(), state.boneyard.count());
+    /// assert!(public.boneyard.remaining().is_sorted());
+    /// ```
+    pub fn without_hidden_info(&self) -> Self {
+        let mut tiles = self.boneyard.remaining().to_vec();
+        tiles.sort();
+        Self {
+            boneyard: Boneyard::with(tiles).with_reserve(self.boneyard.reserve()),
+            hands: vec![Hand::new(); self.hands.len()],
+            dealt: false,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the hand of a player, which is empty if the hands have not been dealt (see `deal`)
+    ///
+    /// # Panics
+    /// Panics if there is no such player
+    pub fn hand_of(&self, player_id: u8) -> &Hand {
+        &self.hands[player_id as usize]
+    }
+
+    /// Returns the number of tiles in the hand of a player
+    ///
+    /// # Panics
+    /// Panics if there is no such player
+    pub fn tiles_remaining(&self, player_id: u8) -> usize {
+        self.hand_of(player_id).len()
+    }
+
+    /// Returns the trains in Mexican Train, or `None` in the other variations
+    pub fn trains(&self) -> Option<&Trains> {
+        self.trains.as_ref()
+    }
+
+    /// Returns the rule for the first tile of a round, which depends on the variation
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Configuration, Opening, Tile, Variation};
+    ///
+    /// let state = DominoesState::new(&Configuration::new(2, Variation::AllFives, 6, 7));
+    /// assert_eq!(state.opening(), Opening::AnyTile);
+    /// assert!(state.can_play_tile(&Tile::from((2, 5)), None));
+    /// ```
+    pub fn opening(&self) -> Opening {
+        self.variation.opening()
+    }
+
+    /// Returns the points scored by each player during play, by player ID
+    ///
+    /// Plays score only in some variations (see `scorer`). In the others, the scores are always 0. Under the muggins rule, the
+    /// points of a play are not added until they are claimed (see `claim_points`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Configuration, Tile, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::AllFives, 6, 7);
+    /// let mut state = DominoesState::new(&config);
+    /// state.play_tile_unchecked(Tile::from((5, 5)), None);
+    /// assert_eq!(state.scores(), &[10, 0]);
+    /// ```
+    pub fn scores(&self) -> &[u32] {
+        &self.scores
+    }
+
+    /// Returns the player who takes the turn after a player
+    ///
+    /// The turn passes to the next player in the order of their IDs, wrapping around to player 0, in every variation. A player
+    /// is never skipped: one who cannot play draws or passes on their turn. Whether an action ends the turn at all depends on the
+    /// variation (see `apply`).
+    ///
+    /// # Arguments
+    /// * `player_id` - The player whose turn ends
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Configuration, Variation};
+    ///
+    /// let state = DominoesState::new(&Configuration::new(3, Variation::Traditional, 6, 5));
+    /// assert_eq!(state.next_player(1), 2);
+    /// assert_eq!(state.next_player(2), 0);
+    /// ```
+    pub fn next_player(&self, player_id: u8) -> u8 {
+        (player_id + 1) % self.num_players()
+    }
+
+    /// Passes the turn to the next player (see `next_player`)
+    ///
+    /// `apply` passes the turn itself. This is for code that updates the state directly with `play_tile`, `draw_tile`, and `pass`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.advance_turn();
+    /// assert_eq!(state.whose_turn, 1);
+    /// state.advance_turn();
+    /// assert_eq!(state.whose_turn, 0);
+    /// ```
+    pub fn advance_turn(&mut self) {
+        self.whose_turn = self.next_player(self.whose_turn);
+    }
+
+    /// Returns the team of a player (see `Configuration::team`)
+    pub fn team(&self, player_id: u8) -> u8 {
+        Configuration::team_of(self.partners, player_id)
+    }
+
+    /// Returns the points scored by each team during play, by team
+    ///
+    /// The points of partners are added together. Without partners, each player is a team of their own, so these are the same as
+    /// `scores`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Configuration, Tile, Variation};
+    ///
+    /// let config = Configuration::new(4, Variation::AllFives, 6, 5).with_partners().unwrap();
+    /// let mut state = DominoesState::new(&config);
+    /// state.add_score(0, 10);
+    /// state.add_score(2, 5);
+    /// state.add_score(3, 15);
+    /// assert_eq!(state.team_scores(), vec![15, 15]);
+    /// ```
+    pub fn team_scores(&self) -> Vec<u32> {
+        let num_teams = if self.partners { 2 } else { self.scores.len() };
+        let mut team_scores = vec![0; num_teams];
+        for (player_id, &score) in self.scores.iter().enumerate() {
+            team_scores[self.team(player_id as u8) as usize] += score;
+        }
+        team_scores
+    }
+
+    /// Adds points to a player's score
+    ///
+    /// Points scored by playing a tile are added by `play_tile`. This is for points scored in other ways, such as by the game
+    /// loop at the end of a round.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who scored
+    /// * `points` - The points scored
+    ///
+    /// # Panics
+    /// Panics if `player_id` is not a player in the game
+    pub fn add_score(&mut self, player_id: u8, points: u32) {
+        self.scores[player_id as usize] += points;
+    }
+
+    /// Returns the player who made the last play and the points that it scored, if they can still be claimed under the muggins
+    /// rule (see `Muggins`)
+    pub fn unclaimed_points(&self) -> Option<(u8, u32)> {
+        self.muggins.as_ref().and_then(Muggins::unclaimed)
+    }
+
+    /// Claims the points scored by the last play under the muggins rule
+    ///
+    /// The player who made the play claims their own points. Any other player is calling "muggins" because the player missed
+    /// them, and takes the points instead. The points must be claimed before the next action (see `Muggins`).
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who scores the points
+    ///
+    /// # Returns
+    /// The points scored, or an error if there are no points to claim
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesError, DominoesState};
+    /// # use rules::{Configuration, Tile, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::AllFives, 6, 7).with_muggins(true);
+    /// let mut state = DominoesState::new(&config);
+    /// state.play_tile_unchecked(Tile::from((5, 5)), None);
+    /// assert_eq!(state.claim_points(0), Ok(10));
+    /// assert_eq!(state.claim_points(1), Err(DominoesError::NoPointsToClaim));
+    /// assert_eq!(state.scores(), &[10, 0]);
+    /// ```
+    pub fn claim_points(&mut self, player_id: u8) -> Result<u32, DominoesError> {
+        let points = self
+            .muggins
+            .as_mut()
+            .and_then(|muggins| muggins.award(player_id))
+            .ok_or(DominoesError::NoPointsToClaim)?;
+        self.add_score(player_id, points);
+        Ok(points)
+    }
+
+    /// Returns the state with the points of every later play claimed at once by the player who made it
+    ///
+    /// Under the muggins rule, the points of a play are only scored once they are claimed (see `Muggins`). A search plays out the
+    /// game for every player as a computer player that never misses its points, so its states score each play as it is made.
+    /// Without the muggins rule, the state is unchanged. The points of the last play that have not been claimed yet are not
+    /// awarded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::{Configuration, Tile, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::AllFives, 6, 7).with_muggins(true);
+    /// let mut state = DominoesState::new(&config).with_points_claimed();
+    /// state.play_tile_unchecked(Tile::from((5, 5)), None);
+    /// assert_eq!(state.scores(), &[10, 0]);
+    /// assert_eq!(state.unclaimed_points(), None);
+    /// ```
+    pub fn with_points_claimed(mut self) -> Self {
+        if let Some(muggins) = &mut self.muggins {
+            muggins.claim_automatically();
+        }
+        self
     }
 
     /// Marks the game as over and optionally declares a winner (or a draw)
@@ -237,80 +951,341 @@ impl DominoesState {
     /// This method sets the internal `done` flag to true and records the winner (if any). Once called, `game_is_over` will
     /// be true and `winner` will contain the ID of the winning player (or `None` if it is a draw).
     ///
-    /// # Arguments
-    /// * `winner` - Player ID of the winner, `None` if it is a draw.
+    /// # Arguments
+    /// * `winner` - Player ID of the winner, `None` if it is a draw.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let config = Configuration::default();
+    /// let mut state = DominoesState::new(&config);
+    ///
+    /// // Initially game is not over
+    /// assert!(!state.game_is_over);
+    /// assert_eq!(state.winner, None);
+    ///
+    /// // End game with a winner (player ID 0)
+    /// state.mark_game_over(Some(0));
+    /// assert!(state.game_is_over);
+    /// assert_eq!(state.winner, Some(0));
+    ///
+    /// // Can also end in a draw
+    /// let mut state2 = DominoesState::new(&config);
+    /// state2.mark_game_over(None);
+    /// assert!(state2.game_is_over);
+    /// assert_eq!(state2.winner, None);
+    /// ```
+    /// # Important Note
+    /// This method does not automatically end the game. It only updates the game state. Game state update logic should call
+    /// `mark_game_over()` when appropriate, and game control logic should check `game_is_over` and `winner` to determine if the game is
+    /// over and who the winner is.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    pub fn mark_game_over(&mut self, winner: Option<u8>) {
+        self.game_is_over = true;
+        self.winner = winner;
+    }
+
+    /// Records a pass
+    ///
+    /// Increments the consecutive passes counter, which is used to track how players have passed in succession. When
+    /// `consecutive_passes` equals the number of players in the game, it typically indicates that the game should end due to all
+    /// players being unable to play (see `is_blocked`). In Mexican Train, the player whose turn it is puts a marker on their
+    /// train.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let config = Configuration::default();
+    /// let mut state = DominoesState::new(&config);
+    ///
+    /// // Initially no passes
+    /// assert_eq!(state.consecutive_passes, 0);
+    ///
+    /// // Record a pass
+    /// state.pass();
+    /// assert_eq!(state.consecutive_passes, 1);
+    ///
+    /// // Record another pass
+    /// state.pass();
+    /// assert_eq!(state.consecutive_passes, 2);
+    ///
+    /// // Playing a tile resets the counter
+    /// let tile = rules::Tile::from((6, 6));
+    /// if state.can_play_tile(&tile, None) {
+    ///     state.play_tile_unchecked(tile, None);
+    ///     assert_eq!(state.consecutive_passes, 0);
+    /// }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(player_id = self.whose_turn)))]
+    pub fn pass(&mut self) {
+        if let Some(muggins) = &mut self.muggins {
+            muggins.expire();
+        }
+        if let Some(trains) = &mut self.trains {
+            self.trains_before_actions.push(trains.clone());
+            trains.pass(self.whose_turn);
+        }
+        self.end_drawing();
+        self.update_consecutive_passes(true);
+        self.check_fingerprint();
+    }
+
+    /// Returns `true` if no player can play, so the game is blocked
+    ///
+    /// The game is blocked when every player has passed in succession. Where a player draws only one tile, a player may pass while
+    /// the boneyard has tiles and draw again on their next turn, so the boneyard must also be empty. In Mexican Train, a player
+    /// who passes puts a marker on their train, which may let the players who passed before them play, so every player must have
+    /// passed twice in succession.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.pass();
+    /// assert!(!state.is_blocked());
+    /// state.pass();
+    /// assert!(state.is_blocked());
+    /// ```
+    pub fn is_blocked(&self) -> bool {
+        if self.draw_rule() == DrawRule::DrawOne && !self.boneyard.is_empty() {
+            return false;
+        }
+        match self.trains {
+            Some(_) => self.consecutive_passes >= 2 * self.num_players(),
+            None => self.consecutive_passes >= self.num_players(),
+        }
+    }
+
+    /// Ends the game if it is blocked
+    ///
+    /// The game is blocked when no player can play (see `is_blocked`). In the Traditional, Mexican Train, and Chicken Foot
+    /// variations, the winner of a blocked game is the player with the fewest pips in their hand, and it is a draw if the fewest
+    /// pips are shared. With partners, the team with the fewest pips wins, and the winner is the partner with fewer pips. As for
+    /// the referee, a blocked game is a draw in the other variations. It is also a draw if the hands have not been dealt (see
+    /// `deal`), since the pips are not known.
+    ///
+    /// # Returns
+    /// `true` if the game is blocked, in which case it has been marked as over
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.pass();
+    /// assert!(!state.check_blocked_game());
+    /// state.pass();
+    /// assert!(state.check_blocked_game());
+    /// assert!(state.game_is_over);
+    /// assert_eq!(state.winner, None);
+    /// ```
+    pub fn check_blocked_game(&mut self) -> bool {
+        if !self.is_blocked() {
+            return false;
+        }
+        let winner = match self.variation {
+            Variation::Traditional | Variation::MexicanTrain | Variation::ChickenFoot if self.dealt => {
+                let hands = self.hands.iter().enumerate().map(|(player_id, hand)| (player_id as u8, hand));
+                lowest_hand(hands, |player_id| self.team(player_id))
+            }
+            _ => None,
+        };
+        self.mark_game_over(winner);
+        true
+    }
+
+    /// Checks that the fingerprint kept up to date as the state changes is the one computed from scratch
+    ///
+    /// Only the layout's part of the fingerprint is kept up to date (see the `fingerprint` field), so only that part is checked.
+    /// The turn, the passes, the boneyard, the trains and their markers, and whether the player has drawn are hashed from their
+    /// fields whenever the fingerprint of the state is needed (see `State::fingerprint`). With the `verify-fingerprint` feature,
+    /// this is checked after every change to the state.
+    ///
+    /// # Panics
+    /// Panics if the fingerprints differ
     ///
     /// # Examples
     /// ```rust
-    /// # use dominoes_state::DominoesState;
-    /// # use rules::Configuration;
-    ///
-    /// let config = Configuration::default();
-    /// let mut state = DominoesState::new(&config);
-    ///
-    /// // Initially game is not over
-    /// assert!(!state.game_is_over);
-    /// assert_eq!(state.winner, None);
-    ///
-    /// // End game with a winner (player ID 0)
-    /// state.mark_game_over(Some(0));
-    /// assert!(state.game_is_over);
-    /// assert_eq!(state.winner, Some(0));
+    /// # use dominoes_state::{DominoesState, ZHash};
+    /// # use rules::{Configuration, Tile};
     ///
-    /// // Can also end in a draw
-    /// let mut state2 = DominoesState::new(&config);
-    /// state2.mark_game_over(None);
-    /// assert!(state2.game_is_over);
-    /// assert_eq!(state2.winner, None);
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    /// state.play_tile_unchecked(Tile::from((5, 6)), Some(6));
+    /// state.verify_fingerprint();
     /// ```
-    /// # Important Note
-    /// This method does not automatically end the game. It only updates the game state. Game state update logic should call
-    /// `mark_game_over()` when appropriate, and game control logic should check `game_is_over` and `winner` to determine if the game is
-    /// over and who the winner is.
-    pub fn mark_game_over(&mut self, winner: Option<u8>) {
-        self.game_is_over = true;
-        self.winner = winner;
+    pub fn verify_fingerprint(&self) {
+        let expected = ZHash::from_layout(&self.layout);
+        assert_eq!(self.fingerprint, expected, "The fingerprint does not match the layout {:?}", self.layout.nodes);
     }
 
-    /// Records a pass
+    // Checks the fingerprint after a change, with the `verify-fingerprint` feature
+    fn check_fingerprint(&self) {
+        #[cfg(feature = "verify-fingerprint")]
+        self.verify_fingerprint();
+    }
+
+    /// Takes back the last action
     ///
-    /// Increments the consecutive passes counter, which is used to track how players have passed in succession. When
-    /// `consecutive_passes` equals the number of players in the game, it typically indicates that the game should end due to all
-    /// players being unable to play.
+    /// Reverses the changes made by the action: a played tile is removed from the layout, a drawn tile is returned to the
+    /// boneyard, the number of consecutive passes, the trains of Mexican Train, and the player's hand are restored, and it becomes
+    /// the turn of the player who took the action. The game is no longer over. Actions must be taken back in the reverse of the
+    /// order in which they were taken. Together with `apply`, this lets a search explore a position in place instead of cloning
+    /// it for every node.
+    ///
+    /// # Arguments
+    /// * `action` - The last action taken
+    ///
+    /// # Panics
+    /// Panics if `action` is not the last action applied to the state
     ///
     /// # Examples
     /// ```rust
-    /// # use dominoes_state::DominoesState;
-    /// # use rules::Configuration;
+    /// # use dominoes_state::{Action, DominoesState};
+    /// # use rules::{Configuration, Tile};
     ///
     /// let config = Configuration::default();
     /// let mut state = DominoesState::new(&config);
+    /// let before = state.clone();
     ///
-    /// // Initially no passes
-    /// assert_eq!(state.consecutive_passes, 0);
-    ///
-    /// // Record a pass
-    /// state.pass();
-    /// assert_eq!(state.consecutive_passes, 1);
-    ///
-    /// // Record another pass
-    /// state.pass();
-    /// assert_eq!(state.consecutive_passes, 2);
-    ///
-    /// // Playing a tile resets the counter
-    /// let tile = rules::Tile::from((6, 6));
-    /// if state.can_play_tile(&tile, None) {
-    ///     state.play_tile(tile, None);
-    ///     assert_eq!(state.consecutive_passes, 0);
-    /// }
+    /// let tile = Tile::from((6, 6));
+    /// state.play_tile_unchecked(tile, None);
+    /// state.undo(&Action::play_unchecked(0, tile, None));
+    /// assert!(state.layout.is_empty());
+    /// assert_eq!(state.fingerprint, before.fingerprint);
     /// ```
-    pub fn pass(&mut self) {
-        self.update_consecutive_passes(true);
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(player_id = action.player_id)))]
+    pub fn undo(&mut self, action: &Action) {
+        if let Some((tile, end)) = action.tile_played {
+            let last = self.layout.nodes.last().expect("The layout is empty");
+            assert_eq!(last.tile, tile, "Tile {tile} is not the last tile played");
+
+            // Reverse the changes to the fingerprint made by play_tile
+            match (end, last.parent) {
+                (Some(matched_end), Some(parent)) => {
+                    let (_, new_end) = tile.matches(&self.layout.nodes[parent].tile).expect("Tiles should be attachable");
+                    // The last tile has no children, so all of the ends it opened are still open
+                    let last_index = self.layout.nodes.len() - 1;
+                    let new_end_change = self.layout.open.get_vec(&last_index).map_or(0, Vec::len) as u8;
+                    let new_end_count = self.layout.open_count(new_end);
+                    let matched_count = self.layout.open_count(matched_end);
+                    self.fingerprint
+                        .change_end_count(matched_end, matched_count + 1, matched_count);
+                    self.fingerprint
+                        .change_end_count(new_end, new_end_count - new_end_change, new_end_count);
+                }
+                _ => self.change_first_tile_end_counts(tile),
+            }
+            self.fingerprint.add_tile(tile.into());
+
+            // Under the muggins rule, the points went to the player who claimed them, if anyone
+            let awarded = match &mut self.muggins {
+                Some(muggins) => muggins.undo_play(),
+                None => Some(action.player_id),
+            };
+            if let (Some(points), Some(player_id)) = (self.points_scored(), awarded) {
+                self.scores[player_id as usize] -= points;
+            }
+            self.layout.detach_last();
+
+            // A state created from a position does not know the passes before the tiles in its layout
+            self.consecutive_passes = self.passes_before_plays.pop().unwrap_or(0);
+            self.restore_trains();
+            self.restore_drawn();
+        }
+        if let Some(tile) = action.tile_drawn {
+            assert_eq!(self.boneyard.undraw(), Some(tile), "Tile {tile} is not the last tile drawn");
+            // A player who may draw only one tile has not drawn before it
+            self.drawn = false;
+            self.restore_unclaimed();
+        }
+
+        if action.is_pass() {
+            self.consecutive_passes = self.consecutive_passes.saturating_sub(1);
+            self.restore_trains();
+            self.restore_drawn();
+            self.restore_unclaimed();
+        }
+        if self.dealt {
+            self.hands[action.player_id as usize].undo(action);
+        }
+        self.whose_turn = action.player_id;
+        self.game_is_over = false;
+        self.winner = None;
+        self.check_fingerprint();
+    }
+
+    // Toggles the fingerprint's end counts for the ends opened by the first tile, so it both adds and removes them. The first tile
+    // must be the only tile in the layout.
+    fn change_first_tile_end_counts(&mut self, tile: Tile) {
+        let (a, b) = tile.as_tuple();
+        if a == b {
+            self.fingerprint.change_end_count(a, 0, self.layout.open_count(a));
+        } else {
+            self.fingerprint.change_end_count(a, 0, 1);
+            self.fingerprint.change_end_count(b, 0, 1);
+        }
+    }
+
+    // Lets the points of the last play be claimed again under the muggins rule if they could be before the draw or pass that is
+    // taken back
+    fn restore_unclaimed(&mut self) {
+        if let Some(muggins) = &mut self.muggins {
+            muggins.restore();
+        }
+    }
+
+    // Restores the trains from before the last tile played or pass in Mexican Train
+    fn restore_trains(&mut self) {
+        if let Some(trains) = self.trains_before_actions.pop() {
+            self.trains = Some(trains);
+        }
+    }
+
+    // Forgets that the player whose turn it is has drawn, since they have played or passed. Where a player may draw only one
+    // tile, whether they had drawn is kept so that `undo` can restore it.
+    fn end_drawing(&mut self) {
+        if self.draw_rule() == DrawRule::DrawOne {
+            self.drawn_before_actions.push(self.drawn);
+        }
+        self.drawn = false;
+    }
+
+    // Restores whether a tile had been drawn before the last tile played or pass (see `end_drawing`)
+    fn restore_drawn(&mut self) {
+        if self.draw_rule() == DrawRule::DrawOne {
+            self.drawn = self.drawn_before_actions.pop().unwrap_or(false);
+        }
+    }
+
+    // Returns the node of the double that must receive the rest of its tiles before any other end may be used in Chicken Foot, if
+    // any. A double can only be attached to a tile that matches it, which is not a double, so there is at most one such double.
+    fn unfilled_double(&self) -> Option<usize> {
+        if self.variation != Variation::ChickenFoot {
+            return None;
+        }
+        self.layout.open.keys().copied().find(|&node| self.layout.nodes[node].tile.is_double())
+    }
+
+    // Returns the number of players. There is a score for every player.
+    fn num_players(&self) -> u8 {
+        self.scores.len() as u8
+    }
+
+    // Returns the points scored by the last tile played, or None if plays do not score in this variation
+    fn points_scored(&self) -> Option<u32> {
+        scorer(self.variation).map(|scorer| scorer.score(&self.layout)).filter(|&points| points > 0)
     }
 
     // Increments the consecutive passes counter, or resets it
     fn update_consecutive_passes(&mut self, increment: bool) {
-        // FIXME: This currently does not change the fingerprint, but it probably should
         self.consecutive_passes = if increment {
             self.consecutive_passes + 1
         } else {
@@ -362,6 +1337,7 @@ impl<'a> PublicView<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::History;
 
     #[test]
     fn test_dominoes_dominoes_state_initialization() {
@@ -371,7 +1347,7 @@ mod tests {
         assert_eq!(state.winner, None);
         assert_eq!(state.whose_turn(), 0); // PlayerId::ALICE as u8
         assert_eq!(state.consecutive_passes, 0);
-        assert_eq!(state.fingerprint(), 0);
+        assert_eq!(state.fingerprint, ZHash::default());
     }
 
     #[test]
@@ -405,6 +1381,25 @@ mod tests {
         assert_eq!(boneyard_ref.count(), 27);
     }
 
+    #[test]
+    fn test_with_position() {
+        let configuration = Configuration::default();
+
+        // Play some tiles one at a time
+        let mut played = DominoesState::new(&configuration);
+        played.play_tile_unchecked(Tile::from((6, 6)), None);
+        played.play_tile_unchecked(Tile::from((3, 6)), Some(6));
+        played.play_tile_unchecked(Tile::from((1, 3)), Some(3));
+
+        // The same position set up directly has the same layout and fingerprint, but a different boneyard
+        let state = DominoesState::with_position(&configuration, played.layout.clone(), Boneyard::with(Vec::new()));
+        assert_eq!(state.layout.end_counts, played.layout.end_counts);
+        assert_eq!(state.fingerprint, played.fingerprint);
+        assert_ne!(state.fingerprint(), played.fingerprint());
+        assert_eq!(state.boneyard.count(), 0);
+        assert!(!state.game_is_over);
+    }
+
     #[test]
     fn test_custom_set() {
         let configuration = Configuration::new(2, rules::Variation::Traditional, 3, 7);
@@ -466,7 +1461,7 @@ mod tests {
 
         // Place initial double
         let initial_tile = Tile::from((3, 3));
-        state.play_tile(initial_tile, None);
+        state.play_tile_unchecked(initial_tile, None);
 
         // Now test tiles that can and cannot be played
         let matching_tile = Tile::from((3, 5));
@@ -518,7 +1513,7 @@ mod tests {
 
         // Should be able to play double on empty layout
         assert!(state.can_play_tile(&double_tile, None));
-        state.play_tile(double_tile, None);
+        state.play_tile_unchecked(double_tile, None);
 
         // Layout should no longer be empty
         assert!(!state.layout.is_empty());
@@ -534,12 +1529,12 @@ mod tests {
 
         // Start with a double
         let initial_tile = Tile::from((2, 2));
-        state.play_tile(initial_tile, None);
+        state.play_tile_unchecked(initial_tile, None);
 
         // Play a matching tile
         let second_tile = Tile::from((2, 5));
         assert!(state.can_play_tile(&second_tile, Some(2)));
-        state.play_tile(second_tile, Some(2));
+        state.play_tile_unchecked(second_tile, Some(2));
 
         // Should now have open ends for 2 and 5
         assert!(state.layout.end_counts[2] > 0);
@@ -548,7 +1543,7 @@ mod tests {
         // Play another tile
         let third_tile = Tile::from((1, 5));
         assert!(state.can_play_tile(&third_tile, Some(5)));
-        state.play_tile(third_tile, Some(5));
+        state.play_tile_unchecked(third_tile, Some(5));
 
         // Should now have open ends for 2 and 1
         assert!(state.layout.end_counts[2] > 0);
@@ -563,7 +1558,85 @@ mod tests {
 
         // Try to play non-double on empty layout
         let invalid_tile = Tile::from((1, 2));
-        state.play_tile(invalid_tile, None);
+        state.play_tile_unchecked(invalid_tile, None);
+    }
+
+    #[test]
+    fn test_advance_turn() {
+        let configuration = Configuration::new(4, Variation::AllFives, 6, 5);
+        let mut state = DominoesState::new(&configuration);
+        let order: Vec<u8> = (0..5)
+            .map(|_| {
+                state.advance_turn();
+                state.whose_turn
+            })
+            .collect();
+        assert_eq!(order, vec![1, 2, 3, 0, 1]);
+        assert_eq!(state.next_player(3), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "It is not player 1's turn")]
+    fn test_apply_out_of_turn() {
+        let state = DominoesState::new(&Configuration::default());
+        state.apply(&Action::pass(1));
+    }
+
+    #[test]
+    fn test_try_apply() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        let mut alice = Hand::new();
+        alice.add_tile(Tile::from((6, 6)));
+        alice.add_tile(Tile::from((0, 1)));
+        state.set_hands(vec![alice, Hand::new()]);
+
+        // The state is not changed by actions that cannot be applied
+        assert_eq!(state.try_apply(&Action::pass(1)).unwrap_err(), DominoesError::WrongTurn(1));
+        let tile = Tile::from((5, 5));
+        assert_eq!(state.try_apply(&Action::play_unchecked(0, tile, None)).unwrap_err(), DominoesError::NotInHand(tile));
+        let tile = Tile::from((0, 1));
+        assert_eq!(state.try_apply(&Action::play_unchecked(0, tile, Some(1))).unwrap_err(), DominoesError::EndOfFirstTile);
+        let next = *state.boneyard.peek().unwrap();
+        let other = (0..28).map(Tile::from).find(|&tile| tile != next).unwrap();
+        assert_eq!(state.try_apply(&Action::draw(0, other)).unwrap_err(), DominoesError::NotNextTile(other));
+        assert_eq!(state.layout.nodes.len(), 0);
+
+        // A legal action gives the same state as `apply`
+        let action = Action::play_unchecked(0, Tile::from((6, 6)), None);
+        let new_state = state.try_apply(&action).unwrap();
+        assert_eq!(new_state.fingerprint(), state.apply(&action).fingerprint());
+        assert!(!new_state.hand_of(0).contains(&Tile::from((6, 6))));
+
+        // Nothing can be applied once the game is over
+        let mut over = new_state.clone();
+        over.mark_game_over(None);
+        assert_eq!(over.try_apply(&Action::pass(1)).unwrap_err(), DominoesError::GameOver);
+    }
+
+    #[test]
+    fn test_play_tile_checked() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        assert_eq!(state.play_tile(Tile::new(253), None), Err(DominoesError::NotCanonical(253)));
+
+        // The first tile is played without an end, and the state is unchanged by tiles that cannot be played
+        let tile = Tile::from((1, 2));
+        assert_eq!(state.play_tile(tile, None), Err(DominoesError::InvalidMove { tile, end: None }));
+        assert_eq!(state.play_tile(Tile::from((3, 3)), Some(3)), Err(DominoesError::EndOfFirstTile));
+        assert!(state.layout.is_empty());
+        assert_eq!(state.play_tile(Tile::from((3, 3)), None), Ok(()));
+
+        // Every other tile is played on an end that matches it
+        let fingerprint = state.fingerprint;
+        let tile = Tile::from((3, 4));
+        assert_eq!(state.play_tile(tile, None), Err(DominoesError::NoEnd(tile)));
+        assert_eq!(state.play_tile(tile, Some(4)), Err(DominoesError::InvalidMove { tile, end: Some(4) }));
+        assert_eq!(state.play_tile(tile, Some(200)), Err(DominoesError::InvalidMove { tile, end: Some(200) }));
+        assert_eq!(state.fingerprint, fingerprint);
+        assert_eq!(state.play_tile(tile, Some(3)), Ok(()));
+        assert_eq!(state.layout.nodes.len(), 2);
     }
 
     #[test]
@@ -574,11 +1647,11 @@ mod tests {
 
         // Place initial tile
         let initial_tile = Tile::from((3, 3));
-        state.play_tile(initial_tile, None);
+        state.play_tile_unchecked(initial_tile, None);
 
         // Try to play without specifying end on non-empty layout
         let second_tile = Tile::from((3, 4));
-        state.play_tile(second_tile, None);
+        state.play_tile_unchecked(second_tile, None);
     }
 
     #[test]
@@ -589,9 +1662,9 @@ mod tests {
         // Test State trait methods
         assert_eq!(state.whose_turn(), 0); // PlayerId::ALICE as u8
 
-        // Test fingerprint (should be valid u64)
+        // Test fingerprint. Only the boneyard contributes to the starting state.
         let fingerprint = state.fingerprint();
-        assert!(fingerprint == 0); // Starting state is always 0
+        assert_eq!(fingerprint, u64::from(*ZHash::default().change_boneyard_size(0, configuration.set_size())));
     }
 
     #[test]
@@ -603,7 +1676,7 @@ mod tests {
 
         // Play a tile
         let tile = Tile::from((3, 3));
-        state.play_tile(tile, None);
+        state.play_tile_unchecked(tile, None);
 
         let after_play_fingerprint = state.fingerprint();
 
@@ -611,6 +1684,120 @@ mod tests {
         assert_ne!(initial_fingerprint, after_play_fingerprint);
     }
 
+    #[test]
+    fn test_fingerprint_distinguishes_turn_passes_and_boneyard() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        let mut fingerprints = vec![state.fingerprint()];
+
+        state.draw_tile();
+        fingerprints.push(state.fingerprint());
+        state.pass();
+        fingerprints.push(state.fingerprint());
+        state.whose_turn = 1;
+        fingerprints.push(state.fingerprint());
+
+        for (i, a) in fingerprints.iter().enumerate() {
+            for b in &fingerprints[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // The layout part is unaffected
+        assert_eq!(state.fingerprint, ZHash::from_state(&state.layout, 0));
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_trains_and_drawn() {
+        // The same tiles, but Alice's train and Bob's train are swapped
+        let configuration = Configuration::new(2, Variation::MexicanTrain, 12, 4);
+        let position = |lines: [(u8, u8); 2]| {
+            let mut layout = Layout::new(&configuration);
+            layout.attach_unchecked(Tile::from((12, 12)), None);
+            for line in lines {
+                layout.attach_unchecked(Tile::from(line), Some(0));
+            }
+            DominoesState::with_position(&configuration, layout, Boneyard::with(vec![Tile::from((0, 1))]))
+        };
+        let state = position([(3, 12), (6, 12)]);
+        let swapped = position([(6, 12), (3, 12)]);
+        assert_eq!(state.fingerprint, swapped.fingerprint);
+        assert_ne!(state.fingerprint(), swapped.fingerprint());
+
+        // Whether the player has drawn matters only where a player may draw only one tile
+        let mut drawn = state.clone();
+        drawn.drawn = true;
+        assert_ne!(state.fingerprint(), drawn.fingerprint());
+        let configuration = Configuration::default();
+        let state = DominoesState::new(&configuration);
+        let mut drawn = state.clone();
+        drawn.drawn = true;
+        assert_eq!(state.fingerprint(), drawn.fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_scores() {
+        // The same position, but Bob has scored
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((5, 5)), None);
+        let mut scored = state.clone();
+        scored.add_score(1, 10);
+        assert_eq!(state.fingerprint, scored.fingerprint);
+        assert_ne!(state.fingerprint(), scored.fingerprint());
+
+        // Points that have not been claimed yet are distinguished from points that have been claimed and from no points
+        let configuration = configuration.with_muggins(true);
+        let mut unclaimed = DominoesState::new(&configuration);
+        unclaimed.play_tile_unchecked(Tile::from((5, 5)), None);
+        let mut claimed = unclaimed.clone();
+        claimed.claim_points(0).unwrap();
+        let mut fingerprints = vec![unclaimed.fingerprint(), claimed.fingerprint()];
+        let mut unscored = state.clone();
+        unscored.scores = vec![0, 0];
+        fingerprints.push(unscored.fingerprint());
+        for (i, a) in fingerprints.iter().enumerate() {
+            assert!(fingerprints[i + 1..].iter().all(|b| a != b));
+        }
+
+        // Taking back the play takes back its points
+        unclaimed.undo(&Action::play_unchecked(0, Tile::from((5, 5)), None));
+        assert_eq!(unclaimed.fingerprint(), DominoesState::new(&configuration).fingerprint());
+    }
+
+    #[test]
+    fn test_verify_fingerprint() {
+        // Every tile played on every end, and taken back, keeps the fingerprint right
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let mut state = DominoesState::new(&configuration);
+        let tiles = [(5, 5), (5, 6), (3, 5), (3, 6), (1, 3), (0, 1)];
+        let mut actions = Vec::new();
+        state.play_tile_unchecked(Tile::from(tiles[0]), None);
+        actions.push(Action::play_unchecked(0, Tile::from(tiles[0]), None));
+        for &tile in &tiles[1..] {
+            let tile = Tile::from(tile);
+            let end = (0..=6).find(|&end| state.can_play_tile(&tile, Some(end)));
+            state.play_tile_unchecked(tile, end);
+            state.verify_fingerprint();
+            actions.push(Action::play_unchecked(0, tile, end));
+        }
+        for action in actions.iter().rev() {
+            state.undo(action);
+            state.verify_fingerprint();
+        }
+        assert_eq!(state.fingerprint, ZHash::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "The fingerprint does not match")]
+    fn test_verify_fingerprint_mismatch() {
+        let mut state = DominoesState::new(&Configuration::default());
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.fingerprint.add_tile(Tile::from((1, 2)).into());
+        state.verify_fingerprint();
+    }
+
     #[test]
     fn test_complex_tile_sequence() {
         let configuration = Configuration::default();
@@ -632,7 +1819,7 @@ mod tests {
                 tile,
                 end
             );
-            state.play_tile(tile, end);
+            state.play_tile_unchecked(tile, end);
         }
 
         // Should have open ends for 4 and 2
@@ -702,10 +1889,10 @@ mod tests {
 
         // Play some tiles first
         let tile1 = Tile::from((3, 3));
-        state.play_tile(tile1, None);
+        state.play_tile_unchecked(tile1, None);
 
         let tile2 = Tile::from((3, 5));
-        state.play_tile(tile2, Some(3));
+        state.play_tile_unchecked(tile2, Some(3));
 
         // Game should still be active
         assert!(!state.game_is_over);
@@ -751,7 +1938,7 @@ mod tests {
 
         // Play a tile (should reset counter)
         let tile = Tile::from((4, 4));
-        state.play_tile(tile, None);
+        state.play_tile_unchecked(tile, None);
         assert_eq!(state.consecutive_passes, 0);
 
         // Pass again after playing
@@ -770,7 +1957,7 @@ mod tests {
         assert_eq!(state.consecutive_passes, 2);
 
         let tile1 = Tile::from((6, 6));
-        state.play_tile(tile1, None);
+        state.play_tile_unchecked(tile1, None);
         assert_eq!(state.consecutive_passes, 0);
 
         // Cycle 2: Pass then play again
@@ -778,7 +1965,7 @@ mod tests {
         assert_eq!(state.consecutive_passes, 1);
 
         let tile2 = Tile::from((5, 6));
-        state.play_tile(tile2, Some(6));
+        state.play_tile_unchecked(tile2, Some(6));
         assert_eq!(state.consecutive_passes, 0);
 
         // Cycle 3: Multiple passes
@@ -837,9 +2024,10 @@ mod tests {
 
         // Play a tile first to establish some game state
         let tile = Tile::from((2, 2));
-        state.play_tile(tile, None);
+        state.play_tile_unchecked(tile, None);
 
         let initial_fingerprint = state.fingerprint();
+        let initial_layout_fingerprint = state.fingerprint;
         let initial_boneyard_count = state.boneyard.count();
         let initial_turn = state.whose_turn();
 
@@ -847,7 +2035,8 @@ mod tests {
         state.pass();
 
         assert_eq!(state.consecutive_passes, 1);
-        assert_eq!(state.fingerprint(), initial_fingerprint); // Fingerprint unchanged
+        assert_eq!(state.fingerprint, initial_layout_fingerprint); // Layout fingerprint unchanged
+        assert_ne!(state.fingerprint(), initial_fingerprint); // The passes are distinguished
         assert_eq!(state.boneyard.count(), initial_boneyard_count); // Boneyard unchanged
         assert_eq!(state.whose_turn(), initial_turn); // Turn unchanged
         assert!(!state.game_is_over); // Game state unchanged
@@ -911,11 +2100,11 @@ mod tests {
 
         // Play some tiles - game should remain non-terminal
         let tile1 = Tile::from((3, 3));
-        state.play_tile(tile1, None);
+        state.play_tile_unchecked(tile1, None);
         assert!(!state.is_terminal());
 
         let tile2 = Tile::from((3, 5));
-        state.play_tile(tile2, Some(3));
+        state.play_tile_unchecked(tile2, Some(3));
         assert!(!state.is_terminal());
 
         // Draw tiles - game should remain non-terminal
@@ -964,4 +2153,489 @@ mod tests {
         assert_eq!(state2.is_terminal(), state2.game_is_over);
         assert!(state2.is_terminal());
     }
+
+    #[test]
+    fn test_undo() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        let mut history = History::new();
+        let mut snapshots = Vec::new();
+
+        // Alice opens with the double six, Bob draws and passes, and Alice plays on a six
+        let drawn = *state.boneyard.peek().unwrap();
+        let actions = vec![
+            Action::play_unchecked(0, Tile::from((6, 6)), None),
+            Action::draw(1, drawn),
+            Action::pass(1),
+            Action::play_unchecked(0, Tile::from((3, 6)), Some(6)),
+        ];
+        for action in &actions {
+            snapshots.push(state.clone());
+            if action.tile_drawn.is_some() {
+                state.draw_tile();
+            }
+            if let Some((tile, end)) = action.tile_played {
+                state.play_tile_unchecked(tile, end);
+            }
+            if action.is_pass() {
+                state.pass();
+            }
+            state.whose_turn = 1 - action.player_id;
+            history.add_action(action.clone());
+        }
+        state.mark_game_over(Some(0));
+
+        // Take back every action, in reverse order
+        while let Some(action) = history.pop_action() {
+            state.undo(&action);
+            let expected = snapshots.pop().unwrap();
+            assert_eq!(state.layout.to_string(), expected.layout.to_string());
+            assert_eq!(state.layout.end_counts, expected.layout.end_counts);
+            assert_eq!(state.boneyard.count(), expected.boneyard.count());
+            assert_eq!(state.fingerprint, expected.fingerprint);
+            assert_eq!(state.consecutive_passes, expected.consecutive_passes);
+            assert_eq!(state.whose_turn, action.player_id);
+            assert!(!state.game_is_over);
+        }
+        assert!(state.layout.is_empty());
+    }
+
+    #[test]
+    fn test_undo_after_apply() {
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        let start = DominoesState::with_position(&configuration, layout, Boneyard::with(vec![]));
+
+        // Alice passes, Bob plays, and then both pass, which blocks the game
+        let actions = [Action::pass(0), Action::play_unchecked(1, Tile::from((5, 6)), Some(6)), Action::pass(0), Action::pass(1)];
+        let mut states = vec![start];
+        for action in &actions {
+            states.push(states.last().unwrap().apply(action));
+        }
+        let mut state = states.pop().unwrap();
+        assert!(state.is_terminal());
+
+        // Taking back each action in place restores the state before it
+        for action in actions.iter().rev() {
+            state.undo(action);
+            let expected = states.pop().unwrap();
+            assert_eq!(state.fingerprint, expected.fingerprint);
+            assert_eq!(state.consecutive_passes, expected.consecutive_passes);
+            assert_eq!(state.whose_turn, expected.whose_turn);
+            assert!(!state.is_terminal());
+        }
+        assert_eq!(state.layout.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_opening() {
+        // Traditional requires a double first
+        let state = DominoesState::new(&Configuration::default());
+        assert!(state.can_play_tile(&Tile::from((4, 4)), None));
+        assert!(!state.can_play_tile(&Tile::from((2, 5)), None));
+
+        // All Fives allows any tile first, and both of its ends are open
+        let configuration = Configuration::new(2, rules::Variation::AllFives, 6, 7);
+        let mut state = DominoesState::new(&configuration);
+        let before = state.clone();
+        assert!(state.can_play_tile(&Tile::from((2, 5)), None));
+        state.play_tile_unchecked(Tile::from((2, 5)), None);
+        assert_eq!(state.layout.open_count(2), 1);
+        assert_eq!(state.layout.open_count(5), 1);
+        assert_eq!(state.scores(), &[0, 0]);
+
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((2, 5)), None);
+        assert_eq!(state.fingerprint, DominoesState::with_position(&configuration, layout, Boneyard::with(vec![])).fingerprint);
+
+        state.undo(&Action::play_unchecked(0, Tile::from((2, 5)), None));
+        assert!(state.layout.is_empty());
+        assert_eq!(state.fingerprint, before.fingerprint);
+    }
+
+    #[test]
+    fn test_scores() {
+        let configuration = Configuration::new(2, rules::Variation::AllFives, 6, 7);
+        let mut state = DominoesState::new(&configuration);
+        assert_eq!(state.scores(), &[0, 0]);
+
+        // Alice scores 10 with the double five, and Bob scores 10 by playing the blank-five, since the double still counts
+        state.play_tile_unchecked(Tile::from((5, 5)), None);
+        state.whose_turn = 1;
+        state.play_tile_unchecked(Tile::from((0, 5)), Some(5));
+        assert_eq!(state.scores(), &[10, 10]);
+
+        // Alice plays the three-five on the other side of the double, which scores nothing
+        state.whose_turn = 0;
+        state.play_tile_unchecked(Tile::from((3, 5)), Some(5));
+        assert_eq!(state.scores(), &[10, 10]);
+
+        // Taking back the plays takes back the points
+        state.undo(&Action::play_unchecked(0, Tile::from((3, 5)), Some(5)));
+        state.undo(&Action::play_unchecked(1, Tile::from((0, 5)), Some(5)));
+        assert_eq!(state.scores(), &[10, 0]);
+        state.undo(&Action::play_unchecked(0, Tile::from((5, 5)), None));
+        assert_eq!(state.scores(), &[0, 0]);
+
+        state.add_score(1, 25);
+        assert_eq!(state.scores(), &[0, 25]);
+
+        // Plays do not score in the traditional game
+        let mut traditional = DominoesState::new(&Configuration::default());
+        traditional.play_tile_unchecked(Tile::from((5, 5)), None);
+        assert_eq!(traditional.scores(), &[0, 0]);
+    }
+
+    #[test]
+    fn test_muggins() {
+        let configuration = Configuration::new(2, rules::Variation::AllFives, 6, 7).with_muggins(true);
+        let boneyard = Boneyard::with(vec![Tile::from((1, 2))]);
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), boneyard);
+
+        // Alice claims the 10 points of the double five
+        state.play_tile_unchecked(Tile::from((5, 5)), None);
+        assert_eq!(state.unclaimed_points(), Some((0, 10)));
+        assert_eq!(state.claim_points(0), Ok(10));
+        assert_eq!(state.unclaimed_points(), None);
+
+        // Bob misses his 10 points, and Alice calls muggins
+        state.whose_turn = 1;
+        state.play_tile_unchecked(Tile::from((0, 5)), Some(5));
+        assert_eq!(state.claim_points(0), Ok(10));
+        assert_eq!(state.scores(), &[20, 0]);
+
+        // Nobody claims the 10 points of the double blank, so they are lost once Bob draws
+        state.whose_turn = 0;
+        state.play_tile_unchecked(Tile::from((0, 0)), Some(0));
+        assert_eq!(state.unclaimed_points(), Some((0, 10)));
+        state.whose_turn = 1;
+        state.draw_tile();
+        assert_eq!(state.claim_points(0), Err(DominoesError::NoPointsToClaim));
+
+        // Taking back the draw lets the points be claimed again, and taking back the plays takes the points back from the players
+        // who claimed them
+        state.undo(&Action::draw(1, Tile::from((1, 2))));
+        assert_eq!(state.unclaimed_points(), Some((0, 10)));
+        state.undo(&Action::play_unchecked(0, Tile::from((0, 0)), Some(0)));
+        assert_eq!(state.unclaimed_points(), None);
+        assert_eq!(state.scores(), &[20, 0]);
+        state.undo(&Action::play_unchecked(1, Tile::from((0, 5)), Some(5)));
+        assert_eq!(state.scores(), &[10, 0]);
+        state.undo(&Action::play_unchecked(0, Tile::from((5, 5)), None));
+        assert_eq!(state.scores(), &[0, 0]);
+
+        // Taking back a pass also lets the points be claimed again
+        state.play_tile_unchecked(Tile::from((5, 5)), None);
+        state.whose_turn = 1;
+        state.pass();
+        assert_eq!(state.unclaimed_points(), None);
+        state.undo(&Action::pass(1));
+        assert_eq!(state.unclaimed_points(), Some((0, 10)));
+
+        // In a search, every play scores at once, and taking it back takes the points back
+        let mut state = state.with_points_claimed();
+        state.play_tile_unchecked(Tile::from((0, 5)), Some(5));
+        assert_eq!(state.scores(), &[0, 10]);
+        assert_eq!(state.unclaimed_points(), None);
+        state.undo(&Action::play_unchecked(1, Tile::from((0, 5)), Some(5)));
+        assert_eq!(state.scores(), &[0, 0]);
+        assert_eq!(state.unclaimed_points(), Some((0, 10)));
+    }
+
+    #[test]
+    fn test_apply() {
+        let configuration = Configuration::default();
+        let boneyard = Boneyard::with(vec![Tile::from((1, 2))]);
+        let state = DominoesState::with_position(&configuration, Layout::new(&configuration), boneyard);
+
+        // Playing a tile passes the turn and changes the fingerprint
+        let state = state.apply(&Action::play_unchecked(0, Tile::from((6, 6)), None));
+        assert_eq!(state.layout.nodes.len(), 1);
+        assert_eq!(state.whose_turn(), 1);
+        assert_ne!(state.fingerprint, DominoesState::new(&configuration).fingerprint);
+
+        // Drawing a tile does not pass the turn
+        let state = state.apply(&Action::draw(1, Tile::from((1, 2))));
+        assert_eq!(state.boneyard.count(), 0);
+        assert_eq!(state.whose_turn(), 1);
+
+        // Passing passes the turn, and the game is blocked when every player passes
+        let state = state.apply(&Action::pass(1));
+        assert_eq!(state.consecutive_passes, 1);
+        assert_eq!(state.whose_turn(), 0);
+        assert!(!state.is_terminal());
+        let state = state.apply(&Action::pass(0));
+        assert!(state.is_terminal());
+        assert_eq!(state.winner, None);
+    }
+
+    #[test]
+    fn test_apply_is_deterministic() {
+        let configuration = Configuration::new(2, rules::Variation::AllFives, 6, 7);
+        let state = DominoesState::new(&configuration);
+        let tile = *state.boneyard.peek().expect("The boneyard is not empty");
+        let actions = [Action::draw(0, tile), Action::play_unchecked(0, Tile::from((5, 5)), None), Action::pass(1)];
+        let a = actions.iter().fold(state.clone(), |state, action| state.apply(action));
+        let b = actions.iter().fold(state.clone(), |state, action| state.apply(action));
+        assert_eq!(a.fingerprint, b.fingerprint);
+        assert_eq!(a.boneyard.remaining(), b.boneyard.remaining());
+        assert_eq!(a.scores(), &[10, 0]);
+        assert_eq!(a.whose_turn(), 0);
+    }
+
+    #[test]
+    fn test_deal() {
+        let configuration = Configuration::new(3, rules::Variation::AllFives, 6, 5);
+        let mut state = DominoesState::new(&configuration);
+        let boneyard = state.boneyard.remaining().to_vec();
+        assert_eq!(state.tiles_remaining(1), 0);
+
+        // Each player draws a full hand in turn
+        state.deal();
+        let drawn: Vec<Tile> = boneyard.iter().take(15).copied().collect();
+        for player_id in 0..3 {
+            assert_eq!(state.hand_of(player_id).tiles(), &drawn[player_id as usize * 5..player_id as usize * 5 + 5]);
+        }
+        assert_eq!(state.boneyard.count(), 13);
+    }
+
+    #[test]
+    fn test_set_hands() {
+        let configuration = Configuration::new(2, rules::Variation::AllFives, 6, 1);
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), Boneyard::with(Vec::new()));
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((0, 5)));
+        state.set_hands(vec![hand, Hand::new()]);
+        assert!(state.is_dealt());
+
+        // The hands are kept track of, so playing the last tile wins
+        let state = state.apply(&Action::play_unchecked(0, Tile::from((0, 5)), None));
+        assert!(state.game_is_over);
+        assert_eq!(state.winner, Some(0));
+    }
+
+    #[test]
+    fn test_apply_tracks_hands() {
+        let configuration = Configuration::new(2, rules::Variation::AllFives, 6, 1);
+        let tiles = [(5, 5), (0, 5), (1, 2), (3, 4)].map(Tile::from);
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), Boneyard::with(tiles.to_vec()));
+        state.deal();
+        let before = state.clone();
+        assert_eq!(state.hand_of(0).tiles(), &[tiles[0]]);
+        assert_eq!(state.hand_of(1).tiles(), &[tiles[1]]);
+
+        // Alice draws and plays the double five, and Bob plays his only tile, which wins
+        let actions =
+            [Action::draw(0, tiles[2]), Action::play_unchecked(0, tiles[0], None), Action::play_unchecked(1, tiles[1], Some(5))];
+        let mut state = actions.iter().fold(state, |state, action| state.apply(action));
+        assert_eq!(state.hand_of(0).tiles(), &[tiles[2]]);
+        assert_eq!(state.tiles_remaining(1), 0);
+        assert!(state.is_terminal());
+        assert_eq!(state.winner, Some(1));
+
+        // Taking back the actions restores the hands
+        for action in actions.iter().rev() {
+            state.undo(action);
+        }
+        assert_eq!(state.hand_of(0).tiles(), before.hand_of(0).tiles());
+        assert_eq!(state.hand_of(1).tiles(), before.hand_of(1).tiles());
+        assert_eq!(state.boneyard.remaining(), before.boneyard.remaining());
+    }
+
+    #[test]
+    fn test_without_hidden_info() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 1);
+        let tiles = [(5, 5), (0, 5), (3, 4), (1, 2)].map(Tile::from);
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), Boneyard::with(tiles.to_vec()));
+        state.deal();
+        let state = state.apply(&Action::play_unchecked(0, tiles[0], None));
+
+        let public = state.without_hidden_info();
+        assert!(!public.is_dealt());
+        assert_eq!(public.tiles_remaining(0), 0);
+        assert_eq!(public.boneyard.remaining(), &[tiles[3], tiles[2]]);
+        assert_eq!(public.layout.nodes.len(), 1);
+        assert_eq!(public.whose_turn, state.whose_turn);
+        assert_eq!(public.scores(), state.scores());
+        assert_eq!(public.fingerprint(), state.fingerprint());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_state_serde() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 2);
+        let tiles = [(5, 5), (1, 2), (0, 5), (3, 4), (2, 6), (6, 6)].map(Tile::from);
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), Boneyard::with(tiles.to_vec()));
+        state.deal();
+        let actions = [Action::play_unchecked(0, tiles[0], None), Action::play_unchecked(1, tiles[2], Some(5))];
+        let state = actions.iter().fold(state, |state, action| state.apply(action));
+
+        // Everything is restored, so the actions can be taken back
+        let json = serde_json::to_string(&state).unwrap();
+        let mut restored: DominoesState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.fingerprint(), state.fingerprint());
+        assert_eq!(restored.scores(), state.scores());
+        assert_eq!(restored.hand_of(0).tiles(), state.hand_of(0).tiles());
+        assert_eq!(restored.boneyard.remaining(), state.boneyard.remaining());
+        for action in actions.iter().rev() {
+            restored.undo(action);
+        }
+        assert!(restored.layout.is_empty());
+        assert_eq!(restored.tiles_remaining(0), 2);
+
+        // Without the hidden information, neither the hands nor the drawn tiles are saved
+        let json = serde_json::to_string(&state.without_hidden_info()).unwrap();
+        assert!(!json.contains("[1,2]"));
+        let restored: DominoesState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.fingerprint(), state.fingerprint());
+        assert!(!restored.is_dealt());
+        assert_eq!(restored.boneyard.remaining(), &[tiles[4], tiles[5]]);
+    }
+
+    #[test]
+    fn test_check_blocked_game() {
+        // Alice holds 1 pip and Bob holds 3 pips, with the layout blocked
+        let configuration = Configuration::new(2, Variation::Traditional, 6, 1);
+        let tiles = [(0, 1), (1, 2), (0, 0)].map(Tile::from);
+        let blocked = |configuration: &Configuration, tiles: &[Tile]| {
+            let mut layout = Layout::new(configuration);
+            layout.attach_unchecked(Tile::from((6, 6)), None);
+            let mut state = DominoesState::with_position(configuration, layout, Boneyard::with(tiles.to_vec()));
+            state.deal();
+            state
+        };
+        let mut state = blocked(&configuration, &tiles);
+        assert!(!state.check_blocked_game());
+        let state = state.apply(&Action::pass(0)).apply(&Action::pass(1));
+        assert!(state.is_terminal());
+        assert_eq!(state.winner, Some(0));
+
+        // A tie is a draw
+        let tiles = [(0, 2), (1, 1)].map(Tile::from);
+        let state = blocked(&configuration, &tiles).apply(&Action::pass(0)).apply(&Action::pass(1));
+        assert!(state.is_terminal());
+        assert_eq!(state.winner, None);
+
+        // In the other variations, a blocked game is a draw
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 1);
+        let tiles = [(0, 1), (1, 2)].map(Tile::from);
+        let state = blocked(&configuration, &tiles).apply(&Action::pass(0)).apply(&Action::pass(1));
+        assert!(state.is_terminal());
+        assert_eq!(state.winner, None);
+
+        // In Mexican Train, every player must pass twice
+        let configuration = Configuration::new(2, Variation::MexicanTrain, 12, 1);
+        let mut state = blocked(&configuration, &tiles);
+        for action in [Action::pass(0), Action::pass(1), Action::pass(0)] {
+            state = state.apply(&action);
+            assert!(!state.is_terminal());
+        }
+        let state = state.apply(&Action::pass(1));
+        assert!(state.is_terminal());
+        assert_eq!(state.winner, Some(0));
+    }
+
+    #[test]
+    fn test_draw_rules() {
+        // Neither Alice nor Bob can play on the double six, and two tiles are left in the boneyard
+        let position = |draw_rule| {
+            let configuration = Configuration::new(2, Variation::Traditional, 6, 1).with_draw_rule(draw_rule);
+            let mut layout = Layout::new(&configuration);
+            layout.attach_unchecked(Tile::from((6, 6)), None);
+            let tiles = [(0, 1), (1, 2), (2, 3), (3, 4)].map(Tile::from);
+            let mut state = DominoesState::with_position(&configuration, layout, Boneyard::with(tiles.to_vec()));
+            state.deal();
+            state
+        };
+        let draw = |player_id, tile| Action::draw(player_id, Tile::from(tile));
+
+        // In a Block game, nobody draws, so the game is blocked when both players pass
+        let state = position(DrawRule::NoDraw);
+        assert_eq!(state.legal_actions(state.hand_of(0)), vec![Action::pass(0)]);
+        assert_eq!(draw(0, (2, 3)).validate(&state, state.hand_of(0)), Err(DominoesError::NoDrawing));
+        let state = state.apply(&Action::pass(0)).apply(&Action::pass(1));
+        assert!(state.is_terminal());
+        assert_eq!(state.boneyard.count(), 2);
+
+        // In a Draw game, a player draws until they can play or the boneyard is empty
+        let state = position(DrawRule::DrawUntilPlayable);
+        let state = state.apply(&draw(0, (2, 3)));
+        assert_eq!(state.legal_actions(state.hand_of(0)), vec![draw(0, (3, 4))]);
+        let state = state.apply(&draw(0, (3, 4)));
+        assert_eq!(state.legal_actions(state.hand_of(0)), vec![Action::pass(0)]);
+
+        // A player who may draw only one tile passes after drawing it, and the next player may draw
+        let mut state = position(DrawRule::DrawOne);
+        state = state.apply(&draw(0, (2, 3)));
+        assert_eq!(state.legal_actions(state.hand_of(0)), vec![Action::pass(0)]);
+        assert_eq!(draw(0, (3, 4)).validate(&state, state.hand_of(0)), Err(DominoesError::AlreadyDrawn));
+        state = state.apply(&Action::pass(0));
+        assert_eq!(state.legal_actions(state.hand_of(1)), vec![draw(1, (3, 4))]);
+
+        // The game is not blocked while a player may still draw
+        state = state.apply(&draw(1, (3, 4))).apply(&Action::pass(1));
+        assert!(state.is_terminal());
+        let mut state = position(DrawRule::DrawOne);
+        state.pass();
+        state.pass();
+        assert!(!state.is_blocked());
+
+        // Taking back a pass restores the tile drawn before it
+        let mut state = position(DrawRule::DrawOne).apply(&draw(0, (2, 3))).apply(&Action::pass(0));
+        assert!(!state.has_drawn());
+        state.undo(&Action::pass(0));
+        assert!(state.has_drawn());
+    }
+
+    #[test]
+    fn test_mexican_train() {
+        let configuration = Configuration::new(2, Variation::MexicanTrain, 12, 4);
+        let alice = [(12, 12), (3, 12), (3, 3), (0, 6)].map(Tile::from);
+        let bob = [(6, 12), (3, 9), (1, 2), (2, 5)].map(Tile::from);
+        let boneyard = [alice.as_slice(), &bob, &[(0, 1), (4, 4), (5, 5)].map(Tile::from)].concat();
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), Boneyard::with(boneyard));
+        state.deal();
+        let before = state.clone();
+        let mut actions = Vec::new();
+        let mut take = |state: &mut DominoesState, action: Action| {
+            *state = state.apply(&action);
+            actions.push(action);
+        };
+
+        // Alice plays the engine, and each player starts their own train
+        take(&mut state, Action::play_unchecked(0, alice[0], None));
+        take(&mut state, Action::play_unchecked(1, bob[0], Some(12)));
+        take(&mut state, Action::play_unchecked(0, alice[1], Some(12)));
+        let trains = state.trains().unwrap();
+        assert_eq!((trains.end(0), trains.end(1), trains.end(2)), (Some(2), Some(1), None));
+
+        // Bob cannot play on Alice's train, so he draws one tile and passes, which puts a marker on his train
+        assert_eq!(state.legal_actions(state.hand_of(1)), vec![Action::draw(1, Tile::from((0, 1)))]);
+        take(&mut state, Action::draw(1, Tile::from((0, 1))));
+        assert_eq!(state.legal_actions(state.hand_of(1)), vec![Action::pass(1)]);
+        take(&mut state, Action::pass(1));
+        assert!(state.trains().unwrap().is_marked(1));
+        assert!(state.legal_actions(state.hand_of(0)).contains(&Action::play_unchecked(0, alice[3], Some(6))));
+
+        // Alice plays a double and must play again, but nothing else can be played until the double is satisfied
+        take(&mut state, Action::play_unchecked(0, alice[2], Some(3)));
+        assert_eq!(state.whose_turn, 0);
+        assert_eq!(state.trains().unwrap().unsatisfied_double(&state.layout), Some(0));
+        assert_eq!(state.legal_actions(state.hand_of(0)), vec![Action::draw(0, Tile::from((4, 4)))]);
+        take(&mut state, Action::draw(0, Tile::from((4, 4))));
+        take(&mut state, Action::pass(0));
+        assert_eq!(state.legal_actions(state.hand_of(1)), vec![Action::play_unchecked(1, bob[1], Some(3))]);
+        take(&mut state, Action::play_unchecked(1, bob[1], Some(3)));
+        assert_eq!(state.trains().unwrap().unsatisfied_double(&state.layout), None);
+        assert_eq!(state.layout.to_string(), "12|12=(12|6,12|3-3|3=(3|9))");
+
+        // Everything is restored when the actions are taken back
+        for action in actions.iter().rev() {
+            state.undo(action);
+        }
+        assert_eq!(state.trains(), before.trains());
+        assert_eq!(state.fingerprint(), before.fingerprint());
+        assert_eq!(state.boneyard.remaining(), before.boneyard.remaining());
+    }
 }
@@ -0,0 +1,100 @@
+//! Error types
+//!
+//! This module defines the errors returned by this crate. Errors from the rules crate convert into them, so they can be returned
+//! with `?`.
+
+use thiserror::Error;
+
+use rules::{LayoutError, Tile};
+
+/// An error in the state of a game, or an action that is not legal in it
+///
+/// This is the error of every fallible function in this crate. The functions that change the state check their arguments and
+/// return it by default, and each has a panicking `_unchecked` version for the search, whose actions are known to be legal.
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, DominoesError, DominoesState, Hand};
+/// # use rules::{Configuration, Tile};
+///
+/// let config = Configuration::default();
+/// let state = DominoesState::new(&config);
+/// let result = Action::play_unchecked(0, Tile::from((6, 6)), None).validate(&state, &Hand::new());
+/// assert_eq!(result, Err(DominoesError::NotInHand(Tile::from((6, 6)))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DominoesError {
+    /// No actions can be taken once the game is over
+    #[error("The game is over")]
+    GameOver,
+    /// The player is not the one whose turn it is
+    #[error("It is not player {0}'s turn")]
+    WrongTurn(u8),
+    /// A player holding a playable tile must play it
+    #[error("A tile cannot be drawn while holding a playable tile")]
+    DrawWithPlayableTile,
+    /// The tile drawn is not the one at the top of the boneyard
+    #[error("{0} is not the next tile in the boneyard")]
+    NotNextTile(Tile),
+    /// There are no tiles to draw
+    #[error("The boneyard is empty")]
+    EmptyBoneyard,
+    /// A player who may draw only one tile has drawn it, and must play it or pass (see `rules::DrawRule::DrawOne`)
+    #[error("A tile has already been drawn this turn")]
+    AlreadyDrawn,
+    /// In a Block game, the tiles left after the deal are never drawn (see `rules::DrawRule::NoDraw`)
+    #[error("Tiles cannot be drawn in a Block game")]
+    NoDrawing,
+    /// The tile played is not held by the player
+    #[error("{0} is not in the player's hand")]
+    NotInHand(Tile),
+    /// The first tile is not played on an end
+    #[error("An end cannot be specified for the first tile")]
+    EndOfFirstTile,
+    /// The opening rule of the variation requires a different first tile (see `rules::Opening`)
+    #[error("{tile} cannot be played first; {required} must be played first")]
+    WrongFirstTile { tile: Tile, required: Tile },
+    /// Every tile after the first must be played on an end
+    #[error("An end must be specified for {0}")]
+    NoEnd(Tile),
+    /// The tile cannot be played on the end, or at all if no end is given
+    #[error("{tile} cannot be played{}", end.map(|end| format!(" on {end}")).unwrap_or_default())]
+    InvalidMove { tile: Tile, end: Option<u8> },
+    /// A tile's ordinal does not stand for a tile, e.g. one received from another program, so its values cannot be found
+    #[error("{0} is not the ordinal of a tile")]
+    NotCanonical(u8),
+    /// A player holding a playable tile must play it
+    #[error("Cannot pass while holding a playable tile")]
+    PassWithPlayableTile,
+    /// A player who cannot play must draw while there are tiles to draw
+    #[error("Cannot pass while the boneyard has tiles")]
+    PassWithTilesToDraw,
+    /// Under the muggins rule, there are no points to claim, e.g. because the last action was not a play that scored, or the
+    /// points have already been claimed (see `Muggins`)
+    #[error("There are no points to claim")]
+    NoPointsToClaim,
+    /// The layout is not valid
+    #[error(transparent)]
+    Layout(#[from] LayoutError),
+}
+
+/// An action of a recorded game that cannot be replayed (see `Replay`)
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Action {index} cannot be replayed: {error}")]
+pub struct ReplayError {
+    /// The index of the action in the history
+    pub index: usize,
+    /// Why the action is not legal
+    #[source]
+    pub error: DominoesError,
+}
+
+/// A line of game notation that cannot be parsed (see `History::from_notation`)
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Line {line}: {message}")]
+pub struct NotationError {
+    /// The number of the line, starting at 1
+    pub line: usize,
+    /// What is wrong with the line
+    pub message: String,
+}
@@ -3,6 +3,7 @@
 //! This module provides the Hand struct, which represents a player's collection of domino tiles during a game. It includes
 //! methods for managing the hand, such as adding and removing tiles, checking for specific tiles, and calculating the hand's score.
 
+use crate::{Action, DominoesError};
 use rules::Tile;
 
 /// A player's hand
@@ -31,6 +32,7 @@ use rules::Tile;
 /// assert_eq!(hand.len(), 2);
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hand {
     /// Vector storing all tiles currently in the hand
     tiles: Vec<Tile>,
@@ -136,7 +138,8 @@ impl Hand {
     /// * `tile` - The tile to remove from the hand
     ///
     /// # Panics
-    /// Panics if the specified tile is not found in the hand
+    /// Panics if the specified tile is not found in the hand. Use `try_remove_tile` for a tile that might not be held, such as one
+    /// played by a remote player.
     ///
     /// # Examples
     /// ```rust
@@ -158,6 +161,34 @@ impl Hand {
         self.tiles.remove(pos);
     }
 
+    /// Removes a tile from the hand, or returns an error if the hand does not hold it
+    ///
+    /// This is the checked version of `remove_tile`. The hand is unchanged if the tile is not found.
+    ///
+    /// # Arguments
+    /// * `tile` - The tile to remove from the hand
+    ///
+    /// # Errors
+    /// Returns `DominoesError::NotInHand` if the tile is not in the hand
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesError, Hand};
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((3, 6)));
+    ///
+    /// assert_eq!(hand.try_remove_tile(&Tile::from((1, 2))), Err(DominoesError::NotInHand(Tile::from((1, 2)))));
+    /// assert_eq!(hand.try_remove_tile(&Tile::from((3, 6))), Ok(()));
+    /// assert!(hand.is_empty());
+    /// ```
+    pub fn try_remove_tile(&mut self, tile: &Tile) -> Result<(), DominoesError> {
+        let pos = self.tiles.iter().position(|x| x == tile).ok_or(DominoesError::NotInHand(*tile))?;
+        self.tiles.remove(pos);
+        Ok(())
+    }
+
     /// Gets the number of tiles in the hand
     ///
     /// # Returns
@@ -212,13 +243,225 @@ impl Hand {
         self.tiles.contains(tile)
     }
 
+    /// Takes back an action made by the player holding this hand
+    ///
+    /// A tile that was played is returned to the hand, and a tile that was drawn is removed from it.
+    ///
+    /// # Arguments
+    /// * `action` - The action to take back
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, Hand};
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// let tile = Tile::from((2, 5));
+    /// hand.add_tile(tile);
+    /// hand.remove_tile(&tile);
+    ///
+    /// hand.undo(&Action::play_unchecked(0, tile, Some(5)));
+    /// assert!(hand.contains(&tile));
+    /// hand.undo(&Action::draw(0, tile));
+    /// assert!(hand.is_empty());
+    /// ```
+    pub fn undo(&mut self, action: &Action) {
+        if let Some((tile, _)) = action.tile_played {
+            self.add_tile(tile);
+        }
+        if let Some(tile) = action.tile_drawn {
+            self.remove_tile(&tile);
+        }
+    }
+
+    /// Makes an action of the player holding this hand again after it was taken back (see `undo`)
+    ///
+    /// A tile that was drawn is added to the hand, and a tile that was played is removed from it.
+    ///
+    /// # Arguments
+    /// * `action` - The action to make again
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, Hand};
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// let tile = Tile::from((2, 5));
+    /// hand.redo(&Action::draw(0, tile));
+    /// assert!(hand.contains(&tile));
+    /// hand.redo(&Action::play_unchecked(0, tile, Some(5)));
+    /// assert!(hand.is_empty());
+    /// ```
+    pub fn redo(&mut self, action: &Action) {
+        if let Some(tile) = action.tile_drawn {
+            self.add_tile(tile);
+        }
+        if let Some((tile, _)) = action.tile_played {
+            self.remove_tile(&tile);
+        }
+    }
+
     /// Returns the score of the hand by adding up the pips on all tiles
     ///
+    /// This is the same as `total_pips`.
+    ///
     /// # Returns
     /// The total score of the hand
     pub fn score(&self) -> u32 {
-        self.tiles.iter().map(|tile| tile.score() as u32).sum()
+        self.total_pips()
+    }
+
+    /// Returns the total number of pips on all tiles in the hand
+    ///
+    /// # Returns
+    /// The sum of the pips on both ends of every tile
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Hand;
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((1, 2)));
+    /// hand.add_tile(Tile::from((6, 6)));
+    /// assert_eq!(hand.total_pips(), 15);
+    /// ```
+    pub fn total_pips(&self) -> u32 {
+        self.tiles.iter().map(|tile| u32::from(tile.score())).sum()
+    }
+
+    /// Returns the number of tiles in the hand with the specified value on either end
+    ///
+    /// A double is counted once.
+    ///
+    /// # Arguments
+    /// * `value` - The number of pips to count
+    ///
+    /// # Returns
+    /// The number of tiles that could be played on an open end with this value
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Hand;
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((3, 3)));
+    /// hand.add_tile(Tile::from((1, 3)));
+    /// hand.add_tile(Tile::from((2, 5)));
+    /// assert_eq!(hand.count_of(3), 2);
+    /// assert_eq!(hand.count_of(4), 0);
+    /// ```
+    pub fn count_of(&self, value: u8) -> usize {
+        self.tiles_matching(value).count()
+    }
+
+    /// Returns the tiles in the hand with the specified value on either end
+    ///
+    /// # Arguments
+    /// * `end` - The number of pips on an open end
+    ///
+    /// # Returns
+    /// An iterator over the tiles that match the end, in the order they are held
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Hand;
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((4, 6)));
+    /// hand.add_tile(Tile::from((0, 1)));
+    /// hand.add_tile(Tile::from((6, 6)));
+    /// let matching: Vec<Tile> = hand.tiles_matching(6).collect();
+    /// assert_eq!(matching, vec![Tile::from((4, 6)), Tile::from((6, 6))]);
+    /// ```
+    pub fn tiles_matching(&self, end: u8) -> impl Iterator<Item = Tile> + '_ {
+        self.tiles.iter().copied().filter(move |tile| {
+            let (a, b) = tile.as_tuple();
+            a == end || b == end
+        })
     }
+
+    /// Returns the tile with the most pips
+    ///
+    /// Ties are broken in favor of the tile that comes last in the canonical order, so the result does not depend on the order
+    /// in which the tiles are held.
+    ///
+    /// # Returns
+    /// The heaviest tile, or `None` if the hand is empty
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Hand;
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// assert_eq!(hand.heaviest_tile(), None);
+    /// hand.add_tile(Tile::from((2, 5)));
+    /// hand.add_tile(Tile::from((1, 1)));
+    /// assert_eq!(hand.heaviest_tile(), Some(Tile::from((2, 5))));
+    /// ```
+    pub fn heaviest_tile(&self) -> Option<Tile> {
+        self.tiles.iter().copied().max_by_key(|tile| (tile.score(), *tile))
+    }
+
+    /// Sorts the tiles in the hand into the canonical order
+    ///
+    /// Tiles are ordered by their ordinals, i.e. by their higher value and then by their lower value: 0|0, 0|1, 1|1, 0|2, 1|2,
+    /// 2|2, ...
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Hand;
+    /// # use rules::Tile;
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((3, 4)));
+    /// hand.add_tile(Tile::from((0, 6)));
+    /// hand.add_tile(Tile::from((1, 1)));
+    /// hand.sort();
+    /// assert_eq!(hand.tiles(), &[Tile::from((1, 1)), Tile::from((3, 4)), Tile::from((0, 6))]);
+    /// ```
+    pub fn sort(&mut self) {
+        self.tiles.sort();
+    }
+}
+
+/// Returns the player with the fewest pips in their hand on the team with the fewest pips, e.g. the winner of a blocked round
+///
+/// Without partners, each player is a team of their own.
+///
+/// # Arguments
+/// * `hands` - The players' IDs and hands
+/// * `team` - Returns the team of a player (see `Configuration::team`)
+///
+/// # Returns
+/// The ID of the player, or `None` if there are no hands or the fewest pips are shared by more than one team
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Hand, lowest_hand};
+/// # use rules::Tile;
+///
+/// let hands = [Hand::from(vec![Tile::from((1, 2))]), Hand::from(vec![Tile::from((0, 1))]), Hand::from(vec![Tile::from((0, 3))])];
+/// let players = || hands.iter().enumerate().map(|(player_id, hand)| (player_id as u8, hand));
+/// assert_eq!(lowest_hand(players(), |player_id| player_id), Some(1));
+/// assert_eq!(lowest_hand(players(), |player_id| player_id % 2), Some(1));
+/// assert_eq!(lowest_hand(players().filter(|&(player_id, _)| player_id != 1), |player_id| player_id), None);
+/// ```
+pub fn lowest_hand<'h>(hands: impl Iterator<Item = (u8, &'h Hand)> + Clone, team: impl Fn(u8) -> u8) -> Option<u8> {
+    let team = &team;
+    let players_of = |team_id| hands.clone().filter(move |&(player_id, _)| team(player_id) == team_id);
+    let team_pips = |team_id| players_of(team_id).map(|(_, hand)| hand.score()).sum::<u32>();
+    let lowest = hands.clone().map(|(player_id, _)| team_pips(team(player_id))).min();
+    let mut lowest_players = hands.clone().filter(|&(player_id, _)| Some(team_pips(team(player_id))) == lowest);
+    let (first, _) = lowest_players.next()?;
+    if lowest_players.any(|(player_id, _)| team(player_id) != team(first)) {
+        return None;
+    }
+    players_of(team(first)).min_by_key(|(_, hand)| hand.score()).map(|(player_id, _)| player_id)
 }
 
 impl Default for Hand {
@@ -227,6 +470,13 @@ impl Default for Hand {
     }
 }
 
+impl From<Vec<Tile>> for Hand {
+    /// Creates a hand holding the tiles, e.g. a hand just dealt (see `Boneyard::deal`)
+    fn from(tiles: Vec<Tile>) -> Self {
+        Self { tiles }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -608,4 +858,78 @@ mod tests {
         assert_eq!(score1, score2);
         assert_eq!(score1, 9);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_hand_total_pips() {
+        let mut hand = Hand::new();
+        assert_eq!(hand.total_pips(), 0);
+        hand.add_tile(Tile::from((0, 5)));
+        hand.add_tile(Tile::from((4, 4)));
+        assert_eq!(hand.total_pips(), 13);
+        assert_eq!(hand.total_pips(), hand.score());
+    }
+
+    #[test]
+    fn test_hand_count_of_and_tiles_matching() {
+        let mut hand = Hand::new();
+        for tile in [(2, 2), (0, 2), (2, 6), (1, 3)] {
+            hand.add_tile(Tile::from(tile));
+        }
+        assert_eq!(hand.count_of(2), 3); // The double is counted once
+        assert_eq!(hand.count_of(3), 1);
+        assert_eq!(hand.count_of(5), 0);
+        assert_eq!(
+            hand.tiles_matching(2).collect::<Vec<_>>(),
+            vec![Tile::from((2, 2)), Tile::from((0, 2)), Tile::from((2, 6))]
+        );
+        assert_eq!(hand.tiles_matching(5).count(), 0);
+    }
+
+    #[test]
+    fn test_hand_heaviest_tile() {
+        let mut hand = Hand::new();
+        assert_eq!(hand.heaviest_tile(), None);
+
+        // 1|5 and 3|3 have the same number of pips, and 1|5 comes last in the canonical order
+        hand.add_tile(Tile::from((3, 3)));
+        hand.add_tile(Tile::from((1, 5)));
+        hand.add_tile(Tile::from((0, 4)));
+        assert_eq!(hand.heaviest_tile(), Some(Tile::from((1, 5))));
+
+        let mut reversed = Hand::new();
+        for tile in hand.tiles().iter().rev() {
+            reversed.add_tile(*tile);
+        }
+        assert_eq!(reversed.heaviest_tile(), hand.heaviest_tile());
+    }
+
+    #[test]
+    fn test_hand_sort() {
+        let mut hand = Hand::new();
+        for tile in [(5, 6), (0, 0), (2, 3), (1, 1)] {
+            hand.add_tile(Tile::from(tile));
+        }
+        hand.sort();
+        let expected: Vec<Tile> = [(0, 0), (1, 1), (2, 3), (5, 6)].into_iter().map(Tile::from).collect();
+        assert_eq!(hand.tiles(), expected.as_slice());
+        assert_eq!(hand.total_pips(), 18);
+    }
+
+    #[test]
+    fn test_hand_try_remove_tile() {
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((1, 2)));
+        hand.add_tile(Tile::from((1, 2)));
+
+        // A tile that is not held leaves the hand unchanged
+        assert_eq!(hand.try_remove_tile(&Tile::from((3, 4))), Err(DominoesError::NotInHand(Tile::from((3, 4)))));
+        assert_eq!(hand.len(), 2);
+
+        // Only one copy is removed at a time
+        assert_eq!(hand.try_remove_tile(&Tile::from((1, 2))), Ok(()));
+        assert_eq!(hand.len(), 1);
+        assert_eq!(hand.try_remove_tile(&Tile::from((1, 2))), Ok(()));
+        assert!(Hand::new().try_remove_tile(&Tile::from((1, 2))).is_err());
+        assert!(hand.is_empty());
+    }
+}
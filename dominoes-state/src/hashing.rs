@@ -0,0 +1,121 @@
+//! Hash maps with faster hashers
+//!
+//! The standard library hashes keys with SipHash, which resists collision attacks but is slow for the small keys used by the
+//! computer players. This module provides hash maps and sets whose hasher is selected by a feature:
+//!
+//! | Feature  | Hasher                                                     |
+//! | -------- | ---------------------------------------------------------- |
+//! | `fxhash` | FxHash, from [rustc-hash](https://docs.rs/rustc-hash)      |
+//! | `ahash`  | aHash, from [ahash](https://docs.rs/ahash)                 |
+//! | (none)   | SipHash, from the standard library                         |
+//!
+//! If both features are enabled, `fxhash` is used.
+//!
+//! Keys that are Zobrist hashes (see `ZHash`) are already uniformly distributed, so they do not need to be hashed again.
+//! `ZHashMap` and `ZHashSet` use them as their own hashes, whatever the features. They are meant for transposition tables and
+//! other caches indexed by position.
+//!
+//! # Example
+//! ```rust
+//! # use dominoes_state::{FastHashMap, ZHash, ZHashMap};
+//!
+//! let mut counts: FastHashMap<u8, u32> = FastHashMap::default();
+//! *counts.entry(6).or_default() += 1;
+//! assert_eq!(counts[&6], 1);
+//!
+//! let mut table: ZHashMap<f32> = ZHashMap::default();
+//! table.insert(ZHash::from(0x1234_5678_9abc_def0), 0.5);
+//! assert_eq!(table.get(&ZHash::from(0x1234_5678_9abc_def0)), Some(&0.5));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+use crate::ZHash;
+
+/// The hasher used by `FastHashMap` and `FastHashSet`, selected by the `fxhash` and `ahash` features
+#[cfg(feature = "fxhash")]
+pub type FastBuildHasher = rustc_hash::FxBuildHasher;
+
+/// The hasher used by `FastHashMap` and `FastHashSet`, selected by the `fxhash` and `ahash` features
+#[cfg(all(feature = "ahash", not(feature = "fxhash")))]
+pub type FastBuildHasher = ahash::RandomState;
+
+/// The hasher used by `FastHashMap` and `FastHashSet`, selected by the `fxhash` and `ahash` features
+#[cfg(not(any(feature = "fxhash", feature = "ahash")))]
+pub type FastBuildHasher = std::hash::RandomState;
+
+/// A `HashMap` using the hasher selected by the features. Create one with `FastHashMap::default()`.
+pub type FastHashMap<K, V> = HashMap<K, V, FastBuildHasher>;
+
+/// A `HashSet` using the hasher selected by the features. Create one with `FastHashSet::default()`.
+pub type FastHashSet<T> = HashSet<T, FastBuildHasher>;
+
+/// A `HashMap` indexed by Zobrist hashes, which uses the keys as their own hashes. Create one with `ZHashMap::default()`.
+pub type ZHashMap<V> = HashMap<ZHash, V, BuildHasherDefault<ZHashHasher>>;
+
+/// A `HashSet` of Zobrist hashes, which uses the values as their own hashes. Create one with `ZHashSet::default()`.
+pub type ZHashSet = HashSet<ZHash, BuildHasherDefault<ZHashHasher>>;
+
+/// A hasher for Zobrist hashes, which returns the hash unchanged
+///
+/// Zobrist hashes are uniformly distributed, so hashing them again only costs time. This hasher is meant only for keys that hash
+/// a single `u64`, such as `ZHash`. Other keys work, but their hashes are not well distributed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZHashHasher {
+    hash: u64,
+}
+
+impl Hasher for ZHashHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(u64::from(byte));
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.hash = self.hash.rotate_left(5) ^ value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn test_zhash_hasher_returns_the_hash() {
+        let build = BuildHasherDefault::<ZHashHasher>::default();
+        let hash = ZHash::from(0xdead_beef_0123_4567);
+        assert_eq!(build.hash_one(hash), 0xdead_beef_0123_4567);
+    }
+
+    #[test]
+    fn test_zhash_hasher_combines_writes() {
+        let mut a = ZHashHasher::default();
+        a.write_u64(1);
+        a.write_u64(2);
+        let mut b = ZHashHasher::default();
+        b.write_u64(2);
+        b.write_u64(1);
+        assert_ne!(a.finish(), b.finish());
+    }
+
+    #[test]
+    fn test_fast_maps() {
+        let mut map: FastHashMap<(u8, u8), u32> = FastHashMap::default();
+        map.insert((3, 4), 7);
+        map.insert((4, 3), 8);
+        assert_eq!(map.get(&(3, 4)), Some(&7));
+        assert_eq!(map.len(), 2);
+
+        let mut set: ZHashSet = ZHashSet::default();
+        assert!(set.insert(ZHash::from(5)));
+        assert!(!set.insert(ZHash::from(5)));
+        assert!(set.contains(&ZHash::from(5)));
+    }
+}
@@ -2,10 +2,24 @@
 
 pub mod action;
 pub mod dominoes_state;
+pub mod error;
 pub mod hand;
+pub mod hashing;
+pub mod moves;
+pub mod notation;
+pub mod replay;
+pub mod scoring;
+pub mod stats;
+pub mod trains;
 pub mod zhash;
 
 pub use crate::action::*;
 pub use crate::dominoes_state::*;
+pub use crate::error::*;
 pub use crate::hand::*;
+pub use crate::hashing::*;
+pub use crate::replay::*;
+pub use crate::scoring::*;
+pub use crate::stats::*;
+pub use crate::trains::*;
 pub use crate::zhash::*;
@@ -0,0 +1,169 @@
+//! Legal action generation
+//!
+//! This module enumerates the actions that the player whose turn it is may take with a given hand. A player with a playable tile
+//! must play it. Otherwise, the player must draw, or pass if the boneyard is empty. The engine, the computer players, and the user
+//! interfaces all use this instead of checking the tiles themselves.
+
+use crate::{Action, DominoesState, Hand};
+use rules::Tile;
+
+impl DominoesState {
+    /// Returns the legal actions of the player whose turn it is
+    ///
+    /// A play is listed for every tile in the hand and every open end that it matches. The first tile must also be allowed by the
+    /// opening rule of the variation (see `opening`), and in Chicken Foot, only a double that has not received all of its tiles
+    /// may be played on (see `can_play_tile`). If no tile can be played, the only legal action is to draw the next tile in the
+    /// boneyard, or to pass if the boneyard is empty or the player may not draw again (see `can_draw`). The plays are listed in
+    /// the order of the tiles in the hand.
+    ///
+    /// An action names an end value rather than a node of the layout. If several nodes have the same open end, the tile is
+    /// attached to the first of them (see `play_tile`). Since the fingerprint depends only on the tiles and the open end counts,
+    /// the resulting positions are equivalent, and only one action is listed.
+    ///
+    /// # Arguments
+    /// * `hand` - The hand of the player whose turn it is
+    ///
+    /// # Returns
+    /// The legal actions. The list is empty only if the game is over.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesState, Hand};
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    /// state.whose_turn = 1;
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((5, 6)));
+    /// hand.add_tile(Tile::from((1, 2)));
+    /// assert_eq!(state.legal_actions(&hand), vec![Action::play_unchecked(1, Tile::from((5, 6)), Some(6))]);
+    /// ```
+    pub fn legal_actions(&self, hand: &Hand) -> Vec<Action> {
+        if self.game_is_over {
+            return Vec::new();
+        }
+
+        let player_id = self.whose_turn;
+        let mut actions: Vec<Action> = hand
+            .tiles()
+            .iter()
+            .flat_map(|&tile| self.playable_ends(tile, hand.tiles()).map(move |end| Action::play_unchecked(player_id, tile, end)))
+            .collect();
+
+        if actions.is_empty() {
+            actions.push(match self.boneyard.peek() {
+                Some(&tile) if self.can_draw() => Action::draw(player_id, tile),
+                _ => Action::pass(player_id),
+            });
+        }
+        actions
+    }
+
+    // Returns the ends on which a tile can be played, or `None` for the first tile. The opening rule may depend on the rest of the
+    // hand.
+    fn playable_ends(&self, tile: Tile, hand: &[Tile]) -> impl Iterator<Item = Option<u8>> + '_ {
+        let ends = if self.layout.is_empty() {
+            if self.can_play_tile(&tile, None) && self.opening().allows(tile, hand) { vec![None] } else { vec![] }
+        } else {
+            let (a, b) = tile.as_tuple();
+            let ends = if a == b { vec![a] } else { vec![a, b] };
+            ends.into_iter().filter(|&end| self.can_play_tile(&tile, Some(end))).map(Some).collect()
+        };
+        ends.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Boneyard, Configuration, Layout, Variation};
+
+    // Returns a hand holding the tiles
+    fn hand(tiles: &[(u8, u8)]) -> Hand {
+        let mut hand = Hand::new();
+        for &tile in tiles {
+            hand.add_tile(Tile::from(tile));
+        }
+        hand
+    }
+
+    #[test]
+    fn test_legal_actions_first_tile() {
+        // Traditional requires the highest double in the hand
+        let state = DominoesState::new(&Configuration::default());
+        let actions = state.legal_actions(&hand(&[(1, 2), (3, 3), (5, 5)]));
+        assert_eq!(actions, vec![Action::play_unchecked(0, Tile::from((5, 5)), None)]);
+
+        // All Fives allows any tile
+        let state = DominoesState::new(&Configuration::new(2, Variation::AllFives, 6, 7));
+        let actions = state.legal_actions(&hand(&[(1, 2), (3, 3)]));
+        assert_eq!(
+            actions,
+            vec![Action::play_unchecked(0, Tile::from((1, 2)), None), Action::play_unchecked(0, Tile::from((3, 3)), None)]
+        );
+    }
+
+    #[test]
+    fn test_legal_actions_every_end() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((3, 3)), None);
+        state.play_tile_unchecked(Tile::from((3, 5)), Some(3));
+
+        // The three-five matches both open ends, and the double five only one
+        let actions = state.legal_actions(&hand(&[(3, 5), (5, 5), (0, 1)]));
+        assert_eq!(
+            actions,
+            vec![
+                Action::play_unchecked(0, Tile::from((3, 5)), Some(3)),
+                Action::play_unchecked(0, Tile::from((3, 5)), Some(5)),
+                Action::play_unchecked(0, Tile::from((5, 5)), Some(5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legal_actions_chicken_foot() {
+        let configuration = Configuration::new(2, Variation::ChickenFoot, 9, 7);
+        let mut state = DominoesState::new(&configuration);
+        let hand = hand(&[(0, 1), (0, 9), (5, 7)]);
+
+        // Six tiles must be played around the first double before anything else
+        state.play_tile_unchecked(Tile::from((9, 9)), None);
+        for value in 0..5 {
+            state.play_tile_unchecked(Tile::from((value, 9)), Some(9));
+            assert_eq!(state.legal_actions(&hand), vec![Action::play_unchecked(0, Tile::from((0, 9)), Some(9))]);
+        }
+        state.play_tile_unchecked(Tile::from((5, 9)), Some(9));
+        assert_eq!(state.legal_actions(&hand).len(), 4);
+
+        // A double played later must receive three tiles, even though another five is open
+        state.play_tile_unchecked(Tile::from((5, 5)), Some(5));
+        state.play_tile_unchecked(Tile::from((5, 6)), Some(5));
+        assert_eq!(state.legal_actions(&hand), vec![Action::play_unchecked(0, Tile::from((5, 7)), Some(5))]);
+        assert!(!state.can_play_tile(&Tile::from((0, 1)), Some(0)));
+        state.play_tile_unchecked(Tile::from((5, 8)), Some(5));
+        state.play_tile_unchecked(Tile::from((4, 5)), Some(5));
+        assert!(state.can_play_tile(&Tile::from((0, 1)), Some(0)));
+        assert_eq!(state.layout.open_count(5), 0);
+    }
+
+    #[test]
+    fn test_legal_actions_draw_or_pass() {
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        let blocked = hand(&[(0, 1)]);
+
+        let state = DominoesState::with_position(&configuration, layout.clone(), Boneyard::with(vec![Tile::from((2, 4))]));
+        assert_eq!(state.legal_actions(&blocked), vec![Action::draw(0, Tile::from((2, 4)))]);
+
+        let mut state = DominoesState::with_position(&configuration, layout, Boneyard::with(vec![]));
+        assert_eq!(state.legal_actions(&blocked), vec![Action::pass(0)]);
+
+        state.mark_game_over(None);
+        assert!(state.legal_actions(&blocked).is_empty());
+    }
+}
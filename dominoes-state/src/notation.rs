@@ -0,0 +1,183 @@
+//! Game notation
+//!
+//! This module writes and reads the actions of a game as text, one action per line, so that games can be shared, archived, and
+//! imported, much like PGN for chess. Each line names the player, followed by what the player did:
+//!
+//! ```text
+//! # Alice opens, and Bob draws until a tile can be played
+//! P0: p 6|6
+//! P1: d 1|2
+//! P1: d 3|6 p 3|6@6
+//! P0: p 6|5@6
+//! P1: pass
+//! ```
+//!
+//! - `d <tile>`: The tile drawn from the boneyard.
+//! - `p <tile>`: The first tile played in the layout.
+//! - `p <tile>@<end>`: A tile played on an open end with that value.
+//! - `pass`: The player passed.
+//!
+//! A tile may be drawn and played in the same action, in which case the draw comes first. Tiles are written as `a|b`, in either
+//! order. Blank lines and lines starting with `#` are ignored. The fingerprints of the states are not written.
+
+use std::fmt::Write;
+
+use rules::{ParseTileError, Tile};
+
+use crate::{Action, History, NotationError};
+
+impl History {
+    /// Returns the actions in game notation (see `notation`), one line per action
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, History};
+    /// # use rules::Tile;
+    ///
+    /// let mut history = History::new();
+    /// history.add_action(Action::play_unchecked(0, Tile::from((6, 6)), None));
+    /// history.add_action(Action::draw(1, Tile::from((3, 4))));
+    /// history.add_action(Action::pass(1));
+    /// assert_eq!(history.to_notation(), "P0: p 6|6\nP1: d 3|4\nP1: pass\n");
+    /// ```
+    pub fn to_notation(&self) -> String {
+        let mut notation = String::new();
+        for action in self.get_actions() {
+            write!(notation, "P{}:", action.player_id).expect("Writing to a string cannot fail");
+            if let Some(tile) = action.tile_drawn {
+                write!(notation, " d {tile}").expect("Writing to a string cannot fail");
+            }
+            match action.tile_played {
+                Some((tile, Some(end))) => write!(notation, " p {tile}@{end}").expect("Writing to a string cannot fail"),
+                Some((tile, None)) => write!(notation, " p {tile}").expect("Writing to a string cannot fail"),
+                None if action.tile_drawn.is_none() => notation.push_str(" pass"),
+                None => {}
+            }
+            notation.push('\n');
+        }
+        notation
+    }
+
+    /// Parses actions written in game notation (see `notation`)
+    ///
+    /// The actions are not checked against the rules; see `Replay` for that.
+    ///
+    /// # Arguments
+    /// * `notation` - The actions, one per line
+    ///
+    /// # Returns
+    /// The history, or the first line that cannot be parsed
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, History};
+    /// # use rules::Tile;
+    ///
+    /// let history = History::from_notation("P0: p 6|6\nP1: d 2|3 p 3|6@6\n").unwrap();
+    /// assert_eq!(history.get_actions()[1], Action::new(1, Some(Tile::from((2, 3))), Some((Tile::from((3, 6)), Some(6)))));
+    /// assert!(History::from_notation("P0: play 6|6").is_err());
+    /// ```
+    pub fn from_notation(notation: &str) -> Result<Self, NotationError> {
+        let mut history = History::new();
+        for (index, line) in notation.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let action = parse_action(line).map_err(|message| NotationError { line: index + 1, message })?;
+            history.add_action(action);
+        }
+        Ok(history)
+    }
+}
+
+// Parses the action on one line
+fn parse_action(line: &str) -> Result<Action, String> {
+    let (player, rest) = line.split_once(':').ok_or_else(|| "Expected 'P<player>: <action>'".to_string())?;
+    let player_id = player
+        .trim()
+        .strip_prefix('P')
+        .and_then(|id| id.parse::<u8>().ok())
+        .ok_or_else(|| format!("Expected a player such as P0, found '{}'", player.trim()))?;
+
+    let mut tokens = rest.split_whitespace().peekable();
+    if tokens.peek() == Some(&"pass") {
+        tokens.next();
+        return match tokens.next() {
+            None => Ok(Action::pass(player_id)),
+            Some(token) => Err(format!("Unexpected '{token}' after 'pass'")),
+        };
+    }
+
+    let mut tile_drawn = None;
+    if tokens.peek() == Some(&"d") {
+        tokens.next();
+        tile_drawn = Some(parse_tile(tokens.next())?);
+    }
+    let mut tile_played = None;
+    if tokens.peek() == Some(&"p") {
+        tokens.next();
+        let token = tokens.next().ok_or_else(|| "Expected a tile after 'p'".to_string())?;
+        tile_played = Some(match token.split_once('@') {
+            Some((tile, end)) => {
+                let end = end.parse::<u8>().map_err(|_| format!("Expected an end value, found '{end}'"))?;
+                (parse_tile(Some(tile))?, Some(end))
+            }
+            None => (parse_tile(Some(token))?, None),
+        });
+    }
+    if let Some(token) = tokens.next() {
+        return Err(format!("Expected 'd', 'p', or 'pass', found '{token}'"));
+    }
+    if tile_drawn.is_none() && tile_played.is_none() {
+        return Err("Expected an action".to_string());
+    }
+    Ok(Action::new(player_id, tile_drawn, tile_played))
+}
+
+// Parses a tile written as a|b
+fn parse_tile(token: Option<&str>) -> Result<Tile, String> {
+    let token = token.ok_or_else(|| "Expected a tile".to_string())?;
+    token.parse().map_err(|error: ParseTileError| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notation_round_trip() {
+        let mut history = History::new();
+        history.add_action(Action::play_unchecked(0, Tile::from((6, 6)), None));
+        history.add_action(Action::draw(1, Tile::from((1, 2))));
+        history.add_action(Action::new(1, Some(Tile::from((3, 6))), Some((Tile::from((3, 6)), Some(6)))));
+        history.add_action(Action::play_unchecked(0, Tile::from((5, 6)), Some(6)));
+        history.add_action(Action::pass(1));
+
+        let notation = history.to_notation();
+        assert_eq!(notation, "P0: p 6|6\nP1: d 1|2\nP1: d 3|6 p 3|6@6\nP0: p 5|6@6\nP1: pass\n");
+        let parsed = History::from_notation(&notation).unwrap();
+        assert_eq!(parsed.get_actions(), history.get_actions());
+    }
+
+    #[test]
+    fn test_from_notation_comments_and_order() {
+        let notation = "# A comment\n\n  P2: p 4|1@4  \nP3:pass\n";
+        let history = History::from_notation(notation).unwrap();
+        assert_eq!(history.get_actions(), &vec![Action::play_unchecked(2, Tile::from((1, 4)), Some(4)), Action::pass(3)]);
+    }
+
+    #[test]
+    fn test_from_notation_errors() {
+        let error = |notation: &str| History::from_notation(notation).unwrap_err();
+        assert_eq!(error("P0: p 6|6\nP1 d 1|2").line, 2);
+        assert!(error("P1 d 1|2").message.contains("Expected 'P<player>: <action>'"));
+        assert!(error("Q1: pass").message.contains("Expected a player"));
+        assert!(error("P0:").message.contains("Expected an action"));
+        assert!(error("P0: pass now").message.contains("Unexpected 'now'"));
+        assert!(error("P0: d").message.contains("Expected a tile"));
+        assert!(error("P0: d 1|22").message.contains("found '1|22'"));
+        assert!(error("P0: p 1|2@x").message.contains("Expected an end value"));
+        assert!(error("P0: p 1|2 d 3|4").message.contains("found 'd'"));
+    }
+}
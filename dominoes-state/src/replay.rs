@@ -0,0 +1,305 @@
+//! Replays of recorded games
+//!
+//! This module reconstructs a game from its history so that it can be reviewed move by move, e.g. by analysis tools or after a
+//! game is over. A history records the tiles drawn and played but not the hands that were dealt, so the positions are rebuilt
+//! from what is public: the layout, the number of tiles in the boneyard, and the number of tiles held by each player.
+
+use hidden_game_player::State;
+use rules::{Boneyard, Configuration, DrawRule, Layout, Tile};
+
+use crate::{Action, DominoesError, DominoesState, FastHashMap, FastHashSet, History, ReplayError};
+
+/// A recorded game that can be stepped through forward and backward
+///
+/// Every action is checked when the replay is created, as far as it can be without knowing the hands: it must be the player's
+/// turn, a tile must be drawn only while the boneyard has tiles, a tile must be played only once and on an end that it matches,
+/// and a player may pass only when the boneyard is empty, or in Mexican Train, after drawing a tile. A player who plays their last
+/// tile wins.
+///
+/// The boneyard of each position holds the tiles that are drawn later in the game, in order, followed by tiles that are never
+/// seen, so it has the right number of tiles, but the tiles that are never seen are not necessarily the ones that were there.
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, History, Replay};
+/// # use rules::{Configuration, Tile};
+///
+/// let mut history = History::new();
+/// history.add_action(Action::play_unchecked(0, Tile::from((6, 6)), None));
+/// history.add_action(Action::play_unchecked(1, Tile::from((5, 6)), Some(6)));
+///
+/// let mut replay = Replay::new(&Configuration::default(), &history).unwrap();
+/// assert_eq!(replay.position(), 0);
+/// assert_eq!(replay.step_forward(), Some(&Action::play_unchecked(0, Tile::from((6, 6)), None)));
+/// replay.step_forward();
+/// assert_eq!(replay.state().layout.nodes.len(), 2);
+/// replay.step_backward();
+/// assert_eq!(replay.state().layout.nodes.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// The actions of the game
+    actions: Vec<Action>,
+    /// The state before the first action and after each action
+    states: Vec<DominoesState>,
+    /// The number of actions that have been replayed
+    position: usize,
+}
+
+impl Replay {
+    /// Creates a replay of a recorded game, positioned at the start of the game
+    ///
+    /// # Arguments
+    /// * `configuration` - The configuration of the recorded game
+    /// * `history` - The actions of the recorded game
+    ///
+    /// # Returns
+    /// The replay, or an error naming the first action that is not legal
+    pub fn new(configuration: &Configuration, history: &History) -> Result<Self, ReplayError> {
+        let actions = history.get_actions().clone();
+        let boneyard = starting_boneyard(configuration, &actions);
+        let mut state = DominoesState::with_position(configuration, Layout::new(configuration), boneyard);
+        state.whose_turn = history.first_player().unwrap_or(0);
+
+        let mut held = vec![configuration.starting_hand_size(); configuration.num_players()];
+        let mut drawn_by: FastHashMap<Tile, u8> = FastHashMap::default();
+        let mut played: FastHashSet<Tile> = FastHashSet::default();
+        let mut states = Vec::with_capacity(actions.len() + 1);
+        for (index, action) in actions.iter().enumerate() {
+            check(&state, action, &held, &drawn_by, &played).map_err(|error| ReplayError { index, error })?;
+
+            let player_id = action.player_id as usize;
+            let mut next = state.apply(action);
+            if let Some(tile) = action.tile_drawn {
+                drawn_by.insert(tile, action.player_id);
+                held[player_id] += 1;
+            }
+            if let Some((tile, _)) = action.tile_played {
+                played.insert(tile);
+                held[player_id] -= 1;
+                if held[player_id] == 0 {
+                    next.mark_game_over(Some(action.player_id));
+                }
+            }
+            states.push(std::mem::replace(&mut state, next));
+        }
+        states.push(state);
+
+        Ok(Self { actions, states, position: 0 })
+    }
+
+    /// Returns the state after the actions that have been replayed
+    pub fn state(&self) -> &DominoesState {
+        &self.states[self.position]
+    }
+
+    /// Returns the number of actions that have been replayed
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of actions in the game
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Returns true if the game has no actions
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Returns the actions of the game
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Replays the next action
+    ///
+    /// # Returns
+    /// The action, or `None` if every action has been replayed
+    pub fn step_forward(&mut self) -> Option<&Action> {
+        let action = self.actions.get(self.position)?;
+        self.position += 1;
+        Some(action)
+    }
+
+    /// Takes back the last action replayed
+    ///
+    /// # Returns
+    /// The action, or `None` if the replay is at the start of the game
+    pub fn step_backward(&mut self) -> Option<&Action> {
+        self.position = self.position.checked_sub(1)?;
+        Some(&self.actions[self.position])
+    }
+
+    /// Moves to the position after a number of actions
+    ///
+    /// # Arguments
+    /// * `position` - The number of actions replayed. It is limited to the number of actions in the game.
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.actions.len());
+    }
+}
+
+// Returns the boneyard after the deal: the tiles drawn in the game, in order, followed by tiles that are never drawn or played
+fn starting_boneyard(configuration: &Configuration, actions: &[Action]) -> Boneyard {
+    let count = configuration.set_size().saturating_sub(configuration.num_players() * configuration.starting_hand_size());
+    let mut tiles: Vec<Tile> = actions.iter().filter_map(|action| action.tile_drawn).collect();
+    let played = actions.iter().filter_map(|action| action.tile_played.map(|(tile, _)| tile));
+    let seen: FastHashSet<Tile> = played.chain(tiles.iter().copied()).collect();
+    let unseen = configuration.all_tiles().iter().filter(|tile| !seen.contains(tile));
+    tiles.extend(unseen.take(count.saturating_sub(tiles.len())));
+    tiles.truncate(count);
+    Boneyard::with(tiles).with_reserve(configuration.reserve())
+}
+
+// Checks an action against what is known about the game without the hands
+fn check(
+    state: &DominoesState,
+    action: &Action,
+    held: &[usize],
+    drawn_by: &FastHashMap<Tile, u8>,
+    played: &FastHashSet<Tile>,
+) -> Result<(), DominoesError> {
+    if state.game_is_over {
+        return Err(DominoesError::GameOver);
+    }
+    if action.player_id != state.whose_turn {
+        return Err(DominoesError::WrongTurn(action.player_id));
+    }
+
+    if let Some(tile) = action.tile_drawn {
+        match state.draw_rule() {
+            DrawRule::NoDraw => return Err(DominoesError::NoDrawing),
+            DrawRule::DrawOne if state.has_drawn() => return Err(DominoesError::AlreadyDrawn),
+            _ => {}
+        }
+        match state.boneyard.peek() {
+            Some(next) if *next == tile && !played.contains(&tile) && !drawn_by.contains_key(&tile) => {}
+            Some(_) => return Err(DominoesError::NotNextTile(tile)),
+            None => return Err(DominoesError::EmptyBoneyard),
+        }
+    }
+
+    if let Some((tile, end)) = action.tile_played {
+        let drawn_by_other = drawn_by.get(&tile).is_some_and(|&player_id| player_id != action.player_id);
+        if played.contains(&tile) || drawn_by_other || held[action.player_id as usize] == 0 {
+            return Err(DominoesError::NotInHand(tile));
+        }
+        if state.layout.is_empty() {
+            if end.is_some() {
+                return Err(DominoesError::EndOfFirstTile);
+            }
+        } else if end.is_none() {
+            return Err(DominoesError::NoEnd(tile));
+        }
+        if !state.can_play_tile(&tile, end) {
+            return Err(DominoesError::InvalidMove { tile, end });
+        }
+    }
+
+    if action.is_pass() && state.can_draw() {
+        return Err(DominoesError::PassWithTilesToDraw);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::Variation;
+
+    // Returns a history of the actions
+    fn history(actions: &[Action]) -> History {
+        let mut history = History::new();
+        for action in actions {
+            history.add_action(action.clone());
+        }
+        history
+    }
+
+    #[test]
+    fn test_replay_steps() {
+        let configuration = Configuration::default();
+        let actions = [
+            Action::play_unchecked(0, Tile::from((6, 6)), None),
+            Action::draw(1, Tile::from((0, 1))),
+            Action::play_unchecked(1, Tile::from((1, 6)), Some(6)),
+            Action::play_unchecked(0, Tile::from((1, 1)), Some(1)),
+        ];
+        let mut replay = Replay::new(&configuration, &history(&actions)).unwrap();
+        assert_eq!(replay.len(), 4);
+        assert_eq!(replay.state().boneyard.count(), 14);
+        assert_eq!(replay.state().boneyard.peek(), Some(&Tile::from((0, 1))));
+
+        while replay.step_forward().is_some() {}
+        assert_eq!(replay.position(), 4);
+        assert_eq!(replay.state().layout.nodes.len(), 3);
+        assert_eq!(replay.state().boneyard.count(), 13);
+        assert_eq!(replay.state().whose_turn, 1);
+
+        assert_eq!(replay.step_backward(), Some(&actions[3]));
+        assert_eq!(replay.state().whose_turn, 0);
+        replay.seek(1);
+        assert_eq!(replay.state().layout.nodes.len(), 1);
+        assert_eq!(replay.state().boneyard.count(), 14);
+        replay.seek(100);
+        assert_eq!(replay.position(), 4);
+        replay.seek(0);
+        assert_eq!(replay.step_backward(), None);
+    }
+
+    #[test]
+    fn test_replay_last_tile_wins() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 2);
+        let mut actions = vec![
+            Action::play_unchecked(0, Tile::from((5, 5)), None),
+            Action::play_unchecked(1, Tile::from((0, 5)), Some(5)),
+            Action::play_unchecked(0, Tile::from((0, 1)), Some(0)),
+        ];
+        let mut replay = Replay::new(&configuration, &history(&actions)).unwrap();
+        replay.seek(2);
+        assert!(!replay.state().is_terminal());
+        replay.seek(3);
+        assert!(replay.state().is_terminal());
+        assert_eq!(replay.state().winner, Some(0));
+
+        // Nothing can be done once the game is over
+        actions.push(Action::pass(1));
+        let error = Replay::new(&configuration, &history(&actions)).unwrap_err();
+        assert_eq!(error, ReplayError { index: 3, error: DominoesError::GameOver });
+    }
+
+    #[test]
+    fn test_replay_rejects_illegal_actions() {
+        let configuration = Configuration::default();
+        let opening = Action::play_unchecked(0, Tile::from((6, 6)), None);
+        let cases = [
+            (Action::play_unchecked(0, Tile::from((5, 6)), Some(6)), DominoesError::WrongTurn(0)),
+            (Action::play_unchecked(1, Tile::from((6, 6)), Some(6)), DominoesError::NotInHand(Tile::from((6, 6)))),
+            (
+                Action::play_unchecked(1, Tile::from((1, 2)), Some(1)),
+                DominoesError::InvalidMove { tile: Tile::from((1, 2)), end: Some(1) },
+            ),
+            (Action::play_unchecked(1, Tile::from((5, 6)), None), DominoesError::NoEnd(Tile::from((5, 6)))),
+            (Action::pass(1), DominoesError::PassWithTilesToDraw),
+        ];
+        for (action, error) in cases {
+            let result = Replay::new(&configuration, &history(&[opening.clone(), action]));
+            assert_eq!(result.unwrap_err(), ReplayError { index: 1, error });
+        }
+
+        let result = Replay::new(&configuration, &history(&[Action::play_unchecked(0, Tile::from((6, 6)), Some(6))]));
+        assert_eq!(result.unwrap_err().error, DominoesError::EndOfFirstTile);
+
+        // A tile drawn by one player cannot be played by another
+        let actions = [
+            opening,
+            Action::draw(1, Tile::from((0, 6))),
+            Action::play_unchecked(1, Tile::from((2, 6)), Some(6)),
+            Action::play_unchecked(0, Tile::from((0, 6)), Some(6)),
+        ];
+        let result = Replay::new(&configuration, &history(&actions));
+        assert_eq!(result.unwrap_err(), ReplayError { index: 3, error: DominoesError::NotInHand(Tile::from((0, 6))) });
+    }
+}
@@ -0,0 +1,218 @@
+//! Scoring during play
+//!
+//! In some variations, a player scores points while playing, as well as at the end of the round. A `Scorer` computes the points
+//! scored by a play from the layout after the tile is placed. `DominoesState` uses the scorer of its variation (see `scorer`)
+//! to keep a running score for each player. Under the muggins rule, the points must also be claimed (see `Muggins`).
+
+use rules::{Layout, Variation};
+
+/// Computes the points scored by playing a tile
+pub trait Scorer {
+    /// Returns the points scored by the player who just played a tile
+    ///
+    /// # Arguments
+    /// * `layout` - The layout after the tile was placed
+    fn score(&self, layout: &Layout) -> u32;
+}
+
+/// Scores the plays of All Fives
+///
+/// After each play, the values of the open ends of the layout are added up. If the sum is a multiple of five, the player who
+/// played the tile scores the sum. A double at the end of a line counts both of its halves. Once tiles are played on more than one
+/// of its arms, as on both sides of the spinner, its other arms do not count until tiles are played on them.
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{AllFivesScorer, Scorer};
+/// # use rules::{Configuration, Layout, Tile, Variation};
+///
+/// let config = Configuration::new(2, Variation::AllFives, 6, 7);
+/// let mut layout = Layout::new(&config);
+/// layout.attach_unchecked(Tile::from((5, 5)), None);
+/// assert_eq!(AllFivesScorer.score(&layout), 10);
+/// layout.attach_unchecked(Tile::from((3, 5)), Some(0));
+/// assert_eq!(AllFivesScorer.score(&layout), 0); // 10 + 3 = 13
+/// layout.attach_unchecked(Tile::from((2, 5)), Some(0));
+/// assert_eq!(AllFivesScorer.score(&layout), 5); // 3 + 2 = 5
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllFivesScorer;
+
+impl AllFivesScorer {
+    /// Returns the sum of the values of the open ends of a layout, counting doubles as described above
+    pub fn open_end_sum(layout: &Layout) -> u32 {
+        layout
+            .open
+            .iter_all()
+            .map(|(&index, values)| {
+                let node = &layout.nodes[index];
+                let arms_used = node.children.len() + usize::from(node.parent.is_some());
+                match node.tile.is_double() {
+                    true if arms_used <= 1 => 2 * u32::from(node.tile.as_tuple().0),
+                    true => 0,
+                    false => values.iter().map(|&value| u32::from(value)).sum(),
+                }
+            })
+            .sum()
+    }
+}
+
+impl Scorer for AllFivesScorer {
+    fn score(&self, layout: &Layout) -> u32 {
+        let sum = Self::open_end_sum(layout);
+        if sum.is_multiple_of(5) { sum } else { 0 }
+    }
+}
+
+/// Returns the scorer for the plays of a variation, or `None` if plays do not score in the variation
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::scorer;
+/// # use rules::Variation;
+///
+/// assert!(scorer(Variation::AllFives).is_some());
+/// assert!(scorer(Variation::Traditional).is_none());
+/// ```
+pub fn scorer(variation: Variation) -> Option<&'static dyn Scorer> {
+    match variation {
+        Variation::AllFives => Some(&AllFivesScorer),
+        _ => None,
+    }
+}
+
+/// The points of the plays under the muggins rule
+///
+/// Under the muggins rule (see `Configuration::muggins`), the points scored by a play are not added to the player's score until
+/// they are claimed. The player who made the play must claim them before the next action is taken. If they miss them, another
+/// player may call "muggins" and take them instead, and otherwise, nobody scores them.
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::DominoesState;
+/// # use rules::{Configuration, Tile, Variation};
+///
+/// let config = Configuration::new(2, Variation::AllFives, 6, 7).with_muggins(true);
+/// let mut state = DominoesState::new(&config);
+/// state.play_tile_unchecked(Tile::from((5, 5)), None);
+/// assert_eq!(state.scores(), &[0, 0]);
+/// assert_eq!(state.unclaimed_points(), Some((0, 10)));
+///
+/// // Bob calls muggins
+/// assert_eq!(state.claim_points(1), Ok(10));
+/// assert_eq!(state.scores(), &[0, 10]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Muggins {
+    /// The player who made the last play and the points that it scored, while they can be claimed
+    unclaimed: Option<(u8, u32)>,
+    /// The player who was awarded the points of each play, so that they can be taken back
+    awards: Vec<Option<u8>>,
+    /// The points that could be claimed before each action, so that they can be claimed again once it is taken back
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    before_actions: Vec<Option<(u8, u32)>>,
+    /// True if the points of each play are awarded at once to the player who made it (see `DominoesState::with_points_claimed`)
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    automatic: bool,
+}
+
+impl Muggins {
+    /// Returns the player who made the last play and the points that it scored, if they can still be claimed
+    pub fn unclaimed(&self) -> Option<(u8, u32)> {
+        self.unclaimed
+    }
+
+    // Records a play and the points that it scored. The points of the play before it can no longer be claimed. If the points
+    // are claimed automatically, they are awarded to the player at once and returned.
+    pub(crate) fn play(&mut self, player_id: u8, points: u32) -> Option<u32> {
+        self.before_actions.push(self.unclaimed);
+        self.unclaimed = (points > 0).then_some((player_id, points));
+        self.awards.push(None);
+        if self.automatic { self.award(player_id) } else { None }
+    }
+
+    // Records an action other than a play, after which the points of the last play can no longer be claimed
+    pub(crate) fn expire(&mut self) {
+        self.before_actions.push(self.unclaimed.take());
+    }
+
+    // Takes back an action other than a play, so that the points of the last play can be claimed again if they could before it
+    pub(crate) fn restore(&mut self) {
+        self.unclaimed = self.before_actions.pop().flatten();
+    }
+
+    // Awards the points of every later play to the player who made it as soon as it is made
+    pub(crate) fn claim_automatically(&mut self) {
+        self.automatic = true;
+    }
+
+    // Awards the unclaimed points to a player. Returns the points, or None if there are none.
+    pub(crate) fn award(&mut self, player_id: u8) -> Option<u32> {
+        let (_, points) = self.unclaimed.take()?;
+        *self.awards.last_mut().expect("Points are scored by a play") = Some(player_id);
+        Some(points)
+    }
+
+    // Takes back the last play. Returns the player who was awarded its points, if anyone.
+    pub(crate) fn undo_play(&mut self) -> Option<u8> {
+        self.restore();
+        self.awards.pop().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Configuration, Tile};
+
+    #[test]
+    fn test_all_fives_scorer() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(AllFivesScorer.score(&layout), 0);
+
+        // The double six is the spinner
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        assert_eq!(AllFivesScorer::open_end_sum(&layout), 12);
+        assert_eq!(AllFivesScorer.score(&layout), 0);
+
+        layout.attach_unchecked(Tile::from((4, 6)), Some(0)); // 12 + 4 = 16
+        assert_eq!(AllFivesScorer.score(&layout), 0);
+
+        layout.attach_unchecked(Tile::from((1, 6)), Some(0)); // 4 + 1 = 5
+        assert_eq!(AllFivesScorer.score(&layout), 5);
+
+        layout.attach_unchecked(Tile::from((4, 4)), Some(1)); // 8 + 1 = 9
+        assert_eq!(AllFivesScorer.score(&layout), 0);
+
+        layout.attach_unchecked(Tile::from((2, 6)), Some(0)); // 8 + 1 + 2 = 11
+        assert_eq!(AllFivesScorer.score(&layout), 0);
+
+        layout.attach_unchecked(Tile::from((1, 5)), Some(2)); // 8 + 5 + 2 = 15
+        assert_eq!(AllFivesScorer.score(&layout), 15);
+
+        layout.attach_unchecked(Tile::from((4, 5)), Some(3)); // 5 + 5 + 2 = 12
+        assert_eq!(AllFivesScorer.score(&layout), 0);
+    }
+
+    #[test]
+    fn test_scorer() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((5, 5)), None);
+        assert_eq!(scorer(Variation::AllFives).map(|scorer| scorer.score(&layout)), Some(10));
+        let others = [
+            Variation::Traditional,
+            Variation::AllSevens,
+            Variation::Bergen,
+            Variation::Blind,
+            Variation::FiveUp,
+            Variation::MexicanTrain,
+            Variation::ChickenFoot,
+        ];
+        for variation in others {
+            assert!(scorer(variation).is_none());
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Statistics of recorded games
+//!
+//! This module computes statistics about each player from the history of a game, e.g. for a summary after the game or for
+//! comparing players over many games. The game is replayed (see `Replay`) to find the points scored by each play and who controls
+//! the open ends of the layout.
+//!
+//! A player controls an open end if they played the tile that it is on. The end control of a player is the share of the open ends
+//! that they control, averaged over the positions after every action. A player who controls the ends decides which values can be
+//! played next.
+
+use rules::Configuration;
+
+use crate::{History, Replay, ReplayError};
+
+/// The statistics of a player in a game
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerStats {
+    /// The number of tiles played
+    pub plays: usize,
+    /// The number of tiles drawn
+    pub tiles_drawn: usize,
+    /// The number of passes
+    pub passes: usize,
+    /// The number of doubles played
+    pub doubles_played: usize,
+    /// The points scored by the tiles played, not counting the points scored at the end of the round
+    pub points: u32,
+    /// The share of the open ends controlled by the player, from 0 to 1, averaged over the positions after every action
+    pub end_control: f64,
+}
+
+impl PlayerStats {
+    /// Returns the average points scored by a play, or 0 if the player did not play a tile
+    pub fn points_per_play(&self) -> f64 {
+        if self.plays == 0 { 0.0 } else { f64::from(self.points) / self.plays as f64 }
+    }
+
+    /// Returns the share of the tiles played that were doubles, from 0 to 1, or 0 if the player did not play a tile
+    pub fn double_rate(&self) -> f64 {
+        if self.plays == 0 { 0.0 } else { self.doubles_played as f64 / self.plays as f64 }
+    }
+}
+
+/// The statistics of every player in a game (see the module documentation)
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, GameStats, History};
+/// # use rules::{Configuration, Tile, Variation};
+///
+/// let mut history = History::new();
+/// history.add_action(Action::play_unchecked(0, Tile::from((5, 5)), None));
+/// history.add_action(Action::play_unchecked(1, Tile::from((0, 5)), Some(5)));
+/// history.add_action(Action::play_unchecked(0, Tile::from((0, 0)), Some(0)));
+///
+/// let stats = GameStats::from_history(&Configuration::new(2, Variation::AllFives, 6, 7), &history).unwrap();
+/// assert_eq!(stats.actions, 3);
+/// assert_eq!(stats.players[0].plays, 2);
+/// assert_eq!(stats.players[0].doubles_played, 2);
+/// assert_eq!(stats.players[0].points, 20);
+/// assert_eq!(stats.players[1].points, 10);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameStats {
+    /// The number of actions taken
+    pub actions: usize,
+    /// The statistics of each player, indexed by player ID
+    pub players: Vec<PlayerStats>,
+}
+
+impl GameStats {
+    /// Computes the statistics of a recorded game
+    ///
+    /// # Arguments
+    /// * `configuration` - The configuration of the recorded game
+    /// * `history` - The actions of the recorded game, from the start of the game
+    ///
+    /// # Returns
+    /// The statistics, or an error naming the first action that cannot be replayed
+    pub fn from_history(configuration: &Configuration, history: &History) -> Result<Self, ReplayError> {
+        let mut replay = Replay::new(configuration, history)?;
+        let mut players = vec![PlayerStats::default(); configuration.num_players()];
+        // The player who played each tile of the layout, in the order the tiles were played
+        let mut owners: Vec<u8> = Vec::new();
+        // The sum over the positions of the share of the open ends controlled by each player
+        let mut control = vec![0.0; players.len()];
+
+        for _ in 0..replay.len() {
+            let before = replay.state().scores().to_vec();
+            let action = replay.step_forward().expect("The replay has another action").clone();
+            let state = replay.state();
+            let stats = &mut players[action.player_id as usize];
+            if action.tile_drawn.is_some() {
+                stats.tiles_drawn += 1;
+            }
+            if let Some((tile, _)) = action.tile_played {
+                stats.plays += 1;
+                stats.doubles_played += usize::from(tile.is_double());
+                stats.points += state.scores()[action.player_id as usize].saturating_sub(before[action.player_id as usize]);
+                owners.push(action.player_id);
+            }
+            if action.is_pass() {
+                stats.passes += 1;
+            }
+
+            let open: Vec<(usize, usize)> = state.layout.open.iter_all().map(|(&node, values)| (node, values.len())).collect();
+            let total: usize = open.iter().map(|&(_, count)| count).sum();
+            if total > 0 {
+                for (node, count) in open {
+                    control[owners[node] as usize] += count as f64 / total as f64;
+                }
+            }
+        }
+
+        let positions = replay.len().max(1) as f64;
+        for (stats, control) in players.iter_mut().zip(control) {
+            stats.end_control = control / positions;
+        }
+        Ok(Self { actions: replay.len(), players })
+    }
+
+    /// Adds the statistics of another game, e.g. the next round of a match
+    ///
+    /// The end control of each player is averaged over the positions of both games.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{GameStats, PlayerStats};
+    ///
+    /// let first = PlayerStats { plays: 1, end_control: 1.0, ..PlayerStats::default() };
+    /// let mut stats = GameStats { actions: 2, players: vec![first] };
+    /// stats.add(&GameStats { actions: 6, players: vec![PlayerStats { plays: 3, ..PlayerStats::default() }] });
+    /// assert_eq!(stats.actions, 8);
+    /// assert_eq!(stats.players[0].plays, 4);
+    /// assert_eq!(stats.players[0].end_control, 0.25);
+    /// ```
+    pub fn add(&mut self, other: &GameStats) {
+        let (positions, other_positions) = (self.actions as f64, other.actions as f64);
+        let total = (positions + other_positions).max(1.0);
+        if self.players.len() < other.players.len() {
+            self.players.resize(other.players.len(), PlayerStats::default());
+        }
+        for (stats, other) in self.players.iter_mut().zip(&other.players) {
+            stats.plays += other.plays;
+            stats.tiles_drawn += other.tiles_drawn;
+            stats.passes += other.passes;
+            stats.doubles_played += other.doubles_played;
+            stats.points += other.points;
+            stats.end_control = (stats.end_control * positions + other.end_control * other_positions) / total;
+        }
+        self.actions += other.actions;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+    use rules::{Tile, Variation};
+
+    // Returns a history of the actions
+    fn history(actions: &[Action]) -> History {
+        let mut history = History::new();
+        for action in actions {
+            history.add_action(action.clone());
+        }
+        history
+    }
+
+    #[test]
+    fn test_game_stats() {
+        let configuration = Configuration::default();
+        let actions = [
+            Action::play_unchecked(0, Tile::from((6, 6)), None),
+            Action::draw(1, Tile::from((0, 1))),
+            Action::play_unchecked(1, Tile::from((1, 6)), Some(6)),
+            Action::play_unchecked(0, Tile::from((1, 1)), Some(1)),
+        ];
+        let stats = GameStats::from_history(&configuration, &history(&actions)).unwrap();
+        assert_eq!(stats.actions, 4);
+        assert_eq!((stats.players[0].plays, stats.players[0].doubles_played, stats.players[0].tiles_drawn), (2, 2, 0));
+        assert_eq!((stats.players[1].plays, stats.players[1].tiles_drawn, stats.players[1].passes), (1, 1, 0));
+        assert_eq!(stats.players[0].double_rate(), 1.0);
+        assert_eq!(stats.players[1].points_per_play(), 0.0);
+
+        // Player 0 controls both ends after the first two actions and one end after the third, and player 1 the other.
+        // After the fourth, the double one is on the end of the line, so player 0 controls both ends again.
+        assert_eq!(stats.players[0].end_control, (1.0 + 1.0 + 0.5 + 1.0) / 4.0);
+        assert_eq!(stats.players[1].end_control, 0.5 / 4.0);
+    }
+
+    #[test]
+    fn test_game_stats_points() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let actions = [
+            Action::play_unchecked(0, Tile::from((5, 5)), None),
+            Action::play_unchecked(1, Tile::from((0, 5)), Some(5)),
+            Action::play_unchecked(0, Tile::from((3, 5)), Some(5)),
+        ];
+        let stats = GameStats::from_history(&configuration, &history(&actions)).unwrap();
+
+        // 10, then 10 + 0, then 0 + 3 which does not score
+        assert_eq!(stats.players[0].points, 10);
+        assert_eq!(stats.players[0].points_per_play(), 5.0);
+        assert_eq!(stats.players[1].points, 10);
+    }
+
+    #[test]
+    fn test_game_stats_illegal_history() {
+        let actions = [Action::play_unchecked(1, Tile::from((6, 6)), None)];
+        assert_eq!(GameStats::from_history(&Configuration::default(), &history(&actions)).unwrap_err().index, 0);
+    }
+}
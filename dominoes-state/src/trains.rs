@@ -0,0 +1,257 @@
+//! Trains of Mexican Train
+//!
+//! In Mexican Train, the tiles are played in lines called trains, which start at the engine, the double played first. Each player
+//! has a train of their own, and there is a communal train, the Mexican train, that anyone may play on. This module keeps track of
+//! where each train ends and which trains have markers, so that the state can decide where a tile may be played.
+
+use rules::Layout;
+
+/// The trains of a game of Mexican Train
+///
+/// A player may play on their own train, on the Mexican train, or on another player's train if it has a marker. A player who
+/// cannot play puts a marker on their own train, which opens it to the other players, and takes it off when they play on it
+/// again. A double played on a train must be satisfied: until a tile is played on it, nothing else can be played anywhere.
+///
+/// A player who cannot play draws one tile. If they still cannot play, they pass, even if the boneyard has more tiles (see
+/// `DrawRule::DrawOne`).
+///
+/// Each train is a line of tiles attached to the engine, so it is known by its last tile. The trains are numbered by player ID,
+/// and the Mexican train comes after the players' trains.
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::Trains;
+/// # use rules::{Configuration, Layout, Tile, Variation};
+///
+/// let config = Configuration::new(2, Variation::MexicanTrain, 12, 15);
+/// let mut layout = Layout::new(&config);
+/// layout.attach_unchecked(Tile::from((12, 12)), None);
+/// let trains = Trains::new(2);
+///
+/// // Alice may start her own train or the Mexican train, and her own train is chosen first
+/// assert_eq!(trains.train_for(&layout, 0, 12), Some(0));
+/// assert_eq!(trains.mexican_train(), 2);
+/// assert_eq!(trains.open_end(&layout, 2), Some(12));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trains {
+    /// The node of the last tile of each train, or `None` if the train has not been started
+    ends: Vec<Option<usize>>,
+    /// Whether each player's train has a marker, by player ID
+    markers: Vec<bool>,
+}
+
+impl Trains {
+    /// Creates the trains of a game that has not started
+    ///
+    /// # Arguments
+    /// * `num_players` - The number of players, each of whom has a train
+    pub fn new(num_players: usize) -> Self {
+        Self { ends: vec![None; num_players + 1], markers: vec![false; num_players] }
+    }
+
+    /// Creates the trains of a layout that is already in progress, e.g. a position set up for analysis
+    ///
+    /// The lines attached to the engine are assigned to the players' trains in the order in which they were attached, and the
+    /// Mexican train is the last. None of the trains have markers.
+    ///
+    /// # Arguments
+    /// * `num_players` - The number of players, each of whom has a train
+    /// * `layout` - The layout, whose first tile is the engine
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Trains;
+    /// # use rules::{Configuration, Layout, Tile, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::MexicanTrain, 12, 15);
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((12, 12)), None);
+    /// layout.attach_unchecked(Tile::from((5, 12)), Some(0));
+    /// layout.attach_unchecked(Tile::from((5, 9)), Some(1));
+    ///
+    /// let trains = Trains::with_layout(2, &layout);
+    /// assert_eq!(trains.end(0), Some(2));
+    /// assert_eq!(trains.open_end(&layout, 0), Some(9));
+    /// assert_eq!(trains.end(1), None);
+    /// ```
+    pub fn with_layout(num_players: usize, layout: &Layout) -> Self {
+        let mut trains = Self::new(num_players);
+        let Some(engine) = layout.nodes.first() else {
+            return trains;
+        };
+        for (end, &first) in trains.ends.iter_mut().zip(&engine.children) {
+            // Each tile in a train has at most one tile attached to it
+            let mut last = first;
+            while let Some(&next) = layout.nodes[last].children.first() {
+                last = next;
+            }
+            *end = Some(last);
+        }
+        trains
+    }
+
+    /// Returns the number of the Mexican train, which comes after the players' trains
+    pub fn mexican_train(&self) -> usize {
+        self.markers.len()
+    }
+
+    /// Returns the node of the last tile of a train, or `None` if the train has not been started
+    ///
+    /// # Panics
+    /// Panics if there is no such train
+    pub fn end(&self, train: usize) -> Option<usize> {
+        self.ends[train]
+    }
+
+    /// Returns `true` if a player's train has a marker, which lets the other players play on it
+    ///
+    /// # Panics
+    /// Panics if there is no such player
+    pub fn is_marked(&self, player_id: u8) -> bool {
+        self.markers[player_id as usize]
+    }
+
+    /// Returns the value that a tile must match to be played on a train, or `None` if nothing can be played on it
+    ///
+    /// A train that has not been started is played on the engine.
+    ///
+    /// # Arguments
+    /// * `layout` - The layout, whose first tile is the engine
+    /// * `train` - The number of the train
+    pub fn open_end(&self, layout: &Layout, train: usize) -> Option<u8> {
+        let node = self.ends[train].unwrap_or(0);
+        layout.open.get(&node).copied()
+    }
+
+    /// Returns the train that ends with a double that has not been satisfied, if any
+    ///
+    /// # Arguments
+    /// * `layout` - The layout, whose first tile is the engine
+    pub fn unsatisfied_double(&self, layout: &Layout) -> Option<usize> {
+        self.ends.iter().position(|end| end.is_some_and(|node| layout.nodes[node].tile.is_double()))
+    }
+
+    /// Returns the train on which a player plays a tile on an end, or `None` if the player cannot play on that end
+    ///
+    /// An action names the end that a tile is played on rather than the train, so if the end is open on more than one train
+    /// that the player may play on, the tile is played on the first of them: the player's own train, then the Mexican train, then
+    /// the other players' trains by player ID. If a double has not been satisfied, it is the only train that can be played on.
+    ///
+    /// # Arguments
+    /// * `layout` - The layout, whose first tile is the engine
+    /// * `player_id` - The player whose turn it is
+    /// * `end` - The value of the end
+    pub fn train_for(&self, layout: &Layout, player_id: u8, end: u8) -> Option<usize> {
+        if let Some(train) = self.unsatisfied_double(layout) {
+            return (self.open_end(layout, train) == Some(end)).then_some(train);
+        }
+        let own = player_id as usize;
+        let mexican = self.mexican_train();
+        let others = (0..self.markers.len()).filter(|&train| train != own && self.markers[train]);
+        [own, mexican].into_iter().chain(others).find(|&train| self.open_end(layout, train) == Some(end))
+    }
+
+    /// Returns a summary of the trains for display, e.g. `"0: 9, 1*: 12, Mexican: 5"`
+    ///
+    /// Each train is listed with the value that a tile must match to be played on it, or `-` if nothing can be played on it. The
+    /// players' trains are listed by player ID, and a `*` marks a train with a marker.
+    ///
+    /// # Arguments
+    /// * `layout` - The layout, whose first tile is the engine
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Trains;
+    /// # use rules::{Configuration, Layout, Tile, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::MexicanTrain, 12, 15);
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((12, 12)), None);
+    /// layout.attach_unchecked(Tile::from((9, 12)), Some(0));
+    ///
+    /// let trains = Trains::with_layout(2, &layout);
+    /// assert_eq!(trains.summary(&layout), "0: 9, 1: 12, Mexican: 12");
+    /// ```
+    pub fn summary(&self, layout: &Layout) -> String {
+        let open_end = |train| self.open_end(layout, train).map_or("-".to_string(), |end| end.to_string());
+        let mut parts: Vec<String> = (0..self.markers.len())
+            .map(|train| {
+                let marker = if self.markers[train] { "*" } else { "" };
+                format!("{train}{marker}: {}", open_end(train))
+            })
+            .collect();
+        parts.push(format!("Mexican: {}", open_end(self.mexican_train())));
+        parts.join(", ")
+    }
+
+    // Records a tile played on a train by a player. A player who plays on their own train takes its marker off.
+    pub(crate) fn extend(&mut self, train: usize, node: usize, player_id: u8) {
+        self.ends[train] = Some(node);
+        if train == player_id as usize {
+            self.markers[train] = false;
+        }
+    }
+
+    // Records a pass by a player, who puts a marker on their train
+    pub(crate) fn pass(&mut self, player_id: u8) {
+        self.markers[player_id as usize] = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Configuration, Tile, Variation};
+
+    // Returns a layout with the engine and a tile on Alice's train
+    fn started_layout() -> Layout {
+        let configuration = Configuration::new(3, Variation::MexicanTrain, 12, 15);
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((12, 12)), None);
+        layout.attach_unchecked(Tile::from((3, 12)), Some(0));
+        layout
+    }
+
+    #[test]
+    fn test_train_for() {
+        let layout = started_layout();
+        let mut trains = Trains::new(3);
+        trains.extend(0, 1, 0);
+
+        // Alice plays on her own train before the Mexican train
+        assert_eq!(trains.train_for(&layout, 0, 3), Some(0));
+        assert_eq!(trains.train_for(&layout, 0, 12), Some(3));
+
+        // Bob cannot play on Alice's train until it has a marker
+        assert_eq!(trains.train_for(&layout, 1, 12), Some(1));
+        assert_eq!(trains.train_for(&layout, 1, 3), None);
+        trains.pass(0);
+        assert!(trains.is_marked(0));
+        assert_eq!(trains.train_for(&layout, 1, 3), Some(0));
+
+        // Alice takes the marker off by playing on her train
+        trains.extend(0, 1, 0);
+        assert!(!trains.is_marked(0));
+    }
+
+    #[test]
+    fn test_unsatisfied_double() {
+        let mut layout = started_layout();
+        let mut trains = Trains::new(3);
+        trains.extend(0, 1, 0);
+        layout.attach_unchecked(Tile::from((3, 3)), Some(1));
+        trains.extend(0, 2, 0);
+
+        // Everyone must play on the double, even on Alice's train without a marker
+        assert_eq!(trains.unsatisfied_double(&layout), Some(0));
+        assert_eq!(trains.train_for(&layout, 1, 3), Some(0));
+        assert_eq!(trains.train_for(&layout, 1, 12), None);
+
+        layout.attach_unchecked(Tile::from((3, 8)), Some(2));
+        trains.extend(0, 3, 1);
+        assert_eq!(trains.unsatisfied_double(&layout), None);
+        assert_eq!(trains.train_for(&layout, 1, 12), Some(1));
+    }
+}
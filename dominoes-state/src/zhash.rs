@@ -5,10 +5,17 @@
 //! for indexing game states and detecting duplicates.
 //!
 //! # Overview
-//! The value combines three key components of domino game state:
+//! The value combines these components of domino game state:
 //! 1. **Tiles in the layout** - Which domino tiles are currently placed
 //! 2. **Open end counts** - How many times each value appears as an open end
 //! 3. **Player turn** - Which player's turn it is
+//! 4. **Consecutive passes** - How many players have passed in succession
+//! 5. **Boneyard size** - How many tiles are left to draw
+//! 6. **Train markers** - Which players' trains have markers, in Mexican Train
+//! 7. **Scores** - How many points each player has scored during play, and the points that can still be claimed
+//!
+//! The value of each component is 0 when the component is 0, e.g. when it is the first player's turn, so a value that only
+//! includes the layout is unchanged by the other components until they change.
 //!
 //! # Collision Probability
 //! With 64-bit uniformly distributed and uncorrelated values, collision probability is low:
@@ -51,6 +58,7 @@ pub type Z = u64;
 /// # Thread Safety
 /// `ZHash` implements `Copy` and contains no mutable state, making it thread-safe.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ZHash {
     /// The current Zobrist hash value
     value: Z,
@@ -105,6 +113,34 @@ impl ZHash {
     pub fn from_state(layout: &Layout, turn: u8) -> Self {
         assert!(turn < 2, "Only valid for two-player games");
 
+        let mut hash = Self::from_layout(layout);
+
+        // Include turn
+        if turn != 0 {
+            hash.turn();
+        }
+
+        hash
+    }
+
+    /// Constructs a value from the tiles and open ends of a layout alone
+    ///
+    /// This is the value that `DominoesState` keeps up to date as tiles are played and taken back, computed from scratch.
+    ///
+    /// # Arguments
+    /// * `layout` - Reference to the current board layout
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    /// # use rules::{Configuration, Layout, Tile};
+    ///
+    /// let mut layout = Layout::new(&Configuration::default());
+    /// assert_eq!(ZHash::from_layout(&layout), ZHash::default());
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// assert_ne!(ZHash::from_layout(&layout), ZHash::default());
+    /// ```
+    pub fn from_layout(layout: &Layout) -> Self {
         let mut value = Self::START;
 
         // Include tiles in the layout
@@ -119,11 +155,6 @@ impl ZHash {
             }
         }
 
-        // Include turn
-        if turn != 0 {
-            value ^= Z_VALUE_TABLE.turn_value();
-        }
-
         Self { value }
     }
 
@@ -202,6 +233,202 @@ impl ZHash {
         self
     }
 
+    /// Updates the value for a change of the player whose turn it is
+    ///
+    /// Unlike `turn`, this supports any number of players. Changing the turn from player 0 to player 1 is the same as `turn`.
+    ///
+    /// # Arguments
+    /// * `old_player` - The player whose turn it was
+    /// * `new_player` - The player whose turn it is now
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.change_turn(0, 2).change_turn(2, 3);
+    /// assert_eq!(hash, *ZHash::default().change_turn(0, 3));
+    /// ```
+    pub fn change_turn(&mut self, old_player: u8, new_player: u8) -> &mut Self {
+        self.value ^= Z_VALUE_TABLE.player_value(old_player as usize) ^ Z_VALUE_TABLE.player_value(new_player as usize);
+        self
+    }
+
+    /// Updates the value for a change in the number of consecutive passes
+    ///
+    /// # Arguments
+    /// * `old_passes` - The number of consecutive passes before the change
+    /// * `new_passes` - The number of consecutive passes after the change
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.change_passes(0, 1);
+    /// assert_ne!(hash, ZHash::default());
+    /// hash.change_passes(1, 0);
+    /// assert_eq!(hash, ZHash::default());
+    /// ```
+    pub fn change_passes(&mut self, old_passes: u8, new_passes: u8) -> &mut Self {
+        self.value ^= Z_VALUE_TABLE.passes_value(old_passes as usize) ^ Z_VALUE_TABLE.passes_value(new_passes as usize);
+        self
+    }
+
+    /// Updates the value for a change in the number of tiles in the boneyard
+    ///
+    /// # Arguments
+    /// * `old_size` - The number of tiles in the boneyard before the change
+    /// * `new_size` - The number of tiles in the boneyard after the change
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Panics
+    /// Panics if either size is 256 or more
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut drawn = ZHash::default();
+    /// drawn.change_boneyard_size(0, 14).change_boneyard_size(14, 13);
+    /// assert_ne!(drawn, *ZHash::default().change_boneyard_size(0, 14));
+    /// ```
+    pub fn change_boneyard_size(&mut self, old_size: usize, new_size: usize) -> &mut Self {
+        self.value ^= Z_VALUE_TABLE.boneyard_value(old_size) ^ Z_VALUE_TABLE.boneyard_value(new_size);
+        self
+    }
+
+    /// Updates the value for a marker put on or taken off a player's train in Mexican Train
+    ///
+    /// # Arguments
+    /// * `player_id` - The player whose train it is
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.toggle_marker(1);
+    /// assert_ne!(hash, ZHash::default());
+    /// hash.toggle_marker(1);
+    /// assert_eq!(hash, ZHash::default());
+    /// ```
+    pub fn toggle_marker(&mut self, player_id: u8) -> &mut Self {
+        self.value ^= Z_VALUE_TABLE.marker_value(player_id as usize);
+        self
+    }
+
+    /// Updates the value for the last tile of a train in Mexican Train, which is where the train is played on
+    ///
+    /// The same tile is hashed differently for each train, so positions whose trains end on the same tiles but belong to
+    /// different players are distinguished.
+    ///
+    /// # Arguments
+    /// * `train` - The train (see `Trains::mexican_train`)
+    /// * `tile` - The ordinal of the last tile of the train
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Panics
+    /// Panics if the train is 16 or more
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.toggle_train_end(0, 3);
+    /// assert_ne!(hash, *ZHash::default().toggle_train_end(1, 3));
+    /// hash.toggle_train_end(0, 3);
+    /// assert_eq!(hash, ZHash::default());
+    /// ```
+    pub fn toggle_train_end(&mut self, train: usize, tile: u8) -> &mut Self {
+        self.value ^= Z_VALUE_TABLE.train_end_value(train, tile as usize);
+        self
+    }
+
+    /// Updates the value for whether the player whose turn it is has drawn a tile since the last tile played or pass
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.toggle_drawn();
+    /// assert_ne!(hash, ZHash::default());
+    /// hash.toggle_drawn();
+    /// assert_eq!(hash, ZHash::default());
+    /// ```
+    pub fn toggle_drawn(&mut self) -> &mut Self {
+        self.value ^= Z_VALUE_TABLE.drawn_hash;
+        self
+    }
+
+    /// Updates the value for a change in the points that a player has scored during play
+    ///
+    /// Scores have no upper limit, so unlike the other components, their hashes are computed rather than looked up.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player whose score changed
+    /// * `old_score` - The player's score before the change
+    /// * `new_score` - The player's score after the change
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.change_score(0, 0, 5).change_score(0, 5, 15);
+    /// assert_eq!(hash, *ZHash::default().change_score(0, 0, 15));
+    /// assert_ne!(hash, *ZHash::default().change_score(1, 0, 15));
+    /// ```
+    pub fn change_score(&mut self, player_id: u8, old_score: u32, new_score: u32) -> &mut Self {
+        self.value ^= points_value(SCORE_KEY, player_id, old_score) ^ points_value(SCORE_KEY, player_id, new_score);
+        self
+    }
+
+    /// Updates the value for the points of the last play that can still be claimed under the muggins rule
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who made the play
+    /// * `points` - The points that can be claimed
+    ///
+    /// # Returns
+    /// Mutable reference to self for method chaining
+    ///
+    /// # Example
+    /// ```rust
+    /// # use dominoes_state::ZHash;
+    ///
+    /// let mut hash = ZHash::default();
+    /// hash.toggle_unclaimed(0, 10);
+    /// assert_ne!(hash, *ZHash::default().change_score(0, 0, 10));
+    /// hash.toggle_unclaimed(0, 10);
+    /// assert_eq!(hash, ZHash::default());
+    /// ```
+    pub fn toggle_unclaimed(&mut self, player_id: u8, points: u32) -> &mut Self {
+        self.value ^= points_value(UNCLAIMED_KEY, player_id, points);
+        self
+    }
+
     /// Checks if this value represents an undefined state
     ///
     /// # Returns
@@ -267,6 +494,18 @@ struct ZTable {
     end_hashes: [[Z; 22]; 22],
     // Hash for turn changes
     turn_hash: Z,
+    // Hashes for whose turn it is (indexed by player ID). The hash for player 1 is the turn hash.
+    player_hashes: [Z; 256],
+    // Hashes for the number of consecutive passes
+    passes_hashes: [Z; 256],
+    // Hashes for the number of tiles in the boneyard
+    boneyard_hashes: [Z; 256],
+    // Hashes for the markers on the players' trains (indexed by player ID)
+    marker_hashes: [Z; 256],
+    // Hashes for the last tiles of the trains [train][tile ordinal]
+    train_end_hashes: [[Z; 256]; 16],
+    // Hash for a player having drawn a tile since the last tile played or pass
+    drawn_hash: Z,
 }
 
 impl ZTable {
@@ -276,6 +515,12 @@ impl ZTable {
             tile_hashes: [0; 256],
             end_hashes: [[0; 22]; 22],
             turn_hash: 0,
+            player_hashes: [0; 256],
+            passes_hashes: [0; 256],
+            boneyard_hashes: [0; 256],
+            marker_hashes: [0; 256],
+            train_end_hashes: [[0; 256]; 16],
+            drawn_hash: 0,
         };
 
         // Use a seeded RNG for reproducible results
@@ -297,6 +542,26 @@ impl ZTable {
         // Initialize turn hash
         table.turn_hash = rng.next_u64();
 
+        // Initialize the hashes for the turn, passes, and boneyard size. They are generated after the others so that the others
+        // do not change. The hash for 0 is 0 because it represents the starting state.
+        for hashes in [&mut table.player_hashes, &mut table.passes_hashes, &mut table.boneyard_hashes] {
+            for hash in &mut hashes[1..] {
+                *hash = rng.next_u64();
+            }
+        }
+        table.player_hashes[1] = table.turn_hash;
+
+        // Initialize the marker hashes after those, so that the others do not change
+        for hash in &mut table.marker_hashes {
+            *hash = rng.next_u64();
+        }
+
+        // Initialize the hashes for the trains and for drawing last, for the same reason
+        for hash in table.train_end_hashes.as_flattened_mut() {
+            *hash = rng.next_u64();
+        }
+        table.drawn_hash = rng.next_u64();
+
         table
     }
 
@@ -343,6 +608,69 @@ impl ZTable {
         // For two-player games, we only need one turn hash as XORing twice returns to original state
         self.turn_hash
     }
+
+    // Returns the hash for whose turn it is
+    //
+    // # Panics
+    // Panics if `player >= 256`
+    const fn player_value(&self, player: usize) -> Z {
+        assert!(player < 256, "Player ID must be < 256");
+        self.player_hashes[player]
+    }
+
+    // Returns the hash for a number of consecutive passes
+    //
+    // # Panics
+    // Panics if `passes >= 256`
+    const fn passes_value(&self, passes: usize) -> Z {
+        assert!(passes < 256, "Passes must be < 256");
+        self.passes_hashes[passes]
+    }
+
+    // Returns the hash for a number of tiles in the boneyard
+    //
+    // # Panics
+    // Panics if `size >= 256`
+    const fn boneyard_value(&self, size: usize) -> Z {
+        assert!(size < 256, "Boneyard size must be < 256");
+        self.boneyard_hashes[size]
+    }
+
+    // Returns the hash for a marker on a player's train
+    //
+    // # Panics
+    // Panics if `player >= 256`
+    const fn marker_value(&self, player: usize) -> Z {
+        assert!(player < 256, "Player ID must be < 256");
+        self.marker_hashes[player]
+    }
+
+    // Returns the hash for the last tile of a train
+    //
+    // # Panics
+    // Panics if `train >= 16` or `tile >= 256`
+    const fn train_end_value(&self, train: usize, tile: usize) -> Z {
+        assert!(train < 16, "Train must be < 16");
+        assert!(tile < 256, "Tile index must be < 256");
+        self.train_end_hashes[train][tile]
+    }
+}
+
+// Keys of the hashes of the scores and of the points that can still be claimed
+const SCORE_KEY: Z = 0x9e37_79b9_7f4a_7c15;
+const UNCLAIMED_KEY: Z = 0xd1b5_4a32_d192_ed03;
+
+// Returns the hash of a number of points held by a player, or 0 if there are no points
+//
+// The key, the player and the points are mixed with the finalizer of SplitMix64, which maps each input to a distinct value.
+const fn points_value(key: Z, player: u8, points: u32) -> Z {
+    if points == 0 {
+        return 0;
+    }
+    let mut z = key ^ ((player as Z) << 32 | points as Z);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
 // Global singleton instance of the hash value lookup table
@@ -583,5 +911,69 @@ mod tests {
         assert_eq!(table.turn_value(), table.turn_value());
     }
 
+    #[test]
+    fn test_zhash_turn_passes_and_boneyard() {
+        // Player 1's turn is the same as a turn change
+        assert_eq!(*ZHash::default().change_turn(0, 1), *ZHash::default().turn());
+
+        // Each component is distinguished, and changes can be reversed
+        let mut hash = ZHash::default();
+        hash.change_turn(0, 2).change_passes(0, 1).change_boneyard_size(0, 10);
+        let values = [
+            *ZHash::default().change_turn(0, 2),
+            *ZHash::default().change_passes(0, 1),
+            *ZHash::default().change_boneyard_size(0, 10),
+        ];
+        for value in values {
+            assert_ne!(hash, value);
+        }
+        hash.change_boneyard_size(10, 0).change_passes(1, 0).change_turn(2, 0);
+        assert_eq!(hash, ZHash::default());
+
+        // A change to the same value does nothing
+        assert_eq!(*ZHash::default().change_passes(3, 3), ZHash::default());
+    }
+
+    #[test]
+    fn test_zhash_trains_and_drawn() {
+        // The trains, the markers and drawing are distinguished from each other and from the other trains
+        let values = [
+            *ZHash::default().toggle_train_end(0, 0),
+            *ZHash::default().toggle_train_end(1, 0),
+            *ZHash::default().toggle_train_end(0, 1),
+            *ZHash::default().toggle_marker(0),
+            *ZHash::default().toggle_drawn(),
+        ];
+        for (i, a) in values.iter().enumerate() {
+            assert_ne!(*a, ZHash::default());
+            assert!(values[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[test]
+    fn test_zhash_scores() {
+        // No points is no change
+        assert_eq!(*ZHash::default().change_score(1, 0, 0), ZHash::default());
+        assert_eq!(*ZHash::default().toggle_unclaimed(1, 0), ZHash::default());
+
+        // Players, points, and scored and unclaimed points are distinguished
+        let values = [
+            *ZHash::default().change_score(0, 0, 5),
+            *ZHash::default().change_score(1, 0, 5),
+            *ZHash::default().change_score(0, 0, 10),
+            *ZHash::default().toggle_unclaimed(0, 5),
+            *ZHash::default().toggle_unclaimed(1, 5),
+        ];
+        for (i, a) in values.iter().enumerate() {
+            assert_ne!(*a, ZHash::default());
+            assert!(values[i + 1..].iter().all(|b| a != b));
+        }
+
+        // Changes can be reversed
+        let mut hash = ZHash::default();
+        hash.change_score(0, 0, 5).change_score(0, 5, 20).change_score(0, 20, 0);
+        assert_eq!(hash, ZHash::default());
+    }
+
     // Additional comprehensive tests would go here...
 }
@@ -0,0 +1,201 @@
+//! WebAssembly bindings for the engine
+//!
+//! This crate exposes the engine to JavaScript with `wasm-bindgen`, so that a game can be played in the browser without a server.
+//! Every seat is external, so the page makes every move, either by choosing one of the legal moves or by asking the AI to choose.
+//! Moves and states are passed to JavaScript as JSON strings (see `engine::view`), and moves can also be made in notation, e.g.
+//! `play 3|6 on 6`.
+//!
+//! The AI is asked for a move with `ai_move`, which returns a promise. The search does not start until the page has had a chance
+//! to update, e.g. to show that the AI is thinking.
+//!
+//! # Examples
+//! ```js
+//! import init, { new_game } from "./dominoes_wasm.js";
+//!
+//! await init();
+//! const game = new_game(2, 42);
+//! while (!JSON.parse(game.state_json()).game_is_over) {
+//!     const player = JSON.parse(game.state_json()).whose_turn;
+//!     const events = player === 0 ? game.play_notation(game.legal_notation()[0]) : await game.ai_move("mcts:easy");
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::Promise;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{JsFuture, future_to_promise};
+
+use dominoes_state::Action;
+use engine::{Game, GameEvent, Move, Seat, StateView};
+use player::PlayerSpec;
+use rules::Configuration;
+
+/// A game played from JavaScript
+#[wasm_bindgen]
+pub struct WasmGame {
+    // Shared with the AI's pending searches
+    game: Rc<RefCell<Game>>,
+}
+
+/// Creates a game and deals the hands
+///
+/// # Arguments
+/// * `num_players` - The number of players (2 to 4)
+/// * `seed` - Determines the deal. The same seed and the same moves always produce the same game.
+///
+/// # Returns
+/// The game, waiting for the first player's move, or an error if the number of players is not supported
+#[wasm_bindgen]
+pub fn new_game(num_players: usize, seed: u32) -> Result<WasmGame, JsError> {
+    if !(2..=4).contains(&num_players) {
+        return Err(JsError::new(&format!("Unsupported number of players: {num_players}")));
+    }
+    let configuration = Configuration::new(
+        num_players,
+        Configuration::DEFAULT_VARIATION,
+        Configuration::DEFAULT_SET_ID,
+        Configuration::default_starting_hand_size(num_players, Configuration::DEFAULT_VARIATION),
+    );
+    let seats = (0..num_players).map(|_| Seat::External).collect();
+    let mut game = Game::with_seed(&configuration, seats, seed as u64);
+    advance(&mut game, Vec::new());
+    Ok(WasmGame { game: Rc::new(RefCell::new(game)) })
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    /// Returns the legal moves of the player whose turn it is
+    ///
+    /// # Returns
+    /// A JSON array of moves, e.g. `[{"kind":"play","tile":[6,6],"end":null},{"kind":"draw"}]`. The tile to be drawn is not
+    /// revealed. The array is empty if the game is over.
+    pub fn legal_moves(&self) -> String {
+        let moves: Vec<Move> = legal_actions(&self.game.borrow()).iter().map(Move::from).collect();
+        serde_json::to_string(&moves).expect("A move can always be serialized")
+    }
+
+    /// Returns the legal moves of the player whose turn it is in notation
+    ///
+    /// # Returns
+    /// The moves, e.g. `["play 3|6 on 6", "play 3|6 on 3"]`, in the same order as `legal_moves`
+    pub fn legal_notation(&self) -> Vec<String> {
+        legal_actions(&self.game.borrow()).iter().map(|action| Move::from(action).to_string()).collect()
+    }
+
+    /// Makes a move for the player whose turn it is
+    ///
+    /// # Arguments
+    /// * `index` - The index of the move in the array returned by `legal_moves`
+    ///
+    /// # Returns
+    /// A JSON array of the events that occurred (see `GameEvent`), or an error if there is no such move
+    pub fn play(&mut self, index: usize) -> Result<String, JsError> {
+        let mut game = self.game.borrow_mut();
+        let action = legal_actions(&game)
+            .get(index)
+            .cloned()
+            .ok_or_else(|| JsError::new(&format!("There is no move {index}")))?;
+        submit(&mut game, action).map_err(|e| JsError::new(&e))
+    }
+
+    /// Makes a move written in notation for the player whose turn it is
+    ///
+    /// # Arguments
+    /// * `notation` - The move, e.g. `play 3|6 on 6`, `draw`, or `pass`
+    ///
+    /// # Returns
+    /// A JSON array of the events that occurred (see `GameEvent`), or an error if the move cannot be read or is not legal
+    pub fn play_notation(&mut self, notation: &str) -> Result<String, JsError> {
+        let requested: Move = notation.parse().map_err(|e: engine::ParseMoveError| JsError::new(&e.to_string()))?;
+        let mut game = self.game.borrow_mut();
+        let action = requested
+            .to_action(&game)
+            .ok_or_else(|| JsError::new(&format!("'{requested}' is not a legal move")))?;
+        submit(&mut game, action).map_err(|e| JsError::new(&e))
+    }
+
+    /// Lets the AI make a move for the player whose turn it is
+    ///
+    /// The search starts after the page has had a chance to update, and the AI moves for whoever's turn it is then.
+    ///
+    /// # Arguments
+    /// * `spec` - The computer player that chooses the move, e.g. `mcts:hard` or `greedy`, or `undefined` for the default AI
+    ///
+    /// # Returns
+    /// A promise of a JSON array of the events that occurred (see `GameEvent`). The promise is rejected if the game is over or
+    /// the player is not a computer player.
+    pub fn ai_move(&self, spec: Option<String>) -> Promise {
+        let game = Rc::clone(&self.game);
+        future_to_promise(async move {
+            // Yield to the page before the search, which blocks until it is done
+            JsFuture::from(Promise::resolve(&JsValue::NULL)).await?;
+
+            let spec: PlayerSpec = spec.as_deref().unwrap_or("mcts").parse().map_err(|e: String| JsValue::from_str(&e))?;
+            let mut game = game.borrow_mut();
+            let action = game.choose_action(&spec).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            submit(&mut game, action).map(|events| JsValue::from_str(&events)).map_err(|e| JsValue::from_str(&e))
+        })
+    }
+
+    /// Returns the state of the game
+    ///
+    /// # Returns
+    /// A JSON object with the layout, the hands of all players, the number of tiles in the boneyard, whose turn it is, and the
+    /// result once the game is over
+    pub fn state_json(&self) -> String {
+        serde_json::to_string(&StateView::new(&self.game.borrow())).expect("The state can always be serialized")
+    }
+}
+
+// Returns the legal actions of the player whose turn it is
+fn legal_actions(game: &Game) -> Vec<Action> {
+    game.legal_actions(game.state().whose_turn)
+}
+
+// Submits an action, and returns the events that occurred until the next move is needed as JSON
+fn submit(game: &mut Game, action: Action) -> Result<String, String> {
+    let events = game.submit(action).map_err(|e| e.to_string())?;
+    let events = advance(game, events);
+    Ok(serde_json::to_string(&events).expect("An event can always be serialized"))
+}
+
+// Steps the game until it needs a move or is over, and returns all of the events
+fn advance(game: &mut Game, mut events: Vec<GameEvent>) -> Vec<GameEvent> {
+    while !game.is_over() && !matches!(events.last(), Some(GameEvent::NeedsInput { .. })) {
+        events.extend(game.step());
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the paths that do not create a JsError are tested, because JavaScript values cannot be created outside of wasm
+
+    #[test]
+    fn test_new_game_and_play_to_the_end() {
+        let mut game = new_game(2, 42).ok().unwrap();
+        for _ in 0..100 {
+            let state: serde_json::Value = serde_json::from_str(&game.state_json()).unwrap();
+            if state["game_is_over"] == true {
+                break;
+            }
+            let moves: Vec<serde_json::Value> = serde_json::from_str(&game.legal_moves()).unwrap();
+            assert_eq!(moves.len(), game.legal_notation().len());
+            let events = game.play(0).ok().unwrap();
+            assert!(events.starts_with('['));
+        }
+        assert_eq!(game.legal_moves(), "[]");
+        assert!(game.legal_notation().is_empty());
+    }
+
+    #[test]
+    fn test_same_seed_same_deal() {
+        let first = new_game(3, 7).ok().unwrap();
+        let second = new_game(3, 7).ok().unwrap();
+        assert_eq!(first.state_json(), second.state_json());
+    }
+}
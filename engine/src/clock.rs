@@ -0,0 +1,134 @@
+//! Clock module
+//!
+//! This module defines time controls and the clock that keeps track of each player's time.
+
+use std::time::Duration;
+
+/// Limits on the time that the players may take
+///
+/// # Examples
+/// ```rust
+/// # use engine::TimeControl;
+/// # use std::time::Duration;
+///
+/// // 30 seconds for each move, and 5 minutes for the whole game
+/// let time_control = TimeControl {
+///     per_move: Some(Duration::from_secs(30)),
+///     per_game: Some(Duration::from_secs(5 * 60)),
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    /// The time allowed for each action, or `None` if it is not limited
+    pub per_move: Option<Duration>,
+    /// The time allowed for all of a player's actions in a game, or `None` if it is not limited
+    pub per_game: Option<Duration>,
+}
+
+/// The time remaining for each player under a time control
+#[derive(Debug, Clone)]
+pub struct Clock {
+    /// The time control being enforced
+    time_control: TimeControl,
+    /// The game time remaining for each player, by player ID
+    remaining: Vec<Option<Duration>>,
+}
+
+impl Clock {
+    /// Creates a clock with the full time for every player
+    ///
+    /// # Arguments
+    /// * `time_control` - The time control to enforce
+    /// * `num_players` - The number of players
+    pub fn new(time_control: TimeControl, num_players: usize) -> Self {
+        Self {
+            time_control,
+            remaining: vec![time_control.per_game; num_players],
+        }
+    }
+
+    /// Returns the time control being enforced
+    pub fn time_control(&self) -> TimeControl {
+        self.time_control
+    }
+
+    /// Returns the game time that a player has left, or `None` if the game time is not limited
+    pub fn remaining(&self, player_id: u8) -> Option<Duration> {
+        self.remaining[player_id as usize]
+    }
+
+    /// Returns the time that a player has for its next action, or `None` if it is not limited
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use engine::{Clock, TimeControl};
+    /// # use std::time::Duration;
+    ///
+    /// let time_control = TimeControl { per_move: Some(Duration::from_secs(30)), per_game: Some(Duration::from_secs(20)) };
+    /// let clock = Clock::new(time_control, 2);
+    /// assert_eq!(clock.budget(0), Some(Duration::from_secs(20)));
+    /// ```
+    pub fn budget(&self, player_id: u8) -> Option<Duration> {
+        match (self.time_control.per_move, self.remaining(player_id)) {
+            (Some(per_move), Some(remaining)) => Some(per_move.min(remaining)),
+            (per_move, remaining) => per_move.or(remaining),
+        }
+    }
+
+    /// Charges a player for the time taken by an action
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who acted
+    /// * `elapsed` - The time taken
+    ///
+    /// # Returns
+    /// `false` if the player took longer than its budget (see `budget`), and has lost on time
+    pub fn charge(&mut self, player_id: u8, elapsed: Duration) -> bool {
+        let in_time = self.budget(player_id).is_none_or(|budget| elapsed <= budget);
+        if let Some(remaining) = &mut self.remaining[player_id as usize] {
+            *remaining = remaining.saturating_sub(elapsed);
+        }
+        in_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_per_move() {
+        let time_control = TimeControl { per_move: Some(Duration::from_secs(30)), per_game: None };
+        let mut clock = Clock::new(time_control, 2);
+        assert_eq!(clock.budget(1), Some(Duration::from_secs(30)));
+        assert_eq!(clock.remaining(1), None);
+
+        // Every move gets the full time
+        assert!(clock.charge(1, Duration::from_secs(25)));
+        assert!(clock.charge(1, Duration::from_secs(30)));
+        assert!(!clock.charge(1, Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_clock_per_game() {
+        let time_control = TimeControl { per_move: None, per_game: Some(Duration::from_secs(60)) };
+        let mut clock = Clock::new(time_control, 2);
+
+        assert!(clock.charge(0, Duration::from_secs(45)));
+        assert_eq!(clock.remaining(0), Some(Duration::from_secs(15)));
+        assert_eq!(clock.budget(0), Some(Duration::from_secs(15)));
+
+        // The other player's clock is not affected
+        assert_eq!(clock.budget(1), Some(Duration::from_secs(60)));
+
+        assert!(!clock.charge(0, Duration::from_secs(16)));
+        assert_eq!(clock.remaining(0), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_clock_unlimited() {
+        let mut clock = Clock::new(TimeControl::default(), 3);
+        assert_eq!(clock.budget(2), None);
+        assert!(clock.charge(2, Duration::from_secs(3600)));
+    }
+}
@@ -0,0 +1,79 @@
+//! Game errors module
+//!
+//! This module defines the errors returned by the engine. Errors from the rules and state crates convert into them, so they can be
+//! returned with `?`.
+
+use dominoes_state::DominoesError;
+use rules::{LayoutError, RulesError};
+use thiserror::Error;
+
+/// An error returned by a `Game`
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, DominoesError};
+/// # use engine::{Game, GameError, Seat};
+/// # use rules::Configuration;
+///
+/// let configuration = Configuration::default();
+/// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 1);
+/// assert_eq!(game.submit(Action::pass(0)), Err(GameError::NotStarted));
+///
+/// game.step();
+/// let player_id = game.state().whose_turn;
+/// assert_eq!(game.submit(Action::pass(1 - player_id)), Err(GameError::State(DominoesError::WrongTurn(1 - player_id))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GameError {
+    /// Actions cannot be submitted until the hands have been dealt
+    #[error("The game has not started")]
+    NotStarted,
+    /// The seat whose turn it is gets its actions from a player, not the front end
+    #[error("Player {0} is not an external seat")]
+    NotExternal(u8),
+    /// No turns have been taken by a person
+    #[error("There is nothing to undo")]
+    NothingToUndo,
+    /// No turns have been taken back since the last action
+    #[error("There is nothing to redo")]
+    NothingToRedo,
+    /// Turns cannot be taken back in this game (see `Game::set_undo_allowed`)
+    #[error("Undo is not allowed in this game")]
+    UndoNotAllowed,
+    /// Only a computer player can be asked to choose an action
+    #[error("A human player cannot choose an action for the AI")]
+    HumanPlayer,
+    /// Only a player on this computer can be asked to choose an action
+    #[error("A remote player cannot choose an action for the AI")]
+    RemotePlayer,
+    /// A replayed game differs from the recorded one (see `Game::verify`)
+    #[error("The state after action {index} differs from the recorded game: expected fingerprint {expected}, got {actual}")]
+    Diverged { index: usize, expected: u64, actual: u64 },
+    /// A history cannot be verified because some of its actions do not have fingerprints
+    #[error("The history does not have a fingerprint for every action")]
+    MissingFingerprints,
+    /// The configuration is not valid
+    #[error(transparent)]
+    Rules(#[from] RulesError),
+    /// The action is not legal, or the state is not valid
+    #[error(transparent)]
+    State(#[from] DominoesError),
+}
+
+/// A move written in notation could not be read (see `Move`)
+///
+/// # Examples
+/// ```rust
+/// # use engine::{Move, ParseMoveError};
+///
+/// assert_eq!("castle".parse::<Move>(), Err(ParseMoveError("castle".to_string())));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("'{0}' is not a move. Expected e.g. 'play 6|6', 'play 3|6 on 6', 'draw', or 'pass'.")]
+pub struct ParseMoveError(pub String);
+
+impl From<LayoutError> for GameError {
+    fn from(error: LayoutError) -> Self {
+        GameError::State(error.into())
+    }
+}
@@ -0,0 +1,50 @@
+//! Game events module
+//!
+//! This module defines the events reported by the engine as a game progresses.
+
+use rules::Tile;
+use serde::Serialize;
+
+/// Something that happened in a game, or something that the game is waiting for
+///
+/// Events serialize to JSON objects with a `type` field, e.g. `{"type":"passed","player_id":1}`.
+///
+/// # Examples
+/// ```rust
+/// # use engine::GameEvent;
+/// # use rules::Tile;
+///
+/// let event = GameEvent::TilePlayed { player_id: 0, tile: Tile::from((6, 6)), end: None };
+/// if let GameEvent::TilePlayed { tile, .. } = event {
+///     assert!(tile.is_double());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// The hands have been dealt and the first player has been determined
+    RoundStarted { first_player: u8 },
+    /// A player drew a tile from the boneyard. The tile is only known to the player and to the engine.
+    TileDrawn { player_id: u8, tile: Tile },
+    /// A player played a tile on an open end, or as the first tile if `end` is `None`
+    TilePlayed { player_id: u8, tile: Tile, end: Option<u8> },
+    /// A player passed
+    Passed { player_id: u8 },
+    /// The game is waiting for an external seat to act (see `Game::submit`)
+    NeedsInput { player_id: u8 },
+    /// Turns were taken back, and it is the player's turn again (see `Game::undo`)
+    Undone { player_id: u8 },
+    /// A player asked to leave the game. The game is not over, and the player is asked again if the game is stepped.
+    Quit { player_id: u8 },
+    /// A player asked to practice a layout instead of taking their turn. The front end sets up the position, if it can (see
+    /// `Game::take_setup_request` and `Game::set_position`).
+    SetupRequested { player_id: u8 },
+    /// A player took longer than its time control allows, and forfeits the round
+    TimedOut { player_id: u8 },
+    /// A player claimed the points scored by their last play under the muggins rule (see `Game::claim`)
+    PointsClaimed { player_id: u8, points: u32 },
+    /// A player called muggins on the points missed by the player who made the last play, and took them
+    Muggins { player_id: u8, from: u8, points: u32 },
+    /// The round is over. The winner is `None` if it is a draw.
+    RoundOver { winner: Option<u8> },
+}
@@ -0,0 +1,1580 @@
+//! Game module
+//!
+//! This module defines `Game`, which runs a round of dominoes one step at a time and reports what happens as `GameEvent`s.
+
+use std::io::{self, Write};
+use std::time::Instant;
+
+use dominoes_state::{Action, DominoesError, DominoesState, Hand, History, scorer};
+use hidden_game_player::State;
+use player::{DecisionStats, Player, PlayerFactory, PlayerSpec};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rules::{Configuration, Opening, RngStream};
+
+use crate::log::{DecisionRecord, LogRecord, StateSummary};
+use crate::{Clock, GameError, GameEvent, TimeControl, first_player, forfeit_result, round_result, turn_is_over};
+
+/// Who controls a seat at the table
+pub enum Seat {
+    /// The seat is controlled by a player, which is asked for its actions by the engine
+    Player(Box<dyn Player>),
+    /// The seat is controlled by the front end, which supplies its actions with `Game::submit`. The engine holds the hand.
+    External,
+}
+
+// A seat and the hand held by the engine for an external seat
+enum Occupant {
+    Player(Box<dyn Player>),
+    External(Hand),
+}
+
+/// A game of dominoes driven one step at a time
+///
+/// Each call to `step` advances the game as far as it can without outside input and returns the events that occurred. When it
+/// is an external seat's turn, `step` returns `GameEvent::NeedsInput`, and the game does not advance until an action is supplied
+/// with `submit`.
+///
+/// # Examples
+/// ```rust
+/// # use engine::{Game, GameEvent, Seat};
+/// # use rules::Configuration;
+///
+/// let configuration = Configuration::default();
+/// let mut game = Game::new(&configuration, vec![Seat::External, Seat::External]);
+///
+/// // Play the first legal action until the round is over
+/// while !game.is_over() {
+///     for event in game.step() {
+///         if let GameEvent::NeedsInput { player_id } = event {
+///             let action = game.legal_actions(player_id)[0].clone();
+///             game.submit(action).expect("A legal action is accepted");
+///         }
+///     }
+/// }
+/// ```
+pub struct Game {
+    /// The game configuration
+    configuration: Configuration,
+    /// The seats, by player ID
+    seats: Vec<Occupant>,
+    /// The current state of the game
+    state: DominoesState,
+    /// History of all actions taken during the game
+    history: History,
+    /// The groups of actions taken back by `undo` that can be made again by `redo`, the most recent last
+    redone: Vec<Vec<Action>>,
+    /// False if turns cannot be taken back, e.g. in a serious game
+    undo_allowed: bool,
+    /// True once the hands have been dealt
+    started: bool,
+    /// Number of actions taken so far
+    turn_count: usize,
+    /// Number of rounds started before this one
+    round: usize,
+    /// Shuffles the boneyard for each deal
+    rng: ChaCha8Rng,
+    /// The players' clocks, if there is a time control
+    clock: Option<Clock>,
+    /// When the engine started waiting for an external seat's action, if there is a time control
+    waiting_since: Option<Instant>,
+    /// Where events are logged, if anywhere
+    log: Option<Box<dyn Write>>,
+    /// The error that stopped the logging, if any
+    log_error: Option<io::Error>,
+    /// How the last computer player chose its action, until it is logged
+    decision: Option<DecisionStats>,
+    /// The layout that a player asked to practice, until it is taken by the front end
+    setup_request: Option<String>,
+}
+
+impl Game {
+    /// Creates a new game
+    ///
+    /// If the configuration has a seed, the deals are the same as those of a game created with `with_seed` and that seed (see
+    /// `Configuration::with_seed`). Otherwise they are random.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings
+    /// * `seats` - Who controls each seat, by player ID. The IDs of the players must match their seats.
+    ///
+    /// # Panics
+    /// Panics if the number of seats does not match the number of players in the configuration
+    pub fn new(configuration: &Configuration, seats: Vec<Seat>) -> Self {
+        Self::with_rng(configuration, seats, configuration.rng(RngStream::Deal))
+    }
+
+    /// Creates a new game whose deals are determined by a seed
+    ///
+    /// Two games created with the same seed and played the same way are identical. Unlike `new`, this does not need a source of
+    /// entropy, so it can be used on any platform.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings
+    /// * `seats` - Who controls each seat, by player ID. The IDs of the players must match their seats.
+    /// * `seed` - The seed for shuffling the boneyard
+    ///
+    /// # Panics
+    /// Panics if the number of seats does not match the number of players in the configuration
+    pub fn with_seed(configuration: &Configuration, seats: Vec<Seat>, seed: u64) -> Self {
+        Self::with_rng(configuration, seats, ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Creates a game that starts from a position that is already in progress
+    ///
+    /// The hands are not dealt. Instead, each player is given its hand from the position, and the game continues with the player
+    /// whose turn it is in the state. This is used to play out scenarios, e.g. for practicing endgames or reproducing bugs.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings
+    /// * `seats` - Who controls each seat, by player ID. The IDs of the players must match their seats.
+    /// * `state` - The state of the game
+    /// * `hands` - The hands of the players, by player ID
+    ///
+    /// # Panics
+    /// Panics if the number of seats or hands does not match the number of players in the configuration
+    pub fn from_position(
+        configuration: &Configuration,
+        seats: Vec<Seat>,
+        state: DominoesState,
+        hands: Vec<Hand>,
+    ) -> Self {
+        assert_eq!(
+            hands.len(),
+            configuration.num_players(),
+            "The number of hands must match the number of players"
+        );
+        let mut game = Self::new(configuration, seats);
+        game.set_position(state, hands);
+        game
+    }
+
+    /// Creates a game that continues a game in progress, e.g. one that was saved
+    ///
+    /// Like `from_position`, the hands are not dealt. In addition, the actions taken so far are restored, so that they can be
+    /// undone.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings
+    /// * `seats` - Who controls each seat, by player ID. The IDs of the players must match their seats.
+    /// * `state` - The state of the game after the actions in the history
+    /// * `hands` - The hands of the players, by player ID
+    /// * `history` - The actions taken so far
+    ///
+    /// # Panics
+    /// Panics if the number of seats or hands does not match the number of players in the configuration
+    pub fn resume(
+        configuration: &Configuration,
+        seats: Vec<Seat>,
+        state: DominoesState,
+        hands: Vec<Hand>,
+        history: History,
+    ) -> Self {
+        let mut game = Self::from_position(configuration, seats, state, hands);
+        game.turn_count = history.get_actions().len();
+        game.history = history;
+        game
+    }
+
+    // Creates a new game that deals with the given random number generator
+    fn with_rng(configuration: &Configuration, seats: Vec<Seat>, mut rng: ChaCha8Rng) -> Self {
+        assert_eq!(
+            seats.len(),
+            configuration.num_players(),
+            "The number of seats must match the number of players"
+        );
+        let seats = seats
+            .into_iter()
+            .map(|seat| match seat {
+                Seat::Player(player) => Occupant::Player(player),
+                Seat::External => Occupant::External(Hand::new()),
+            })
+            .collect();
+        Self {
+            configuration: configuration.clone(),
+            seats,
+            state: DominoesState::with_rng(configuration, &mut rng),
+            history: History::new(),
+            redone: Vec::new(),
+            undo_allowed: true,
+            started: false,
+            turn_count: 0,
+            round: 0,
+            rng,
+            clock: None,
+            waiting_since: None,
+            log: None,
+            log_error: None,
+            decision: None,
+            setup_request: None,
+        }
+    }
+
+    /// Creates a game by replaying the actions of a recorded game
+    ///
+    /// Every seat is external. The hands are dealt from the seed, and the actions are submitted in order.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings of the recorded game
+    /// * `seed` - The seed of the recorded game (see `with_seed`)
+    /// * `history` - The actions of the recorded game
+    ///
+    /// # Returns
+    /// The game after the last action, or an error if an action is not legal
+    pub fn replay(configuration: &Configuration, seed: u64, history: &History) -> Result<Self, GameError> {
+        Self::replay_history(configuration, seed, history, false)
+    }
+
+    /// Creates a game by replaying the actions of a recorded game, and checks that it turns out the same way
+    ///
+    /// The fingerprint of the state after each action is compared with the one recorded in the history (see
+    /// `History::add_action_with_fingerprint`), so any difference between the recorded game and the way the engine plays it now,
+    /// e.g. a change to the rules or a source of nondeterminism, is found at the action where it first appears.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings of the recorded game
+    /// * `seed` - The seed of the recorded game (see `with_seed`)
+    /// * `history` - The actions of the recorded game and the fingerprints of the states after them
+    ///
+    /// # Returns
+    /// The game after the last action, or an error if an action is not legal, a fingerprint differs, or the history does not
+    /// have a fingerprint for every action
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use engine::{Game, GameError, Seat};
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 7);
+    /// game.step();
+    /// for _ in 0..4 {
+    ///     let action = game.legal_actions(game.state().whose_turn)[0].clone();
+    ///     game.submit(action).unwrap();
+    /// }
+    ///
+    /// assert!(Game::verify(&configuration, 7, game.history()).is_ok());
+    /// assert!(Game::verify(&configuration, 8, game.history()).is_err());
+    /// ```
+    pub fn verify(configuration: &Configuration, seed: u64, history: &History) -> Result<Self, GameError> {
+        if !history.has_fingerprints() {
+            return Err(GameError::MissingFingerprints);
+        }
+        Self::replay_history(configuration, seed, history, true)
+    }
+
+    /// Starts a new round
+    ///
+    /// The history is cleared, and the hands are dealt again by the next call to `step`. The players' clocks keep running, so a
+    /// time limit for the whole game applies to all of its rounds. If the opening rule of the variation allows any tile, the
+    /// first player of each round is the player after the first player of the previous round. Otherwise, the first player is
+    /// determined by the hands (see `first_player`).
+    pub fn new_round(&mut self) {
+        self.round += 1;
+        self.state = DominoesState::with_rng(&self.configuration, &mut self.rng);
+        self.history = History::new();
+        self.redone.clear();
+        self.started = false;
+        self.turn_count = 0;
+        self.waiting_since = None;
+    }
+
+    /// Starts over from a position, e.g. one that a player asked to practice (see `GameEvent::SetupRequested`)
+    ///
+    /// Like `from_position`, each player is given its hand from the position, and the game continues with the player whose turn
+    /// it is in the state. The history is cleared.
+    ///
+    /// # Arguments
+    /// * `state` - The state of the game
+    /// * `hands` - The hands of the players, by player ID
+    ///
+    /// # Panics
+    /// Panics if the number of hands does not match the number of players in the configuration
+    pub fn set_position(&mut self, state: DominoesState, hands: Vec<Hand>) {
+        assert_eq!(hands.len(), self.seats.len(), "The number of hands must match the number of players");
+        self.state = state;
+        for (occupant, hand) in self.seats.iter_mut().zip(hands) {
+            match occupant {
+                Occupant::Player(player) => {
+                    player.reset();
+                    player.set_up_from(hand, &self.state);
+                }
+                Occupant::External(held) => *held = hand,
+            }
+        }
+        self.history = History::new();
+        self.redone.clear();
+        self.started = true;
+        self.turn_count = 0;
+        self.waiting_since = None;
+    }
+
+    /// Returns the layout that a player asked to practice, if it has not been taken yet (see `GameEvent::SetupRequested`)
+    ///
+    /// The layout is written as text, in a syntax that the front end understands (see `FrontEnd::requested_setup`).
+    pub fn take_setup_request(&mut self) -> Option<String> {
+        self.setup_request.take()
+    }
+
+    /// Enforces a time control
+    ///
+    /// Each player's time is measured from when it is asked for an action until the action is made. A player that takes longer
+    /// than the time control allows forfeits the round (see `GameEvent::TimedOut` and `forfeit_result`). Players are told their
+    /// budget before each action with `Player::set_time_budget`. Time controls are not supported on `wasm32`.
+    ///
+    /// # Arguments
+    /// * `time_control` - The time control. Every player's clock starts with the full time.
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        self.clock = Some(Clock::new(time_control, self.seats.len()));
+        self.waiting_since = None;
+    }
+
+    /// Logs every event to a writer
+    ///
+    /// Each event is written as a JSON object on its own line (see `LogRecord`), along with the fingerprint of the resulting
+    /// state and, for the actions of computer players, how the action was chosen (see `Player::decision_stats`). Requests for
+    /// input are not logged. If writing fails, logging stops and the error is kept (see `take_log_error`).
+    ///
+    /// # Arguments
+    /// * `log` - Where the events are written
+    pub fn set_log(&mut self, log: Box<dyn Write>) {
+        self.log = Some(log);
+        self.log_error = None;
+    }
+
+    /// Returns the error that stopped the logging, if any, and clears it
+    pub fn take_log_error(&mut self) -> Option<io::Error> {
+        self.log_error.take()
+    }
+
+    /// Returns the players' clocks, or `None` if there is no time control
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Returns the game configuration
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    /// Returns the current state of the game
+    pub fn state(&self) -> &DominoesState {
+        &self.state
+    }
+
+    /// Returns the history of all actions taken during the game
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Determines which player goes first with the hands as they are dealt
+    ///
+    /// The hands change once play begins, so after that, the first player is the one recorded in the history.
+    ///
+    /// In traditional dominoes, the player with the highest double goes first, and in Bergen, the player with the lowest double
+    /// (see `first_player`). Where any tile may be played first, the first player rotates from round to round. The first player
+    /// of each round is recorded in the history (see `History::first_player`).
+    ///
+    /// # Returns
+    /// The ID of the first player, or `None` if nobody holds a double that can start, so the hands must be redealt
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use engine::{Game, Seat};
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 3);
+    /// game.step();
+    /// assert_eq!(game.determine_first_player(), Some(game.state().whose_turn));
+    /// assert_eq!(game.history().first_player(), game.determine_first_player());
+    /// ```
+    pub fn determine_first_player(&self) -> Option<u8> {
+        match self.configuration.opening() {
+            Opening::AnyTile => Some((self.round % self.seats.len()) as u8),
+            _ => {
+                let hands: Vec<&Hand> = (0..self.seats.len() as u8).map(|player_id| self.hand(player_id)).collect();
+                first_player(&self.configuration, &hands)
+            }
+        }
+    }
+
+    /// Returns true if the round is over
+    pub fn is_over(&self) -> bool {
+        self.state.game_is_over
+    }
+
+    /// Returns the hand of a player
+    pub fn hand(&self, player_id: u8) -> &Hand {
+        match &self.seats[player_id as usize] {
+            Occupant::Player(player) => player.hand(),
+            Occupant::External(hand) => hand,
+        }
+    }
+
+    /// Returns the name of a player. External seats are named by their IDs.
+    pub fn player_name(&self, player_id: u8) -> String {
+        match &self.seats[player_id as usize] {
+            Occupant::Player(player) => player.name().to_string(),
+            Occupant::External(_) => format!("Player {player_id}"),
+        }
+    }
+
+    /// Advances the game as far as possible without outside input
+    ///
+    /// The first call deals the hands. After that, each call lets the current player act, until the round is over or an
+    /// external seat must act.
+    ///
+    /// # Returns
+    /// The events that occurred. Nothing happens once the round is over, so the returned list is empty.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(turn = self.turn_count)))]
+    pub fn step(&mut self) -> Vec<GameEvent> {
+        let events = self.advance();
+        self.write_log(&events);
+        events
+    }
+
+    /// Supplies the action of an external seat whose turn it is
+    ///
+    /// # Arguments
+    /// * `action` - The action taken. A drawn tile must be the next tile in the boneyard (see `legal_actions`).
+    ///
+    /// # Returns
+    /// The events that occurred, or an error if the action is not legal or it is not an external seat's turn
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(%action), err))]
+    pub fn submit(&mut self, action: Action) -> Result<Vec<GameEvent>, GameError> {
+        let events = self.accept(action)?;
+        self.write_log(&events);
+        Ok(events)
+    }
+
+    /// Takes back the last turn
+    ///
+    /// The actions of the last turn are taken back, and so are the turns before it until it is the turn of a person (a human
+    /// player or an external seat). Against a computer player, this takes back the computer's turn and the person's turn before
+    /// it.
+    ///
+    /// # Returns
+    /// The events that occurred, or an error if there are no turns to take back or undo is not allowed
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, err))]
+    pub fn undo(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        if !self.undo_allowed {
+            return Err(GameError::UndoNotAllowed);
+        }
+        let events = self.take_back()?;
+        self.write_log(&events);
+        Ok(events)
+    }
+
+    /// Makes the turns taken back by the last `undo` again
+    ///
+    /// Turns can be redone until another action is taken, which discards them.
+    ///
+    /// # Returns
+    /// The events that occurred, or an error if there are no turns to make again or undo is not allowed
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use engine::{Game, GameError, Seat};
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 1);
+    /// game.step();
+    /// let player_id = game.state().whose_turn;
+    /// let action = game.legal_actions(player_id)[0].clone();
+    /// game.submit(action).unwrap();
+    ///
+    /// game.undo().unwrap();
+    /// game.redo().unwrap();
+    /// assert_eq!(game.history().get_actions().len(), 1);
+    /// assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, err))]
+    pub fn redo(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        if !self.undo_allowed {
+            return Err(GameError::UndoNotAllowed);
+        }
+        let events = self.make_again()?;
+        self.write_log(&events);
+        Ok(events)
+    }
+
+    /// Allows or disallows taking back turns
+    ///
+    /// Undo is allowed by default. If it is disallowed, `undo` and `redo` return an error, and players who ask to take back a turn
+    /// or to practice a layout are simply asked again, since setting up a position would also discard the turns taken.
+    ///
+    /// # Arguments
+    /// * `allowed` - True if turns can be taken back
+    pub fn set_undo_allowed(&mut self, allowed: bool) {
+        self.undo_allowed = allowed;
+        if !allowed {
+            self.redone.clear();
+        }
+    }
+
+    /// Returns true if turns can be taken back (see `set_undo_allowed`)
+    pub fn undo_allowed(&self) -> bool {
+        self.undo_allowed
+    }
+
+    /// Claims the points scored by the last play under the muggins rule (see `Configuration::muggins`)
+    ///
+    /// An external seat that made the play claims its own points. Any other external seat calls muggins and takes the points that
+    /// were missed. The points must be claimed before the game is stepped again, because the computer players call muggins on
+    /// points that are missed. Computer players claim their own points (see `Player::claim_points`).
+    ///
+    /// # Arguments
+    /// * `player_id` - The external seat claiming the points
+    ///
+    /// # Returns
+    /// The events that occurred, or an error if the seat is not external or there are no points to claim
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use engine::{Game, GameEvent, Seat};
+    /// # use rules::{Configuration, Variation};
+    ///
+    /// let configuration = Configuration::new(2, Variation::AllFives, 6, 7).with_muggins(true);
+    /// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 3);
+    /// game.step();
+    /// while game.state().unclaimed_points().is_none() {
+    ///     for event in game.step() {
+    ///         if let GameEvent::NeedsInput { player_id } = event {
+    ///             game.submit(game.legal_actions(player_id)[0].clone()).unwrap();
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// // The other player calls muggins
+    /// let (player_id, points) = game.state().unclaimed_points().unwrap();
+    /// let events = game.claim(1 - player_id).unwrap();
+    /// assert_eq!(events, vec![GameEvent::Muggins { player_id: 1 - player_id, from: player_id, points }]);
+    /// ```
+    pub fn claim(&mut self, player_id: u8) -> Result<Vec<GameEvent>, GameError> {
+        if !matches!(self.seats.get(player_id as usize), Some(Occupant::External(_))) {
+            return Err(GameError::NotExternal(player_id));
+        }
+        let (scorer, _) = self.state.unclaimed_points().ok_or(DominoesError::NoPointsToClaim)?;
+        let points = self.state.claim_points(player_id)?;
+        let events = vec![claim_event(player_id, scorer, points)];
+        self.write_log(&events);
+        Ok(events)
+    }
+
+    /// Returns the legal actions of a player
+    ///
+    /// A player with a playable tile must play it. Otherwise, the player must draw, or pass if the boneyard is empty. The first
+    /// tile must be allowed by the opening rule of the variation (see `DominoesState::legal_actions`).
+    ///
+    /// # Returns
+    /// The legal actions, or an empty list if it is not the player's turn
+    pub fn legal_actions(&self, player_id: u8) -> Vec<Action> {
+        if !self.started || self.state.game_is_over || self.state.whose_turn() != player_id {
+            return Vec::new();
+        }
+
+        self.state.legal_actions(self.hand(player_id))
+    }
+
+    /// Asks a computer player to choose an action for the player whose turn it is
+    ///
+    /// The computer player is given the hand of the player whose turn it is, so this lets the AI move for an external seat. The
+    /// action is not taken.
+    ///
+    /// # Arguments
+    /// * `spec` - The computer player, e.g. `mcts:hard` or `greedy` (see `player::player_factory`)
+    ///
+    /// # Returns
+    /// The action, or an error if the round has not started or is over, `spec` is a human or remote player, or the action chosen
+    /// is not legal
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use engine::{Game, Seat};
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 1);
+    /// game.step();
+    /// let action = game.choose_action(&"greedy".parse().unwrap()).unwrap();
+    /// assert!(game.legal_actions(action.player_id).contains(&action));
+    /// ```
+    pub fn choose_action(&self, spec: &PlayerSpec) -> Result<Action, GameError> {
+        if !self.started {
+            return Err(GameError::NotStarted);
+        }
+        if self.state.game_is_over {
+            return Err(DominoesError::GameOver.into());
+        }
+        if spec.is_human() {
+            return Err(GameError::HumanPlayer);
+        }
+        if spec.is_remote() {
+            return Err(GameError::RemotePlayer);
+        }
+
+        let player_id = self.state.whose_turn;
+        let hand = self.hand(player_id);
+        let mut player = PlayerFactory::new(&self.configuration).create(player_id, spec, "AI");
+        player.set_up_from(hand.clone(), &self.state);
+        let (action, _) = player.my_turn(&self.state);
+        action.validate(&self.state, hand)?;
+        Ok(action)
+    }
+
+    // Advances the game as far as possible without outside input (see `step`)
+    fn advance(&mut self) -> Vec<GameEvent> {
+        if !self.started {
+            return vec![self.deal()];
+        }
+        if self.state.game_is_over {
+            return Vec::new();
+        }
+
+        // The points missed by an external seat are taken before the next turn
+        let mut events = self.call_muggins();
+        events.extend(self.take_turn());
+        events
+    }
+
+    // Lets the player whose turn it is act, unless it is an external seat (see `advance`)
+    fn take_turn(&mut self) -> Vec<GameEvent> {
+        // Prevent infinite loop in stub implementation
+        // A draw, a play and, in Mexican Train, a pass after drawing for each tile, plus the passes that block the game
+        let max_turns = self.configuration.set_size() * 3 + 2 * self.configuration.num_players();
+        if self.turn_count >= max_turns {
+            self.state.mark_game_over(None);
+            return vec![GameEvent::RoundOver { winner: None }];
+        }
+
+        let player_id = self.state.whose_turn();
+        let (action, new_state) = match &mut self.seats[player_id as usize] {
+            Occupant::Player(player) => {
+                player.set_time_budget(self.clock.as_ref().and_then(|clock| clock.budget(player_id)));
+                let started = self.clock.is_some().then(Instant::now);
+                let turn = player.my_turn(&self.state);
+                // If there is nothing to undo or redo, or undo is not allowed, the player is simply asked again. Neither can a
+                // player who may not take back turns set up another position.
+                if let Some(front_end) = player.front_end() {
+                    if front_end.requested_undo() {
+                        return if self.undo_allowed { self.take_back().unwrap_or_default() } else { Vec::new() };
+                    }
+                    if front_end.requested_redo() {
+                        return if self.undo_allowed { self.make_again().unwrap_or_default() } else { Vec::new() };
+                    }
+                    if front_end.requested_quit() {
+                        return vec![GameEvent::Quit { player_id }];
+                    }
+                    if let Some(layout) = front_end.requested_setup() {
+                        if !self.undo_allowed {
+                            return Vec::new();
+                        }
+                        self.setup_request = Some(layout);
+                        return vec![GameEvent::SetupRequested { player_id }];
+                    }
+                }
+                self.decision = player.decision_stats();
+                if !self.charge_clock(player_id, started) {
+                    return self.forfeit(player_id);
+                }
+                turn
+            }
+            Occupant::External(_) => {
+                if self.clock.is_some() && self.waiting_since.is_none() {
+                    self.waiting_since = Some(Instant::now());
+                }
+                return vec![GameEvent::NeedsInput { player_id }];
+            }
+        };
+        self.record(action, new_state)
+    }
+
+    // Applies the action of an external seat (see `submit`)
+    fn accept(&mut self, action: Action) -> Result<Vec<GameEvent>, GameError> {
+        if !self.started {
+            return Err(GameError::NotStarted);
+        }
+        let player_id = self.state.whose_turn();
+        let Occupant::External(hand) = &self.seats[player_id as usize] else {
+            return Err(GameError::NotExternal(player_id));
+        };
+        action.validate(&self.state, hand)?;
+
+        let started = self.waiting_since.take();
+        if !self.charge_clock(player_id, started) {
+            return Ok(self.forfeit(player_id));
+        }
+
+        let Occupant::External(hand) = &mut self.seats[player_id as usize] else {
+            unreachable!("The seat is external");
+        };
+        let mut new_state = self.state.clone();
+        if action.tile_drawn.is_some() {
+            let tile = new_state.draw_tile().expect("A validated draw must succeed");
+            hand.add_tile(tile);
+        }
+        if let Some((tile, end)) = action.tile_played {
+            new_state.play_tile(tile, end)?;
+            hand.try_remove_tile(&tile)?;
+        }
+        if action.is_pass() {
+            new_state.pass();
+        }
+        Ok(self.record(action, new_state))
+    }
+
+    // Replays the actions of a history, comparing the fingerprints if asked to (see `replay` and `verify`)
+    fn replay_history(configuration: &Configuration, seed: u64, history: &History, verify: bool) -> Result<Self, GameError> {
+        let seats = (0..configuration.num_players()).map(|_| Seat::External).collect();
+        let mut game = Self::with_seed(configuration, seats, seed);
+        game.step();
+        for (index, action) in history.get_actions().iter().enumerate() {
+            game.submit(action.clone())?;
+            if verify {
+                let expected = history.fingerprints()[index];
+                let actual = game.state.fingerprint();
+                if actual != expected {
+                    return Err(GameError::Diverged { index, expected, actual });
+                }
+            }
+        }
+        Ok(game)
+    }
+
+    // Takes back the last turn (see `undo`)
+    fn take_back(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        // Find the first action of the turn to go back to
+        let actions = self.history.get_actions();
+        let mut start = actions.len();
+        loop {
+            let Some(last) = start.checked_sub(1).map(|i| actions[i].player_id) else {
+                return Err(GameError::NothingToUndo);
+            };
+            while start > 0 && actions[start - 1].player_id == last {
+                start -= 1;
+            }
+            if self.is_human(last) {
+                break;
+            }
+        }
+
+        let mut taken_back = Vec::new();
+        while self.history.get_actions().len() > start {
+            let action = self.history.pop_action().expect("The history is not empty");
+            self.state.undo(&action);
+            for (seat, occupant) in self.seats.iter_mut().enumerate() {
+                match occupant {
+                    Occupant::Player(player) if seat == action.player_id as usize => player.undo(&action),
+                    Occupant::Player(player) => player.forget(&action),
+                    Occupant::External(hand) if seat == action.player_id as usize => hand.undo(&action),
+                    Occupant::External(_) => {}
+                }
+            }
+            self.turn_count -= 1;
+            taken_back.push(action);
+        }
+        taken_back.reverse();
+        self.redone.push(taken_back);
+        self.waiting_since = None;
+        Ok(vec![GameEvent::Undone { player_id: self.state.whose_turn }])
+    }
+
+    // Makes the turns taken back by the last undo again (see `redo`)
+    fn make_again(&mut self) -> Result<Vec<GameEvent>, GameError> {
+        let actions = self.redone.pop().ok_or(GameError::NothingToRedo)?;
+        // Recording an action discards the turns that can be redone, so the earlier ones are kept aside
+        let redone = std::mem::take(&mut self.redone);
+        let mut events = Vec::new();
+        for action in actions {
+            let mut new_state = self.state.clone();
+            if action.tile_drawn.is_some() {
+                new_state.draw_tile().expect("A redone draw must succeed");
+            }
+            if let Some((tile, end)) = action.tile_played {
+                new_state.play_tile_unchecked(tile, end);
+            }
+            if action.is_pass() {
+                new_state.pass();
+            }
+            match &mut self.seats[action.player_id as usize] {
+                Occupant::Player(player) => player.redo(&action),
+                Occupant::External(hand) => hand.redo(&action),
+            }
+            events.extend(self.record(action, new_state));
+        }
+        self.redone = redone;
+        self.waiting_since = None;
+        Ok(events)
+    }
+
+    // Charges a player for the time since it was asked for an action. Returns false if the player ran out of time.
+    fn charge_clock(&mut self, player_id: u8, started: Option<Instant>) -> bool {
+        match (&mut self.clock, started) {
+            (Some(clock), Some(started)) => clock.charge(player_id, started.elapsed()),
+            _ => true,
+        }
+    }
+
+    // Ends the round because a player ran out of time
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    fn forfeit(&mut self, player_id: u8) -> Vec<GameEvent> {
+        let hands: Vec<&Hand> = (0..self.seats.len() as u8).map(|id| self.hand(id)).collect();
+        let winner = forfeit_result(&self.configuration, &hands, player_id);
+        self.state.mark_game_over(winner);
+        vec![GameEvent::TimedOut { player_id }, GameEvent::RoundOver { winner }]
+    }
+
+    // Writes events to the log, if there is one
+    fn write_log(&mut self, events: &[GameEvent]) {
+        let decision = self.decision.take().map(DecisionRecord::from);
+        if self.log.is_none() {
+            return;
+        }
+        let hand_sizes = (0..self.configuration.num_players() as u8).map(|player_id| self.hand(player_id).len()).collect();
+        let state = StateSummary::new(&self.state, hand_sizes);
+        let Some(log) = &mut self.log else {
+            return;
+        };
+        let mut result = Ok(());
+        for &event in events {
+            let decision = match event {
+                GameEvent::NeedsInput { .. } => continue,
+                GameEvent::TileDrawn { .. } | GameEvent::TilePlayed { .. } | GameEvent::Passed { .. } => decision,
+                _ => None,
+            };
+            let record = LogRecord {
+                turn: self.turn_count,
+                event,
+                fingerprint: self.state.fingerprint(),
+                state: state.clone(),
+                decision,
+            };
+            result = serde_json::to_writer(&mut *log, &record)
+                .map_err(io::Error::from)
+                .and_then(|()| writeln!(log));
+            if result.is_err() {
+                break;
+            }
+        }
+        if let Err(error) = result.and_then(|()| log.flush()) {
+            self.log = None;
+            self.log_error = Some(error);
+        }
+    }
+
+    // Lets the player who made the last play claim its points under the muggins rule. An external seat claims them with `claim`.
+    fn claim_points(&mut self, player_id: u8) -> Vec<GameEvent> {
+        let Occupant::Player(player) = &mut self.seats[player_id as usize] else {
+            return Vec::new();
+        };
+        let claimed = player.claim_points(&self.state);
+        match self.state.unclaimed_points() {
+            Some((_, points)) if points == claimed => {
+                self.state.claim_points(player_id).expect("The points are unclaimed");
+                vec![GameEvent::PointsClaimed { player_id, points }]
+            }
+            // A player who misses its points may lose them to the computer players
+            Some(_) => self.call_muggins(),
+            None => Vec::new(),
+        }
+    }
+
+    // Lets the first computer player call muggins on the points missed by the player who made the last play, if any
+    fn call_muggins(&mut self) -> Vec<GameEvent> {
+        let Some((scorer, _)) = self.state.unclaimed_points() else {
+            return Vec::new();
+        };
+        let caller = (0..self.seats.len() as u8).find(|&player_id| {
+            player_id != scorer && matches!(&self.seats[player_id as usize], Occupant::Player(player) if !player.is_human())
+        });
+        let Some(caller) = caller else {
+            return Vec::new();
+        };
+        let points = self.state.claim_points(caller).expect("The points are unclaimed");
+        vec![claim_event(caller, scorer, points)]
+    }
+
+    // Returns true if a seat is controlled by a person
+    fn is_human(&self, player_id: u8) -> bool {
+        match &self.seats[player_id as usize] {
+            Occupant::Player(player) => player.is_human(),
+            Occupant::External(_) => true,
+        }
+    }
+
+    // Deals the hands and determines the first player, redealing if necessary
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, ret))]
+    fn deal(&mut self) -> GameEvent {
+        self.state = DominoesState::with_rng(&self.configuration, &mut self.rng);
+        let hands = self.state.boneyard.deal_round(&self.configuration, &mut self.rng);
+        for (occupant, tiles) in self.seats.iter_mut().zip(hands) {
+            match occupant {
+                Occupant::Player(player) => {
+                    player.reset();
+                    player.set_up_from(Hand::from(tiles), &self.state);
+                }
+                Occupant::External(hand) => *hand = Hand::from(tiles),
+            }
+        }
+
+        let first_player = self.determine_first_player().expect("The hands are redealt until somebody can start");
+        self.state.whose_turn = first_player;
+        self.history.set_first_player(first_player);
+        self.started = true;
+        GameEvent::RoundStarted { first_player }
+    }
+
+    // Records an action and its resulting state, and determines what happens next
+    fn record(&mut self, action: Action, new_state: DominoesState) -> Vec<GameEvent> {
+        let player_id = action.player_id;
+        let mut events = Vec::new();
+        if let Some(tile) = action.tile_drawn {
+            events.push(GameEvent::TileDrawn { player_id, tile });
+        }
+        if let Some((tile, end)) = action.tile_played {
+            events.push(GameEvent::TilePlayed { player_id, tile, end });
+        }
+        if action.is_pass() {
+            events.push(GameEvent::Passed { player_id });
+        }
+
+        // The computer players learn from the other players' actions
+        for (seat, occupant) in self.seats.iter_mut().enumerate() {
+            if let Occupant::Player(player) = occupant
+                && seat != player_id as usize
+            {
+                player.observe(&action, &self.state);
+            }
+        }
+
+        self.state = new_state;
+        self.turn_count += 1;
+        // Nobody is asked to claim points in a variation where plays do not score
+        if self.configuration.muggins() && action.is_play() && scorer(self.configuration.variation()).is_some() {
+            events.extend(self.claim_points(player_id));
+        }
+
+        // Determine if the game should end according to the variation
+        let hands: Vec<&Hand> = (0..self.seats.len() as u8).map(|id| self.hand(id)).collect();
+        if let Some(winner) = round_result(&self.configuration, &self.state, &hands) {
+            self.state.mark_game_over(winner);
+            events.push(GameEvent::RoundOver { winner });
+        } else if turn_is_over(&self.configuration, &action) {
+            self.state.advance_turn();
+        }
+
+        let fingerprint = self.state.fingerprint();
+        self.history.add_action_with_fingerprint(action, fingerprint);
+        self.redone.clear();
+        events
+    }
+}
+
+// Returns the event for points claimed by a player, either for its own play or by calling muggins
+fn claim_event(player_id: u8, scorer: u8, points: u32) -> GameEvent {
+    if player_id == scorer {
+        GameEvent::PointsClaimed { player_id, points }
+    } else {
+        GameEvent::Muggins { player_id, from: scorer, points }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dominoes_state::{DominoesError, Replay};
+    use player::FrontEnd;
+    use rules::{Boneyard, Layout, Tile, Variation};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::thread;
+    use std::time::Duration;
+
+    // A computer player that always passes
+    struct Passer {
+        player_id: u8,
+        hand: Hand,
+    }
+
+    impl Player for Passer {
+        fn reset(&mut self) {
+            self.hand = Hand::new();
+        }
+
+        fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+            self.hand = hand;
+        }
+
+        fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+            let mut new_state = state.clone();
+            new_state.pass();
+            (Action::pass(self.player_id), new_state)
+        }
+
+        fn has_playable_tile(&self, _state: &DominoesState) -> bool {
+            false
+        }
+
+        fn hand(&self) -> &Hand {
+            &self.hand
+        }
+
+        fn hand_mut(&mut self) -> &mut Hand {
+            &mut self.hand
+        }
+
+        fn name(&self) -> &str {
+            "Passer"
+        }
+
+        fn id(&self) -> u8 {
+            self.player_id
+        }
+    }
+
+    // A computer player that always asks to practice a layout instead of taking its turn
+    struct Practicer {
+        player_id: u8,
+        hand: Hand,
+    }
+
+    impl Player for Practicer {
+        fn reset(&mut self) {
+            self.hand = Hand::new();
+        }
+
+        fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+            self.hand = hand;
+        }
+
+        fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+            let mut new_state = state.clone();
+            new_state.pass();
+            (Action::pass(self.player_id), new_state)
+        }
+
+        fn has_playable_tile(&self, _state: &DominoesState) -> bool {
+            false
+        }
+
+        fn front_end(&mut self) -> Option<&mut dyn FrontEnd> {
+            Some(self)
+        }
+
+        fn hand(&self) -> &Hand {
+            &self.hand
+        }
+
+        fn hand_mut(&mut self) -> &mut Hand {
+            &mut self.hand
+        }
+
+        fn name(&self) -> &str {
+            "Practicer"
+        }
+
+        fn id(&self) -> u8 {
+            self.player_id
+        }
+    }
+
+    impl FrontEnd for Practicer {
+        fn requested_setup(&mut self) -> Option<String> {
+            Some("6|6".to_string())
+        }
+    }
+
+    // Plays the first legal action for every external seat until the round is over, and returns all the events
+    fn play_out(game: &mut Game) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        while !game.is_over() {
+            for event in game.step() {
+                events.push(event);
+                if let GameEvent::NeedsInput { player_id } = event {
+                    let action = game.legal_actions(player_id)[0].clone();
+                    events.extend(game.submit(action).unwrap());
+                }
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_game_deals_on_first_step() {
+        let configuration = Configuration::default();
+        let mut game = Game::new(&configuration, vec![Seat::External, Seat::External]);
+        assert!(game.legal_actions(0).is_empty());
+
+        let events = game.step();
+        assert!(matches!(events[..], [GameEvent::RoundStarted { .. }]));
+        assert_eq!(game.hand(0).len(), 7);
+        assert_eq!(game.hand(1).len(), 7);
+        assert_eq!(game.state().boneyard.count(), 14);
+
+        // The first player holds the highest double, which is the only legal opening
+        let GameEvent::RoundStarted { first_player } = events[0] else { unreachable!() };
+        assert_eq!(game.step(), vec![GameEvent::NeedsInput { player_id: first_player }]);
+        assert!(game.legal_actions(1 - first_player).is_empty());
+        assert!(game.legal_actions(first_player).iter().all(|action| action.is_play()));
+    }
+
+    #[test]
+    fn test_game_submit_rejects_illegal_actions() {
+        let configuration = Configuration::default();
+        let mut game = Game::new(&configuration, vec![Seat::External, Seat::External]);
+        assert_eq!(game.submit(Action::pass(0)), Err(GameError::NotStarted));
+
+        game.step();
+        let player_id = game.state().whose_turn;
+        assert_eq!(game.submit(Action::pass(1 - player_id)), Err(DominoesError::WrongTurn(1 - player_id).into()));
+        assert_eq!(game.submit(Action::pass(player_id)), Err(DominoesError::PassWithPlayableTile.into()));
+        assert!(game.history().get_actions().is_empty());
+
+        let action = game.legal_actions(player_id)[0].clone();
+        let events = game.submit(action.clone()).unwrap();
+        let (tile, end) = action.tile_played.unwrap();
+        assert_eq!(events, vec![GameEvent::TilePlayed { player_id, tile, end }]);
+        assert_eq!(game.history().get_actions(), &vec![action]);
+        assert_eq!(game.state().whose_turn, 1 - player_id);
+    }
+
+    #[test]
+    fn test_game_plays_to_the_end() {
+        let configuration = Configuration::new(2, Variation::Traditional, 6, 7);
+        for _ in 0..10 {
+            let mut game = Game::new(&configuration, vec![Seat::External, Seat::External]);
+            let events = play_out(&mut game);
+
+            let Some(GameEvent::RoundOver { winner }) = events.last().copied() else {
+                panic!("The last event must be the end of the round");
+            };
+            assert_eq!(winner, game.state().winner);
+            assert!(game.step().is_empty());
+
+            // Every tile is somewhere
+            let played = events.iter().filter(|event| matches!(event, GameEvent::TilePlayed { .. })).count();
+            let held = game.hand(0).len() + game.hand(1).len();
+            assert_eq!(played + held + game.state().boneyard.count(), configuration.set_size());
+        }
+    }
+
+    #[test]
+    fn test_game_with_seed_is_reproducible() {
+        let configuration = Configuration::default();
+        let mut a = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 1234);
+        let mut b = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 1234);
+        assert_eq!(play_out(&mut a), play_out(&mut b));
+        assert_eq!(a.history().get_actions(), b.history().get_actions());
+    }
+
+    #[test]
+    fn test_game_verify() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 31);
+        play_out(&mut game);
+        let actions = game.history().get_actions();
+        let fingerprints = game.history().fingerprints();
+        assert_eq!(fingerprints.len(), actions.len());
+        assert_eq!(fingerprints.last(), Some(&game.state().fingerprint()));
+
+        let replayed = Game::verify(&configuration, 31, game.history()).unwrap();
+        assert!(replayed.is_over());
+        assert_eq!(replayed.state().winner, game.state().winner);
+
+        // A fingerprint that differs is found at its action
+        let mut tampered = History::new();
+        for (index, (action, &fingerprint)) in actions.iter().zip(fingerprints).enumerate() {
+            tampered.add_action_with_fingerprint(action.clone(), if index == 2 { fingerprint ^ 1 } else { fingerprint });
+        }
+        assert_eq!(
+            Game::verify(&configuration, 31, &tampered).err(),
+            Some(GameError::Diverged { index: 2, expected: fingerprints[2] ^ 1, actual: fingerprints[2] })
+        );
+        assert!(Game::replay(&configuration, 31, &tampered).is_ok());
+
+        // A history without fingerprints can be replayed but not verified
+        let mut unverified = History::new();
+        for action in actions {
+            unverified.add_action(action.clone());
+        }
+        assert_eq!(Game::verify(&configuration, 31, &unverified).err(), Some(GameError::MissingFingerprints));
+        assert!(Game::replay(&configuration, 31, &unverified).unwrap().is_over());
+    }
+
+    #[test]
+    fn test_game_history_can_be_replayed() {
+        let configuration = Configuration::default();
+        for seed in 0..10 {
+            let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], seed);
+            play_out(&mut game);
+
+            let mut replay = Replay::new(&configuration, game.history()).unwrap();
+            replay.seek(replay.len());
+            assert_eq!(replay.state().fingerprint, game.state().fingerprint);
+            assert_eq!(replay.state().layout.nodes.len(), game.state().layout.nodes.len());
+            assert_eq!(replay.state().boneyard.count(), game.state().boneyard.count());
+        }
+    }
+
+    #[test]
+    fn test_game_undo() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 99);
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+
+        game.step();
+        let player_id = game.state().whose_turn;
+        let mut hand = game.hand(player_id).tiles().to_vec();
+        hand.sort();
+        let boneyard_count = game.state().boneyard.count();
+        let action = game.legal_actions(player_id)[0].clone();
+        game.submit(action).unwrap();
+
+        // The opening play is taken back, and the same player must play again
+        assert_eq!(game.undo().unwrap(), vec![GameEvent::Undone { player_id }]);
+        assert!(game.history().get_actions().is_empty());
+        assert!(game.state().layout.is_empty());
+        assert_eq!(game.state().whose_turn, player_id);
+        assert_eq!(game.state().boneyard.count(), boneyard_count);
+        let mut restored = game.hand(player_id).tiles().to_vec();
+        restored.sort();
+        assert_eq!(restored, hand);
+        assert_eq!(game.step(), vec![GameEvent::NeedsInput { player_id }]);
+        assert!(game.undo().is_err());
+    }
+
+    #[test]
+    fn test_game_undo_against_computer() {
+        let configuration = Configuration::default();
+        let passer = Box::new(Passer { player_id: 1, hand: Hand::new() });
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::Player(passer)], 5);
+
+        // Play until the computer has answered a turn of the person
+        let mut turn_start = None;
+        loop {
+            assert!(!game.is_over());
+            let events = game.step();
+            let last = game.history().get_last_action().map(|action| action.player_id);
+            if let [GameEvent::NeedsInput { player_id: 0 }] = events[..] {
+                if turn_start.is_some() && last == Some(1) {
+                    break;
+                }
+                if last != Some(0) {
+                    turn_start = Some((game.history().get_actions().len(), game.hand(0).len()));
+                }
+                let action = game.legal_actions(0)[0].clone();
+                game.submit(action).unwrap();
+            }
+        }
+
+        // Both the computer's turn and the person's turn are taken back
+        let (history_len, hand_len) = turn_start.unwrap();
+        assert_eq!(game.undo().unwrap(), vec![GameEvent::Undone { player_id: 0 }]);
+        assert_eq!(game.history().get_actions().len(), history_len);
+        assert_eq!(game.hand(0).len(), hand_len);
+        assert_eq!(game.state().whose_turn, 0);
+    }
+
+    #[test]
+    fn test_game_redo() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 99);
+        game.step();
+        assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+
+        let player_id = game.state().whose_turn;
+        let action = game.legal_actions(player_id)[0].clone();
+        game.submit(action.clone()).unwrap();
+        let fingerprint = game.state().fingerprint();
+        let hand_len = game.hand(player_id).len();
+
+        // The opening play is made again, and it is the next player's turn
+        game.undo().unwrap();
+        let (tile, end) = action.tile_played.unwrap();
+        assert_eq!(game.redo().unwrap(), vec![GameEvent::TilePlayed { player_id, tile, end }]);
+        assert_eq!(game.history().get_actions(), &[action]);
+        assert_eq!(game.state().fingerprint(), fingerprint);
+        assert_eq!(game.hand(player_id).len(), hand_len);
+        assert_ne!(game.state().whose_turn, player_id);
+        assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+
+        // Taking another action discards the turns that were taken back
+        game.undo().unwrap();
+        let other = game.legal_actions(player_id).last().unwrap().clone();
+        game.submit(other).unwrap();
+        assert_eq!(game.redo(), Err(GameError::NothingToRedo));
+    }
+
+    #[test]
+    fn test_game_undo_not_allowed() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 99);
+        game.step();
+        let player_id = game.state().whose_turn;
+        let action = game.legal_actions(player_id)[0].clone();
+        game.submit(action).unwrap();
+
+        game.set_undo_allowed(false);
+        assert!(!game.undo_allowed());
+        assert_eq!(game.undo(), Err(GameError::UndoNotAllowed));
+        assert_eq!(game.redo(), Err(GameError::UndoNotAllowed));
+        assert_eq!(game.history().get_actions().len(), 1);
+    }
+
+    #[test]
+    fn test_game_setup_request() {
+        let configuration = Configuration::default();
+        let seats = || -> Vec<Seat> {
+            (0..2).map(|player_id| Seat::Player(Box::new(Practicer { player_id, hand: Hand::new() }))).collect()
+        };
+        let mut game = Game::with_seed(&configuration, seats(), 5);
+        game.step();
+        let player_id = game.state().whose_turn;
+        assert_eq!(game.step(), vec![GameEvent::SetupRequested { player_id }]);
+        assert_eq!(game.take_setup_request(), Some("6|6".to_string()));
+        assert_eq!(game.take_setup_request(), None);
+
+        // If undo is not allowed, the request is ignored and the player is asked again
+        let mut game = Game::with_seed(&configuration, seats(), 5);
+        game.set_undo_allowed(false);
+        game.step();
+        assert!(game.step().is_empty());
+        assert_eq!(game.take_setup_request(), None);
+        assert!(game.history().get_actions().is_empty());
+    }
+
+    #[test]
+    fn test_game_muggins() {
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7).with_muggins(true);
+        let passer = Box::new(Passer { player_id: 1, hand: Hand::new() });
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::Player(passer)], 3);
+        assert_eq!(game.claim(1), Err(GameError::NotExternal(1)));
+
+        // Plays the first legal action for the person until it scores
+        let play_until_scored = |game: &mut Game| loop {
+            assert!(!game.is_over());
+            if let [.., GameEvent::NeedsInput { player_id: 0 }] = game.step()[..] {
+                game.submit(game.legal_actions(0)[0].clone()).unwrap();
+                if let Some((0, points)) = game.state().unclaimed_points() {
+                    return points;
+                }
+            }
+        };
+
+        // The person claims their points
+        game.step();
+        assert_eq!(game.claim(0), Err(DominoesError::NoPointsToClaim.into()));
+        let points = play_until_scored(&mut game);
+        assert_eq!(game.claim(0).unwrap(), vec![GameEvent::PointsClaimed { player_id: 0, points }]);
+        assert_eq!(game.state().scores()[0], points);
+
+        // The computer calls muggins on points that the person misses
+        let score = game.state().scores()[0];
+        let points = play_until_scored(&mut game);
+        assert_eq!(game.step()[0], GameEvent::Muggins { player_id: 1, from: 0, points });
+        assert_eq!(game.state().scores(), &[score, points]);
+    }
+
+    #[test]
+    fn test_game_from_position() {
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        let mut state = DominoesState::with_position(&configuration, layout, Boneyard::with(vec![Tile::from((0, 0))]));
+        state.whose_turn = 1;
+        let mut hands = vec![Hand::new(), Hand::new()];
+        hands[0].add_tile(Tile::from((1, 2)));
+        hands[1].add_tile(Tile::from((3, 6)));
+
+        // The hands are not dealt, and the game continues with the player whose turn it is
+        let mut game = Game::from_position(&configuration, vec![Seat::External, Seat::External], state, hands);
+        assert_eq!(game.step(), vec![GameEvent::NeedsInput { player_id: 1 }]);
+        assert_eq!(game.hand(0).tiles(), &[Tile::from((1, 2))]);
+        assert_eq!(game.state().boneyard.count(), 1);
+
+        let action = Action::play_unchecked(1, Tile::from((3, 6)), Some(6));
+        assert_eq!(game.legal_actions(1), vec![action.clone()]);
+        let events = game.submit(action).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::RoundOver { winner: Some(1) }));
+    }
+
+    #[test]
+    fn test_game_set_position() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 99);
+        game.step();
+        let player_id = game.state().whose_turn;
+        let action = game.legal_actions(player_id)[0].clone();
+        game.submit(action).unwrap();
+
+        // The game starts over from the position, and the turns before it cannot be undone
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((5, 5)), None);
+        let state = DominoesState::with_position(&configuration, layout, Boneyard::with(Vec::new()));
+        let mut hands = vec![Hand::new(), Hand::new()];
+        hands[0].add_tile(Tile::from((2, 5)));
+        game.set_position(state, hands);
+        assert!(game.history().get_actions().is_empty());
+        assert_eq!(game.hand(0).tiles(), &[Tile::from((2, 5))]);
+        assert_eq!(game.step(), vec![GameEvent::NeedsInput { player_id: 0 }]);
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+    }
+
+    #[test]
+    fn test_game_resume() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 99);
+        game.step();
+        let player_id = game.state().whose_turn;
+        let hand = game.hand(player_id).len();
+        let action = game.legal_actions(player_id)[0].clone();
+        game.submit(action).unwrap();
+
+        // The game continues where it was, and the play can still be taken back
+        let hands = vec![game.hand(0).clone(), game.hand(1).clone()];
+        let mut resumed = Game::resume(
+            &configuration,
+            vec![Seat::External, Seat::External],
+            game.state().clone(),
+            hands,
+            game.history().clone(),
+        );
+        assert_eq!(resumed.step(), vec![GameEvent::NeedsInput { player_id: 1 - player_id }]);
+        assert_eq!(resumed.history().get_actions(), game.history().get_actions());
+        assert_eq!(resumed.undo().unwrap(), vec![GameEvent::Undone { player_id }]);
+        assert!(resumed.state().layout.is_empty());
+        assert_eq!(resumed.hand(player_id).len(), hand);
+    }
+
+    #[test]
+    fn test_game_time_control() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 3);
+        game.set_time_control(TimeControl { per_move: Some(Duration::from_millis(1)), per_game: None });
+        game.step();
+        let player_id = game.state().whose_turn;
+        assert_eq!(game.step(), vec![GameEvent::NeedsInput { player_id }]);
+
+        // The player takes too long and forfeits
+        thread::sleep(Duration::from_millis(10));
+        let action = game.legal_actions(player_id)[0].clone();
+        let events = game.submit(action).unwrap();
+        let winner = Some(1 - player_id);
+        assert_eq!(events, vec![GameEvent::TimedOut { player_id }, GameEvent::RoundOver { winner }]);
+        assert!(game.is_over());
+        assert_eq!(game.state().winner, winner);
+    }
+
+    #[test]
+    fn test_game_time_control_in_time() {
+        let configuration = Configuration::default();
+        let passer = Box::new(Passer { player_id: 1, hand: Hand::new() });
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::Player(passer)], 3);
+        game.set_time_control(TimeControl { per_move: None, per_game: Some(Duration::from_secs(600)) });
+
+        let events = play_out(&mut game);
+        assert!(!events.iter().any(|event| matches!(event, GameEvent::TimedOut { .. })));
+        let clock = game.clock().unwrap();
+        assert!(clock.remaining(0).unwrap() < Duration::from_secs(600));
+    }
+
+    // A log that can be read while the game that writes to it still has it
+    #[derive(Clone, Default)]
+    struct SharedLog(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_game_log() {
+        let configuration = Configuration::default();
+        let log = SharedLog::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 5);
+        game.set_log(Box::new(log.clone()));
+        let events = play_out(&mut game);
+        assert!(game.take_log_error().is_none());
+
+        // Every event except requests for input is logged, one per line
+        let records: Vec<serde_json::Value> = String::from_utf8(log.0.borrow().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let logged: Vec<&GameEvent> = events.iter().filter(|event| !matches!(event, GameEvent::NeedsInput { .. })).collect();
+        assert_eq!(records.len(), logged.len());
+        assert_eq!(records[0]["type"], "round_started");
+        assert_eq!(records.last().unwrap()["type"], "round_over");
+        assert!(records.iter().all(|record| record["fingerprint"].is_u64()));
+        assert_eq!(records[0]["state"]["hand_sizes"], serde_json::json!([7, 7]));
+        assert!(records.iter().all(|record| record["state"]["scores"].as_array().unwrap().len() == 2));
+    }
+
+    #[test]
+    fn test_game_new_round() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 9);
+        play_out(&mut game);
+
+        game.new_round();
+        assert!(!game.is_over());
+        assert!(game.history().get_actions().is_empty());
+        assert!(matches!(game.step()[..], [GameEvent::RoundStarted { .. }]));
+        let dealt: usize = (0..2).map(|player_id| game.hand(player_id).len()).sum();
+        assert_eq!(dealt, 2 * configuration.starting_hand_size());
+    }
+
+    #[test]
+    fn test_game_new_round_rotates_first_player() {
+        let configuration = Configuration::new(3, Variation::AllFives, 6, 5);
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External, Seat::External], 4);
+        for round in 0..4 {
+            if round > 0 {
+                game.new_round();
+            }
+            assert!(matches!(game.step()[..], [GameEvent::RoundStarted { first_player }] if first_player == round % 3));
+            assert_eq!(game.history().first_player(), Some(round % 3));
+        }
+    }
+
+    #[test]
+    fn test_game_player_name() {
+        let configuration = Configuration::default();
+        let game = Game::new(&configuration, vec![Seat::External, Seat::External]);
+        assert_eq!(game.player_name(1), "Player 1");
+        assert!(game.hand(0).is_empty());
+        assert!(!game.hand(0).contains(&Tile::from((0, 0))));
+    }
+
+    #[test]
+    fn test_game_choose_action() {
+        let configuration = Configuration::default();
+        let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 5);
+        let greedy = "greedy".parse().unwrap();
+        assert_eq!(game.choose_action(&greedy), Err(GameError::NotStarted));
+
+        game.step();
+        assert_eq!(game.choose_action(&"human".parse().unwrap()), Err(GameError::HumanPlayer));
+        assert_eq!(game.choose_action(&"remote:localhost:7878".parse().unwrap()), Err(GameError::RemotePlayer));
+        while !game.is_over() {
+            let action = game.choose_action(&greedy).unwrap();
+            game.submit(action).unwrap();
+        }
+        assert_eq!(game.choose_action(&greedy), Err(GameError::State(DominoesError::GameOver)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The number of seats must match the number of players")]
+    fn test_game_new_wrong_number_of_seats() {
+        let configuration = Configuration::default();
+        Game::new(&configuration, vec![Seat::External]);
+    }
+}
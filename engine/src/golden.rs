@@ -0,0 +1,37 @@
+//! Golden game tests
+//!
+//! Each file in the `golden` directory of this crate is the transcript of a complete game (see `Transcript`). The games are
+//! replayed and the results are compared with the transcripts, so a change to the rules that alters the outcome of a game is
+//! caught. A change that is meant to alter it must update the transcripts.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::Transcript;
+
+// The directory holding the transcripts
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden");
+
+// Returns the paths of the transcripts, in order
+fn transcript_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(GOLDEN_DIR)
+        .expect("The golden directory should be readable")
+        .map(|entry| entry.expect("The golden directory should be readable").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_golden_games() {
+    let paths = transcript_paths();
+    assert!(!paths.is_empty(), "There are no transcripts in {GOLDEN_DIR}");
+    for path in paths {
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let transcript = Transcript::load(&path).unwrap_or_else(|e| panic!("{file_name}: {e}"));
+        if let Err(e) = transcript.replay() {
+            panic!("{file_name} ({}): {e}", transcript.description);
+        }
+    }
+}
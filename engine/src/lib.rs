@@ -0,0 +1,35 @@
+//! Game engine for dominoes.
+//!
+//! This crate runs a game of dominoes without any user interface. A front end (a terminal, a GUI, or a server) creates a `Game`,
+//! calls `Game::step` repeatedly, and reacts to the `GameEvent`s that are returned. Seats that are not controlled by a `Player`
+//! are external: the engine reports that it needs input for them, and the front end supplies their actions with `Game::submit`.
+//!
+//! The crate does not read from or write to the terminal, and a game created with `Game::with_seed` needs no source of entropy,
+//! so the crate can be compiled for `wasm32-unknown-unknown`. Time controls (see `Game::set_time_control`) need the system clock,
+//! so they are not supported there. The `dominoes-wasm` crate adds JavaScript bindings, and the `dominoes-ffi` crate adds C
+//! bindings. Both show moves and states to the front end as JSON (see the `view` module).
+//!
+//! With the `test-support` feature, the `transcript` module records games and checks that they turn out the same way later, for
+//! regression tests.
+
+pub mod clock;
+pub mod error;
+pub mod event;
+pub mod game;
+#[cfg(test)]
+mod golden;
+pub mod log;
+pub mod referee;
+#[cfg(any(test, feature = "test-support"))]
+pub mod transcript;
+pub mod view;
+
+pub use crate::clock::*;
+pub use crate::error::*;
+pub use crate::event::*;
+pub use crate::game::*;
+pub use crate::log::*;
+pub use crate::referee::*;
+#[cfg(any(test, feature = "test-support"))]
+pub use crate::transcript::*;
+pub use crate::view::*;
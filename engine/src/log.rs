@@ -0,0 +1,134 @@
+//! Game log module
+//!
+//! This module defines the records written to a game log (see `Game::set_log`). The log is meant for analyzing games afterwards,
+//! e.g. after a long batch of simulated games. Each record is a JSON object on its own line, e.g.
+//!
+//! ```json
+//! {"turn":3,"type":"tile_played","player_id":1,"tile":[3,6],"end":6,"fingerprint":1234,
+//!  "state":{"scores":[0,0],"hand_sizes":[6,5],"boneyard":14,"open_ends":[3,6]},"decision":{"iterations":1000,"elapsed_ms":2.5}}
+//! ```
+//!
+//! (The record is on one line in the log.) The summary of the state after the event is enough to follow the game without
+//! replaying it, e.g. to train a model on the positions.
+
+use player::DecisionStats;
+use serde::Serialize;
+
+use crate::GameEvent;
+use dominoes_state::DominoesState;
+
+/// A record in a game log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogRecord {
+    /// The number of actions taken so far
+    pub turn: usize,
+    /// What happened. Its fields are written as fields of the record.
+    #[serde(flatten)]
+    pub event: GameEvent,
+    /// The fingerprint of the state after the event
+    pub fingerprint: u64,
+    /// A summary of the state after the event
+    pub state: StateSummary,
+    /// How a computer player chose the action, if the event is the result of its decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<DecisionRecord>,
+}
+
+/// A summary of a state, as it is written to a game log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateSummary {
+    /// The score of each player, indexed by player ID
+    pub scores: Vec<u32>,
+    /// The number of tiles in each player's hand, indexed by player ID
+    pub hand_sizes: Vec<usize>,
+    /// The number of tiles in the boneyard
+    pub boneyard: usize,
+    /// The value of each open end of the layout, lowest first
+    pub open_ends: Vec<u8>,
+}
+
+impl StateSummary {
+    /// Summarizes a state
+    ///
+    /// # Arguments
+    /// * `state` - The state
+    /// * `hand_sizes` - The number of tiles in each player's hand, which the state may not know (see `DominoesState::is_dealt`)
+    pub fn new(state: &DominoesState, hand_sizes: Vec<usize>) -> Self {
+        let layout = &state.layout;
+        let open_ends = (0..layout.end_counts.len() as u8)
+            .flat_map(|value| std::iter::repeat_n(value, usize::from(layout.open_count(value))))
+            .collect();
+        Self { scores: state.scores().to_vec(), hand_sizes, boneyard: state.boneyard.count(), open_ends }
+    }
+}
+
+/// How a computer player chose an action (see `DecisionStats`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DecisionRecord {
+    /// The number of search iterations
+    pub iterations: u32,
+    /// The time taken to choose the action, in milliseconds
+    pub elapsed_ms: f64,
+}
+
+impl From<DecisionStats> for DecisionRecord {
+    fn from(stats: DecisionStats) -> Self {
+        Self {
+            iterations: stats.iterations,
+            elapsed_ms: stats.elapsed.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Configuration, Tile};
+    use std::time::Duration;
+
+    #[test]
+    fn test_log_record_serialization() {
+        let record = LogRecord {
+            turn: 3,
+            event: GameEvent::TilePlayed { player_id: 1, tile: Tile::from((3, 6)), end: Some(6) },
+            fingerprint: 1234,
+            state: StateSummary { scores: vec![0, 0], hand_sizes: vec![6, 5], boneyard: 14, open_ends: vec![3, 6] },
+            decision: Some(DecisionStats { iterations: 1000, elapsed: Duration::from_micros(2500) }.into()),
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            concat!(
+                r#"{"turn":3,"type":"tile_played","player_id":1,"tile":[3,6],"end":6,"fingerprint":1234,"#,
+                r#""state":{"scores":[0,0],"hand_sizes":[6,5],"boneyard":14,"open_ends":[3,6]},"#,
+                r#""decision":{"iterations":1000,"elapsed_ms":2.5}}"#
+            )
+        );
+
+        // The decision is omitted if there is none
+        let state = StateSummary { scores: vec![0, 0], hand_sizes: vec![7, 7], boneyard: 14, open_ends: vec![] };
+        let event = GameEvent::RoundStarted { first_player: 0 };
+        let record = LogRecord { turn: 0, event, fingerprint: 0, state, decision: None };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            concat!(
+                r#"{"turn":0,"type":"round_started","first_player":0,"fingerprint":0,"#,
+                r#""state":{"scores":[0,0],"hand_sizes":[7,7],"boneyard":14,"open_ends":[]}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_state_summary() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.draw_tile();
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.play_tile_unchecked(Tile::from((3, 6)), Some(6));
+
+        // The three-six covers one of the two ends of the double six
+        let summary = StateSummary::new(&state, vec![6, 5]);
+        assert_eq!(summary.open_ends, vec![3, 6]);
+        assert_eq!(summary.boneyard, configuration.set_size() - 1);
+        assert_eq!(summary.scores, vec![0, 0]);
+    }
+}
@@ -0,0 +1,277 @@
+//! Rules of play module
+//!
+//! This module decides who goes first, when a turn is over, and when and how a round ends, according to the variation being
+//! played. The functions are independent of how the players are controlled, so any front end can use them.
+
+use dominoes_state::{Action, DominoesState, Hand, lowest_hand};
+use hidden_game_player::PlayerId;
+use rules::{Configuration, Opening, Variation};
+
+/// Determines which player goes first after the hands have been dealt
+///
+/// The first player depends on the opening rule of the variation (see `rules::Opening`). In traditional dominoes, the player with
+/// the highest double goes first, and in Bergen, the player with the lowest double. If nobody has a double, there is no first
+/// player and the hands must be redealt. In the other variations, the first player goes first.
+///
+/// # Arguments
+/// * `configuration` - The game configuration
+/// * `hands` - The hands of the players, by player ID
+///
+/// # Returns
+/// The ID of the first player, or `None` if the hands must be redealt
+///
+/// # Examples
+/// ```rust
+/// # use engine::first_player;
+/// # use dominoes_state::Hand;
+/// # use rules::{Configuration, Tile};
+///
+/// let configuration = Configuration::default();
+/// let mut alice = Hand::new();
+/// alice.add_tile(Tile::from((3, 3)));
+/// let mut bob = Hand::new();
+/// bob.add_tile(Tile::from((5, 5)));
+///
+/// assert_eq!(first_player(&configuration, &[&alice, &bob]), Some(1));
+/// ```
+pub fn first_player(configuration: &Configuration, hands: &[&Hand]) -> Option<u8> {
+    let opening = configuration.opening();
+    let doubles = hands
+        .iter()
+        .enumerate()
+        .filter_map(|(player_id, hand)| opening.required_tile(hand.tiles()).map(|double| (double, player_id as u8)));
+    let first = match opening {
+        Opening::HighestDouble => doubles.max(),
+        Opening::LowestDouble => doubles.min(),
+        Opening::AnyTile => return Some(PlayerId::ALICE as u8),
+    };
+    first.map(|(_, player_id)| player_id)
+}
+
+/// Determines whether the turn is over after an action
+///
+/// # Arguments
+/// * `configuration` - The game configuration
+/// * `action` - The action just taken
+///
+/// # Returns
+/// `true` if the next player's turn begins, `false` if the same player must act again (e.g. after drawing)
+pub fn turn_is_over(configuration: &Configuration, action: &Action) -> bool {
+    action.ends_turn(configuration.variation())
+}
+
+/// Determines whether the round is over
+///
+/// The round is blocked when no player can play (see `DominoesState::is_blocked`). With partners, the winner of a blocked round is
+/// on the team with the fewest pips (see `Configuration::partners`).
+///
+/// # Arguments
+/// * `configuration` - The game configuration
+/// * `state` - The state after the last action
+/// * `hands` - The hands of the players, by player ID
+///
+/// # Returns
+/// `None` if the round is not over. Otherwise, `Some(winner)` where `winner` is `None` for a draw.
+pub fn round_result(configuration: &Configuration, state: &DominoesState, hands: &[&Hand]) -> Option<Option<u8>> {
+    let blocked = state.is_blocked();
+    match configuration.variation() {
+        Variation::Traditional | Variation::MexicanTrain | Variation::ChickenFoot => {
+            // In the Traditional, Mexican Train, and Chicken Foot variations, the round ends when a player empties their hand or
+            // the round is blocked. The winner of a blocked round is the player with the lowest hand score, unless the lowest
+            // score is shared.
+            if let Some(player_id) = hands.iter().position(|hand| hand.is_empty()) {
+                return Some(Some(player_id as u8));
+            }
+            if blocked {
+                return Some(lowest_hand(players(hands), |player_id| configuration.team(player_id)));
+            }
+        }
+        _ => {
+            // FIXME: Add real game ending logic based on variation here.
+            if blocked {
+                return Some(None); // Game ends in a draw
+            }
+        }
+    }
+    // Otherwise, the round is not over
+    None
+}
+
+/// Determines the winner when a player forfeits the round, e.g. by running out of time
+///
+/// The player who forfeits loses, along with their partner. Of the other players, the one with the lowest hand score wins, unless
+/// the lowest score is shared.
+///
+/// # Arguments
+/// * `configuration` - The game configuration
+/// * `hands` - The hands of the players, by player ID
+/// * `player_id` - The player who forfeits
+///
+/// # Returns
+/// The ID of the winner, or `None` for a draw
+pub fn forfeit_result(configuration: &Configuration, hands: &[&Hand], player_id: u8) -> Option<u8> {
+    let team = configuration.team(player_id);
+    let opponents = players(hands).filter(|&(id, _)| configuration.team(id) != team);
+    lowest_hand(opponents, |player_id| configuration.team(player_id))
+}
+
+/// Determines the points scored by the winner of a round
+///
+/// The winner scores the pips left in the other players' hands. With partners, the pips in the winner's partner's hand are not
+/// scored, since the points are for the team.
+///
+/// # Arguments
+/// * `configuration` - The game configuration
+/// * `hands` - The hands of the players at the end of the round, by player ID
+/// * `winner` - The winner of the round
+///
+/// # Examples
+/// ```rust
+/// # use engine::round_points;
+/// # use dominoes_state::Hand;
+/// # use rules::{Configuration, Tile};
+///
+/// let alice = Hand::new();
+/// let mut bob = Hand::new();
+/// bob.add_tile(Tile::from((4, 6)));
+///
+/// assert_eq!(round_points(&Configuration::default(), &[&alice, &bob], 0), 10);
+/// ```
+pub fn round_points(configuration: &Configuration, hands: &[&Hand], winner: u8) -> u32 {
+    let team = configuration.team(winner);
+    hands
+        .iter()
+        .enumerate()
+        .filter(|&(player_id, _)| configuration.team(player_id as u8) != team)
+        .map(|(_, hand)| hand.score())
+        .sum()
+}
+
+// Returns the hands of the players with their IDs (see `lowest_hand`)
+fn players<'h>(hands: &'h [&'h Hand]) -> impl Iterator<Item = (u8, &'h Hand)> + Clone {
+    hands.iter().enumerate().map(|(player_id, &hand)| (player_id as u8, hand))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::Tile;
+
+    fn hand_of(tiles: &[(u8, u8)]) -> Hand {
+        let mut hand = Hand::new();
+        for &tile in tiles {
+            hand.add_tile(Tile::from(tile));
+        }
+        hand
+    }
+
+    #[test]
+    fn test_first_player() {
+        let traditional = Configuration::default();
+        let alice = hand_of(&[(6, 6), (1, 2)]);
+        let bob = hand_of(&[(2, 2), (5, 5)]);
+        assert_eq!(first_player(&traditional, &[&alice, &bob]), Some(0));
+        assert_eq!(first_player(&traditional, &[&bob, &alice]), Some(1));
+
+        // Nobody has a double, so the hands must be redealt
+        let no_doubles = hand_of(&[(1, 2)]);
+        assert_eq!(first_player(&traditional, &[&no_doubles, &no_doubles]), None);
+
+        // In Bergen, the player with the lowest double goes first
+        let bergen = Configuration::new(2, Variation::Bergen, 6, 6);
+        assert_eq!(first_player(&bergen, &[&alice, &bob]), Some(1));
+        assert_eq!(first_player(&bergen, &[&no_doubles, &alice]), Some(1));
+        assert_eq!(first_player(&bergen, &[&no_doubles, &no_doubles]), None);
+
+        // Other variations always start with the first player
+        let all_fives = Configuration::new(2, Variation::AllFives, 6, 7);
+        assert_eq!(first_player(&all_fives, &[&no_doubles, &bob]), Some(0));
+    }
+
+    #[test]
+    fn test_turn_is_over() {
+        let traditional = Configuration::default();
+        let tile = Tile::from((2, 3));
+        assert!(!turn_is_over(&traditional, &Action::draw(0, tile)));
+        assert!(turn_is_over(&traditional, &Action::play_unchecked(0, tile, Some(3))));
+        assert!(turn_is_over(&traditional, &Action::pass(0)));
+
+        // In Mexican Train, a player who plays a double plays again, except for the engine
+        let mexican_train = Configuration::new(4, Variation::MexicanTrain, 12, 15);
+        let double = Tile::from((3, 3));
+        assert!(!turn_is_over(&mexican_train, &Action::draw(0, tile)));
+        assert!(!turn_is_over(&mexican_train, &Action::play_unchecked(0, double, Some(3))));
+        assert!(turn_is_over(&mexican_train, &Action::play_unchecked(0, double, None)));
+        assert!(turn_is_over(&mexican_train, &Action::play_unchecked(0, tile, Some(3))));
+        assert!(turn_is_over(&mexican_train, &Action::pass(0)));
+    }
+
+    #[test]
+    fn test_round_result() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        let alice = hand_of(&[(1, 2)]);
+        let bob = hand_of(&[(3, 4)]);
+        let empty = Hand::new();
+
+        // Not over
+        assert_eq!(round_result(&configuration, &state, &[&alice, &bob]), None);
+
+        // Domino
+        assert_eq!(round_result(&configuration, &state, &[&alice, &empty]), Some(Some(1)));
+
+        // Blocked: the lowest hand wins, and a tie is a draw
+        state.pass();
+        state.pass();
+        assert_eq!(round_result(&configuration, &state, &[&alice, &bob]), Some(Some(0)));
+        assert_eq!(round_result(&configuration, &state, &[&bob, &alice]), Some(Some(1)));
+        let tied = hand_of(&[(0, 3)]);
+        assert_eq!(round_result(&configuration, &state, &[&alice, &tied]), Some(None));
+
+        // With partners, the team with the fewest pips wins a blocked round, even without the lowest hand
+        let configuration = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..4 {
+            state.pass();
+        }
+        let high = hand_of(&[(5, 6)]);
+        assert_eq!(round_result(&configuration, &state, &[&bob, &alice, &high, &tied]), Some(Some(1)));
+        assert_eq!(round_result(&configuration, &state, &[&alice, &tied, &tied, &alice]), Some(None));
+        let lowest = hand_of(&[(0, 1)]);
+        assert_eq!(round_result(&configuration, &state, &[&alice, &lowest, &tied, &high]), Some(Some(0)));
+    }
+
+    #[test]
+    fn test_round_points() {
+        let alice = hand_of(&[(1, 2)]);
+        let bob = hand_of(&[(3, 4), (0, 6)]);
+        let carol = Hand::new();
+        let configuration = Configuration::new(3, Variation::Traditional, 6, 7);
+        assert_eq!(round_points(&configuration, &[&alice, &bob, &carol], 2), 16);
+        assert_eq!(round_points(&configuration, &[&alice, &bob, &carol], 0), 13);
+
+        // The points in the winner's partner's hand are not scored
+        let configuration = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+        let dave = hand_of(&[(5, 5)]);
+        assert_eq!(round_points(&configuration, &[&alice, &bob, &carol, &dave], 2), 23);
+        assert_eq!(round_points(&configuration, &[&alice, &bob, &carol, &dave], 1), 3);
+    }
+
+    #[test]
+    fn test_forfeit_result() {
+        let alice = hand_of(&[(1, 2)]);
+        let bob = hand_of(&[(3, 4)]);
+        let carol = hand_of(&[(0, 3)]);
+
+        // The player who forfeits loses even with the lowest hand
+        let configuration = Configuration::new(3, Variation::Traditional, 6, 7);
+        assert_eq!(forfeit_result(&configuration, &[&alice, &bob], 0), Some(1));
+        assert_eq!(forfeit_result(&configuration, &[&alice, &bob, &carol], 1), None);
+        assert_eq!(forfeit_result(&configuration, &[&alice, &bob, &carol], 2), Some(0));
+
+        // So does their partner
+        let configuration = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+        let dave = hand_of(&[(5, 6)]);
+        assert_eq!(forfeit_result(&configuration, &[&alice, &bob, &carol, &dave], 2), Some(1));
+    }
+}
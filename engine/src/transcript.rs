@@ -0,0 +1,337 @@
+//! Game transcripts
+//!
+//! This module records complete games and checks that later runs of the same games turn out the same way, so that a bug in the
+//! rules can be pinned down with a regression test once it is fixed. A transcript holds the configuration, the seed of the deal,
+//! every action, the fingerprint of the state after each action, and the final scores and winner. It is stored as pretty-printed
+//! JSON, in the same form every time, so that a change to a transcript shows up as a readable diff.
+//!
+//! A test records a game and calls `Transcript::check` with the path of its transcript. The first run writes the file. Later runs
+//! compare the game with the file, and fail at the first difference. Setting the `UPDATE_TRANSCRIPTS` environment variable writes
+//! the file again instead, for changes that are meant to alter the game.
+//!
+//! The module is only compiled with the `test-support` feature, so other crates enable it in their `dev-dependencies`.
+
+use std::path::Path;
+use std::{env, fmt, fs, io};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use dominoes_state::{Action, History};
+use hidden_game_player::State;
+use rules::Configuration;
+
+use crate::{Game, GameError, Seat};
+
+/// The environment variable that makes `Transcript::check` write the transcript instead of comparing it
+pub const UPDATE_TRANSCRIPTS: &str = "UPDATE_TRANSCRIPTS";
+
+/// The record of a complete game
+#[derive(Serialize, Deserialize)]
+pub struct Transcript {
+    /// What the game is meant to exercise
+    pub description: String,
+    /// The game configuration
+    pub configuration: Configuration,
+    /// The seed of the deal (see `Game::with_seed`)
+    pub seed: u64,
+    /// Every action taken, in order
+    pub history: History,
+    /// The fingerprint of the state after each action
+    pub fingerprints: Vec<u64>,
+    /// The pips left in each hand at the end, by player ID
+    pub scores: Vec<u32>,
+    /// The winner, or `None` if the game was blocked without one
+    pub winner: Option<u8>,
+}
+
+/// A transcript could not be read or written, or a game did not turn out as recorded
+#[derive(Debug, Error)]
+pub enum TranscriptError {
+    /// The file could not be read or written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The file is not a transcript
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The transcript does not have a fingerprint for each action
+    #[error("There are {actions} actions but {fingerprints} fingerprints")]
+    Malformed { actions: usize, fingerprints: usize },
+    /// The games do not have the same configuration or seed
+    #[error("The game is set up differently: expected {expected}, got {actual}")]
+    Setup { expected: String, actual: String },
+    /// An action was not accepted
+    #[error("Action {index} ({action}) was rejected: {error}")]
+    Rejected { index: usize, action: String, error: GameError },
+    /// The game ended before all of the actions were taken
+    #[error("The game ended before action {index}")]
+    EndedEarly { index: usize },
+    /// The game is not over after all of the actions were taken
+    #[error("The game did not end after action {index}")]
+    NotOver { index: usize },
+    /// A different action was taken
+    #[error("Action {index} differs: expected {expected}, got {actual}")]
+    Action { index: usize, expected: String, actual: String },
+    /// The state after an action is different
+    #[error("The fingerprint after action {index} ({action}) differs: expected {expected}, got {actual}")]
+    Fingerprint { index: usize, action: String, expected: u64, actual: u64 },
+    /// The game has a different number of actions
+    #[error("Expected {expected} actions, got {actual}")]
+    Length { expected: usize, actual: usize },
+    /// The game has a different winner
+    #[error("The winner differs: expected {expected:?}, got {actual:?}")]
+    Winner { expected: Option<u8>, actual: Option<u8> },
+    /// The hands have different scores at the end
+    #[error("The scores differ: expected {expected:?}, got {actual:?}")]
+    Scores { expected: Vec<u32>, actual: Vec<u32> },
+}
+
+impl Transcript {
+    /// Records a game played from a seeded deal
+    ///
+    /// Every seat is external, and `choose` chooses the action of the player whose turn it is until the game is over.
+    ///
+    /// # Arguments
+    /// * `description` - What the game is meant to exercise
+    /// * `configuration` - Game rules and settings
+    /// * `seed` - The seed of the deal
+    /// * `choose` - Returns the next action, given the game
+    ///
+    /// # Returns
+    /// The transcript, or an error if an action was not accepted
+    pub fn record(
+        description: &str,
+        configuration: &Configuration,
+        seed: u64,
+        mut choose: impl FnMut(&Game) -> Action,
+    ) -> Result<Self, TranscriptError> {
+        let mut game = new_game(configuration, seed);
+        let mut fingerprints = Vec::new();
+        while !game.is_over() {
+            let action = choose(&game);
+            submit(&mut game, fingerprints.len(), action)?;
+            fingerprints.push(game.state().fingerprint());
+        }
+
+        let mut history = History::new();
+        for action in game.history().get_actions() {
+            history.add_action(action.clone());
+        }
+        Ok(Self {
+            description: description.to_string(),
+            configuration: configuration.clone(),
+            seed,
+            history,
+            fingerprints,
+            scores: scores(&game),
+            winner: game.state().winner,
+        })
+    }
+
+    /// Reads a transcript from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TranscriptError> {
+        let transcript: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let actions = transcript.history.get_actions().len();
+        let fingerprints = transcript.fingerprints.len();
+        if actions != fingerprints {
+            return Err(TranscriptError::Malformed { actions, fingerprints });
+        }
+        Ok(transcript)
+    }
+
+    /// Writes the transcript to a file, replacing it if it exists
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TranscriptError> {
+        Ok(fs::write(path, self.to_string())?)
+    }
+
+    /// Plays the recorded actions again, and checks that the game turns out as recorded
+    ///
+    /// # Returns
+    /// An error describing the first difference, if there is one
+    pub fn replay(&self) -> Result<(), TranscriptError> {
+        let mut game = new_game(&self.configuration, self.seed);
+        for (index, (action, &expected)) in self.history.get_actions().iter().zip(&self.fingerprints).enumerate() {
+            if game.is_over() {
+                return Err(TranscriptError::EndedEarly { index });
+            }
+            submit(&mut game, index, action.clone())?;
+            let actual = game.state().fingerprint();
+            if actual != expected {
+                return Err(TranscriptError::Fingerprint { index, action: action.to_string(), expected, actual });
+            }
+        }
+
+        if !game.is_over() {
+            return Err(TranscriptError::NotOver { index: self.fingerprints.len() });
+        }
+        self.compare_result(&scores(&game), game.state().winner)
+    }
+
+    /// Compares this transcript with one that was recorded earlier
+    ///
+    /// # Arguments
+    /// * `expected` - The transcript that was recorded earlier
+    ///
+    /// # Returns
+    /// An error describing the first difference, if there is one. The descriptions may differ.
+    pub fn compare(&self, expected: &Transcript) -> Result<(), TranscriptError> {
+        let setup = |transcript: &Transcript| {
+            format!("{} with seed {}", serde_json::to_string(&transcript.configuration).unwrap_or_default(), transcript.seed)
+        };
+        if setup(self) != setup(expected) {
+            return Err(TranscriptError::Setup { expected: setup(expected), actual: setup(self) });
+        }
+
+        let actual_actions = self.history.get_actions();
+        let expected_actions = expected.history.get_actions();
+        let steps = actual_actions.iter().zip(&self.fingerprints).zip(expected_actions.iter().zip(&expected.fingerprints));
+        for (index, ((actual, &actual_fingerprint), (action, &expected_fingerprint))) in steps.enumerate() {
+            if actual != action {
+                return Err(TranscriptError::Action { index, expected: action.to_string(), actual: actual.to_string() });
+            }
+            if actual_fingerprint != expected_fingerprint {
+                return Err(TranscriptError::Fingerprint {
+                    index,
+                    action: action.to_string(),
+                    expected: expected_fingerprint,
+                    actual: actual_fingerprint,
+                });
+            }
+        }
+        if actual_actions.len() != expected_actions.len() {
+            return Err(TranscriptError::Length { expected: expected_actions.len(), actual: actual_actions.len() });
+        }
+        expected.compare_result(&self.scores, self.winner)
+    }
+
+    /// Compares this transcript with the one in a file, or writes it to the file
+    ///
+    /// The transcript is written if the file does not exist or if the `UPDATE_TRANSCRIPTS` environment variable is set.
+    ///
+    /// # Arguments
+    /// * `path` - The file holding the transcript that was recorded earlier
+    ///
+    /// # Returns
+    /// An error describing the first difference, if there is one
+    pub fn check(&self, path: impl AsRef<Path>) -> Result<(), TranscriptError> {
+        let path = path.as_ref();
+        if env::var_os(UPDATE_TRANSCRIPTS).is_some() || !path.exists() {
+            return self.save(path);
+        }
+        self.compare(&Transcript::load(path)?)
+    }
+
+    // Returns an error if the final scores or winner are not the ones in this transcript
+    fn compare_result(&self, scores: &[u32], winner: Option<u8>) -> Result<(), TranscriptError> {
+        if winner != self.winner {
+            return Err(TranscriptError::Winner { expected: self.winner, actual: winner });
+        }
+        if scores != self.scores {
+            return Err(TranscriptError::Scores { expected: self.scores.clone(), actual: scores.to_vec() });
+        }
+        Ok(())
+    }
+}
+
+/// Displays the transcript as pretty-printed JSON ending with a newline, which is how it is stored
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string_pretty(self).map_err(|_| fmt::Error)?;
+        writeln!(f, "{json}")
+    }
+}
+
+// Creates a game with only external seats, and deals the hands
+fn new_game(configuration: &Configuration, seed: u64) -> Game {
+    let seats = (0..configuration.num_players()).map(|_| Seat::External).collect();
+    let mut game = Game::with_seed(configuration, seats, seed);
+    game.step();
+    game
+}
+
+// Submits the action with the given index, converting a rejection into an error
+fn submit(game: &mut Game, index: usize, action: Action) -> Result<(), TranscriptError> {
+    let description = action.to_string();
+    game.submit(action).map(|_| ()).map_err(|error| TranscriptError::Rejected { index, action: description, error })
+}
+
+// Returns the pips left in each hand, by player ID
+fn scores(game: &Game) -> Vec<u32> {
+    (0..game.configuration().num_players() as u8).map(|player_id| game.hand(player_id).score()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::Variation;
+
+    // Records a two-player game in which each player takes its first legal action
+    fn first_actions(seed: u64) -> Transcript {
+        Transcript::record("first legal actions", &Configuration::default(), seed, |game| {
+            game.legal_actions(game.state().whose_turn)[0].clone()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let transcript = first_actions(1);
+        assert!(!transcript.history.get_actions().is_empty());
+        assert_eq!(transcript.history.get_actions().len(), transcript.fingerprints.len());
+        assert_eq!(transcript.scores.len(), 2);
+        transcript.replay().unwrap();
+        transcript.compare(&first_actions(1)).unwrap();
+    }
+
+    #[test]
+    fn test_replay_detects_a_different_game() {
+        let mut transcript = first_actions(1);
+        transcript.fingerprints[3] ^= 1;
+        assert!(matches!(transcript.replay(), Err(TranscriptError::Fingerprint { index: 3, .. })));
+
+        let mut transcript = first_actions(1);
+        transcript.winner = transcript.winner.map(|winner| winner + 1).or(Some(0));
+        assert!(matches!(transcript.replay(), Err(TranscriptError::Winner { .. })));
+
+        let mut transcript = first_actions(1);
+        transcript.history.pop_action();
+        transcript.fingerprints.pop();
+        assert!(matches!(transcript.replay(), Err(TranscriptError::NotOver { .. })));
+    }
+
+    #[test]
+    fn test_compare_detects_a_different_game() {
+        let transcript = first_actions(1);
+        assert!(matches!(first_actions(2).compare(&transcript), Err(TranscriptError::Setup { .. })));
+
+        let other = Transcript::record("last legal actions", &Configuration::default(), 1, |game| {
+            game.legal_actions(game.state().whose_turn).last().unwrap().clone()
+        })
+        .unwrap();
+        assert!(matches!(other.compare(&transcript), Err(TranscriptError::Action { .. })));
+
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let other = Transcript::record("first legal actions", &configuration, 1, |game| {
+            game.legal_actions(game.state().whose_turn)[0].clone()
+        })
+        .unwrap();
+        assert!(matches!(other.compare(&transcript), Err(TranscriptError::Setup { .. })));
+    }
+
+    #[test]
+    fn test_check_writes_then_compares() {
+        let path = env::temp_dir().join(format!("dominoes-transcript-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let transcript = first_actions(3);
+        transcript.check(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), transcript.to_string());
+
+        let loaded = Transcript::load(&path).unwrap();
+        assert_eq!(loaded.description, transcript.description);
+        loaded.replay().unwrap();
+        if env::var_os(UPDATE_TRANSCRIPTS).is_none() {
+            assert!(matches!(first_actions(4).check(&path), Err(TranscriptError::Setup { .. })));
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+//! Views for front ends
+//!
+//! This module defines how moves and states are shown to front ends written in other languages, such as the JavaScript bindings
+//! (see the `dominoes-wasm` crate) and the C bindings (see the `dominoes-ffi` crate). Views are passed to them as JSON. Moves can
+//! also be written in a notation that people can read, e.g. `play 3|6 on 6`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use dominoes_state::Action;
+use rules::{Layout, Tile};
+
+use crate::{Game, ParseMoveError};
+
+/// A move, as seen by a front end
+///
+/// A draw does not name the tile because the front end does not know which tile is next in the boneyard. In notation, a move is
+/// `play <TILE>` for the first tile, `play <TILE> on <END>` for the others, `draw`, or `pass`.
+///
+/// # Examples
+/// ```rust
+/// # use engine::{Game, Move, Seat};
+/// # use rules::{Configuration, Tile};
+///
+/// let configuration = Configuration::default();
+/// let mut game = Game::with_seed(&configuration, vec![Seat::External, Seat::External], 1);
+/// game.step();
+/// let player_id = game.state().whose_turn;
+/// let first = Move::from(&game.legal_actions(player_id)[0]);
+/// assert_eq!(first.to_action(&game), Some(game.legal_actions(player_id)[0].clone()));
+/// assert_eq!(serde_json::from_str::<Move>(r#"{"kind":"draw"}"#).unwrap(), Move::Draw);
+/// assert_eq!("play 3|6 on 6".parse::<Move>(), Ok(Move::Play { tile: Tile::from((3, 6)), end: Some(6) }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Move {
+    /// Plays a tile from the hand on an open end (`None` for the first tile)
+    Play { tile: Tile, end: Option<u8> },
+    /// Draws a tile from the boneyard
+    Draw,
+    /// Passes the turn
+    Pass,
+}
+
+impl From<&Action> for Move {
+    fn from(action: &Action) -> Self {
+        match action.tile_played {
+            Some((tile, end)) => Move::Play { tile, end },
+            None if action.is_draw() => Move::Draw,
+            None => Move::Pass,
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Play { tile, end: None } => write!(f, "play {tile}"),
+            Move::Play { tile, end: Some(end) } => write!(f, "play {tile} on {end}"),
+            Move::Draw => write!(f, "draw"),
+            Move::Pass => write!(f, "pass"),
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<String> = s.split_whitespace().map(str::to_lowercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let parsed = match words.as_slice() {
+            ["draw"] => Some(Move::Draw),
+            ["pass"] => Some(Move::Pass),
+            ["play", tile] => tile.parse::<Tile>().ok().map(|tile| Move::Play { tile, end: None }),
+            ["play", tile, "on", end] => {
+                tile.parse::<Tile>().ok().zip(end.parse().ok()).map(|(tile, end)| Move::Play { tile, end: Some(end) })
+            }
+            _ => None,
+        };
+        parsed.ok_or_else(|| ParseMoveError(s.to_string()))
+    }
+}
+
+impl Move {
+    /// Returns the action that makes this move for the player whose turn it is
+    ///
+    /// # Arguments
+    /// * `game` - The game
+    ///
+    /// # Returns
+    /// The action, or `None` if the move is not legal
+    pub fn to_action(self, game: &Game) -> Option<Action> {
+        game.legal_actions(game.state().whose_turn).into_iter().find(|action| Move::from(action) == self)
+    }
+}
+
+/// The state of a game, as seen by a front end
+///
+/// The view includes the hands of all players, so the front end decides what to show to whom.
+#[derive(Debug, Serialize)]
+pub struct StateView<'a> {
+    /// The layout
+    pub layout: &'a Layout,
+    /// The tiles in each player's hand
+    pub hands: Vec<&'a [Tile]>,
+    /// The number of tiles in the boneyard
+    pub boneyard_count: usize,
+    /// Whose turn it is
+    pub whose_turn: u8,
+    /// Whether the game is over
+    pub game_is_over: bool,
+    /// The ID of the winner, or `None` if the game is not over or is a draw
+    pub winner: Option<u8>,
+}
+
+impl<'a> StateView<'a> {
+    /// Creates a view of the state of a game
+    ///
+    /// # Arguments
+    /// * `game` - The game
+    pub fn new(game: &'a Game) -> Self {
+        let state = game.state();
+        Self {
+            layout: &state.layout,
+            hands: (0..game.configuration().num_players() as u8)
+                .map(|player_id| game.hand(player_id).tiles())
+                .collect(),
+            boneyard_count: state.boneyard.count(),
+            whose_turn: state.whose_turn,
+            game_is_over: state.game_is_over,
+            winner: state.winner,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_notation() {
+        let moves = [
+            ("play 6|6", Move::Play { tile: Tile::from((6, 6)), end: None }),
+            ("play 3|6 on 6", Move::Play { tile: Tile::from((3, 6)), end: Some(6) }),
+            ("draw", Move::Draw),
+            ("pass", Move::Pass),
+        ];
+        for (notation, expected) in moves {
+            assert_eq!(notation.parse::<Move>(), Ok(expected));
+            assert_eq!(expected.to_string(), notation);
+        }
+        assert_eq!("  Play 6|3   ON 3 ".parse::<Move>(), Ok(Move::Play { tile: Tile::from((3, 6)), end: Some(3) }));
+
+        for invalid in ["", "play", "play 6", "play 6|x", "play 99|0", "play 3|6 at 6", "play 3|6 on", "draw 6|6"] {
+            assert_eq!(invalid.parse::<Move>(), Err(ParseMoveError(invalid.to_string())), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Fuzzes the messages received from a network client
+//!
+//! Any message must be deserialized or rejected with an error, and the action requested in a message must be validated without
+//! panicking, whatever the tile and end.
+
+#![no_main]
+
+use dominoes_state::{Action, DominoesState, Hand};
+use game::protocol::{ActionRequest, ClientMessage};
+use libfuzzer_sys::fuzz_target;
+use rules::{Configuration, Tile};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(message) = serde_json::from_slice::<ClientMessage>(data) else {
+        return;
+    };
+
+    let json = serde_json::to_string(&message).expect("A message can always be serialized");
+    assert_eq!(serde_json::from_str::<ClientMessage>(&json).expect("A serialized message must deserialize"), message);
+
+    let ClientMessage::Action { action } = message else {
+        return;
+    };
+
+    // Validate the action in an empty position and in one with a tile played
+    let configuration = Configuration::default();
+    let mut state = DominoesState::new(&configuration);
+    let mut hand = Hand::new();
+    for values in [(6, 6), (3, 6), (2, 5)] {
+        hand.add_tile(Tile::from(values));
+    }
+    if let Some(action) = to_action(action, &state) {
+        let _ = action.validate(&state, &hand);
+    }
+
+    state.play_tile_unchecked(Tile::from((6, 6)), None);
+    hand.remove_tile(&Tile::from((6, 6)));
+    state.whose_turn = 0;
+    if let Some(action) = to_action(action, &state) {
+        let _ = action.validate(&state, &hand);
+    }
+});
+
+// Converts a request into the action of player 0, as the server does
+fn to_action(request: ActionRequest, state: &DominoesState) -> Option<Action> {
+    match request {
+        ActionRequest::Play { tile, end } => Some(Action::new(0, None, Some((tile, end)))),
+        ActionRequest::Draw => state.boneyard.peek().map(|&tile| Action::draw(0, tile)),
+        ActionRequest::Pass => Some(Action::pass(0)),
+    }
+}
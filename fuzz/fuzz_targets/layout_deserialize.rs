@@ -0,0 +1,40 @@
+//! Fuzzes the deserialization of a `Layout`
+//!
+//! Any JSON must be deserialized or rejected with an error, and a layout that is deserialized must be valid, so that it can be
+//! written, serialized, and extended without panicking.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rules::{Layout, Tile};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mut layout) = serde_json::from_slice::<Layout>(data) else {
+        return;
+    };
+
+    let _ = layout.to_string();
+    let _ = layout.to_tree();
+    for value in 0..=u8::MAX {
+        let _ = layout.open_count(value);
+        let _ = layout.get_nodes_with_open_end(value);
+    }
+
+    let json = serde_json::to_string(&layout).expect("A layout can always be serialized");
+    let reloaded: Layout = serde_json::from_str(&json).expect("A serialized layout must deserialize");
+    assert_eq!(reloaded.end_counts, layout.end_counts);
+
+    // Try to attach every tile to every node, then take the tiles back
+    let nodes = layout.nodes.len();
+    for ordinal in 0..=u8::MAX {
+        let Some(tile) = Tile::checked_new(ordinal) else {
+            break;
+        };
+        for parent in (0..=nodes).map(Some).chain([None]) {
+            if layout.attach(tile, parent).is_ok() {
+                assert_eq!(layout.detach_last(), Some(tile));
+            }
+        }
+    }
+    assert_eq!(layout.end_counts, reloaded.end_counts);
+});
@@ -0,0 +1,28 @@
+//! Fuzzes the layout parser
+//!
+//! Any text must be parsed or rejected with an error, and a layout that is parsed must be written in a form that parses to the
+//! same layout.
+
+#![no_main]
+
+use game::layout_parser::{parse, parse_hand, parse_layout};
+use libfuzzer_sys::fuzz_target;
+use rules::{Configuration, Variation};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = parse(input);
+    let _ = parse_hand(input);
+
+    for configuration in [Configuration::default(), Configuration::new(2, Variation::Traditional, 12, 12)] {
+        if let Ok(layout) = parse_layout(input, &configuration) {
+            let text = layout.to_string();
+            let reparsed = parse_layout(&text, &configuration).expect("A layout's text must parse");
+            assert_eq!(reparsed.to_string(), text);
+            assert_eq!(reparsed.end_counts, layout.end_counts);
+        }
+    }
+});
@@ -0,0 +1,238 @@
+//! Batches of games
+//!
+//! A `BatchRunner` plays a batch of independent games between computer players on several threads and collects their results. It
+//! is the loop shared by everything that plays many games, e.g. the `simulate` command and `simulation::estimate_win_rate`. Game
+//! `i` of a batch is dealt with the seed `seed + i`, and the results are returned in that order no matter which thread played each
+//! game, so a batch can be repeated. The seed of a game also seeds the random choices of its players (see `rules::random`).
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
+
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+
+use dominoes_state::Hand;
+use engine::{Game, Seat, round_points};
+use player::{PlayerFactory, PlayerSpec};
+use rules::Configuration;
+
+use crate::bot_player::BotPlayer;
+use crate::error::GameError;
+use crate::simulation::{GameRecord, WinStats};
+
+// A function that reports the progress of a batch
+type ProgressFn<'a> = Box<dyn Fn(&GameRecord, u32) + Send + Sync + 'a>;
+
+/// The results of a batch of games
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResults {
+    /// The result of each game, in the order of their seeds
+    pub records: Vec<GameRecord>,
+    /// The number of games won by each player and the number drawn
+    pub stats: WinStats,
+}
+
+/// Plays batches of games between computer players in parallel
+///
+/// # Examples
+/// ```rust
+/// # use game::batch::BatchRunner;
+/// # use player::PlayerSpec;
+/// # use rules::Configuration;
+///
+/// let runner = BatchRunner::new(&Configuration::default(), vec![PlayerSpec::Greedy, PlayerSpec::Mcts { iterations: 10 }])
+///     .unwrap()
+///     .with_threads(2)
+///     .on_progress(|record, done| println!("Game {done} (seed {}) was won by {:?}", record.seed, record.winner));
+/// let results = runner.run(42, 4);
+/// assert_eq!(results.records.len(), 4);
+/// assert_eq!(results.records[1].seed, 43);
+/// assert_eq!(results.stats.wins.iter().sum::<u32>() + results.stats.draws, 4);
+/// ```
+pub struct BatchRunner<'a> {
+    /// The configuration of every game
+    configuration: Configuration,
+    /// The players, by player ID
+    players: Vec<PlayerSpec>,
+    /// The number of threads, or `None` to use all available cores
+    threads: Option<usize>,
+    /// Called after each game with its result and the number of games finished so far
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> BatchRunner<'a> {
+    /// Creates a runner for games between computer players
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings
+    /// * `players` - The players, by player ID. There must be one for each player in the configuration, and none can be human.
+    ///
+    /// # Returns
+    /// The runner, or an error if the players are not valid
+    pub fn new(configuration: &Configuration, players: Vec<PlayerSpec>) -> Result<Self, GameError> {
+        if players.len() != configuration.num_players() {
+            return Err(GameError::PlayerCount { expected: configuration.num_players(), found: players.len() });
+        }
+        if let Some(human) = players.iter().find(|spec| spec.is_human()) {
+            return Err(GameError::HumanPlayer(human.clone()));
+        }
+        Ok(Self { configuration: configuration.clone(), players, threads: None, progress: None })
+    }
+
+    /// Sets the number of threads that play the games. By default, all available cores are used.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Sets a function that is called after each game with its result and the number of games finished so far
+    ///
+    /// The function is called from the threads that play the games, so the games are not reported in order.
+    pub fn on_progress(mut self, progress: impl Fn(&GameRecord, u32) + Send + Sync + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Returns the players, by player ID
+    pub fn players(&self) -> &[PlayerSpec] {
+        &self.players
+    }
+
+    /// Plays a batch of games
+    ///
+    /// # Arguments
+    /// * `seed` - The seed of the first deal
+    /// * `num_games` - The number of games to play
+    ///
+    /// # Returns
+    /// The result of each game and the totals
+    ///
+    /// # Panics
+    /// Panics if the thread pool cannot be created
+    pub fn run(&self, seed: u64, num_games: u32) -> BatchResults {
+        let finished = AtomicU32::new(0);
+        let play = || -> Vec<GameRecord> {
+            (0..num_games)
+                .into_par_iter()
+                .map(|index| {
+                    let record = self.play_game(seed.wrapping_add(u64::from(index)));
+                    if let Some(progress) = &self.progress {
+                        progress(&record, finished.fetch_add(1, Ordering::Relaxed) + 1);
+                    }
+                    record
+                })
+                .collect()
+        };
+        let records = match self.threads {
+            Some(threads) => ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("The thread pool should be created")
+                .install(play),
+            None => play(),
+        };
+
+        let mut stats = WinStats::new(self.players.len());
+        for record in &records {
+            stats.add(record);
+        }
+        BatchResults { records, stats }
+    }
+
+    /// Plays a single game on the current thread
+    ///
+    /// # Arguments
+    /// * `seed` - The seed of the deal
+    ///
+    /// # Returns
+    /// The result of the game. The progress function is not called.
+    pub fn play_game(&self, seed: u64) -> GameRecord {
+        // The seed also seeds the players, so the whole game can be reproduced
+        let configuration = self.configuration.clone().with_seed(seed);
+        let factory = PlayerFactory::new(&configuration).with_remote(|player_id, configuration, address, name| {
+            Box::new(BotPlayer::connect(player_id, configuration, address, name))
+        });
+        let seats = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(player_id, spec)| Seat::Player(factory.create(player_id as u8, spec, &spec.to_string())))
+            .collect();
+        let mut game = Game::with_seed(&configuration, seats, seed);
+        let start = Instant::now();
+        while !game.is_over() {
+            game.step();
+        }
+        let duration = start.elapsed();
+
+        let num_players = self.players.len() as u8;
+        let winner = game.state().winner;
+        let mut scores = vec![0; num_players as usize];
+        if let Some(player_id) = winner {
+            let hands: Vec<&Hand> = (0..num_players).map(|player_id| game.hand(player_id)).collect();
+            scores[player_id as usize] = round_points(&configuration, &hands, player_id);
+        }
+
+        GameRecord {
+            seed,
+            players: (0..num_players).map(|player_id| game.player_name(player_id)).collect(),
+            winner,
+            scores,
+            moves: game.history().get_actions().len(),
+            duration_ms: duration.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::SkillLevel;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_batch_runner_run() {
+        let configuration = Configuration::default();
+        let seen = Mutex::new(Vec::new());
+        let runner = BatchRunner::new(&configuration, vec![PlayerSpec::Greedy, PlayerSpec::Greedy])
+            .unwrap()
+            .with_threads(3)
+            .on_progress(|record, finished| seen.lock().unwrap().push((record.seed, finished)));
+        let results = runner.run(10, 6);
+
+        let seeds: Vec<u64> = results.records.iter().map(|record| record.seed).collect();
+        assert_eq!(seeds, [10, 11, 12, 13, 14, 15]);
+        assert_eq!(results.stats.num_games, 6);
+        assert_eq!(results.stats.wins.iter().sum::<u32>() + results.stats.draws, 6);
+
+        // Every game is reported once, and the counts of finished games go from 1 to 6
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort();
+        assert_eq!(seen.iter().map(|&(seed, _)| seed).collect::<Vec<_>>(), seeds);
+        let mut counts: Vec<u32> = seen.iter().map(|&(_, finished)| finished).collect();
+        counts.sort();
+        assert_eq!(counts, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_batch_runner_is_repeatable() {
+        let configuration = Configuration::default();
+        // The random player's choices are seeded too
+        let players = vec![PlayerSpec::Level(SkillLevel::Beginner), PlayerSpec::Greedy];
+        let runner = BatchRunner::new(&configuration, players).unwrap();
+        let a = runner.run(5, 4);
+        let b = runner.with_threads(1).run(5, 4);
+        let games = |results: &BatchResults| {
+            results.records.iter().map(|record| (record.winner, record.scores.clone(), record.moves)).collect::<Vec<_>>()
+        };
+        assert_eq!(games(&a), games(&b));
+        assert_eq!(a.stats, b.stats);
+    }
+
+    #[test]
+    fn test_batch_runner_rejects_invalid_players() {
+        let configuration = Configuration::default();
+        assert!(BatchRunner::new(&configuration, vec![PlayerSpec::Greedy]).is_err());
+        assert!(BatchRunner::new(&configuration, vec![PlayerSpec::Greedy, PlayerSpec::Human { name: None }]).is_err());
+    }
+}
@@ -10,6 +10,8 @@ const VARIATION_NAMES: &[(&str, Variation)] = &[
     ("bergen", Variation::Bergen),
     ("blind", Variation::Blind),
     ("fiveup", Variation::FiveUp),
+    ("mexicantrain", Variation::MexicanTrain),
+    ("chickenfoot", Variation::ChickenFoot),
 ];
 
 fn main() {
@@ -72,7 +74,7 @@ fn main() {
     let starting_hand_size = 7; // Doesn't matter for layout generation
 
     if set_id == u8::MAX {
-        set_id = Configuration::DEFAULT_SET_ID;
+        set_id = Configuration::default_set_id(variation);
     } else if set_id > rules::MAX_PIPS {
         eprintln!("Error: set must be between 0 and {} (inclusive)", rules::MAX_PIPS);
         std::process::exit(1);
@@ -118,7 +120,7 @@ fn generate_random_layout(configuration: &Configuration, max_size: usize, priori
     // Draw from the boneyard until a double is found and play it.
     while let Some(tile) = boneyard.draw() {
         if tile.is_double() {
-            layout.attach(tile, None);
+            layout.attach_unchecked(tile, None);
             size += 1;
             break;
         } else {
@@ -142,7 +144,7 @@ fn generate_random_layout(configuration: &Configuration, max_size: usize, priori
 
             if let Some((tile, index)) = found {
                 // Attach the tile to the layout at the found index
-                layout.attach(tile, Some(index));
+                layout.attach_unchecked(tile, Some(index));
                 size += 1;
                 // Remove the tile from the hand
                 find_and_remove_tile(&mut hand, tile);
@@ -193,6 +195,6 @@ fn find_and_remove_tile(hand: &mut Vec<Tile>, tile: Tile) {
 /// Checks both ends of the tile for a match with any open end in the layout.
 fn can_attach(layout: &Layout, tile: &Tile) -> Option<usize> {
     let (a, b) = tile.into();
-    layout.get_nodes_with_open_end(a).first().copied()
-        .or_else(|| layout.get_nodes_with_open_end(b).first().copied())
+    layout.nodes_with_open_end(a).next()
+        .or_else(|| layout.nodes_with_open_end(b).next())
 }
@@ -0,0 +1,302 @@
+//! Bot player implementation
+//!
+//! This module provides a player whose decisions are made by another program, a bot, which may be written in any language. The
+//! bot is told the state of the game whenever it is its turn, and answers with the action it takes. Messages are JSON objects, one
+//! per line, sent over a connection (see `Connection`), which is usually the standard input and output of the bot. A turn goes
+//! like this:
+//!
+//! 1. The player sends `your_turn` with the bot's ID, its hand, and the state of the game without the hidden tiles (see
+//!    `DominoesState::without_hidden_info`).
+//! 2. The bot answers with an action, in the same form as a network client (see `ActionRequest`).
+//! 3. An illegal action is answered with `rejected`, and the bot must send another.
+//!
+//! ```text
+//! <-- {"type":"your_turn","player_id":1,"hand":[[2,5],[6,6]],"state":{...}}
+//! --> {"kind":"play","tile":[5,6],"end":6}
+//! <-- {"type":"rejected","reason":"..."}
+//! --> {"kind":"draw"}
+//! ```
+
+use std::io;
+use std::process::{Child, Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use dominoes_state::{Action, DominoesState, Hand};
+use player::Player;
+use rules::{Configuration, Tile};
+
+use crate::protocol::{ActionRequest, Connection};
+use crate::remote_player::take_action;
+
+/// A message sent from the game to a bot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    /// It is the bot's turn to act. The state has no hands and the boneyard is sorted, so the bot's hand is given separately.
+    YourTurn { player_id: u8, hand: Vec<Tile>, state: DominoesState },
+    /// The bot's last action was not legal
+    Rejected { reason: String },
+}
+
+/// A player controlled by another program
+///
+/// As with a network client (see `RemotePlayer`), the hand is held here, so every action requested by the bot can be checked with
+/// `Action::validate` before it is accepted. Since the `Player` methods cannot fail, an error in the connection is recorded
+/// instead, and the player passes. The error can be retrieved with `take_error`.
+///
+/// # Examples
+/// ```rust
+/// # use game::bot_player::BotPlayer;
+/// # use game::protocol::Connection;
+/// # use player::Player;
+/// # use rules::Configuration;
+///
+/// // A bot that always passes
+/// let answers = "{\"kind\":\"pass\"}\n".repeat(10);
+/// let connection = Connection::with_streams(std::io::Cursor::new(answers), std::io::sink());
+/// let bot = BotPlayer::new(1, &Configuration::default(), "Passer", connection);
+/// assert_eq!(bot.name(), "Passer");
+/// ```
+#[derive(Debug)]
+pub struct BotPlayer {
+    /// Unique identifier for this player in the game
+    player_id: u8,
+    /// The connection to the bot
+    connection: Connection,
+    /// The bot's process, if it was started by this player
+    child: Option<Child>,
+    /// The tiles currently held by this player
+    hand: Hand,
+    /// Display name for this player
+    name: String,
+    /// The first error encountered, if any
+    error: Option<io::Error>,
+}
+
+impl BotPlayer {
+    /// Creates a player controlled by a bot on the other end of a connection
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `_configuration` - Game rules and settings, which this player does not need
+    /// * `name` - Display name for this player
+    /// * `connection` - The connection to the bot
+    pub fn new(player_id: u8, _configuration: &Configuration, name: &str, connection: Connection) -> Self {
+        Self {
+            player_id,
+            connection,
+            child: None,
+            hand: Hand::new(),
+            name: name.to_string(),
+            error: None,
+        }
+    }
+
+    /// Starts a bot and creates a player controlled by it
+    ///
+    /// The bot's standard input and output are connected to the player, and its standard error is left alone, so a bot can write
+    /// diagnostics there. The bot is stopped when the player is dropped.
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `configuration` - Game rules and settings
+    /// * `name` - Display name for this player
+    /// * `command` - The command that starts the bot
+    ///
+    /// # Returns
+    /// The player, or an error if the bot cannot be started
+    pub fn spawn(player_id: u8, configuration: &Configuration, name: &str, command: &mut Command) -> io::Result<Self> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(io::Error::other("The bot's standard input and output are not available"));
+        };
+        let mut player = Self::new(player_id, configuration, name, Connection::with_streams(stdout, stdin));
+        player.child = Some(child);
+        Ok(player)
+    }
+
+    /// Connects to a bot listening on the network and creates a player controlled by it
+    ///
+    /// This is how a seat given by `remote:<address>` is played (see `PlayerSpec::Remote`). If the bot cannot be reached, the
+    /// error is recorded as for any other error in the connection, and the player passes.
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `configuration` - Game rules and settings
+    /// * `address` - The address of the bot, e.g. "localhost:7878"
+    /// * `name` - Display name for this player
+    pub fn connect(player_id: u8, configuration: &Configuration, address: &str, name: &str) -> Self {
+        match Connection::connect(address) {
+            Ok(connection) => Self::new(player_id, configuration, name, connection),
+            Err(e) => {
+                let mut player = Self::new(player_id, configuration, name, Connection::with_streams(io::empty(), io::sink()));
+                player.error = Some(e);
+                player
+            }
+        }
+    }
+
+    /// Returns the error that occurred, if any, and clears it
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    // Asks the bot for an action until a legal one is received
+    fn receive_action(&mut self, state: &DominoesState) -> io::Result<Action> {
+        self.connection.send(&BotMessage::YourTurn {
+            player_id: self.player_id,
+            hand: self.hand.tiles().to_vec(),
+            state: state.without_hidden_info(),
+        })?;
+        loop {
+            let request = self.connection.receive::<ActionRequest>()?;
+            let result = request
+                .to_action(self.player_id, state)
+                .and_then(|action| action.validate(state, &self.hand).map(|_| action));
+            match result {
+                Ok(action) => return Ok(action),
+                Err(e) => self.connection.send(&BotMessage::Rejected { reason: e.to_string() })?,
+            }
+        }
+    }
+}
+
+impl Drop for BotPlayer {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Player for BotPlayer {
+    fn reset(&mut self) {
+        self.hand = Hand::new();
+    }
+
+    fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+        self.hand = hand;
+    }
+
+    fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+        let mut new_state = state.clone();
+        if self.error.is_some() {
+            new_state.pass();
+            return (Action::pass(self.player_id), new_state);
+        }
+
+        let action = match self.receive_action(state) {
+            Ok(action) => action,
+            Err(e) => {
+                self.error = Some(e);
+                new_state.pass();
+                return (Action::pass(self.player_id), new_state);
+            }
+        };
+
+        take_action(&mut self.hand, &mut new_state, &action);
+        (action, new_state)
+    }
+
+    fn has_playable_tile(&self, state: &DominoesState) -> bool {
+        self.hand
+            .tiles()
+            .iter()
+            .any(|tile| state.can_play_tile(tile, None))
+    }
+
+    fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> u8 {
+        self.player_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Creates a bot that sends the given answers and holds the given tiles, on a layout with the double six
+    fn bot_with(answers: &[&str], tiles: &[(u8, u8)]) -> (BotPlayer, DominoesState) {
+        let configuration = Configuration::default();
+        let answers = answers.iter().map(|answer| format!("{answer}\n")).collect::<String>();
+        let connection = Connection::with_streams(Cursor::new(answers), io::sink());
+        let mut bot = BotPlayer::new(1, &configuration, "Bot", connection);
+        let mut hand = Hand::new();
+        tiles.iter().for_each(|&tile| hand.add_tile(Tile::from(tile)));
+        let mut state = DominoesState::new(&configuration);
+        bot.set_up_from(hand, &state);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+        (bot, state)
+    }
+
+    #[test]
+    fn test_bot_player_plays() {
+        // The first action is illegal, so the bot is asked again
+        let (mut bot, state) = bot_with(&[r#"{"kind":"pass"}"#, r#"{"kind":"play","tile":[5,6],"end":6}"#], &[(5, 6), (1, 2)]);
+        let (action, new_state) = bot.my_turn(&state);
+        assert_eq!(action, Action::play_unchecked(1, Tile::from((5, 6)), Some(6)));
+        assert_eq!(bot.hand().len(), 1);
+        assert!(new_state.can_play_tile(&Tile::from((1, 5)), Some(5)));
+        assert!(bot.take_error().is_none());
+    }
+
+    #[test]
+    fn test_bot_player_disconnects() {
+        // A bot that stops answering passes from then on
+        let (mut bot, state) = bot_with(&[], &[(5, 6)]);
+        assert_eq!(bot.my_turn(&state).0, Action::pass(1));
+        assert_eq!(bot.take_error().map(|e| e.kind()), Some(io::ErrorKind::UnexpectedEof));
+        assert_eq!(bot.my_turn(&state).0, Action::pass(1));
+    }
+
+    #[test]
+    fn test_bot_player_connect() {
+        // A bot listening on the network is asked for its actions over the connection
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let bot = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut connection = Connection::new(stream).unwrap();
+            assert!(matches!(connection.receive::<BotMessage>().unwrap(), BotMessage::YourTurn { player_id: 1, .. }));
+            connection.send(&ActionRequest::Draw).unwrap();
+        });
+        let configuration = Configuration::default();
+        let mut player = BotPlayer::connect(1, &configuration, &address, "Remote");
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+        assert!(player.my_turn(&state).0.tile_drawn.is_some());
+        assert!(player.take_error().is_none());
+        bot.join().unwrap();
+
+        // A bot that cannot be reached passes
+        let mut player = BotPlayer::connect(1, &configuration, "localhost:0", "Nobody");
+        assert_eq!(player.my_turn(&state).0, Action::pass(1));
+        assert!(player.take_error().is_some());
+    }
+
+    #[test]
+    fn test_bot_message_format() {
+        let state = DominoesState::new(&Configuration::default());
+        let message = BotMessage::YourTurn { player_id: 1, hand: vec![Tile::from((2, 5))], state };
+        let json = serde_json::to_string(&message).unwrap();
+        assert!(json.starts_with(r#"{"type":"your_turn","player_id":1,"hand":[[2,5]],"state":{"#), "{json}");
+    }
+}
@@ -0,0 +1,398 @@
+//! Network play client
+//!
+//! This module lets a person at a terminal play in a game hosted by a server on another machine. The client keeps its own copy of
+//! the layout and the player's hand, updated from the messages sent by the server, and it prompts the player whenever the server
+//! says that it is their turn.
+
+use std::io::{self, Write};
+use std::net::ToSocketAddrs;
+
+use dominoes_state::{DominoesState, Hand};
+use rules::{Commitment, Configuration, Reveal, Tile};
+
+use crate::protocol::{ActionRequest, ClientMessage, Connection, PROTOCOL_VERSION, ServerMessage, StateDelta};
+
+/// A client connected to a game server
+///
+/// # Examples
+/// ```no_run
+/// use game::client::Client;
+///
+/// let mut client = Client::connect("192.168.1.10:7878", "Alice").expect("Unable to join");
+/// let winner = client.run().expect("Network error");
+/// ```
+pub struct Client {
+    /// The connection to the server
+    connection: Connection,
+    /// The seat assigned by the server
+    player_id: u8,
+    /// Names of the players, by seat
+    players: Vec<String>,
+    /// The player's hand
+    hand: Hand,
+    /// A copy of the game state. Only the layout is meaningful because the boneyard is hidden.
+    state: DominoesState,
+    /// Number of tiles left in the boneyard
+    boneyard_count: usize,
+    /// The ID of the set, whose tiles must all be in the revealed order
+    set_id: u8,
+    /// The commitment to the order of the tiles and the hand that was dealt, checked when the order is revealed
+    commitment: Option<(Commitment, Vec<Tile>)>,
+    /// Every draw since the deal, in order, with the tile if the player drew it, checked when the order is revealed
+    draws: Vec<Option<Tile>>,
+}
+
+impl Client {
+    /// Connects to a server and joins its lobby
+    ///
+    /// # Arguments
+    /// * `address` - The address of the server
+    /// * `name` - The name to play under
+    ///
+    /// # Returns
+    /// The client, or an error if the server cannot be reached or refuses to seat the player
+    pub fn connect<A: ToSocketAddrs>(address: A, name: &str) -> io::Result<Self> {
+        let mut connection = Connection::connect(address)?;
+        connection.send(&ClientMessage::Join {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+        })?;
+
+        match connection.receive::<ServerMessage>()? {
+            ServerMessage::Seated { player_id, num_players, set_id, .. } => {
+                // The variation and hand size do not affect the layout
+                let configuration = Configuration::new(num_players, Configuration::DEFAULT_VARIATION, set_id, 0);
+                Ok(Self {
+                    connection,
+                    player_id,
+                    players: Vec::new(),
+                    hand: Hand::new(),
+                    state: DominoesState::new(&configuration),
+                    boneyard_count: 0,
+                    set_id,
+                    commitment: None,
+                    draws: Vec::new(),
+                })
+            }
+            ServerMessage::Rejected { reason } => Err(io::Error::other(reason)),
+            message => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected message: {message:?}"),
+            )),
+        }
+    }
+
+    /// Returns the seat assigned by the server
+    pub fn player_id(&self) -> u8 {
+        self.player_id
+    }
+
+    /// Plays the game until it is over
+    ///
+    /// # Returns
+    /// The ID of the winner, or `None` if the game is a draw
+    pub fn run(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.connection.receive::<ServerMessage>()? {
+                ServerMessage::Seated { .. } => {}
+                ServerMessage::Lobby { players } => {
+                    println!("Players waiting: {}", players.join(", "));
+                }
+                ServerMessage::Deal { players, hand, boneyard_count, whose_turn, commitment } => {
+                    self.players = players;
+                    self.commitment = commitment.map(|commitment| (commitment, hand.clone()));
+                    self.draws.clear();
+                    self.hand = Hand::new();
+                    for tile in hand {
+                        self.hand.add_tile(tile);
+                    }
+                    self.boneyard_count = boneyard_count;
+                    println!("The game has started. {} goes first.", self.player_name(whose_turn));
+                }
+                ServerMessage::YourTurn => self.take_turn()?,
+                ServerMessage::Rejected { reason } => {
+                    println!("{reason}");
+                    self.take_turn()?;
+                }
+                ServerMessage::StateDelta(delta) => self.apply(&delta)?,
+                ServerMessage::GameOver { winner, hands, reveal } => {
+                    println!("Game Over!");
+                    println!("Final Layout:\n{}", self.state.layout);
+                    for (player_id, hand) in hands.iter().enumerate() {
+                        let tiles = hand.iter().map(Tile::to_string).collect::<Vec<_>>().join(" ");
+                        println!("  {}: {tiles}", self.player_name(player_id as u8));
+                    }
+                    match winner {
+                        Some(winner) => println!("Winner: {}", self.player_name(winner)),
+                        None => println!("It's a draw"),
+                    }
+                    if let Some(reveal) = reveal {
+                        if self.check_reveal(&reveal) {
+                            println!("The order of the tiles matches the server's commitment.");
+                        } else {
+                            println!("Warning: the order of the tiles does not match the server's commitment!");
+                        }
+                    }
+                    return Ok(winner);
+                }
+            }
+        }
+    }
+
+    // Returns true if the revealed order of the tiles is the one committed to in the deal, it holds every tile of the set once,
+    // and the hand that was dealt and the tiles that the player drew are where they would be in it. The hands are dealt in order
+    // of seat, and the draws follow them.
+    fn check_reveal(&self, reveal: &Reveal) -> bool {
+        let Some((commitment, dealt)) = &self.commitment else {
+            return false;
+        };
+        let mut ordinals: Vec<u8> = reveal.tiles.iter().map(|tile| tile.ordinal).collect();
+        ordinals.sort_unstable();
+        let start = self.player_id as usize * dealt.len();
+        let first_draw = self.players.len() * dealt.len();
+        reveal.verify(commitment)
+            && ordinals == rules::all_tiles_as_ordinals(self.set_id)
+            && reveal.tiles.get(start..start + dealt.len()) == Some(dealt.as_slice())
+            && self.draws.iter().enumerate().all(|(i, drawn)| {
+                drawn.is_none_or(|tile| reveal.tiles.get(first_draw + i) == Some(&tile))
+            })
+    }
+
+    // Updates the copy of the state with the result of an action. A delta that does not fit the copy means that the client and the
+    // server disagree about the game, so it cannot continue.
+    fn apply(&mut self, delta: &StateDelta) -> io::Result<()> {
+        let name = self.player_name(delta.player_id);
+        if delta.drew || delta.tile_drawn.is_some() {
+            self.draws.push(delta.tile_drawn);
+        }
+        if let Some(tile) = delta.tile_drawn {
+            println!("You drew a tile: {tile}");
+            self.hand.add_tile(tile);
+        } else if delta.drew {
+            println!("{name} drew a tile");
+        }
+        if let Some((tile, end)) = delta.tile_played {
+            if delta.player_id == self.player_id {
+                self.hand.try_remove_tile(&tile).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            }
+            self.state.play_tile(tile, end).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            match end {
+                Some(end) => println!("{name} played {tile} on {end}"),
+                None => println!("{name} played {tile}"),
+            }
+        }
+        if !delta.drew && delta.tile_played.is_none() {
+            self.state.pass();
+            println!("{name} passed");
+        }
+        self.boneyard_count = delta.boneyard_count;
+        Ok(())
+    }
+
+    // Chooses an action and sends it to the server
+    fn take_turn(&mut self) -> io::Result<()> {
+        let action = if !self.has_playable_tile() {
+            if self.boneyard_count > 0 {
+                println!("No playable tiles. Drawing a tile.");
+                ActionRequest::Draw
+            } else {
+                println!("No playable tiles and boneyard is empty. Passing turn.");
+                ActionRequest::Pass
+            }
+        } else {
+            println!("Current Layout:\n\n{}\n", self.state.layout);
+            self.display_open_ends();
+            self.display_hand();
+            let (tile, end) = self.get_player_input()?;
+            ActionRequest::Play { tile, end }
+        };
+        self.connection.send(&ClientMessage::Action { action })
+    }
+
+    // Returns true if any tile in the hand can be played
+    fn has_playable_tile(&self) -> bool {
+        self.hand.tiles().iter().any(|tile| self.state.can_play_tile(tile, None))
+    }
+
+    // Returns the name of a player
+    fn player_name(&self, player_id: u8) -> String {
+        self.players
+            .get(player_id as usize)
+            .cloned()
+            .unwrap_or_else(|| format!("Player {player_id}"))
+    }
+
+    // Get the player's choice of tile to play and the end to play it on
+    fn get_player_input(&self) -> io::Result<(Tile, Option<u8>)> {
+        loop {
+            let input = prompt(&format!("Choose a tile (enter index 0-{}): ", self.hand.len() - 1))?;
+            let tile = match input.parse::<usize>() {
+                Ok(index) if index < self.hand.len() => self.hand.tiles()[index],
+                _ => {
+                    println!("Invalid tile index. Please try again.");
+                    continue;
+                }
+            };
+
+            if self.state.layout.is_empty() {
+                return Ok((tile, None));
+            }
+
+            let max_end = self.state.layout.end_counts.len() - 1;
+            let input = prompt(&format!("Choose an end (0-{max_end}): "))?;
+            match input.parse::<u8>() {
+                Ok(end) if (end as usize) <= max_end => return Ok((tile, Some(end))),
+                _ => println!("Invalid end value. Please try again."),
+            }
+        }
+    }
+
+    // Display the player's hand, sorted, and its pip total
+    fn display_hand(&mut self) {
+        self.hand.sort();
+        println!(
+            "Your hand:  {}   ({} pips)",
+            self.hand
+                .tiles()
+                .iter()
+                .enumerate()
+                .map(|(i, tile)| format!("{i}: {tile}"))
+                .collect::<Vec<_>>()
+                .join("   "),
+            self.hand.total_pips()
+        );
+    }
+
+    // Display the open ends available for tile placement
+    fn display_open_ends(&self) {
+        let layout = &self.state.layout;
+        let open_ends = (0..layout.end_counts.len() as u8)
+            .filter(|&end| layout.open_count(end) > 0)
+            .map(|end| match layout.open_count(end) {
+                1 => format!("{end}"),
+                count => format!("{end}x{count}"),
+            })
+            .collect::<Vec<_>>();
+        println!("Open ends: {}", open_ends.join(", "));
+    }
+}
+
+// Prints a prompt and returns the trimmed line entered
+fn prompt(text: &str) -> io::Result<String> {
+    print!("{text}");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "No more input"));
+    }
+    Ok(input.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_client_follows_the_game() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // The order of the tiles: the hands of Alice and Bob, and then the tile that Alice draws
+        let hands = [Tile::from((1, 2)), Tile::from((3, 4)), Tile::from((6, 6)), Tile::from((0, 0)), Tile::from((5, 6))];
+        let mut order = hands.to_vec();
+        order.extend(rules::tiles_in_set(6).filter(|tile| !hands.contains(tile)));
+        let reveal = Reveal::new(order, [9; 32]);
+        let commitment = reveal.commitment();
+
+        // A scripted server. Bob (seat 1) plays the opening double and then draws, and then the game ends.
+        let revealed = reveal.clone();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut connection = Connection::new(stream).unwrap();
+            let join = connection.receive::<ClientMessage>().unwrap();
+            let messages = vec![
+                ServerMessage::Seated { version: PROTOCOL_VERSION, player_id: 0, num_players: 2, set_id: 6 },
+                ServerMessage::Deal {
+                    players: vec!["Alice".to_string(), "Bob".to_string()],
+                    hand: vec![Tile::from((1, 2)), Tile::from((3, 4))],
+                    boneyard_count: 14,
+                    whose_turn: 1,
+                    commitment: Some(commitment),
+                },
+                ServerMessage::StateDelta(StateDelta {
+                    player_id: 1,
+                    drew: false,
+                    tile_drawn: None,
+                    tile_played: Some((Tile::from((6, 6)), None)),
+                    boneyard_count: 14,
+                    hand_sizes: vec![2, 1],
+                    whose_turn: 0,
+                }),
+                ServerMessage::StateDelta(StateDelta {
+                    player_id: 0,
+                    drew: true,
+                    tile_drawn: Some(Tile::from((5, 6))),
+                    tile_played: None,
+                    boneyard_count: 13,
+                    hand_sizes: vec![3, 1],
+                    whose_turn: 0,
+                }),
+                ServerMessage::GameOver { winner: Some(1), hands: vec![vec![], vec![]], reveal: Some(revealed) },
+            ];
+            for message in &messages {
+                connection.send(message).unwrap();
+            }
+            join
+        });
+
+        let mut client = Client::connect(address, "Alice").unwrap();
+        assert_eq!(client.player_id(), 0);
+        assert_eq!(client.run().unwrap(), Some(1));
+        assert_eq!(
+            server.join().unwrap(),
+            ClientMessage::Join { version: PROTOCOL_VERSION, name: "Alice".to_string() }
+        );
+
+        assert_eq!(client.players, vec!["Alice", "Bob"]);
+        assert_eq!(client.hand.len(), 3);
+        assert!(client.hand.contains(&Tile::from((5, 6))));
+        assert_eq!(client.state.layout.open_count(6), 2);
+        assert_eq!(client.boneyard_count, 13);
+        assert_eq!(client.draws, vec![Some(Tile::from((5, 6)))]);
+        assert!(client.check_reveal(&reveal));
+
+        // An order that the server committed to is still rejected if the drawn tile is not where it was drawn from, or if the
+        // order is not the whole set
+        let mut moved = reveal.clone();
+        moved.tiles.swap(4, 5);
+        client.commitment = Some((moved.commitment(), hands[..2].to_vec()));
+        assert!(!client.check_reveal(&moved));
+        let mut short = reveal.clone();
+        short.tiles.pop();
+        client.commitment = Some((short.commitment(), hands[..2].to_vec()));
+        assert!(!client.check_reveal(&short));
+        let mut duplicated = reveal;
+        duplicated.tiles[27] = Tile::from((0, 0));
+        client.commitment = Some((duplicated.commitment(), hands[..2].to_vec()));
+        assert!(!client.check_reveal(&duplicated));
+    }
+
+    #[test]
+    fn test_client_connect_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut connection = Connection::new(stream).unwrap();
+            let _ = connection.receive::<ClientMessage>().unwrap();
+            connection
+                .send(&ServerMessage::Rejected { reason: "Unsupported protocol version 1".to_string() })
+                .unwrap();
+        });
+        let error = Client::connect(address, "Alice").err().expect("The client should be rejected");
+        assert!(error.to_string().contains("version"));
+        server.join().unwrap();
+    }
+}
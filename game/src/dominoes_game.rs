@@ -1,23 +1,88 @@
-//! Manages the entire dominoes game, including player setup, turn management, and game state transitions.
-
-use dominoes_state::{Action, DominoesState, History};
-use hidden_game_player::{PlayerId, State};
-use player::{HumanPlayer, Player};
+//! Runs a dominoes game in the terminal.
+//!
+//! The game itself is run by the engine. This module sets up the players and reports what happens as the game progresses. The
+//! game is saved after every turn, so that it can be resumed if the program is interrupted (see `game::saved_game`).
+
+use std::fs;
+use std::thread;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dominoes_state::{GameStats, Hand};
+use engine::{Game, GameEvent, Seat, TimeControl, round_points};
+use game::bot_player::BotPlayer;
+use game::error::GameError;
+use game::saved_game::SavedGame;
+use game::scenario::Scenario;
+use game::svg::layout_svg;
+use game::table::Table;
+use hidden_game_player::PlayerId;
+use player::{DominoesPlayer, HumanPlayer, PlayerFactory, PlayerSpec};
 use rules::Configuration;
 
+// Names of the players, by player ID
+const PLAYER_NAMES: [&str; 4] = ["Alice", "Bob", "Carol", "Dave"];
+
+/// Returns the path of the file where the game in progress is saved
+pub fn autosave_path() -> PathBuf {
+    std::env::temp_dir().join("dominoes-autosave.json")
+}
+
+/// Returns the date (in UTC) and the seed of the daily deal
+///
+/// Everyone who plays the daily deal on the same day gets the same seed, and so the same hands. The seed is the date written as a
+/// number, e.g. 20261017 for 2026-10-17.
+///
+/// # Arguments
+/// * `now` - The current time
+pub fn daily_seed(now: SystemTime) -> (String, u64) {
+    let days = now.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() / 86400) as i64;
+    let (year, month, day) = civil_date(days);
+    (
+        format!("{year:04}-{month:02}-{day:02}"),
+        (year * 10000 + month * 100 + day) as u64,
+    )
+}
+
+// Converts a number of days since 1970-01-01 to a year, month, and day (see http://howardhinnant.github.io/date_algorithms.html)
+fn civil_date(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
 /// An instance of a dominoes game
-pub struct DominoesGame<'a> {
+pub struct DominoesGame {
     /// The game configuration
-    configuration: &'a Configuration,
-    /// Human player representing Alice (Player 0)
-    alice: HumanPlayer<'a>,
-    /// Human player representing Bob (Player 1)
-    bob: HumanPlayer<'a>,
-    /// History of all actions taken during the game
-    history: History,
+    configuration: Configuration,
+    /// The game, with Alice (Player 0) and Bob (Player 1) as human players, except in an exhibition
+    game: Game,
+    /// The delay after each action in an exhibition game, in which case the layout is shown after each action
+    pace: Option<Duration>,
+    /// True if the game in progress is saved after every turn
+    autosave: bool,
+    /// The directory where an image of the board is written whenever the layout changes
+    render_dir: Option<PathBuf>,
+    /// The number of images of the board written so far
+    snapshots: usize,
+    /// The points scored by each team in the match so far, by team, which is by player ID without partners (see
+    /// `Configuration::team`). It is empty unless a match is played.
+    totals: Vec<u32>,
+    /// The points scored by each team in each round of the match that is over
+    rounds: Vec<Vec<u32>>,
+    /// The statistics of the players in the rounds of the match that are over, since the history is cleared for each round
+    match_stats: GameStats,
 }
 
-impl<'a> DominoesGame<'a> {
+impl DominoesGame {
     /// Creates a new dominoes game with the given configuration
     ///
     /// Initializes a fresh dominoes game by setting up the game state, creating two human players (Alice and Bob), and preparing
@@ -40,15 +105,270 @@ impl<'a> DominoesGame<'a> {
     ///
     /// // Game is initialized with two players
     /// ```
-    pub fn new(configuration: &'a Configuration) -> Self {
+    pub fn new(configuration: &Configuration) -> Self {
+        Self {
+            configuration: configuration.clone(),
+            game: Game::new(configuration, Self::seats(configuration)),
+            pace: None,
+            autosave: true,
+            render_dir: None,
+            snapshots: 0,
+            totals: Vec::new(),
+            rounds: Vec::new(),
+            match_stats: GameStats::default(),
+        }
+    }
+
+    /// Creates a new dominoes game whose deal is determined by a seed
+    ///
+    /// Games created with the same seed and configuration are dealt the same hands (see `daily_seed`).
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings including hand size, set size, and game variation
+    /// * `seed` - The seed for shuffling the boneyard
+    pub fn with_seed(configuration: &Configuration, seed: u64) -> Self {
+        Self {
+            configuration: configuration.clone(),
+            game: Game::with_seed(configuration, Self::seats(configuration), seed),
+            pace: None,
+            autosave: true,
+            render_dir: None,
+            snapshots: 0,
+            totals: Vec::new(),
+            rounds: Vec::new(),
+            match_stats: GameStats::default(),
+        }
+    }
+
+    /// Creates a new dominoes game with the given players
+    ///
+    /// A game is saved after every turn only if every player is a person, because a saved game is resumed with human players.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings. The number of players must match the number of specifications.
+    /// * `specs` - The players, by seat (see `PlayerFactory`). Players that are not named are named after their seats.
+    /// * `seed` - The seed for shuffling the boneyard, or `None` for a random deal
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use player::PlayerSpec;
+    /// use rules::Configuration;
+    /// # use dominoes_game::DominoesGame;
+    ///
+    /// let config = Configuration::default();
+    /// let specs = ["human".parse::<PlayerSpec>().unwrap(), "mcts:hard".parse().unwrap()];
+    /// let game = DominoesGame::with_seats(&config, &specs, None);
+    /// ```
+    pub fn with_seats(configuration: &Configuration, specs: &[PlayerSpec], seed: Option<u64>) -> Self {
+        let seats = Self::spec_seats(configuration, specs);
+        let game = match seed {
+            Some(seed) => Game::with_seed(configuration, seats, seed),
+            None => Game::new(configuration, seats),
+        };
+        Self {
+            configuration: configuration.clone(),
+            game,
+            pace: None,
+            autosave: specs.iter().all(PlayerSpec::is_human),
+            render_dir: None,
+            snapshots: 0,
+            totals: Vec::new(),
+            rounds: Vec::new(),
+            match_stats: GameStats::default(),
+        }
+    }
+
+    /// Creates a game that continues from the position described by a scenario
+    ///
+    /// Every seat is played by a human player. The hands are not dealt; each player starts with the hand given in the scenario.
+    ///
+    /// # Arguments
+    /// * `scenario` - The position to play from, including the configuration
+    ///
+    /// # Returns
+    /// A new `DominoesGame` instance
+    pub fn from_scenario(scenario: &Scenario) -> Self {
+        let configuration = &scenario.configuration;
+        let seats = Self::human_seats(configuration);
         Self {
-            configuration,
-            alice: HumanPlayer::new(PlayerId::ALICE as u8, configuration, "Alice"),
-            bob: HumanPlayer::new(PlayerId::BOB as u8, configuration, "Bob"),
-            history: History::new(),
+            configuration: configuration.clone(),
+            game: Game::from_position(configuration, seats, scenario.state.clone(), scenario.hands.clone()),
+            pace: None,
+            autosave: true,
+            render_dir: None,
+            snapshots: 0,
+            totals: Vec::new(),
+            rounds: Vec::new(),
+            match_stats: GameStats::default(),
         }
     }
 
+    /// Creates a game that continues from the position described by a scenario, with the given players
+    ///
+    /// Like `with_seats`, a game is saved after every turn only if every player is a person.
+    ///
+    /// # Arguments
+    /// * `scenario` - The position to play from, including the configuration
+    /// * `specs` - The players, by seat (see `PlayerFactory`). The number of players must match the scenario.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use game::scenario::Scenario;
+    /// use player::PlayerSpec;
+    /// use rules::Configuration;
+    /// # use dominoes_game::DominoesGame;
+    ///
+    /// let scenario = Scenario::from_layout("6|6-6|3", &Configuration::default()).unwrap();
+    /// let game = DominoesGame::from_scenario_with_seats(&scenario, &[PlayerSpec::Human { name: None }, PlayerSpec::Greedy]);
+    /// ```
+    pub fn from_scenario_with_seats(scenario: &Scenario, specs: &[PlayerSpec]) -> Self {
+        let configuration = &scenario.configuration;
+        let seats = Self::spec_seats(configuration, specs);
+        Self {
+            configuration: configuration.clone(),
+            game: Game::from_position(configuration, seats, scenario.state.clone(), scenario.hands.clone()),
+            pace: None,
+            autosave: specs.iter().all(PlayerSpec::is_human),
+            render_dir: None,
+            snapshots: 0,
+            totals: Vec::new(),
+            rounds: Vec::new(),
+            match_stats: GameStats::default(),
+        }
+    }
+
+    /// Creates a game that continues a saved game where it left off
+    ///
+    /// Every seat is played by a human player. The actions taken before the game was saved can be undone. If the game is a round
+    /// of a match, the points scored in the rounds before it are restored, and the match is continued with `run_match`.
+    ///
+    /// # Arguments
+    /// * `saved` - The saved game, including the configuration (see `SavedGame::load`)
+    ///
+    /// # Returns
+    /// A new `DominoesGame` instance
+    pub fn load(saved: &SavedGame) -> Self {
+        let configuration = &saved.configuration;
+        let seats = Self::human_seats(configuration);
+        let game = Game::resume(configuration, seats, saved.state.clone(), saved.hands.clone(), saved.history.clone());
+        let rounds = saved.rounds.clone().unwrap_or_default();
+        let totals = match saved.rounds {
+            Some(_) => (0..configuration.num_teams()).map(|team| rounds.iter().map(|round| round[team]).sum()).collect(),
+            None => Vec::new(),
+        };
+        Self {
+            configuration: configuration.clone(),
+            game,
+            pace: None,
+            autosave: true,
+            render_dir: None,
+            snapshots: 0,
+            totals,
+            rounds,
+            match_stats: saved.stats.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if the game is a round of a match (see `run_match`)
+    pub fn is_match(&self) -> bool {
+        !self.totals.is_empty()
+    }
+
+    /// Saves the game in progress, so that it can be continued later (see `load`)
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file. An existing file is replaced.
+    ///
+    /// # Returns
+    /// An error if the file cannot be written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GameError> {
+        let saved = SavedGame {
+            configuration: self.configuration.clone(),
+            state: self.game.state().clone(),
+            hands: (0..self.configuration.num_players() as u8)
+                .map(|player_id| self.game.hand(player_id).clone())
+                .collect(),
+            history: self.game.history().clone(),
+            rounds: self.is_match().then(|| self.rounds.clone()),
+            stats: self.is_match().then(|| self.match_stats.clone()),
+        };
+        saved.save(path)
+    }
+
+    /// Creates an exhibition game, in which computer players play each other
+    ///
+    /// The layout is shown after every action, followed by a pause so that the game can be followed, e.g. to learn a variation
+    /// by watching. Exhibition games are not saved.
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings including hand size, set size, and game variation
+    /// * `iterations` - The number of MCTS iterations used by the players to choose each action
+    /// * `delay` - The pause after each action
+    pub fn exhibition(configuration: &Configuration, iterations: u32, delay: Duration) -> Self {
+        let seats = (0..configuration.num_players() as u8)
+            .map(|player_id| {
+                let mut player = DominoesPlayer::new(player_id, configuration);
+                player.set_name(PLAYER_NAMES[player_id as usize]);
+                player.set_iterations(iterations);
+                Seat::Player(Box::new(player))
+            })
+            .collect();
+        Self {
+            configuration: configuration.clone(),
+            game: Game::new(configuration, seats),
+            pace: Some(delay),
+            autosave: false,
+            render_dir: None,
+            snapshots: 0,
+            totals: Vec::new(),
+            rounds: Vec::new(),
+            match_stats: GameStats::default(),
+        }
+    }
+
+    /// Enforces a time control
+    ///
+    /// A player who takes too long to make a move loses. The time left is shown when a player's turn begins.
+    ///
+    /// # Arguments
+    /// * `time_control` - The time allowed for each move and for the whole game
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        self.game.set_time_control(time_control);
+    }
+
+    /// Allows or disallows taking back turns with `undo` and making them again with `redo`
+    ///
+    /// Undo is allowed by default. A serious game disallows it (see `Game::set_undo_allowed`).
+    ///
+    /// # Arguments
+    /// * `allowed` - True if turns can be taken back
+    pub fn set_undo_allowed(&mut self, allowed: bool) {
+        self.game.set_undo_allowed(allowed);
+    }
+
+    /// Logs the game for analysis
+    ///
+    /// Every action is written to the log as a line of JSON, separately from what is shown in the terminal (see `Game::set_log`).
+    ///
+    /// # Arguments
+    /// * `log` - Where the game is logged
+    pub fn set_log(&mut self, log: Box<dyn Write>) {
+        self.game.set_log(log);
+    }
+
+    /// Writes an image of the board whenever a tile is played or a turn is undone
+    ///
+    /// The images are SVG files of the layout, named in the order they are written, e.g. `board-0001.svg`, so that together they
+    /// are a flipbook of the game (see `game::svg`).
+    ///
+    /// # Arguments
+    /// * `dir` - The directory where the images are written. It must exist.
+    pub fn set_render_dir(&mut self, dir: PathBuf) {
+        self.render_dir = Some(dir);
+    }
+
     /// Runs the main game loop
     ///
     /// This method handles the complete game flow:
@@ -73,226 +393,359 @@ impl<'a> DominoesGame<'a> {
     /// game.run();
     /// ```
     pub fn run(&mut self) {
-        println!("Setting up the game...\n");
-        println!("Game Variation: {}", self.configuration.variation().name());
-        println!("Number of Players: {}", self.configuration.num_players());
-        println!("Domino Set: Double-{}", self.configuration.set_id());
-
-        let mut state = DominoesState::new(self.configuration);
-
-        // Setup players
-        self.set_up_players_by_variation(&mut state);
-
-        println!("Starting the game...");
-
-        // Prevent infinite loop in stub implementation
-        let mut turn_count = 0;
-        let max_turns = self.configuration.set_size() * 2 + self.configuration.num_players(); // draw+play for each tile plus passing
+        self.show_setup();
+        if self.play_round() {
+            self.wrap_up();
+        } else if self.autosave {
+            println!("The game has been saved. Enter `dominoes resume` to continue it.");
+        }
+    }
 
-        while !state.game_is_over && turn_count < max_turns {
-            let current_player_id = state.whose_turn();
-            let player_name = self.player(current_player_id).name().to_string();
-            println!("\nIt's {player_name}'s turn");
-            loop {
-                let (action, mut new_state) = self.player_mut(current_player_id).my_turn(&state);
-                if !action.is_draw() {
-                    println!("{player_name}'s action: {action}");
+    /// Plays rounds until a player has enough points to win the match
+    ///
+    /// The number of points needed is given by the configuration (see `Configuration::target_score`). The winner of each round
+    /// scores the pips left in the other players' hands (see `round_points`), and the totals are carried from round to round. The
+    /// score table is shown after every round. If the opening rule allows any tile, the first player rotates from round to round
+    /// (see `Game::new_round`). If the leaders are tied at the end of a round, another round is played. With partners, the
+    /// points are scored by the teams, and the score table has a column for each team.
+    ///
+    /// As with a single game, the round in progress is saved after every turn if every player is a person, along with the points
+    /// scored in the rounds before it, so a match that is interrupted can be continued (see `load`).
+    ///
+    /// # Returns
+    /// The ID of the player who won the match, or with partners, the first player of the team that won it, or `None` if a player
+    /// quit
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    pub fn run_match(&mut self) -> Option<u8> {
+        let target = self.configuration.target_score();
+        self.show_setup();
+        println!("Playing to {target} points.");
+
+        // Each team is scored, and without partners, each player is a team of their own
+        let num_players = self.configuration.num_players();
+        let num_teams = self.configuration.num_teams();
+        let names: Vec<String> = (0..num_teams as u8)
+            .map(|team| {
+                let players = (0..num_players as u8).filter(|&player_id| self.configuration.team(player_id) == team);
+                players.map(|player_id| self.game.player_name(player_id)).collect::<Vec<_>>().join(" & ")
+            })
+            .collect();
+
+        // A match that was saved continues with the round in progress
+        if self.is_match() {
+            println!("\n{}", score_table(&names, &self.rounds));
+        } else {
+            self.totals = vec![0; num_teams];
+            self.rounds = Vec::new();
+            self.match_stats = GameStats::default();
+        }
+        loop {
+            println!("\n--- Round {} ---", self.rounds.len() + 1);
+            if !self.play_round() {
+                if self.autosave {
+                    println!("The match has been saved. Enter `dominoes resume` to continue it.");
                 }
+                return None;
+            }
 
-                // Determine if the game should end according to the variation
-                if let Some(winner) = self.game_is_over_by_variation(&new_state) {
-                    new_state.mark_game_over(winner);
+            let mut points = vec![0; num_teams];
+            match self.game.state().winner {
+                Some(winner) => {
+                    let hands: Vec<&Hand> = (0..num_players as u8).map(|player_id| self.game.hand(player_id)).collect();
+                    let team = self.configuration.team(winner) as usize;
+                    points[team] = round_points(&self.configuration, &hands, winner);
+                    println!("{} wins the round and scores {}.", names[team], points[team]);
                 }
+                None => println!("The round is a draw."),
+            }
+            for (total, points) in self.totals.iter_mut().zip(&points) {
+                *total += points;
+            }
+            self.rounds.push(points);
+            println!("\n{}", score_table(&names, &self.rounds));
 
-                // Update the game state
-                state = new_state;
-
-                // Record the action in history
-                self.history.add_action(action.clone());
+            // The history is cleared for the next round, so the statistics of the round are kept. A round that did not start
+            // from a deal is left out.
+            if let Ok(stats) = GameStats::from_history(&self.configuration, self.game.history()) {
+                self.match_stats.add(&stats);
+            }
+            if let Some(winner) = match_winner(&self.totals, target) {
+                println!("{} wins the match!", names[winner as usize]);
+                let players: Vec<String> = (0..num_players as u8).map(|id| self.game.player_name(id)).collect();
+                println!("\nStatistics:\n{}", stats_table(&players, &self.match_stats));
+                return Some(winner);
+            }
+            self.game.new_round();
+        }
+    }
 
-                turn_count += 1;
+    // Shows the rules of the game
+    fn show_setup(&self) {
+        println!("Setting up the game...\n");
+        println!("Game Variation: {}", self.configuration.variation().name());
+        println!("Number of Players: {}", self.configuration.num_players());
+        println!("Domino Set: Double-{}", self.configuration.set_id());
+    }
 
-                // The turn is over if the game is over
-                if state.game_is_over {
-                    break;
+    // Plays a round until it is over. Returns false if a player quit before then.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "round", skip_all))]
+    fn play_round(&mut self) -> bool {
+        let mut whose_turn = None;
+        let mut quit = false;
+        while !quit && !self.game.is_over() {
+            let events = self.game.step();
+            for &event in &events {
+                match event {
+                    GameEvent::RoundStarted { first_player } => {
+                        println!("Starting the game...");
+                        println!("{} goes first.", self.game.player_name(first_player));
+                    }
+                    GameEvent::TilePlayed { player_id, .. } | GameEvent::Passed { player_id } => {
+                        let name = self.game.player_name(player_id);
+                        if let Some(action) = self.game.history().get_last_action() {
+                            println!("{name}'s action: {action}");
+                        }
+                    }
+                    GameEvent::Undone { .. } => {
+                        println!("The last turn has been undone.");
+                        whose_turn = None;
+                    }
+                    GameEvent::SetupRequested { player_id } => {
+                        self.set_up_practice(player_id);
+                        whose_turn = None;
+                    }
+                    GameEvent::Quit { player_id } => {
+                        println!("{} quits.", self.game.player_name(player_id));
+                        quit = true;
+                    }
+                    GameEvent::TimedOut { player_id } => {
+                        println!("{} ran out of time.", self.game.player_name(player_id));
+                    }
+                    GameEvent::PointsClaimed { player_id, points } => {
+                        println!("{} claims {points} points.", self.game.player_name(player_id));
+                    }
+                    GameEvent::Muggins { player_id, from, points } => {
+                        let (name, other) = (self.game.player_name(player_id), self.game.player_name(from));
+                        println!("Muggins! {name} takes {points} points missed by {other}.");
+                    }
+                    GameEvent::TileDrawn { .. } | GameEvent::NeedsInput { .. } | GameEvent::RoundOver { .. } => {}
                 }
+            }
+            let layout_changed = events.iter().any(|event| {
+                matches!(event, GameEvent::TilePlayed { .. } | GameEvent::Undone { .. } | GameEvent::SetupRequested { .. })
+            });
+            if layout_changed && self.render_dir.is_some() {
+                self.render_board();
+            }
+            if self.autosave {
+                self.autosave();
+            }
+            if let Some(delay) = self.pace {
+                self.show_layout();
+                thread::sleep(delay);
+            }
 
-                // Determine other criteria for the turn being over according to the variation
-                if self.turn_is_over_by_variation(&action) {
-                    break;
+            // Announce the next player when the turn changes
+            let next = self.game.state().whose_turn;
+            if !self.game.is_over() && whose_turn != Some(next) {
+                let name = self.game.player_name(next);
+                match self.game.clock().and_then(|clock| clock.budget(next)) {
+                    Some(budget) => println!("\nIt's {name}'s turn ({} left)", format_time(budget)),
+                    None => println!("\nIt's {name}'s turn"),
                 }
+                whose_turn = Some(next);
             }
-
-            // Next player's turn
-            state.whose_turn = (state.whose_turn + 1) % 2;
         }
 
-        self.wrap_up(&state);
+        if let Some(e) = self.game.take_log_error() {
+            eprintln!("Warning: The game could not be logged. {e}");
+        }
+        !quit
     }
 
-    // Helper to get player by ID
-    fn player(&self, player_id: u8) -> &dyn Player {
-        match player_id {
-            0 => &self.alice,
-            1 => &self.bob,
-            _ => unreachable!("Only two players supported"),
-        }
+    // Returns the seats of a new game, with Alice and Bob as human players
+    fn seats(configuration: &Configuration) -> Vec<Seat> {
+        let alice = HumanPlayer::new(PlayerId::ALICE as u8, configuration, PLAYER_NAMES[0]);
+        let bob = HumanPlayer::new(PlayerId::BOB as u8, configuration, PLAYER_NAMES[1]);
+        vec![Seat::Player(Box::new(alice)), Seat::Player(Box::new(bob))]
     }
 
-    // Helper to get mutable player by ID
-    fn player_mut(&mut self, player_id: u8) -> &mut dyn Player {
-        match player_id {
-            0 => &mut self.alice,
-            1 => &mut self.bob,
-            _ => unreachable!("Only two players supported"),
-        }
+    // Returns a seat with the player created from each specification (see `PlayerFactory`). A remote player is a bot listening
+    // on the network (see `BotPlayer::connect`).
+    fn spec_seats(configuration: &Configuration, specs: &[PlayerSpec]) -> Vec<Seat> {
+        let factory = PlayerFactory::new(configuration).with_remote(|player_id, configuration, address, name| {
+            Box::new(BotPlayer::connect(player_id, configuration, address, name))
+        });
+        specs
+            .iter()
+            .enumerate()
+            .map(|(player_id, spec)| Seat::Player(factory.create(player_id as u8, spec, PLAYER_NAMES[player_id])))
+            .collect()
     }
 
-    // Sets up both players according to the game variation
-    fn set_up_players_by_variation(&mut self, state: &mut DominoesState) {
-        self.alice.set_up(state);
-        self.bob.set_up(state);
-
-        // Handle setup variations
-        match self.configuration.variation() {
-            // In traditional dominoes, the player with the highest double starts. If nobody has a double, players must redraw.
-            rules::Variation::Traditional => {
-                // Determine starting player based on highest double
-                println!("Determining starting player based on highest double...");
-                let mut first_player = None;
-                while first_player.is_none() {
-                    first_player = match (self.alice.highest_double(), self.bob.highest_double()) {
-                        (Some(a), Some(b)) => {
-                            // Both players have doubles, highest starts
-                            Some(if a > b {
-                                PlayerId::ALICE as u8
-                            } else {
-                                PlayerId::BOB as u8
-                            })
-                        }
-                        (Some(_), None) => {
-                            // Alice has a double, Bob does not
-                            Some(PlayerId::ALICE as u8)
-                        }
-                        (None, Some(_)) => {
-                            // Bob has a double, Alice does not
-                            Some(PlayerId::BOB as u8)
-                        }
-                        (None, None) => {
-                            // Neither have doubles, must redraw
-                            println!("No doubles found. Both players must redraw.");
-                            self.alice.reset();
-                            self.bob.reset();
-                            *state = DominoesState::new(self.configuration);
-                            self.alice.set_up(state);
-                            self.bob.set_up(state);
-                            None
-                        }
-                    };
-                }
+    // Returns a seat with a human player for every player
+    fn human_seats(configuration: &Configuration) -> Vec<Seat> {
+        (0..configuration.num_players() as u8)
+            .map(|player_id| {
+                let player = HumanPlayer::new(player_id, configuration, PLAYER_NAMES[player_id as usize]);
+                Seat::Player(Box::new(player))
+            })
+            .collect()
+    }
 
-                // Now we know first_player is Some, but still use match to be safe
-                state.whose_turn = first_player.expect("Should have a first player after the loop");
-            }
-            _ => {
-                // For other variations, nothing special to do here
+    // Sets up the layout that a player asked to practice, with the other tiles dealt at random (see `Scenario::from_layout`). It
+    // is the player's turn. Layouts cannot be practiced during a match, and a practice position is not saved, so the game that
+    // was saved before can still be resumed.
+    fn set_up_practice(&mut self, player_id: u8) {
+        let Some(layout) = self.game.take_setup_request() else {
+            return;
+        };
+        if self.is_match() {
+            println!("Layouts cannot be practiced during a match.");
+            return;
+        }
+        match Scenario::from_layout(&layout, &self.configuration) {
+            Ok(mut scenario) => {
+                scenario.state.whose_turn = player_id;
+                self.game.set_position(scenario.state, scenario.hands);
+                self.autosave = false;
+                println!("Practicing the layout {layout}.");
             }
+            Err(e) => println!("The layout cannot be set up. {e}"),
         }
     }
 
-    // Marks the game as over according to the variation
-    fn game_is_over_by_variation(&self, state: &DominoesState) -> Option<Option<u8>> {
-        match self.configuration.variation() {
-            rules::Variation::Traditional => {
-                // In Traditional variation, game ends when a player empties their hand or both players pass. The winner is
-                // the player with the lowest hand score.
-                if self.alice.hand().is_empty() {
-                    return Some(Some(self.alice.id()));
-                } else if self.bob.hand().is_empty() {
-                    return Some(Some(self.bob.id()));
-                } else if state.consecutive_passes as usize >= self.configuration.num_players() {
-                    let alice_score = self.alice.hand().score();
-                    let bob_score = self.bob.hand().score();
-                    return Some(if alice_score < bob_score {
-                        Some(PlayerId::ALICE as u8)
-                    } else if bob_score < alice_score {
-                        Some(PlayerId::BOB as u8)
-                    } else {
-                        None
-                    });
-                }
-            }
-            _ => {
-                // FIXME: Add real game ending logic based on variation here.
-                if state.consecutive_passes as usize >= self.configuration.num_players() {
-                    return Some(None); // Game ends in a draw
-                }
-            }
+    // Shows the layout and the number of tiles held by each player
+    fn show_layout(&self) {
+        println!("\n{}\n", self.game.state().layout.render_ascii());
+        let hands: Vec<String> = (0..self.configuration.num_players() as u8)
+            .map(|player_id| format!("{}: {}", self.game.player_name(player_id), self.game.hand(player_id).len()))
+            .collect();
+        println!("Tiles in hand: {}", hands.join(", "));
+        if let Some(trains) = self.game.state().trains() {
+            println!("Trains: {}", trains.summary(&self.game.state().layout));
         }
-        // Otherwise, the game is not over
-        None
+        println!("Tiles in the boneyard: {}", self.game.state().boneyard.count());
     }
 
-    // Determines if the turn is over according to the variation
-    fn turn_is_over_by_variation(&self, action: &Action) -> bool {
-        match self.configuration.variation() {
-            rules::Variation::Traditional => {
-                if action.tile_drawn.is_none() {
-                    return true; // Tile was played or passed, end turn
-                }
-            }
-            _ => {
-                if action.tile_played.is_some() || action.tile_drawn.is_none() {
-                    return true; // Tile was played or passed, end turn
-                }
-            }
+    // Writes the next image of the board. Rendering stops after an error, so that the error is only reported once.
+    fn render_board(&mut self) {
+        let Some(dir) = &self.render_dir else {
+            return;
+        };
+        self.snapshots += 1;
+        let path = dir.join(format!("board-{:04}.svg", self.snapshots));
+        if let Err(e) = fs::write(&path, layout_svg(&self.game.state().layout)) {
+            eprintln!("Warning: The board could not be written to {}. {e}", path.display());
+            self.render_dir = None;
+        }
+    }
+
+    // Saves the game in progress, or removes the saved game once the game is over
+    fn autosave(&self) {
+        if self.game.is_over() {
+            let _ = fs::remove_file(autosave_path());
+            return;
+        }
+        if let Err(e) = self.save(autosave_path()) {
+            eprintln!("Warning: The game could not be saved. {e}");
         }
-        false
     }
 
     // Handles end of game logic
-    fn wrap_up(&self, state: &DominoesState) {
+    fn wrap_up(&self) {
         println!("Game Over!");
 
-        if let Some(winner_id) = state.winner {
-            println!("Winner: {}", self.player(winner_id).name());
+        if let Some(winner_id) = self.game.state().winner {
+            println!("Winner: {}", self.game.player_name(winner_id));
         } else {
             println!("It's a draw");
         }
 
         // Display final game statistics
-        self.display_game_summary(state);
+        self.display_game_summary();
     }
 
     // Displays a summary of the game
-    fn display_game_summary(&self, state: &DominoesState) {
+    fn display_game_summary(&self) {
         println!("\n--- Game Summary ---");
         println!("Players:");
-        println!("  {}", self.alice.name());
-        println!("  {}", self.bob.name());
+        for player_id in 0..self.configuration.num_players() as u8 {
+            println!("  {}", self.game.player_name(player_id));
+        }
 
         // Display the final layout
-        let layout_string = state.layout.to_string();
+        let layout_string = self.game.state().layout.to_string();
         println!("Final Layout:\n{layout_string}");
 
         // Display action history
-        let actions = self.history.get_actions();
+        let actions = self.game.history().get_actions();
         println!("\nAction History ({} actions):", actions.len());
         for (i, action) in actions.iter().enumerate() {
             println!(
                 "  {}: {} - {action}",
                 i + 1,
-                self.player(action.player_id).name()
+                self.game.player_name(action.player_id)
             );
         }
 
+        // Display the statistics of each player, unless the game did not start from a deal
+        if let Ok(stats) = GameStats::from_history(&self.configuration, self.game.history()) {
+            let names: Vec<String> = (0..self.configuration.num_players() as u8).map(|id| self.game.player_name(id)).collect();
+            println!("\nStatistics:\n{}", stats_table(&names, &stats));
+        }
+
         println!("Game completed successfully!");
     }
 }
 
+// Returns the team that has won a match, which is the only team with the highest total if it reaches the target
+fn match_winner(totals: &[u32], target: u32) -> Option<u8> {
+    let best = totals.iter().copied().max()?;
+    let mut leaders = (0..totals.len()).filter(|&team| totals[team] == best);
+    let winner = leaders.next()?;
+    (best >= target && leaders.next().is_none()).then_some(winner as u8)
+}
+
+// Returns the score table of a match: the points scored by each team in each round, and the totals
+fn score_table(names: &[String], rounds: &[Vec<u32>]) -> Table {
+    let mut table = Table::new(std::iter::once("Round").chain(names.iter().map(String::as_str)));
+    for (round, points) in rounds.iter().enumerate() {
+        table.add_row(std::iter::once((round + 1).to_string()).chain(points.iter().map(u32::to_string)));
+    }
+    table.add_separator();
+    let totals = (0..names.len()).map(|team| rounds.iter().map(|round| round[team]).sum::<u32>().to_string());
+    table.add_row(std::iter::once("Total".to_string()).chain(totals));
+    table
+}
+
+// Returns the table of the statistics of each player in a game
+fn stats_table(names: &[String], stats: &GameStats) -> Table {
+    let mut table = Table::new(["Player", "Plays", "Drawn", "Passes", "Doubles", "Points/Play", "End Control"]);
+    for (name, player) in names.iter().zip(&stats.players) {
+        table.add_row([
+            name.clone(),
+            player.plays.to_string(),
+            player.tiles_drawn.to_string(),
+            player.passes.to_string(),
+            player.doubles_played.to_string(),
+            format!("{:.1}", player.points_per_play()),
+            format!("{:.0}%", player.end_control * 100.0),
+        ]);
+    }
+    table
+}
+
+// Formats a time as minutes and seconds, e.g. 4:05
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dominoes_state::PlayerStats;
     use rules::{Configuration, Variation};
 
     fn create_test_configuration() -> Configuration {
@@ -359,7 +812,7 @@ mod tests {
         let game = DominoesGame::new(&config);
 
         // History should start empty
-        assert!(game.history.get_actions().is_empty());
+        assert!(game.game.history().get_actions().is_empty());
     }
 
     #[test]
@@ -374,7 +827,7 @@ mod tests {
             minimal_config.starting_hand_size()
         );
         assert_eq!(game.configuration.set_id(), minimal_config.set_id());
-        assert!(game.history.get_actions().is_empty());
+        assert!(game.game.history().get_actions().is_empty());
     }
 
     #[test]
@@ -389,7 +842,7 @@ mod tests {
             large_config.starting_hand_size()
         );
         assert_eq!(game.configuration.set_id(), large_config.set_id());
-        assert!(game.history.get_actions().is_empty());
+        assert!(game.game.history().get_actions().is_empty());
     }
 
     #[test]
@@ -408,7 +861,7 @@ mod tests {
 
     #[test]
     fn test_dominoes_game_new_memory_safety() {
-        // Test that the game properly handles the lifetime of the configuration reference
+        // Test that the game keeps its own copy of the configuration
         let config = create_test_configuration();
         let game = DominoesGame::new(&config);
 
@@ -426,7 +879,7 @@ mod tests {
         let game = DominoesGame::new(&config);
 
         // History should be properly initialized
-        let actions = game.history.get_actions();
+        let actions = game.game.history().get_actions();
         assert!(actions.is_empty());
         assert_eq!(actions.len(), 0);
     }
@@ -440,8 +893,8 @@ mod tests {
         let game2 = DominoesGame::new(&config);
 
         // Both should be initialized consistently
-        assert!(game1.history.get_actions().is_empty());
-        assert!(game2.history.get_actions().is_empty());
+        assert!(game1.game.history().get_actions().is_empty());
+        assert!(game2.game.history().get_actions().is_empty());
 
         assert_eq!(
             game1.configuration.num_players(),
@@ -457,10 +910,204 @@ mod tests {
             let game = DominoesGame::new(&config);
 
             assert_eq!(game.configuration.variation(), variation);
-            assert!(game.history.get_actions().is_empty());
+            assert!(game.game.history().get_actions().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_dominoes_game_from_scenario() {
+        let scenario = Scenario::parse("layout: 6|6\nhand: [1|2]\nhand: [3|6 0|0]\nhand: [4|4]\nturn: 1").unwrap();
+        let game = DominoesGame::from_scenario(&scenario);
+
+        assert_eq!(game.configuration.num_players(), 3);
+        assert_eq!(game.game.player_name(2), "Carol");
+        assert_eq!(game.game.hand(1).tiles(), scenario.hands[1].tiles());
+        assert_eq!(game.game.state().whose_turn, 1);
+        assert_eq!(game.game.state().layout.nodes.len(), 1);
+        assert!(game.game.history().get_actions().is_empty());
+    }
+
+    #[test]
+    fn test_dominoes_game_save_and_load() {
+        let config = create_test_configuration();
+        let specs: Vec<PlayerSpec> = ["greedy", "greedy"].iter().map(|spec| spec.parse().unwrap()).collect();
+        let mut game = DominoesGame::with_seats(&config, &specs, Some(3));
+        for _ in 0..4 {
+            game.game.step();
+        }
+        assert!(!game.game.history().get_actions().is_empty());
+
+        let path = std::env::temp_dir().join(format!("dominoes-game-save-{}.json", std::process::id()));
+        game.save(&path).unwrap();
+        let saved = SavedGame::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        // The game continues with human players, from the same position
+        let saved = saved.unwrap();
+        let loaded = DominoesGame::load(&saved);
+        assert_eq!(loaded.game.player_name(1), "Bob");
+        assert_eq!(loaded.game.history().get_actions(), game.game.history().get_actions());
+        assert_eq!(loaded.game.state().fingerprint, game.game.state().fingerprint);
+        assert_eq!(loaded.game.state().whose_turn, game.game.state().whose_turn);
+        for player_id in 0..2 {
+            assert_eq!(loaded.game.hand(player_id).tiles(), game.game.hand(player_id).tiles());
         }
     }
 
+    #[test]
+    fn test_dominoes_game_save_and_load_match() {
+        let config = create_test_configuration();
+        let specs = vec![PlayerSpec::Greedy, PlayerSpec::Greedy];
+        let mut game = DominoesGame::with_seats(&config, &specs, Some(3));
+        let path = std::env::temp_dir().join(format!("dominoes-game-save-match-{}.json", std::process::id()));
+        game.save(&path).unwrap();
+        let single = SavedGame::load(&path);
+
+        // The points scored in the rounds that are over are saved with the round in progress
+        game.totals = vec![12, 5];
+        game.rounds = vec![vec![12, 0], vec![0, 5]];
+        game.match_stats = GameStats { actions: 30, players: vec![PlayerStats::default(); 2] };
+        game.save(&path).unwrap();
+        let saved = SavedGame::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(!DominoesGame::load(&single.unwrap()).is_match());
+        let saved = saved.unwrap();
+        let loaded = DominoesGame::load(&saved);
+        assert!(loaded.is_match());
+        assert_eq!(loaded.totals, [12, 5]);
+        assert_eq!(loaded.rounds, game.rounds);
+        assert_eq!(loaded.match_stats, game.match_stats);
+    }
+
+    #[test]
+    fn test_dominoes_game_with_seats() {
+        let config = Configuration::new(3, Variation::Traditional, 6, 5);
+        let specs: Vec<PlayerSpec> = ["greedy", "mcts:10", "greedy"].iter().map(|spec| spec.parse().unwrap()).collect();
+        let mut game = DominoesGame::with_seats(&config, &specs, Some(1));
+        assert_eq!(game.game.player_name(2), "Carol");
+        assert!(!game.autosave);
+
+        game.run();
+        assert!(game.game.is_over());
+
+        let config = create_test_configuration();
+        let humans = vec![PlayerSpec::Human { name: Some("Zed".to_string()) }, PlayerSpec::Human { name: None }];
+        let game = DominoesGame::with_seats(&config, &humans, None);
+        assert_eq!(game.game.player_name(0), "Zed");
+        assert!(game.autosave);
+    }
+
+    #[test]
+    fn test_dominoes_game_exhibition() {
+        let config = create_test_configuration();
+        let mut game = DominoesGame::exhibition(&config, 10, Duration::ZERO);
+        assert_eq!(game.game.player_name(1), "Bob");
+        assert!(!game.autosave);
+
+        // Nobody needs to be asked for input
+        game.run();
+        assert!(game.game.is_over());
+    }
+
+    #[test]
+    fn test_daily_seed() {
+        let (date, seed) = daily_seed(UNIX_EPOCH);
+        assert_eq!(date, "1970-01-01");
+        assert_eq!(seed, 19700101);
+
+        // Late on a leap day
+        let (date, seed) = daily_seed(UNIX_EPOCH + Duration::from_secs(1_709_251_199));
+        assert_eq!(date, "2024-02-29");
+        assert_eq!(seed, 20240229);
+
+        // The same day always gets the same seed
+        let morning = UNIX_EPOCH + Duration::from_secs(1_790_000_000 / 86400 * 86400);
+        assert_eq!(daily_seed(morning).1, daily_seed(morning + Duration::from_secs(86399)).1);
+    }
+
+    #[test]
+    fn test_score_table() {
+        let names = vec!["Alice".to_string(), "Bob".to_string()];
+        let table = score_table(&names, &[vec![12, 0], vec![0, 7], vec![0, 0]]);
+        let expected = "\
+Round  Alice  Bob
+-----  -----  ---
+1         12    0
+2          0    7
+3          0    0
+-----  -----  ---
+Total     12    7
+";
+        assert_eq!(table.to_string(), expected);
+    }
+
+    #[test]
+    fn test_stats_table() {
+        let names = vec!["Alice".to_string(), "Bob".to_string()];
+        let alice = PlayerStats { plays: 2, doubles_played: 2, points: 10, end_control: 0.75, ..PlayerStats::default() };
+        let bob = PlayerStats { plays: 1, tiles_drawn: 1, end_control: 0.25, ..PlayerStats::default() };
+        let table = stats_table(&names, &GameStats { actions: 4, players: vec![alice, bob] });
+        let expected = "\
+Player  Plays  Drawn  Passes  Doubles  Points/Play  End Control
+------  -----  -----  ------  -------  -----------  -----------
+Alice       2      0       0        2          5.0          75%
+Bob         1      1       0        0          0.0          25%
+";
+        assert_eq!(table.to_string(), expected);
+    }
+
+    #[test]
+    fn test_dominoes_game_run_match() {
+        let config = create_test_configuration();
+        let specs = vec![PlayerSpec::Greedy, PlayerSpec::Greedy];
+        let config = config.with_target_score(1);
+        let mut game = DominoesGame::with_seats(&config, &specs, Some(3));
+        let winner = game.run_match().unwrap();
+        assert!(game.game.is_over());
+        assert!(game.totals[winner as usize] >= 1);
+        assert_eq!(game.totals.len(), 2);
+
+        // The statistics cover every round, not just the last one
+        let last_round = GameStats::from_history(&config, game.game.history()).unwrap();
+        assert!(game.match_stats.actions >= last_round.actions);
+        assert_eq!(game.match_stats.players.len(), 2);
+    }
+
+    #[test]
+    fn test_match_winner() {
+        assert_eq!(match_winner(&[0, 0], 100), None);
+        assert_eq!(match_winner(&[40, 99], 100), None);
+        assert_eq!(match_winner(&[40, 120], 100), Some(1));
+        assert_eq!(match_winner(&[120, 120, 30], 100), None);
+        assert_eq!(match_winner(&[130, 120, 30], 100), Some(0));
+    }
+
+    #[test]
+    fn test_dominoes_game_render_dir() {
+        let dir = std::env::temp_dir().join(format!("dominoes-render-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = create_test_configuration();
+        let specs = vec![PlayerSpec::Greedy, PlayerSpec::Greedy];
+        let mut game = DominoesGame::with_seats(&config, &specs, Some(5));
+        game.set_render_dir(dir.clone());
+        game.run();
+
+        // There is an image for every tile played
+        let num_tiles = game.game.state().layout.nodes.len();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), num_tiles);
+        let last = fs::read_to_string(dir.join(format!("board-{num_tiles:04}.svg"))).unwrap();
+        assert_eq!(last.matches("<g ").count(), num_tiles);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(Duration::from_secs(0)), "0:00");
+        assert_eq!(format_time(Duration::from_millis(30_900)), "0:30");
+        assert_eq!(format_time(Duration::from_secs(245)), "4:05");
+    }
+
     // Note: Testing run() method directly is challenging because it requires user input
     // from HumanPlayer. Integration tests would be needed to test the full game flow.
 
@@ -471,7 +1118,7 @@ mod tests {
 
         // Verify that the public API is accessible
         // new() method works
-        assert!(game.history.get_actions().is_empty());
+        assert!(game.game.history().get_actions().is_empty());
 
         // run() method exists and can be called (though we can't test it fully without mocking input)
         // We're not actually calling run() here to avoid requiring user input
@@ -481,15 +1128,15 @@ mod tests {
 
     #[test]
     fn test_dominoes_game_lifetime_parameters() {
-        // Test that lifetime parameters work correctly
-        fn create_and_return_game(config: &Configuration) -> DominoesGame<'_> {
-            DominoesGame::new(config)
+        // Test that a game can outlive the configuration that it was created with
+        fn create_and_return_game() -> DominoesGame {
+            let config = create_test_configuration();
+            DominoesGame::new(&config)
         }
 
-        let config = create_test_configuration();
-        let game = create_and_return_game(&config);
+        let game = create_and_return_game();
 
-        assert!(game.history.get_actions().is_empty());
+        assert!(game.game.history().get_actions().is_empty());
     }
 
     #[test]
@@ -499,6 +1146,6 @@ mod tests {
         let game = DominoesGame::new(&config);
 
         // Game is initialized (as claimed in doctest)
-        assert!(game.history.get_actions().is_empty());
+        assert!(game.game.history().get_actions().is_empty());
     }
 }
@@ -0,0 +1,318 @@
+//! Engine protocol
+//!
+//! The engine protocol lets an external program, such as a GUI or a match manager, use the AI to analyze positions, much like
+//! chess engines are driven with UCI. Messages are JSON-RPC 2.0 objects, one per line, read from the input and written to the
+//! output. A session goes like this:
+//!
+//! 1. The program sends `position` with a scenario (see `crate::scenario`), which replaces the current position.
+//! 2. The program sends `go`, and the AI chooses an action for the player whose turn it is. The engine sends an `info`
+//!    notification with statistics about the search, and then answers with the `bestmove`.
+//! 3. The program sends `quit`, or closes the input, and the session ends.
+//!
+//! ```text
+//! --> {"jsonrpc":"2.0","id":1,"method":"position","params":{"scenario":"hand: [1|2 2|2]\nhand: [5|6 0|0]"}}
+//! <-- {"jsonrpc":"2.0","id":1,"result":null}
+//! --> {"jsonrpc":"2.0","id":2,"method":"go","params":{"budget":500}}
+//! <-- {"jsonrpc":"2.0","method":"info","params":{"iterations":1000,"elapsed_ms":12}}
+//! <-- {"jsonrpc":"2.0","id":2,"result":{"bestmove":{"kind":"play","tile":[2,2],"end":null}}}
+//! --> {"jsonrpc":"2.0","id":3,"method":"quit"}
+//! <-- {"jsonrpc":"2.0","id":3,"result":null}
+//! ```
+//!
+//! The parameters of `go` are optional. `budget` is the time allowed for the search, in milliseconds, and `iterations` is the
+//! number of search iterations. If only a budget is given, the number of iterations is estimated from the speed of the previous
+//! search. Errors are reported with the standard JSON-RPC error codes.
+
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use dominoes_state::Action;
+use player::{DecisionStats, DominoesPlayer, Player};
+
+use crate::protocol::ActionRequest;
+use crate::scenario::Scenario;
+
+/// The line could not be parsed as JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// The message is not a JSON-RPC request
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method is not supported
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters of the method are invalid
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request is valid, but cannot be carried out in the current position
+pub const POSITION_ERROR: i64 = -32000;
+
+/// A request sent to the engine
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Request {
+    /// The JSON-RPC version, which must be "2.0"
+    pub jsonrpc: String,
+    /// The ID of the request, which is returned in the response. Notifications have no ID and get no response.
+    #[serde(default)]
+    pub id: Option<Value>,
+    /// The method to call
+    pub method: String,
+    /// The parameters of the method
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// An error reported by the engine
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    /// One of the error codes defined in this module
+    pub code: i64,
+    /// A description of the error
+    pub message: String,
+}
+
+// The parameters of `position`
+#[derive(Debug, Clone, Deserialize)]
+struct PositionParams {
+    scenario: String,
+}
+
+// The parameters of `go`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct GoParams {
+    budget: Option<u64>,
+    iterations: Option<u32>,
+}
+
+// An engine session
+#[derive(Default)]
+struct Session {
+    // The position to search
+    scenario: Option<Scenario>,
+    // Statistics about the last search, used to estimate the number of iterations that fit in a budget
+    last_search: Option<DecisionStats>,
+    // True once `quit` has been received
+    done: bool,
+}
+
+/// Runs an engine session until `quit` is received or the input ends
+///
+/// # Arguments
+/// * `input` - Where the requests are read from
+/// * `output` - Where the responses and notifications are written
+///
+/// # Returns
+/// An error if reading or writing fails
+///
+/// # Examples
+/// ```rust
+/// # use game::engine_protocol;
+/// # use std::io::Cursor;
+///
+/// let input = r#"{"jsonrpc":"2.0","id":1,"method":"go"}"#;
+/// let mut output = Vec::new();
+/// engine_protocol::run(Cursor::new(input), &mut output).unwrap();
+///
+/// // There is no position to search yet
+/// let response = String::from_utf8(output).unwrap();
+/// assert!(response.contains(r#""code":-32000"#));
+/// ```
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut session = Session::default();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (id, result) = match serde_json::from_str::<Value>(&line) {
+            Err(e) => (Value::Null, Err(rpc_error(PARSE_ERROR, e))),
+            Ok(message) => match serde_json::from_value::<Request>(message) {
+                Err(e) => (Value::Null, Err(rpc_error(INVALID_REQUEST, e))),
+                Ok(request) if request.jsonrpc != "2.0" => {
+                    (request.id.unwrap_or_default(), Err(rpc_error(INVALID_REQUEST, "Expected jsonrpc 2.0")))
+                }
+                Ok(request) => {
+                    let result = session.handle(&request, &mut output)?;
+                    // Notifications are not answered
+                    let Some(id) = request.id else {
+                        if session.done {
+                            break;
+                        }
+                        continue;
+                    };
+                    (id, result)
+                }
+            },
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        };
+        writeln!(output, "{response}")?;
+        output.flush()?;
+        if session.done {
+            break;
+        }
+    }
+    Ok(())
+}
+
+impl Session {
+    // Carries out a request. Notifications sent during the request are written to the output.
+    fn handle<W: Write>(&mut self, request: &Request, output: &mut W) -> io::Result<Result<Value, RpcError>> {
+        Ok(match request.method.as_str() {
+            "position" => self.position(&request.params),
+            "go" => match self.go(&request.params) {
+                Ok((action, stats)) => {
+                    if let Some(stats) = stats {
+                        let info = json!({
+                            "jsonrpc": "2.0",
+                            "method": "info",
+                            "params": { "iterations": stats.iterations, "elapsed_ms": stats.elapsed.as_millis() as u64 },
+                        });
+                        writeln!(output, "{info}")?;
+                    }
+                    Ok(json!({ "bestmove": action }))
+                }
+                Err(error) => Err(error),
+            },
+            "quit" => {
+                self.done = true;
+                Ok(Value::Null)
+            }
+            method => Err(rpc_error(METHOD_NOT_FOUND, format!("Unknown method '{method}'"))),
+        })
+    }
+
+    // Replaces the position to search
+    fn position(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let params: PositionParams = parse_params(params)?;
+        let scenario = Scenario::parse(&params.scenario).map_err(|e| rpc_error(INVALID_PARAMS, e))?;
+        self.scenario = Some(scenario);
+        Ok(Value::Null)
+    }
+
+    // Chooses an action for the player whose turn it is
+    fn go(&mut self, params: &Value) -> Result<(ActionRequest, Option<DecisionStats>), RpcError> {
+        let params: GoParams = if params.is_null() { GoParams::default() } else { parse_params(params)? };
+        let Some(scenario) = &self.scenario else {
+            return Err(rpc_error(POSITION_ERROR, "There is no position to search"));
+        };
+        if scenario.state.game_is_over {
+            return Err(rpc_error(POSITION_ERROR, "The game is over"));
+        }
+
+        let player_id = scenario.state.whose_turn;
+        let mut player = DominoesPlayer::new(player_id, &scenario.configuration);
+        player.set_up_from(scenario.hands[player_id as usize].clone(), &scenario.state);
+        let budget = params.budget.map(Duration::from_millis);
+        player.set_time_budget(budget);
+        match (params.iterations, budget, self.last_search) {
+            (Some(iterations), _, _) => player.set_iterations(iterations),
+            (None, Some(budget), Some(last)) => player.set_iterations(estimate_iterations(budget, last)),
+            _ => {}
+        }
+
+        let (action, _) = player.my_turn(&scenario.state);
+        // An action taken without searching, e.g. from the opening book, says nothing about the speed of the search
+        let stats = player.decision_stats();
+        if stats.is_some_and(|stats| stats.iterations > 0) {
+            self.last_search = stats;
+        }
+        Ok((to_request(&action), stats))
+    }
+}
+
+// Estimates the number of iterations that can be done in a budget, judging by the speed of an earlier search. At least one
+// iteration is always done.
+fn estimate_iterations(budget: Duration, last: DecisionStats) -> u32 {
+    if last.elapsed.is_zero() {
+        return last.iterations.max(1);
+    }
+    let per_iteration = last.elapsed.as_secs_f64() / f64::from(last.iterations);
+    ((budget.as_secs_f64() / per_iteration) as u32).max(1)
+}
+
+// Converts an action into its representation in the protocol
+fn to_request(action: &Action) -> ActionRequest {
+    match action.tile_played {
+        Some((tile, end)) => ActionRequest::Play { tile, end },
+        None if action.is_draw() => ActionRequest::Draw,
+        None => ActionRequest::Pass,
+    }
+}
+
+// Parses the parameters of a method
+fn parse_params<T: for<'de> Deserialize<'de>>(params: &Value) -> Result<T, RpcError> {
+    T::deserialize(params).map_err(|e| rpc_error(INVALID_PARAMS, e))
+}
+
+// Creates an error
+fn rpc_error(code: i64, message: impl ToString) -> RpcError {
+    RpcError { code, message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // Runs a session and returns the messages written by the engine
+    fn run_session(input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        run(Cursor::new(input), &mut output).unwrap();
+        String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_engine_protocol_go() {
+        let input = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"position","params":{"scenario":"layout: 6|6\nhand: [6|1 2|2]\nhand: [5|5 0|0]"}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"go","params":{"iterations":10}}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"quit"}"#,
+            r#"{"jsonrpc":"2.0","id":4,"method":"go"}"#,
+        ]
+        .join("\n");
+        let messages = run_session(&input);
+
+        // The session ends after quit, so the last go is not answered
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0], json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        assert_eq!(messages[1]["method"], "info");
+        assert_eq!(messages[1]["params"]["iterations"], 10);
+        assert_eq!(messages[2]["id"], 2);
+        let bestmove: ActionRequest = serde_json::from_value(messages[2]["result"]["bestmove"].clone()).unwrap();
+        assert!(matches!(bestmove, ActionRequest::Play { .. } | ActionRequest::Draw | ActionRequest::Pass));
+        assert_eq!(messages[3]["id"], 3);
+    }
+
+    #[test]
+    fn test_engine_protocol_errors() {
+        let input = [
+            "not json",
+            r#"{"id":1,"method":"go"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"ponder"}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"position","params":{"scenario":"hand: [9|9]"}}"#,
+            r#"{"jsonrpc":"2.0","id":4,"method":"go"}"#,
+            r#"{"jsonrpc":"2.0","method":"go"}"#,
+        ]
+        .join("\n");
+        let messages = run_session(&input);
+
+        // Notifications are not answered, even if they fail
+        let codes: Vec<i64> = messages.iter().map(|message| message["error"]["code"].as_i64().unwrap()).collect();
+        assert_eq!(codes, [PARSE_ERROR, INVALID_REQUEST, METHOD_NOT_FOUND, INVALID_PARAMS, POSITION_ERROR]);
+        assert_eq!(messages[2]["id"], 2);
+    }
+
+    #[test]
+    fn test_estimate_iterations() {
+        let last = DecisionStats { iterations: 1000, elapsed: Duration::from_millis(100) };
+        assert_eq!(estimate_iterations(Duration::from_millis(50), last), 500);
+        assert_eq!(estimate_iterations(Duration::from_secs(1), last), 10000);
+
+        // A budget too short for a single iteration still gets one
+        assert_eq!(estimate_iterations(Duration::ZERO, last), 1);
+        assert_eq!(estimate_iterations(Duration::from_micros(50), last), 1);
+    }
+}
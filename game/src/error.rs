@@ -0,0 +1,84 @@
+//! Error types
+//!
+//! This module defines the errors returned by this crate when files are read or written and when batches of games and tournaments
+//! are set up, so that callers can match on the kind of failure instead of its message.
+
+use std::io;
+use std::path::PathBuf;
+
+use player::PlayerSpec;
+use thiserror::Error;
+
+use crate::layout_parser::ParseError;
+
+/// An error in a file or in the players of a batch of games
+///
+/// # Examples
+/// ```rust
+/// # use game::error::GameError;
+/// # use game::saved_game::SavedGame;
+///
+/// let error = SavedGame::load("no/such/file.json").unwrap_err();
+/// assert!(matches!(error, GameError::Read { .. }));
+/// assert!(error.to_string().starts_with("Failed to read no/such/file.json"));
+/// ```
+#[derive(Debug, Error)]
+pub enum GameError {
+    /// A file cannot be read
+    #[error("Failed to read {}: {source}", .path.display())]
+    Read { path: PathBuf, source: io::Error },
+    /// A file cannot be opened for writing
+    #[error("Failed to open {}: {source}", .path.display())]
+    Open { path: PathBuf, source: io::Error },
+    /// A file cannot be written
+    #[error("Failed to write {}: {source}", .path.display())]
+    Write { path: PathBuf, source: io::Error },
+    /// A directory cannot be created
+    #[error("Failed to create {}: {source}", .path.display())]
+    CreateDir { path: PathBuf, source: io::Error },
+    /// A file that is already open cannot be written
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A file does not hold the JSON that was expected
+    #[error("{}: {source}", .path.display())]
+    Json { path: PathBuf, source: serde_json::Error },
+    /// Something cannot be converted to JSON
+    #[error("Failed to convert to JSON: {0}")]
+    Serialize(#[from] serde_json::Error),
+    /// A scenario file cannot be parsed (see `Scenario::parse`)
+    #[error("{}: {source}", .path.display())]
+    Scenario { path: PathBuf, source: ParseError },
+    /// The extension of a results file is not one of the supported formats (see `ResultsFormat::from_path`)
+    #[error("{}: The results file must end with .csv or .json", .0.display())]
+    ResultsFormat(PathBuf),
+    /// A saved game does not have a hand for every player
+    #[error("{}: Expected {expected} hands, found {found}", .path.display())]
+    HandCount { path: PathBuf, expected: usize, found: usize },
+    /// A round of a saved match does not have the points of every team
+    #[error("{}: Expected the points of {expected} teams in each round, found {found}", .path.display())]
+    RoundPoints { path: PathBuf, expected: usize, found: usize },
+    /// The results in a simulation checkpoint do not add up to the number of games played
+    #[error("{}: The results do not match the number of games played", .0.display())]
+    ResultsMismatch(PathBuf),
+    /// The pairings in a tournament checkpoint are not those of its entrants
+    #[error("{}: The pairings do not match the tournament", .0.display())]
+    PairingsMismatch(PathBuf),
+    /// The tournament in a checkpoint is not valid
+    #[error("{}: {source}", .path.display())]
+    Checkpoint { path: PathBuf, source: Box<GameError> },
+    /// A player cannot be read (see `PlayerSpec::from_str`)
+    #[error("{0}")]
+    PlayerSpec(String),
+    /// The number of players does not match the configuration
+    #[error("Expected {expected} players, found {found}")]
+    PlayerCount { expected: usize, found: usize },
+    /// Batches of games and tournaments are played only by computer players
+    #[error("'{0}' is not a computer player")]
+    HumanPlayer(PlayerSpec),
+    /// A tournament is played in two-player games
+    #[error("A tournament is played in two-player games, not {0}-player games")]
+    TournamentPlayers(usize),
+    /// A tournament needs at least two entrants
+    #[error("A tournament needs at least 2 players, found {0}")]
+    TooFewEntrants(usize),
+}
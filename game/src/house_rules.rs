@@ -0,0 +1,273 @@
+//! House rules
+//!
+//! House rules change the configuration of a game from the command line, so that local rules can be played without new code for
+//! each one. A rule is written as `key=value`:
+//!
+//! - `variation=<NAME>`: The variation, e.g. `allfives` (see `crate::scenario` for the names)
+//! - `set=<N>`: The domino set, e.g. `9` for double-nine. The default is double-twelve for Mexican Train, double-nine for Chicken
+//!   Foot, and double-six otherwise.
+//! - `hand=<N>`: The number of tiles dealt to each player. The default depends on the variation and the number of players.
+//! - `target=<N>`: The number of points needed to win a match, e.g. `250`
+//! - `draw=<none|until-playable|one>`: Whether a player who cannot play draws: never, as in a Block game, until they can play,
+//!   as in a Draw game, or only one tile. The default is `one` for Mexican Train, and `until-playable` otherwise.
+//! - `muggins=<on|off>`: Whether players must claim the points they score, and lose any they miss to an opponent who calls
+//!   "muggins". The default is `off`.
+//! - `partners=<on|off>`: Whether four players play as two teams of partners who sit opposite each other and share their
+//!   scores. The default is `off`.
+//! - `reserve=<N>`: The number of tiles that must be left in the boneyard and cannot be drawn, e.g. `2`. The default is `0`.
+//! - `seed=<N>`: The seed of the deals and of the computer players' random choices, so that a game can be reproduced, e.g. to
+//!   debug it. By default, every game is different.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rules::{Configuration, DrawRule, RulesError, Variation};
+
+use crate::scenario::VARIATION_NAMES;
+
+/// A rule that changes the configuration of a game
+///
+/// # Examples
+/// ```rust
+/// # use game::house_rules::HouseRule;
+/// # use rules::DrawRule;
+///
+/// let rule: HouseRule = "set=9".parse().unwrap();
+/// assert_eq!(rule, HouseRule::SetId(9));
+/// assert_eq!("draw=none".parse::<HouseRule>(), Ok(HouseRule::Draw(DrawRule::NoDraw)));
+/// assert!("draw=max2".parse::<HouseRule>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HouseRule {
+    /// The variation
+    Variation(Variation),
+    /// The ID of the domino set, which is the highest value on the tiles
+    SetId(u8),
+    /// The number of tiles dealt to each player
+    StartingHandSize(usize),
+    /// The number of points needed to win a match
+    TargetScore(u32),
+    /// The rule for drawing from the boneyard
+    Draw(DrawRule),
+    /// Whether the points scored must be claimed
+    Muggins(bool),
+    /// Whether the players play as two teams of partners
+    Partners(bool),
+    /// The number of tiles that must be left in the boneyard
+    Reserve(usize),
+    /// The seed of every random number generator in a game
+    Seed(u64),
+}
+
+impl FromStr for HouseRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s.split_once('=').ok_or_else(|| format!("Expected a rule as key=value, found '{s}'"))?;
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "variation" => VARIATION_NAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(value))
+                .map(|&(_, variation)| HouseRule::Variation(variation))
+                .ok_or_else(|| format!("Unknown variation '{value}'")),
+            "set" => value
+                .parse::<u8>()
+                .ok()
+                .filter(|&set_id| set_id <= rules::MAX_PIPS)
+                .map(HouseRule::SetId)
+                .ok_or_else(|| format!("Expected a set from 0 to {}, found '{value}'", rules::MAX_PIPS)),
+            "hand" => value
+                .parse::<usize>()
+                .ok()
+                .filter(|&size| size > 0)
+                .map(HouseRule::StartingHandSize)
+                .ok_or_else(|| format!("Expected a number of tiles, found '{value}'")),
+            "target" => value
+                .parse::<u32>()
+                .ok()
+                .filter(|&points| points > 0)
+                .map(HouseRule::TargetScore)
+                .ok_or_else(|| format!("Expected a number of points, found '{value}'")),
+            "draw" => DRAW_RULE_NAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(value))
+                .map(|&(_, draw_rule)| HouseRule::Draw(draw_rule))
+                .ok_or_else(|| format!("Expected none, until-playable, or one, found '{value}'")),
+            "muggins" => match value.to_ascii_lowercase().as_str() {
+                "on" => Ok(HouseRule::Muggins(true)),
+                "off" => Ok(HouseRule::Muggins(false)),
+                _ => Err(format!("Expected on or off, found '{value}'")),
+            },
+            "partners" => match value.to_ascii_lowercase().as_str() {
+                "on" => Ok(HouseRule::Partners(true)),
+                "off" => Ok(HouseRule::Partners(false)),
+                _ => Err(format!("Expected on or off, found '{value}'")),
+            },
+            "reserve" => {
+                value.parse::<usize>().map(HouseRule::Reserve).map_err(|_| format!("Expected a number of tiles, found '{value}'"))
+            }
+            "seed" => value.parse::<u64>().map(HouseRule::Seed).map_err(|_| format!("Expected a number, found '{value}'")),
+            _ => Err(format!(
+                "Unknown rule '{key}'. Expected variation, set, hand, target, draw, muggins, partners, reserve, or seed."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HouseRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HouseRule::Variation(variation) => {
+                let name = VARIATION_NAMES.iter().find(|(_, v)| v == variation).map_or("", |&(name, _)| name);
+                write!(f, "variation={name}")
+            }
+            HouseRule::SetId(set_id) => write!(f, "set={set_id}"),
+            HouseRule::StartingHandSize(size) => write!(f, "hand={size}"),
+            HouseRule::TargetScore(points) => write!(f, "target={points}"),
+            HouseRule::Draw(draw_rule) => {
+                let name = DRAW_RULE_NAMES.iter().find(|(_, rule)| rule == draw_rule).map_or("", |&(name, _)| name);
+                write!(f, "draw={name}")
+            }
+            HouseRule::Muggins(muggins) => write!(f, "muggins={}", if *muggins { "on" } else { "off" }),
+            HouseRule::Partners(partners) => write!(f, "partners={}", if *partners { "on" } else { "off" }),
+            HouseRule::Reserve(reserve) => write!(f, "reserve={reserve}"),
+            HouseRule::Seed(seed) => write!(f, "seed={seed}"),
+        }
+    }
+}
+
+/// Creates the configuration of a game with house rules
+///
+/// The rules are applied in order, so a later rule overrides an earlier one with the same key. Anything not changed by a rule has
+/// its default value.
+///
+/// # Arguments
+/// * `num_players` - The number of players
+/// * `rules` - The house rules
+///
+/// # Returns
+/// The configuration, or an error if a game cannot be played with it, e.g. if there are not enough tiles to deal every player a
+/// hand, if more tiles are reserved than are left after the deal, or if there are partners but not four players
+///
+/// # Examples
+/// ```rust
+/// # use game::house_rules::{HouseRule, configure};
+/// # use rules::Variation;
+///
+/// let rules = ["variation=allfives".parse().unwrap(), "set=9".parse().unwrap()];
+/// let configuration = configure(4, &rules).unwrap();
+/// assert_eq!(configuration.variation(), Variation::AllFives);
+/// assert_eq!(configuration.set_size(), 55);
+/// assert_eq!(configuration.starting_hand_size(), 6);
+/// ```
+pub fn configure(num_players: usize, rules: &[HouseRule]) -> Result<Configuration, RulesError> {
+    let mut variation = Configuration::DEFAULT_VARIATION;
+    let mut set_id = None;
+    let mut starting_hand_size = None;
+    let mut target_score = Configuration::DEFAULT_TARGET_SCORE;
+    let mut draw_rule = None;
+    let mut muggins = false;
+    let mut partners = false;
+    let mut reserve = 0;
+    let mut seed = None;
+    for rule in rules {
+        match *rule {
+            HouseRule::Variation(v) => variation = v,
+            HouseRule::SetId(id) => set_id = Some(id),
+            HouseRule::StartingHandSize(size) => starting_hand_size = Some(size),
+            HouseRule::TargetScore(points) => target_score = points,
+            HouseRule::Draw(rule) => draw_rule = Some(rule),
+            HouseRule::Muggins(m) => muggins = m,
+            HouseRule::Partners(p) => partners = p,
+            HouseRule::Reserve(r) => reserve = r,
+            HouseRule::Seed(s) => seed = Some(s),
+        }
+    }
+
+    let set_id = set_id.unwrap_or_else(|| Configuration::default_set_id(variation));
+    let starting_hand_size =
+        starting_hand_size.unwrap_or_else(|| Configuration::default_starting_hand_size(num_players, variation));
+    let configuration = Configuration::try_new(num_players, variation, set_id, starting_hand_size)?
+        .with_target_score(target_score)
+        .with_muggins(muggins)
+        .with_reserve(reserve)?;
+    let configuration = match draw_rule {
+        Some(draw_rule) => configuration.with_draw_rule(draw_rule),
+        None => configuration,
+    };
+    let configuration = match seed {
+        Some(seed) => configuration.with_seed(seed),
+        None => configuration,
+    };
+    if partners { configuration.with_partners() } else { Ok(configuration) }
+}
+
+// The names of the rules for drawing, as they are written in a house rule
+const DRAW_RULE_NAMES: &[(&str, DrawRule)] =
+    &[("none", DrawRule::NoDraw), ("until-playable", DrawRule::DrawUntilPlayable), ("one", DrawRule::DrawOne)];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_house_rule_parse() {
+        assert_eq!("variation=AllFives".parse::<HouseRule>(), Ok(HouseRule::Variation(Variation::AllFives)));
+        assert_eq!(" hand = 5 ".parse::<HouseRule>(), Ok(HouseRule::StartingHandSize(5)));
+        let rules = [
+            "variation=allfives", "set=12", "hand=9", "target=250", "draw=until-playable", "muggins=on", "partners=on",
+            "reserve=2", "seed=7",
+        ];
+        for rule in rules {
+            assert_eq!(rule.parse::<HouseRule>().unwrap().to_string(), rule);
+        }
+
+        let invalid_rules = [
+            "set", "set=22", "hand=0", "target=0", "variation=muggins", "muggins=yes", "partners=2", "reserve=-2", "seed=-1",
+            "open=any-double",
+        ];
+        for invalid in invalid_rules {
+            assert!(invalid.parse::<HouseRule>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_configure() {
+        let configuration = configure(2, &[]).unwrap();
+        assert_eq!(configuration.set_id(), Configuration::DEFAULT_SET_ID);
+        assert_eq!(configuration.starting_hand_size(), 7);
+        assert_eq!(configuration.target_score(), Configuration::DEFAULT_TARGET_SCORE);
+        assert_eq!(configure(2, &[HouseRule::TargetScore(150)]).unwrap().target_score(), 150);
+        assert!(!configuration.muggins());
+        assert!(configure(2, &[HouseRule::Muggins(true)]).unwrap().muggins());
+
+        // A Block game, and a rule for drawing that depends on the variation
+        assert_eq!(configure(2, &[HouseRule::Draw(DrawRule::NoDraw)]).unwrap().draw_rule(), DrawRule::NoDraw);
+        let configuration = configure(4, &[HouseRule::Variation(Variation::MexicanTrain)]).unwrap();
+        assert_eq!(configuration.draw_rule(), DrawRule::DrawOne);
+
+        // Partners need four players
+        assert!(configure(4, &[HouseRule::Partners(true)]).unwrap().partners());
+        assert!(matches!(configure(3, &[HouseRule::Partners(true)]), Err(RulesError::PartnersNeedFourPlayers(3))));
+
+        // A seed makes the games reproducible
+        assert_eq!(configure(2, &[HouseRule::Seed(7)]).unwrap().seed(), Some(7));
+        assert_eq!(configuration.seed(), None);
+
+        // A later rule overrides an earlier one
+        let configuration = configure(2, &[HouseRule::StartingHandSize(5), HouseRule::StartingHandSize(3)]).unwrap();
+        assert_eq!(configuration.starting_hand_size(), 3);
+
+        // Mexican Train is played with a double-twelve set unless a set is given
+        let configuration = configure(4, &[HouseRule::Variation(Variation::MexicanTrain)]).unwrap();
+        assert_eq!((configuration.set_id(), configuration.starting_hand_size()), (12, 15));
+        let configuration = configure(2, &[HouseRule::Variation(Variation::MexicanTrain), HouseRule::SetId(9)]).unwrap();
+        assert_eq!(configuration.set_id(), 9);
+
+        // There must be enough tiles to deal, and to leave the reserve after the deal
+        assert!(matches!(configure(4, &[HouseRule::SetId(3)]), Err(RulesError::NotEnoughTiles { .. })));
+        assert_eq!(configure(2, &[HouseRule::Reserve(2)]).unwrap().reserve(), 2);
+        assert_eq!(configure(4, &[HouseRule::Reserve(5)]).unwrap_err(), RulesError::ReserveTooLarge { reserve: 5, undealt: 4 });
+    }
+}
@@ -1,12 +1,16 @@
 //! Parser for domino layout strings.
 //!
-//! This module provides functionality to parse a layout specified in a string format into a tree structure.
+//! This module provides functionality to parse a layout specified in a string format into a tree structure, a `Layout`, or a
+//! complete position, so that positions can be set up from text for tests, puzzles, and analysis.
 //!
 //! See [`parse`] for detailed documentation on the layout string syntax and rules.
 
+use std::collections::HashSet;
+
 use regex::Regex;
 use ego_tree::{NodeMut, NodeRef, Tree};
-use rules::{self, Tile};
+use dominoes_state::{DominoesState, Hand};
+use rules::{self, Boneyard, Configuration, Layout, Tile};
 
 /// Error type returned when parsing a domino layout string fails.
 ///
@@ -45,6 +49,10 @@ impl std::fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+// Markers of the arms of a double: left, right, up, and down. The chains following a double are attached to its arms in
+// this order.
+const ARM_MARKERS: [char; 4] = ['<', '>', '^', 'v'];
+
 struct ParseState<'a> {
     input: &'a str,
     chars: Vec<char>,
@@ -79,7 +87,7 @@ impl<'a> ParseState<'a> {
 
             // Check for '=' indicating a group follows
             if self.consume('=') {
-                let group = self.parse_group(Some(first_end))?;
+                let group = self.parse_group(Some(first_end), parent_end.is_none())?;
                 for g in group {
                     append_tree(&mut chain.root_mut(), g);
                 }
@@ -87,7 +95,17 @@ impl<'a> ParseState<'a> {
 
             Ok(chain)
         } else {
-            // Normal tiles cannot be followed by '='
+            // The first tile may be followed by '=' and a chain for each end
+            if parent_end.is_none() && self.consume('=') {
+                let (a, b) = tile.as_tuple();
+                let left_end = if first_end == a { b } else { a };
+                for end in self.parse_ends(left_end, first_end)? {
+                    append_tree(&mut chain.root_mut(), end);
+                }
+                return Ok(chain);
+            }
+
+            // Other normal tiles cannot be followed by '='
             if self.next_is('=') {
                 return Err(self.error(&format!("{} followed by '='. Only doubles can be followed by =", tile)));
             }
@@ -102,20 +120,43 @@ impl<'a> ParseState<'a> {
         }
     }
 
-    fn parse_group(&mut self, parent_end: Option<u8>) -> Result<Vec<Tree<Tile>>, ParseError> {
+    fn parse_group(&mut self, parent_end: Option<u8>, first: bool) -> Result<Vec<Tree<Tile>>, ParseError> {
         self.skip_whitespace();
 
         if !self.consume('(') {
             return Err(self.error("Expected '(' to start group"));
         }
 
-        let mut chains = Vec::new();
+        // A double that is not the first tile is attached to the preceding tile by its left arm
+        let available = if first { &ARM_MARKERS[..] } else { &ARM_MARKERS[1..] };
+        let mut arms: Vec<(usize, Tree<Tile>)> = Vec::new();
 
         loop {
             self.skip_whitespace();
 
+            // The chain is on the marked arm, or on the first free arm if it is not marked
+            let is_used = |arm: usize| arms.iter().any(|&(used, _)| used == arm);
+            let arm = match ARM_MARKERS.iter().position(|&marker| self.next_is(marker)) {
+                Some(arm) => {
+                    let marker = ARM_MARKERS[arm];
+                    if !available.contains(&marker) {
+                        return Err(self.error(&format!(
+                            "Arm '{marker}' is not available. It is attached to the preceding tile."
+                        )));
+                    }
+                    if is_used(arm) {
+                        return Err(self.error(&format!("Arm '{marker}' is used more than once")));
+                    }
+                    self.advance_by(1);
+                    arm
+                }
+                None => (ARM_MARKERS.len() - available.len()..ARM_MARKERS.len())
+                    .find(|&arm| !is_used(arm))
+                    .ok_or_else(|| self.error(&format!("A double has at most {} arms here", available.len())))?,
+            };
+
             let chain = self.parse_chain(parent_end)?;
-            chains.push(chain);
+            arms.push((arm, chain));
 
             self.skip_whitespace();
 
@@ -129,7 +170,31 @@ impl<'a> ParseState<'a> {
             }
         }
 
-        Ok(chains)
+        // The chains are in the order of the arms
+        arms.sort_by_key(|&(arm, _)| arm);
+        Ok(arms.into_iter().map(|(_, chain)| chain).collect())
+    }
+
+    // Parses the group following a first tile that is not a double. The group has a chain for each end of the tile, the first
+    // attached to the left value and the second attached to the right value.
+    fn parse_ends(&mut self, left_end: u8, right_end: u8) -> Result<Vec<Tree<Tile>>, ParseError> {
+        self.skip_whitespace();
+        if !self.consume('(') {
+            return Err(self.error("Expected '(' to start group"));
+        }
+
+        let left = self.parse_chain(Some(left_end))?;
+        self.skip_whitespace();
+        if !self.consume(',') {
+            return Err(self.error("Expected ',' in group. The group following the first tile has a chain for each end."));
+        }
+        let right = self.parse_chain(Some(right_end))?;
+        self.skip_whitespace();
+        if !self.consume(')') {
+            return Err(self.error("Expected ')' to end group"));
+        }
+
+        Ok(vec![left, right])
     }
 
     // Parses x|y into a Tile and also returns the open end y
@@ -157,6 +222,36 @@ impl<'a> ParseState<'a> {
         }
     }
 
+    // Parses a list of tiles separated by whitespace or commas, optionally enclosed in brackets, and returns each tile with its
+    // position
+    fn parse_tile_list(&mut self) -> Result<Vec<(Tile, usize)>, ParseError> {
+        self.skip_whitespace();
+        let bracketed = self.consume('[');
+
+        let mut tiles = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.pos >= self.chars.len() || self.next_is(']') {
+                break;
+            }
+            let position = self.pos;
+            let (tile, _) = self.parse_tile(None)?;
+            tiles.push((tile, position));
+
+            self.skip_whitespace();
+            self.consume(',');
+        }
+
+        if bracketed && !self.consume(']') {
+            return Err(self.error("Expected ']' to end the list of tiles"));
+        }
+        self.skip_whitespace();
+        if self.pos < self.chars.len() {
+            return Err(self.error("Unexpected characters after tiles"));
+        }
+        Ok(tiles)
+    }
+
     // Parses a string number
     fn parse_number(&self, str: &str) -> Result<u8, ParseError> {
         str.parse::<u8>()
@@ -222,21 +317,29 @@ impl<'a> ParseState<'a> {
 /// The layout string syntax follows this grammar:
 ///
 /// ```bnf
-/// - <layout> ::= <chain>
+/// - <layout> ::= <chain> | <tile> "=" "(" <chain> "," <chain> ")"
 /// - <chain> ::= <tile> | <double> | <tile> "-" <chain> | <double> "=" "(" <group> ")"
 /// - <tile> ::= <number> "|" <number>
 /// - <double> ::= <number> "|" <number>
-/// - <group> ::= <chain> | <chain> "," <chain> | <chain> "," <chain> "," <chain>
+/// - <group> ::= <arm> | <arm> "," <group>
+/// - <arm> ::= <chain> | <marker> <chain>
+/// - <marker> ::= "<" | ">" | "^" | "v"
 /// - <number> ::= "0" | "1" | ... | "MAX_PIPS"
 /// ```
 ///
 /// where,
 /// - The two numbers in a `<tile>` are different
 /// - The two numbers in a `<double>` are the same
-/// - A `<group>` contains 1 to 3 chains
+/// - A `<group>` contains a chain for each arm of the double that is in use. The arms are marked `<` (left), `>` (right), `^`
+///   (up), and `v` (down), e.g. for the spinner in All Fives. The first tile has four arms, and other doubles have three because
+///   their left arm is attached to the preceding tile. A chain without a marker is on the first free arm, and each arm may be
+///   used only once. The chains are attached to the double in the order of the arms, so a position has only one tree.
 /// - When tiles are connected with "-", the left number of the succeeding tile must match the right number of the preceding
 ///   tile (the open end).
 /// - In the group following a double, the left number of each first tile of each chain must match the double's value.
+/// - The first tile of the layout may be followed by "=" and a chain for each end even if it is not a double. The left number of
+///   the first tile of the first chain must match the tile's left number, and that of the second chain must match the tile's
+///   right number.
 /// - Whitespace is ignored.
 ///
 /// ## Valid Examples
@@ -245,6 +348,10 @@ impl<'a> ParseState<'a> {
 /// - Double tile with branches: `3|3=(3|4-4|5,3|6)`
 /// - Double tile with single branch: `4|4=(4|5-5|6)`
 /// - Layout with multiple doubles: `1|5-0|5-3|5-5|5=(4|5-2|5-5|6-3|6-6|6=(0|6-1|6-4|6-2|6))`
+/// - First tile with a chain at each end: `1|2=(1|3,2|4-4|4)`
+/// - Spinner with four arms: `5|5=(<5|0,>5|3-3|3=(^3|1),^5|1,v5|6)`
+///
+/// The syntax is the same as the `Display` format of `Layout`, so a layout can be written out and parsed back.
 ///
 /// # Arguments
 /// * `input` - A string slice containing the layout to parse
@@ -258,7 +365,7 @@ impl<'a> ParseState<'a> {
 /// - Numbers outside the range 0-MAX_PIPS
 /// - Mismatched connections (tile ends don't connect properly)
 /// - Invalid syntax (missing parentheses, commas, etc.)
-/// - Non-double tiles followed by "="
+/// - Non-double tiles other than the first tile followed by "="
 /// - Double tiles followed by "-"
 /// - Unexpected characters after the layout
 ///
@@ -275,11 +382,6 @@ impl<'a> ParseState<'a> {
 /// // Single tile
 /// let tree = parse("5|6").unwrap();
 /// ```
-///
-
-
-
-
 pub fn parse(input: &str) -> Result<Tree<Tile>, ParseError> {
     let mut state = ParseState::new(input);
     let layout = state.parse_chain(None)?;
@@ -292,6 +394,152 @@ pub fn parse(input: &str) -> Result<Tree<Tile>, ParseError> {
     Ok(layout)
 }
 
+/// Parse a layout string into a `Layout`.
+///
+/// The syntax is the same as for [`parse`]. The first tile in the string is the first tile played. The number of chains on each
+/// double is limited by the branching rule of the configuration (see `Configuration::branching`), e.g. the spinner in All Fives
+/// has four arms, so a position using more arms of a double than the rule allows can be parsed by [`parse`], but not into a
+/// `Layout`.
+///
+/// # Arguments
+/// * `input` - A string slice containing the layout to parse
+/// * `configuration` - The configuration determining the domino set
+///
+/// # Returns
+/// Returns `Ok(Layout)` if parsing succeeds, or `Err(ParseError)` if the input is invalid or the layout is not possible under the
+/// configuration, e.g. a tile is not in the set or a double has more chains than arms (see `rules::LayoutError`).
+///
+/// # Examples
+/// ```rust
+/// # use game::layout_parser::parse_layout;
+/// # use rules::{Configuration, Variation};
+///
+/// let layout = parse_layout("3|3=(3|4-4|5,3|6)", &Configuration::default()).unwrap();
+/// assert_eq!(layout.nodes.len(), 4);
+/// assert_eq!(layout.open_count(5), 1);
+/// assert_eq!(layout.open_count(6), 1);
+///
+/// // The spinner in All Fives has four arms, and a double in Traditional has two
+/// let spinner = "5|5=(<5|0,>5|3,^5|1,v5|6)";
+/// assert!(parse_layout(spinner, &Configuration::new(2, Variation::AllFives, 6, 7)).is_ok());
+/// assert!(parse_layout(spinner, &Configuration::default()).is_err());
+/// ```
+pub fn parse_layout(input: &str, configuration: &Configuration) -> Result<Layout, ParseError> {
+    let tree = parse(input)?;
+    Layout::from_tree(configuration, &tree).map_err(|e| ParseError { message: e.to_string(), position: 0 })
+}
+
+/// Parse a hand string into a `Hand`.
+///
+/// Tiles are in x|y format and are separated by whitespace or commas. The list may be enclosed in brackets, e.g.
+/// `"[6|6 3|5 0|2]"`.
+///
+/// # Arguments
+/// * `input` - A string slice containing the hand to parse
+///
+/// # Returns
+/// Returns `Ok(Hand)` if parsing succeeds, or `Err(ParseError)` if the input is invalid or a tile appears more than once.
+///
+/// # Examples
+/// ```rust
+/// # use game::layout_parser::parse_hand;
+/// # use rules::Tile;
+///
+/// let hand = parse_hand("[6|6 3|5 0|2]").unwrap();
+/// assert_eq!(hand.tiles(), &[Tile::from((6, 6)), Tile::from((3, 5)), Tile::from((0, 2))]);
+/// ```
+pub fn parse_hand(input: &str) -> Result<Hand, ParseError> {
+    let mut hand = Hand::new();
+    for (tile, position) in ParseState::new(input).parse_tile_list()? {
+        if hand.contains(&tile) {
+            return Err(ParseError { message: format!("{tile} appears more than once"), position });
+        }
+        hand.add_tile(tile);
+    }
+    Ok(hand)
+}
+
+/// Set up a position from a layout and strings listing the tiles in each hand and in the boneyard.
+///
+/// The lists have the same syntax as for [`parse_hand`]. The tiles in the boneyard are drawn in the order listed. Every tile must
+/// be in the set, and no tile may appear more than once in the layout, the hands, and the boneyard.
+///
+/// # Arguments
+/// * `layout` - The tiles that have been played (see [`parse_layout`])
+/// * `hands` - The tiles in each player's hand, in order of player ID
+/// * `boneyard` - The tiles in the boneyard
+/// * `configuration` - The configuration determining the domino set
+///
+/// # Returns
+/// Returns the state and the hands, or `Err(ParseError)` if a list is invalid. The position of an error is relative to the list
+/// containing it.
+///
+/// # Examples
+/// ```rust
+/// # use game::layout_parser::{parse_layout, parse_position};
+/// # use rules::{Configuration, Tile};
+///
+/// let configuration = Configuration::default();
+/// let layout = parse_layout("6|6=(6|3)", &configuration).unwrap();
+/// let (state, hands) = parse_position(layout, &["3|1, 2|2", "[6|5]"], "0|0 0|1", &configuration).unwrap();
+/// assert_eq!(hands[0].tiles(), &[Tile::from((1, 3)), Tile::from((2, 2))]);
+/// assert_eq!(state.boneyard.count(), 2);
+/// ```
+pub fn parse_position(
+    layout: Layout,
+    hands: &[&str],
+    boneyard: &str,
+    configuration: &Configuration,
+) -> Result<(DominoesState, Vec<Hand>), ParseError> {
+    let hands: Vec<(&str, usize)> = hands.iter().map(|&hand| (hand, 0)).collect();
+    parse_position_at(layout, &hands, (boneyard, 0), configuration)
+}
+
+// Implements parse_position. Each list is paired with its offset in the original input, which is added to the position of an
+// error in the list.
+pub(crate) fn parse_position_at(
+    layout: Layout,
+    hands: &[(&str, usize)],
+    boneyard: (&str, usize),
+    configuration: &Configuration,
+) -> Result<(DominoesState, Vec<Hand>), ParseError> {
+    let mut seen: HashSet<Tile> = layout.nodes.iter().map(|node| node.tile).collect();
+
+    // Parses a list of tiles and checks that each one is in the set and has not been seen already
+    let mut parse_list = |(input, offset): (&str, usize), name: &str| -> Result<Vec<Tile>, ParseError> {
+        let tiles = ParseState::new(input).parse_tile_list().map_err(|e| ParseError {
+            message: format!("{name}: {}", e.message),
+            position: offset + e.position,
+        })?;
+        tiles
+            .into_iter()
+            .map(|(tile, position)| {
+                let (a, b) = tile.as_tuple();
+                let message = if a > configuration.set_id() || b > configuration.set_id() {
+                    format!("{name}: {tile} is not in a double-{} set", configuration.set_id())
+                } else if !seen.insert(tile) {
+                    format!("{name}: {tile} appears more than once")
+                } else {
+                    return Ok(tile);
+                };
+                Err(ParseError { message, position: offset + position })
+            })
+            .collect()
+    };
+
+    let mut parsed_hands = Vec::with_capacity(hands.len());
+    for (player_id, &hand) in hands.iter().enumerate() {
+        let mut parsed = Hand::new();
+        for tile in parse_list(hand, &format!("Hand {player_id}"))? {
+            parsed.add_tile(tile);
+        }
+        parsed_hands.push(parsed);
+    }
+    let boneyard = Boneyard::with(parse_list(boneyard, "Boneyard")?).with_reserve(configuration.reserve());
+
+    Ok((DominoesState::with_position(configuration, layout, boneyard), parsed_hands))
+}
+
 // Helper function appends source tree as a child of the destination node. This is how tiles are prepended in a chain.
 fn append_tree(dest: &mut NodeMut<Tile>, source: Tree<Tile>) {
     fn append_node_r(dest: &mut NodeMut<Tile>, source: NodeRef<Tile>) {
@@ -308,7 +556,10 @@ fn append_tree(dest: &mut NodeMut<Tile>, source: Tree<Tile>) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rules::Tile;
+    use rand::rngs::StdRng;
+    use rand::seq::{IndexedRandom, SliceRandom};
+    use rand::{Rng, SeedableRng};
+    use rules::{Tile, Variation};
 
     /// Helper function to verify tree structure by walking it and collecting tiles in order
     fn collect_tiles_preorder(tree: &Tree<Tile>) -> Vec<Tile> {
@@ -613,7 +864,7 @@ mod tests {
 
     #[test]
     fn test_parse_error_non_double_followed_by_equals() {
-        let result = parse("1|2=(2|3)");
+        let result = parse("0|1-1|2=(2|3)");
         assert!(result.is_err());
 
         let error = result.unwrap_err();
@@ -621,6 +872,57 @@ mod tests {
         assert!(error.message.contains("Only doubles can be followed by ="));
     }
 
+    #[test]
+    fn test_parse_first_tile_with_chain_at_each_end() {
+        let tree = parse("1|2=(1|3,2|4-4|4)").unwrap();
+        let tiles = collect_tiles_preorder(&tree);
+        assert_eq!(tiles, vec![Tile::from((1, 2)), Tile::from((1, 3)), Tile::from((2, 4)), Tile::from((4, 4))]);
+        assert_eq!(count_root_children(&tree), 2);
+
+        // The chains are attached to the left and right values, in that order
+        let tree = parse("2|1=(2|3,1|4)").unwrap();
+        assert_eq!(collect_tiles_preorder(&tree)[1], Tile::from((2, 3)));
+        assert!(parse("1|2=(2|3,1|4)").is_err());
+
+        // There must be a chain for each end
+        let error = parse("1|2=(1|3)").unwrap_err();
+        assert!(error.message.contains("chain for each end"));
+        assert!(parse("1|2=(1|3,2|4,2|5)").is_err());
+    }
+
+    #[test]
+    fn test_parse_arm_markers() {
+        // The chains are attached in the order of the arms, whatever order they are written in
+        let tree = parse("5|5=(v5|6,^5|1,>5|3-3|3=(^3|1),<5|0)").unwrap();
+        let arms: Vec<Tile> = tree.root().children().map(|child| *child.value()).collect();
+        assert_eq!(arms, vec![Tile::from((0, 5)), Tile::from((3, 5)), Tile::from((1, 5)), Tile::from((5, 6))]);
+        assert_eq!(collect_tiles_preorder(&tree).len(), 7);
+
+        // A chain without a marker is on the first free arm
+        let tree = parse("5|5=(^5|1,5|0)").unwrap();
+        let arms: Vec<Tile> = tree.root().children().map(|child| *child.value()).collect();
+        assert_eq!(arms, vec![Tile::from((0, 5)), Tile::from((1, 5))]);
+        assert_eq!(collect_tiles_preorder(&parse("3|3=(3|4,3|6)").unwrap())[1], Tile::from((3, 4)));
+    }
+
+    #[test]
+    fn test_parse_error_arm_markers() {
+        // Only the first tile has a left arm
+        let error = parse("1|5-5|5=(<5|2)").unwrap_err();
+        assert!(error.message.contains("Arm '<' is not available"));
+        assert!(parse("1|5-5|5=(>5|2,^5|3,v5|4)").is_ok());
+
+        let error = parse("5|5=(^5|1,^5|2)").unwrap_err();
+        assert!(error.message.contains("used more than once"));
+        let error = parse("5|5=(5|0,5|1,5|2,5|3,5|4)").unwrap_err();
+        assert!(error.message.contains("at most 4 arms"));
+
+        // Only a double's arms can be marked, and a layout has at most two arms on a double
+        assert!(parse("1|2-2|3=(>3|4)").is_err());
+        assert!(parse("1|2=(<1|3,2|4)").is_err());
+        assert!(parse_layout("5|5=(5|0,5|1,5|2)", &Configuration::default()).is_err());
+    }
+
     #[test]
     fn test_parse_error_missing_opening_paren() {
         let result = parse("3|3=3|4)");
@@ -692,4 +994,143 @@ mod tests {
         // This should compile because ParseError implements std::error::Error
         let _: &dyn std::error::Error = &error;
     }
+
+    #[test]
+    fn test_parse_layout() {
+        let configuration = Configuration::default();
+
+        let layout = parse_layout("1|2-2|3", &configuration).unwrap();
+        assert_eq!(layout.nodes.len(), 2);
+        assert_eq!(layout.nodes[1].parent, Some(0));
+        assert_eq!(layout.open_count(1), 1);
+        assert_eq!(layout.open_count(3), 1);
+
+        let layout = parse_layout("4|4=(4|5-5|6,4|1)", &configuration).unwrap();
+        assert_eq!(layout.nodes.len(), 4);
+        assert_eq!(layout.open_count(4), 0);
+        assert_eq!(layout.open_count(6), 1);
+        assert_eq!(layout.open_count(1), 1);
+    }
+
+    #[test]
+    fn test_parse_layout_errors() {
+        let configuration = Configuration::default();
+
+        // Syntax errors are reported as by parse
+        assert!(parse_layout("1|2-3|4", &configuration).is_err());
+
+        // The tiles must be in the set
+        let error = parse_layout("6|7", &configuration).unwrap_err();
+        assert!(error.message.contains("not in a double-6 set"));
+    }
+
+    // Builds a layout by attaching random tiles to random open ends, starting with any tile
+    fn random_layout(configuration: &Configuration, rng: &mut StdRng) -> Layout {
+        let mut tiles = rules::all_tiles_as_tiles(configuration.set_id());
+        tiles.shuffle(rng);
+
+        let mut layout = Layout::from_tree(configuration, &Tree::new(tiles.pop().unwrap())).unwrap();
+        let size = rng.random_range(1..=tiles.len());
+        for tile in tiles {
+            if layout.nodes.len() >= size {
+                break;
+            }
+            let (a, b) = tile.as_tuple();
+            let mut parents = layout.get_nodes_with_open_end(a);
+            parents.extend(layout.get_nodes_with_open_end(b));
+            if let Some(&parent) = parents.choose(rng) {
+                layout.attach_unchecked(tile, Some(parent));
+            }
+        }
+        layout
+    }
+
+    // Returns true if the tiles below the two nodes are the same and are connected in the same order
+    fn same_structure(a: &Layout, a_index: usize, b: &Layout, b_index: usize) -> bool {
+        let (a_node, b_node) = (&a.nodes[a_index], &b.nodes[b_index]);
+        a_node.tile == b_node.tile
+            && a_node.children.len() == b_node.children.len()
+            && a_node.children.iter().zip(&b_node.children).all(|(&a_child, &b_child)| same_structure(a, a_child, b, b_child))
+    }
+
+    #[test]
+    fn test_parse_layout_display_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for configuration in [Configuration::default(), Configuration::new(2, Variation::Traditional, 9, 7)] {
+            for _ in 0..500 {
+                let layout = random_layout(&configuration, &mut rng);
+                let text = layout.to_string();
+                let parsed = parse_layout(&text, &configuration).unwrap_or_else(|e| panic!("{text}: {e}"));
+                assert!(same_structure(&parsed, 0, &layout, 0), "{text} was parsed as {parsed}");
+                assert_eq!(parsed.end_counts, layout.end_counts, "{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let configuration = Configuration::default();
+        let layout = parse_layout("6|6=(6|3-3|1)", &configuration).unwrap();
+        let (state, hands) = parse_position(layout, &["1|2 2|2", "5|6, 0|0", ""], "0|1, 0|2", &configuration).unwrap();
+
+        assert_eq!(hands.len(), 3);
+        assert_eq!(hands[0].tiles(), &[Tile::from((1, 2)), Tile::from((2, 2))]);
+        assert_eq!(hands[1].tiles(), &[Tile::from((5, 6)), Tile::from((0, 0))]);
+        assert!(hands[2].is_empty());
+
+        assert_eq!(state.layout.nodes.len(), 3);
+        assert_eq!(state.boneyard.count(), 2);
+        assert_eq!(state.boneyard.peek(), Some(&Tile::from((0, 1))));
+        assert!(state.can_play_tile(&Tile::from((1, 2)), Some(1)));
+    }
+
+    #[test]
+    fn test_parse_position_errors() {
+        let configuration = Configuration::default();
+        let layout = || parse_layout("6|6", &configuration).unwrap();
+
+        // A tile may only appear once
+        let error = parse_position(layout(), &["6|6"], "", &configuration).unwrap_err();
+        assert!(error.message.contains("Hand 0"));
+        assert!(error.message.contains("more than once"));
+
+        let error = parse_position(layout(), &["1|2", "0|0"], "0|0", &configuration).unwrap_err();
+        assert!(error.message.contains("Boneyard"));
+        assert_eq!(error.position, 0);
+
+        // Tiles must be in the set and well-formed
+        let error = parse_position(layout(), &["1|2 7|7"], "", &configuration).unwrap_err();
+        assert!(error.message.contains("not in a double-6 set"));
+        assert_eq!(error.position, 4);
+        assert!(parse_position(layout(), &["1|2 x"], "", &configuration).is_err());
+    }
+
+    #[test]
+    fn test_parse_hand() {
+        let hand = parse_hand("[6|6 3|5 0|2]").unwrap();
+        assert_eq!(hand.tiles(), &[Tile::from((6, 6)), Tile::from((3, 5)), Tile::from((0, 2))]);
+
+        // Brackets are optional and commas may separate the tiles
+        let hand = parse_hand(" 5|3, 2|0 ").unwrap();
+        assert_eq!(hand.tiles(), &[Tile::from((3, 5)), Tile::from((0, 2))]);
+
+        assert!(parse_hand("[]").unwrap().is_empty());
+        assert!(parse_hand("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_hand_errors() {
+        let error = parse_hand("[6|6 3|5").unwrap_err();
+        assert!(error.message.contains("']'"));
+        assert_eq!(error.position, 8);
+
+        let error = parse_hand("[6|6] 3|5").unwrap_err();
+        assert!(error.message.contains("Unexpected characters"));
+
+        let error = parse_hand("[6|6 3|5 6|6]").unwrap_err();
+        assert!(error.message.contains("more than once"));
+        assert_eq!(error.position, 9);
+
+        assert!(parse_hand("[6|6 3-5]").is_err());
+    }
 }
@@ -1,2 +1,17 @@
+pub mod batch;
+pub mod bot_player;
+pub mod client;
+pub mod engine_protocol;
+pub mod error;
+pub mod house_rules;
 pub mod layout_parser;
+pub mod protocol;
+pub mod remote_player;
+pub mod saved_game;
+pub mod scenario;
 pub mod scene_graph;
+pub mod server;
+pub mod simulation;
+pub mod svg;
+pub mod table;
+pub mod tournament;
@@ -1,21 +1,768 @@
 //! Dominoes Game Application
+//!
+//! # Command Line Syntax
+//!
+//! ```bash
+//! dominoes [--scenario <FILE> | --load <FILE> | --daily | --layout <LAYOUT>] [--seat0 <SPEC> ... --seat3 <SPEC>] \
+//!     [--match [<POINTS>]] [--rule <KEY=VALUE> ...] [--move-time <SECONDS>] [--game-time <MINUTES>] [--serious] [--log <FILE>] \
+//!     [--render-dir <DIR>] [COMMAND]
+//! ```
+//!
+//! ## Commands
+//! * `serve` - Host a network game that clients join over TCP, or over WebSockets with `--websocket`
+//! * `connect` - Join a network game hosted by `dominoes serve`
+//! * `resume` - Continue the last game played in the terminal, if it was interrupted
+//! * `exhibition` - Watch computer players play each other
+//! * `simulate` - Play a batch of games between computer players and show the results
+//! * `tournament` - Play a round robin between computer players and rank them by Elo rating (see `game::tournament`)
+//! * `engine` - Let another program use the AI through the engine protocol on stdin and stdout (see `game::engine_protocol`)
+//!
+//! With no command, a game is played in the terminal. With `--scenario`, the game continues from the position described in the
+//! file (see `game::scenario`) instead of being dealt. With `--load`, a saved game is continued where it left off (see
+//! `game::saved_game`), e.g. a copy of the game saved after every turn. With `--daily`, the game is the daily deal, which is the
+//! same for everyone on the same day (UTC). With `--layout`, the game starts from a layout (see `game::layout_parser`), e.g.
+//! `--layout "6|6-6|3"`, and the other tiles are dealt at random, for practicing a position. The layout can also be changed
+//! during a game with the `setup` command. `--seat0` to `--seat3` choose the players (see `player::player_factory`), e.g.
+//! `--seat1 mcts:hard`. The number of players is set by the highest seat given, and seats that are not given are played by
+//! people. With `--match`, rounds are played until a player has scored enough points, and the score table is shown after every
+//! round. The points are given with `--match` or with the `target` house rule, and default to
+//! `rules::Configuration::DEFAULT_TARGET_SCORE`. A game or match played in the terminal is saved after every turn until it is
+//! over, and can be continued with `resume`. `--rule` changes the configuration with a house rule (see `game::house_rules`), e.g.
+//! `--rule set=9 --rule hand=10`. `--move-time` and `--game-time` limit the time that each player may take for a move and for the
+//! whole game. A player who runs out of time loses. `--serious` disables undo and redo. `--log` writes every action of a game
+//! played in the terminal to a file, as JSON lines, for later analysis. `--render-dir` writes an SVG image of the board to a
+//! directory whenever a tile is played, making a flipbook of the game.
+//!
+//! When built with the `metrics` feature, `simulate` and `serve` show the metrics recorded by the crates (see `rules::metrics`).
+//! When built with the `tracing` feature, spans are written to stderr, filtered by `RUST_LOG`, e.g. `RUST_LOG=engine=debug`.
 
 mod dominoes_game;
 
-use crate::dominoes_game::DominoesGame;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Arg, ArgAction, ArgMatches, Command as ClapCommand};
+
+use crate::dominoes_game::{DominoesGame, autosave_path, daily_seed};
+use game::client::Client;
+use game::engine_protocol;
+use game::house_rules::{HouseRule, configure};
+use game::saved_game::SavedGame;
+use game::scenario::Scenario;
+use engine::TimeControl;
+use game::server::Server;
+use game::simulation::{ResultsFile, Simulation};
+use game::tournament::TournamentCheckpoint;
+use player::PlayerSpec;
 use rules::Configuration;
 
+// Options that choose the player in each seat
+const SEAT_ARGS: [&str; 4] = ["seat0", "seat1", "seat2", "seat3"];
+
+// How a game played in the terminal is played
+struct PlayOptions {
+    // The time allowed for the players' moves
+    time_control: TimeControl,
+    // True if turns cannot be undone
+    serious: bool,
+}
+
+// Where a game is recorded, in addition to being shown in the terminal
+struct Output {
+    // The file where every action is logged
+    log: Option<BufWriter<File>>,
+    // The directory where images of the board are written
+    render_dir: Option<PathBuf>,
+}
+
 fn main() {
+    let matches = ClapCommand::new("Dominoes")
+        .version("1.0")
+        .author("Jambolo <jambolo@users.noreply.github.com>")
+        .about("Play dominoes.")
+        .arg(
+            Arg::new("scenario")
+                .long("scenario")
+                .short('s')
+                .value_name("FILE")
+                .help("Play from the position described in a scenario file"),
+        )
+        .arg(
+            Arg::new("load")
+                .long("load")
+                .value_name("FILE")
+                .help("Continue a saved game")
+                .conflicts_with("scenario"),
+        )
+        .arg(
+            Arg::new("daily")
+                .long("daily")
+                .help("Play the daily deal, which is the same for everyone on the same day")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["scenario", "load"]),
+        )
+        .arg(
+            Arg::new("layout")
+                .long("layout")
+                .value_name("LAYOUT")
+                .help("Practice a layout, e.g. \"6|6-6|3\". The other tiles are dealt at random.")
+                .conflicts_with_all(["scenario", "load", "daily", "match"]),
+        )
+        .args(SEAT_ARGS.iter().enumerate().map(|(seat, &name)| {
+            Arg::new(name)
+                .long(name)
+                .value_name("SPEC")
+                .help(format!(
+                    "Player in seat {seat}: human[:NAME], mcts[:easy|medium|hard|ITERATIONS], greedy, score-chaser, \
+                     random[:SEED], beginner|intermediate|expert, or remote:HOST:PORT"
+                ))
+                .value_parser(clap::value_parser!(PlayerSpec))
+                .conflicts_with_all(["scenario", "load"])
+        }))
+        .arg(
+            Arg::new("match")
+                .long("match")
+                .value_name("POINTS")
+                .help("Play rounds until a player scores this many points (default: the target house rule, or 100)")
+                .num_args(0..=1)
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .conflicts_with_all(["scenario", "load"]),
+        )
+        .arg(
+            Arg::new("rule")
+                .long("rule")
+                .value_name("KEY=VALUE")
+                .help(
+                    "House rule: variation=NAME, set=N, hand=N, target=N, draw=none|until-playable|one, muggins=on|off, \
+                     partners=on|off, reserve=N, or seed=N. May be repeated.",
+                )
+                .value_parser(clap::value_parser!(HouseRule))
+                .action(ArgAction::Append)
+                .conflicts_with_all(["scenario", "load"]),
+        )
+        .arg(
+            Arg::new("move-time")
+                .long("move-time")
+                .value_name("SECONDS")
+                .help("Time allowed for each move")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("game-time")
+                .long("game-time")
+                .value_name("MINUTES")
+                .help("Time allowed for each player's moves in the whole game")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("serious")
+                .long("serious")
+                .help("Play a serious game, in which turns cannot be undone or redone")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .value_name("FILE")
+                .help("Log every action of the game to a file, as JSON lines"),
+        )
+        .arg(
+            Arg::new("render-dir")
+                .long("render-dir")
+                .value_name("DIR")
+                .help("Write an SVG image of the board to a directory whenever a tile is played"),
+        )
+        .subcommand(
+            ClapCommand::new("serve")
+                .about("Hosts a network game that clients join over TCP")
+                .arg(
+                    Arg::new("address")
+                        .long("address")
+                        .short('a')
+                        .help("Address to listen on")
+                        .default_value("0.0.0.0:7878"),
+                )
+                .arg(
+                    Arg::new("players")
+                        .long("players")
+                        .short('p')
+                        .help("Number of seats")
+                        .default_value("2")
+                        .value_parser(clap::value_parser!(u8).range(2..=4)),
+                )
+                .arg(
+                    Arg::new("websocket")
+                        .long("websocket")
+                        .short('w')
+                        .help("Accept clients over WebSockets, e.g. from a browser, instead of plain TCP")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("connect")
+                .about("Joins a network game hosted by `dominoes serve`")
+                .arg(
+                    Arg::new("address")
+                        .value_name("ADDRESS")
+                        .help("Address of the server, e.g. 192.168.1.10:7878")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .short('n')
+                        .help("Name to play under")
+                        .default_value("Player"),
+                ),
+        )
+        .subcommand(ClapCommand::new("resume").about("Continues the last game played in the terminal, if it was interrupted"))
+        .subcommand(
+            ClapCommand::new("exhibition")
+                .about("Shows a game between computer players, one action at a time")
+                .arg(
+                    Arg::new("delay")
+                        .long("delay")
+                        .short('d')
+                        .value_name("MILLISECONDS")
+                        .help("Pause after each action")
+                        .default_value("1500")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("iterations")
+                        .long("iterations")
+                        .short('i')
+                        .help("Number of search iterations for each action")
+                        .default_value("1000")
+                        .value_parser(clap::value_parser!(u32).range(1..)),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("simulate")
+                .about("Plays a batch of games between computer players and shows the results")
+                .arg(
+                    Arg::new("games")
+                        .long("games")
+                        .short('g')
+                        .help("Number of games to play")
+                        .default_value("100")
+                        .value_parser(clap::value_parser!(u32).range(1..)),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed of the first deal. Defaults to a random seed.")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("iterations")
+                        .long("iterations")
+                        .short('i')
+                        .help("Number of search iterations for each action")
+                        .default_value("1000")
+                        .value_parser(clap::value_parser!(u32).range(1..)),
+                )
+                .arg(
+                    Arg::new("checkpoint")
+                        .long("checkpoint")
+                        .value_name("DIR")
+                        .help("Directory where the progress is saved, so that an interrupted run can be resumed"),
+                )
+                .arg(
+                    Arg::new("export")
+                        .long("export")
+                        .value_name("FILE")
+                        .help("Write the result of each game to a .csv or .json file"),
+                )
+                .arg(
+                    Arg::new("resume-run")
+                        .long("resume-run")
+                        .value_name("DIR")
+                        .help("Continue the interrupted run checkpointed in a directory")
+                        .conflicts_with_all(["games", "seed", "iterations", "checkpoint"]),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("tournament")
+                .about("Plays a round robin between computer players and ranks them by Elo rating")
+                .arg(
+                    Arg::new("player")
+                        .long("player")
+                        .short('p')
+                        .value_name("SPEC")
+                        .help(
+                            "A player in the tournament: mcts[:easy|medium|hard|ITERATIONS], greedy, score-chaser, \
+                             random[:SEED], or beginner|intermediate|expert",
+                        )
+                        .required_unless_present("resume-run")
+                        .action(ArgAction::Append)
+                        .value_parser(clap::value_parser!(PlayerSpec)),
+                )
+                .arg(
+                    Arg::new("deals")
+                        .long("deals")
+                        .short('d')
+                        .help("Number of deals played by each pair of players, once from each seat")
+                        .default_value("50")
+                        .value_parser(clap::value_parser!(u32).range(1..)),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .help("Seed of the first deal. Defaults to a random seed.")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("checkpoint")
+                        .long("checkpoint")
+                        .value_name("DIR")
+                        .help("Directory where the progress is saved, so that an interrupted tournament can be resumed"),
+                )
+                .arg(
+                    Arg::new("export")
+                        .long("export")
+                        .value_name("FILE")
+                        .help("Write the result of each game to a .csv or .json file"),
+                )
+                .arg(
+                    Arg::new("resume-run")
+                        .long("resume-run")
+                        .value_name("DIR")
+                        .help("Continue the interrupted tournament checkpointed in a directory")
+                        .conflicts_with_all(["player", "deals", "seed", "checkpoint"]),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("engine").about("Lets another program use the AI through the engine protocol on stdin and stdout"),
+        )
+        .get_matches();
+
+    // Spans go to stderr, so they do not mix with the game or the engine protocol on stdout
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        .with_writer(io::stderr)
+        .init();
+
+    let time_control = TimeControl {
+        per_move: matches.get_one::<u64>("move-time").map(|&seconds| Duration::from_secs(seconds)),
+        per_game: matches.get_one::<u64>("game-time").map(|&minutes| Duration::from_secs(minutes * 60)),
+    };
+    let options = PlayOptions { time_control, serious: matches.get_flag("serious") };
+
+    let log = matches.get_one::<String>("log").map(|path| open_log(path));
+    let render_dir = matches.get_one::<String>("render-dir").map(|path| open_render_dir(path));
+    let output = Output { log, render_dir };
+    let house_rules: Vec<HouseRule> = matches.get_many::<HouseRule>("rule").unwrap_or_default().copied().collect();
+
+    match matches.subcommand() {
+        Some(("serve", serve_matches)) => serve(serve_matches),
+        Some(("connect", connect_matches)) => connect(connect_matches),
+        Some(("resume", _)) => resume(options, output),
+        Some(("exhibition", exhibition_matches)) => exhibition(exhibition_matches, &house_rules, output),
+        Some(("simulate", simulate_matches)) => simulate(simulate_matches),
+        Some(("tournament", tournament_matches)) => tournament(tournament_matches, &house_rules),
+        Some(("engine", _)) => run_engine(),
+        _ => match (matches.get_one::<String>("scenario"), matches.get_one::<String>("load")) {
+            (Some(path), _) => play_scenario(path, options, output),
+            (None, Some(path)) => {
+                println!("Welcome back to the Dominoes Game!");
+                play_saved(Path::new(path), options, output)
+            }
+            (None, None) if matches.contains_id("layout") => {
+                let layout = matches.get_one::<String>("layout").expect("The layout is given");
+                play_layout(layout, seat_specs(&matches), &house_rules, options, output)
+            }
+            (None, None) => {
+                // Points given with --match override the target house rule
+                let mut house_rules = house_rules;
+                house_rules.extend(matches.get_one::<u32>("match").map(|&points| HouseRule::TargetScore(points)));
+                let match_play = matches.contains_id("match");
+                play(matches.get_flag("daily"), seat_specs(&matches), &house_rules, match_play, options, output)
+            }
+        },
+    }
+}
+
+// Returns the players chosen with --seat0 to --seat3, or `None` if none were chosen
+fn seat_specs(matches: &ArgMatches) -> Option<Vec<PlayerSpec>> {
+    let chosen: Vec<Option<PlayerSpec>> =
+        SEAT_ARGS.iter().map(|&name| matches.get_one::<PlayerSpec>(name).cloned()).collect();
+    let num_players = chosen.iter().rposition(Option::is_some)? + 1;
+    let specs = chosen
+        .into_iter()
+        .take(num_players.max(Configuration::DEFAULT_NUM_PLAYERS))
+        .map(|spec| spec.unwrap_or(PlayerSpec::Human { name: None }))
+        .collect();
+    Some(specs)
+}
+
+// Creates the configuration of a game with house rules, or exits if the rules cannot be played
+fn configure_or_exit(num_players: usize, house_rules: &[HouseRule]) -> Configuration {
+    configure(num_players, house_rules).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    })
+}
+
+// Creates the log file, or exits if it cannot be created
+fn open_log(path: &str) -> BufWriter<File> {
+    match File::create(path) {
+        Ok(file) => BufWriter::new(file),
+        Err(e) => {
+            eprintln!("Error: Cannot create the log file {path}. {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+// Creates the directory where images of the board are written, or exits if it cannot be created
+fn open_render_dir(path: &str) -> PathBuf {
+    if let Err(e) = fs::create_dir_all(path) {
+        eprintln!("Error: Cannot create the directory {path}. {e}");
+        std::process::exit(1);
+    }
+    PathBuf::from(path)
+}
+
+// Applies the options that are common to all games played in the terminal
+fn set_up(game: &mut DominoesGame, options: PlayOptions, output: Output) {
+    game.set_time_control(options.time_control);
+    game.set_undo_allowed(!options.serious);
+    record(game, output);
+}
+
+// Records the game where the options say to, in addition to showing it
+fn record(game: &mut DominoesGame, output: Output) {
+    if let Some(log) = output.log {
+        game.set_log(Box::new(log));
+    }
+    if let Some(dir) = output.render_dir {
+        game.set_render_dir(dir);
+    }
+}
+
+// Plays a game in the terminal, or the daily deal if `daily` is true. The players are chosen by `specs`, if given. If `match_play`
+// is true, a match is played to the target score of the configuration.
+fn play(
+    daily: bool,
+    specs: Option<Vec<PlayerSpec>>,
+    house_rules: &[HouseRule],
+    match_play: bool,
+    options: PlayOptions,
+    output: Output,
+) {
     println!("Welcome to the Dominoes Game!");
 
-    // Create default configuration
-    let configuration = Configuration::default();
+    // Create the configuration for the number of players
+    let num_players = specs.as_ref().map_or(Configuration::DEFAULT_NUM_PLAYERS, Vec::len);
+    let configuration = configure_or_exit(num_players, house_rules);
 
     // Initialize the game with the configuration
-    let mut game = DominoesGame::new(&configuration);
+    let seed = daily.then(|| {
+        let (date, seed) = daily_seed(SystemTime::now());
+        println!("Playing the daily deal for {date}.");
+        seed
+    });
+    let mut game = match (specs, seed) {
+        (Some(specs), seed) => DominoesGame::with_seats(&configuration, &specs, seed),
+        (None, Some(seed)) => DominoesGame::with_seed(&configuration, seed),
+        (None, None) => DominoesGame::new(&configuration),
+    };
+    set_up(&mut game, options, output);
 
     // Run the game loop
+    if match_play {
+        game.run_match();
+    } else {
+        game.run();
+    }
+
+    println!("Thanks for playing!");
+}
+
+// Plays a game in the terminal, starting from a layout with the other tiles dealt at random. The players are chosen by `specs`, if
+// given, and are people otherwise.
+fn play_layout(layout: &str, specs: Option<Vec<PlayerSpec>>, house_rules: &[HouseRule], options: PlayOptions, output: Output) {
+    let num_players = specs.as_ref().map_or(Configuration::DEFAULT_NUM_PLAYERS, Vec::len);
+    let configuration = configure_or_exit(num_players, house_rules);
+    let scenario = Scenario::from_layout(layout, &configuration).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+
+    println!("Welcome to the Dominoes Game!");
+    println!("Practicing the layout {layout}.");
+
+    let mut game = match specs {
+        Some(specs) => DominoesGame::from_scenario_with_seats(&scenario, &specs),
+        None => DominoesGame::from_scenario(&scenario),
+    };
+    set_up(&mut game, options, output);
+    game.run();
+
+    println!("Thanks for playing!");
+}
+
+// Plays a game in the terminal, starting from the position in a scenario file
+fn play_scenario(path: &str, options: PlayOptions, output: Output) {
+    let scenario = Scenario::load(path).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+
+    println!("Welcome to the Dominoes Game!");
+    println!("Playing the scenario in {path}.");
+
+    let mut game = DominoesGame::from_scenario(&scenario);
+    set_up(&mut game, options, output);
+    game.run();
+
+    println!("Thanks for playing!");
+}
+
+// Continues the game saved by the last game played in the terminal
+fn resume(options: PlayOptions, output: Output) {
+    let path = autosave_path();
+    if !path.exists() {
+        eprintln!("Error: There is no game to resume.");
+        std::process::exit(1);
+    }
+    println!("Welcome back to the Dominoes Game!");
+    play_saved(&path, options, output);
+}
+
+// Continues a saved game in the terminal
+fn play_saved(path: &Path, options: PlayOptions, output: Output) {
+    let saved = SavedGame::load(path).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+
+    let mut game = DominoesGame::load(&saved);
+    set_up(&mut game, options, output);
+    if game.is_match() {
+        game.run_match();
+    } else {
+        game.run();
+    }
+
+    println!("Thanks for playing!");
+}
+
+// Shows a game between computer players
+fn exhibition(matches: &ArgMatches, house_rules: &[HouseRule], output: Output) {
+    let delay = *matches.get_one::<u64>("delay").expect("delay has a default value");
+    let iterations = *matches.get_one::<u32>("iterations").expect("iterations has a default value");
+
+    println!("Welcome to the Dominoes Game!");
+    println!("Watching an exhibition game.");
+
+    let configuration = configure_or_exit(Configuration::DEFAULT_NUM_PLAYERS, house_rules);
+    let mut game = DominoesGame::exhibition(&configuration, iterations, Duration::from_millis(delay));
+    record(&mut game, output);
     game.run();
+}
+
+// Plays a batch of games between computer players, checkpointing the progress if asked to
+fn simulate(matches: &ArgMatches) {
+    // Number of games played between checkpoints
+    const CHECKPOINT_INTERVAL: u32 = 10;
+
+    let (mut simulation, checkpoint) = match matches.get_one::<String>("resume-run") {
+        Some(dir) => {
+            let simulation = Simulation::load(dir).unwrap_or_else(|e| {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            });
+            println!("Resuming the run in {dir} after {} of {} games.", simulation.games_played, simulation.num_games);
+            (simulation, Some(PathBuf::from(dir)))
+        }
+        None => {
+            let num_games = *matches.get_one::<u32>("games").expect("games has a default value");
+            let iterations = *matches.get_one::<u32>("iterations").expect("iterations has a default value");
+            let seed = matches.get_one::<u64>("seed").copied().unwrap_or_else(|| {
+                SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos() as u64)
+            });
+            let checkpoint = matches.get_one::<String>("checkpoint").map(PathBuf::from);
+            if let Some(dir) = &checkpoint
+                && game::simulation::checkpoint_path(dir).exists()
+            {
+                eprintln!("Error: {} already has a run in it. Use --resume-run to continue it.", dir.display());
+                std::process::exit(1);
+            }
+            println!("Simulating {num_games} games with seed {seed}.");
+            (Simulation::new(num_games, seed, iterations), checkpoint)
+        }
+    };
+
+    let mut results = matches.get_one::<String>("export").map(|path| {
+        ResultsFile::open(path).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    // The games between checkpoints are played in parallel, and each one is tallied after the whole batch is done
+    let total = simulation.num_games;
+    let played = AtomicU32::new(simulation.games_played);
+    let runner = simulation.runner().on_progress(|_, _| {
+        eprint!("\rPlayed {} of {total} games", played.fetch_add(1, Ordering::Relaxed) + 1);
+    });
+    while !simulation.is_done() {
+        let batch = runner.run(simulation.next_seed(), simulation.games_left().min(CHECKPOINT_INTERVAL));
+        for record in &batch.records {
+            simulation.add(record);
+            if let Some(file) = &mut results
+                && let Err(e) = file.write(record)
+            {
+                eprintln!("\nWarning: The results could not be exported. {e}");
+                results = None;
+            }
+            // When exporting, the progress is saved after every game so that a resumed run does not export games again
+            if let Some(dir) = &checkpoint
+                && (simulation.games_played % CHECKPOINT_INTERVAL == 0 || simulation.is_done() || results.is_some())
+                && let Err(e) = simulation.save(dir)
+            {
+                eprintln!("\nWarning: The progress could not be saved. {e}");
+            }
+        }
+    }
+    eprintln!();
+
+    println!("Results of {} games:\n\n{}", simulation.games_played, simulation.summary());
+    show_metrics();
+}
+
+// Plays a round robin between computer players and shows the leaderboard, checkpointing the progress after each pairing if asked
+// to
+fn tournament(matches: &ArgMatches, house_rules: &[HouseRule]) {
+    fn exit<E: std::fmt::Display>(e: E) -> ! {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+
+    let (mut checkpoint, checkpoint_dir) = match matches.get_one::<String>("resume-run") {
+        Some(dir) => (TournamentCheckpoint::load(dir).unwrap_or_else(exit), Some(PathBuf::from(dir))),
+        None => {
+            let entrants: Vec<PlayerSpec> = matches.get_many::<PlayerSpec>("player").unwrap_or_default().cloned().collect();
+            let num_deals = *matches.get_one::<u32>("deals").expect("deals has a default value");
+            let seed = matches.get_one::<u64>("seed").copied().unwrap_or_else(|| {
+                SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos() as u64)
+            });
+            let checkpoint_dir = matches.get_one::<String>("checkpoint").map(PathBuf::from);
+            if let Some(dir) = &checkpoint_dir
+                && game::simulation::checkpoint_path(dir).exists()
+            {
+                exit(format!("{} already has a run in it. Use --resume-run to continue it.", dir.display()));
+            }
+            let configuration = configure_or_exit(2, house_rules);
+            (TournamentCheckpoint::new(&configuration, &entrants, seed, num_deals), checkpoint_dir)
+        }
+    };
+    let tournament = checkpoint.tournament().unwrap_or_else(exit);
+    let mut results = matches.get_one::<String>("export").map(|path| ResultsFile::open(path).unwrap_or_else(exit));
+
+    let total = tournament.num_pairings();
+    let (seed, num_deals) = (checkpoint.seed, checkpoint.num_deals);
+    match checkpoint.pairings.len() {
+        0 => println!("Playing a round robin of {total} pairings with {num_deals} deals each and seed {seed}."),
+        played => println!("Resuming the tournament after {played} of {total} pairings."),
+    }
+    while let Some(entrants) = checkpoint.next_pair(&tournament) {
+        let (pairing, records) = tournament.play_pairing(entrants, seed, num_deals);
+        let [a, b] = entrants.map(|entrant| &checkpoint.entrants[entrant]);
+        eprintln!(
+            "Pairing {} of {total}: {a} {} - {} {b} ({} drawn)",
+            checkpoint.pairings.len() + 1,
+            pairing.wins[0],
+            pairing.wins[1],
+            pairing.draws
+        );
+        for record in &records {
+            if let Some(file) = &mut results
+                && let Err(e) = file.write(record)
+            {
+                eprintln!("Warning: The results could not be exported. {e}");
+                results = None;
+            }
+        }
+        checkpoint.pairings.push(pairing);
+        if let Some(dir) = &checkpoint_dir
+            && let Err(e) = checkpoint.save(dir)
+        {
+            eprintln!("Warning: The progress could not be saved. {e}");
+        }
+    }
+
+    println!("\n{}", tournament.results(checkpoint.pairings).leaderboard());
+    show_metrics();
+}
+
+// Speaks the engine protocol on stdin and stdout
+fn run_engine() {
+    if let Err(e) = engine_protocol::run(io::stdin().lock(), io::stdout().lock()) {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+// Hosts a network game
+fn serve(matches: &ArgMatches) {
+    let address = matches.get_one::<String>("address").expect("address has a default value");
+    let num_players = *matches.get_one::<u8>("players").expect("players has a default value") as usize;
+
+    let configuration = Configuration::new(
+        num_players,
+        Configuration::DEFAULT_VARIATION,
+        Configuration::DEFAULT_SET_ID,
+        Configuration::default_starting_hand_size(num_players, Configuration::DEFAULT_VARIATION),
+    );
+
+    let server = Server::bind(address.as_str(), &configuration).unwrap_or_else(|e| {
+        eprintln!("Error: Unable to listen on {address}: {e}");
+        std::process::exit(1);
+    });
+    let server = if matches.get_flag("websocket") { server.with_websockets() } else { server };
+    println!("Waiting for {num_players} players on {address}...");
+
+    match server.run() {
+        Ok(Some(winner)) => println!("Game over. Player {winner} wins."),
+        Ok(None) => println!("Game over. It's a draw."),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    }
+    show_metrics();
+}
+
+// Shows the work done by the crates, in the Prometheus text format, if the metrics were recorded
+fn show_metrics() {
+    if cfg!(feature = "metrics") {
+        print!("\nMetrics:\n{}", rules::metrics::snapshot());
+    }
+}
+
+// Joins a network game
+fn connect(matches: &ArgMatches) {
+    let address = matches.get_one::<String>("address").expect("address is required");
+    let name = matches.get_one::<String>("name").expect("name has a default value");
+
+    let mut client = Client::connect(address.as_str(), name).unwrap_or_else(|e| {
+        eprintln!("Error: Unable to join the game at {address}: {e}");
+        std::process::exit(1);
+    });
+    println!("Joined the game at {address} as player {}.", client.player_id());
 
+    if let Err(e) = client.run() {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
     println!("Thanks for playing!");
 }
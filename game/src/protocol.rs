@@ -0,0 +1,364 @@
+//! Network play wire protocol
+//!
+//! Messages are JSON objects, one per line, or one per text message over a WebSocket, and each has a `type` field identifying
+//! it. A session goes like this:
+//!
+//! 1. The client sends `join` with its name and the protocol version it speaks. The server answers with `seated`, or with
+//!    `rejected` and closes the connection if the version is not supported.
+//! 2. The server sends `lobby` whenever a seat is filled.
+//! 3. When every seat is filled, each client receives `deal` with its own hand and a commitment to the order of the tiles (see
+//!    `rules::HiddenBoneyard`).
+//! 4. The server sends `your_turn` to the player who must act, and that client answers with `action`. An illegal action is
+//!    answered with `rejected`, and the client must send another.
+//! 5. After each action, every client receives `state_delta`. Only the player who drew a tile is told which tile it was.
+//! 6. The server sends `game_over`, revealing the order of the tiles and the salt, and the session ends. A client can check them
+//!    against the commitment with `rules::Reveal::verify`, and should check that the order holds every tile of the set and that
+//!    its hand and the tiles it drew are where they would be in it. The hands are dealt in order of seat, and the draws follow.
+//!
+//! # Examples
+//! ```
+//! use game::protocol::{ActionRequest, ClientMessage};
+//! use rules::Tile;
+//!
+//! let message = ClientMessage::Action { action: ActionRequest::Play { tile: Tile::from((3, 4)), end: Some(4) } };
+//! let json = serde_json::to_string(&message).unwrap();
+//! assert_eq!(json, r#"{"type":"action","action":{"kind":"play","tile":[3,4],"end":4}}"#);
+//! ```
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+
+use dominoes_state::{Action, DominoesError, DominoesState};
+use rules::{Commitment, Reveal, Tile};
+
+/// The version of the protocol implemented by this module
+///
+/// The version must be incremented whenever a change to the messages would prevent an older client or server from understanding
+/// them.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long a server waits for a client to complete the WebSocket handshake (see `Connection::accept_websocket`)
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A message sent from a client to the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Asks for a seat
+    Join { version: u32, name: String },
+    /// The action taken in response to `your_turn`
+    Action { action: ActionRequest },
+}
+
+/// An action requested by a client
+///
+/// A request to draw does not name a tile because the client does not know which tile is next in the boneyard.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActionRequest {
+    /// Plays a tile from the hand on an open end (`None` for the first tile)
+    Play { tile: Tile, end: Option<u8> },
+    /// Draws a tile from the boneyard
+    Draw,
+    /// Passes the turn
+    Pass,
+}
+
+impl ActionRequest {
+    /// Converts the request into an action
+    ///
+    /// The action is not checked (see `Action::validate`).
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who requested the action
+    /// * `state` - The state of the game. A request to draw takes the next tile in the boneyard.
+    ///
+    /// # Returns
+    /// The action, or an error if a tile is to be drawn but the boneyard is empty
+    pub fn to_action(self, player_id: u8, state: &DominoesState) -> Result<Action, DominoesError> {
+        match self {
+            ActionRequest::Play { tile, end } => Ok(Action::new(player_id, None, Some((tile, end)))),
+            ActionRequest::Draw => state
+                .boneyard
+                .peek()
+                .map(|tile| Action::draw(player_id, *tile))
+                .ok_or(DominoesError::EmptyBoneyard),
+            ActionRequest::Pass => Ok(Action::pass(player_id)),
+        }
+    }
+}
+
+/// A message sent from the server to one or more clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// The client has been given a seat
+    Seated {
+        version: u32,
+        player_id: u8,
+        num_players: usize,
+        set_id: u8,
+    },
+    /// The players in the lobby have changed
+    Lobby { players: Vec<String> },
+    /// The game has started. The hand is the recipient's own, and the commitment is to the order in which the tiles were dealt
+    /// and are to be drawn.
+    Deal {
+        players: Vec<String>,
+        hand: Vec<Tile>,
+        boneyard_count: usize,
+        whose_turn: u8,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        commitment: Option<Commitment>,
+    },
+    /// It is the recipient's turn to act
+    YourTurn,
+    /// The client's last message was not accepted
+    Rejected { reason: String },
+    /// The result of an action
+    StateDelta(StateDelta),
+    /// The game is over. All hands are revealed, and so is the order of the tiles that was committed to in the deal.
+    GameOver {
+        winner: Option<u8>,
+        hands: Vec<Vec<Tile>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reveal: Option<Reveal>,
+    },
+}
+
+/// The changes to the state resulting from an action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateDelta {
+    /// The player who took the action
+    pub player_id: u8,
+    /// True if the player drew a tile
+    pub drew: bool,
+    /// The tile that was drawn. This is only revealed to the player who drew it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tile_drawn: Option<Tile>,
+    /// The tile played and the end it was played on, if any
+    pub tile_played: Option<(Tile, Option<u8>)>,
+    /// Number of tiles left in the boneyard
+    pub boneyard_count: usize,
+    /// Number of tiles in each player's hand
+    pub hand_sizes: Vec<usize>,
+    /// The player whose turn is next
+    pub whose_turn: u8,
+}
+
+/// A connection that sends and receives JSON messages
+///
+/// The messages usually travel over a network connection, one per line, but they can travel over any pair of streams, such as the
+/// standard input and output of another program (see `with_streams`), or over a WebSocket, one per text message (see
+/// `accept_websocket`), so that a browser can play.
+pub struct Connection {
+    transport: Transport,
+}
+
+// How the messages of a connection travel
+enum Transport {
+    // One message per line
+    Lines { reader: Box<dyn BufRead + Send>, writer: Box<dyn Write + Send> },
+    // One message per WebSocket text message
+    WebSocket(Box<WebSocket<TcpStream>>),
+}
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection").finish_non_exhaustive()
+    }
+}
+
+impl Connection {
+    /// Creates a connection from an open stream
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self::with_streams(stream.try_clone()?, stream))
+    }
+
+    /// Creates a connection that receives messages from one stream and sends them to another
+    ///
+    /// # Arguments
+    /// * `reader` - The stream that messages are received from, e.g. the standard output of another program
+    /// * `writer` - The stream that messages are sent to, e.g. the standard input of another program
+    pub fn with_streams<R, W>(reader: R, writer: W) -> Self
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        Self {
+            transport: Transport::Lines {
+                reader: Box::new(BufReader::new(reader)),
+                writer: Box::new(writer),
+            },
+        }
+    }
+
+    /// Creates a connection from an open stream by completing the server's side of the WebSocket handshake
+    ///
+    /// A client that has not completed the handshake within `HANDSHAKE_TIMEOUT` is refused, so that a client that connects and
+    /// says nothing cannot hold up a server that is waiting for others.
+    ///
+    /// # Returns
+    /// The connection, or an error if the client does not complete the handshake in time
+    pub fn accept_websocket(stream: TcpStream) -> io::Result<Self> {
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let socket = tungstenite::accept(stream).map_err(io::Error::other)?;
+        socket.get_ref().set_read_timeout(None)?;
+        Ok(Self { transport: Transport::WebSocket(Box::new(socket)) })
+    }
+
+    /// Opens a connection to a server
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Self::new(TcpStream::connect(address)?)
+    }
+
+    /// Opens a WebSocket connection to a server
+    ///
+    /// # Arguments
+    /// * `address` - The address of the server, e.g. "192.168.1.10:7878"
+    pub fn connect_websocket(address: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        let (socket, _) = tungstenite::client(format!("ws://{address}/"), stream).map_err(io::Error::other)?;
+        Ok(Self { transport: Transport::WebSocket(Box::new(socket)) })
+    }
+
+    /// Sends a message
+    pub fn send<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let json = serde_json::to_string(message).map_err(io::Error::other)?;
+        match &mut self.transport {
+            Transport::Lines { writer, .. } => {
+                writeln!(writer, "{json}")?;
+                writer.flush()
+            }
+            Transport::WebSocket(socket) => socket.send(Message::text(json)).map_err(websocket_error),
+        }
+    }
+
+    /// Waits for the next message
+    ///
+    /// # Returns
+    /// The message, or an error if the connection is closed or the message cannot be decoded
+    pub fn receive<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        let result = match &mut self.transport {
+            Transport::Lines { reader, .. } => {
+                let mut line = String::new();
+                if reader.read_line(&mut line)? == 0 {
+                    return Err(closed());
+                }
+                serde_json::from_str(line.trim())
+            }
+            Transport::WebSocket(socket) => loop {
+                // Pings are answered by the socket itself, so only messages with data are decoded
+                match socket.read().map_err(websocket_error)? {
+                    Message::Text(text) => break serde_json::from_str(text.as_str()),
+                    Message::Binary(data) => break serde_json::from_slice(&data),
+                    Message::Close(_) => return Err(closed()),
+                    Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+                }
+            },
+        };
+        result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// Returns the error for a connection that the other end has closed
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed")
+}
+
+// Converts a WebSocket error into an I/O error, so that a closed WebSocket looks like any other closed connection
+fn websocket_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(e) => e,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => closed(),
+        e => io::Error::other(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_message_format() {
+        let join = ClientMessage::Join { version: PROTOCOL_VERSION, name: "Alice".to_string() };
+        assert_eq!(serde_json::to_string(&join).unwrap(), r#"{"type":"join","version":1,"name":"Alice"}"#);
+
+        let draw: ClientMessage = serde_json::from_str(r#"{"type":"action","action":{"kind":"draw"}}"#).unwrap();
+        assert_eq!(draw, ClientMessage::Action { action: ActionRequest::Draw });
+
+        let play: ClientMessage =
+            serde_json::from_str(r#"{"type":"action","action":{"kind":"play","tile":[4,3],"end":null}}"#).unwrap();
+        assert_eq!(play, ClientMessage::Action { action: ActionRequest::Play { tile: Tile::from((3, 4)), end: None } });
+    }
+
+    #[test]
+    fn test_action_request_to_action() {
+        let mut state = DominoesState::new(&rules::Configuration::default());
+        let next = *state.boneyard.peek().unwrap();
+        assert_eq!(ActionRequest::Draw.to_action(1, &state), Ok(Action::draw(1, next)));
+        assert_eq!(ActionRequest::Pass.to_action(1, &state), Ok(Action::pass(1)));
+        let play = ActionRequest::Play { tile: Tile::from((3, 4)), end: Some(4) };
+        assert_eq!(play.to_action(0, &state), Ok(Action::play_unchecked(0, Tile::from((3, 4)), Some(4))));
+
+        while state.draw_tile().is_some() {}
+        assert_eq!(ActionRequest::Draw.to_action(1, &state), Err(DominoesError::EmptyBoneyard));
+    }
+
+    #[test]
+    fn test_server_message_round_trip() {
+        let messages = vec![
+            ServerMessage::Seated { version: PROTOCOL_VERSION, player_id: 1, num_players: 2, set_id: 6 },
+            ServerMessage::Lobby { players: vec!["Alice".to_string()] },
+            ServerMessage::Deal {
+                players: vec!["Alice".to_string(), "Bob".to_string()],
+                hand: vec![Tile::from((0, 0)), Tile::from((5, 6))],
+                boneyard_count: 14,
+                whose_turn: 0,
+                commitment: Some(Reveal::new(vec![Tile::from((0, 0))], [1; 32]).commitment()),
+            },
+            ServerMessage::YourTurn,
+            ServerMessage::Rejected { reason: "No".to_string() },
+            ServerMessage::StateDelta(StateDelta {
+                player_id: 0,
+                drew: true,
+                tile_drawn: Some(Tile::from((2, 3))),
+                tile_played: None,
+                boneyard_count: 13,
+                hand_sizes: vec![8, 7],
+                whose_turn: 0,
+            }),
+            ServerMessage::GameOver { winner: None, hands: vec![vec![], vec![Tile::from((1, 1))]], reveal: None },
+            ServerMessage::GameOver {
+                winner: Some(0),
+                hands: vec![vec![], vec![]],
+                reveal: Some(Reveal::new(vec![Tile::from((0, 0)), Tile::from((0, 1))], [2; 32])),
+            },
+        ];
+        for message in messages {
+            let json = serde_json::to_string(&message).unwrap();
+            assert_eq!(serde_json::from_str::<ServerMessage>(&json).unwrap(), message, "{json}");
+        }
+    }
+
+    #[test]
+    fn test_state_delta_hides_missing_drawn_tile() {
+        let delta = ServerMessage::StateDelta(StateDelta {
+            player_id: 0,
+            drew: true,
+            tile_drawn: None,
+            tile_played: None,
+            boneyard_count: 13,
+            hand_sizes: vec![8, 7],
+            whose_turn: 0,
+        });
+        let json = serde_json::to_string(&delta).unwrap();
+        assert!(json.starts_with(r#"{"type":"state_delta","player_id":0,"drew":true,"tile_played":null"#));
+    }
+}
@@ -0,0 +1,157 @@
+//! Remote player implementation
+//!
+//! This module provides a player whose decisions are made by a client on the other end of a network connection.
+
+use std::io;
+
+use dominoes_state::{Action, DominoesState, Hand};
+use player::Player;
+use rules::Configuration;
+
+use crate::protocol::{ClientMessage, Connection, ServerMessage};
+
+/// A player controlled by a client over the network
+///
+/// The hand is held here, on the server, so every action requested by the client can be checked with `Action::validate` before it
+/// is accepted. Illegal requests are rejected and the client is asked again.
+///
+/// Since the `Player` methods cannot fail, a network error is recorded instead, and the player passes. The error can be retrieved
+/// with `take_error`.
+#[derive(Debug)]
+pub struct RemotePlayer {
+    /// Unique identifier for this player in the game
+    player_id: u8,
+    /// The connection to the client
+    connection: Connection,
+    /// The tiles currently held by this player
+    hand: Hand,
+    /// Display name for this player
+    name: String,
+    /// The first network error encountered, if any
+    error: Option<io::Error>,
+}
+
+impl RemotePlayer {
+    /// Creates a new remote player
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `_configuration` - Game rules and settings, which this player does not need
+    /// * `name` - Display name for this player, as given by the client
+    /// * `connection` - The connection to the client
+    pub fn new(player_id: u8, _configuration: &Configuration, name: &str, connection: Connection) -> Self {
+        Self {
+            player_id,
+            connection,
+            hand: Hand::new(),
+            name: name.to_string(),
+            error: None,
+        }
+    }
+
+    /// Sends a message to the client
+    ///
+    /// Nothing is sent after a network error has occurred.
+    pub fn send(&mut self, message: &ServerMessage) {
+        if self.error.is_none()
+            && let Err(e) = self.connection.send(message)
+        {
+            self.error = Some(e);
+        }
+    }
+
+    /// Returns the network error that occurred, if any, and clears it
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    // Asks the client for an action until a legal one is received
+    fn receive_action(&mut self, state: &DominoesState) -> io::Result<Action> {
+        self.connection.send(&ServerMessage::YourTurn)?;
+        loop {
+            let result = match self.connection.receive::<ClientMessage>()? {
+                ClientMessage::Action { action } => action
+                    .to_action(self.player_id, state)
+                    .and_then(|action| action.validate(state, &self.hand).map(|_| action))
+                    .map_err(|e| e.to_string()),
+                ClientMessage::Join { .. } => Err("Already seated".to_string()),
+            };
+            match result {
+                Ok(action) => return Ok(action),
+                Err(reason) => self.connection.send(&ServerMessage::Rejected { reason })?,
+            }
+        }
+    }
+}
+
+impl Player for RemotePlayer {
+    fn reset(&mut self) {
+        self.hand = Hand::new();
+    }
+
+    fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+        self.hand = hand;
+    }
+
+    fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+        let mut new_state = state.clone();
+        if self.error.is_some() {
+            new_state.pass();
+            return (Action::pass(self.player_id), new_state);
+        }
+
+        let action = match self.receive_action(state) {
+            Ok(action) => action,
+            Err(e) => {
+                self.error = Some(e);
+                new_state.pass();
+                return (Action::pass(self.player_id), new_state);
+            }
+        };
+
+        take_action(&mut self.hand, &mut new_state, &action);
+        (action, new_state)
+    }
+
+    fn has_playable_tile(&self, state: &DominoesState) -> bool {
+        self.hand
+            .tiles()
+            .iter()
+            .any(|tile| state.can_play_tile(tile, None))
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+
+    fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> u8 {
+        self.player_id
+    }
+}
+
+// Takes a validated action for a player whose hand is held here, updating the hand and the state
+pub(crate) fn take_action(hand: &mut Hand, state: &mut DominoesState, action: &Action) {
+    if action.tile_drawn.is_some() {
+        let tile = state.draw_tile().expect("A validated draw must succeed");
+        hand.add_tile(tile);
+    }
+    if let Some((tile, end)) = action.tile_played {
+        hand.remove_tile(&tile);
+        state.play_tile_unchecked(tile, end);
+    }
+    if action.is_pass() {
+        state.pass();
+    }
+}
@@ -0,0 +1,141 @@
+//! Saved games
+//!
+//! A saved game holds everything needed to continue a game in the terminal where it left off: the configuration, the state
+//! (including the order of the boneyard), the hands, and the actions taken so far, so that they can still be undone. It is saved
+//! as JSON. Unlike a scenario (see `crate::scenario`), it is not meant to be written by hand.
+
+use std::fs;
+use std::path::Path;
+
+use dominoes_state::{DominoesState, GameStats, Hand, History};
+use rules::Configuration;
+use serde::{Deserialize, Serialize};
+
+use crate::error::GameError;
+
+/// A game in progress, as it is saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// The configuration of the game
+    pub configuration: Configuration,
+    /// The state of the game after the actions in the history
+    pub state: DominoesState,
+    /// The hands of the players, in order of player ID
+    pub hands: Vec<Hand>,
+    /// The actions taken so far
+    pub history: History,
+    /// If the game is a round of a match, the points scored by each team in each of the rounds before it (see
+    /// `Configuration::team`). Games saved before matches were saved have none.
+    #[serde(default)]
+    pub rounds: Option<Vec<Vec<u32>>>,
+    /// If the game is a round of a match, the statistics of the players in the rounds before it
+    #[serde(default)]
+    pub stats: Option<GameStats>,
+}
+
+impl SavedGame {
+    /// Loads a saved game from a file
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// The saved game, or an error if the file cannot be read or is invalid
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GameError> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path).map_err(|source| GameError::Read { path: path.to_path_buf(), source })?;
+        let saved: Self = serde_json::from_str(&input).map_err(|source| GameError::Json { path: path.to_path_buf(), source })?;
+        if saved.hands.len() != saved.configuration.num_players() {
+            return Err(GameError::HandCount {
+                path: path.to_path_buf(),
+                expected: saved.configuration.num_players(),
+                found: saved.hands.len(),
+            });
+        }
+        let num_teams = saved.configuration.num_teams();
+        if let Some(round) = saved.rounds.iter().flatten().find(|round| round.len() != num_teams) {
+            return Err(GameError::RoundPoints { path: path.to_path_buf(), expected: num_teams, found: round.len() });
+        }
+        Ok(saved)
+    }
+
+    /// Saves the game to a file
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file. An existing file is replaced.
+    ///
+    /// # Returns
+    /// An error if the file cannot be written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GameError> {
+        let path = path.as_ref();
+        let json = serde_json::to_string(self)?;
+        fs::write(path, json).map_err(|source| GameError::Write { path: path.to_path_buf(), source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dominoes_state::Action;
+    use hidden_game_player::State;
+    use rules::{Boneyard, Layout, Tile};
+
+    // Returns a game in which Alice has played the double six and Bob has drawn a tile
+    fn saved_game() -> SavedGame {
+        let configuration = Configuration::default();
+        let boneyard = Boneyard::with(vec![Tile::from((0, 0)), Tile::from((1, 1))]);
+        let mut state = DominoesState::with_position(&configuration, Layout::new(&configuration), boneyard);
+        let mut history = History::new();
+        for action in [Action::play_unchecked(0, Tile::from((6, 6)), None), Action::draw(1, Tile::from((0, 0)))] {
+            state = state.apply(&action);
+            history.add_action_with_fingerprint(action, state.fingerprint());
+        }
+        let mut hands = vec![Hand::new(), Hand::new()];
+        hands[0].add_tile(Tile::from((2, 3)));
+        hands[1].add_tile(Tile::from((4, 5)));
+        hands[1].add_tile(Tile::from((0, 0)));
+        SavedGame { configuration, state, hands, history, rounds: None, stats: None }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("saved-game-{}.json", std::process::id()));
+        let saved = saved_game();
+        saved.save(&path).unwrap();
+        let loaded = SavedGame::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        let mut loaded = loaded.unwrap();
+        assert_eq!(loaded.configuration.variation(), saved.configuration.variation());
+        assert_eq!(loaded.state.fingerprint(), saved.state.fingerprint());
+        assert_eq!(loaded.hands[1].tiles(), saved.hands[1].tiles());
+        assert_eq!(loaded.history.get_actions(), saved.history.get_actions());
+        assert_eq!(loaded.history.fingerprints(), saved.history.fingerprints());
+
+        // The tile that was drawn can be put back in the boneyard
+        loaded.state.undo(&saved.history.get_actions()[1]);
+        assert_eq!(loaded.state.boneyard.peek(), Some(&Tile::from((0, 0))));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert!(SavedGame::load("no/such/file.json").unwrap_err().to_string().contains("Failed to read"));
+
+        let path = std::env::temp_dir().join(format!("saved-game-errors-{}.json", std::process::id()));
+        fs::write(&path, "not json").unwrap();
+        let error = SavedGame::load(&path);
+        let mut saved = saved_game();
+        saved.hands.pop();
+        saved.save(&path).unwrap();
+        let mismatched = SavedGame::load(&path);
+        let mut saved = saved_game();
+        saved.rounds = Some(vec![vec![10, 0], vec![5]]);
+        saved.save(&path).unwrap();
+        let short_round = SavedGame::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(error, Err(GameError::Json { .. })));
+        assert!(matches!(mismatched, Err(GameError::HandCount { expected: 2, found: 1, .. })));
+        assert!(short_round.unwrap_err().to_string().contains("Expected the points of 2 teams in each round, found 1"));
+    }
+}
@@ -0,0 +1,404 @@
+//! Scenarios
+//!
+//! A scenario describes a complete position (the variation, the layout, the hands, the boneyard, and whose turn it is) in one
+//! string or file, so that positions can be set up for tests, puzzles, and analysis. Each line has a key and a value separated by
+//! a colon. Blank lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # Bob must block the 1s
+//! variation: traditional
+//! layout: 6|6=(6|3-3|1)
+//! hand: [1|2 2|2]
+//! hand: [5|6 0|0]
+//! boneyard: [0|1 0|2]
+//! turn: 1
+//! ```
+//!
+//! - `variation`: The variation (traditional, allfives, allsevens, bergen, blind, fiveup, mexicantrain, or chickenfoot). Optional;
+//!   the default is traditional.
+//! - `set`: The domino set, e.g. 6 for double-six. Optional; the default is 12 for mexicantrain, 9 for chickenfoot, and 6
+//!   otherwise.
+//! - `layout`: The layout, in the syntax described in [`crate::layout_parser::parse`]. Optional; the layout is empty by default.
+//! - `hand`: A player's hand (see [`crate::layout_parser::parse_hand`]). There is one `hand` line for each player, in order of
+//!   player ID, and there are 2 to 4 players.
+//! - `boneyard`: The tiles in the boneyard, in the order in which they are drawn. Optional; the boneyard is empty by default.
+//! - `turn`: The ID of the player whose turn it is. Optional; the default is 0.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use dominoes_state::{DominoesState, Hand};
+use rules::{Boneyard, Configuration, Layout, Tile, Variation};
+
+use crate::error::GameError;
+use crate::layout_parser::{ParseError, parse_layout, parse_position_at};
+
+// Names of the variations in a scenario
+pub(crate) const VARIATION_NAMES: &[(&str, Variation)] = &[
+    ("traditional", Variation::Traditional),
+    ("allfives", Variation::AllFives),
+    ("allsevens", Variation::AllSevens),
+    ("bergen", Variation::Bergen),
+    ("blind", Variation::Blind),
+    ("fiveup", Variation::FiveUp),
+    ("mexicantrain", Variation::MexicanTrain),
+    ("chickenfoot", Variation::ChickenFoot),
+];
+
+/// A position described by a scenario
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// The configuration of the game. The number of players is the number of hands.
+    pub configuration: Configuration,
+    /// The state of the game. `whose_turn` is set from the scenario.
+    pub state: DominoesState,
+    /// The hands of the players, in order of player ID
+    pub hands: Vec<Hand>,
+}
+
+impl Scenario {
+    /// Parses a scenario
+    ///
+    /// # Arguments
+    /// * `input` - The scenario
+    ///
+    /// # Returns
+    /// The scenario, or `Err(ParseError)` if it is invalid. The position of the error is relative to the start of the input.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game::scenario::Scenario;
+    ///
+    /// let input = "
+    ///     layout: 6|6=(6|3-3|1)
+    ///     hand: [1|2 2|2]
+    ///     hand: [5|6 0|0]
+    ///     turn: 1
+    /// ";
+    /// let scenario = Scenario::parse(input).unwrap();
+    /// assert_eq!(scenario.configuration.num_players(), 2);
+    /// assert_eq!(scenario.state.whose_turn, 1);
+    /// assert_eq!(scenario.hands[1].len(), 2);
+    /// ```
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        // Collect the values first, because the configuration is needed to parse the layout and the hands
+        let mut values: Vec<(&str, &str, usize, usize)> = Vec::new(); // key, value, key offset, value offset
+        let mut offset = 0;
+        for line in input.split_inclusive('\n') {
+            let line_offset = offset;
+            offset += line.len();
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let key_offset = line_offset + (line.len() - line.trim_start().len());
+            let Some((key, value)) = trimmed.split_once(':') else {
+                return Err(ParseError { message: "Expected 'key: value'".to_string(), position: key_offset });
+            };
+            let key = key.trim();
+            if !matches!(key, "variation" | "set" | "layout" | "hand" | "boneyard" | "turn") {
+                return Err(ParseError { message: format!("Unknown key '{key}'"), position: key_offset });
+            }
+            if key != "hand" && values.iter().any(|&(other, ..)| other == key) {
+                return Err(ParseError { message: format!("Duplicate '{key}'"), position: key_offset });
+            }
+            values.push((key, value, key_offset, key_offset + trimmed.find(':').unwrap() + 1));
+        }
+        let find = |key: &str| values.iter().find(|&&(other, ..)| other == key).map(|&(_, value, _, offset)| (value, offset));
+        let hands: Vec<(&str, usize)> =
+            values.iter().filter(|&&(key, ..)| key == "hand").map(|&(_, value, _, offset)| (value, offset)).collect();
+
+        let variation = match find("variation") {
+            None => Configuration::DEFAULT_VARIATION,
+            Some((value, offset)) => VARIATION_NAMES
+                .iter()
+                .find(|(name, _)| *name == value.trim())
+                .map(|&(_, variation)| variation)
+                .ok_or_else(|| ParseError { message: format!("Unknown variation '{}'", value.trim()), position: offset })?,
+        };
+        let set_id = match find("set") {
+            None => Configuration::default_set_id(variation),
+            Some((value, offset)) => value
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&set_id| set_id <= rules::MAX_PIPS)
+                .ok_or_else(|| ParseError {
+                    message: format!("Expected a set from 0 to {}", rules::MAX_PIPS),
+                    position: offset,
+                })?,
+        };
+        if !(2..=4).contains(&hands.len()) {
+            return Err(ParseError {
+                message: format!("Expected 2 to 4 hands, found {}", hands.len()),
+                position: input.len(),
+            });
+        }
+        let num_players = hands.len();
+        let configuration = Configuration::new(
+            num_players,
+            variation,
+            set_id,
+            Configuration::default_starting_hand_size(num_players, variation),
+        );
+
+        let whose_turn = match find("turn") {
+            None => 0,
+            Some((value, offset)) => value
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|&player_id| (player_id as usize) < num_players)
+                .ok_or_else(|| ParseError {
+                    message: format!("Expected a player ID from 0 to {}", num_players - 1),
+                    position: offset,
+                })?,
+        };
+
+        let layout = match find("layout") {
+            None => Layout::new(&configuration),
+            Some((value, offset)) => parse_layout(value, &configuration).map_err(|e| ParseError {
+                message: e.message,
+                position: offset + e.position,
+            })?,
+        };
+        let boneyard = find("boneyard").unwrap_or(("", 0));
+        let (mut state, hands) = parse_position_at(layout, &hands, boneyard, &configuration)?;
+        state.whose_turn = whose_turn;
+        Ok(Self { configuration, state, hands })
+    }
+
+    /// Creates a scenario for practicing a layout
+    ///
+    /// The tiles that are not in the layout are shuffled (see `Boneyard::new`), and each player is dealt a starting hand from
+    /// them, or an equal share if there are not enough. The rest are left in the boneyard. It is player 0's turn.
+    ///
+    /// # Arguments
+    /// * `layout` - The layout, in the syntax described in [`crate::layout_parser::parse`]
+    /// * `configuration` - The configuration of the game
+    ///
+    /// # Returns
+    /// The scenario, or `Err(ParseError)` if the layout is invalid
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game::scenario::Scenario;
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let scenario = Scenario::from_layout("6|6=(6|3-3|1)", &configuration).unwrap();
+    /// assert_eq!(scenario.state.layout.nodes.len(), 3);
+    /// assert_eq!(scenario.hands[0].len(), configuration.starting_hand_size());
+    /// assert_eq!(scenario.state.boneyard.count(), 28 - 3 - 2 * configuration.starting_hand_size());
+    /// ```
+    pub fn from_layout(layout: &str, configuration: &Configuration) -> Result<Self, ParseError> {
+        let layout = parse_layout(layout, configuration)?;
+        let played: HashSet<Tile> = layout.nodes.iter().map(|node| node.tile).collect();
+        let mut tiles: Vec<Tile> =
+            Boneyard::new(configuration).remaining().iter().copied().filter(|tile| !played.contains(tile)).collect();
+
+        let num_players = configuration.num_players();
+        let hand_size = configuration.starting_hand_size().min(tiles.len() / num_players);
+        let hands = (0..num_players)
+            .map(|_| {
+                let mut hand = Hand::new();
+                for tile in tiles.drain(..hand_size) {
+                    hand.add_tile(tile);
+                }
+                hand
+            })
+            .collect();
+        let boneyard = Boneyard::with(tiles).with_reserve(configuration.reserve());
+        let state = DominoesState::with_position(configuration, layout, boneyard);
+        Ok(Self { configuration: configuration.clone(), state, hands })
+    }
+
+    /// Loads a scenario from a file
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    /// # Returns
+    /// The scenario, or an error if the file cannot be read or is invalid
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GameError> {
+        let path = path.as_ref();
+        let input = fs::read_to_string(path).map_err(|source| GameError::Read { path: path.to_path_buf(), source })?;
+        Self::parse(&input).map_err(|source| GameError::Scenario { path: path.to_path_buf(), source })
+    }
+
+    /// Saves the scenario to a file
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file. An existing file is replaced.
+    ///
+    /// # Returns
+    /// An error if the file cannot be written
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GameError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string()).map_err(|source| GameError::Write { path: path.to_path_buf(), source })
+    }
+}
+
+/// Formats the scenario so that it can be parsed by `Scenario::parse`
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Writes a list of tiles as [a|b c|d ...]
+        fn tiles(list: &[Tile]) -> String {
+            let tiles: Vec<String> = list.iter().map(|tile| tile.to_string()).collect();
+            format!("[{}]", tiles.join(" "))
+        }
+
+        let variation = VARIATION_NAMES
+            .iter()
+            .find(|&&(_, variation)| variation == self.configuration.variation())
+            .map(|&(name, _)| name)
+            .expect("Every variation has a name");
+        writeln!(f, "variation: {variation}")?;
+        writeln!(f, "set: {}", self.configuration.set_id())?;
+        if !self.state.layout.is_empty() {
+            writeln!(f, "layout: {}", self.state.layout)?;
+        }
+        for hand in &self.hands {
+            writeln!(f, "hand: {}", tiles(hand.tiles()))?;
+        }
+        writeln!(f, "boneyard: {}", tiles(self.state.boneyard.remaining()))?;
+        writeln!(f, "turn: {}", self.state.whose_turn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = "\
+# Bob must block the 1s
+variation: allfives
+layout: 6|6=(6|3-3|1)
+hand: [1|2 2|2]
+hand: [5|6 0|0]
+
+boneyard: [0|1 0|2]
+turn: 1
+";
+
+    #[test]
+    fn test_parse() {
+        let scenario = Scenario::parse(SCENARIO).unwrap();
+        assert_eq!(scenario.configuration.variation(), Variation::AllFives);
+        assert_eq!(scenario.configuration.num_players(), 2);
+        assert_eq!(scenario.state.layout.nodes.len(), 3);
+        assert_eq!(scenario.hands[0].tiles(), &[Tile::from((1, 2)), Tile::from((2, 2))]);
+        assert_eq!(scenario.hands[1].tiles(), &[Tile::from((5, 6)), Tile::from((0, 0))]);
+        assert_eq!(scenario.state.boneyard.peek(), Some(&Tile::from((0, 1))));
+        assert_eq!(scenario.state.whose_turn, 1);
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let scenario = Scenario::parse("hand: [6|6]\nhand: []\nhand: [1|1]").unwrap();
+        assert_eq!(scenario.configuration.num_players(), 3);
+        assert_eq!(scenario.configuration.variation(), Configuration::DEFAULT_VARIATION);
+        assert_eq!(scenario.configuration.set_id(), Configuration::DEFAULT_SET_ID);
+        assert!(scenario.state.layout.is_empty());
+        assert_eq!(scenario.state.boneyard.count(), 0);
+        assert_eq!(scenario.state.whose_turn, 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = Scenario::parse("hand: [1|2]\nhands: [3|4]").unwrap_err();
+        assert!(error.message.contains("Unknown key 'hands'"));
+        assert_eq!(error.position, 12);
+
+        let error = Scenario::parse("hand: [1|2]").unwrap_err();
+        assert!(error.message.contains("Expected 2 to 4 hands"));
+
+        let error = Scenario::parse("hand: [1|2]\nhand: [3|4]\nturn: 2").unwrap_err();
+        assert!(error.message.contains("player ID"));
+
+        // The position of an error within a value is relative to the start of the input
+        let error = Scenario::parse("hand: [1|2]\nhand: [3|4 1|2]").unwrap_err();
+        assert!(error.message.contains("Hand 1"));
+        assert_eq!(error.position, 23);
+
+        let error = Scenario::parse("layout: 1|2\nlayout: 2|3").unwrap_err();
+        assert!(error.message.contains("Duplicate"));
+
+        let error = Scenario::parse("variation: muggins\nhand: [1|2]\nhand: [3|4]").unwrap_err();
+        assert!(error.message.contains("Unknown variation"));
+        assert_eq!(error.position, 10);
+
+        // The set determines which tiles are valid
+        let error = Scenario::parse("set: 3\nhand: [1|2]\nhand: [3|4 5|5]").unwrap_err();
+        assert!(error.message.contains("not in a double-3 set"));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let scenario = Scenario::parse(SCENARIO).unwrap();
+        let text = scenario.to_string();
+        assert!(text.contains("layout: 6|6=(6|3-3|1)\n"));
+        assert!(text.contains("hand: [1|2 2|2]\n"));
+
+        let parsed = Scenario::parse(&text).unwrap();
+        assert_eq!(parsed.configuration.variation(), scenario.configuration.variation());
+        assert_eq!(parsed.state.layout.to_string(), scenario.state.layout.to_string());
+        assert_eq!(parsed.state.fingerprint, scenario.state.fingerprint);
+        assert_eq!(parsed.state.boneyard.remaining(), scenario.state.boneyard.remaining());
+        assert_eq!(parsed.state.whose_turn, scenario.state.whose_turn);
+        for (parsed, hand) in parsed.hands.iter().zip(scenario.hands.iter()) {
+            assert_eq!(parsed.tiles(), hand.tiles());
+        }
+    }
+
+    #[test]
+    fn test_from_layout() {
+        let configuration = Configuration::default().with_seed(7);
+        let scenario = Scenario::from_layout("6|6=(6|3-3|1,6|5)", &configuration).unwrap();
+        assert_eq!(scenario.state.whose_turn, 0);
+
+        // Every tile is in the layout, a hand, or the boneyard, once
+        let mut tiles: Vec<Tile> = scenario.state.layout.nodes.iter().map(|node| node.tile).collect();
+        tiles.extend(scenario.hands.iter().flat_map(|hand| hand.tiles().iter().copied()));
+        tiles.extend(scenario.state.boneyard.remaining().iter().copied());
+        assert_eq!(tiles.iter().collect::<HashSet<_>>().len(), 28);
+        assert_eq!(tiles.len(), 28);
+
+        // The deal is determined by the seed of the configuration
+        let again = Scenario::from_layout("6|6=(6|3-3|1,6|5)", &configuration).unwrap();
+        assert_eq!(again.hands[0].tiles(), scenario.hands[0].tiles());
+
+        assert!(Scenario::from_layout("6|6-5|5", &configuration).is_err());
+    }
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join(format!("scenario-save-{}.txt", std::process::id()));
+        Scenario::parse(SCENARIO).unwrap().save(&path).unwrap();
+        let scenario = Scenario::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(scenario.unwrap().hands[1].len(), 2);
+
+        // The layout does not have to start with a double
+        let scenario = Scenario::parse("layout: 1|2=(1|1,2|3)\nhand: []\nhand: []").unwrap();
+        scenario.save(&path).unwrap();
+        let scenario = Scenario::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(scenario.unwrap().state.layout.to_string(), "1|2=(1|1,2|3)");
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("scenario-{}.txt", std::process::id()));
+        fs::write(&path, SCENARIO).unwrap();
+        let scenario = Scenario::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(scenario.unwrap().state.whose_turn, 1);
+
+        assert!(matches!(Scenario::load("no/such/scenario.txt"), Err(GameError::Read { .. })));
+    }
+}
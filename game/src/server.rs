@@ -0,0 +1,323 @@
+//! Network play server
+//!
+//! This module hosts a dominoes game over TCP, or over WebSockets so that browsers can play. Clients connect and join a lobby,
+//! and they are seated in the order in which they join. Once every seat is filled, the server deals the hands and runs the game.
+//! The server holds the authoritative state: every action received from a client is checked with `Action::validate` before it is
+//! applied, and the resulting changes are broadcast to all clients.
+//!
+//! See the `protocol` module for the messages exchanged with clients.
+
+use std::io;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+
+use dominoes_state::{DominoesState, Hand};
+use engine::{first_player, round_result, turn_is_over};
+use player::Player;
+use rules::{Boneyard, Configuration, HiddenBoneyard, RngStream};
+
+use crate::protocol::{ClientMessage, Connection, PROTOCOL_VERSION, ServerMessage, StateDelta};
+use crate::remote_player::RemotePlayer;
+
+/// A game server that accepts clients over TCP
+///
+/// # Examples
+/// ```no_run
+/// use game::server::Server;
+/// use rules::Configuration;
+///
+/// let configuration = Configuration::default();
+/// let server = Server::bind("0.0.0.0:7878", &configuration).expect("Failed to bind");
+/// let winner = server.run().expect("Network error");
+/// ```
+pub struct Server {
+    configuration: Configuration,
+    listener: TcpListener,
+    /// Whether clients connect with WebSockets rather than plain TCP
+    websockets: bool,
+}
+
+impl Server {
+    /// Creates a server listening on the given address
+    ///
+    /// # Arguments
+    /// * `address` - The address to listen on, e.g. "0.0.0.0:7878"
+    /// * `configuration` - The configuration of the games hosted by the server. Its number of players is the number of seats.
+    ///
+    /// # Returns
+    /// The server, or an error if the address cannot be bound
+    pub fn bind<A: ToSocketAddrs>(address: A, configuration: &Configuration) -> io::Result<Self> {
+        Ok(Self {
+            configuration: configuration.clone(),
+            listener: TcpListener::bind(address)?,
+            websockets: false,
+        })
+    }
+
+    /// Returns the server with clients connecting with WebSockets rather than plain TCP
+    ///
+    /// The messages are the same, one per text message (see `Connection::accept_websocket`).
+    pub fn with_websockets(mut self) -> Self {
+        self.websockets = true;
+        self
+    }
+
+    /// Returns the address that the server is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Fills the seats, then hosts a single game
+    ///
+    /// # Returns
+    /// The ID of the winner, or `None` if the game is a draw. An error is returned if a client disconnects.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    pub fn run(&self) -> io::Result<Option<u8>> {
+        let mut seats = self.lobby()?;
+        let (mut state, order) = self.deal(&mut seats)?;
+        self.play(&mut seats, &mut state, order)
+    }
+
+    // Accepts clients until every seat is filled
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    fn lobby(&self) -> io::Result<Vec<RemotePlayer>> {
+        let num_players = self.configuration.num_players();
+        let mut seats: Vec<RemotePlayer> = Vec::with_capacity(num_players);
+        while seats.len() < num_players {
+            let (stream, _) = self.listener.accept()?;
+            let mut connection = if self.websockets {
+                // A client that fails the handshake or does not finish it in time is not a player, so it is dropped
+                match Connection::accept_websocket(stream) {
+                    Ok(connection) => connection,
+                    Err(_) => continue,
+                }
+            } else {
+                Connection::new(stream)?
+            };
+
+            // The first message must be a request to join with a supported version. Anything else loses the connection.
+            let reason = match connection.receive::<ClientMessage>() {
+                Ok(ClientMessage::Join { version, name }) if version == PROTOCOL_VERSION => {
+                    let player_id = seats.len() as u8;
+                    connection.send(&ServerMessage::Seated {
+                        version: PROTOCOL_VERSION,
+                        player_id,
+                        num_players,
+                        set_id: self.configuration.set_id(),
+                    })?;
+                    seats.push(RemotePlayer::new(player_id, &self.configuration, &name, connection));
+
+                    let players = seats.iter().map(|seat| seat.name().to_string()).collect();
+                    broadcast(&mut seats, &ServerMessage::Lobby { players })?;
+                    continue;
+                }
+                Ok(ClientMessage::Join { version, .. }) => format!("Unsupported protocol version {version}"),
+                Ok(_) | Err(_) => "Expected a join message".to_string(),
+            };
+            let _ = connection.send(&ServerMessage::Rejected { reason });
+        }
+        Ok(seats)
+    }
+
+    // Deals the hands, determines who goes first, and tells everyone. The order of the tiles is committed to in the deal, and the
+    // hidden boneyard holding it is returned so that it can be revealed when the game is over.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    fn deal(&self, seats: &mut [RemotePlayer]) -> io::Result<(DominoesState, HiddenBoneyard)> {
+        // If nobody can start, the hands are redealt from a new shuffle
+        let mut rng = self.configuration.rng(RngStream::Deal);
+        let mut state = DominoesState::with_rng(&self.configuration, &mut rng);
+        let hands = state.boneyard.deal_round(&self.configuration, &mut rng);
+        let mut tiles = hands.concat();
+        tiles.extend_from_slice(state.boneyard.remaining());
+        let order = HiddenBoneyard::from_boneyard(Boneyard::with(tiles).with_reserve(state.boneyard.reserve()));
+        let commitment = order.commitment();
+        for (seat, tiles) in seats.iter_mut().zip(hands) {
+            seat.reset();
+            seat.set_up_from(Hand::from(tiles), &state);
+        }
+        let hands: Vec<&Hand> = seats.iter().map(|seat| seat.hand()).collect();
+        state.whose_turn = first_player(&self.configuration, &hands).expect("The hands are redealt until somebody can start");
+
+        let players: Vec<String> = seats.iter().map(|seat| seat.name().to_string()).collect();
+        for seat in seats.iter_mut() {
+            let deal = ServerMessage::Deal {
+                players: players.clone(),
+                hand: seat.hand().tiles().to_vec(),
+                boneyard_count: state.boneyard.count(),
+                whose_turn: state.whose_turn,
+                commitment: Some(commitment),
+            };
+            seat.send(&deal);
+        }
+        check_errors(seats)?;
+        Ok((state, order))
+    }
+
+    // Runs the game until it is over, then reveals the order of the tiles
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    fn play(&self, seats: &mut [RemotePlayer], state: &mut DominoesState, order: HiddenBoneyard) -> io::Result<Option<u8>> {
+        while !state.game_is_over {
+            let player_id = state.whose_turn;
+            let (action, new_state) = seats[player_id as usize].my_turn(state);
+            check_errors(seats)?;
+            *state = new_state;
+
+            let hands: Vec<&Hand> = seats.iter().map(|seat| seat.hand()).collect();
+            if let Some(winner) = round_result(&self.configuration, state, &hands) {
+                state.mark_game_over(winner);
+            } else if turn_is_over(&self.configuration, &action) {
+                state.advance_turn();
+            }
+
+            let hand_sizes: Vec<usize> = seats.iter().map(|seat| seat.hand().len()).collect();
+            for seat in seats.iter_mut() {
+                // Only the player who drew a tile may see it
+                let tile_drawn = if seat.id() == player_id { action.tile_drawn } else { None };
+                seat.send(&ServerMessage::StateDelta(StateDelta {
+                    player_id,
+                    drew: action.is_draw(),
+                    tile_drawn,
+                    tile_played: action.tile_played,
+                    boneyard_count: state.boneyard.count(),
+                    hand_sizes: hand_sizes.clone(),
+                    whose_turn: state.whose_turn,
+                }));
+            }
+            check_errors(seats)?;
+        }
+
+        let hands = seats.iter().map(|seat| seat.hand().tiles().to_vec()).collect();
+        broadcast(seats, &ServerMessage::GameOver { winner: state.winner, hands, reveal: Some(order.reveal()) })?;
+        Ok(state.winner)
+    }
+}
+
+// Sends a message to every seated client
+fn broadcast(seats: &mut [RemotePlayer], message: &ServerMessage) -> io::Result<()> {
+    for seat in seats.iter_mut() {
+        seat.send(message);
+    }
+    check_errors(seats)
+}
+
+// Returns the first network error recorded by any of the seats
+fn check_errors(seats: &mut [RemotePlayer]) -> io::Result<()> {
+    match seats.iter_mut().find_map(|seat| seat.take_error()) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ActionRequest;
+    use rules::{Commitment, Tile};
+    use std::thread;
+
+    // Connects to the server and joins the lobby
+    fn join(address: SocketAddr, name: &str, version: u32) -> Connection {
+        let mut connection = Connection::connect(address).unwrap();
+        connection
+            .send(&ClientMessage::Join { version, name: name.to_string() })
+            .unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_lobby_seats_players_in_order() {
+        let configuration = Configuration::default();
+        let server = Server::bind("127.0.0.1:0", &configuration).unwrap();
+        let address = server.local_addr().unwrap();
+
+        let clients = thread::spawn(move || {
+            // Bob does not connect until Alice has been seated
+            let mut alice = join(address, "Alice", PROTOCOL_VERSION);
+            let alice_seat = alice.receive::<ServerMessage>().unwrap();
+            let mut bob = join(address, "Bob", PROTOCOL_VERSION);
+            let bob_seat = bob.receive::<ServerMessage>().unwrap();
+            (alice_seat, bob_seat, alice, bob)
+        });
+        let seats = server.lobby().unwrap();
+        let (alice_seat, bob_seat, _alice, _bob) = clients.join().unwrap();
+
+        assert_eq!(
+            alice_seat,
+            ServerMessage::Seated { version: PROTOCOL_VERSION, player_id: 0, num_players: 2, set_id: 6 }
+        );
+        assert_eq!(
+            bob_seat,
+            ServerMessage::Seated { version: PROTOCOL_VERSION, player_id: 1, num_players: 2, set_id: 6 }
+        );
+        assert_eq!(seats.iter().map(|seat| seat.name()).collect::<Vec<_>>(), vec!["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn test_lobby_rejects_clients_that_do_not_join() {
+        let configuration = Configuration::default();
+        let server = Server::bind("127.0.0.1:0", &configuration).unwrap();
+        let address = server.local_addr().unwrap();
+
+        let clients = thread::spawn(move || {
+            let mut rude = Connection::connect(address).unwrap();
+            rude.send(&ClientMessage::Action { action: ActionRequest::Pass }).unwrap();
+            let rejected = rude.receive::<ServerMessage>().unwrap();
+            let mut old = join(address, "Old", PROTOCOL_VERSION + 1);
+            let unsupported = old.receive::<ServerMessage>().unwrap();
+            let mut alice = join(address, "Alice", PROTOCOL_VERSION);
+            alice.receive::<ServerMessage>().unwrap();
+            let bob = join(address, "Bob", PROTOCOL_VERSION);
+            (rejected, unsupported, alice, bob)
+        });
+        let seats = server.lobby().unwrap();
+        let (rejected, unsupported, _alice, _bob) = clients.join().unwrap();
+        assert!(matches!(rejected, ServerMessage::Rejected { .. }));
+        assert!(matches!(unsupported, ServerMessage::Rejected { reason } if reason.contains("version")));
+        assert_eq!(seats.iter().map(|seat| seat.name()).collect::<Vec<_>>(), vec!["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn test_websocket_lobby_and_deal() {
+        let configuration = Configuration::default();
+        let server = Server::bind("127.0.0.1:0", &configuration).unwrap().with_websockets();
+        let address = server.local_addr().unwrap();
+
+        let clients = thread::spawn(move || {
+            // A client that does not complete the handshake is dropped
+            let mut plain = join(address, "Plain", PROTOCOL_VERSION);
+            let dropped = plain.receive::<ServerMessage>().is_err();
+
+            let mut connections: Vec<Connection> = ["Alice", "Bob"]
+                .into_iter()
+                .map(|name| {
+                    let mut connection = Connection::connect_websocket(&address.to_string()).unwrap();
+                    connection.send(&ClientMessage::Join { version: PROTOCOL_VERSION, name: name.to_string() }).unwrap();
+                    assert!(matches!(connection.receive::<ServerMessage>().unwrap(), ServerMessage::Seated { .. }));
+                    connection
+                })
+                .collect();
+            let deals: Vec<(Vec<Tile>, Option<Commitment>)> = connections
+                .iter_mut()
+                .map(|connection| loop {
+                    if let ServerMessage::Deal { hand, commitment, .. } = connection.receive::<ServerMessage>().unwrap() {
+                        break (hand, commitment);
+                    }
+                })
+                .collect();
+            (dropped, deals)
+        });
+        let mut seats = server.lobby().unwrap();
+        let (_, order) = server.deal(&mut seats).unwrap();
+        let reveal = order.reveal();
+        let (dropped, deals) = clients.join().unwrap();
+
+        assert!(dropped);
+        assert_eq!(seats.iter().map(|seat| seat.name()).collect::<Vec<_>>(), vec!["Alice", "Bob"]);
+        assert_eq!(reveal.tiles.len(), 28);
+        for (seat, (hand, commitment)) in seats.iter().zip(&deals) {
+            assert_eq!(seat.hand().tiles(), hand.as_slice());
+            assert!(reveal.verify(&commitment.unwrap()));
+            let start = seat.id() as usize * hand.len();
+            assert_eq!(reveal.tiles[start..start + hand.len()], hand[..]);
+        }
+    }
+}
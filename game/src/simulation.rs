@@ -0,0 +1,602 @@
+//! Simulations
+//!
+//! A simulation plays a batch of games between computer players and tallies the results, e.g. to measure how changes to the AI
+//! affect its play. The deals are determined by a seed: game `i` of the batch is dealt with the seed `seed + i`, so the position
+//! in the random number stream is just the number of games played.
+//!
+//! A long simulation can be checkpointed to a directory (see `Simulation::save`). The checkpoint holds the results so far and the
+//! number of games played, so a run that is interrupted can be continued with `Simulation::load` instead of being restarted.
+//!
+//! The result of each game can also be written to a results file (see `ResultsFile`), as CSV or JSON lines, so that the results
+//! can be analyzed with other tools. A summary of the results, with the average scores, game lengths, and times, can be shown as a
+//! table (see `Simulation::summary`).
+//!
+//! The games are played by a `BatchRunner`, several at a time. To measure the strength of players from a program, e.g. a tuning
+//! script, `estimate_win_rate` plays a batch of games between any computer players and returns how often each one won.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use player::PlayerSpec;
+use rules::Configuration;
+
+use crate::batch::BatchRunner;
+use crate::error::GameError;
+use crate::table::Table;
+
+/// The name of the checkpoint file in a checkpoint directory
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// The result of a game in a simulation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// The seed of the deal
+    pub seed: u64,
+    /// The players, by player ID
+    pub players: Vec<String>,
+    /// The winner, or `None` if the game was drawn
+    pub winner: Option<u8>,
+    /// The points scored by each player, by player ID. The winner scores the pips left in the other players' hands.
+    pub scores: Vec<u32>,
+    /// The number of actions in the game
+    pub moves: usize,
+    /// The time taken to play the game, in milliseconds
+    pub duration_ms: f64,
+}
+
+impl GameRecord {
+    /// The header of a results file in CSV format
+    pub const CSV_HEADER: &'static str = "seed,players,winner,scores,moves,duration_ms";
+
+    /// Returns the record as a row of a results file in CSV format
+    ///
+    /// The players and the scores are separated by semicolons, and the winner is empty if the game was drawn.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game::simulation::GameRecord;
+    ///
+    /// let record = GameRecord {
+    ///     seed: 42,
+    ///     players: vec!["mcts:1000".to_string(), "mcts:1000".to_string()],
+    ///     winner: Some(1),
+    ///     scores: vec![0, 17],
+    ///     moves: 23,
+    ///     duration_ms: 1520.25,
+    /// };
+    /// assert_eq!(record.to_csv(), "42,mcts:1000;mcts:1000,1,0;17,23,1520.25");
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let scores: Vec<String> = self.scores.iter().map(u32::to_string).collect();
+        format!(
+            "{},{},{},{},{},{}",
+            self.seed,
+            self.players.join(";"),
+            self.winner.map_or(String::new(), |winner| winner.to_string()),
+            scores.join(";"),
+            self.moves,
+            self.duration_ms
+        )
+    }
+}
+
+/// The format of a results file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsFormat {
+    /// Comma-separated values, with a header
+    Csv,
+    /// A JSON object on each line
+    Json,
+}
+
+impl ResultsFormat {
+    /// Returns the format indicated by the extension of a file, `.csv` or `.json`, or `None` if the extension is neither
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(ResultsFormat::Csv),
+            "json" | "jsonl" => Some(ResultsFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A file that the result of each game is written to
+///
+/// Results are appended to an existing file, so the results of a run that is resumed are added to those written before it was
+/// interrupted.
+pub struct ResultsFile {
+    /// Where the results are written
+    writer: BufWriter<File>,
+    /// The format of the file
+    format: ResultsFormat,
+}
+
+impl ResultsFile {
+    /// Opens a results file, creating it if it does not exist
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file. The format is determined by its extension (see `ResultsFormat::from_path`).
+    ///
+    /// # Returns
+    /// The file, or an error if the extension is not supported or the file cannot be opened
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, GameError> {
+        let path = path.as_ref();
+        let format = ResultsFormat::from_path(path).ok_or_else(|| GameError::ResultsFormat(path.to_path_buf()))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|source| GameError::Open { path: path.to_path_buf(), source })?;
+        let is_empty = file.metadata().map_or(true, |metadata| metadata.len() == 0);
+        let mut results = Self { writer: BufWriter::new(file), format };
+        if format == ResultsFormat::Csv && is_empty {
+            results.write_line(GameRecord::CSV_HEADER)?;
+        }
+        Ok(results)
+    }
+
+    /// Writes the result of a game
+    ///
+    /// # Returns
+    /// An error if the result cannot be written
+    pub fn write(&mut self, record: &GameRecord) -> Result<(), GameError> {
+        let line = match self.format {
+            ResultsFormat::Csv => record.to_csv(),
+            ResultsFormat::Json => serde_json::to_string(record)?,
+        };
+        self.write_line(&line)
+    }
+
+    // Writes a line and flushes it, so that the results survive an interruption
+    fn write_line(&mut self, line: &str) -> Result<(), GameError> {
+        writeln!(self.writer, "{line}")?;
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// A batch of games between computer players, and the results so far
+///
+/// # Examples
+/// ```rust
+/// # use game::simulation::Simulation;
+///
+/// let mut simulation = Simulation::new(2, 42, 10);
+/// while !simulation.is_done() {
+///     simulation.play_next();
+/// }
+/// assert_eq!(simulation.wins.iter().sum::<u32>() + simulation.draws, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Simulation {
+    /// The seed of the first deal
+    pub seed: u64,
+    /// The number of games in the batch
+    pub num_games: u32,
+    /// The number of MCTS iterations used by the players to choose each action
+    pub iterations: u32,
+    /// The number of games played so far
+    pub games_played: u32,
+    /// The number of games won by each player, by player ID
+    pub wins: Vec<u32>,
+    /// The number of games that were drawn
+    pub draws: u32,
+    /// The points scored by each player in the games played so far, by player ID. Checkpoints saved before the points were
+    /// tallied have none.
+    #[serde(default)]
+    pub total_scores: Vec<u64>,
+    /// The number of actions in the games played so far
+    #[serde(default)]
+    pub total_moves: u64,
+    /// The time taken to play the games played so far, in microseconds
+    #[serde(default)]
+    pub total_duration_us: u64,
+}
+
+impl Simulation {
+    /// Creates a simulation in which no games have been played
+    ///
+    /// The games are played with the default configuration.
+    ///
+    /// # Arguments
+    /// * `num_games` - The number of games to play
+    /// * `seed` - The seed of the first deal
+    /// * `iterations` - The number of MCTS iterations used by the players to choose each action
+    pub fn new(num_games: u32, seed: u64, iterations: u32) -> Self {
+        Self {
+            seed,
+            num_games,
+            iterations,
+            games_played: 0,
+            wins: vec![0; Configuration::DEFAULT_NUM_PLAYERS],
+            draws: 0,
+            total_scores: vec![0; Configuration::DEFAULT_NUM_PLAYERS],
+            total_moves: 0,
+            total_duration_us: 0,
+        }
+    }
+
+    /// Returns true if every game has been played
+    pub fn is_done(&self) -> bool {
+        self.games_played >= self.num_games
+    }
+
+    /// Returns the seed of the next game to be played
+    pub fn next_seed(&self) -> u64 {
+        self.seed.wrapping_add(u64::from(self.games_played))
+    }
+
+    /// Returns the number of games that have not been played yet
+    pub fn games_left(&self) -> u32 {
+        self.num_games.saturating_sub(self.games_played)
+    }
+
+    /// Returns a runner that plays the games of this simulation
+    ///
+    /// The games are played with the default configuration, between MCTS players that use the simulation's number of iterations.
+    pub fn runner<'a>(&self) -> BatchRunner<'a> {
+        let players = vec![PlayerSpec::Mcts { iterations: self.iterations }; Configuration::DEFAULT_NUM_PLAYERS];
+        BatchRunner::new(&Configuration::default(), players).expect("MCTS players are computer players")
+    }
+
+    /// Plays the next game and adds its result to the tally
+    ///
+    /// # Returns
+    /// The result of the game
+    ///
+    /// # Panics
+    /// Panics if every game has already been played
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(game = self.games_played, iterations = self.iterations)))]
+    pub fn play_next(&mut self) -> GameRecord {
+        assert!(!self.is_done(), "Every game has already been played");
+        let record = self.runner().play_game(self.next_seed());
+        self.add(&record);
+        record
+    }
+
+    /// Adds the result of the next game to the tally
+    ///
+    /// This is used to tally games played by a runner (see `runner`), in the order of their seeds.
+    ///
+    /// # Panics
+    /// Panics if the game was not dealt with the next seed
+    pub fn add(&mut self, record: &GameRecord) {
+        assert_eq!(record.seed, self.next_seed(), "The games must be added in the order of their seeds");
+        match record.winner {
+            Some(player_id) => self.wins[player_id as usize] += 1,
+            None => self.draws += 1,
+        }
+        for (total, &score) in self.total_scores.iter_mut().zip(&record.scores) {
+            *total += u64::from(score);
+        }
+        self.total_moves += record.moves as u64;
+        self.total_duration_us += (record.duration_ms * 1000.0).round() as u64;
+        self.games_played += 1;
+    }
+
+    /// Returns a summary of the results so far: the wins, the win rate, and the average score of each player, the draws, the
+    /// average number of actions in a game, and the average time taken to play one
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game::simulation::Simulation;
+    ///
+    /// let mut simulation = Simulation::new(1, 42, 10);
+    /// simulation.play_next();
+    /// let summary = simulation.summary().to_string();
+    /// assert!(summary.starts_with("Result"));
+    /// assert!(summary.contains("Player 1"));
+    /// assert!(summary.contains("Average moves"));
+    /// ```
+    pub fn summary(&self) -> Table {
+        let games = f64::from(self.games_played.max(1));
+        let percent = |count: u32| format!("{:.1}%", 100.0 * f64::from(count) / games);
+        let mut table = Table::new(["Result", "Games", "Rate", "Avg score"]);
+        for (player_id, &wins) in self.wins.iter().enumerate() {
+            let score = self.total_scores.get(player_id).map_or(0.0, |&total| total as f64 / games);
+            table.add_row([format!("Player {player_id} won"), wins.to_string(), percent(wins), format!("{score:.1}")]);
+        }
+        table.add_row(["Drawn".to_string(), self.draws.to_string(), percent(self.draws)]);
+        table.add_separator();
+        table.add_row(["Average moves".to_string(), format!("{:.1}", self.total_moves as f64 / games)]);
+        table.add_row(["Average ms".to_string(), format!("{:.1}", self.total_duration_us as f64 / 1000.0 / games)]);
+        table
+    }
+
+    /// Saves a checkpoint to a directory
+    ///
+    /// The checkpoint is written to a temporary file that then replaces the previous checkpoint, so an interruption while saving
+    /// leaves the previous checkpoint intact.
+    ///
+    /// # Arguments
+    /// * `dir` - The checkpoint directory. It is created if it does not exist.
+    ///
+    /// # Returns
+    /// An error if the checkpoint cannot be written
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<(), GameError> {
+        let json = serde_json::to_string_pretty(self)?;
+        write_checkpoint(dir.as_ref(), &json)
+    }
+
+    /// Loads the checkpoint in a directory
+    ///
+    /// # Arguments
+    /// * `dir` - The checkpoint directory
+    ///
+    /// # Returns
+    /// The simulation, or an error if there is no valid checkpoint in the directory
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, GameError> {
+        let path = checkpoint_path(dir.as_ref());
+        let json = fs::read_to_string(&path).map_err(|source| GameError::Read { path: path.clone(), source })?;
+        let mut simulation: Self = serde_json::from_str(&json).map_err(|source| GameError::Json { path: path.clone(), source })?;
+        if simulation.total_scores.is_empty() {
+            simulation.total_scores = vec![0; Configuration::DEFAULT_NUM_PLAYERS];
+        }
+        if simulation.wins.len() != Configuration::DEFAULT_NUM_PLAYERS
+            || simulation.total_scores.len() != Configuration::DEFAULT_NUM_PLAYERS
+            || simulation.wins.iter().sum::<u32>() + simulation.draws != simulation.games_played
+        {
+            return Err(GameError::ResultsMismatch(path));
+        }
+        Ok(simulation)
+    }
+}
+
+/// How often each player won a batch of games (see `estimate_win_rate`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WinStats {
+    /// The number of games played
+    pub num_games: u32,
+    /// The number of games won by each player, by player ID
+    pub wins: Vec<u32>,
+    /// The number of games that were drawn
+    pub draws: u32,
+}
+
+impl WinStats {
+    /// Creates the stats of a batch in which no games have been played
+    ///
+    /// # Arguments
+    /// * `num_players` - The number of players in each game
+    pub fn new(num_players: usize) -> Self {
+        Self { num_games: 0, wins: vec![0; num_players], draws: 0 }
+    }
+
+    /// Adds the result of a game
+    pub fn add(&mut self, record: &GameRecord) {
+        match record.winner {
+            Some(player_id) => self.wins[player_id as usize] += 1,
+            None => self.draws += 1,
+        }
+        self.num_games += 1;
+    }
+
+    /// Returns the fraction of the games won by a player, or 0 if no games were played
+    pub fn win_rate(&self, player_id: u8) -> f64 {
+        rate(self.wins[player_id as usize], self.num_games)
+    }
+
+    /// Returns the fraction of the games that were drawn, or 0 if no games were played
+    pub fn draw_rate(&self) -> f64 {
+        rate(self.draws, self.num_games)
+    }
+
+    /// Returns the margin of error of a player's win rate at 95% confidence
+    ///
+    /// The win rate of the player against the same opponents is probably within this much of the estimate. The margin shrinks
+    /// with the square root of the number of games, so four times as many games are needed to halve it.
+    pub fn margin_of_error(&self, player_id: u8) -> f64 {
+        if self.num_games == 0 {
+            return 0.0;
+        }
+        let p = self.win_rate(player_id);
+        1.96 * (p * (1.0 - p) / f64::from(self.num_games)).sqrt()
+    }
+}
+
+/// Estimates how often each of a set of computer players wins by playing a batch of games in parallel
+///
+/// Game `i` of the batch is dealt with the seed `seed + i`, as in a `Simulation`. The games are played on all available cores (see
+/// `BatchRunner`).
+///
+/// # Arguments
+/// * `configuration` - Game rules and settings
+/// * `players` - The players, by player ID. There must be one for each player in the configuration, and none can be human.
+/// * `num_games` - The number of games to play
+/// * `seed` - The seed of the first deal
+///
+/// # Returns
+/// The number of games won by each player and the number drawn, or an error if the players are not valid
+///
+/// # Examples
+/// ```rust
+/// # use game::simulation::estimate_win_rate;
+/// # use player::PlayerSpec;
+/// # use rules::Configuration;
+///
+/// let players = [PlayerSpec::Mcts { iterations: 10 }, PlayerSpec::Greedy];
+/// let stats = estimate_win_rate(&Configuration::default(), &players, 4, 42).unwrap();
+/// assert_eq!(stats.wins.iter().sum::<u32>() + stats.draws, 4);
+/// assert!((0.0..=1.0).contains(&stats.win_rate(0)));
+/// ```
+pub fn estimate_win_rate(
+    configuration: &Configuration,
+    players: &[PlayerSpec],
+    num_games: u32,
+    seed: u64,
+) -> Result<WinStats, GameError> {
+    Ok(BatchRunner::new(configuration, players.to_vec())?.run(seed, num_games).stats)
+}
+
+/// Returns the path of the checkpoint file in a checkpoint directory
+pub fn checkpoint_path(dir: &Path) -> PathBuf {
+    dir.join(CHECKPOINT_FILE)
+}
+
+// Writes a checkpoint to a directory, creating the directory if it does not exist. The checkpoint is written to a temporary file
+// that then replaces the previous checkpoint, so an interruption while saving leaves the previous checkpoint intact.
+pub(crate) fn write_checkpoint(dir: &Path, json: &str) -> Result<(), GameError> {
+    fs::create_dir_all(dir).map_err(|source| GameError::CreateDir { path: dir.to_path_buf(), source })?;
+    let path = checkpoint_path(dir);
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, json).map_err(|source| GameError::Write { path: temporary.clone(), source })?;
+    fs::rename(&temporary, &path).map_err(|source| GameError::Write { path, source })
+}
+
+// Returns a count as a fraction of a total, or 0 if the total is 0
+fn rate(count: u32, total: u32) -> f64 {
+    if total == 0 { 0.0 } else { f64::from(count) / f64::from(total) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation_play() {
+        let mut simulation = Simulation::new(3, 7, 10);
+        assert!(!simulation.is_done());
+        for _ in 0..3 {
+            simulation.play_next();
+        }
+        assert!(simulation.is_done());
+        assert_eq!(simulation.games_played, 3);
+        assert_eq!(simulation.wins.iter().sum::<u32>() + simulation.draws, 3);
+    }
+
+    #[test]
+    fn test_simulation_summary() {
+        let mut simulation = Simulation::new(2, 0, 10);
+        let record = |seed, winner, scores: Vec<u32>, moves| GameRecord {
+            seed,
+            players: vec!["a".to_string(), "b".to_string()],
+            winner,
+            scores,
+            moves,
+            duration_ms: 1.5,
+        };
+        simulation.add(&record(0, Some(1), vec![0, 12], 20));
+        simulation.add(&record(1, None, vec![0, 0], 31));
+        assert_eq!(simulation.total_scores, vec![0, 12]);
+        assert_eq!((simulation.total_moves, simulation.total_duration_us), (51, 3000));
+
+        let expected = "\
+Result         Games   Rate  Avg score
+-------------  -----  -----  ---------
+Player 0 won       0   0.0%        0.0
+Player 1 won       1  50.0%        6.0
+Drawn              1  50.0%
+-------------  -----  -----  ---------
+Average moves   25.5
+Average ms       1.5
+";
+        assert_eq!(simulation.summary().to_string(), expected);
+    }
+
+    #[test]
+    fn test_simulation_add_from_runner() {
+        let mut simulation = Simulation::new(4, 20, 10);
+        simulation.play_next();
+        assert_eq!(simulation.games_left(), 3);
+        let results = simulation.runner().run(simulation.next_seed(), simulation.games_left());
+        for record in &results.records {
+            simulation.add(record);
+        }
+        assert!(simulation.is_done());
+        assert_eq!(results.records[0].players, ["mcts:10", "mcts:10"]);
+        assert_eq!(simulation.wins.iter().sum::<u32>() + simulation.draws, 4);
+    }
+
+    #[test]
+    fn test_simulation_game_record() {
+        let mut simulation = Simulation::new(2, 7, 10);
+        simulation.play_next();
+        let record = simulation.play_next();
+        assert_eq!(record.seed, 8);
+        assert_eq!(record.players, ["mcts:10", "mcts:10"]);
+        assert_eq!(record.scores.len(), 2);
+        // Only the winner scores
+        for (player_id, &score) in record.scores.iter().enumerate() {
+            if record.winner != Some(player_id as u8) {
+                assert_eq!(score, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_results_file() {
+        let dir = std::env::temp_dir().join(format!("dominoes-results-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let record = GameRecord {
+            seed: 3,
+            players: vec!["a".to_string(), "b".to_string()],
+            winner: None,
+            scores: vec![0, 0],
+            moves: 9,
+            duration_ms: 0.5,
+        };
+
+        // The CSV header is only written to a new file
+        let csv = dir.join("results.csv");
+        ResultsFile::open(&csv).unwrap().write(&record).unwrap();
+        ResultsFile::open(&csv).unwrap().write(&record).unwrap();
+        assert_eq!(fs::read_to_string(&csv).unwrap(), format!("{}\n3,a;b,,0;0,9,0.5\n3,a;b,,0;0,9,0.5\n", GameRecord::CSV_HEADER));
+
+        let json = dir.join("results.json");
+        ResultsFile::open(&json).unwrap().write(&record).unwrap();
+        let line = fs::read_to_string(&json).unwrap();
+        assert_eq!(serde_json::from_str::<GameRecord>(&line).unwrap(), record);
+
+        assert!(matches!(ResultsFile::open(dir.join("results.txt")), Err(GameError::ResultsFormat(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_simulation_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("dominoes-simulation-test-{}", std::process::id()));
+        let mut simulation = Simulation::new(5, 11, 10);
+        simulation.play_next();
+        simulation.save(&dir).unwrap();
+
+        // An interrupted run continues where the checkpoint left off
+        let mut resumed = Simulation::load(&dir).unwrap();
+        assert_eq!(resumed, simulation);
+        resumed.play_next();
+        assert_eq!(resumed.games_played, 2);
+
+        // A checkpoint whose results do not add up is rejected
+        simulation.draws += 1;
+        simulation.save(&dir).unwrap();
+        assert!(matches!(Simulation::load(&dir), Err(GameError::ResultsMismatch(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(Simulation::load(&dir), Err(GameError::Read { .. })));
+    }
+
+    #[test]
+    fn test_estimate_win_rate() {
+        let configuration = Configuration::default();
+        let players = [PlayerSpec::Greedy, PlayerSpec::Mcts { iterations: 10 }];
+        let stats = estimate_win_rate(&configuration, &players, 6, 3).unwrap();
+        assert_eq!(stats.num_games, 6);
+        assert_eq!(stats.wins.iter().sum::<u32>() + stats.draws, 6);
+        let total = stats.win_rate(0) + stats.win_rate(1) + stats.draw_rate();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(stats.margin_of_error(0) <= 1.96 * 0.5 / 6f64.sqrt() + 1e-9);
+
+        // The same seed deals the same games, so greedy players always get the same results
+        let greedy = [PlayerSpec::Greedy, PlayerSpec::Greedy];
+        let stats = estimate_win_rate(&configuration, &greedy, 8, 5).unwrap();
+        assert_eq!(stats, estimate_win_rate(&configuration, &greedy, 8, 5).unwrap());
+
+        let empty = estimate_win_rate(&configuration, &greedy, 0, 5).unwrap();
+        assert_eq!((empty.win_rate(0), empty.draw_rate(), empty.margin_of_error(0)), (0.0, 0.0, 0.0));
+
+        let one = estimate_win_rate(&configuration, &[PlayerSpec::Greedy], 1, 0);
+        assert!(matches!(one, Err(GameError::PlayerCount { expected: 2, found: 1 })));
+        let human = estimate_win_rate(&configuration, &[PlayerSpec::Greedy, PlayerSpec::Human { name: None }], 1, 0);
+        assert!(matches!(human, Err(GameError::HumanPlayer(PlayerSpec::Human { .. }))));
+    }
+}
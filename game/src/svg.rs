@@ -0,0 +1,130 @@
+//! SVG export
+//!
+//! This module draws a layout as an SVG image, so that positions and games can be shared and documented without the visualizer.
+//! The tiles are placed by the scene graph (see `crate::scene_graph`), so the image matches what the visualizer shows, unlike the
+//! rows of tiles drawn by `Layout::to_svg`. Tiles are drawn with pips up to 9, and with numbers above that.
+
+use rules::Layout;
+use rules::svg::pips;
+
+use crate::scene_graph::{RenderListNode, SceneGraph};
+
+// Space around the tiles, in model space
+const MARGIN: f32 = 160.0;
+// Number of units of model space in a pixel of the image
+const UNITS_PER_PIXEL: f32 = 16.0;
+// Distance between the centers of adjacent pips, in model space
+const PIP_SPACING: f32 = 160.0;
+// Radius of a pip, in model space
+const PIP_RADIUS: f32 = 48.0;
+
+/// Draws a layout as an SVG image
+///
+/// # Arguments
+/// * `layout` - The layout to draw. An empty layout is drawn as an empty image.
+///
+/// # Returns
+/// The SVG document
+///
+/// # Examples
+/// ```rust
+/// # use game::layout_parser::parse_layout;
+/// # use game::svg::layout_svg;
+/// # use rules::Configuration;
+///
+/// let layout = parse_layout("3|3=(3|4-4|5,3|6)", &Configuration::default()).unwrap();
+/// let svg = layout_svg(&layout);
+/// assert!(svg.starts_with("<svg"));
+/// assert_eq!(svg.matches("<g ").count(), 4);
+/// ```
+pub fn layout_svg(layout: &Layout) -> String {
+    let Some(tree) = layout.to_tree() else {
+        return format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}"></svg>"#, pixels(2.0 * MARGIN)) + "\n";
+    };
+
+    let scene_graph = SceneGraph::new(&tree);
+    let bounds = scene_graph.bounds();
+    let (x, y) = (bounds.x - MARGIN, bounds.y - MARGIN);
+    let (width, height) = (bounds.width + 2.0 * MARGIN, bounds.height + 2.0 * MARGIN);
+
+    let mut lines = vec![format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{x} {y} {width} {height}">"#,
+        pixels(width),
+        pixels(height)
+    )];
+    for node in scene_graph.render_list() {
+        lines.extend(tile_lines(node));
+    }
+    lines.push("</svg>".to_string());
+    lines.join("\n") + "\n"
+}
+
+// Converts a length in model space to a whole number of pixels
+fn pixels(length: f32) -> u32 {
+    (length / UNITS_PER_PIXEL).ceil() as u32
+}
+
+// Returns the lines of a group containing the outline of a tile, the line dividing its halves, and its values. The first value is
+// in the top half of the tile in its default orientation.
+fn tile_lines(node: &RenderListNode) -> Vec<String> {
+    let (width, height) = (node.size.width, node.size.height);
+    let (a, b) = node.tile.as_tuple();
+    let mut lines = vec![
+        format!(
+            r#"<g transform="translate({} {}) rotate({})">"#,
+            node.position.x,
+            node.position.y,
+            node.rotation.to_degrees()
+        ),
+        format!(
+            r#"<rect x="{}" y="{}" width="{width}" height="{height}" rx="{}" fill="ivory" stroke="black" stroke-width="16"/>"#,
+            -width / 2.0,
+            -height / 2.0,
+            width / 10.0
+        ),
+        format!(r#"<line x1="{}" y1="0" x2="{}" y2="0" stroke="black" stroke-width="16"/>"#, -width * 0.4, width * 0.4),
+    ];
+    lines.extend(value_lines(a, -height / 4.0));
+    lines.extend(value_lines(b, height / 4.0));
+    lines.push("</g>".to_string());
+    lines
+}
+
+// Returns the lines drawing the value of a half of a tile, centered at the given height
+fn value_lines(value: u8, center: f32) -> Vec<String> {
+    match pips(value) {
+        Some(pips) => pips
+            .iter()
+            .map(|&(column, row)| {
+                format!(
+                    r#"<circle cx="{}" cy="{}" r="{PIP_RADIUS}"/>"#,
+                    f32::from(column) * PIP_SPACING,
+                    center + f32::from(row) * PIP_SPACING
+                )
+            })
+            .collect(),
+        None => vec![format!(
+            r#"<text x="0" y="{center}" font-size="320" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">{value}</text>"#
+        )],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Configuration, Tile};
+
+    #[test]
+    fn test_layout_svg() {
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        assert!(!layout_svg(&layout).contains("<g "));
+
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+        let svg = layout_svg(&layout);
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<g ").count(), 2);
+        assert_eq!(svg.matches("<circle ").count(), 6 + 6 + 3 + 6);
+    }
+}
@@ -0,0 +1,133 @@
+//! Text tables
+//!
+//! This module formats rows of text into aligned columns for the terminal, e.g. for the score table of a match. The first column
+//! is aligned to the left and the others to the right, which suits a label followed by numbers.
+
+use std::fmt;
+
+// A row of a table
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    Cells(Vec<String>),
+    Separator,
+}
+
+/// A table of text
+///
+/// # Examples
+/// ```rust
+/// # use game::table::Table;
+///
+/// let mut table = Table::new(["Round", "Alice", "Bob"]);
+/// table.add_row(["1", "12", "0"]);
+/// table.add_separator();
+/// table.add_row(["Total", "12", "0"]);
+///
+/// let expected = "\
+/// Round  Alice  Bob
+/// -----  -----  ---
+/// 1         12    0
+/// -----  -----  ---
+/// Total     12    0
+/// ";
+/// assert_eq!(table.to_string(), expected);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// The column headings
+    header: Vec<String>,
+    /// The rows, below the header
+    rows: Vec<Row>,
+}
+
+impl Table {
+    /// Creates a table with the given column headings and no rows
+    pub fn new<I, S>(header: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        Self {
+            header: header.into_iter().map(|heading| heading.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row
+    ///
+    /// A row with fewer cells than there are columns is padded with empty cells, and extra cells are ignored.
+    pub fn add_row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        let mut cells: Vec<String> = cells.into_iter().map(|cell| cell.to_string()).take(self.header.len()).collect();
+        cells.resize(self.header.len(), String::new());
+        self.rows.push(Row::Cells(cells));
+    }
+
+    /// Adds a line that separates the rows above it from the rows below it
+    pub fn add_separator(&mut self) {
+        self.rows.push(Row::Separator);
+    }
+
+    // Returns the width of each column
+    fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.header.iter().map(|heading| heading.chars().count()).collect();
+        for row in &self.rows {
+            if let Row::Cells(cells) = row {
+                for (width, cell) in widths.iter_mut().zip(cells) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+        }
+        widths
+    }
+}
+
+// Writes a line of cells, aligning the first to the left and the others to the right
+fn write_line(f: &mut fmt::Formatter<'_>, cells: &[String], widths: &[usize]) -> fmt::Result {
+    let line: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .enumerate()
+        .map(|(column, (cell, &width))| if column == 0 { format!("{cell:<width$}") } else { format!("{cell:>width$}") })
+        .collect();
+    writeln!(f, "{}", line.join("  ").trim_end())
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self.widths();
+        let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+        write_line(f, &self.header, &widths)?;
+        write_line(f, &separator, &widths)?;
+        for row in &self.rows {
+            match row {
+                Row::Cells(cells) => write_line(f, cells, &widths)?,
+                Row::Separator => write_line(f, &separator, &widths)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_widths() {
+        let mut table = Table::new(["", "A"]);
+        table.add_row(["Round 10", "1234"]);
+        table.add_row(["2"]);
+        assert_eq!(table.to_string(), "             A\n--------  ----\nRound 10  1234\n2\n");
+    }
+
+    #[test]
+    fn test_table_extra_cells_are_ignored() {
+        let mut table = Table::new(["X"]);
+        table.add_row(["1", "2"]);
+        assert_eq!(table.to_string(), "X\n-\n1\n");
+    }
+}
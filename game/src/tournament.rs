@@ -0,0 +1,550 @@
+//! Tournaments
+//!
+//! A tournament measures the relative strength of several computer players, e.g. different difficulties of the AI, by playing a
+//! round robin of two-player games between them. Every pair of entrants plays the same deals, and each deal is played twice with
+//! the seats swapped, so neither the luck of the deal nor the advantage of playing first favors either player.
+//!
+//! The results are summarized by Elo ratings, which are fitted to all of the games at once, so they do not depend on the order in
+//! which the games were played. A draw counts as half a win. Each rating has a 95% confidence interval, and the entrants are
+//! ranked on a leaderboard (see `TournamentResults::leaderboard`).
+//!
+//! The pairings can also be played one at a time (see `Tournament::play_pairing`), which gives the result of each game, e.g. to
+//! write it to a results file (see `ResultsFile`). A long tournament can be checkpointed to a directory after each pairing (see
+//! `TournamentCheckpoint`), so a tournament that is interrupted can be continued instead of being restarted.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use player::PlayerSpec;
+use rules::Configuration;
+
+use crate::batch::BatchRunner;
+use crate::error::GameError;
+use crate::simulation::{GameRecord, checkpoint_path, write_checkpoint};
+use crate::table::Table;
+
+/// The average rating of the entrants in a tournament
+pub const BASE_RATING: f64 = 1500.0;
+
+// The number of iterations used to fit the ratings, which is plenty for them to converge to a fraction of a point
+const FIT_ITERATIONS: usize = 1000;
+
+// A function that reports the progress of a tournament
+type ProgressFn<'a> = Box<dyn Fn(&Pairing, usize) + Send + Sync + 'a>;
+
+/// The results of the games between two entrants
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pairing {
+    /// The indexes of the two entrants
+    pub entrants: [usize; 2],
+    /// The number of games won by each of the two entrants
+    pub wins: [u32; 2],
+    /// The number of games that were drawn
+    pub draws: u32,
+}
+
+impl Pairing {
+    /// Returns the number of games played
+    pub fn num_games(&self) -> u32 {
+        self.wins[0] + self.wins[1] + self.draws
+    }
+
+    /// Returns the points scored by one of the two entrants, counting a draw as half a win
+    ///
+    /// # Arguments
+    /// * `side` - 0 for the first entrant, or 1 for the second
+    pub fn points(&self, side: usize) -> f64 {
+        f64::from(self.wins[side]) + f64::from(self.draws) / 2.0
+    }
+}
+
+/// An entrant's place on the leaderboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    /// The index of the entrant
+    pub entrant: usize,
+    /// The entrant
+    pub player: PlayerSpec,
+    /// The Elo rating
+    pub rating: f64,
+    /// The margin of error of the rating at 95% confidence
+    pub margin: f64,
+    /// The number of games played
+    pub games: u32,
+    /// The points scored, counting a draw as half a win
+    pub points: f64,
+}
+
+/// The results of a tournament
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentResults {
+    /// The results of each pair of entrants
+    pub pairings: Vec<Pairing>,
+    /// The standings of the entrants, from the highest rating to the lowest
+    pub standings: Vec<Standing>,
+}
+
+impl TournamentResults {
+    /// Returns the standings as a table
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use game::tournament::Tournament;
+    /// # use player::{PlayerSpec, SkillLevel};
+    /// # use rules::Configuration;
+    ///
+    /// let entrants = vec![PlayerSpec::Greedy, PlayerSpec::Level(SkillLevel::Beginner)];
+    /// let results = Tournament::new(&Configuration::default(), entrants).unwrap().run(42, 2);
+    /// println!("{}", results.leaderboard());
+    /// ```
+    pub fn leaderboard(&self) -> Table {
+        let mut table = Table::new(["Player", "Elo", "95% CI", "Games", "Score"]);
+        for (rank, standing) in self.standings.iter().enumerate() {
+            let score = 100.0 * standing.points / f64::from(standing.games.max(1));
+            table.add_row([
+                format!("{}. {}", rank + 1, standing.player),
+                format!("{:.0}", standing.rating),
+                format!("±{:.0}", standing.margin),
+                standing.games.to_string(),
+                format!("{score:.1}%"),
+            ]);
+        }
+        table
+    }
+}
+
+/// Plays a round robin between computer players and rates them
+///
+/// # Examples
+/// ```rust
+/// # use game::tournament::{BASE_RATING, Tournament};
+/// # use player::{PlayerSpec, SkillLevel};
+/// # use rules::Configuration;
+///
+/// let entrants = vec![PlayerSpec::Greedy, PlayerSpec::Level(SkillLevel::Beginner), PlayerSpec::Mcts { iterations: 10 }];
+/// let tournament = Tournament::new(&Configuration::default(), entrants).unwrap().with_threads(2);
+/// let results = tournament.run(42, 2);
+///
+/// // Three pairs of entrants each play two deals from both seats
+/// assert_eq!(results.pairings.len(), 3);
+/// assert!(results.standings.iter().all(|standing| standing.games == 8));
+/// let average = results.standings.iter().map(|standing| standing.rating).sum::<f64>() / 3.0;
+/// assert!((average - BASE_RATING).abs() < 0.001);
+/// ```
+pub struct Tournament<'a> {
+    /// The configuration of every game
+    configuration: Configuration,
+    /// The players in the tournament
+    entrants: Vec<PlayerSpec>,
+    /// The number of threads, or `None` to use all available cores
+    threads: Option<usize>,
+    /// Called after each pairing with its results and the number of pairings finished so far
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> Tournament<'a> {
+    /// Creates a tournament between computer players
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings. The games are between two players.
+    /// * `entrants` - The players in the tournament. There must be at least two, and none can be human.
+    ///
+    /// # Returns
+    /// The tournament, or an error if the configuration or the entrants are not valid
+    pub fn new(configuration: &Configuration, entrants: Vec<PlayerSpec>) -> Result<Self, GameError> {
+        if configuration.num_players() != 2 {
+            return Err(GameError::TournamentPlayers(configuration.num_players()));
+        }
+        if entrants.len() < 2 {
+            return Err(GameError::TooFewEntrants(entrants.len()));
+        }
+        if let Some(human) = entrants.iter().find(|spec| spec.is_human()) {
+            return Err(GameError::HumanPlayer(human.clone()));
+        }
+        Ok(Self { configuration: configuration.clone(), entrants, threads: None, progress: None })
+    }
+
+    /// Sets the number of threads that play the games. By default, all available cores are used.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Sets a function that is called after each pairing with its results and the number of pairings finished so far
+    pub fn on_progress(mut self, progress: impl Fn(&Pairing, usize) + Send + Sync + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Returns the players in the tournament
+    pub fn entrants(&self) -> &[PlayerSpec] {
+        &self.entrants
+    }
+
+    /// Returns the number of pairs of entrants, each of which plays a match
+    pub fn num_pairings(&self) -> usize {
+        self.entrants.len() * (self.entrants.len() - 1) / 2
+    }
+
+    /// Returns the indexes of the pairs of entrants, in the order in which their matches are played
+    pub fn pairs(&self) -> Vec<[usize; 2]> {
+        let num_entrants = self.entrants.len();
+        (0..num_entrants).flat_map(|first| (first + 1..num_entrants).map(move |second| [first, second])).collect()
+    }
+
+    /// Plays the tournament
+    ///
+    /// Every pair of entrants plays the deals with the seeds `seed` to `seed + num_deals - 1`, each one twice with the seats
+    /// swapped, so each pair plays `2 * num_deals` games.
+    ///
+    /// # Arguments
+    /// * `seed` - The seed of the first deal
+    /// * `num_deals` - The number of deals played by each pair of entrants
+    ///
+    /// # Returns
+    /// The results of each pairing and the standings
+    pub fn run(&self, seed: u64, num_deals: u32) -> TournamentResults {
+        let mut pairings = Vec::with_capacity(self.num_pairings());
+        for entrants in self.pairs() {
+            let (pairing, _) = self.play_pairing(entrants, seed, num_deals);
+            if let Some(progress) = &self.progress {
+                progress(&pairing, pairings.len() + 1);
+            }
+            pairings.push(pairing);
+        }
+        self.results(pairings)
+    }
+
+    /// Plays the deals between two entrants from both seats
+    ///
+    /// # Arguments
+    /// * `entrants` - The indexes of the two entrants (see `pairs`)
+    /// * `seed` - The seed of the first deal
+    /// * `num_deals` - The number of deals, each of which is played twice
+    ///
+    /// # Returns
+    /// The results of the pairing, and the result of each game, with the games in which the first entrant is player 0 first
+    pub fn play_pairing(&self, entrants: [usize; 2], seed: u64, num_deals: u32) -> (Pairing, Vec<GameRecord>) {
+        let [first, second] = entrants;
+        let mut pairing = Pairing { entrants, wins: [0, 0], draws: 0 };
+        let mut records = Vec::with_capacity(2 * num_deals as usize);
+        for (seats, sides) in [([first, second], [0, 1]), ([second, first], [1, 0])] {
+            let players = seats.iter().map(|&entrant| self.entrants[entrant].clone()).collect();
+            let mut runner = BatchRunner::new(&self.configuration, players).expect("The entrants were checked");
+            if let Some(threads) = self.threads {
+                runner = runner.with_threads(threads);
+            }
+            let batch = runner.run(seed, num_deals);
+            pairing.wins[sides[0]] += batch.stats.wins[0];
+            pairing.wins[sides[1]] += batch.stats.wins[1];
+            pairing.draws += batch.stats.draws;
+            records.extend(batch.records);
+        }
+        (pairing, records)
+    }
+
+    /// Rates the entrants from the results of the pairings
+    ///
+    /// # Arguments
+    /// * `pairings` - The results of the pairings, e.g. from `play_pairing`
+    ///
+    /// # Returns
+    /// The results of each pairing and the standings
+    pub fn results(&self, pairings: Vec<Pairing>) -> TournamentResults {
+        let (ratings, margins) = fit_ratings(self.entrants.len(), &pairings);
+        let mut standings: Vec<Standing> = self
+            .entrants
+            .iter()
+            .enumerate()
+            .map(|(entrant, player)| {
+                let mut standing = Standing {
+                    entrant,
+                    player: player.clone(),
+                    rating: ratings[entrant],
+                    margin: margins[entrant],
+                    games: 0,
+                    points: 0.0,
+                };
+                for pairing in &pairings {
+                    if let Some(side) = pairing.entrants.iter().position(|&e| e == entrant) {
+                        standing.games += pairing.num_games();
+                        standing.points += pairing.points(side);
+                    }
+                }
+                standing
+            })
+            .collect();
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+
+        TournamentResults { pairings, standings }
+    }
+}
+
+/// The progress of a tournament, which is saved after each pairing so that a tournament that is interrupted can be resumed
+///
+/// # Examples
+/// ```rust
+/// # use game::tournament::TournamentCheckpoint;
+/// # use player::{PlayerSpec, SkillLevel};
+/// # use rules::Configuration;
+///
+/// let entrants = vec![PlayerSpec::Greedy, PlayerSpec::Level(SkillLevel::Beginner)];
+/// let mut checkpoint = TournamentCheckpoint::new(&Configuration::default(), &entrants, 42, 2);
+/// let tournament = checkpoint.tournament().unwrap();
+/// while let Some(entrants) = checkpoint.next_pair(&tournament) {
+///     let (pairing, _) = tournament.play_pairing(entrants, checkpoint.seed, checkpoint.num_deals);
+///     checkpoint.pairings.push(pairing);
+/// }
+/// assert_eq!(tournament.results(checkpoint.pairings).standings.len(), 2);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentCheckpoint {
+    /// The configuration of every game
+    pub configuration: Configuration,
+    /// The players in the tournament, as they are parsed (see `PlayerSpec`)
+    pub entrants: Vec<String>,
+    /// The seed of the first deal
+    pub seed: u64,
+    /// The number of deals played by each pair of entrants
+    pub num_deals: u32,
+    /// The results of the pairings played so far, in the order in which they were played (see `Tournament::pairs`)
+    pub pairings: Vec<Pairing>,
+}
+
+impl TournamentCheckpoint {
+    /// Creates the checkpoint of a tournament in which no pairings have been played
+    ///
+    /// # Arguments
+    /// * `configuration` - Game rules and settings
+    /// * `entrants` - The players in the tournament
+    /// * `seed` - The seed of the first deal
+    /// * `num_deals` - The number of deals played by each pair of entrants
+    pub fn new(configuration: &Configuration, entrants: &[PlayerSpec], seed: u64, num_deals: u32) -> Self {
+        Self {
+            configuration: configuration.clone(),
+            entrants: entrants.iter().map(ToString::to_string).collect(),
+            seed,
+            num_deals,
+            pairings: Vec::new(),
+        }
+    }
+
+    /// Returns the tournament that the checkpoint is the progress of
+    ///
+    /// # Returns
+    /// The tournament, or an error if the entrants are not valid
+    pub fn tournament<'a>(&self) -> Result<Tournament<'a>, GameError> {
+        let entrants = self.entrants.iter().map(|entrant| entrant.parse()).collect::<Result<Vec<PlayerSpec>, String>>();
+        Tournament::new(&self.configuration, entrants.map_err(GameError::PlayerSpec)?)
+    }
+
+    /// Returns the next pair of entrants to play, or `None` if every pairing has been played
+    pub fn next_pair(&self, tournament: &Tournament) -> Option<[usize; 2]> {
+        tournament.pairs().get(self.pairings.len()).copied()
+    }
+
+    /// Saves the checkpoint to a directory
+    ///
+    /// As with a simulation (see `Simulation::save`), an interruption while saving leaves the previous checkpoint intact.
+    ///
+    /// # Arguments
+    /// * `dir` - The checkpoint directory. It is created if it does not exist.
+    ///
+    /// # Returns
+    /// An error if the checkpoint cannot be written
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<(), GameError> {
+        let json = serde_json::to_string_pretty(self)?;
+        write_checkpoint(dir.as_ref(), &json)
+    }
+
+    /// Loads the checkpoint in a directory
+    ///
+    /// # Arguments
+    /// * `dir` - The checkpoint directory
+    ///
+    /// # Returns
+    /// The checkpoint, or an error if there is no valid checkpoint of a tournament in the directory
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, GameError> {
+        let path = checkpoint_path(dir.as_ref());
+        let json = fs::read_to_string(&path).map_err(|source| GameError::Read { path: path.clone(), source })?;
+        let checkpoint: Self = serde_json::from_str(&json).map_err(|source| GameError::Json { path: path.clone(), source })?;
+        let tournament = checkpoint.tournament().map_err(|e| GameError::Checkpoint { path: path.clone(), source: Box::new(e) })?;
+        let pairs = tournament.pairs();
+        let played = checkpoint.pairings.len() <= pairs.len()
+            && checkpoint.pairings.iter().zip(&pairs).all(|(pairing, &entrants)| {
+                pairing.entrants == entrants && pairing.num_games() == 2 * checkpoint.num_deals
+            });
+        if !played {
+            return Err(GameError::PairingsMismatch(path));
+        }
+        Ok(checkpoint)
+    }
+}
+
+/// Fits Elo ratings to the results of games between entrants
+///
+/// The ratings are the most likely ones under the Elo model, in which a player rated `d` points above another is expected to
+/// score `1 / (1 + 10^(-d / 400))` against them. Every pair of entrants is also given one extra drawn game, which keeps the
+/// rating of an entrant that won or lost every game finite and pulls the ratings of entrants that played only a few games toward
+/// the average.
+///
+/// # Arguments
+/// * `num_entrants` - The number of entrants
+/// * `pairings` - The results of the games between pairs of entrants
+///
+/// # Returns
+/// The rating of each entrant, which average `BASE_RATING`, and the margin of error of each rating at 95% confidence
+pub fn fit_ratings(num_entrants: usize, pairings: &[Pairing]) -> (Vec<f64>, Vec<f64>) {
+    // The games and the points between each pair, including the extra draw
+    let mut games = vec![vec![0.0; num_entrants]; num_entrants];
+    let mut points = vec![0.0; num_entrants];
+    for a in 0..num_entrants {
+        for b in 0..num_entrants {
+            if a != b {
+                games[a][b] = 1.0;
+                points[a] += 0.5;
+            }
+        }
+    }
+    for pairing in pairings {
+        let [a, b] = pairing.entrants;
+        games[a][b] += f64::from(pairing.num_games());
+        games[b][a] += f64::from(pairing.num_games());
+        points[a] += pairing.points(0);
+        points[b] += pairing.points(1);
+    }
+
+    // Each entrant's strength is 10^(rating / 400). The strengths are found by minorization-maximization, and scaled after each
+    // iteration so that their geometric mean is 1.
+    let mut strengths = vec![1.0; num_entrants];
+    for _ in 0..FIT_ITERATIONS {
+        for a in 0..num_entrants {
+            let expected: f64 = (0..num_entrants).map(|b| games[a][b] / (strengths[a] + strengths[b])).sum();
+            strengths[a] = points[a] / expected;
+        }
+        let mean = (strengths.iter().map(|s: &f64| s.ln()).sum::<f64>() / num_entrants as f64).exp();
+        strengths.iter_mut().for_each(|s| *s /= mean);
+    }
+
+    // The margin comes from the Fisher information of an entrant's rating, with the ratings of the others held fixed
+    let scale = 400.0 / std::f64::consts::LN_10;
+    let ratings = strengths.iter().map(|s| BASE_RATING + scale * s.ln()).collect();
+    let margins = (0..num_entrants)
+        .map(|a| {
+            let information: f64 = (0..num_entrants)
+                .map(|b| {
+                    let p = strengths[a] / (strengths[a] + strengths[b]);
+                    games[a][b] * p * (1.0 - p)
+                })
+                .sum();
+            if information > 0.0 { 1.96 * scale / information.sqrt() } else { 0.0 }
+        })
+        .collect();
+    (ratings, margins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::SkillLevel;
+
+    #[test]
+    fn test_fit_ratings() {
+        // 3 wins in 4 games, plus the extra draw, is a score of 70%
+        let pairings = [Pairing { entrants: [0, 1], wins: [3, 1], draws: 0 }];
+        let (ratings, margins) = fit_ratings(2, &pairings);
+        let difference = 400.0 * (0.7_f64 / 0.3).log10();
+        assert!((ratings[0] - ratings[1] - difference).abs() < 0.01, "{ratings:?}");
+        assert!((ratings[0] + ratings[1] - 2.0 * BASE_RATING).abs() < 0.01);
+        assert!((margins[0] - margins[1]).abs() < 0.01);
+
+        // More games give a smaller margin
+        let pairings = [Pairing { entrants: [0, 1], wins: [30, 10], draws: 0 }];
+        let (_, more_margins) = fit_ratings(2, &pairings);
+        assert!(more_margins[0] < margins[0]);
+    }
+
+    #[test]
+    fn test_fit_ratings_without_games() {
+        let (ratings, margins) = fit_ratings(3, &[]);
+        assert!(ratings.iter().all(|rating| (rating - BASE_RATING).abs() < 0.01));
+        assert!(margins.iter().all(|&margin| margin > 0.0));
+    }
+
+    #[test]
+    fn test_tournament_run() {
+        let entrants = vec![PlayerSpec::Greedy, PlayerSpec::Level(SkillLevel::Beginner)];
+        let tournament = Tournament::new(&Configuration::default(), entrants).unwrap().with_threads(2);
+        let results = tournament.run(7, 3);
+
+        assert_eq!(results.pairings.len(), 1);
+        assert_eq!(results.pairings[0].num_games(), 6);
+        assert_eq!(results.standings.len(), 2);
+        assert!(results.standings[0].rating >= results.standings[1].rating);
+        assert_eq!(results.standings.iter().map(|standing| standing.points).sum::<f64>(), 6.0);
+
+        // The tournament is repeatable
+        assert_eq!(tournament.run(7, 3), results);
+        assert_eq!(results.leaderboard().to_string().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_tournament_rejects_invalid_entrants() {
+        let configuration = Configuration::default();
+        assert!(matches!(Tournament::new(&configuration, vec![PlayerSpec::Greedy]), Err(GameError::TooFewEntrants(1))));
+        let human = Tournament::new(&configuration, vec![PlayerSpec::Greedy, PlayerSpec::Human { name: None }]);
+        assert!(matches!(human, Err(GameError::HumanPlayer(_))));
+        let three_players = Configuration::new(3, Configuration::DEFAULT_VARIATION, Configuration::DEFAULT_SET_ID, 7);
+        let three = Tournament::new(&three_players, vec![PlayerSpec::Greedy, PlayerSpec::Greedy]);
+        assert!(matches!(three, Err(GameError::TournamentPlayers(3))));
+    }
+
+    #[test]
+    fn test_tournament_play_pairing() {
+        let entrants = vec![PlayerSpec::Greedy, PlayerSpec::Level(SkillLevel::Beginner), PlayerSpec::Greedy];
+        let tournament = Tournament::new(&Configuration::default(), entrants).unwrap().with_threads(2);
+        assert_eq!(tournament.pairs(), vec![[0, 1], [0, 2], [1, 2]]);
+
+        // Each deal is played from both seats, and the results match the games
+        let (pairing, records) = tournament.play_pairing([0, 1], 7, 2);
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].players, ["greedy", "beginner"]);
+        assert_eq!(records[2].players, ["beginner", "greedy"]);
+        assert_eq!(records.iter().map(|record| record.seed).collect::<Vec<_>>(), [7, 8, 7, 8]);
+        assert_eq!(records.iter().filter(|record| record.winner.is_none()).count() as u32, pairing.draws);
+        // The first entrant is player 0 in the first two games, and player 1 in the others
+        let first_wins = records.iter().enumerate().filter(|&(i, record)| record.winner == Some(u8::from(i >= 2))).count();
+        assert_eq!(first_wins as u32, pairing.wins[0]);
+    }
+
+    #[test]
+    fn test_tournament_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("dominoes-tournament-test-{}", std::process::id()));
+        let entrants = vec![PlayerSpec::Greedy, PlayerSpec::Level(SkillLevel::Beginner), PlayerSpec::Greedy];
+        let configuration = Configuration::default();
+        let tournament = Tournament::new(&configuration, entrants.clone()).unwrap().with_threads(2);
+        let expected = tournament.run(7, 1);
+
+        // The tournament is interrupted after the first pairing, and resumed from the checkpoint
+        let mut checkpoint = TournamentCheckpoint::new(&configuration, &entrants, 7, 1);
+        let pair = checkpoint.next_pair(&tournament).unwrap();
+        checkpoint.pairings.push(tournament.play_pairing(pair, 7, 1).0);
+        checkpoint.save(&dir).unwrap();
+
+        let mut resumed = TournamentCheckpoint::load(&dir).unwrap();
+        assert_eq!(resumed.pairings.len(), 1);
+        let tournament = resumed.tournament().unwrap().with_threads(2);
+        while let Some(pair) = resumed.next_pair(&tournament) {
+            resumed.pairings.push(tournament.play_pairing(pair, resumed.seed, resumed.num_deals).0);
+        }
+        assert_eq!(tournament.results(resumed.pairings.clone()), expected);
+
+        // A checkpoint whose pairings do not match its tournament is rejected
+        resumed.pairings.swap(0, 1);
+        resumed.save(&dir).unwrap();
+        assert!(matches!(TournamentCheckpoint::load(&dir), Err(GameError::PairingsMismatch(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
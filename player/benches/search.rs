@@ -0,0 +1,68 @@
+//! Benchmarks of the AI's search
+//!
+//! Run with `cargo bench -p player`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use hidden_game_player::mcts::ResponseGenerator;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use dominoes_state::{DominoesState, Hand};
+use player::{DominoesPlayer, DominoesResponseGenerator, Player};
+use rules::{Configuration, Tile, TileMask};
+
+// Number of iterations of the fixed-budget search
+const SEARCH_ITERATIONS: u32 = 200;
+
+// Returns a state in the middle of a game and the hand of the player whose turn it is. The hand is drawn first, and then tiles
+// are drawn from the boneyard and played on the first end that they match until the given number have been played.
+fn mid_game(configuration: &Configuration, num_played: usize) -> (DominoesState, Hand) {
+    let mut state = DominoesState::with_rng(configuration, &mut ChaCha8Rng::seed_from_u64(1));
+    let mut hand = Hand::new();
+    for _ in 0..configuration.starting_hand_size() {
+        hand.add_tile(state.draw_tile().expect("The boneyard should have enough tiles"));
+    }
+    let mut played = 0;
+    while played < num_played {
+        let tile = state.draw_tile().expect("The boneyard should have enough tiles");
+        if let Some(end) = playable_end(&state, tile) {
+            state.play_tile_unchecked(tile, end);
+            played += 1;
+        }
+    }
+    (state, hand)
+}
+
+// Returns the end that a tile can be played on, or None if it cannot be played
+fn playable_end(state: &DominoesState, tile: Tile) -> Option<Option<u8>> {
+    if state.layout.is_empty() {
+        return tile.is_double().then_some(None);
+    }
+    let (a, b) = tile.as_tuple();
+    [a, b].into_iter().find(|&end| state.can_play_tile(&tile, Some(end))).map(Some)
+}
+
+fn bench_generate(c: &mut Criterion) {
+    let configuration = Configuration::default();
+    let (state, hand) = mid_game(&configuration, 6);
+    let hidden = TileMask::all(configuration.set_id());
+    let probabilities = vec![0.25; configuration.set_size()];
+    let generator = DominoesResponseGenerator::with_knowledge(state.whose_turn, &hand, &hidden, &probabilities);
+
+    c.bench_function("DominoesResponseGenerator::generate", |b| b.iter(|| generator.generate(black_box(&state))));
+}
+
+fn bench_search(c: &mut Criterion) {
+    let configuration = Configuration::default();
+    let (state, hand) = mid_game(&configuration, 6);
+    let mut player = DominoesPlayer::new(state.whose_turn, &configuration);
+    player.set_iterations(SEARCH_ITERATIONS);
+    player.set_up_from(hand, &state);
+
+    c.bench_function("DominoesPlayer::my_turn", |b| b.iter(|| player.my_turn(black_box(&state))));
+}
+
+criterion_group!(benches, bench_generate, bench_search);
+criterion_main!(benches);
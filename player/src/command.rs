@@ -0,0 +1,148 @@
+//! Turn commands
+//!
+//! This module reads the commands that a person types on their turn (see `HumanPlayer`), e.g. `play 3|4 on 6`. The words can be in
+//! any case, and a command that cannot be read is explained so that the person can try again.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rules::Tile;
+
+/// A command typed by a person on their turn
+///
+/// # Examples
+/// ```rust
+/// # use player::Command;
+/// # use rules::Tile;
+///
+/// assert_eq!("play 3|4 on 4".parse::<Command>(), Ok(Command::Play { tile: Tile::from((3, 4)), end: Some(4) }));
+/// assert_eq!("Hint".parse::<Command>(), Ok(Command::Hint));
+/// assert!("play 3|4 at 4".parse::<Command>().unwrap_err().contains("'on'"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Play a tile on an open end, or as the first tile if `end` is `None`
+    Play { tile: Tile, end: Option<u8> },
+    /// Draw a tile from the boneyard
+    Draw,
+    /// Pass the turn
+    Pass,
+    /// Show the tiles in the hand
+    Hand,
+    /// Show the layout and its open ends
+    Board,
+    /// Show the computer's top suggestions
+    Hint,
+    /// Take back the last turn
+    Undo,
+    /// Make the turns taken back by the last undo again
+    Redo,
+    /// Leave the game
+    Quit,
+    /// Practice a position with the given layout, written as text for the front end, e.g. `6|6-6|3`
+    Setup { layout: String },
+}
+
+impl Command {
+    /// A summary of the commands, for showing to a person who typed one that cannot be read
+    pub const HELP: &'static str =
+        "Commands: play <tile> [on <end>] (e.g. play 3|4 on 6), draw, pass, hand, board, hint, undo, redo, setup <layout>, quit";
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The layout is kept as it was typed, because it is read by the front end
+        if let Some((word, layout)) = s.trim().split_once(char::is_whitespace)
+            && word.eq_ignore_ascii_case("setup")
+        {
+            return Ok(Command::Setup { layout: layout.trim().to_string() });
+        }
+
+        let words: Vec<String> = s.split_whitespace().map(str::to_lowercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let command = match words.as_slice() {
+            [] => return Err("Enter a command, e.g. play 3|4 on 6".to_string()),
+            ["play"] => return Err("Expected a tile after 'play', e.g. play 3|4 on 6".to_string()),
+            ["play", tile] => Command::Play { tile: tile.parse::<Tile>().map_err(|error| error.to_string())?, end: None },
+            ["play", tile, "on"] => {
+                tile.parse::<Tile>().map_err(|error| error.to_string())?;
+                return Err(format!("Expected an end after 'on', e.g. play {tile} on 6"));
+            }
+            ["play", tile, "on", end] => {
+                let tile = tile.parse::<Tile>().map_err(|error| error.to_string())?;
+                let end = end.parse().map_err(|_| format!("Expected the value of an end after 'on', found '{end}'"))?;
+                Command::Play { tile, end: Some(end) }
+            }
+            ["play", _, word, ..] if *word != "on" => return Err(format!("Expected 'on' after the tile, found '{word}'")),
+            ["play", ..] => return Err("Too many words. Expected play <tile> on <end>, e.g. play 3|4 on 6".to_string()),
+            [word] => match *word {
+                "draw" => Command::Draw,
+                "pass" => Command::Pass,
+                "hand" => Command::Hand,
+                "board" => Command::Board,
+                "hint" => Command::Hint,
+                "undo" => Command::Undo,
+                "redo" => Command::Redo,
+                "quit" => Command::Quit,
+                "setup" => return Err("Expected a layout after 'setup', e.g. setup 6|6-6|3".to_string()),
+                _ => return Err(format!("Unknown command '{word}'")),
+            },
+            [word, ..] => match *word {
+                "draw" | "pass" | "hand" | "board" | "hint" | "undo" | "redo" | "quit" => {
+                    return Err(format!("'{word}' is not followed by anything"));
+                }
+                _ => return Err(format!("Unknown command '{word}'")),
+            },
+        };
+        Ok(command)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Play { tile, end: None } => write!(f, "play {tile}"),
+            Command::Play { tile, end: Some(end) } => write!(f, "play {tile} on {end}"),
+            Command::Draw => write!(f, "draw"),
+            Command::Pass => write!(f, "pass"),
+            Command::Hand => write!(f, "hand"),
+            Command::Board => write!(f, "board"),
+            Command::Hint => write!(f, "hint"),
+            Command::Undo => write!(f, "undo"),
+            Command::Redo => write!(f, "redo"),
+            Command::Quit => write!(f, "quit"),
+            Command::Setup { layout } => write!(f, "setup {layout}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_parse() {
+        assert_eq!("play 6|6".parse(), Ok(Command::Play { tile: Tile::from((6, 6)), end: None }));
+        assert_eq!("  PLAY 4|3  on  3 ".parse(), Ok(Command::Play { tile: Tile::from((3, 4)), end: Some(3) }));
+        assert_eq!("setup 6|6 - 6|3".parse(), Ok(Command::Setup { layout: "6|6 - 6|3".to_string() }));
+        for command in ["draw", "pass", "hand", "board", "hint", "undo", "redo", "quit", "setup 6|6-6|3"] {
+            assert_eq!(command.parse::<Command>().unwrap().to_string(), command);
+        }
+    }
+
+    #[test]
+    fn test_command_parse_errors() {
+        let error = |s: &str| s.parse::<Command>().unwrap_err();
+        assert_eq!(error(""), "Enter a command, e.g. play 3|4 on 6");
+        assert_eq!(error("fold"), "Unknown command 'fold'");
+        assert_eq!(error("play"), "Expected a tile after 'play', e.g. play 3|4 on 6");
+        assert_eq!(error("play 3-4"), "Expected a tile such as 3|4, found '3-4'");
+        assert_eq!(error("play 3|4 on"), "Expected an end after 'on', e.g. play 3|4 on 6");
+        assert_eq!(error("play 3|4 on six"), "Expected the value of an end after 'on', found 'six'");
+        assert_eq!(error("play 3|4 at 4"), "Expected 'on' after the tile, found 'at'");
+        assert_eq!(error("draw 2"), "'draw' is not followed by anything");
+        assert_eq!(error("setup"), "Expected a layout after 'setup', e.g. setup 6|6-6|3");
+    }
+}
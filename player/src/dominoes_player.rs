@@ -2,143 +2,549 @@
 //!
 //! This module defines a computer player for dominoes games, implementing the Player trait.
 //! It uses Monte Carlo Tree Search (MCTS) for decision making and maintains
-//! knowledge of hidden tiles and opponent tile probabilities.
+//! knowledge of hidden tiles and opponent tile probabilities. If the configuration has a seed, the random choices of the
+//! search are the same every time (see `rules::random`), so a search limited by iterations rather than time can be reproduced.
 
-use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
 
 use dominoes_state::{Action, DominoesState};
-use crate::{Hand, Player, DominoesResponseGenerator, DominoesRollout};
-use rules::{Configuration, Tile};
-use hidden_game_player::{mcts, State};
+use crate::{
+    DecisionStats, Hand, IsmctsTree, Player, DominoesResponseGenerator, DominoesRollout, OpeningBook, PlayModel, RolloutPolicy,
+    SearchTree, TileKnowledge,
+};
+use rules::metrics::{self, Counter, Histogram};
+use rules::{Configuration, GameRng, RngStream, Tile, TileMask};
+use hidden_game_player::State;
+
+// Number of MCTS iterations used to choose an action, unless set in the search options
+const DEFAULT_MCTS_ITERATIONS: u32 = 1000;
+
+// Exploration constant of the search, unless set in the search options
+const DEFAULT_MCTS_EXPLORATION: f32 = 1.414;
+
+// Number of iterations between checks of the time budget, when the search is limited by time
+const CHECKPOINT_ITERATIONS: u32 = 100;
+
+// Weights of the factors considered by `DominoesPlayer::analyze`
+const HINT_WEIGHT_MOBILITY: f32 = 1.0;
+const HINT_WEIGHT_PIPS: f32 = 0.1;
+const HINT_WEIGHT_DOUBLE: f32 = 0.5;
+const HINT_WEIGHT_BLOCKING: f32 = 2.0;
+
+/// A play suggested by `DominoesPlayer::analyze`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suggestion {
+    /// The suggested play
+    pub action: Action,
+    /// How good the play is. Only the order of the scores is meaningful.
+    pub score: f32,
+    /// A one-line explanation of why the play is good
+    pub rationale: String,
+}
+
+/// How a `DominoesPlayer` treats the hands that it cannot see while it searches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchAlgorithm {
+    /// MCTS of a single world, either one sampled deal per search or a new sample at every node (see
+    /// `SearchOptions::determinizations`)
+    #[default]
+    Mcts,
+    /// Information Set MCTS, which samples a new deal at the root of every iteration and shares one tree between them (see
+    /// `IsmctsTree`). `SearchOptions::determinizations` is ignored.
+    Ismcts,
+}
+
+/// How a `DominoesPlayer` searches for its actions
+///
+/// The defaults are 1000 iterations, an exploration constant of 1.414, random rollouts, no time limit, a single MCTS search in one
+/// thread that samples the hidden hands at every node, and no inference from the other players' plays.
+///
+/// # Examples
+/// ```rust
+/// # use player::{DominoesPlayer, RolloutPolicy, SearchOptions};
+/// # use rules::Configuration;
+///
+/// let options = SearchOptions { iterations: 5000, rollout_policy: RolloutPolicy::Greedy, ..SearchOptions::default() };
+/// let configuration = Configuration::default();
+/// let player = DominoesPlayer::with_options(0, &configuration, options);
+/// assert_eq!(player.options().iterations, 5000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchOptions {
+    /// The number of MCTS iterations used to choose an action. More iterations play better but take longer.
+    pub iterations: u32,
+    /// The longest time to spend choosing an action, or `None` if it is not limited. The search is split into parts of up to
+    /// 100 iterations (or one part per sampled deal), and it stops early with the most visited action so far if the next part
+    /// would not finish in time. At least one part is always searched.
+    pub time_budget: Option<Duration>,
+    /// The exploration constant of UCB1. Larger values spread the iterations more evenly over the actions.
+    pub exploration: f32,
+    /// How the rollouts choose their actions
+    pub rollout_policy: RolloutPolicy,
+    /// The number of sampled deals that are searched separately, with the iterations split between them. The visits of the
+    /// actions at the roots of their trees are added up, and the most visited action is taken. If 0, a single search samples
+    /// the hidden hands again at every node instead.
+    pub determinizations: u32,
+    /// How the other players' plays change the probabilities of the tiles they might hold
+    pub play_model: PlayModel,
+    /// How the hidden hands are searched
+    pub algorithm: SearchAlgorithm,
+    /// The number of threads that search at the same time, each with its own tree and its share of the iterations. The visits
+    /// of the actions at the roots of the threads' trees are added up before the most visited action is chosen.
+    pub threads: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            iterations: DEFAULT_MCTS_ITERATIONS,
+            time_budget: None,
+            exploration: DEFAULT_MCTS_EXPLORATION,
+            rollout_policy: RolloutPolicy::Random,
+            determinizations: 0,
+            play_model: PlayModel::Uniform,
+            algorithm: SearchAlgorithm::Mcts,
+            threads: 1,
+        }
+    }
+}
 
 /// An AI implementation of Player for dominoes games
 #[derive(Debug, Clone)]
-pub struct DominoesPlayer<'a> {
+pub struct DominoesPlayer {
     /// Player ID
     player_id: u8,
     /// Game configuration
-    configuration: &'a Configuration,
-    /// List of tiles that are still hidden/unknown to this player
+    configuration: Configuration,
+    /// Set of tiles that are still hidden/unknown to this player
     /// Initially contains all tiles, but tiles are removed as they are played or drawn by this player
-    hidden: Vec<Tile>,
+    hidden: TileMask,
     /// List of tiles that the player currently holds in their hand
     hand: Hand,
     /// Probability of the other player having each possible tile
-    /// Maps tile -> probability (0.0 to 1.0)
-    opponent_tile_probabilities: HashMap<Tile, f64>,
+    /// Indexed by the tile's ordinal, probability (0.0 to 1.0)
+    opponent_tile_probabilities: Vec<f64>,
+    /// What this player has deduced from the other players' draws and passes
+    tile_knowledge: TileKnowledge,
+    /// Display name for this player
+    name: String,
+    /// How actions are searched for
+    options: SearchOptions,
+    /// Statistics about the last decision, if any
+    last_decision: Option<DecisionStats>,
+    /// Number of levels of the search tree kept after each action, or 0 to keep none
+    search_tree_depth: usize,
+    /// The top levels of the search tree behind the last decision, if they are kept
+    last_search_tree: Option<SearchTree>,
+    /// The plays made without searching in the first few turns, if any
+    opening_book: Option<OpeningBook>,
+    /// Seeds the random number generator of each search
+    rng: GameRng,
 }
 
-impl<'a> DominoesPlayer<'a> {
+impl DominoesPlayer {
     /// Creates a new dominoes player with the specified configuration
-    pub fn new(player_id: u8, configuration: &'a Configuration) -> Self {
-        // Initialize opponent tile probabilities - initially the opponent's hand is empty
-        let mut opponent_tile_probabilities = HashMap::new();
-        for tile in configuration.all_tiles() {
-            opponent_tile_probabilities.insert(*tile, 0.0);
-        }
+    pub fn new(player_id: u8, configuration: &Configuration) -> Self {
+        Self::with_options(player_id, configuration, SearchOptions::default())
+    }
 
+    /// Creates a new dominoes player that searches with the given options
+    pub fn with_options(player_id: u8, configuration: &Configuration, options: SearchOptions) -> Self {
         Self {
             player_id,
-            configuration,
-            hidden: configuration.all_tiles().to_vec().clone(),
+            configuration: configuration.clone(),
+            hidden: TileMask::all(configuration.set_id()),
             hand: Hand::new(),
-            opponent_tile_probabilities,
+            // Initially the opponent's hand is empty
+            opponent_tile_probabilities: vec![0.0; configuration.set_size()],
+            tile_knowledge: TileKnowledge::new(configuration.num_players()),
+            name: "Computer Player".to_string(),
+            options: SearchOptions { iterations: options.iterations.max(1), threads: options.threads.max(1), ..options },
+            last_decision: None,
+            search_tree_depth: 0,
+            last_search_tree: None,
+            opening_book: None,
+            rng: configuration.rng(RngStream::Player(player_id)),
+        }
+    }
+
+    /// Sets the name of the player, which is "Computer Player" by default
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Sets the number of MCTS iterations used to choose an action
+    ///
+    /// More iterations play better but take longer. The default is 1000.
+    pub fn set_iterations(&mut self, iterations: u32) {
+        self.options.iterations = iterations.max(1);
+    }
+
+    /// Gets the options of the search
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// Keeps the top levels of the search tree behind each action, for visualization (see `Player::search_tree`)
+    ///
+    /// The tree is the one built by the search that chose the action, so keeping it takes no extra searching.
+    ///
+    /// # Arguments
+    /// * `depth` - The number of levels below the root to keep, or 0 to keep none
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::{DominoesPlayer, Player};
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut player = DominoesPlayer::new(0, &configuration);
+    /// player.set_search_tree_depth(2);
+    /// assert!(player.search_tree().is_none());
+    /// ```
+    pub fn set_search_tree_depth(&mut self, depth: usize) {
+        self.search_tree_depth = depth;
+        if depth == 0 {
+            self.last_search_tree = None;
         }
     }
 
-    /// Gets the list of tiles still hidden from this player
-    pub fn hidden_tiles(&self) -> &Vec<Tile> {
+    /// Sets the opening book, whose plays are made without searching, or `None` to always search
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::{DominoesPlayer, OpeningBook};
+    /// # use rules::Configuration;
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut player = DominoesPlayer::new(0, &configuration);
+    /// player.set_opening_book(OpeningBook::embedded(&configuration));
+    /// ```
+    pub fn set_opening_book(&mut self, book: Option<OpeningBook>) {
+        self.opening_book = book;
+    }
+
+    /// Gets the set of tiles still hidden from this player
+    pub fn hidden_tiles(&self) -> &TileMask {
         &self.hidden
     }
 
-    /// Gets the probability map for opponent having each tile
-    pub fn opponent_tile_probabilities(&self) -> &HashMap<Tile, f64> {
+    /// Gets the probability of the opponent having each tile, indexed by the tile's ordinal
+    pub fn opponent_tile_probabilities(&self) -> &[f64] {
         &self.opponent_tile_probabilities
     }
 
     /// Gets the probability that the opponent has a specific tile
     pub fn opponent_tile_probability(&self, tile: Tile) -> f64 {
         self.opponent_tile_probabilities
-            .get(&tile)
+            .get(tile.ordinal as usize)
             .copied()
             .unwrap_or(0.0)
     }
 
-    /// Removes a tile from the hidden list (when played or drawn by this player)
+    /// Gets what this player has deduced about the other players' hands from their draws and passes
+    pub fn tile_knowledge(&self) -> &TileKnowledge {
+        &self.tile_knowledge
+    }
+
+    /// Removes a tile from the hidden set (when played or drawn by this player)
     pub fn remove_hidden_tile(&mut self, tile: Tile) {
-        if let Some(pos) = self.hidden.iter().position(|&t| t == tile) {
-            self.hidden.remove(pos);
-        }
+        self.hidden.remove(tile);
         // Also set opponent probability to 0 since this tile is no longer available
-        self.opponent_tile_probabilities.insert(tile, 0.0);
+        if let Some(probability) = self.opponent_tile_probabilities.get_mut(tile.ordinal as usize) {
+            *probability = 0.0;
+        }
     }
 
-    /// Removes multiple tiles from the hidden list
+    /// Removes multiple tiles from the hidden set
     pub fn remove_hidden_tiles(&mut self, tiles: &[Tile]) {
         for tile in tiles {
             self.remove_hidden_tile(*tile);
         }
     }
 
+    /// Ranks the plays available to a player
+    ///
+    /// Only the information available to the player is used: the player's hand and the layout. Each play is scored by how many of
+    /// the player's remaining tiles can still be played afterward, how many pips it sheds, whether it gets rid of a double, and
+    /// how few of the unseen tiles match the open ends that it leaves.
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player
+    /// * `hand` - The player's hand
+    /// * `state` - The current state of the game. The boneyard is not examined.
+    ///
+    /// # Returns
+    /// The plays, best first. The list is empty if no tile in the hand can be played.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesState, Hand};
+    /// # use player::DominoesPlayer;
+    /// # use rules::{Configuration, Tile, TileMask};
+    ///
+    /// let config = Configuration::default();
+    /// let mut state = DominoesState::new(&config);
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((5, 6)));
+    /// hand.add_tile(Tile::from((5, 5)));
+    ///
+    /// let suggestions = DominoesPlayer::analyze(0, &hand, &state);
+    /// assert_eq!(suggestions[0].action.tile_played, Some((Tile::from((5, 6)), Some(6))));
+    /// println!("{}", suggestions[0].rationale);
+    /// ```
+    pub fn analyze(player_id: u8, hand: &Hand, state: &DominoesState) -> Vec<Suggestion> {
+        // The tiles that the player has not seen might be held by any other player or be in the boneyard
+        let set_id = (state.layout.end_counts.len() - 1) as u8;
+        let mut unseen = TileMask::all(set_id);
+        for tile in hand.tiles().iter().copied().chain(state.layout.nodes.iter().map(|node| node.tile)) {
+            unseen.remove(tile);
+        }
+
+        let mut suggestions = Vec::new();
+        // Only plays are suggested. The player takes the suggestion, so the action is for the player rather than for whoever's
+        // turn it is.
+        for (tile, end) in state.legal_actions(hand).into_iter().filter_map(|action| action.tile_played) {
+            let mut after = state.clone();
+            after.play_tile_unchecked(tile, end);
+
+            let remaining = hand.tiles().iter().filter(|&&other| other != tile);
+            let playable = remaining.filter(|other| after.can_play_tile(other, None)).count();
+            let matching = unseen.iter().filter(|other| after.can_play_tile(other, None)).count();
+            let blocking = if unseen.is_empty() { 0.0 } else { 1.0 - matching as f32 / unseen.len() as f32 };
+            let score = HINT_WEIGHT_MOBILITY * playable as f32
+                + HINT_WEIGHT_PIPS * tile.score() as f32
+                + HINT_WEIGHT_DOUBLE * if tile.is_double() { 1.0 } else { 0.0 }
+                + HINT_WEIGHT_BLOCKING * blocking;
+
+            // Explain the play with its most important factors
+            let mut reasons = vec![
+                (HINT_WEIGHT_MOBILITY * playable as f32, match playable {
+                    0 => "leaves none of your tiles playable".to_string(),
+                    1 => "keeps 1 of your tiles playable".to_string(),
+                    n => format!("keeps {n} of your tiles playable"),
+                }),
+                (HINT_WEIGHT_PIPS * tile.score() as f32, format!("sheds {} pips", tile.score())),
+                (HINT_WEIGHT_BLOCKING * blocking, format!("only {matching} of {} unseen tiles can follow", unseen.len())),
+            ];
+            if tile.is_double() {
+                reasons.push((HINT_WEIGHT_DOUBLE, "gets rid of a double".to_string()));
+            }
+            reasons.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let rationale = reasons.into_iter().take(2).map(|(_, reason)| reason).collect::<Vec<_>>().join(" and ");
+
+            suggestions.push(Suggestion {
+                action: Action::play_unchecked(player_id, tile, end),
+                score,
+                rationale,
+            });
+        }
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+        suggestions
+    }
+
     /// Updates opponent tile probabilities based on current game state
-    /// This method recalculates probabilities assuming uniform distribution
-    /// of remaining tiles between opponent hand and boneyard
+    /// This method recalculates probabilities of the remaining tiles being in the opponent's hand rather than the boneyard. A
+    /// tile with a value that every other player is known not to hold (see `TileKnowledge`) must be in the boneyard, so its
+    /// probability is 0. The others share the opponent's hand in proportion to their weights under the play model of the options
+    /// (see `PlayModel`), so that with `PlayModel::Uniform` they are equally likely.
     pub fn update_opponent_probabilities(&mut self, _boneyard_count: usize) {
         let opponent_hand_size = self.configuration.starting_hand_size(); // Assume opponent still has starting hand size
+        let opponents: Vec<u8> =
+            (0..self.configuration.num_players() as u8).filter(|&player_id| player_id != self.player_id).collect();
 
-        // For tiles still hidden, calculate probability they're in opponent's hand
-        // vs. still in the boneyard
-        let total_unknown_tiles = self.hidden.len();
-
-        if total_unknown_tiles > 0 {
-            // Probability a hidden tile is in opponent's hand rather than boneyard
-            let prob_in_opponent_hand = if total_unknown_tiles <= opponent_hand_size {
-                // If there are fewer unknown tiles than opponent has, then the opponent must have all of them
-                1.0
-            } else {
-                // Otherwise, probability is proportional to opponent hand size
-                opponent_hand_size as f64 / total_unknown_tiles as f64
-            };
-
-            // Update probabilities for all hidden tiles
-            for tile in &self.hidden {
-                self.opponent_tile_probabilities
-                    .insert(*tile, prob_in_opponent_hand);
+        // The weight of a tile is its weight for the likeliest opponent to hold it
+        let tiles: Vec<Tile> = self.hidden.iter().collect();
+        let weights: Vec<f64> = tiles
+            .iter()
+            .map(|&tile| {
+                opponents
+                    .iter()
+                    .map(|&player_id| self.options.play_model.weight(&self.tile_knowledge, player_id, tile))
+                    .fold(0.0, f64::max)
+            })
+            .collect();
+        let probabilities = share_hand(&weights, opponent_hand_size);
+        for (tile, probability) in tiles.into_iter().zip(probabilities) {
+            self.opponent_tile_probabilities[tile.ordinal as usize] = probability;
+        }
+    }
+}
+
+impl DominoesPlayer {
+    // Searches for an action according to the options, and returns it with the top levels of the tree of the search, whose
+    // iterations are the number of iterations that were done. Each response generator searches in its own thread with its share
+    // of the iterations, the first threads taking one more if they cannot be shared equally, and the threads' trees are merged.
+    fn search(
+        &self,
+        state: &DominoesState,
+        rgs: &[DominoesResponseGenerator],
+        rollout: &DominoesRollout,
+        started: Instant,
+    ) -> (Option<Action>, SearchTree) {
+        let (num_threads, iterations) = (rgs.len() as u32, self.options.iterations);
+        let share = |thread: u32| iterations / num_threads + u32::from(thread < iterations % num_threads);
+        let trees: Vec<SearchTree> = match rgs {
+            [rg] => vec![self.search_thread(state, rg, rollout, started, iterations)],
+            _ => thread::scope(|scope| {
+                let threads: Vec<_> = rgs
+                    .iter()
+                    .zip((0..num_threads).map(share))
+                    .filter(|&(_, iterations)| iterations > 0)
+                    .map(|(rg, iterations)| scope.spawn(move || self.search_thread(state, rg, rollout, started, iterations)))
+                    .collect();
+                threads.into_iter().map(|thread| thread.join().expect("A search thread panicked")).collect()
+            }),
+        };
+        let tree = trees.into_iter().reduce(SearchTree::merge).expect("At least one thread searches");
+
+        // A sampled deal draws a sampled tile, so the draws at the root are changed to draw the tile that is really next
+        let mut root = tree.root;
+        for child in &mut root.children {
+            child.action = child.action.take().map(|action| action.drawing_from(state));
+        }
+        let tree = SearchTree::new(tree.player_id, root);
+        (most_visited(tree.visits().into_iter()), tree)
+    }
+
+    // Searches for an action in one thread, and returns the top levels of its tree. Only as many levels as are exported are
+    // kept, but at least the root and its children. The search is split into parts with a tree each if there are sampled deals
+    // or a time budget (see `SearchOptions`), and the trees of the parts are merged. The parts are searched with
+    // `SearchTree::search` rather than `mcts::search`, which only returns the action that it chose, so that the parts and the
+    // threads can be added up by their visits rather than by their choices.
+    fn search_thread(
+        &self,
+        state: &DominoesState,
+        rg: &DominoesResponseGenerator,
+        rollout: &DominoesRollout,
+        started: Instant,
+        iterations: u32,
+    ) -> SearchTree {
+        let SearchOptions { time_budget, exploration, determinizations, algorithm, .. } = self.options;
+        let depth = self.search_tree_depth.max(1);
+        if algorithm == SearchAlgorithm::Ismcts {
+            return self.search_ismcts(state, rg, rollout, started, iterations).truncated(depth);
+        }
+        let parts = match (determinizations, time_budget) {
+            (0, Some(_)) => iterations.div_ceil(CHECKPOINT_ITERATIONS),
+            (0, None) => 1,
+            (determinizations, _) => determinizations.min(iterations),
+        };
+
+        let mut tree: Option<SearchTree> = None;
+        for part in 0..parts {
+            // Stop if the next part would take longer than the time that is left, judging by the parts so far
+            if let Some(budget) = time_budget.filter(|_| part > 0) {
+                let elapsed = started.elapsed();
+                if elapsed + elapsed / part >= budget {
+                    break;
+                }
             }
+            // The iterations are shared exactly, the first parts taking one more if they cannot be shared equally
+            let share = iterations / parts + u32::from(part < iterations % parts);
+            let part_state = if determinizations > 0 { rg.determinize(state) } else { state.clone() };
+            let part_tree = SearchTree::search(&part_state, rg, rollout, exploration, share, usize::MAX).truncated(depth);
+            tree = Some(match tree {
+                Some(tree) => tree.merge(part_tree),
+                None => part_tree,
+            });
         }
+        tree.expect("At least one part is searched")
+    }
+
+    // Searches for an action with Information Set MCTS, and returns its tree. With a time budget, the tree is searched in parts,
+    // and the search stops early like the others.
+    fn search_ismcts(
+        &self,
+        state: &DominoesState,
+        rg: &DominoesResponseGenerator,
+        rollout: &DominoesRollout,
+        started: Instant,
+        iterations: u32,
+    ) -> SearchTree {
+        let SearchOptions { time_budget, exploration, .. } = self.options;
+        let share = if time_budget.is_some() { CHECKPOINT_ITERATIONS.min(iterations) } else { iterations };
+        let mut tree = IsmctsTree::new(state);
+        for part in 0..iterations.div_ceil(share) {
+            if let Some(budget) = time_budget.filter(|_| part > 0) {
+                let elapsed = started.elapsed();
+                if elapsed + elapsed / part >= budget {
+                    break;
+                }
+            }
+            tree.search(rg, rollout, exploration, share.min(iterations - tree.iterations()));
+        }
+        tree.to_search_tree()
     }
 }
 
-impl<'a> Player for DominoesPlayer<'a> {
+impl Player for DominoesPlayer {
     fn reset(&mut self) {
         self.hand = Hand::new();
-        self.hidden = self.configuration.all_tiles().to_vec().clone();
+        self.hidden = TileMask::all(self.configuration.set_id());
+        self.last_decision = None;
+        self.last_search_tree = None;
         // Reset opponent probabilities
-        for tile in self.configuration.all_tiles() {
-            self.opponent_tile_probabilities.insert(*tile, 0.0);
-        }
+        self.opponent_tile_probabilities.fill(0.0);
+        self.tile_knowledge.reset();
     }
 
-    fn set_up(&mut self, state: &mut DominoesState) {
-        // Draw the starting hand size number of tiles from the boneyard
-        let hand_size = self.configuration.starting_hand_size();
-        for _ in 0..hand_size {
-            if let Some(tile) = state.draw_tile() {
-                self.hand.add_tile(tile);
-                self.remove_hidden_tile(tile); // Remove drawn tile from hidden
-            }
-        }
+    fn set_up_from(&mut self, hand: Hand, state: &DominoesState) {
+        // The tiles in the hand and in the layout are known
+        self.remove_hidden_tiles(hand.tiles());
+        let played: Vec<Tile> = state.layout.nodes.iter().map(|node| node.tile).collect();
+        self.remove_hidden_tiles(&played);
+        self.hand = hand;
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "search",
+            level = "debug",
+            skip_all,
+            fields(player_id = self.player_id, iterations = self.options.iterations)
+        )
+    )]
     fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
-        // TODO: Implement dominoes-specific game logic
-        // Rules is available as self.configuration: self.configuration.num_players, self.configuration.variation, etc.
-        // Action history is available via state.get_actions()
-        // This is a stub implementation that just returns a pass action and the same state
+        // A position in the opening book is not searched
+        if let Some(action) = self.opening_book.as_ref().and_then(|book| book.choose(state, &self.hand)) {
+            self.last_decision = Some(DecisionStats { iterations: 0, elapsed: Duration::ZERO });
+            self.last_search_tree = None;
+            let new_state = state.apply(&action);
+            return (action, new_state);
+        }
 
-        let rg = DominoesResponseGenerator::new();
-        let rollout = DominoesRollout::new();
-        let action: Option<Action> = mcts::search(state, &rg, &rollout, 1.414f32, 1000);
+        // The other players' actions are generated for hands sampled from what this player knows
+        self.update_opponent_probabilities(state.boneyard.count());
+        let rg = DominoesResponseGenerator::with_knowledge(
+            self.player_id,
+            &self.hand,
+            &self.hidden,
+            &self.opponent_tile_probabilities,
+        )
+        .with_tile_knowledge(&self.tile_knowledge);
+        // Every thread samples with its own random number generator
+        let rgs: Vec<DominoesResponseGenerator> =
+            (0..self.options.threads).map(|_| rg.clone().with_rng(GameRng::from_rng(&mut self.rng))).collect();
+        let rollout = DominoesRollout::with_policy(self.options.rollout_policy);
+        // Every player in the search claims the points of its plays, so they are scored under the muggins rule
+        let searched = state.clone().with_points_claimed();
+        let started = Instant::now();
+        let (action, tree) = self.search(&searched, &rgs, &rollout, started);
+        let elapsed = started.elapsed();
+        self.last_decision = Some(DecisionStats { iterations: tree.iterations, elapsed });
+        metrics::increment(Counter::Searches);
+        metrics::record(Histogram::SearchMicros, elapsed.as_micros() as u64);
+        self.last_search_tree = (self.search_tree_depth > 0).then_some(tree);
 
         match action {
             Some(action) => {
@@ -160,12 +566,51 @@ impl<'a> Player for DominoesPlayer<'a> {
             .any(|tile| state.can_play_tile(tile, None))
     }
 
+    fn undo(&mut self, action: &Action) {
+        self.hand.undo(action);
+        // A tile that is put back in the boneyard is hidden again
+        if let Some(tile) = action.tile_drawn {
+            self.hidden.insert(tile);
+        }
+    }
+
+    fn redo(&mut self, action: &Action) {
+        self.hand.redo(action);
+        if let Some(tile) = action.tile_drawn {
+            self.remove_hidden_tile(tile);
+        }
+    }
+
+    fn observe(&mut self, action: &Action, state: &DominoesState) {
+        self.tile_knowledge.observe(action, state);
+    }
+
+    fn forget(&mut self, _action: &Action) {
+        self.tile_knowledge.forget();
+    }
+
+    fn set_time_budget(&mut self, budget: Option<Duration>) {
+        self.options.time_budget = budget;
+    }
+
+    fn decision_stats(&self) -> Option<DecisionStats> {
+        self.last_decision
+    }
+
+    fn search_tree(&self) -> Option<&SearchTree> {
+        self.last_search_tree.as_ref()
+    }
+
     fn hand(&self) -> &Hand {
         &self.hand
     }
 
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
     fn name(&self) -> &str {
-        "Computer Player"
+        &self.name
     }
 
     fn id(&self) -> u8 {
@@ -173,9 +618,54 @@ impl<'a> Player for DominoesPlayer<'a> {
     }
 }
 
+// Returns the probability of each tile being in a hand of the given size, in proportion to its weight. No probability is more
+// than 1, so the share of a tile that would have more is given to the others.
+fn share_hand(weights: &[f64], hand_size: usize) -> Vec<f64> {
+    let mut probabilities = vec![0.0; weights.len()];
+    let mut remaining = hand_size as f64;
+    let mut certain = vec![false; weights.len()];
+    loop {
+        let total: f64 = weights.iter().zip(&certain).filter(|(_, &c)| !c).map(|(w, _)| w).sum();
+        if total <= 0.0 {
+            return probabilities;
+        }
+        let share = remaining / total;
+        let mut done = true;
+        for (i, &weight) in weights.iter().enumerate() {
+            if !certain[i] {
+                probabilities[i] = share * weight;
+                if probabilities[i] >= 1.0 {
+                    // This tile must be in the hand, so the others share what is left
+                    probabilities[i] = 1.0;
+                    certain[i] = true;
+                    remaining -= 1.0;
+                    done = false;
+                }
+            }
+        }
+        if done {
+            return probabilities;
+        }
+    }
+}
+
+// Returns the action with the most visits in total, or the first of them if several have equally many. A sampled deal draws a
+// sampled tile, so the draws are counted together (see `Action::is_same_choice`).
+fn most_visited(visits: impl Iterator<Item = (Action, u32)>) -> Option<Action> {
+    let mut counts: Vec<(Action, u32)> = Vec::new();
+    for (action, visits) in visits {
+        match counts.iter_mut().find(|(other, _)| other.is_same_choice(&action)) {
+            Some((_, count)) => *count += visits,
+            None => counts.push((action, visits)),
+        }
+    }
+    counts.into_iter().rev().max_by_key(|&(_, count)| count).map(|(action, _)| action)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hidden_game_player::mcts;
 
     #[test]
     fn test_dominoes_player_creation() {
@@ -185,7 +675,7 @@ mod tests {
         assert_eq!(player.name(), "Computer Player");
         // Test that hidden tiles are initialized with all tiles
         assert_eq!(player.hidden_tiles().len(), configuration.set_size());
-        assert_eq!(player.hidden_tiles(), configuration.all_tiles());
+        assert_eq!(*player.hidden_tiles(), TileMask::all(configuration.set_id()));
         // Test that hand is initially empty
         assert_eq!(player.hand.len(), 0);
         assert_eq!(player.hand.tiles().len(), 0);
@@ -194,29 +684,31 @@ mod tests {
     #[test]
     fn test_dominoes_player_name() {
         let configuration = Configuration::default();
-        let player = DominoesPlayer::new(1, &configuration);
+        let mut player = DominoesPlayer::new(1, &configuration);
         // Test that DominoesPlayer returns the correct name
         assert_eq!(player.name(), "Computer Player");
+
+        player.set_name("Hal");
+        assert_eq!(player.name(), "Hal");
     }
 
     #[test]
     fn test_dominoes_player_implements_player_trait() {
         let configuration = Configuration::default();
         let mut player = DominoesPlayer::new(1, &configuration);
-        let state = DominoesState::new(&configuration);
+        let mut state = DominoesState::new(&configuration);
+        state.whose_turn = 1;
 
-        // Test that my_turn method exists and returns expected types
-        // Focus on DominoesPlayer's implementation, not external dependencies
+        // Traditional requires the highest double, so the player has only one legal action
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((6, 6)));
+        hand.add_tile(Tile::from((1, 2)));
+        player.set_up_from(hand, &state);
         let (returned_action, new_state) = player.my_turn(&state);
 
-        // Test DominoesPlayer's specific behavior: should return pass action in stub implementation
-        assert_eq!(returned_action.player_id, 1);
-        assert!(returned_action.tile_drawn.is_none());
-        assert!(returned_action.tile_played.is_none());
-
-        // Note: Since the stub implementation returns the same state, we can't test for added actions
-        // This test focuses on verifying the method exists and returns correct types
-        assert_eq!(new_state.whose_turn, state.whose_turn);
+        assert_eq!(returned_action, Action::play_unchecked(1, Tile::from((6, 6)), None));
+        assert_eq!(new_state.layout.nodes.len(), 1);
+        assert_eq!(new_state.whose_turn, 0);
     }
 
     #[test]
@@ -224,11 +716,11 @@ mod tests {
         let configuration = Configuration::default();
         let player = DominoesPlayer::new(1, &configuration);
 
-        // Test that DominoesPlayer stores the configuration reference correctly
+        // Test that DominoesPlayer stores the configuration correctly
         // This verifies our constructor works by checking initialization based on configuration
         // Verify that hidden tiles are initialized to the full set
         assert_eq!(player.hidden_tiles().len(), configuration.set_size());
-        assert_eq!(player.hidden_tiles(), configuration.all_tiles());
+        assert_eq!(*player.hidden_tiles(), TileMask::all(configuration.set_id()));
     }
 
     #[test]
@@ -239,7 +731,7 @@ mod tests {
         // Test initial state - all tiles should be hidden
         let initial_count = player.hidden_tiles().len();
         assert_eq!(initial_count, configuration.set_size());
-        assert_eq!(player.hidden_tiles(), configuration.all_tiles());
+        assert_eq!(*player.hidden_tiles(), TileMask::all(configuration.set_id()));
 
         // Test removing a single tile
         let tile_to_remove = Tile::from((0, 0));
@@ -501,7 +993,7 @@ mod tests {
 
         // Hidden tiles should have updated probabilities
         for tile in player.hidden_tiles() {
-            let prob = player.opponent_tile_probability(*tile);
+            let prob = player.opponent_tile_probability(tile);
             assert!(prob >= 0.0 && prob <= 1.0); // Valid probability range
         }
 
@@ -511,6 +1003,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_opponent_probabilities_with_voids() {
+        let configuration = Configuration::default();
+        let mut player = DominoesPlayer::new(0, &configuration);
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        player.remove_hidden_tile(Tile::from((6, 6)));
+        state.whose_turn = 1;
+
+        // The opponent passes on the double six, so they hold no six, and the other tiles share their hand
+        player.observe(&Action::pass(1), &state);
+        player.update_opponent_probabilities(state.boneyard.count());
+        assert_eq!(player.tile_knowledge().voids(1), vec![6]);
+        for tile in player.hidden_tiles() {
+            let expected = if tile.as_tuple().1 == 6 { 0.0 } else { 7.0 / 21.0 };
+            assert_eq!(player.opponent_tile_probability(tile), expected);
+        }
+
+        // Taking back the pass forgets what it showed
+        player.forget(&Action::pass(1));
+        player.update_opponent_probabilities(state.boneyard.count());
+        assert_eq!(player.opponent_tile_probability(Tile::from((5, 6))), 7.0 / 27.0);
+
+        // So does a new round
+        player.observe(&Action::pass(1), &state);
+        player.reset();
+        assert!(player.tile_knowledge().voids(1).is_empty());
+    }
+
+    #[test]
+    fn test_opponent_probabilities_with_plays() {
+        let configuration = Configuration::default();
+        let state = DominoesState::new(&configuration);
+        let probabilities = |play_model: PlayModel| {
+            let options = SearchOptions { play_model, ..SearchOptions::default() };
+            let mut player = DominoesPlayer::with_options(0, &configuration, options);
+            player.observe(&Action::play_unchecked(1, Tile::from((6, 6)), None), &state);
+            player.remove_hidden_tile(Tile::from((6, 6)));
+            player.update_opponent_probabilities(state.boneyard.count());
+            (player.opponent_tile_probability(Tile::from((5, 6))), player.opponent_tile_probability(Tile::from((4, 5))))
+        };
+
+        // The 6 other sixes weigh 2 each and the 21 other tiles 1, so a six gets 2/33 of the opponent's 7 tiles
+        assert_eq!(probabilities(PlayModel::Uniform), (7.0 / 27.0, 7.0 / 27.0));
+        assert_eq!(probabilities(PlayModel::Suited { affinity: 1.0 }), (7.0 / 27.0, 7.0 / 27.0));
+        assert_eq!(probabilities(PlayModel::Suited { affinity: 2.0 }), (7.0 / 33.0 * 2.0, 7.0 / 33.0));
+
+        // With a weight of 30, a six would get 7 * 30/201 > 1, so the opponent holds all of them and the others share one tile
+        assert_eq!(probabilities(PlayModel::Suited { affinity: 30.0 }), (1.0, 1.0 / 21.0));
+    }
+
     #[test]
     fn test_probability_calculation_logic() {
         let configuration = Configuration::default();
@@ -539,7 +1082,7 @@ mod tests {
         // (7 tiles in opponent hand out of 21 unknown tiles)
         let expected_prob = 7.0 / 21.0;
         for tile in player.hidden_tiles() {
-            let actual_prob = player.opponent_tile_probability(*tile);
+            let actual_prob = player.opponent_tile_probability(tile);
             assert!((actual_prob - expected_prob).abs() < 0.001); // Float comparison with tolerance
         }
     }
@@ -572,8 +1115,288 @@ mod tests {
         let expected_prob = opponent_hand_size as f64 / remaining_hidden as f64;
 
         for tile in player.hidden_tiles() {
-            let actual_prob = player.opponent_tile_probability(*tile);
+            let actual_prob = player.opponent_tile_probability(tile);
             assert!((actual_prob - expected_prob).abs() < 0.001);
         }
     }
+
+    #[test]
+    fn test_analyze() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        let mut hand = Hand::new();
+
+        // Nothing in the hand can be played
+        hand.add_tile(Tile::from((1, 2)));
+        assert!(DominoesPlayer::analyze(0, &hand, &state).is_empty());
+
+        // Playing 5|6 on the double six lets the 5|5 and the 0|6 follow, and playing 0|6 only lets the 5|6 follow
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        hand.add_tile(Tile::from((5, 6)));
+        hand.add_tile(Tile::from((5, 5)));
+        hand.add_tile(Tile::from((0, 6)));
+        let suggestions = DominoesPlayer::analyze(1, &hand, &state);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].action, Action::play_unchecked(1, Tile::from((5, 6)), Some(6)));
+        assert_eq!(suggestions[1].action, Action::play_unchecked(1, Tile::from((0, 6)), Some(6)));
+        assert!(suggestions[0].score >= suggestions[1].score);
+        assert!(suggestions[0].rationale.contains("keeps 2 of your tiles playable"));
+    }
+
+    #[test]
+    fn test_analyze_first_tile() {
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((2, 2)));
+        hand.add_tile(Tile::from((4, 4)));
+        hand.add_tile(Tile::from((1, 3)));
+
+        // Only the highest double can be played first in the traditional game
+        let state = DominoesState::new(&Configuration::default());
+        let suggestions = DominoesPlayer::analyze(0, &hand, &state);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].action, Action::play_unchecked(0, Tile::from((4, 4)), None));
+
+        // Any tile can be played first in All Fives
+        let state = DominoesState::new(&Configuration::new(2, rules::Variation::AllFives, 6, 7));
+        assert_eq!(DominoesPlayer::analyze(0, &hand, &state).len(), 3);
+    }
+
+    #[test]
+    fn test_search_options() {
+        let configuration = Configuration::default();
+        let player = DominoesPlayer::new(0, &configuration);
+        assert_eq!(*player.options(), SearchOptions::default());
+
+        let options = SearchOptions { iterations: 0, determinizations: 4, ..SearchOptions::default() };
+        let mut player = DominoesPlayer::with_options(1, &configuration, options);
+        assert_eq!(player.options().iterations, 1);
+        player.set_time_budget(Some(Duration::from_millis(50)));
+        assert_eq!(player.options().time_budget, Some(Duration::from_millis(50)));
+
+        // Every sampled deal chooses the only legal action
+        let mut state = DominoesState::new(&configuration);
+        state.whose_turn = 1;
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((6, 6)));
+        hand.add_tile(Tile::from((1, 2)));
+        player.set_up_from(hand, &state);
+        let (action, _) = player.my_turn(&state);
+        assert_eq!(action, Action::play_unchecked(1, Tile::from((6, 6)), None));
+    }
+
+    #[test]
+    fn test_ismcts() {
+        let configuration = Configuration::default();
+        let options = SearchOptions { iterations: 30, algorithm: SearchAlgorithm::Ismcts, ..SearchOptions::default() };
+        let mut player = DominoesPlayer::with_options(1, &configuration, options);
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..14 {
+            state.draw_tile();
+        }
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+
+        // Only the five-six can be played on the double six
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((5, 6)));
+        hand.add_tile(Tile::from((1, 2)));
+        player.set_up_from(hand, &state);
+        let (action, _) = player.my_turn(&state);
+        assert_eq!(action.tile_played.map(|(tile, _)| tile), Some(Tile::from((5, 6))));
+        assert_eq!(player.decision_stats().unwrap().iterations, 30);
+
+        // With a time budget, the tree is searched in parts
+        player.set_time_budget(Some(Duration::from_secs(60)));
+        player.my_turn(&state);
+        assert_eq!(player.decision_stats().unwrap().iterations, 30);
+    }
+
+    #[test]
+    fn test_threads() {
+        let configuration = Configuration::default().with_seed(3);
+        let mut state = DominoesState::new(&configuration);
+        state.whose_turn = 1;
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((6, 6)));
+        hand.add_tile(Tile::from((1, 2)));
+
+        // The iterations are shared exactly by the threads, and every thread chooses the only legal action
+        for algorithm in [SearchAlgorithm::Mcts, SearchAlgorithm::Ismcts] {
+            for (iterations, threads) in [(40, 4), (40, 3), (2, 4)] {
+                let options = SearchOptions { iterations, threads, algorithm, ..SearchOptions::default() };
+                let mut player = DominoesPlayer::with_options(1, &configuration, options);
+                player.set_up_from(hand.clone(), &state);
+                let (action, _) = player.my_turn(&state);
+                assert_eq!(action, Action::play_unchecked(1, Tile::from((6, 6)), None));
+                assert_eq!(player.decision_stats().unwrap().iterations, iterations);
+            }
+        }
+
+        let options = SearchOptions { threads: 0, ..SearchOptions::default() };
+        assert_eq!(DominoesPlayer::with_options(1, &configuration, options).options().threads, 1);
+    }
+
+    #[test]
+    fn test_opening_book() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((1, 6)));
+        hand.add_tile(Tile::from((4, 6)));
+
+        // The book's play is made without searching
+        let mut player = DominoesPlayer::new(1, &configuration);
+        player.set_up_from(hand, &state);
+        player.set_opening_book(Some(OpeningBook::parse("6|6 : 5|6@6 4|6@6").unwrap()));
+        let (action, new_state) = player.my_turn(&state);
+        assert_eq!(action, Action::play_unchecked(1, Tile::from((4, 6)), Some(6)));
+        assert_eq!(new_state.layout.nodes.len(), 2);
+        assert_eq!(player.decision_stats().unwrap().iterations, 0);
+
+        // A position that is not in the book is searched
+        player.set_opening_book(Some(OpeningBook::parse(": 5|5").unwrap()));
+        player.set_iterations(10);
+        player.my_turn(&state);
+        assert_eq!(player.decision_stats().unwrap().iterations, 10);
+    }
+
+    #[test]
+    fn test_my_turn_block_game() {
+        let configuration = Configuration::default().with_draw_rule(rules::DrawRule::NoDraw);
+        let options = SearchOptions { iterations: 10, determinizations: 2, ..SearchOptions::default() };
+        let mut player = DominoesPlayer::with_options(1, &configuration, options);
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+
+        // Nothing in the hand can be played on the double six, so the player passes rather than draw
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((1, 2)));
+        player.set_up_from(hand, &state);
+        assert_eq!(player.my_turn(&state).0, Action::pass(1));
+    }
+
+    #[test]
+    fn test_my_turn_draw() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..14 {
+            state.draw_tile();
+        }
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+
+        // Nothing in the hand can be played on the double six, so the player must draw. Every search samples the boneyard, but
+        // the tile that is really next is drawn.
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((1, 2)));
+        hand.add_tile(Tile::from((0, 3)));
+        let draw = Action::draw(1, *state.boneyard.peek().unwrap());
+        for algorithm in [SearchAlgorithm::Mcts, SearchAlgorithm::Ismcts] {
+            let options = SearchOptions { iterations: 20, determinizations: 4, algorithm, ..SearchOptions::default() };
+            let mut player = DominoesPlayer::with_options(1, &configuration, options);
+            player.set_up_from(hand.clone(), &state);
+            let (action, new_state) = player.my_turn(&state);
+            assert_eq!(action, draw);
+            assert_eq!(new_state.boneyard.count(), state.boneyard.count() - 1);
+        }
+    }
+
+    #[test]
+    fn test_most_visited() {
+        let (a, b) = (Action::pass(0), Action::draw(0, Tile::from((1, 2))));
+        assert_eq!(most_visited([(a.clone(), 1), (b.clone(), 1), (b.clone(), 1)].into_iter()), Some(b.clone()));
+        let c = Action::draw(0, Tile::from((3, 4)));
+        assert_eq!(most_visited([(a.clone(), 1), (b.clone(), 1), (c, 1)].into_iter()), Some(b.clone()));
+        assert_eq!(most_visited([(a.clone(), 1), (b.clone(), 1)].into_iter()), Some(a.clone()));
+        assert_eq!(most_visited([(a.clone(), 3), (b.clone(), 2), (b, 2)].into_iter()), Some(b));
+        assert_eq!(most_visited([(a.clone(), 3)].into_iter()), Some(a));
+        assert_eq!(most_visited(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_search_tree() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..14 {
+            state.draw_tile();
+        }
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((5, 6)));
+        hand.add_tile(Tile::from((4, 6)));
+
+        // The tree is the one that chose the action, with the trees of the threads and sampled deals merged
+        let searches = [(SearchAlgorithm::Mcts, 0, 1), (SearchAlgorithm::Mcts, 2, 2), (SearchAlgorithm::Ismcts, 0, 2)];
+        for (algorithm, determinizations, threads) in searches {
+            let options = SearchOptions { iterations: 40, determinizations, algorithm, threads, ..SearchOptions::default() };
+            let mut player = DominoesPlayer::with_options(1, &configuration, options);
+            player.set_up_from(hand.clone(), &state);
+            player.set_search_tree_depth(1);
+            let (action, _) = player.my_turn(&state);
+            let tree = player.search_tree().unwrap();
+            assert_eq!(tree.iterations, 40);
+            assert_eq!(tree.principal_variation, vec![action]);
+            assert!(tree.root.children.iter().all(|child| child.children.is_empty()));
+        }
+
+        // The tree is not kept unless it is asked for
+        let mut player = DominoesPlayer::new(1, &configuration);
+        player.set_up_from(hand, &state);
+        player.my_turn(&state);
+        assert!(player.search_tree().is_none());
+    }
+
+    #[test]
+    fn test_search_tree_chooses_like_library_search() {
+        // Player 0 wins by playing the two-six first, which player 1 cannot follow, and loses by playing the one-six first, which
+        // player 1 follows with their last tile
+        let configuration = Configuration::default().with_draw_rule(rules::DrawRule::NoDraw);
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 0;
+        state.set_hands(vec![
+            Hand::from(vec![Tile::from((1, 6)), Tile::from((2, 6))]),
+            Hand::from(vec![Tile::from((1, 1))]),
+        ]);
+        let (rg, rollout) = (DominoesResponseGenerator::new(), DominoesRollout::new());
+        let best = Action::play_unchecked(0, Tile::from((2, 6)), Some(6));
+        assert_eq!(mcts::search(&state, &rg, &rollout, DEFAULT_MCTS_EXPLORATION, 40), Some(best.clone()));
+
+        // The visits of two parts added up choose the same action
+        let visits = (0..2)
+            .flat_map(|_| SearchTree::search(&state, &rg, &rollout, DEFAULT_MCTS_EXPLORATION, 20, usize::MAX).visits());
+        assert_eq!(most_visited(visits), Some(best));
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.whose_turn = 1;
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((6, 6)));
+        hand.add_tile(Tile::from((1, 2)));
+
+        // Out of time, only the first part is searched, and its action is taken
+        let mut player = DominoesPlayer::new(1, &configuration);
+        player.set_up_from(hand.clone(), &state);
+        player.set_time_budget(Some(Duration::ZERO));
+        let (action, _) = player.my_turn(&state);
+        assert_eq!(action, Action::play_unchecked(1, Tile::from((6, 6)), None));
+        assert_eq!(player.decision_stats().unwrap().iterations, CHECKPOINT_ITERATIONS);
+
+        // With plenty of time, every iteration is done
+        player.set_time_budget(Some(Duration::from_secs(60)));
+        player.my_turn(&state);
+        assert_eq!(player.decision_stats().unwrap().iterations, DEFAULT_MCTS_ITERATIONS);
+
+        // The parts share the iterations exactly
+        player.set_iterations(250);
+        player.my_turn(&state);
+        assert_eq!(player.decision_stats().unwrap().iterations, 250);
+    }
 }
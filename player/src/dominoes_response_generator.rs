@@ -2,12 +2,37 @@
 //!
 //! This module defines the response generator for the Dominoes game, which is responsible for generating all possible
 //! actions from a given game state.
+//!
+//! A player does not know the other hands, so the actions of the other players are generated for hands that they might hold.
+//! These hands are sampled from the tiles that the player has not seen, weighted by the probability of the other player holding
+//! each of them. This is called determinization. Each node of the search sees a different sample, so the search averages over
+//! plausible hands rather than depending on one guess. A player is never given a tile with a value that they are known not to
+//! hold (see `TileKnowledge`). The hands and the boneyard are dealt together by a `WorldSampler`, so that they are consistent.
+//!
+//! The samples are drawn from the generator's own random number generator, which is also used by the rollouts (see
+//! `DominoesRollout`). A generator created with `with_rng` and a seeded generator makes the same samples every time, so a search
+//! can be reproduced.
+
+use std::sync::{Mutex, PoisonError};
+
+use rand::{Rng, SeedableRng};
 
 use hidden_game_player::mcts::ResponseGenerator;
-use dominoes_state::{DominoesState, Action};
+use dominoes_state::{DominoesState, Action, Hand};
+use crate::{TileKnowledge, WorldSampler};
+use rules::{Boneyard, GameRng, Tile, TileMask};
+use rules::metrics::{self, Counter};
 
 /// A response generator for the Dominoes game that implements the `ResponseGenerator` trait.
 ///
+/// The hand of the player to move is determined as follows:
+/// 1. If the state keeps track of the hands (see `DominoesState::deal`), the hand in the state is used.
+/// 2. If it is the turn of the player whose knowledge the generator has (see `with_knowledge`), that player's hand is used,
+///    without the tiles that have been played since.
+/// 3. Otherwise, a hand is sampled from the tiles that the player has not seen (see the module documentation).
+///
+/// A generator created with `new` has no knowledge, so it only generates plays for the hands in the state.
+///
 /// # Examples
 /// ```rust
 /// # use player::DominoesResponseGenerator;
@@ -15,13 +40,26 @@ use dominoes_state::{DominoesState, Action};
 /// let generator = DominoesResponseGenerator::new();
 /// // Use generator with MCTS algorithm...
 /// ```
-pub struct DominoesResponseGenerator;
+#[derive(Debug)]
+pub struct DominoesResponseGenerator {
+    // What the searching player knows, if anything
+    knowledge: Option<Knowledge>,
+    // Samples the hands and chooses the actions of the rollouts. The search only borrows the generator, so it is behind a lock.
+    rng: Mutex<GameRng>,
+}
+
+// A clone continues from the same state of the random number generator, so it makes the same choices
+impl Clone for DominoesResponseGenerator {
+    fn clone(&self) -> Self {
+        Self { knowledge: self.knowledge.clone(), rng: Mutex::new(self.use_rng(|rng| rng.clone())) }
+    }
+}
 
 impl DominoesResponseGenerator {
     /// Creates a new `DominoesResponseGenerator` instance.
     ///
-    /// This is a simple constructor that creates a new response generator for use with the MCTS algorithm. Since the generator is
-    /// stateless, this simply returns a new instance of the struct.
+    /// This is a simple constructor that creates a new response generator for use with the MCTS algorithm. The generator has no
+    /// knowledge of the hidden tiles, so it relies on the hands in the state.
     ///
     /// # Returns
     /// A new `DominoesResponseGenerator` instance ready for use.
@@ -33,7 +71,228 @@ impl DominoesResponseGenerator {
     /// let generator = DominoesResponseGenerator::new();
     /// ```
     pub fn new() -> Self {
-        Self
+        Self { knowledge: None, rng: Mutex::new(GameRng::from_rng(&mut rand::rng())) }
+    }
+
+    /// Creates a response generator for a player that searches with what it knows about the hidden tiles
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the searching player
+    /// * `hand` - The player's hand
+    /// * `hidden` - The tiles that the player has not seen
+    /// * `opponent_tile_probabilities` - The probability of another player holding each tile, indexed by the tile's ordinal. The
+    ///   sum of the probabilities of the hidden tiles is the expected size of another player's hand.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesState, Hand};
+    /// # use hidden_game_player::mcts::ResponseGenerator;
+    /// # use player::DominoesResponseGenerator;
+    /// # use rules::{Configuration, Tile, TileMask};
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut state = DominoesState::new(&configuration);
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((5, 6)));
+    /// let mut hidden = TileMask::all(configuration.set_id());
+    /// hidden.remove(Tile::from((5, 6)));
+    /// hidden.remove(Tile::from((6, 6)));
+    /// let probabilities = vec![0.25; configuration.set_size()];
+    ///
+    /// let generator = DominoesResponseGenerator::with_knowledge(0, &hand, &hidden, &probabilities);
+    /// assert_eq!(generator.generate(&state).len(), 1);
+    /// ```
+    pub fn with_knowledge(player_id: u8, hand: &Hand, hidden: &TileMask, opponent_tile_probabilities: &[f64]) -> Self {
+        Self {
+            knowledge: Some(Knowledge {
+                player_id,
+                hand: hand.clone(),
+                hidden: *hidden,
+                opponent_tile_probabilities: opponent_tile_probabilities.to_vec(),
+                tile_knowledge: None,
+            }),
+            rng: Mutex::new(GameRng::from_rng(&mut rand::rng())),
+        }
+    }
+
+    /// Returns the generator with what the searching player has deduced about the other players' hands
+    ///
+    /// The hands sampled for the other players only hold tiles that they might hold. This has no effect on a generator without
+    /// knowledge (see `with_knowledge`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesState, Hand};
+    /// # use player::{DominoesResponseGenerator, TileKnowledge};
+    /// # use rules::{Configuration, Tile, TileMask};
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut state = DominoesState::new(&configuration);
+    /// for _ in 0..14 {
+    ///     state.draw_tile();
+    /// }
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    /// state.whose_turn = 1;
+    /// let mut knowledge = TileKnowledge::new(2);
+    /// knowledge.observe(&Action::pass(1), &state);
+    ///
+    /// let hidden = TileMask::all(configuration.set_id());
+    /// let probabilities = vec![0.25; configuration.set_size()];
+    /// let generator =
+    ///     DominoesResponseGenerator::with_knowledge(0, &Hand::new(), &hidden, &probabilities).with_tile_knowledge(&knowledge);
+    /// let determinized = generator.determinize(&state);
+    /// assert!(determinized.hand_of(1).tiles().iter().all(|tile| tile.as_tuple().1 != 6));
+    /// ```
+    pub fn with_tile_knowledge(mut self, tile_knowledge: &TileKnowledge) -> Self {
+        if let Some(knowledge) = &mut self.knowledge {
+            knowledge.tile_knowledge = Some(tile_knowledge.clone());
+        }
+        self
+    }
+
+    /// Returns the generator with a different random number generator, e.g. a seeded one so that the samples can be reproduced
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesState, Hand};
+    /// # use player::DominoesResponseGenerator;
+    /// # use rules::{Configuration, RngStream, Tile, TileMask};
+    ///
+    /// let configuration = Configuration::default().with_seed(5);
+    /// let state = DominoesState::new(&configuration);
+    /// let hidden = TileMask::all(configuration.set_id());
+    /// let probabilities = vec![0.25; configuration.set_size()];
+    /// let sample = || {
+    ///     DominoesResponseGenerator::with_knowledge(0, &Hand::new(), &hidden, &probabilities)
+    ///         .with_rng(configuration.rng(RngStream::Player(0)))
+    ///         .determinize(&state)
+    /// };
+    /// assert_eq!(sample().hand_of(1).tiles(), sample().hand_of(1).tiles());
+    /// ```
+    pub fn with_rng(mut self, rng: GameRng) -> Self {
+        self.rng = Mutex::new(rng);
+        self
+    }
+
+    // Calls a function with the random number generator
+    pub(crate) fn use_rng<T>(&self, f: impl FnOnce(&mut GameRng) -> T) -> T {
+        f(&mut self.rng.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Returns a copy of the state in which every player holds a plausible hand
+    ///
+    /// The searching player holds its own hand, and the other players hold hands sampled from the tiles that it has not seen (see
+    /// the module documentation). The boneyard holds the rest of those tiles, shuffled. The number of tiles in the boneyard does
+    /// not change, unless too few tiles are left. From then on, the state keeps track of the hands, so a simulation of the rest
+    /// of the game ends when a player plays the last tile.
+    ///
+    /// If the state already keeps track of the hands, or the generator has no knowledge, the copy is unchanged.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesState, Hand};
+    /// # use player::DominoesResponseGenerator;
+    /// # use rules::{Configuration, Tile, TileMask};
+    ///
+    /// let configuration = Configuration::default();
+    /// let state = DominoesState::new(&configuration);
+    ///
+    /// let mut hand = Hand::new();
+    /// hand.add_tile(Tile::from((5, 6)));
+    /// let mut hidden = TileMask::all(configuration.set_id());
+    /// hidden.remove(Tile::from((5, 6)));
+    /// let probabilities = vec![0.25; configuration.set_size()];
+    ///
+    /// let generator = DominoesResponseGenerator::with_knowledge(0, &hand, &hidden, &probabilities);
+    /// let determinized = generator.determinize(&state);
+    /// assert_eq!(determinized.hand_of(0).tiles(), hand.tiles());
+    /// assert!(!determinized.hand_of(1).contains(&Tile::from((5, 6))));
+    /// ```
+    pub fn determinize(&self, state: &DominoesState) -> DominoesState {
+        self.use_rng(|rng| self.determinize_with(state, rng))
+    }
+
+    /// Returns a sampler of complete deals of the tiles that the searching player has not seen
+    ///
+    /// Each other player is dealt a hand of the expected size, holding only tiles that they might hold, and the boneyard is dealt
+    /// as many tiles as it holds now. If too few tiles are left, the boneyard is dealt enough first. This is how `determinize`
+    /// deals the hands, and a perfect-information search can use it to deal worlds of its own (see `WorldSampler`).
+    ///
+    /// # Returns
+    /// The sampler, or `None` if the state already keeps track of the hands or the generator has no knowledge
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{DominoesState, Hand};
+    /// # use player::DominoesResponseGenerator;
+    /// # use rules::{Configuration, GameRng, Tile, TileMask};
+    ///
+    /// let configuration = Configuration::default();
+    /// let state = DominoesState::new(&configuration);
+    /// let hidden = TileMask::all(configuration.set_id());
+    /// let probabilities = vec![0.25; configuration.set_size()];
+    /// let generator = DominoesResponseGenerator::with_knowledge(0, &Hand::new(), &hidden, &probabilities);
+    ///
+    /// let sampler = generator.world_sampler(&state).unwrap();
+    /// let world = sampler.sample(&mut rand::rng());
+    /// assert_eq!(world.hands[1].len(), 7);
+    /// assert_eq!(world.boneyard.len(), state.boneyard.remaining().len());
+    /// ```
+    pub fn world_sampler(&self, state: &DominoesState) -> Option<WorldSampler> {
+        let knowledge = self.knowledge.as_ref().filter(|_| !state.is_dealt())?;
+        let unseen = knowledge.unseen(state);
+        // The reserved tiles are in the boneyard too, though they cannot be drawn
+        let boneyard_count = state.boneyard.remaining().len().min(unseen.len());
+        let mut available = unseen.len() - boneyard_count;
+        let hand_size = knowledge.hand_size(&unseen);
+        let hand_sizes: Vec<usize> = (0..state.scores().len() as u8)
+            .map(|player_id| {
+                if player_id == knowledge.player_id {
+                    0
+                } else {
+                    let size = hand_size.min(available);
+                    available -= size;
+                    size
+                }
+            })
+            .collect();
+        Some(knowledge.world_sampler(&unseen, &hand_sizes, boneyard_count))
+    }
+
+    // Returns a copy of the state with every player's hand (see `determinize`)
+    fn determinize_with<R: Rng + ?Sized>(&self, state: &DominoesState, rng: &mut R) -> DominoesState {
+        let mut determinized = state.clone();
+        let (Some(knowledge), Some(sampler)) = (&self.knowledge, self.world_sampler(state)) else {
+            return determinized;
+        };
+
+        let mut world = sampler.sample(rng);
+        world.hands[knowledge.player_id as usize] = knowledge.remaining_hand(state);
+        determinized.boneyard = Boneyard::with(world.boneyard).with_reserve(state.boneyard.reserve());
+        determinized.set_hands(world.hands);
+        determinized
+    }
+
+    // Returns the hand of the player whose turn it is (see `DominoesResponseGenerator`)
+    fn hand_to_move<R: Rng + ?Sized>(&self, state: &DominoesState, rng: &mut R) -> Hand {
+        let player_id = state.whose_turn;
+        let in_state = state.hand_of(player_id);
+        let Some(knowledge) = self.knowledge.as_ref().filter(|_| in_state.is_empty()) else {
+            return in_state.clone();
+        };
+
+        if player_id == knowledge.player_id {
+            knowledge.remaining_hand(state)
+        } else {
+            // Only this player's hand is dealt
+            let unseen = knowledge.unseen(state);
+            let mut hand_sizes = vec![0; state.scores().len()];
+            hand_sizes[player_id as usize] = knowledge.hand_size(&unseen);
+            let mut world = knowledge.world_sampler(&unseen, &hand_sizes, 0).sample(rng);
+            world.hands.swap_remove(player_id as usize)
+        }
     }
 }
 
@@ -65,20 +324,83 @@ impl ResponseGenerator for DominoesResponseGenerator {
     type State = DominoesState;
 
     fn generate(&self, state: &DominoesState) -> Vec<Action> {
-        let _ = state; // Suppress unused parameter warning
-        // TODO: Unimplemented
-        vec![]
+        // The search calls this once for every node that it expands
+        metrics::increment(Counter::NodesExpanded);
+        let hand = self.use_rng(|rng| self.hand_to_move(state, rng));
+        state.legal_actions(&hand)
+    }
+}
+
+// What a player knows about the hidden tiles
+#[derive(Debug, Clone)]
+struct Knowledge {
+    player_id: u8,
+    hand: Hand,
+    hidden: TileMask,
+    opponent_tile_probabilities: Vec<f64>,
+    tile_knowledge: Option<TileKnowledge>,
+}
+
+impl Knowledge {
+    // Returns the player's hand without the tiles that have been played since
+    fn remaining_hand(&self, state: &DominoesState) -> Hand {
+        let played = |tile| state.layout.nodes.iter().any(|node| node.tile == tile);
+        let mut hand = Hand::new();
+        for &tile in self.hand.tiles().iter().filter(|&&tile| !played(tile)) {
+            hand.add_tile(tile);
+        }
+        hand
+    }
+
+    // Returns the tiles that the player has not seen, and have not been played since
+    fn unseen(&self, state: &DominoesState) -> Vec<Tile> {
+        let mut unseen = self.hidden;
+        for node in &state.layout.nodes {
+            unseen.remove(node.tile);
+        }
+        unseen.iter().collect()
+    }
+
+    // Returns the probability of another player holding a tile
+    fn probability(&self, tile: &Tile) -> f64 {
+        self.opponent_tile_probabilities[tile.ordinal as usize]
+    }
+
+    // Returns the expected size of another player's hand, which is the sum of the probabilities of the unseen tiles
+    fn hand_size(&self, unseen: &[Tile]) -> usize {
+        (unseen.iter().map(|tile| self.probability(tile)).sum::<f64>().round() as usize).min(unseen.len())
+    }
+
+    // Returns a sampler that deals the unseen tiles weighted by their probabilities, and only to players who might hold them
+    fn world_sampler(&self, unseen: &[Tile], hand_sizes: &[usize], boneyard_count: usize) -> WorldSampler {
+        let sampler = WorldSampler::new(unseen, hand_sizes, boneyard_count).with_weights(&self.opponent_tile_probabilities);
+        match &self.tile_knowledge {
+            Some(tile_knowledge) => sampler.with_tile_knowledge(tile_knowledge),
+            None => sampler,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use rules::Configuration;
+
+    // Returns a hand holding the tiles
+    fn hand(tiles: &[(u8, u8)]) -> Hand {
+        let mut hand = Hand::new();
+        for &tile in tiles {
+            hand.add_tile(Tile::from(tile));
+        }
+        hand
+    }
 
     #[test]
     fn test_new_creates_generator() {
         let generator = DominoesResponseGenerator::new();
-        // Since the struct is a unit struct, we just verify it can be created
+        // Since the generator has no state to check, we just verify it can be created
         // This test ensures the constructor works correctly
         let _ = generator;
     }
@@ -103,8 +425,145 @@ mod tests {
         let generator2 = DominoesResponseGenerator::new();
         let generator3 = DominoesResponseGenerator::default();
 
-        // Since this is a unit struct, all instances are effectively the same
+        // Since these generators have no knowledge, all instances are effectively the same
         // but we can create multiple instances without issues
         let _ = (generator1, generator2, generator3);
     }
+
+    #[test]
+    fn test_generate_uses_the_hands_in_the_state() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::with_rng(&configuration, &mut ChaCha8Rng::seed_from_u64(1));
+        state.deal();
+        let expected = state.legal_actions(state.hand_of(0));
+        assert!(!expected.is_empty());
+        assert_eq!(DominoesResponseGenerator::new().generate(&state), expected);
+    }
+
+    #[test]
+    fn test_generate_uses_own_hand() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        let own = hand(&[(6, 6), (5, 6), (1, 2)]);
+        let generator =
+            DominoesResponseGenerator::with_knowledge(0, &own, &TileMask::all(configuration.set_id()), &[0.0; 28]);
+        assert_eq!(generator.generate(&state), vec![Action::play_unchecked(0, Tile::from((6, 6)), None)]);
+
+        // Played tiles are no longer in the hand
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        assert_eq!(generator.generate(&state), vec![Action::play_unchecked(0, Tile::from((5, 6)), Some(6))]);
+    }
+
+    #[test]
+    fn test_generate_samples_opponent_hands() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+
+        // The opponent holds two of the three tiles that the player has not seen, and never the one that is known not to be held
+        let own = hand(&[(0, 0)]);
+        let hidden: TileMask = [(6, 6), (5, 6), (1, 2), (3, 4)].into_iter().map(Tile::from).collect();
+        let mut probabilities = vec![0.0; configuration.set_size()];
+        for tile in [(5, 6), (1, 2)] {
+            probabilities[Tile::from(tile).ordinal as usize] = 1.0;
+        }
+        let generator = DominoesResponseGenerator::with_knowledge(0, &own, &hidden, &probabilities);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..10 {
+            let sampled = generator.hand_to_move(&state, &mut rng);
+            assert_eq!(sampled.len(), 2);
+            assert!(sampled.contains(&Tile::from((5, 6))) && sampled.contains(&Tile::from((1, 2))));
+        }
+        assert_eq!(generator.generate(&state), vec![Action::play_unchecked(1, Tile::from((5, 6)), Some(6))]);
+    }
+
+    #[test]
+    fn test_determinize() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..14 {
+            state.draw_tile();
+        }
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+
+        // The player has seen its own hand and the double six
+        let own = hand(&[(6, 6), (0, 0), (1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]);
+        let mut hidden = TileMask::all(configuration.set_id());
+        for &tile in own.tiles() {
+            hidden.remove(tile);
+        }
+        let probabilities = vec![7.0 / 21.0; configuration.set_size()];
+        let generator = DominoesResponseGenerator::with_knowledge(0, &own, &hidden, &probabilities);
+
+        let determinized = generator.determinize_with(&state, &mut ChaCha8Rng::seed_from_u64(1));
+        assert!(determinized.is_dealt());
+        assert_eq!(determinized.tiles_remaining(0), 6);
+        assert_eq!(determinized.tiles_remaining(1), 7);
+        assert_eq!(determinized.boneyard.count(), 14);
+
+        // Every unseen tile is in the opponent's hand or in the boneyard
+        let mut tiles: Vec<Tile> = determinized.hand_of(1).tiles().to_vec();
+        tiles.extend(determinized.boneyard.remaining());
+        tiles.sort_by_key(|tile| tile.ordinal);
+        assert_eq!(tiles, hidden.iter().collect::<Vec<_>>());
+
+        // A state that keeps track of the hands is unchanged
+        let again = generator.determinize_with(&determinized, &mut ChaCha8Rng::seed_from_u64(2));
+        assert_eq!(again.hand_of(1).tiles(), determinized.hand_of(1).tiles());
+    }
+
+    #[test]
+    fn test_determinize_with_tile_knowledge() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..14 {
+            state.draw_tile();
+        }
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+
+        // The opponent passed on the double six, so every other six is in the boneyard
+        let mut knowledge = TileKnowledge::new(2);
+        knowledge.observe(&Action::pass(1), &state);
+        let own = hand(&[(6, 6), (0, 0), (1, 1), (2, 2), (3, 3), (4, 4), (5, 5)]);
+        let mut hidden = TileMask::all(configuration.set_id());
+        for &tile in own.tiles() {
+            hidden.remove(tile);
+        }
+        let probabilities = vec![7.0 / 21.0; configuration.set_size()];
+        let generator =
+            DominoesResponseGenerator::with_knowledge(0, &own, &hidden, &probabilities).with_tile_knowledge(&knowledge);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        for _ in 0..10 {
+            let determinized = generator.determinize_with(&state, &mut rng);
+            assert_eq!(determinized.tiles_remaining(1), 7);
+            assert!(determinized.hand_of(1).tiles().iter().all(|tile| knowledge.can_hold(1, *tile)));
+            for value in 0..6 {
+                assert!(determinized.boneyard.remaining().contains(&Tile::from((value, 6))));
+            }
+            assert!(generator.hand_to_move(&state, &mut rng).tiles().iter().all(|tile| tile.as_tuple().1 != 6));
+        }
+    }
+
+    #[test]
+    fn test_determinize_with_seeded_rng() {
+        let configuration = Configuration::default();
+        let state = DominoesState::new(&configuration);
+        let hidden = TileMask::all(configuration.set_id());
+        let probabilities = vec![0.25; configuration.set_size()];
+        let generator = DominoesResponseGenerator::with_knowledge(0, &Hand::new(), &hidden, &probabilities)
+            .with_rng(ChaCha8Rng::seed_from_u64(4));
+
+        // A clone continues from the same state, so it makes the same samples
+        let clone = generator.clone();
+        let samples = |generator: &DominoesResponseGenerator| -> Vec<Vec<Tile>> {
+            (0..5).map(|_| generator.determinize(&state).hand_of(1).tiles().to_vec()).collect()
+        };
+        let expected = samples(&generator);
+        assert_eq!(samples(&clone), expected);
+        assert!(expected.iter().any(|tiles| *tiles != expected[0]));
+    }
 }
@@ -4,42 +4,60 @@
 //! random games from a given state and evaluate the potential outcomes.
 
 use rand::Rng;
+use rand::seq::IndexedRandom;
 
-use hidden_game_player::{mcts::Rollout, State};
+use hidden_game_player::{mcts::{ResponseGenerator, Rollout}, State};
 use dominoes_state::{Action, DominoesState};
-use rules::Boneyard;
 use crate::DominoesResponseGenerator;
 
+/// How a rollout chooses each action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RolloutPolicy {
+    /// Any legal action, chosen uniformly at random
+    #[default]
+    Random,
+    /// The play that sheds the most pips. Rollouts are slower, but closer to how the players actually play.
+    Greedy,
+}
+
 /// A rollout strategy for the Dominoes game that implements the `Rollout` trait.
 ///
-/// This struct is responsible for simulating random game play from a given state to estimate the value of that state. It's a key
-/// component in the Monte Carlo Tree Search (MCTS) algorithm, used during the simulation phase to quickly evaluate leaf nodes.
+/// This struct is responsible for simulating play from a given state to the end of the game to estimate the value of that
+/// state. It's a key component in the Monte Carlo Tree Search (MCTS) algorithm, used during the simulation phase to quickly
+/// evaluate leaf nodes.
 ///
-/// The rollout strategy currently returns values between 0.0 and 1.0 to simulate game outcomes, where higher values indicate
-/// better positions for the current player.
+/// The hidden hands and the boneyard are first dealt by the response generator as a complete, consistent world (see
+/// `DominoesResponseGenerator::determinize` and `WorldSampler`), and then every player takes an action chosen by the policy (see
+/// `RolloutPolicy`), including drawing and passing, until the game is over. The result is 1.0 for a win, 0.0 for a loss, and 0.5
+/// for a draw.
 ///
 /// # Examples
 /// ```rust
-/// use player::DominoesRollout;
+/// use player::{DominoesResponseGenerator, DominoesRollout};
 /// use dominoes_state::DominoesState;
 /// use rules::Configuration;
 /// use hidden_game_player::mcts::Rollout;
 ///
 /// let rollout = DominoesRollout::new();
 /// let config = Configuration::default();
-/// let state = DominoesState::new(&config);
+/// let mut state = DominoesState::new(&config);
+/// state.deal();
 ///
-/// let outcome = rollout.play(&state);
-/// assert!(outcome >= 0.0 && outcome <= 1.0);
+/// let outcome = rollout.play(&state, &DominoesResponseGenerator::new());
+/// assert!((0.0..=1.0).contains(&outcome));
 /// ```
-pub struct DominoesRollout;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DominoesRollout {
+    // How each action is chosen
+    policy: RolloutPolicy,
+}
 
 impl DominoesRollout {
     /// Creates a new `DominoesRollout` instance.
     ///
-    /// This constructor creates a new rollout strategy for use with the MCTS algorithm.
-    /// Since the rollout strategy is stateless, this simply returns a new instance
-    /// of the struct.
+    /// This constructor creates a new rollout strategy for use with the MCTS algorithm. The actions are chosen at random (see
+    /// `RolloutPolicy::Random`).
     ///
     /// # Returns
     ///
@@ -53,29 +71,59 @@ impl DominoesRollout {
     /// let rollout = DominoesRollout::new();
     /// ```
     pub fn new() -> Self {
-        Self
+        Self::with_policy(RolloutPolicy::Random)
     }
-}
 
-impl Default for DominoesRollout {
-    /// Creates a default `DominoesRollout` instance.
-    ///
-    /// This implementation uses the `new()` method to create a default instance,
-    /// providing a convenient way to create the rollout strategy using Rust's `Default` trait.
-    /// This is particularly useful when the rollout strategy is used as part of larger
-    /// configuration structures that implement `Default`.
-    ///
-    /// # Returns
-    /// A new `DominoesRollout` instance.
+    /// Creates a rollout strategy that chooses the actions with the given policy
     ///
     /// # Examples
     /// ```rust
-    /// # use player::DominoesRollout;
+    /// # use player::{DominoesRollout, RolloutPolicy};
     ///
-    /// let rollout = DominoesRollout::default();
+    /// let rollout = DominoesRollout::with_policy(RolloutPolicy::Greedy);
+    /// assert_eq!(rollout.policy(), RolloutPolicy::Greedy);
     /// ```
-    fn default() -> Self {
-        Self::new()
+    pub fn with_policy(policy: RolloutPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Returns the policy that chooses the actions
+    pub fn policy(&self) -> RolloutPolicy {
+        self.policy
+    }
+
+    /// Simulates play from the given state until the game is over, and returns the result for each player
+    ///
+    /// The random choices are made with the response generator's random number generator, so a seeded generator plays the same
+    /// way every time (see `DominoesResponseGenerator::with_rng`).
+    ///
+    /// # Arguments
+    /// * `state` - The state from which to simulate the play
+    /// * `rg` - Samples the hidden hands and generates the legal actions
+    ///
+    /// # Returns
+    /// The result for each player, by player ID: 1.0 for the winner and their partner, 0.0 for the others, or 0.5 for everyone if
+    /// the game is a draw.
+    pub fn simulate(&self, state: &DominoesState, rg: &DominoesResponseGenerator) -> Vec<f32> {
+        let mut state = rg.determinize(state);
+        while !state.is_terminal() {
+            let actions = rg.generate(&state);
+            let Some(action) = rg.use_rng(|rng| self.choose(&actions, rng)) else {
+                break;
+            };
+            state = state.apply(action);
+        }
+        results(&state)
+    }
+
+    // Chooses one of the actions according to the policy, or returns None if there are none
+    fn choose<'a, R: Rng + ?Sized>(&self, actions: &'a [Action], rng: &mut R) -> Option<&'a Action> {
+        match self.policy {
+            RolloutPolicy::Random => actions.choose(rng),
+            RolloutPolicy::Greedy => {
+                actions.iter().max_by_key(|action| action.tile_played.map_or(0, |(tile, _)| tile.score()))
+            }
+        }
     }
 }
 
@@ -83,72 +131,32 @@ impl Rollout for DominoesRollout {
     type State = DominoesState;
     type ResponseGenerator = DominoesResponseGenerator;
 
-    /// Simulates play from the given game state using simple heuristics and returns an evaluation score.
+    /// Simulates play from the given game state and returns the result for the current player.
     ///
-    /// This method performs a random simulation of the game starting from the provided state.
-    /// The result is a floating-point score between -1.0 and 1.0, where higher values indicate better outcomes for the current
-    /// player.
+    /// This method simulates the rest of the game starting from the provided state (see `simulate`). The result is 1.0 if the
+    /// player whose turn it is wins, 0.0 if the player loses, and 0.5 for a draw.
     ///
     /// # Arguments
     /// * `state` - The current game state from which to simulate the play.
+    /// * `rg` - Samples the hidden hands and generates the legal actions.
     ///
     /// # Returns
-    /// A floating-point score between -1.0 and 1.0 representing the outcome of the simulated play.
+    /// A floating-point score between 0.0 and 1.0 representing the outcome of the simulated play.
     fn play(&self, state: &DominoesState, rg: &DominoesResponseGenerator) -> f32 {
-        let rollout_state = RolloutState::new(state);
-        play_randomly_until_terminal(&rollout_state, rg)
+        self.simulate(state, rg)[state.whose_turn() as usize]
     }
 }
 
-// A simplified state representation for rollouts
-#[derive(Clone)]
-struct RolloutState {
-    boneyard: Boneyard,
-    layout: Layout,
-}
-impl RolloutState {
-    fn new(state: &DominoesState) -> Self {
-        Self { state: state.clone() }
-    }
-
-    fn is_terminal(&self) -> bool {
-        self.state.is_game_over()
-    }
-
-    fn legal_actions(&self) -> Vec<Action> {
-        self.state.legal_actions()
-    }
-
-    fn apply_action(&self, action: &Action) -> Self {
-        let mut new_state = self.state.clone();
-        new_state.apply_action(action);
-        Self { state: new_state }
-    }
-
-    fn whose_turn(&self) -> u8 {
-        self.state.whose_turn()
-    }
-}
-
-// Simulates random play until a terminal state is reached and returns an evaluation score
-fn play_randomly_until_terminal(state: &RolloutState, _rg: &DominoesResponseGenerator) -> f32 {
-    let mut current_state = state.clone();
-    let mut rng = rand::rng();
-
-    while !current_state.is_terminal() {
-        let legal_actions = current_state.legal_actions();
-        if legal_actions.is_empty() {
-            // No legal actions, pass the turn
-            current_state = current_state.apply_action(&Action::pass(current_state.whose_turn()));
-        } else {
-            // Randomly select a legal action
-            let action = legal_actions[rng.gen_range(0..legal_actions.len())].clone();
-            current_state = current_state.apply_action(&action);
+// Returns the result of a finished game for each player (see `DominoesRollout::simulate`)
+fn results(state: &DominoesState) -> Vec<f32> {
+    let num_players = state.scores().len();
+    match state.winner {
+        Some(winner) => {
+            let team = state.team(winner);
+            (0..num_players as u8).map(|player_id| if state.team(player_id) == team { 1.0 } else { 0.0 }).collect()
         }
+        None => vec![0.5; num_players],
     }
-
-    // Evaluate the terminal state
-    evaluate_terminal_state(&current_state)
 }
 
 // Heuristic functions
@@ -247,21 +255,23 @@ fn _pip_sum_steering_heuristic(state: &DominoesState) -> (Action, f32) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rules::Configuration;
+    use dominoes_state::Hand;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use rules::{Configuration, Tile};
 
     #[test]
     fn test_dominoes_rollout_new() {
         let rollout = DominoesRollout::new();
-        // Test that the struct is created successfully
-        // Since it's a unit struct, just verify it exists
-        let _ = rollout;
+        // Test that the struct is created successfully with the default policy
+        assert_eq!(rollout.policy(), RolloutPolicy::Random);
     }
 
     #[test]
     fn test_dominoes_rollout_default() {
         let rollout = DominoesRollout::default();
         // Test that default implementation works
-        let _ = rollout;
+        assert_eq!(rollout.policy(), RolloutPolicy::Random);
     }
 
     #[test]
@@ -271,8 +281,8 @@ mod tests {
         let state = DominoesState::new(&configuration);
 
         // Test that play method returns a value in expected range
-        let result = rollout.play(&state);
-        assert!(result >= 0.0 && result <= 1.0, "Rollout result should be between 0.0 and 1.0");
+        let result = rollout.play(&state, &DominoesResponseGenerator::new());
+        assert!((0.0..=1.0).contains(&result), "Rollout result should be between 0.0 and 1.0");
     }
 
     #[test]
@@ -287,7 +297,76 @@ mod tests {
         let result1 = rollout.play(&state1, &response_generator);
         let result2 = rollout.play(&state2, &response_generator);
 
-        assert!(result1 >= 0.0 && result1 <= 1.0);
-        assert!(result2 >= 0.0 && result2 <= 1.0);
+        assert!((0.0..=1.0).contains(&result1));
+        assert!((0.0..=1.0).contains(&result2));
+    }
+
+    #[test]
+    fn test_simulate_plays_to_the_end() {
+        let configuration = Configuration::default();
+        for policy in [RolloutPolicy::Random, RolloutPolicy::Greedy] {
+            let rollout = DominoesRollout::with_policy(policy);
+            for seed in 0..10 {
+                let mut state = DominoesState::with_rng(&configuration, &mut ChaCha8Rng::seed_from_u64(seed));
+                state.deal();
+                let results = rollout.simulate(&state, &DominoesResponseGenerator::new());
+                assert_eq!(results.len(), 2);
+                assert_eq!(results.iter().sum::<f32>(), 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulate_with_seed_is_repeatable() {
+        let configuration = Configuration::default().with_seed(8);
+        let rollout = DominoesRollout::new();
+        let results = || -> Vec<Vec<f32>> {
+            let generator = DominoesResponseGenerator::new().with_rng(configuration.rng(rules::RngStream::Player(0)));
+            (0..20)
+                .map(|seed| {
+                    let mut state = DominoesState::with_rng(&configuration, &mut ChaCha8Rng::seed_from_u64(seed));
+                    state.deal();
+                    rollout.simulate(&state, &generator)
+                })
+                .collect()
+        };
+        assert_eq!(results(), results());
+    }
+
+    #[test]
+    fn test_simulate_finished_game() {
+        let mut state = DominoesState::new(&Configuration::default());
+        state.mark_game_over(Some(1));
+        let rollout = DominoesRollout::new();
+        assert_eq!(rollout.simulate(&state, &DominoesResponseGenerator::new()), vec![0.0, 1.0]);
+        assert_eq!(rollout.play(&state, &DominoesResponseGenerator::new()), 0.0);
+
+        // A player wins along with their partner
+        let configuration = Configuration::new(4, rules::Variation::Traditional, 6, 7).with_partners().unwrap();
+        let mut state = DominoesState::new(&configuration);
+        state.mark_game_over(Some(3));
+        assert_eq!(rollout.simulate(&state, &DominoesResponseGenerator::new()), vec![0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_simulate_with_knowledge() {
+        // The player holds the last tile that can be played, and wins by playing it
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((5, 6)));
+        let hidden = rules::TileMask::default();
+        let generator = DominoesResponseGenerator::with_knowledge(0, &hand, &hidden, &[0.0; 28]);
+        assert_eq!(DominoesRollout::new().play(&state, &generator), 1.0);
+    }
+
+    #[test]
+    fn test_greedy_policy_chooses_heaviest_play() {
+        let actions = [(1, 2), (5, 6), (3, 3)].map(|tile| Action::play_unchecked(0, Tile::from(tile), None));
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let chosen = DominoesRollout::with_policy(RolloutPolicy::Greedy).choose(&actions, &mut rng);
+        assert_eq!(chosen, Some(&actions[1]));
+        assert_eq!(DominoesRollout::new().choose(&[], &mut rng), None);
     }
 }
@@ -68,20 +68,51 @@ impl DominoesEvaluator
         // TODO: Unimplemented
         0.0
     }
-    fn pip_advantage(&self, _state: &DominoesState) -> f32
+
+    // Returns the pips held by Alice's opponents less the pips held by her team, as a fraction of all the pips in the hands. The
+    // team with fewer pips loses less when the game is blocked. The value is 0 until the hands have been dealt.
+    fn pip_advantage(&self, state: &DominoesState) -> f32
     {
-        // TODO: Unimplemented
-        0.0
+        if !state.is_dealt()
+        {
+            return 0.0;
+        }
+        let num_players = state.scores().len();
+        let alice_team = state.team(0);
+        let (balance, total) = (0..num_players as u8).fold((0i64, 0i64), |(balance, total), player_id| {
+            let pips = i64::from(state.hand_of(player_id).total_pips());
+            let sign = if state.team(player_id) == alice_team { -1 } else { 1 };
+            (balance + sign * pips, total + pips)
+        });
+        if total == 0
+        {
+            return 0.0;
+        }
+        balance as f32 / total as f32
     }
+
     fn scoring_potential(&self, _state: &DominoesState) -> f32
     {
         // TODO: Unimplemented
         0.0
     }
-    fn blocking_potential(&self, _state: &DominoesState) -> f32
+
+    // Returns the number of blocked opponents less the number of blocked members of Alice's team, as a fraction of the number of
+    // players. A player is blocked if nothing in their hand can be played, so blocking a partner counts against Alice as much as
+    // blocking an opponent counts for her. The value is 0 until the hands have been dealt and the first tile has been played.
+    fn blocking_potential(&self, state: &DominoesState) -> f32
     {
-        // TODO: Unimplemented
-        0.0
+        if !state.is_dealt() || state.layout.is_empty()
+        {
+            return 0.0;
+        }
+        let num_players = state.scores().len();
+        let alice_team = state.team(0);
+        let balance: i32 = (0..num_players as u8)
+            .filter(|&player_id| !state.hand_of(player_id).tiles().iter().any(|tile| state.can_play_tile(tile, None)))
+            .map(|player_id| if state.team(player_id) == alice_team { -1 } else { 1 })
+            .sum();
+        balance as f32 / num_players as f32
     }
 }
 
@@ -130,7 +161,8 @@ impl StaticEvaluator<DominoesState> for DominoesEvaluator
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rules::Configuration;
+    use dominoes_state::Hand;
+    use rules::{Configuration, Tile, Variation};
 
     #[test]
     fn test_new_creates_evaluator() {
@@ -145,10 +177,67 @@ mod tests {
         let config = Configuration::default();
         let state = DominoesState::new(&config);
         let value = evaluator.evaluate(&state);
-        // Since all heuristics are unimplemented, value should be 0.0
+        // Nothing has been dealt or played yet, so the value should be 0.0
         assert_eq!(value, 0.0);
     }
 
+    #[test]
+    fn test_blocking_potential() {
+        let evaluator = DominoesEvaluator::new();
+        let config = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+        let mut state = DominoesState::new(&config);
+        state.layout.attach_unchecked(Tile::from((6, 6)), None);
+        let hand = |tiles: &[(u8, u8)]| {
+            let mut hand = Hand::new();
+            tiles.iter().for_each(|&tile| hand.add_tile(Tile::from(tile)));
+            hand
+        };
+
+        // Bob is blocked, which is good for Alice and Carol
+        state.set_hands(vec![hand(&[(1, 6)]), hand(&[(0, 1)]), hand(&[(2, 6)]), hand(&[(3, 6)])]);
+        assert_eq!(evaluator.blocking_potential(&state), 0.25);
+
+        // Carol is blocked too, and she is Alice's partner
+        state.set_hands(vec![hand(&[(1, 6)]), hand(&[(0, 1)]), hand(&[(0, 2)]), hand(&[(3, 6)])]);
+        assert_eq!(evaluator.blocking_potential(&state), 0.0);
+
+        // Without partners, every blocked player is an opponent
+        let config = Configuration::new(4, Variation::Traditional, 6, 7);
+        let mut state = DominoesState::new(&config);
+        state.layout.attach_unchecked(Tile::from((6, 6)), None);
+        state.set_hands(vec![hand(&[(1, 6)]), hand(&[(0, 1)]), hand(&[(0, 2)]), hand(&[(3, 6)])]);
+        assert_eq!(evaluator.blocking_potential(&state), 0.5);
+    }
+
+    #[test]
+    fn test_pip_advantage() {
+        let evaluator = DominoesEvaluator::new();
+        let config = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+        let mut state = DominoesState::new(&config);
+        let hand = |tiles: &[(u8, u8)]| {
+            let mut hand = Hand::new();
+            tiles.iter().for_each(|&tile| hand.add_tile(Tile::from(tile)));
+            hand
+        };
+
+        // Before the deal, there is no advantage
+        assert_eq!(evaluator.pip_advantage(&state), 0.0);
+
+        // Alice and Carol hold 2 + 4 pips, and Bob and Dave hold 6 + 8 pips
+        state.set_hands(vec![hand(&[(1, 1)]), hand(&[(2, 4)]), hand(&[(0, 4)]), hand(&[(4, 4)])]);
+        assert_eq!(evaluator.pip_advantage(&state), 0.4);
+
+        // Without partners, Carol is an opponent
+        let config = Configuration::new(4, Variation::Traditional, 6, 7);
+        let mut state = DominoesState::new(&config);
+        state.set_hands(vec![hand(&[(1, 1)]), hand(&[(2, 4)]), hand(&[(0, 4)]), hand(&[(4, 4)])]);
+        assert_eq!(evaluator.pip_advantage(&state), 0.8);
+
+        // Empty hands have no pips
+        state.set_hands(vec![Hand::new(), Hand::new(), Hand::new(), Hand::new()]);
+        assert_eq!(evaluator.pip_advantage(&state), 0.0);
+    }
+
     #[test]
     fn test_alice_wins_value() {
         let evaluator = DominoesEvaluator::new();
@@ -0,0 +1,50 @@
+//! Module defining the FrontEnd trait
+//!
+//! A player that is driven by a person through a user interface may ask for things other than a move, such as taking back a
+//! turn. These requests are not decisions about the game, so they are made through this trait rather than the `Player` trait.
+
+/// The requests that a person may make through a user interface instead of taking their turn
+///
+/// The game reaches a player's front end through `Player::front_end`, and checks it after each call to `my_turn`. If a request
+/// was made, the action returned by `my_turn` is ignored. Each request is cleared once it has been reported. The defaults make no
+/// requests.
+///
+/// # Examples
+/// ```rust
+/// # use player::{FrontEnd, HumanPlayer, Player};
+/// # use rules::Configuration;
+///
+/// let config = Configuration::default();
+/// let mut player = HumanPlayer::new(0, &config, "Alice");
+/// let front_end = player.front_end().unwrap();
+/// assert!(!front_end.requested_undo());
+/// ```
+pub trait FrontEnd {
+    /// Returns true if the person asked to undo instead of taking their turn
+    ///
+    /// If it returns true, the last turn is undone instead. The default is `false`.
+    fn requested_undo(&mut self) -> bool {
+        false
+    }
+
+    /// Returns true if the person asked to redo the turns that were undone instead of taking their turn
+    ///
+    /// The default is `false`.
+    fn requested_redo(&mut self) -> bool {
+        false
+    }
+
+    /// Returns true if the person asked to leave the game instead of taking their turn
+    ///
+    /// The game is not over, and the person is asked again if the game continues. The default is `false`.
+    fn requested_quit(&mut self) -> bool {
+        false
+    }
+
+    /// Returns the layout that the person asked to practice instead of taking their turn, if any
+    ///
+    /// The layout is written as text for the front end, which sets up the position. The default is `None`.
+    fn requested_setup(&mut self) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,146 @@
+//! Greedy player implementation
+//!
+//! This module provides a computer player that does not search. It always makes the play ranked best by `DominoesPlayer::analyze`,
+//! which makes it fast and predictable, e.g. as a sparring partner for beginners or a baseline for measuring the AI.
+
+use dominoes_state::{Action, DominoesState};
+use crate::{DominoesPlayer, Hand, Player};
+use rules::Configuration;
+
+/// A computer player that always makes the play that looks best right now
+///
+/// # Examples
+/// ```rust
+/// # use player::{GreedyPlayer, Player};
+/// # use rules::Configuration;
+///
+/// let config = Configuration::default();
+/// let player = GreedyPlayer::new(1, &config, "Bob");
+/// assert!(!player.is_human());
+/// ```
+#[derive(Debug, Clone)]
+pub struct GreedyPlayer {
+    /// Unique identifier for this player in the game
+    player_id: u8,
+    /// The tiles currently held by this player
+    hand: Hand,
+    /// Display name for this player
+    name: String,
+}
+
+impl GreedyPlayer {
+    /// Creates a new greedy player
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `_configuration` - Game rules and settings, which this player does not need
+    /// * `name` - Display name for this player
+    pub fn new(player_id: u8, _configuration: &Configuration, name: &str) -> Self {
+        Self {
+            player_id,
+            hand: Hand::new(),
+            name: name.to_string(),
+        }
+    }
+}
+
+impl Player for GreedyPlayer {
+    fn reset(&mut self) {
+        self.hand = Hand::new();
+    }
+
+    fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+        self.hand = hand;
+    }
+
+    fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+        let mut new_state = state.clone();
+
+        // Without a playable tile, the player must draw, or pass if they cannot (see `DominoesState::can_draw`)
+        let Some(best) = DominoesPlayer::analyze(self.player_id, &self.hand, state).into_iter().next() else {
+            let tile = if new_state.can_draw() { new_state.draw_tile() } else { None };
+            return match tile {
+                Some(tile) => {
+                    self.hand.add_tile(tile);
+                    (Action::draw(self.player_id, tile), new_state)
+                }
+                None => {
+                    new_state.pass();
+                    (Action::pass(self.player_id), new_state)
+                }
+            };
+        };
+
+        let (tile, end) = best.action.tile_played.expect("A suggestion is a play");
+        self.hand.remove_tile(&tile);
+        new_state.play_tile_unchecked(tile, end);
+        (best.action, new_state)
+    }
+
+    fn has_playable_tile(&self, state: &DominoesState) -> bool {
+        self.hand
+            .tiles()
+            .iter()
+            .any(|tile| state.can_play_tile(tile, None))
+    }
+
+    fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> u8 {
+        self.player_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::Tile;
+
+    #[test]
+    fn test_greedy_player_plays_the_best_tile() {
+        let config = Configuration::default();
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((5, 6)));
+        hand.add_tile(Tile::from((5, 5)));
+        let mut player = GreedyPlayer::new(0, &config, "Greta");
+        player.set_up_from(hand, &state);
+
+        let (action, new_state) = player.my_turn(&state);
+        assert_eq!(action.tile_played, Some((Tile::from((5, 6)), Some(6))));
+        assert_eq!(new_state.layout.nodes.len(), 2);
+        assert_eq!(player.hand().len(), 1);
+    }
+
+    #[test]
+    fn test_greedy_player_draws_without_a_playable_tile() {
+        let config = Configuration::default();
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((0, 0)));
+        let mut player = GreedyPlayer::new(0, &config, "Greta");
+        player.set_up_from(hand, &state);
+
+        let (action, _) = player.my_turn(&state);
+        assert!(action.is_draw());
+        assert_eq!(player.hand().len(), 2);
+
+        // The draw is taken back
+        player.undo(&action);
+        assert_eq!(player.hand().len(), 1);
+    }
+}
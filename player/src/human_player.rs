@@ -2,16 +2,26 @@
 //!
 //! This module provides a concrete player implementation for human-controlled gameplay.
 
-use std::io::{self, Write};
-
 use dominoes_state::{Action, DominoesState};
-use crate::{Hand, Player};
+use crate::{Command, ConsoleTerminal, DominoesPlayer, FrontEnd, Hand, Player, Terminal};
 use rules::{Configuration, Tile};
 
+// Number of plays suggested by a hint
+const HINT_COUNT: usize = 3;
+
+// What the player chose to do on their turn
+enum Choice {
+    Play(Tile, Option<u8>),
+    Undo,
+    Redo,
+    Quit,
+    Setup(String),
+}
+
 /// A concrete implementation of Player for human players
 ///
-/// This player implementation handles human interaction through console input/output, allowing users to play dominoes by selecting
-/// tiles and placement positions through keyboard input.
+/// This player implementation handles human interaction through a terminal, allowing users to play dominoes by selecting tiles
+/// and placement positions through keyboard input. The terminal is the console unless another is given (see `with_terminal`).
 ///
 /// # Examples
 /// ```rust
@@ -23,18 +33,28 @@ use rules::{Configuration, Tile};
 /// assert_eq!(player.name(), "Alice");
 /// ```
 #[derive(Debug)]
-pub struct HumanPlayer<'a> {
+pub struct HumanPlayer {
     /// Unique identifier for this player in the game (0 or 1 for two-player games)
     player_id: u8,
-    /// Reference to the game configuration
-    configuration: &'a Configuration,
+    /// The game configuration
+    configuration: Configuration,
     /// The tiles currently held by this player
     hand: Hand,
     /// Display name for this player
     name: String,
+    /// True if the player asked to undo the last turn
+    undo_requested: bool,
+    /// True if the player asked to redo the turns taken back by the last undo
+    redo_requested: bool,
+    /// True if the player asked to quit the game
+    quit_requested: bool,
+    /// The layout that the player asked to practice, if any
+    setup_requested: Option<String>,
+    /// Where the player is shown the game and asked for their choices
+    terminal: Box<dyn Terminal>,
 }
 
-impl<'a> HumanPlayer<'a> {
+impl HumanPlayer {
     /// Creates a new human player with the given configuration
     ///
     /// Initializes a new human player with an empty hand and the specified name.
@@ -63,78 +83,136 @@ impl<'a> HumanPlayer<'a> {
     /// assert_eq!(alice.name(), "Alice");
     /// assert_eq!(bob.name(), "Bob");
     /// ```
-    pub fn new(player_id: u8, configuration: &'a Configuration, name: &str) -> Self {
+    pub fn new(player_id: u8, configuration: &Configuration, name: &str) -> Self {
+        Self::with_terminal(player_id, configuration, name, Box::new(ConsoleTerminal))
+    }
+
+    /// Creates a new human player who plays through the given terminal
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `configuration` - Game rules and settings
+    /// * `name` - Display name for this player
+    /// * `terminal` - Where the player is shown the game and asked for their choices
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::{HumanPlayer, Player, ScriptedTerminal};
+    /// # use dominoes_state::DominoesState;
+    /// # use rules::Configuration;
+    ///
+    /// let config = Configuration::default().with_muggins(true);
+    /// let terminal = ScriptedTerminal::new(["10"]);
+    /// let mut alice = HumanPlayer::with_terminal(0, &config, "Alice", Box::new(terminal.clone()));
+    ///
+    /// // Alice claims 10 points under the muggins rule
+    /// assert_eq!(alice.claim_points(&DominoesState::new(&config)), 10);
+    /// assert_eq!(terminal.remaining(), 0);
+    /// ```
+    pub fn with_terminal(player_id: u8, configuration: &Configuration, name: &str, terminal: Box<dyn Terminal>) -> Self {
         Self {
             player_id,
-            configuration,
+            configuration: configuration.clone(),
             hand: Hand::new(),
             name: name.to_string(),
+            undo_requested: false,
+            redo_requested: false,
+            quit_requested: false,
+            setup_requested: None,
+            terminal,
         }
     }
 
-    // Get the player's choice of tile to play after displaying their hand
-    fn get_player_input(&self, state: &DominoesState) -> (Tile, Option<u8>) {
+    /// Returns the game configuration
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    // Asks the player for commands until they choose a tile to play or ask to undo, redo, quit, or set up a position. The other
+    // commands are carried out, and commands that cannot be carried out are explained.
+    fn get_player_input(&mut self, state: &DominoesState) -> Choice {
         loop {
-            // Get tile selection
-            print!("Choose a tile (enter index 0-{}): ", self.hand.len() - 1);
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            io::stdin()
-                .read_line(&mut input)
-                .expect("Failed to read input");
-
-            let tile_index: usize = match input.trim().parse() {
-                Ok(index) if index < self.hand.tiles().len() => index,
-                _ => {
-                    println!("Invalid tile index. Please try again.");
+            let command = match self.terminal.ask("Your move (e.g. \"play 3|4 on 6\", or \"hint\"): ").parse::<Command>() {
+                Ok(command) => command,
+                Err(e) => {
+                    self.terminal.show(&format!("{e}. {}", Command::HELP));
                     continue;
                 }
             };
-
-            let selected_tile = self.hand.get_tile(tile_index).unwrap();
-
-            if state.layout.is_empty() {
-                return (*selected_tile, None);
+            match command {
+                Command::Play { tile, end } => match self.check_play(state, tile, end) {
+                    Ok(()) => return Choice::Play(tile, end),
+                    Err(e) => self.terminal.show(&e),
+                },
+                // The player is only asked for a command if they have a tile that can be played
+                Command::Draw | Command::Pass => self.terminal.show("You have a tile that can be played, so you must play it."),
+                Command::Hand => self.display_hand(),
+                Command::Board => self.display_board(state),
+                Command::Hint => self.display_hint(state),
+                Command::Undo if self.confirm("Undo your last turn?") => return Choice::Undo,
+                Command::Redo => return Choice::Redo,
+                Command::Quit if self.confirm("Quit the game?") => return Choice::Quit,
+                Command::Setup { layout } => return Choice::Setup(layout),
+                Command::Undo | Command::Quit => {}
             }
+        }
+    }
 
-            // Get end selection
-            print!("Choose an end (0-{}): ", self.configuration.set_id());
-            io::stdout().flush().unwrap();
+    // Returns an explanation if the player cannot play the tile on the end
+    fn check_play(&self, state: &DominoesState, tile: Tile, end: Option<u8>) -> Result<(), String> {
+        if !self.hand.contains(&tile) {
+            return Err(format!("You do not have {tile}."));
+        }
+        match end {
+            None if !state.layout.is_empty() => {
+                let (a, b) = tile.as_tuple();
+                let example = if state.can_play_tile(&tile, Some(b)) { b } else { a };
+                return Err(format!("Choose an end to play it on, e.g. play {tile} on {example}."));
+            }
+            Some(_) if state.layout.is_empty() => {
+                return Err(format!("The first tile is not played on an end. Enter play {tile}."));
+            }
+            _ => {}
+        }
+        if !state.legal_actions(&self.hand).iter().any(|action| action.tile_played == Some((tile, end))) {
+            return Err(match end {
+                Some(end) => format!("{tile} cannot be played on {end}."),
+                None => format!("{tile} cannot be played first."),
+            });
+        }
+        Ok(())
+    }
 
-            let mut end_input = String::new();
-            io::stdin()
-                .read_line(&mut end_input)
-                .expect("Failed to read input");
+    // Display the best plays suggested by the computer
+    fn display_hint(&mut self, state: &DominoesState) {
+        for suggestion in DominoesPlayer::analyze(self.player_id, &self.hand, state).iter().take(HINT_COUNT) {
+            let Some((tile, end)) = suggestion.action.tile_played else { continue };
+            let command = Command::Play { tile, end };
+            self.terminal.show(&format!("Hint: {command} ({})", suggestion.rationale));
+        }
+    }
 
-            let end: u8 = match end_input.trim().parse() {
-                Ok(end_val) if end_val <= self.configuration.set_id() => end_val,
-                _ => {
-                    println!("Invalid end value. Please try again.");
-                    continue;
-                }
-            };
+    // Asks a yes or no question, and returns true if the answer is yes
+    fn confirm(&mut self, question: &str) -> bool {
+        let input = self.terminal.ask(&format!("{question} (y/n): "));
+        matches!(input.to_lowercase().as_str(), "y" | "yes")
+    }
 
-            return (*selected_tile, Some(end));
-        }
+    // Display the player's hand, sorted so that the tiles are easier to find, and its pip total
+    fn display_hand(&mut self) {
+        self.hand.sort();
+        let hand = self.hand.tiles().iter().map(Tile::to_string).collect::<Vec<_>>().join("   ");
+        self.terminal.show(&format!("Your hand:  {hand}   ({} pips)", self.hand.total_pips()));
     }
 
-    // Display the player's hand
-    fn display_hand(&self) {
-        println!(
-            "Your hand:  {}",
-            self.hand
-                .tiles()
-                .iter()
-                .enumerate()
-                .map(|(i, tile)| format!("{i}: {tile}"))
-                .collect::<Vec<_>>()
-                .join("   ")
-        );
+    // Display the layout and its open ends
+    fn display_board(&mut self, state: &DominoesState) {
+        self.terminal.show(&format!("Current Layout:\n\n{}\n", state.layout.render_ascii()));
+        self.display_open_ends(state);
     }
 
     // Display the open ends available for tile placement
-    fn display_open_ends(&self, state: &DominoesState) {
+    fn display_open_ends(&mut self, state: &DominoesState) {
         // Create a vector containing indexes of open ends whose count is greater than 0
         let open_ends: Vec<u8> = state
             .layout
@@ -143,42 +221,36 @@ impl<'a> HumanPlayer<'a> {
             .enumerate()
             .filter_map(|(end, &count)| if count > 0 { Some(end as u8) } else { None })
             .collect();
-        println!(
-            "Open ends: {}",
-            open_ends
-                .iter()
-                .map(|&e| {
-                    let count = state.layout.open_count(e);
-                    if count > 1 {
-                        format!("{e}x{count}")
-                    } else {
-                        format!("{e}")
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(", ")
-        );
-    }
-
-    // Draw the starting hand size number of tiles from the boneyard
-    fn draw_starting_hand(&mut self, state: &mut DominoesState) {
-        // Draw the starting hand size number of tiles from the boneyard
-        let hand_size = self.configuration.starting_hand_size();
-        for _ in 0..hand_size {
-            let tile = state.draw_tile().expect("Failed to draw tile during setup");
-            self.hand.add_tile(tile);
+        let open_ends = open_ends
+            .iter()
+            .map(|&e| {
+                let count = state.layout.open_count(e);
+                if count > 1 {
+                    format!("{e}x{count}")
+                } else {
+                    format!("{e}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.terminal.show(&format!("Open ends: {open_ends}"));
+        if let Some(trains) = state.trains() {
+            self.terminal.show(&format!("Trains: {}", trains.summary(&state.layout)));
         }
     }
 }
 
-impl<'a> Player for HumanPlayer<'a> {
+impl Player for HumanPlayer {
     fn reset(&mut self) {
         self.hand = Hand::new();
+        self.undo_requested = false;
+        self.redo_requested = false;
+        self.quit_requested = false;
+        self.setup_requested = None;
     }
 
-    fn set_up(&mut self, state: &mut DominoesState) {
-        // Draw the starting hand size number of tiles from the boneyard
-        self.draw_starting_hand(state);
+    fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+        self.hand = hand;
     }
 
     fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
@@ -186,33 +258,47 @@ impl<'a> Player for HumanPlayer<'a> {
 
         // If the player has no playable tiles, they must draw
         if !self.has_playable_tile(&new_state) {
-            // Draw a tile from the boneyard, but if the boneyard is empty, the player must pass
-            if let Some(tile) = new_state.draw_tile() {
-                println!("You drew a tile: {tile}");
+            // Draw a tile from the boneyard, but if the player may not draw (see `DominoesState::can_draw`), the player must pass
+            if new_state.can_draw()
+                && let Some(tile) = new_state.draw_tile()
+            {
+                self.terminal.show(&format!("You drew a tile: {tile}"));
                 self.hand.add_tile(tile);
                 return (Action::draw(self.player_id, tile), new_state);
             } else {
-                println!("No playable tiles and boneyard is empty. Passing turn.");
+                self.terminal.show("No playable tiles and no tile to draw. Passing turn.");
                 new_state.pass();
                 return (Action::pass(self.player_id), new_state);
             }
         }
 
-        // Display the current layout
-        println!("Current Layout:\n\n{}\n", new_state.layout);
-        self.display_open_ends(&new_state);
-
-        // Get the player's choice from the console input
+        self.display_board(state);
         self.display_hand();
-        let (mut tile, mut end) = self.get_player_input(state);
-        while !state.can_play_tile(&tile, end) {
-            println!("Please choose a playable tile and open end.");
-            (tile, end) = self.get_player_input(state);
-        }
+
+        // Get the player's choice. If the player asks for anything but a play, the game ignores the returned action.
+        let (tile, end) = match self.get_player_input(state) {
+            Choice::Play(tile, end) => (tile, end),
+            Choice::Undo => {
+                self.undo_requested = true;
+                return (Action::pass(self.player_id), new_state);
+            }
+            Choice::Redo => {
+                self.redo_requested = true;
+                return (Action::pass(self.player_id), new_state);
+            }
+            Choice::Quit => {
+                self.quit_requested = true;
+                return (Action::pass(self.player_id), new_state);
+            }
+            Choice::Setup(layout) => {
+                self.setup_requested = Some(layout);
+                return (Action::pass(self.player_id), new_state);
+            }
+        };
         self.hand.remove_tile(&tile);
-        new_state.play_tile(tile, end);
+        new_state.play_tile_unchecked(tile, end);
 
-        (Action::play(self.player_id, tile, end), new_state)
+        (Action::play_unchecked(self.player_id, tile, end), new_state)
     }
 
     fn has_playable_tile(&self, state: &DominoesState) -> bool {
@@ -222,10 +308,37 @@ impl<'a> Player for HumanPlayer<'a> {
             .any(|tile| state.can_play_tile(tile, None))
     }
 
+    fn is_human(&self) -> bool {
+        true
+    }
+
+    fn front_end(&mut self) -> Option<&mut dyn FrontEnd> {
+        Some(self)
+    }
+
+    fn claim_points(&mut self, _state: &DominoesState) -> u32 {
+        // Under the muggins rule, the player must count the points themselves. A mistyped number is asked for again rather than
+        // taken as no points.
+        loop {
+            let input = self.terminal.ask("Points scored (press Enter for none): ");
+            if input.is_empty() {
+                return 0;
+            }
+            match input.parse() {
+                Ok(points) => return points,
+                Err(_) => self.terminal.show("Enter the number of points, e.g. 10, or press Enter for none."),
+            }
+        }
+    }
+
     fn hand(&self) -> &Hand {
         &self.hand
     }
 
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -235,9 +348,28 @@ impl<'a> Player for HumanPlayer<'a> {
     }
 }
 
+impl FrontEnd for HumanPlayer {
+    fn requested_undo(&mut self) -> bool {
+        std::mem::take(&mut self.undo_requested)
+    }
+
+    fn requested_redo(&mut self) -> bool {
+        std::mem::take(&mut self.redo_requested)
+    }
+
+    fn requested_quit(&mut self) -> bool {
+        std::mem::take(&mut self.quit_requested)
+    }
+
+    fn requested_setup(&mut self) -> Option<String> {
+        self.setup_requested.take()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ScriptedTerminal;
 
     #[test]
     fn test_human_player_creation() {
@@ -267,7 +399,7 @@ mod tests {
         let configuration = Configuration::default();
         let player = HumanPlayer::new(0, &configuration, "Test Player");
 
-        // Test that HumanPlayer stores the configuration reference correctly
+        // Test that HumanPlayer stores the configuration correctly
         // This verifies our constructor works and configuration is accessible
         assert_eq!(player.configuration.num_players(), configuration.num_players());
         assert_eq!(player.configuration.variation(), configuration.variation());
@@ -331,7 +463,7 @@ mod tests {
         assert_eq!(player.hand.len(), 0); // Hand should start empty
         assert!(player.hand.tiles().is_empty());
 
-        // Test configuration reference is stored correctly
+        // Test configuration is stored correctly
         assert_eq!(player.configuration.num_players(), configuration.num_players());
         assert_eq!(player.configuration.variation(), configuration.variation());
         assert_eq!(player.configuration.set_id(), configuration.set_id());
@@ -426,8 +558,8 @@ mod tests {
         assert_ne!(player1.player_id, player2.player_id);
         assert_ne!(player1.name(), player2.name());
 
-        // But they should share the same configuration reference
-        assert!(std::ptr::eq(player1.configuration, player2.configuration));
+        // But they should have the same configuration
+        assert_eq!(player1.configuration.tiles(), player2.configuration.tiles());
     }
 
     #[test]
@@ -502,6 +634,128 @@ mod tests {
         assert!(!player.has_playable_tile(&state));
     }
 
+    #[test]
+    fn test_human_player_my_turn_draw_rule() {
+        // The player holds nothing that can be played on the double six
+        let play_turn = |configuration: &Configuration| {
+            let mut player = HumanPlayer::new(0, configuration, "Test Player");
+            let mut state = DominoesState::new(configuration);
+            state.play_tile_unchecked(Tile::from((6, 6)), None);
+            player.my_turn(&state).0
+        };
+
+        // In a Block game, the player passes rather than draw
+        let block = Configuration::default().with_draw_rule(rules::DrawRule::NoDraw);
+        assert_eq!(play_turn(&block), Action::pass(0));
+        assert!(play_turn(&Configuration::default()).is_draw());
+    }
+
+    #[test]
+    fn test_human_player_my_turn_scripted() {
+        let configuration = Configuration::default();
+        let terminal = ScriptedTerminal::new(["0", "play 2|6 on 6", "play 1|6", "play 1|6 on 1", "hand", "play 1|6 on 6"]);
+        let mut player = HumanPlayer::with_terminal(0, &configuration, "Test Player", Box::new(terminal.clone()));
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        player.hand.add_tile(Tile::from((1, 6)));
+
+        // Commands that cannot be carried out are explained, and the player is asked again
+        let (action, _) = player.my_turn(&state);
+        assert_eq!(action, Action::play_unchecked(0, Tile::from((1, 6)), Some(6)));
+        assert!(player.hand().is_empty());
+        assert_eq!(terminal.remaining(), 0);
+        let output = terminal.output();
+        for explanation in [
+            format!("Unknown command '0'. {}", Command::HELP),
+            "You do not have 2|6.".to_string(),
+            "Choose an end to play it on, e.g. play 1|6 on 6.".to_string(),
+            "1|6 cannot be played on 1.".to_string(),
+        ] {
+            assert!(output.contains(&explanation), "{explanation}");
+        }
+    }
+
+    #[test]
+    fn test_human_player_quit_scripted() {
+        let configuration = Configuration::default();
+        let terminal = ScriptedTerminal::new(["draw", "quit", "n", "quit", "y"]);
+        let mut player = HumanPlayer::with_terminal(0, &configuration, "Test Player", Box::new(terminal.clone()));
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        player.hand.add_tile(Tile::from((1, 6)));
+
+        // A player with a playable tile cannot draw, and the first request to quit is not confirmed
+        player.my_turn(&state);
+        assert!(player.requested_quit());
+        assert_eq!(player.requested_setup(), None);
+        assert!(!player.requested_undo());
+        assert_eq!(terminal.remaining(), 0);
+        assert!(terminal.output().iter().any(|line| line == "You have a tile that can be played, so you must play it."));
+    }
+
+    #[test]
+    fn test_human_player_undo_scripted() {
+        let configuration = Configuration::default();
+        let terminal = ScriptedTerminal::new(["undo", "n", "undo", "y"]);
+        let mut player = HumanPlayer::with_terminal(0, &configuration, "Test Player", Box::new(terminal.clone()));
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        player.hand.add_tile(Tile::from((1, 6)));
+
+        // The first request to undo is not confirmed
+        player.my_turn(&state);
+        assert!(player.requested_undo());
+        assert_eq!(terminal.remaining(), 0);
+        assert_eq!(player.hand().len(), 1);
+    }
+
+    #[test]
+    fn test_human_player_redo_scripted() {
+        let configuration = Configuration::default();
+        let terminal = ScriptedTerminal::new(["redo"]);
+        let mut player = HumanPlayer::with_terminal(0, &configuration, "Test Player", Box::new(terminal.clone()));
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        player.hand.add_tile(Tile::from((1, 6)));
+
+        // A request to redo is not confirmed, because nothing is lost
+        player.my_turn(&state);
+        assert!(!player.requested_undo());
+        assert!(player.requested_redo());
+        assert!(!player.requested_redo());
+        assert_eq!(player.hand().len(), 1);
+    }
+
+    #[test]
+    fn test_human_player_setup_scripted() {
+        let configuration = Configuration::default();
+        let terminal = ScriptedTerminal::new(["setup 5|5-5|2"]);
+        let mut player = HumanPlayer::with_terminal(0, &configuration, "Test Player", Box::new(terminal.clone()));
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        player.hand.add_tile(Tile::from((1, 6)));
+
+        // The layout is passed on as it was typed
+        player.my_turn(&state);
+        assert_eq!(player.requested_setup(), Some("5|5-5|2".to_string()));
+        assert_eq!(player.requested_setup(), None);
+        assert_eq!(player.hand().len(), 1);
+    }
+
+    #[test]
+    fn test_human_player_claim_points_scripted() {
+        let configuration = Configuration::default().with_muggins(true);
+        let terminal = ScriptedTerminal::new(["1o", "10", ""]);
+        let mut player = HumanPlayer::with_terminal(0, &configuration, "Test Player", Box::new(terminal.clone()));
+        let state = DominoesState::new(&configuration);
+
+        // A mistyped number is asked for again, and nothing claims no points
+        assert_eq!(player.claim_points(&state), 10);
+        assert!(terminal.output().iter().any(|line| line.starts_with("Enter the number of points")));
+        assert_eq!(player.claim_points(&state), 0);
+        assert_eq!(terminal.remaining(), 0);
+    }
+
     #[test]
     fn test_human_player_player_trait_consistency() {
         let configuration = Configuration::default();
@@ -0,0 +1,270 @@
+//! Information Set Monte Carlo Tree Search
+//!
+//! The other search modes search a single world at a time, either one sampled deal of the hidden tiles per search, or a new
+//! sample at every node. Information Set MCTS (ISMCTS) instead samples a new deal at the root of every iteration (see
+//! `DominoesResponseGenerator::determinize`) and follows it down one shared tree. A node stands for an action rather than a
+//! position, so the statistics of an action are gathered over every deal in which it could be taken, and the search does not
+//! rely on one guess about the other hands.
+//!
+//! An action can only be chosen in the deals in which it is legal, so an action is scored by UCB1 with the number of iterations
+//! in which it was available in place of the number of visits to its parent. A player cannot choose which tile they draw, so a
+//! draw is the same node whichever tile it draws in a deal (see `Action::is_same_choice`), and a draw chosen at the root draws
+//! the tile that is really next in the boneyard.
+
+use hidden_game_player::State;
+use hidden_game_player::mcts::{ResponseGenerator, Rollout};
+
+use dominoes_state::{Action, DominoesState};
+use crate::search_tree::Outcome;
+use crate::{DominoesResponseGenerator, DominoesRollout, SearchNode, SearchTree};
+
+/// The tree of an Information Set MCTS search (see the module documentation)
+///
+/// The search can be continued in parts, e.g. to check a time limit between them.
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{DominoesState, Hand};
+/// # use player::{DominoesResponseGenerator, DominoesRollout, IsmctsTree};
+/// # use rules::{Configuration, Tile, TileMask};
+///
+/// let configuration = Configuration::default();
+/// let mut state = DominoesState::new(&configuration);
+/// for _ in 0..14 {
+///     state.draw_tile();
+/// }
+/// state.play_tile_unchecked(Tile::from((6, 6)), None);
+///
+/// let mut hand = Hand::new();
+/// hand.add_tile(Tile::from((5, 6)));
+/// hand.add_tile(Tile::from((1, 2)));
+/// let mut hidden = TileMask::all(configuration.set_id());
+/// for tile in [(5, 6), (1, 2), (6, 6)] {
+///     hidden.remove(Tile::from(tile));
+/// }
+/// let probabilities = vec![7.0 / 25.0; configuration.set_size()];
+/// let rg = DominoesResponseGenerator::with_knowledge(0, &hand, &hidden, &probabilities);
+///
+/// let mut tree = IsmctsTree::new(&state);
+/// tree.search(&rg, &DominoesRollout::new(), 1.414, 50);
+/// tree.search(&rg, &DominoesRollout::new(), 1.414, 50);
+/// assert_eq!(tree.iterations(), 100);
+/// assert_eq!(tree.best_action().unwrap().tile_played, Some((Tile::from((5, 6)), Some(6))));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IsmctsTree {
+    // The position searched
+    state: DominoesState,
+    // The root of the tree
+    root: Node,
+}
+
+impl IsmctsTree {
+    /// Creates a tree for a position, with no iterations yet
+    ///
+    /// # Arguments
+    /// * `state` - The position. The player to move is the player whose turn it is.
+    pub fn new(state: &DominoesState) -> Self {
+        Self { state: state.clone(), root: Node::new(None) }
+    }
+
+    /// Continues the search
+    ///
+    /// # Arguments
+    /// * `rg` - Samples the deals and generates the actions considered at each node
+    /// * `rollout` - Estimates the value of a node when it is added to the tree
+    /// * `exploration` - The exploration constant of UCB1. Larger values spread the visits more evenly.
+    /// * `iterations` - The number of iterations
+    pub fn search(&mut self, rg: &DominoesResponseGenerator, rollout: &DominoesRollout, exploration: f32, iterations: u32) {
+        let player_id = self.state.whose_turn();
+        for _ in 0..iterations {
+            let world = rg.determinize(&self.state);
+            let outcome = self.root.iterate(world, rg, rollout, exploration);
+            self.root.record(outcome, player_id);
+        }
+    }
+
+    /// Returns the number of iterations so far
+    pub fn iterations(&self) -> u32 {
+        self.root.visits
+    }
+
+    /// Returns the number of visits of each action at the root, in the order they were first tried
+    pub fn visits(&self) -> Vec<(Action, u32)> {
+        self.root.children.iter().filter_map(|child| Some((self.actual(child)?, child.visits))).collect()
+    }
+
+    /// Returns the action at the root that was visited the most, or `None` if there were none
+    pub fn best_action(&self) -> Option<Action> {
+        self.root.children.iter().max_by_key(|child| child.visits).and_then(|child| self.actual(child))
+    }
+
+    /// Returns the tree as a `SearchTree`, e.g. to show why the best action was chosen
+    ///
+    /// The draws at the root draw the tile that is really next in the boneyard, like the actions returned by `visits`.
+    pub fn to_search_tree(&self) -> SearchTree {
+        let mut root = self.root.to_search_node();
+        for child in &mut root.children {
+            child.action = child.action.take().map(|action| action.drawing_from(&self.state));
+        }
+        SearchTree::new(self.state.whose_turn(), root)
+    }
+
+    // Returns the action of a child of the root, drawing from the real boneyard rather than a sampled one
+    fn actual(&self, child: &Node) -> Option<Action> {
+        Some(child.action.clone()?.drawing_from(&self.state))
+    }
+}
+
+/// Searches a position with Information Set MCTS and returns the best action, or `None` if there is none
+///
+/// # Arguments
+/// * `state` - The position. The player to move is the player whose turn it is.
+/// * `rg` - Samples the deals and generates the actions considered at each node
+/// * `rollout` - Estimates the value of a node when it is added to the tree
+/// * `exploration` - The exploration constant of UCB1. Larger values spread the visits more evenly.
+/// * `iterations` - The number of iterations
+pub fn search(
+    state: &DominoesState,
+    rg: &DominoesResponseGenerator,
+    rollout: &DominoesRollout,
+    exploration: f32,
+    iterations: u32,
+) -> Option<Action> {
+    let mut tree = IsmctsTree::new(state);
+    tree.search(rg, rollout, exploration, iterations);
+    tree.best_action()
+}
+
+// A node of the tree, which stands for an action in every deal in which it can be taken
+#[derive(Debug, Clone)]
+struct Node {
+    action: Option<Action>,
+    visits: u32,
+    // The number of iterations in which the action could have been chosen
+    availability: u32,
+    // The sum of the values of the iterations for the player who took the action
+    total: f32,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(action: Option<Action>) -> Self {
+        Self { action, visits: 0, availability: 0, total: 0.0, children: Vec::new() }
+    }
+
+    // Runs one iteration below this node in a deal, and returns the value of the position that it reached. An action that has
+    // not been tried yet is added to the tree and evaluated with a rollout.
+    fn iterate(
+        &mut self,
+        world: DominoesState,
+        rg: &DominoesResponseGenerator,
+        rollout: &DominoesRollout,
+        exploration: f32,
+    ) -> Outcome {
+        if world.is_terminal() {
+            return evaluate(&world, rg, rollout);
+        }
+        let actions = rg.generate(&world);
+        if actions.is_empty() {
+            return evaluate(&world, rg, rollout);
+        }
+
+        let mover = world.whose_turn();
+        // Returns the action in this deal that a child stands for, if it is legal
+        let in_world = |child: &Node| {
+            let action = child.action.as_ref()?;
+            actions.iter().find(|other| other.is_same_choice(action))
+        };
+        for child in self.children.iter_mut().filter(|child| in_world(child).is_some()) {
+            child.availability += 1;
+        }
+        let untried = actions.iter().find(|&action| {
+            self.children.iter().all(|child| !child.action.as_ref().is_some_and(|other| other.is_same_choice(action)))
+        });
+        if let Some(action) = untried {
+            let mut child = Node::new(Some(action.clone()));
+            child.availability = 1;
+            let outcome = evaluate(&world.apply(action), rg, rollout);
+            child.record(outcome, mover);
+            self.children.push(child);
+            return outcome;
+        }
+
+        let child = self
+            .children
+            .iter_mut()
+            .filter(|child| in_world(child).is_some())
+            .max_by(|a, b| a.ucb(exploration).total_cmp(&b.ucb(exploration)))
+            .expect("Every legal action has a child");
+        let next = world.apply(in_world(child).expect("The child is available"));
+        let outcome = child.iterate(next, rg, rollout, exploration);
+        child.record(outcome, mover);
+        outcome
+    }
+
+    // Adds the outcome of an iteration, for the player who took the action
+    fn record(&mut self, outcome: Outcome, player_id: u8) {
+        self.visits += 1;
+        self.total += outcome.value_for(player_id);
+    }
+
+    // Converts the node and its children to a `SearchNode`, most visited first
+    fn to_search_node(&self) -> SearchNode {
+        let mut children: Vec<SearchNode> = self.children.iter().map(Node::to_search_node).collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.visits));
+        let value = if self.visits == 0 { 0.0 } else { self.total / self.visits as f32 };
+        SearchNode { action: self.action.clone(), visits: self.visits, value, children }
+    }
+
+    // Returns the UCB1 score of the node, counting the iterations in which it was available rather than its parent's visits
+    fn ucb(&self, exploration: f32) -> f32 {
+        let visits = self.visits as f32;
+        self.total / visits + exploration * ((self.availability.max(1) as f32).ln() / visits).sqrt()
+    }
+}
+
+// Estimates the value of a position with a rollout
+fn evaluate(world: &DominoesState, rg: &DominoesResponseGenerator, rollout: &DominoesRollout) -> Outcome {
+    Outcome { value: rollout.play(world, rg), player_id: world.whose_turn() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dominoes_state::Hand;
+    use rules::{Configuration, Tile, TileMask};
+
+    #[test]
+    fn test_only_draw() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        for _ in 0..14 {
+            state.draw_tile();
+        }
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 0;
+
+        // Nothing in the hand can be played on the double six, so the player must draw
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((1, 2)));
+        hand.add_tile(Tile::from((0, 3)));
+        let mut hidden = TileMask::all(configuration.set_id());
+        for tile in [(1, 2), (0, 3), (6, 6)] {
+            hidden.remove(Tile::from(tile));
+        }
+        let probabilities = vec![7.0 / 25.0; configuration.set_size()];
+        let rg = DominoesResponseGenerator::with_knowledge(0, &hand, &hidden, &probabilities);
+
+        // Every sampled deal draws a different tile, but they are all the same choice, and the real next tile is drawn
+        let mut tree = IsmctsTree::new(&state);
+        tree.search(&rg, &DominoesRollout::new(), 1.414, 50);
+        let draw = Action::draw(0, *state.boneyard.peek().unwrap());
+        assert_eq!(tree.visits(), vec![(draw.clone(), 50)]);
+        assert_eq!(tree.best_action(), Some(draw.clone()));
+        let search_tree = tree.to_search_tree();
+        assert_eq!(search_tree.iterations, 50);
+        assert_eq!(search_tree.visits(), vec![(draw.clone(), 50)]);
+        assert_eq!(state.apply(&draw).boneyard.count(), state.boneyard.count() - 1);
+    }
+}
@@ -5,15 +5,39 @@
 
 pub mod player;
 pub mod human_player;
+pub mod command;
 pub mod dominoes_player;
 pub mod dominoes_response_generator;
 pub mod dominoes_rollout;
 pub mod dominoes_static_evaluator;
+pub mod front_end;
+pub mod greedy_player;
+pub mod ismcts;
+pub mod opening_book;
+pub mod random_player;
+pub mod score_chaser_player;
+pub mod player_factory;
+pub mod search_tree;
+pub mod terminal;
+pub mod tile_knowledge;
+pub mod world_sampler;
 
 pub use player::*;
 pub use human_player::*;
+pub use command::Command;
 pub use dominoes_player::*;
 pub use dominoes_response_generator::*;
 pub use dominoes_rollout::*;
 pub use dominoes_static_evaluator::*;
+pub use front_end::FrontEnd;
+pub use greedy_player::*;
+pub use ismcts::IsmctsTree;
+pub use opening_book::OpeningBook;
+pub use random_player::*;
+pub use score_chaser_player::*;
+pub use player_factory::*;
+pub use search_tree::*;
+pub use terminal::*;
+pub use tile_knowledge::*;
+pub use world_sampler::*;
 
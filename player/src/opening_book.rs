@@ -0,0 +1,255 @@
+//! Opening books
+//!
+//! An opening book holds the best plays for the first few turns of a game, so that a computer player does not have to search
+//! for them. Each line of a book is a position, given by the tiles in the layout in the order they were played, followed by the
+//! plays to make in it, best first:
+//!
+//! ```text
+//! # The best first tiles, and the best replies to the double six
+//! : 5|5 4|6 2|3
+//! 6|6 : 5|6@6 4|6@6
+//! ```
+//!
+//! A play is written as `a|b@end`, or `a|b` for the first tile. The first play in the list that is legal for the player's hand is
+//! made. If there is none, or the position is not in the book, the player searches as usual. Blank lines and lines starting with
+//! `#` are ignored.
+//!
+//! Books for common configurations are embedded in the crate (see `OpeningBook::embedded`), and others can be loaded from files.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use dominoes_state::{Action, DominoesState, FastHashMap, Hand, NotationError};
+use rules::{Configuration, ParseTileError, Tile, Variation};
+
+// The embedded books, by variation, set, and number of players
+const EMBEDDED_BOOKS: [(Variation, u8, usize, &str); 2] = [
+    (Variation::AllFives, 6, 2, include_str!("../books/all_fives_double_six_2.txt")),
+    (Variation::Traditional, 6, 2, include_str!("../books/traditional_double_six_2.txt")),
+];
+
+/// The best plays for the first few turns of a game (see the module documentation)
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, DominoesState, Hand};
+/// # use player::OpeningBook;
+/// # use rules::{Configuration, Tile};
+///
+/// let book = OpeningBook::parse("6|6 : 5|6@6 4|6@6").unwrap();
+/// let mut state = DominoesState::new(&Configuration::default());
+/// state.play_tile_unchecked(Tile::from((6, 6)), None);
+/// state.whose_turn = 1;
+///
+/// let mut hand = Hand::new();
+/// hand.add_tile(Tile::from((4, 6)));
+/// hand.add_tile(Tile::from((1, 6)));
+/// assert_eq!(book.choose(&state, &hand), Some(Action::play_unchecked(1, Tile::from((4, 6)), Some(6))));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OpeningBook {
+    // The plays for each position, best first, by the tiles in the layout
+    lines: FastHashMap<Vec<Tile>, Vec<(Tile, Option<u8>)>>,
+}
+
+impl OpeningBook {
+    /// Creates an empty book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the book embedded in the crate for a configuration, or `None` if there is none
+    ///
+    /// There are books for All Fives and Traditional dominoes with a double-six set and two players.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::OpeningBook;
+    /// # use rules::{Configuration, Variation};
+    ///
+    /// assert!(OpeningBook::embedded(&Configuration::new(2, Variation::AllFives, 6, 7)).is_some());
+    /// assert!(OpeningBook::embedded(&Configuration::new(4, Variation::AllFives, 6, 7)).is_none());
+    /// ```
+    pub fn embedded(configuration: &Configuration) -> Option<Self> {
+        let (_, _, _, text) = EMBEDDED_BOOKS.iter().find(|&&(variation, set_id, num_players, _)| {
+            variation == configuration.variation()
+                && set_id == configuration.set_id()
+                && num_players == configuration.num_players()
+        })?;
+        Some(Self::parse(text).expect("The embedded books are valid"))
+    }
+
+    /// Parses a book (see the module documentation)
+    ///
+    /// # Arguments
+    /// * `text` - The lines of the book
+    ///
+    /// # Returns
+    /// The book, or the first line that cannot be parsed
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::OpeningBook;
+    ///
+    /// let book = OpeningBook::parse("# The best first tiles\n: 5|5 4|6\n").unwrap();
+    /// assert_eq!(book.len(), 1);
+    /// assert_eq!(OpeningBook::parse(": 5|5\n6|6 5|6@6").unwrap_err().line, 2);
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, NotationError> {
+        let mut book = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| NotationError { line: index + 1, message };
+            let (position, plays) = line.split_once(':').ok_or_else(|| error("Expected ':' after the position".to_string()))?;
+            let position = position.split_whitespace().map(parse_tile).collect::<Result<Vec<Tile>, String>>().map_err(error)?;
+            let plays = plays.split_whitespace().map(parse_play).collect::<Result<Vec<_>, String>>().map_err(error)?;
+            book.lines.insert(position, plays);
+        }
+        Ok(book)
+    }
+
+    /// Loads a book from a file (see `parse`)
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, or an error of kind `InvalidData` if it cannot be parsed
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Returns the number of positions in the book
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns `true` if the book has no positions
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the plays for a position, best first, or `None` if it is not in the book
+    ///
+    /// # Arguments
+    /// * `layout` - The tiles in the layout, in the order they were played
+    pub fn plays(&self, layout: &[Tile]) -> Option<&[(Tile, Option<u8>)]> {
+        self.lines.get(layout).map(Vec::as_slice)
+    }
+
+    /// Returns the best play in the book for the player whose turn it is, or `None` if the book has no legal play
+    ///
+    /// # Arguments
+    /// * `state` - The position
+    /// * `hand` - The player's hand
+    pub fn choose(&self, state: &DominoesState, hand: &Hand) -> Option<Action> {
+        let layout: Vec<Tile> = state.layout.nodes.iter().map(|node| node.tile).collect();
+        let legal = state.legal_actions(hand);
+        self.plays(&layout)?
+            .iter()
+            .filter(|(tile, _)| !layout.contains(tile))
+            .map(|&(tile, end)| Action::play_unchecked(state.whose_turn, tile, end))
+            .find(|action| legal.contains(action))
+    }
+}
+
+// Parses a tile written as a|b
+fn parse_tile(token: &str) -> Result<Tile, String> {
+    token.parse().map_err(|error: ParseTileError| error.to_string())
+}
+
+// Parses a play written as a|b@end, or a|b for the first tile
+fn parse_play(token: &str) -> Result<(Tile, Option<u8>), String> {
+    match token.split_once('@') {
+        Some((tile, end)) => {
+            let end = end.parse::<u8>().map_err(|_| format!("Expected an end value, found '{end}'"))?;
+            Ok((parse_tile(tile)?, Some(end)))
+        }
+        None => Ok((parse_tile(token)?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dominoes_state::{AllFivesScorer, Scorer};
+
+    // Returns a hand holding the tiles
+    fn hand(tiles: &[(u8, u8)]) -> Hand {
+        let mut hand = Hand::new();
+        for &tile in tiles {
+            hand.add_tile(Tile::from(tile));
+        }
+        hand
+    }
+
+    #[test]
+    fn test_parse() {
+        let book = OpeningBook::parse("# A book\n\n: 5|5 6|4\n6|6 : 5|6@6\n").unwrap();
+        assert_eq!(book.len(), 2);
+        assert_eq!(book.plays(&[]), Some(&[(Tile::from((5, 5)), None), (Tile::from((4, 6)), None)][..]));
+        assert_eq!(book.plays(&[Tile::from((6, 6))]), Some(&[(Tile::from((5, 6)), Some(6))][..]));
+        assert_eq!(book.plays(&[Tile::from((5, 5))]), None);
+
+        let errors = [("6|6 5|6@6", 1, "Expected ':'"), (": 5|9", 1, "Expected a tile"), ("\n6|6 : 5|6@x", 2, "Expected an end")];
+        for (text, line, message) in errors {
+            let error = OpeningBook::parse(text).unwrap_err();
+            assert_eq!(error.line, line);
+            assert!(error.message.starts_with(message), "{}", error.message);
+        }
+    }
+
+    #[test]
+    fn test_embedded_books() {
+        for (variation, set_id, num_players, _) in EMBEDDED_BOOKS {
+            let configuration = Configuration::new(num_players, variation, set_id, 7);
+            assert!(!OpeningBook::embedded(&configuration).unwrap().is_empty());
+        }
+        assert!(OpeningBook::embedded(&Configuration::new(2, Variation::Bergen, 6, 7)).is_none());
+    }
+
+    #[test]
+    fn test_all_fives_book_order() {
+        // The plays that score come first, most points first, then the heaviest tiles
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let book = OpeningBook::embedded(&configuration).unwrap();
+        for (layout, plays) in &book.lines {
+            let mut state = DominoesState::new(&configuration);
+            for &tile in layout {
+                let end = (0..=6).find(|&end| state.can_play_tile(&tile, Some(end)));
+                state.play_tile_unchecked(tile, end);
+            }
+            let order: Vec<(u32, u8)> = plays
+                .iter()
+                .map(|&(tile, end)| {
+                    let mut after = state.clone();
+                    after.play_tile_unchecked(tile, end);
+                    (AllFivesScorer.score(&after.layout), tile.score())
+                })
+                .collect();
+            assert!(order.is_sorted_by(|a, b| a >= b), "{layout:?}: {order:?}");
+        }
+    }
+
+    #[test]
+    fn test_choose() {
+        // The first tile that scores is played, and the plays that are not legal are skipped
+        let configuration = Configuration::new(2, Variation::AllFives, 6, 7);
+        let book = OpeningBook::embedded(&configuration).unwrap();
+        let mut state = DominoesState::new(&configuration);
+        let expected = Action::play_unchecked(0, Tile::from((2, 3)), None);
+        assert_eq!(book.choose(&state, &hand(&[(1, 2), (2, 3), (6, 6)])), Some(expected));
+
+        state.play_tile_unchecked(Tile::from((5, 5)), None);
+        state.whose_turn = 1;
+        assert_eq!(book.choose(&state, &hand(&[(1, 2), (4, 5)])), Some(Action::play_unchecked(1, Tile::from((4, 5)), Some(5))));
+        assert_eq!(book.choose(&state, &hand(&[(1, 2)])), None);
+
+        // A position that is not in the book
+        state.play_tile_unchecked(Tile::from((4, 5)), Some(5));
+        state.whose_turn = 0;
+        assert_eq!(book.choose(&state, &hand(&[(4, 4)])), None);
+    }
+}
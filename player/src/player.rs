@@ -1,15 +1,30 @@
 //! Module defining the Player trait and related functionality
 //!
 
-use crate::Hand;
+use std::time::Duration;
+
+use crate::{FrontEnd, Hand, SearchTree};
 use dominoes_state::{Action, DominoesState};
 use rules::Tile;
 
+/// Statistics about how a computer player chose its last action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecisionStats {
+    /// The number of search iterations
+    pub iterations: u32,
+    /// The time taken to choose the action
+    pub elapsed: Duration,
+}
+
 /// Base trait for all players in the game
 ///
 /// This trait defines the interface that all players (human, AI, etc.) must implement to participate in a dominoes game. It
 /// provides methods for game setup, turn taking, and querying player capabilities.
 ///
+/// The trait is object safe, and the players in this crate own what they need rather than borrowing it, so a table of players of
+/// different kinds can be chosen at run time and held as `Box<dyn Player>`.
+///
 /// # Examples
 /// ```rust
 /// # use player::{Player, Hand};
@@ -27,8 +42,8 @@ use rules::Tile;
 ///         self.hand = Hand::new();
 ///     }
 ///
-///     fn set_up(&mut self, state: &mut DominoesState) {
-///         // Draw starting tiles
+///     fn set_up_from(&mut self, hand: Hand, state: &DominoesState) {
+///         self.hand = hand;
 ///     }
 ///
 ///     fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
@@ -45,6 +60,10 @@ use rules::Tile;
 ///         &self.hand
 ///     }
 ///
+///     fn hand_mut(&mut self) -> &mut Hand {
+///         &mut self.hand
+///     }
+///
 ///     fn name(&self) -> &str {
 ///         &self.name
 ///     }
@@ -58,27 +77,26 @@ pub trait Player {
     /// Resets the player to the initial state.
     fn reset(&mut self);
 
-    /// Called during game setup to set up the player's state before the game starts.
+    /// Deals the player a starting hand from the boneyard of a game that has not started, and sets the player up with it
+    ///
+    /// By default, the hand is dealt with `Boneyard::deal` and passed to `set_up_from`. A game never calls this: the engine deals
+    /// every hand at once and calls `set_up_from`, so that the hands can be redealt if nobody can play the first tile. It is a
+    /// shortcut for setting up a player on its own, e.g. in a test.
     ///
     /// # Arguments
     /// * `state` - The current state of the game
+    fn set_up(&mut self, state: &mut DominoesState) {
+        let hand = state.boneyard.deal(1, state.starting_hand_size()).remove(0);
+        self.set_up_from(Hand::from(hand), state);
+    }
+
+    /// Called with the player's hand when the hands have been dealt, or instead of `set_up` when the game starts from a position
+    /// that is already in progress, e.g. a scenario.
     ///
-    /// # Examples
-    /// ```rust
-    /// # use dominoes_state::DominoesState;
-    /// # use player::Hand;
-    /// # struct MyPlayer { hand: Hand }
-    /// # impl MyPlayer {
-    /// fn set_up(&mut self, state: &mut DominoesState) {
-    ///     for _ in 0..7 {  // Draw 7 tiles for standard game
-    ///         if let Some(tile) = state.draw_tile() {
-    ///             self.hand.add_tile(tile);
-    ///         }
-    ///     }
-    /// }
-    /// # }
-    /// ```
-    fn set_up(&mut self, state: &mut DominoesState);
+    /// # Arguments
+    /// * `hand` - The player's hand
+    /// * `state` - The current state of the game
+    fn set_up_from(&mut self, hand: Hand, state: &DominoesState);
 
     /// Called when it's this player's turn to make a move
     ///
@@ -106,8 +124,8 @@ pub trait Player {
     ///         // Play a tile
     ///         let tile = self.choose_tile_to_play(state);
     ///         let mut new_state = state.clone();
-    ///         new_state.play_tile(tile, None);
-    ///         (Action::play(self.player_id, tile, None), new_state)
+    ///         new_state.play_tile_unchecked(tile, None);
+    ///         (Action::play_unchecked(self.player_id, tile, None), new_state)
     ///     } else {
     ///         // Pass turn
     ///         (Action::pass(self.player_id), state.clone())
@@ -143,6 +161,101 @@ pub trait Player {
     /// ```
     fn has_playable_tile(&self, state: &DominoesState) -> bool;
 
+    /// Takes back an action made by this player
+    ///
+    /// Called when turns are undone, once for each of the player's actions, in reverse order. The player must return a tile that
+    /// it played to its hand and give back a tile that it drew. The state is restored by the game. The default undoes the action
+    /// in the hand (see `Hand::undo`).
+    ///
+    /// # Arguments
+    /// * `action` - The action to take back
+    fn undo(&mut self, action: &Action) {
+        self.hand_mut().undo(action);
+    }
+
+    /// Makes an action of this player again after it was taken back
+    ///
+    /// Called when undone turns are redone, once for each of the player's actions, in order (see `undo`). The player must remove
+    /// a tile that it played from its hand and add a tile that it drew. The state is restored by the game, and the other players
+    /// observe the action again (see `observe`). The default redoes the action in the hand (see `Hand::redo`).
+    ///
+    /// # Arguments
+    /// * `action` - The action to make again
+    fn redo(&mut self, action: &Action) {
+        self.hand_mut().redo(action);
+    }
+
+    /// Tells the player about an action taken by another player
+    ///
+    /// The game calls this for every action of the other players, in order, so that a computer player can learn about their
+    /// hands, e.g. that a player who draws or passes holds no tile that could be played. A tile that another player draws is
+    /// hidden, and a player must only take into account that a tile was drawn. The default ignores the action.
+    ///
+    /// # Arguments
+    /// * `action` - The action taken by another player
+    /// * `state` - The state before the action
+    fn observe(&mut self, _action: &Action, _state: &DominoesState) {}
+
+    /// Takes back an action of another player that was observed
+    ///
+    /// Called when turns are undone, once for each of the other players' actions, in reverse order (see `observe` and `undo`).
+    /// The default does nothing.
+    ///
+    /// # Arguments
+    /// * `action` - The action to take back
+    fn forget(&mut self, _action: &Action) {}
+
+    /// Returns true if the player is a person
+    ///
+    /// When turns are undone, the game takes back turns until it is a person's turn again. The default is `false`.
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    /// Returns the player's front end, if it is driven by a person through a user interface
+    ///
+    /// The game checks the front end for requests, such as undoing a turn, after each call to `my_turn` (see `FrontEnd`). The
+    /// default is `None`, for players that only make decisions.
+    fn front_end(&mut self) -> Option<&mut dyn FrontEnd> {
+        None
+    }
+
+    /// Returns the points that the player claims for its last play under the muggins rule (see `Configuration::muggins`)
+    ///
+    /// The game calls this after each of the player's plays, whether or not it scores, so that a person is not told that they
+    /// scored. The player scores the points only if it claims the right number, and otherwise another player may call muggins and
+    /// take them. The default claims the points scored, so a computer player never misses them.
+    ///
+    /// # Arguments
+    /// * `state` - The state after the play (see `DominoesState::unclaimed_points`)
+    fn claim_points(&mut self, state: &DominoesState) -> u32 {
+        state.unclaimed_points().map_or(0, |(_, points)| points)
+    }
+
+    /// Tells the player how much time it has for its next action
+    ///
+    /// The game calls this before each call to `my_turn` when a time control is in effect. A player that takes longer loses the
+    /// round, so a player that searches for its move should stop searching in time. The default ignores the budget.
+    ///
+    /// # Arguments
+    /// * `budget` - The time for the next action, or `None` if it is not limited
+    fn set_time_budget(&mut self, _budget: Option<Duration>) {}
+
+    /// Returns statistics about how the player chose the action returned by the last call to `my_turn`
+    ///
+    /// These are recorded in the game log for later analysis. The default is `None`, for players that do not search.
+    fn decision_stats(&self) -> Option<DecisionStats> {
+        None
+    }
+
+    /// Returns the top levels of the search tree behind the action returned by the last call to `my_turn`
+    ///
+    /// The default is `None`, for players that do not search or have not been asked to keep their trees (see
+    /// `DominoesPlayer::set_search_tree_depth`).
+    fn search_tree(&self) -> Option<&SearchTree> {
+        None
+    }
+
     /// Returns the player's hand
     ///
     /// Provides access to the player's current hand. Must be implemented by all players.
@@ -151,6 +264,9 @@ pub trait Player {
     /// A reference to the player's Hand
     fn hand(&self) -> &Hand;
 
+    /// Returns the player's hand, to be changed, e.g. when an action is undone (see `undo`)
+    fn hand_mut(&mut self) -> &mut Hand;
+
     /// Returns the highest double tile in the player's hand, if any
     fn highest_double(&self) -> Option<Tile> {
         self.hand()
@@ -235,6 +351,10 @@ mod tests {
             }
         }
 
+        fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+            self.hand = hand;
+        }
+
         fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
             // Always pass for test
             (Action::pass(self.id), state.clone())
@@ -248,6 +368,10 @@ mod tests {
             &self.hand
         }
 
+        fn hand_mut(&mut self) -> &mut Hand {
+            &mut self.hand
+        }
+
         fn name(&self) -> &str {
             &self.name
         }
@@ -373,4 +497,23 @@ mod tests {
         assert_eq!(player.name(), "Boxed Player");
         assert!(player.hand().is_empty());
     }
+
+    #[test]
+    fn test_player_mixed_roster() {
+        // The players do not borrow the configuration, so they outlive it
+        let mut roster: Vec<Box<dyn Player>> = {
+            let config = Configuration::new(4, rules::Variation::Traditional, 6, 5);
+            vec![
+                Box::new(crate::HumanPlayer::new(0, &config, "Alice")),
+                Box::new(crate::DominoesPlayer::new(1, &config)),
+                Box::new(crate::GreedyPlayer::new(2, &config, "Carol")),
+                Box::new(TestPlayer::new(3, "Dave")),
+            ]
+        };
+
+        let ids: Vec<u8> = roster.iter().map(|player| player.id()).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert!(roster[0].is_human() && !roster[1].is_human());
+        assert!(roster[0].front_end().is_some() && roster[3].front_end().is_none());
+    }
 }
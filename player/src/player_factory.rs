@@ -0,0 +1,416 @@
+//! Player factory
+//!
+//! This module creates players from short specifications, so that the players at a table can be chosen from the command line or a
+//! configuration file. A specification is a kind of player, optionally followed by a colon and an option:
+//!
+//! - `human` or `human:<name>`: A person at the terminal (see `HumanPlayer`)
+//! - `mcts`, `mcts:<difficulty>`, or `mcts:<iterations>`: The AI (see `DominoesPlayer`). The difficulty is `easy`, `medium`, or
+//!   `hard`, and the default is `medium`.
+//! - `greedy`: A computer player that does not look ahead (see `GreedyPlayer`)
+//! - `score-chaser`: A computer player that makes the play that scores the most points (see `ScoreChaserPlayer`)
+//! - `random` or `random:<seed>`: A computer player that takes a legal action at random (see `RandomPlayer`). With a seed, its
+//!   choices are the same in every game.
+//! - `beginner`, `intermediate`, or `expert`: A computer player of the given skill (see `SkillLevel`)
+//! - `remote:<host>:<port>`: A program listening on the network at the given address, e.g. `remote:localhost:7878`. Whoever runs
+//!   the game decides how such a player is reached (see `PlayerFactory::with_remote`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use rules::Configuration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::{
+    DominoesPlayer, GreedyPlayer, HumanPlayer, OpeningBook, PlayModel, Player, RandomPlayer, ScoreChaserPlayer, SearchOptions,
+};
+
+// Number of sampled deals searched by an expert (see `SkillLevel::Expert`)
+const EXPERT_DETERMINIZATIONS: u32 = 10;
+
+// How much more likely an expert believes a tile is for each tile played by another player that shares a value with it
+const EXPERT_AFFINITY: f64 = 1.5;
+
+/// How well the AI plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns the number of MCTS iterations used by the AI at this difficulty
+    pub fn iterations(self) -> u32 {
+        match self {
+            Difficulty::Easy => 100,
+            Difficulty::Medium => 1000,
+            Difficulty::Hard => 5000,
+        }
+    }
+}
+
+/// How strong a computer player is, for a host that offers a choice of opponents
+///
+/// Each level is a different kind of player, rather than the same player searching more or less (see `Difficulty`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SkillLevel {
+    /// Takes any legal action, chosen at random (see `RandomPlayer`)
+    Beginner,
+    /// Makes the play that looks best right now (see `GreedyPlayer`)
+    #[default]
+    Intermediate,
+    /// Searches separately for each of several sampled deals of the hidden tiles (see `DominoesPlayer` and `SearchOptions`), and
+    /// plays from an opening book if there is one for the configuration (see `OpeningBook`)
+    Expert,
+}
+
+impl SkillLevel {
+    /// Returns the name of the level, as it is parsed in a player specification
+    pub fn name(self) -> &'static str {
+        match self {
+            SkillLevel::Beginner => "beginner",
+            SkillLevel::Intermediate => "intermediate",
+            SkillLevel::Expert => "expert",
+        }
+    }
+}
+
+/// A specification of a player
+///
+/// With the `serde` feature, a specification is serialized as a string in the same form that it is parsed from.
+///
+/// # Examples
+/// ```rust
+/// # use player::{Difficulty, PlayerSpec};
+///
+/// let spec: PlayerSpec = "mcts:hard".parse().unwrap();
+/// assert_eq!(spec, PlayerSpec::Mcts { iterations: Difficulty::Hard.iterations() });
+/// assert!("robot".parse::<PlayerSpec>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerSpec {
+    /// A person at the terminal, with the given name or the seat's default name
+    Human { name: Option<String> },
+    /// The AI, searching with the given number of iterations
+    Mcts { iterations: u32 },
+    /// A computer player that always makes the play that looks best right now
+    Greedy,
+    /// A computer player that always makes the play that scores the most points right now
+    ScoreChaser,
+    /// A computer player that takes a legal action at random, with its own seed if given
+    Random { seed: Option<u64> },
+    /// A computer player of the given skill
+    Level(SkillLevel),
+    /// A program listening on the network at the given address, e.g. localhost:7878
+    Remote { address: String },
+}
+
+impl PlayerSpec {
+    /// Returns true if the player is a person
+    pub fn is_human(&self) -> bool {
+        matches!(self, PlayerSpec::Human { .. })
+    }
+
+    /// Returns true if the player is reached over the network
+    pub fn is_remote(&self) -> bool {
+        matches!(self, PlayerSpec::Remote { .. })
+    }
+}
+
+impl FromStr for PlayerSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, option) = match s.split_once(':') {
+            Some((kind, option)) => (kind.trim(), Some(option.trim())),
+            None => (s.trim(), None),
+        };
+        match (kind, option) {
+            ("human", None) => Ok(PlayerSpec::Human { name: None }),
+            ("human", Some("")) => Err("Expected a name after 'human:'".to_string()),
+            ("human", Some(name)) => Ok(PlayerSpec::Human { name: Some(name.to_string()) }),
+            ("mcts", None) => Ok(PlayerSpec::Mcts { iterations: Difficulty::default().iterations() }),
+            ("mcts", Some(option)) => {
+                let iterations = match option {
+                    "easy" => Difficulty::Easy.iterations(),
+                    "medium" => Difficulty::Medium.iterations(),
+                    "hard" => Difficulty::Hard.iterations(),
+                    _ => option.parse::<u32>().ok().filter(|&iterations| iterations > 0).ok_or_else(|| {
+                        format!("Expected easy, medium, hard, or a number of iterations after 'mcts:', found '{option}'")
+                    })?,
+                };
+                Ok(PlayerSpec::Mcts { iterations })
+            }
+            ("greedy", None) => Ok(PlayerSpec::Greedy),
+            ("greedy", Some(_)) => Err("A greedy player has no options".to_string()),
+            ("score-chaser", None) => Ok(PlayerSpec::ScoreChaser),
+            ("score-chaser", Some(_)) => Err("A score chaser has no options".to_string()),
+            ("random", None) => Ok(PlayerSpec::Random { seed: None }),
+            ("random", Some(option)) => match option.parse::<u64>() {
+                Ok(seed) => Ok(PlayerSpec::Random { seed: Some(seed) }),
+                Err(_) => Err(format!("Expected a seed after 'random:', found '{option}'")),
+            },
+            ("beginner", None) => Ok(PlayerSpec::Level(SkillLevel::Beginner)),
+            ("intermediate", None) => Ok(PlayerSpec::Level(SkillLevel::Intermediate)),
+            ("expert", None) => Ok(PlayerSpec::Level(SkillLevel::Expert)),
+            ("beginner" | "intermediate" | "expert", Some(_)) => Err(format!("The '{kind}' level has no options")),
+            ("remote", Some(address))
+                if address.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()) =>
+            {
+                Ok(PlayerSpec::Remote { address: address.to_string() })
+            }
+            ("remote", _) => Err("Expected an address such as localhost:7878 after 'remote:'".to_string()),
+            _ => Err(format!(
+                "Unknown player '{kind}'. Expected human, mcts, greedy, score-chaser, random, beginner, intermediate, expert, or \
+                 remote."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PlayerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerSpec::Human { name: None } => write!(f, "human"),
+            PlayerSpec::Human { name: Some(name) } => write!(f, "human:{name}"),
+            PlayerSpec::Mcts { iterations } => write!(f, "mcts:{iterations}"),
+            PlayerSpec::Greedy => write!(f, "greedy"),
+            PlayerSpec::ScoreChaser => write!(f, "score-chaser"),
+            PlayerSpec::Random { seed: None } => write!(f, "random"),
+            PlayerSpec::Random { seed: Some(seed) } => write!(f, "random:{seed}"),
+            PlayerSpec::Level(level) => write!(f, "{}", level.name()),
+            PlayerSpec::Remote { address } => write!(f, "remote:{address}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for PlayerSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PlayerSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Creates the player in a seat given by `remote:<address>` (see `PlayerFactory::with_remote`)
+///
+/// The arguments are the ID of the player, the configuration of the game, the address, and the name of the player.
+pub type RemoteConnector = fn(u8, &Configuration, &str, &str) -> Box<dyn Player>;
+
+/// Creates players for a game
+///
+/// # Examples
+/// ```rust
+/// # use player::{Player, PlayerFactory, PlayerSpec};
+/// # use rules::Configuration;
+///
+/// let config = Configuration::default();
+/// let factory = PlayerFactory::new(&config);
+/// let player = factory.create(1, &"greedy".parse::<PlayerSpec>().unwrap(), "Bob");
+/// assert_eq!(player.name(), "Bob");
+/// assert_eq!(player.id(), 1);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerFactory<'a> {
+    /// The configuration of the game that the players are created for
+    configuration: &'a Configuration,
+    /// Creates the players that are reached over the network, if they can be
+    remote: Option<RemoteConnector>,
+}
+
+impl<'a> PlayerFactory<'a> {
+    /// Creates a factory for players of a game with the given configuration
+    pub fn new(configuration: &'a Configuration) -> Self {
+        Self { configuration, remote: None }
+    }
+
+    /// Returns the factory with a way to create the players that are reached over the network
+    ///
+    /// This crate has no networking, so the host of a game supplies it. A factory without a connector cannot create remote
+    /// players.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::{GreedyPlayer, Player, PlayerFactory, PlayerSpec};
+    /// # use rules::Configuration;
+    ///
+    /// // A stand-in that plays locally instead of connecting
+    /// let config = Configuration::default();
+    /// let factory = PlayerFactory::new(&config)
+    ///     .with_remote(|player_id, configuration, _address, name| Box::new(GreedyPlayer::new(player_id, configuration, name)));
+    /// let player = factory.create(1, &"remote:localhost:7878".parse::<PlayerSpec>().unwrap(), "Bob");
+    /// assert_eq!(player.name(), "Bob");
+    /// ```
+    pub fn with_remote(mut self, connector: RemoteConnector) -> Self {
+        self.remote = Some(connector);
+        self
+    }
+
+    /// Creates a player
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player, which is its seat
+    /// * `spec` - What kind of player to create
+    /// * `default_name` - The name of the player, unless the specification gives one
+    ///
+    /// # Panics
+    /// Panics if the player is remote and the factory has no connector (see `with_remote`)
+    pub fn create(&self, player_id: u8, spec: &PlayerSpec, default_name: &str) -> Box<dyn Player> {
+        match spec {
+            PlayerSpec::Human { name } => {
+                Box::new(HumanPlayer::new(player_id, self.configuration, name.as_deref().unwrap_or(default_name)))
+            }
+            PlayerSpec::Mcts { iterations } => {
+                let mut player = DominoesPlayer::new(player_id, self.configuration);
+                player.set_name(default_name);
+                player.set_iterations(*iterations);
+                Box::new(player)
+            }
+            PlayerSpec::Greedy => Box::new(GreedyPlayer::new(player_id, self.configuration, default_name)),
+            PlayerSpec::ScoreChaser => Box::new(ScoreChaserPlayer::new(player_id, self.configuration, default_name)),
+            PlayerSpec::Random { seed } => {
+                let player = RandomPlayer::new(player_id, self.configuration, default_name);
+                Box::new(match seed {
+                    Some(seed) => player.with_seed(*seed),
+                    None => player,
+                })
+            }
+            PlayerSpec::Level(level) => self.create_at_level(player_id, *level, default_name),
+            PlayerSpec::Remote { address } => {
+                let connect = self.remote.expect("Remote players need a connector (see PlayerFactory::with_remote)");
+                connect(player_id, self.configuration, address, default_name)
+            }
+        }
+    }
+
+    /// Creates a computer player of the given skill
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player, which is its seat
+    /// * `level` - How strong the player is
+    /// * `name` - The name of the player
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::{Player, PlayerFactory, SkillLevel};
+    /// # use rules::Configuration;
+    ///
+    /// let config = Configuration::default();
+    /// let player = PlayerFactory::new(&config).create_at_level(1, SkillLevel::Expert, "Deep Pips");
+    /// assert_eq!(player.name(), "Deep Pips");
+    /// ```
+    pub fn create_at_level(&self, player_id: u8, level: SkillLevel, name: &str) -> Box<dyn Player> {
+        match level {
+            SkillLevel::Beginner => Box::new(RandomPlayer::new(player_id, self.configuration, name)),
+            SkillLevel::Intermediate => Box::new(GreedyPlayer::new(player_id, self.configuration, name)),
+            SkillLevel::Expert => {
+                let options = SearchOptions {
+                    iterations: Difficulty::Hard.iterations(),
+                    determinizations: EXPERT_DETERMINIZATIONS,
+                    play_model: PlayModel::Suited { affinity: EXPERT_AFFINITY },
+                    ..SearchOptions::default()
+                };
+                let mut player = DominoesPlayer::with_options(player_id, self.configuration, options);
+                player.set_name(name);
+                player.set_opening_book(OpeningBook::embedded(self.configuration));
+                Box::new(player)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_spec_parse() {
+        assert_eq!("human".parse::<PlayerSpec>(), Ok(PlayerSpec::Human { name: None }));
+        assert_eq!("human:Alice".parse::<PlayerSpec>(), Ok(PlayerSpec::Human { name: Some("Alice".to_string()) }));
+        assert_eq!("mcts".parse::<PlayerSpec>(), Ok(PlayerSpec::Mcts { iterations: 1000 }));
+        assert_eq!("mcts:easy".parse::<PlayerSpec>(), Ok(PlayerSpec::Mcts { iterations: 100 }));
+        assert_eq!("mcts:250".parse::<PlayerSpec>(), Ok(PlayerSpec::Mcts { iterations: 250 }));
+        assert_eq!("greedy".parse::<PlayerSpec>(), Ok(PlayerSpec::Greedy));
+        assert_eq!("score-chaser".parse::<PlayerSpec>(), Ok(PlayerSpec::ScoreChaser));
+        assert_eq!("random".parse::<PlayerSpec>(), Ok(PlayerSpec::Random { seed: None }));
+        assert_eq!("random:42".parse::<PlayerSpec>(), Ok(PlayerSpec::Random { seed: Some(42) }));
+
+        assert_eq!("beginner".parse::<PlayerSpec>(), Ok(PlayerSpec::Level(SkillLevel::Beginner)));
+        assert_eq!("expert".parse::<PlayerSpec>(), Ok(PlayerSpec::Level(SkillLevel::Expert)));
+        let remote = "remote:localhost:7878".parse::<PlayerSpec>();
+        assert_eq!(remote, Ok(PlayerSpec::Remote { address: "localhost:7878".to_string() }));
+        assert!(remote.unwrap().is_remote());
+
+        let invalid_specs = [
+            "", "human:", "mcts:0", "mcts:expert", "greedy:fast", "score-chaser:5", "random:x", "expert:10", "remote",
+            "remote:localhost", "remote::7878", "remote:localhost:port",
+        ];
+        for invalid in invalid_specs {
+            assert!(invalid.parse::<PlayerSpec>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_player_spec_display_round_trip() {
+        let specs = [
+            "human", "human:Alice", "mcts:5000", "greedy", "score-chaser", "random", "random:42", "beginner", "intermediate",
+            "expert", "remote:192.168.1.10:7878",
+        ];
+        for spec in specs {
+            assert_eq!(spec.parse::<PlayerSpec>().unwrap().to_string(), spec);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_player_spec_serde() {
+        let specs = vec![PlayerSpec::Human { name: Some("Alice".to_string()) }, PlayerSpec::Mcts { iterations: 100 }];
+        let json = serde_json::to_string(&specs).unwrap();
+        assert_eq!(json, r#"["human:Alice","mcts:100"]"#);
+        assert_eq!(serde_json::from_str::<Vec<PlayerSpec>>(&json).unwrap(), specs);
+        assert!(serde_json::from_str::<PlayerSpec>(r#""robot""#).is_err());
+        assert_eq!(serde_json::to_string(&Difficulty::Hard).unwrap(), r#""hard""#);
+    }
+
+    #[test]
+    fn test_player_factory_create() {
+        let config = Configuration::default();
+        let factory = PlayerFactory::new(&config);
+
+        let human = factory.create(0, &PlayerSpec::Human { name: Some("Alice".to_string()) }, "Player 0");
+        assert_eq!(human.name(), "Alice");
+        assert!(human.is_human());
+
+        let computer = factory.create(1, &PlayerSpec::Mcts { iterations: 10 }, "Bob");
+        assert_eq!(computer.name(), "Bob");
+        assert_eq!(computer.id(), 1);
+        assert!(!computer.is_human());
+
+        for level in [SkillLevel::Beginner, SkillLevel::Intermediate, SkillLevel::Expert] {
+            let computer = factory.create(1, &PlayerSpec::Level(level), "Carol");
+            assert_eq!(computer.name(), "Carol");
+            assert!(!computer.is_human());
+        }
+
+        // The connector is given the address and the name
+        let factory = factory.with_remote(|player_id, configuration, address, name| {
+            Box::new(GreedyPlayer::new(player_id, configuration, &format!("{name}@{address}")))
+        });
+        let remote = factory.create(1, &PlayerSpec::Remote { address: "localhost:7878".to_string() }, "Dave");
+        assert_eq!(remote.name(), "Dave@localhost:7878");
+        assert_eq!(remote.id(), 1);
+    }
+}
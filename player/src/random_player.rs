@@ -0,0 +1,212 @@
+//! Random player implementation
+//!
+//! This module provides a computer player that takes a legal action chosen at random. It is the easiest opponent, e.g. for
+//! players who are learning the rules. If the configuration has a seed, the player makes the same choices every time (see
+//! `rules::random`).
+
+use rand::SeedableRng;
+use rand::seq::IndexedRandom;
+
+use dominoes_state::{Action, DominoesState};
+use crate::{Hand, Player};
+use rules::{Configuration, GameRng, RngStream};
+
+/// A computer player that takes any legal action, chosen at random
+///
+/// # Examples
+/// ```rust
+/// # use player::{Player, RandomPlayer};
+/// # use rules::Configuration;
+///
+/// let config = Configuration::default();
+/// let player = RandomPlayer::new(1, &config, "Randy");
+/// assert!(!player.is_human());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomPlayer {
+    /// Unique identifier for this player in the game
+    player_id: u8,
+    /// The tiles currently held by this player
+    hand: Hand,
+    /// Display name for this player
+    name: String,
+    /// Chooses the actions
+    rng: GameRng,
+}
+
+impl RandomPlayer {
+    /// Creates a new random player
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `configuration` - Game rules and settings reference
+    /// * `name` - Display name for this player
+    pub fn new(player_id: u8, configuration: &Configuration, name: &str) -> Self {
+        Self {
+            player_id,
+            hand: Hand::new(),
+            name: name.to_string(),
+            rng: configuration.rng(RngStream::Player(player_id)),
+        }
+    }
+
+    /// Returns the player with its choices made by a random number generator with the given seed
+    ///
+    /// This makes the player's choices repeatable without seeding the whole game, e.g. to replay a game found by a fuzzer.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use player::{Player, RandomPlayer};
+    /// # use rules::Configuration;
+    ///
+    /// let config = Configuration::default();
+    /// let player = RandomPlayer::new(1, &config, "Randy").with_seed(42);
+    /// assert_eq!(player.name(), "Randy");
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = GameRng::seed_from_u64(seed);
+        self
+    }
+}
+
+impl Player for RandomPlayer {
+    fn reset(&mut self) {
+        self.hand = Hand::new();
+    }
+
+    fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+        self.hand = hand;
+    }
+
+    fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+        let mut new_state = state.clone();
+        let actions = state.legal_actions(&self.hand);
+        let Some(action) = actions.choose(&mut self.rng) else {
+            new_state.pass();
+            return (Action::pass(self.player_id), new_state);
+        };
+
+        // The legal actions are for the player whose turn it is, which is this player
+        if let Some(tile) = action.tile_drawn {
+            new_state.draw_tile();
+            self.hand.add_tile(tile);
+        } else if let Some((tile, end)) = action.tile_played {
+            self.hand.remove_tile(&tile);
+            new_state.play_tile_unchecked(tile, end);
+        } else {
+            new_state.pass();
+        }
+        (action.clone(), new_state)
+    }
+
+    fn has_playable_tile(&self, state: &DominoesState) -> bool {
+        self.hand
+            .tiles()
+            .iter()
+            .any(|tile| state.can_play_tile(tile, None))
+    }
+
+    fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> u8 {
+        self.player_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::Tile;
+
+    #[test]
+    fn test_random_player_takes_a_legal_action() {
+        let config = Configuration::default();
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((5, 6)));
+        hand.add_tile(Tile::from((4, 6)));
+        hand.add_tile(Tile::from((0, 0)));
+        for _ in 0..10 {
+            let mut player = RandomPlayer::new(0, &config, "Randy");
+            player.set_up_from(hand.clone(), &state);
+
+            let (action, new_state) = player.my_turn(&state);
+            assert!(state.legal_actions(&hand).contains(&action));
+            assert_eq!(new_state.layout.nodes.len(), 2);
+            assert_eq!(player.hand().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_random_player_draws_without_a_playable_tile() {
+        let config = Configuration::default();
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((0, 0)));
+        let mut player = RandomPlayer::new(0, &config, "Randy");
+        player.set_up_from(hand, &state);
+
+        let (action, new_state) = player.my_turn(&state);
+        assert!(action.is_draw());
+        assert_eq!(player.hand().len(), 2);
+        assert_eq!(new_state.boneyard.count(), state.boneyard.count() - 1);
+    }
+
+    #[test]
+    fn test_random_player_with_seed_is_repeatable() {
+        let config = Configuration::default().with_seed(3);
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((3, 3)), None);
+
+        let mut hand = Hand::new();
+        for tile in [(0, 3), (1, 3), (2, 3), (3, 4), (3, 5), (3, 6)] {
+            hand.add_tile(Tile::from(tile));
+        }
+        let choices = || -> Vec<Action> {
+            let mut player = RandomPlayer::new(0, &config, "Randy");
+            (0..5)
+                .map(|_| {
+                    player.set_up_from(hand.clone(), &state);
+                    player.my_turn(&state).0
+                })
+                .collect()
+        };
+        assert_eq!(choices(), choices());
+    }
+
+    #[test]
+    fn test_random_player_with_seed() {
+        let config = Configuration::default();
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((3, 3)), None);
+
+        let mut hand = Hand::new();
+        for tile in [(0, 3), (1, 3), (2, 3), (3, 4), (3, 5), (3, 6)] {
+            hand.add_tile(Tile::from(tile));
+        }
+        let choices = |seed| -> Vec<Action> {
+            let mut player = RandomPlayer::new(0, &config, "Randy").with_seed(seed);
+            (0..5)
+                .map(|_| {
+                    player.set_up_from(hand.clone(), &state);
+                    player.my_turn(&state).0
+                })
+                .collect()
+        };
+        assert_eq!(choices(7), choices(7));
+    }
+}
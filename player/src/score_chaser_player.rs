@@ -0,0 +1,160 @@
+//! Score chaser player implementation
+//!
+//! This module provides a computer player that does not search. It always makes the play that scores the most points right now,
+//! e.g. in All Fives, and otherwise sheds the most pips. It is a baseline for benchmarks and tournaments that plays for points
+//! rather than position (compare `GreedyPlayer`).
+
+use dominoes_state::{Action, DominoesState, scorer};
+use crate::{Hand, Player};
+use rules::Configuration;
+
+/// A computer player that always makes the play that scores the most points right now
+///
+/// # Examples
+/// ```rust
+/// # use player::{Player, ScoreChaserPlayer};
+/// # use rules::Configuration;
+///
+/// let config = Configuration::default();
+/// let player = ScoreChaserPlayer::new(1, &config, "Chase");
+/// assert!(!player.is_human());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScoreChaserPlayer {
+    /// Unique identifier for this player in the game
+    player_id: u8,
+    /// The game configuration
+    configuration: Configuration,
+    /// The tiles currently held by this player
+    hand: Hand,
+    /// Display name for this player
+    name: String,
+}
+
+impl ScoreChaserPlayer {
+    /// Creates a new score chaser player
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique identifier for this player
+    /// * `configuration` - Game rules and settings reference
+    /// * `name` - Display name for this player
+    pub fn new(player_id: u8, configuration: &Configuration, name: &str) -> Self {
+        Self {
+            player_id,
+            configuration: configuration.clone(),
+            hand: Hand::new(),
+            name: name.to_string(),
+        }
+    }
+
+    // Returns the points scored by a play, or 0 if plays do not score in the variation
+    fn points(&self, state: &DominoesState, action: &Action) -> u32 {
+        let (Some(scorer), Some((tile, end))) = (scorer(self.configuration.variation()), action.tile_played) else {
+            return 0;
+        };
+        let mut after = state.clone();
+        after.play_tile_unchecked(tile, end);
+        scorer.score(&after.layout)
+    }
+}
+
+impl Player for ScoreChaserPlayer {
+    fn reset(&mut self) {
+        self.hand = Hand::new();
+    }
+
+    fn set_up_from(&mut self, hand: Hand, _state: &DominoesState) {
+        self.hand = hand;
+    }
+
+    fn my_turn(&mut self, state: &DominoesState) -> (Action, DominoesState) {
+        let mut new_state = state.clone();
+
+        // Ties in points are broken by the pips shed. Without a playable tile, the only legal action is a draw or a pass.
+        let best = state
+            .legal_actions(&self.hand)
+            .into_iter()
+            .max_by_key(|action| (self.points(state, action), action.tile_played.map_or(0, |(tile, _)| tile.score())));
+        let Some(action) = best else {
+            new_state.pass();
+            return (Action::pass(self.player_id), new_state);
+        };
+
+        if let Some(tile) = action.tile_drawn {
+            new_state.draw_tile();
+            self.hand.add_tile(tile);
+        } else if let Some((tile, end)) = action.tile_played {
+            self.hand.remove_tile(&tile);
+            new_state.play_tile_unchecked(tile, end);
+        } else {
+            new_state.pass();
+        }
+        (action, new_state)
+    }
+
+    fn has_playable_tile(&self, state: &DominoesState) -> bool {
+        self.hand
+            .tiles()
+            .iter()
+            .any(|tile| state.can_play_tile(tile, None))
+    }
+
+    fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    fn hand_mut(&mut self) -> &mut Hand {
+        &mut self.hand
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> u8 {
+        self.player_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Tile, Variation};
+
+    #[test]
+    fn test_score_chaser_player_plays_for_points() {
+        let config = Configuration::new(2, Variation::AllFives, 6, 7);
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((5, 5)), None);
+        state.whose_turn = 1;
+
+        // The five-zero leaves 10 + 0 = 10, and the six-five leaves 16, which scores nothing despite its pips
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((5, 6)));
+        hand.add_tile(Tile::from((0, 5)));
+        let mut player = ScoreChaserPlayer::new(1, &config, "Chase");
+        player.set_up_from(hand, &state);
+
+        let (action, new_state) = player.my_turn(&state);
+        assert_eq!(action.tile_played.map(|(tile, _)| tile), Some(Tile::from((0, 5))));
+        assert_eq!(new_state.layout.nodes.len(), 2);
+        assert_eq!(player.hand().len(), 1);
+    }
+
+    #[test]
+    fn test_score_chaser_player_sheds_pips_without_scoring() {
+        let config = Configuration::default();
+        let mut state = DominoesState::new(&config);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        state.whose_turn = 1;
+
+        let mut hand = Hand::new();
+        hand.add_tile(Tile::from((1, 6)));
+        hand.add_tile(Tile::from((4, 6)));
+        let mut player = ScoreChaserPlayer::new(1, &config, "Chase");
+        player.set_up_from(hand, &state);
+
+        let (action, _) = player.my_turn(&state);
+        assert_eq!(action.tile_played.map(|(tile, _)| tile), Some(Tile::from((4, 6))));
+    }
+}
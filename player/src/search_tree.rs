@@ -0,0 +1,342 @@
+//! Search trees
+//!
+//! This module builds the top levels of a search tree for a position, so that a viewer can show why a computer player chose its
+//! action: which actions it considered, how often it visited each one, how good each one looked, and the line of play that it
+//! expects (the principal variation). The tree is the one built by the search that chose the action. If the search was split
+//! between threads, sampled deals, or parts, their trees are merged (see `SearchTree::merge`). With the `serde` feature, a tree
+//! can be serialized, e.g. as JSON.
+
+use hidden_game_player::State;
+use hidden_game_player::mcts::{ResponseGenerator, Rollout};
+
+use dominoes_state::{Action, DominoesState};
+
+/// A node of a search tree
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchNode {
+    /// The action that leads to this node, or `None` for the root
+    pub action: Option<Action>,
+    /// The number of iterations that passed through this node
+    pub visits: u32,
+    /// The mean value of the iterations, from 0 to 1, for the player who took the action (or, at the root, the player to move)
+    pub value: f32,
+    /// The nodes after each action that was considered, most visited first
+    pub children: Vec<SearchNode>,
+}
+
+/// The top levels of a search tree
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::Action;
+/// # use player::{SearchNode, SearchTree};
+///
+/// let leaf = |action, visits| SearchNode { action: Some(action), visits, value: 0.5, children: vec![] };
+/// let children = vec![leaf(Action::pass(0), 7), leaf(Action::pass(1), 3)];
+/// let root = SearchNode { action: None, visits: 10, value: 0.5, children };
+/// let tree = SearchTree::new(0, root);
+/// assert_eq!(tree.principal_variation, vec![Action::pass(0)]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchTree {
+    /// The player to move at the root
+    pub player_id: u8,
+    /// The number of iterations
+    pub iterations: u32,
+    /// The most visited action at each level, starting at the root
+    pub principal_variation: Vec<Action>,
+    /// The root of the tree
+    pub root: SearchNode,
+}
+
+impl SearchTree {
+    /// Creates a tree from its root, and determines its principal variation
+    ///
+    /// # Arguments
+    /// * `player_id` - The player to move at the root
+    /// * `root` - The root of the tree. Its children must be sorted with the most visited first.
+    pub fn new(player_id: u8, root: SearchNode) -> Self {
+        let mut principal_variation = Vec::new();
+        let mut node = &root;
+        while let Some(child) = node.children.first() {
+            principal_variation.extend(child.action.clone());
+            node = child;
+        }
+        Self { player_id, iterations: root.visits, principal_variation, root }
+    }
+
+    /// Searches a position and returns the top levels of the tree
+    ///
+    /// # Arguments
+    /// * `state` - The position. The player to move is the player whose turn it is.
+    /// * `rg` - Generates the actions considered at each node
+    /// * `rollout` - Estimates the value of a node below the top levels
+    /// * `exploration` - The exploration constant of UCB1. Larger values spread the visits more evenly.
+    /// * `iterations` - The number of iterations
+    /// * `depth` - The number of levels of nodes below the root
+    pub fn search<G, R>(state: &DominoesState, rg: &G, rollout: &R, exploration: f32, iterations: u32, depth: usize) -> Self
+    where
+        G: ResponseGenerator<State = DominoesState>,
+        R: Rollout<State = DominoesState, ResponseGenerator = G>,
+    {
+        let player_id = state.whose_turn();
+        let mut root = Node::new(None, state.clone());
+        for _ in 0..iterations {
+            let outcome = root.iterate(rg, rollout, exploration, depth);
+            root.record(outcome, player_id);
+        }
+        Self::new(player_id, root.into_search_node())
+    }
+
+    /// Returns the number of visits of each action at the root, most visited first
+    pub fn visits(&self) -> Vec<(Action, u32)> {
+        self.root.children.iter().filter_map(|child| Some((child.action.clone()?, child.visits))).collect()
+    }
+
+    /// Merges the tree with another tree of the same position, e.g. the tree of another thread or sampled deal
+    ///
+    /// The visits of the same action are added up, and its values are averaged, weighted by the visits. A draw is the same
+    /// action whichever tile it draws (see `Action::is_same_choice`), so the draws of different sampled deals are merged.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::Action;
+    /// # use player::{SearchNode, SearchTree};
+    ///
+    /// let leaf = |player_id, visits| SearchNode { action: Some(Action::pass(player_id)), visits, value: 0.5, children: vec![] };
+    /// let tree = |visits: [u32; 2]| {
+    ///     let children = vec![leaf(0, visits[0]), leaf(1, visits[1])];
+    ///     SearchTree::new(0, SearchNode { action: None, visits: visits.iter().sum(), value: 0.5, children })
+    /// };
+    /// let merged = tree([3, 2]).merge(tree([2, 1]));
+    /// assert_eq!(merged.iterations, 8);
+    /// assert_eq!(merged.visits(), vec![(Action::pass(0), 5), (Action::pass(1), 3)]);
+    /// ```
+    pub fn merge(self, other: SearchTree) -> Self {
+        let mut root = self.root;
+        root.merge(other.root);
+        Self::new(self.player_id, root)
+    }
+
+    /// Returns the top levels of the tree
+    ///
+    /// # Arguments
+    /// * `depth` - The number of levels of nodes below the root to keep
+    pub fn truncated(self, depth: usize) -> Self {
+        let mut root = self.root;
+        root.truncate(depth);
+        Self::new(self.player_id, root)
+    }
+}
+
+impl SearchNode {
+    // Adds the visits and values of the same node in another tree, and merges their children
+    fn merge(&mut self, other: SearchNode) {
+        let visits = self.visits + other.visits;
+        if visits > 0 {
+            self.value = (self.value * self.visits as f32 + other.value * other.visits as f32) / visits as f32;
+        }
+        self.visits = visits;
+        for child in other.children {
+            let same = self.children.iter_mut().find(|node| match (&node.action, &child.action) {
+                (Some(action), Some(other)) => action.is_same_choice(other),
+                _ => false,
+            });
+            match same {
+                Some(node) => node.merge(child),
+                None => self.children.push(child),
+            }
+        }
+        self.children.sort_by_key(|child| std::cmp::Reverse(child.visits));
+    }
+
+    // Removes the nodes that are more than `depth` levels below this one
+    fn truncate(&mut self, depth: usize) {
+        match depth.checked_sub(1) {
+            Some(depth) => self.children.iter_mut().for_each(|child| child.truncate(depth)),
+            None => self.children.clear(),
+        }
+    }
+}
+
+// The value of a position for a player, from 0 to 1
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Outcome {
+    pub(crate) value: f32,
+    pub(crate) player_id: u8,
+}
+
+impl Outcome {
+    // Returns the value for another player. Whatever is good for one player is assumed to be bad for the others.
+    pub(crate) fn value_for(self, player_id: u8) -> f32 {
+        if player_id == self.player_id { self.value } else { 1.0 - self.value }
+    }
+}
+
+// A node of the tree while it is being built
+struct Node {
+    action: Option<Action>,
+    state: DominoesState,
+    visits: u32,
+    // The sum of the values of the iterations for the player who took the action
+    total: f32,
+    children: Vec<Node>,
+    expanded: bool,
+}
+
+impl Node {
+    fn new(action: Option<Action>, state: DominoesState) -> Self {
+        Self { action, state, visits: 0, total: 0.0, children: Vec::new(), expanded: false }
+    }
+
+    // Runs one iteration below this node, and returns the value of the position that it reached
+    fn iterate<G, R>(&mut self, rg: &G, rollout: &R, exploration: f32, depth: usize) -> Outcome
+    where
+        G: ResponseGenerator<State = DominoesState>,
+        R: Rollout<State = DominoesState, ResponseGenerator = G>,
+    {
+        if depth == 0 || self.state.is_terminal() {
+            return self.evaluate(rg, rollout);
+        }
+        if !self.expanded {
+            self.children = rg
+                .generate(&self.state)
+                .into_iter()
+                .map(|action| Node::new(Some(action.clone()), self.state.apply(&action)))
+                .collect();
+            self.expanded = true;
+        }
+        if self.children.is_empty() {
+            return self.evaluate(rg, rollout);
+        }
+
+        let mover = self.state.whose_turn();
+        let parent_visits = self.visits;
+        let child = self
+            .children
+            .iter_mut()
+            .max_by(|a, b| a.ucb(parent_visits, exploration).total_cmp(&b.ucb(parent_visits, exploration)))
+            .expect("There is at least one child");
+        let outcome = child.iterate(rg, rollout, exploration, depth - 1);
+        child.record(outcome, mover);
+        outcome
+    }
+
+    // Estimates the value of the node with a rollout
+    fn evaluate<G, R>(&self, rg: &G, rollout: &R) -> Outcome
+    where
+        G: ResponseGenerator<State = DominoesState>,
+        R: Rollout<State = DominoesState, ResponseGenerator = G>,
+    {
+        Outcome { value: rollout.play(&self.state, rg), player_id: self.state.whose_turn() }
+    }
+
+    // Adds the outcome of an iteration, for the player who took the action
+    fn record(&mut self, outcome: Outcome, player_id: u8) {
+        self.visits += 1;
+        self.total += outcome.value_for(player_id);
+    }
+
+    // Returns the UCB1 score of the node. Nodes that have not been visited are tried first.
+    fn ucb(&self, parent_visits: u32, exploration: f32) -> f32 {
+        if self.visits == 0 {
+            return f32::INFINITY;
+        }
+        let visits = self.visits as f32;
+        self.total / visits + exploration * ((parent_visits.max(1) as f32).ln() / visits).sqrt()
+    }
+
+    // Converts the node and its children to a `SearchNode`, dropping the children that were never visited
+    fn into_search_node(self) -> SearchNode {
+        let mut children: Vec<SearchNode> =
+            self.children.into_iter().filter(|child| child.visits > 0).map(Node::into_search_node).collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.visits));
+        let value = if self.visits == 0 { 0.0 } else { self.total / self.visits as f32 };
+        SearchNode { action: self.action, visits: self.visits, value, children }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DominoesResponseGenerator, DominoesRollout};
+    use rules::{Configuration, Tile};
+
+    // Returns a node with the given action and visits
+    fn node(action: Action, visits: u32, children: Vec<SearchNode>) -> SearchNode {
+        SearchNode { action: Some(action), visits, value: 0.5, children }
+    }
+
+    #[test]
+    fn test_principal_variation() {
+        let root = SearchNode {
+            action: None,
+            visits: 12,
+            value: 0.5,
+            children: vec![
+                node(Action::pass(0), 8, vec![node(Action::pass(1), 5, vec![]), node(Action::pass(1), 3, vec![])]),
+                node(Action::pass(0), 4, vec![]),
+            ],
+        };
+        let tree = SearchTree::new(0, root);
+        assert_eq!(tree.iterations, 12);
+        assert_eq!(tree.principal_variation, vec![Action::pass(0), Action::pass(1)]);
+
+        let leaf = SearchTree::new(1, SearchNode { action: None, visits: 0, value: 0.0, children: vec![] });
+        assert!(leaf.principal_variation.is_empty());
+    }
+
+    #[test]
+    fn test_search_terminal_position() {
+        let configuration = Configuration::default();
+        let mut state = DominoesState::new(&configuration);
+        state.mark_game_over(Some(0));
+        let tree = SearchTree::search(&state, &DominoesResponseGenerator::new(), &DominoesRollout::new(), 1.414, 5, 2);
+        assert_eq!(tree.iterations, 5);
+        assert!(tree.root.children.is_empty());
+        assert!((0.0..=1.0).contains(&tree.root.value));
+    }
+
+    #[test]
+    fn test_merge_and_truncate() {
+        let (draw, other_draw) = (Action::draw(0, Tile::from((1, 2))), Action::draw(0, Tile::from((3, 4))));
+        let first = SearchTree::new(0, SearchNode {
+            action: None,
+            visits: 4,
+            value: 0.5,
+            children: vec![
+                SearchNode { action: Some(draw.clone()), visits: 3, value: 1.0, children: vec![node(Action::pass(1), 3, vec![])] },
+                node(Action::pass(0), 1, vec![]),
+            ],
+        });
+        let second = SearchTree::new(0, SearchNode {
+            action: None,
+            visits: 2,
+            value: 0.5,
+            children: vec![
+                SearchNode { action: Some(other_draw), visits: 1, value: 0.0, children: vec![] },
+                node(Action::pass(0), 1, vec![]),
+            ],
+        });
+
+        // The draws of different tiles are the same action, and their values are weighted by their visits
+        let merged = first.merge(second);
+        assert_eq!(merged.iterations, 6);
+        assert_eq!(merged.visits(), vec![(draw.clone(), 4), (Action::pass(0), 2)]);
+        assert_eq!(merged.root.children[0].value, 0.75);
+        assert_eq!(merged.principal_variation, vec![draw.clone(), Action::pass(1)]);
+
+        let truncated = merged.truncated(1);
+        assert!(truncated.root.children.iter().all(|child| child.children.is_empty()));
+        assert_eq!(truncated.principal_variation, vec![draw]);
+    }
+
+    #[test]
+    fn test_outcome_value_for() {
+        let outcome = Outcome { value: 0.75, player_id: 1 };
+        assert_eq!(outcome.value_for(1), 0.75);
+        assert_eq!(outcome.value_for(0), 0.25);
+    }
+}
@@ -0,0 +1,129 @@
+//! Terminals
+//!
+//! This module provides the input and output of a person playing the game (see `HumanPlayer`). A terminal shows lines of text and
+//! asks questions, so the same player can be played at the console, in a window, or from a script in a test.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// The input and output of a person playing the game
+///
+/// # Examples
+/// ```rust
+/// # use player::{ScriptedTerminal, Terminal};
+///
+/// let mut terminal = ScriptedTerminal::new(["3"]);
+/// terminal.show("Your hand: 0: [1|2]");
+/// assert_eq!(terminal.ask("Choose a tile: "), "3");
+/// assert_eq!(terminal.output(), vec!["Your hand: 0: [1|2]", "Choose a tile: "]);
+/// ```
+pub trait Terminal: fmt::Debug {
+    /// Shows a line of text
+    fn show(&mut self, line: &str);
+
+    /// Asks a question and returns the answer, with any surrounding whitespace removed
+    ///
+    /// # Arguments
+    /// * `prompt` - The question, which is shown on the same line as the answer
+    fn ask(&mut self, prompt: &str) -> String;
+}
+
+/// A terminal that shows text on the standard output and reads answers from the standard input
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleTerminal;
+
+impl Terminal for ConsoleTerminal {
+    fn show(&mut self, line: &str) {
+        println!("{line}");
+    }
+
+    fn ask(&mut self, prompt: &str) -> String {
+        print!("{prompt}");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read input");
+        input.trim().to_string()
+    }
+}
+
+/// A terminal that gives answers from a script and records what it is shown, e.g. for testing
+///
+/// Clones share the script and the record, so a test can keep a clone of a terminal that it gives to a player and check what the
+/// player showed.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedTerminal {
+    /// The answers that have not been given yet
+    answers: Rc<RefCell<VecDeque<String>>>,
+    /// The lines shown and the questions asked, in order
+    output: Rc<RefCell<Vec<String>>>,
+}
+
+impl ScriptedTerminal {
+    /// Creates a terminal that gives the answers in order
+    ///
+    /// # Arguments
+    /// * `answers` - The answers to the questions, in the order that they are asked
+    pub fn new<I, S>(answers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            answers: Rc::new(RefCell::new(answers.into_iter().map(Into::into).collect())),
+            output: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Returns the lines shown and the questions asked so far, in order
+    pub fn output(&self) -> Vec<String> {
+        self.output.borrow().clone()
+    }
+
+    /// Returns the number of answers that have not been given yet
+    pub fn remaining(&self) -> usize {
+        self.answers.borrow().len()
+    }
+}
+
+impl Terminal for ScriptedTerminal {
+    fn show(&mut self, line: &str) {
+        self.output.borrow_mut().push(line.to_string());
+    }
+
+    // Panics if the script has run out of answers, since a player would otherwise ask forever
+    fn ask(&mut self, prompt: &str) -> String {
+        self.output.borrow_mut().push(prompt.to_string());
+        let answer = self.answers.borrow_mut().pop_front().expect("The script has no more answers");
+        answer.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_terminal() {
+        let terminal = ScriptedTerminal::new([" 1 ", "undo"]);
+        let mut player_terminal = terminal.clone();
+        player_terminal.show("Open ends: 6");
+        assert_eq!(player_terminal.ask("Choose a tile: "), "1");
+        assert_eq!(terminal.remaining(), 1);
+        assert_eq!(player_terminal.ask("Choose a tile: "), "undo");
+
+        // Clones share the record
+        assert_eq!(terminal.output(), vec!["Open ends: 6", "Choose a tile: ", "Choose a tile: "]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The script has no more answers")]
+    fn test_scripted_terminal_out_of_answers() {
+        ScriptedTerminal::new(Vec::<String>::new()).ask("Choose a tile: ");
+    }
+}
@@ -0,0 +1,300 @@
+//! Inference about the other players' hands
+//!
+//! A player who draws or passes shows that they hold no tile that could be played, so they hold no tile with any of the values
+//! that could be played on. Such a value is called a void. A computer player keeps track of the voids of the other players by
+//! observing their actions (see `Player::observe`), and only samples hands for them that are consistent with what they have
+//! shown.
+//!
+//! A void lasts until the player draws a tile on a later turn, when the values that could be played on may be different. The
+//! drawn tile may hold any value except those, so only those remain voids.
+//!
+//! The tiles that a player plays also say something about the rest of their hand, though not for certain. How much they are
+//! believed to say is chosen by a `PlayModel`.
+
+use dominoes_state::{Action, DominoesState};
+use rules::Tile;
+
+/// How the plays of a player change the chances of the tiles they might still hold
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, DominoesState};
+/// # use player::{PlayModel, TileKnowledge};
+/// # use rules::{Configuration, Tile};
+///
+/// let mut state = DominoesState::new(&Configuration::default());
+/// let mut knowledge = TileKnowledge::new(2);
+/// knowledge.observe(&Action::play_unchecked(0, Tile::from((5, 6)), None), &state);
+///
+/// // Each play of a 6 makes every other 6 twice as likely
+/// let model = PlayModel::Suited { affinity: 2.0 };
+/// assert_eq!(model.weight(&knowledge, 0, Tile::from((3, 6))), 2.0);
+/// assert_eq!(model.weight(&knowledge, 0, Tile::from((3, 4))), 1.0);
+/// assert_eq!(PlayModel::Uniform.weight(&knowledge, 0, Tile::from((3, 6))), 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlayModel {
+    /// The plays say nothing, so every tile that a player might hold is as likely as any other
+    #[default]
+    Uniform,
+    /// Players tend to play from the values they hold the most of. Each tile played by a player that shares a value with a tile
+    /// multiplies the weight of the tile by `affinity`.
+    Suited {
+        /// How much more likely a tile is for each play that shares a value with it. 1.0 is the same as `Uniform`.
+        affinity: f64,
+    },
+}
+
+impl PlayModel {
+    /// Returns the relative likelihood that a player holds a tile, given the tiles they have played
+    ///
+    /// The weight is only meaningful compared to the weights of other tiles. It is 0 if the player cannot hold the tile.
+    ///
+    /// # Arguments
+    /// * `knowledge` - What is known about the player's hand
+    /// * `player_id` - The player
+    /// * `tile` - The tile
+    pub fn weight(&self, knowledge: &TileKnowledge, player_id: u8, tile: Tile) -> f64 {
+        if !knowledge.can_hold(player_id, tile) {
+            return 0.0;
+        }
+        match self {
+            PlayModel::Uniform => 1.0,
+            PlayModel::Suited { affinity } => affinity.powi(knowledge.plays_matching(player_id, tile) as i32),
+        }
+    }
+}
+
+/// What a player has deduced about the values that the other players do not hold
+///
+/// # Examples
+/// ```rust
+/// # use dominoes_state::{Action, DominoesState};
+/// # use player::TileKnowledge;
+/// # use rules::{Configuration, Tile};
+///
+/// let configuration = Configuration::default();
+/// let mut state = DominoesState::new(&configuration);
+/// state.play_tile_unchecked(Tile::from((6, 6)), None);
+/// state.whose_turn = 1;
+///
+/// // Bob passes, so he has no 6s
+/// let mut knowledge = TileKnowledge::new(2);
+/// knowledge.observe(&Action::pass(1), &state);
+/// assert!(knowledge.is_void(1, 6));
+/// assert!(!knowledge.can_hold(1, Tile::from((3, 6))));
+/// assert!(knowledge.can_hold(1, Tile::from((3, 4))));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TileKnowledge {
+    // For each player, the values of which they hold no tile, as bits
+    voids: Vec<u32>,
+    // For each player, the tiles they have played
+    played: Vec<Vec<Tile>>,
+    // The player, their voids before each observed action, and whether it was a play, so that the actions can be taken back
+    before: Vec<(u8, u32, bool)>,
+}
+
+impl TileKnowledge {
+    /// Creates knowledge of the players' hands in which nothing is known yet
+    ///
+    /// # Arguments
+    /// * `num_players` - The number of players in the game
+    pub fn new(num_players: usize) -> Self {
+        Self { voids: vec![0; num_players], played: vec![Vec::new(); num_players], before: Vec::new() }
+    }
+
+    /// Forgets everything, e.g. for a new round
+    pub fn reset(&mut self) {
+        self.voids.fill(0);
+        self.played.iter_mut().for_each(Vec::clear);
+        self.before.clear();
+    }
+
+    /// Learns from an action of a player
+    ///
+    /// A pass adds the values that could be played on to the player's voids. A draw replaces their voids with those values,
+    /// because the drawn tile may hold any of the others. A play does not change them, but it is remembered (see `PlayModel`).
+    ///
+    /// # Arguments
+    /// * `action` - The action
+    /// * `state` - The state before the action
+    pub fn observe(&mut self, action: &Action, state: &DominoesState) {
+        let player_id = action.player_id;
+        let voids = &mut self.voids[player_id as usize];
+        self.before.push((player_id, *voids, action.is_play()));
+        if action.is_draw() {
+            *voids = playable_values(state);
+        } else if action.is_pass() {
+            *voids |= playable_values(state);
+        }
+        if let Some((tile, _)) = action.tile_played {
+            self.played[player_id as usize].push(tile);
+        }
+    }
+
+    /// Takes back the last action observed
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use dominoes_state::{Action, DominoesState};
+    /// # use player::TileKnowledge;
+    /// # use rules::{Configuration, Tile};
+    ///
+    /// let mut state = DominoesState::new(&Configuration::default());
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    /// let mut knowledge = TileKnowledge::new(2);
+    /// knowledge.observe(&Action::pass(0), &state);
+    /// knowledge.forget();
+    /// assert!(!knowledge.is_void(0, 6));
+    /// ```
+    pub fn forget(&mut self) {
+        if let Some((player_id, voids, played)) = self.before.pop() {
+            self.voids[player_id as usize] = voids;
+            if played {
+                self.played[player_id as usize].pop();
+            }
+        }
+    }
+
+    /// Returns true if a player is known to hold no tile with a value
+    ///
+    /// # Arguments
+    /// * `player_id` - The player
+    /// * `value` - The number of pips
+    pub fn is_void(&self, player_id: u8, value: u8) -> bool {
+        self.voids[player_id as usize] & (1 << value) != 0
+    }
+
+    /// Returns true if a player might hold a tile, i.e. neither of its values is a void
+    ///
+    /// # Arguments
+    /// * `player_id` - The player
+    /// * `tile` - The tile
+    pub fn can_hold(&self, player_id: u8, tile: Tile) -> bool {
+        let (a, b) = tile.as_tuple();
+        !self.is_void(player_id, a) && !self.is_void(player_id, b)
+    }
+
+    /// Returns the number of tiles that a player has played that share a value with a tile
+    ///
+    /// # Arguments
+    /// * `player_id` - The player
+    /// * `tile` - The tile
+    pub fn plays_matching(&self, player_id: u8, tile: Tile) -> usize {
+        let (a, b) = tile.as_tuple();
+        self.played[player_id as usize]
+            .iter()
+            .filter(|played| {
+                let (c, d) = played.as_tuple();
+                c == a || c == b || d == a || d == b
+            })
+            .count()
+    }
+
+    /// Returns the values that a player is known to hold no tile of, in increasing order
+    ///
+    /// # Arguments
+    /// * `player_id` - The player
+    pub fn voids(&self, player_id: u8) -> Vec<u8> {
+        let voids = self.voids[player_id as usize];
+        (0..u32::BITS as u8).filter(|&value| voids & (1 << value) != 0).collect()
+    }
+}
+
+// Returns the values that the player whose turn it is could play on, as bits. Nothing is learned before the first tile, which
+// depends on the opening rule rather than on the values.
+fn playable_values(state: &DominoesState) -> u32 {
+    if state.layout.is_empty() {
+        return 0;
+    }
+    (0..state.layout.end_counts.len() as u8)
+        .filter(|&value| state.can_play_tile(&Tile::from((value, value)), Some(value)))
+        .fold(0, |bits, value| bits | 1 << value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rules::{Configuration, Variation};
+
+    #[test]
+    fn test_tile_knowledge_voids() {
+        let configuration = Configuration::new(3, Variation::AllFives, 6, 5);
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((3, 5)), None);
+        let mut knowledge = TileKnowledge::new(3);
+
+        // A play teaches nothing, and a pass makes the open values voids
+        state.whose_turn = 1;
+        knowledge.observe(&Action::play_unchecked(1, Tile::from((5, 6)), Some(5)), &state);
+        assert!(knowledge.voids(1).is_empty());
+        state.play_tile_unchecked(Tile::from((5, 6)), Some(5));
+        state.whose_turn = 2;
+        knowledge.observe(&Action::pass(2), &state);
+        assert_eq!(knowledge.voids(2), vec![3, 6]);
+        assert!(knowledge.voids(0).is_empty());
+
+        // Voids accumulate while the player passes, but a draw may bring back the values that could not be played on
+        state.play_tile_unchecked(Tile::from((1, 6)), Some(6));
+        knowledge.observe(&Action::pass(2), &state);
+        assert_eq!(knowledge.voids(2), vec![1, 3, 6]);
+        state.play_tile_unchecked(Tile::from((1, 4)), Some(1));
+        knowledge.observe(&Action::draw(2, Tile::from((0, 6))), &state);
+        assert_eq!(knowledge.voids(2), vec![3, 4]);
+        assert!(knowledge.can_hold(2, Tile::from((0, 6))));
+        assert!(!knowledge.can_hold(2, Tile::from((4, 6))));
+
+        // Observations are taken back in reverse order
+        knowledge.forget();
+        assert_eq!(knowledge.voids(2), vec![1, 3, 6]);
+        knowledge.forget();
+        knowledge.forget();
+        knowledge.forget();
+        assert!(knowledge.voids(1).is_empty() && knowledge.voids(2).is_empty());
+        knowledge.forget();
+
+        knowledge.observe(&Action::pass(2), &state);
+        knowledge.reset();
+        assert!(knowledge.voids(2).is_empty());
+    }
+
+    #[test]
+    fn test_tile_knowledge_plays() {
+        let mut state = DominoesState::new(&Configuration::default());
+        let mut knowledge = TileKnowledge::new(2);
+        knowledge.observe(&Action::play_unchecked(1, Tile::from((4, 4)), None), &state);
+        state.play_tile_unchecked(Tile::from((4, 4)), None);
+        knowledge.observe(&Action::play_unchecked(1, Tile::from((2, 4)), Some(4)), &state);
+        assert_eq!(knowledge.plays_matching(1, Tile::from((4, 6))), 2);
+        assert_eq!(knowledge.plays_matching(1, Tile::from((2, 6))), 1);
+        assert_eq!(knowledge.plays_matching(1, Tile::from((1, 6))), 0);
+        assert_eq!(knowledge.plays_matching(0, Tile::from((4, 6))), 0);
+
+        // A 4 is four times as likely, a 2 twice, but a void is impossible
+        let model = PlayModel::Suited { affinity: 2.0 };
+        assert_eq!(model.weight(&knowledge, 1, Tile::from((4, 6))), 4.0);
+        assert_eq!(model.weight(&knowledge, 1, Tile::from((2, 6))), 2.0);
+        state.play_tile_unchecked(Tile::from((2, 4)), Some(4));
+        knowledge.observe(&Action::pass(1), &state);
+        assert_eq!(model.weight(&knowledge, 1, Tile::from((2, 6))), 0.0);
+        assert_eq!(model.weight(&knowledge, 1, Tile::from((1, 6))), 1.0);
+
+        // Taking back a pass keeps the plays, and taking back a play removes it
+        knowledge.forget();
+        assert_eq!(knowledge.plays_matching(1, Tile::from((4, 6))), 2);
+        knowledge.forget();
+        assert_eq!(knowledge.plays_matching(1, Tile::from((4, 6))), 1);
+        knowledge.reset();
+        assert_eq!(knowledge.plays_matching(1, Tile::from((4, 6))), 0);
+    }
+
+    #[test]
+    fn test_tile_knowledge_before_first_tile() {
+        let state = DominoesState::new(&Configuration::default());
+        let mut knowledge = TileKnowledge::new(2);
+        knowledge.observe(&Action::pass(0), &state);
+        assert!(knowledge.voids(0).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Sampling of complete deals of the hidden tiles
+//!
+//! A perfect-information search needs a world in which every hand and the boneyard are known. A world is sampled by dealing the
+//! tiles that the searching player has not seen to the other players and the boneyard, so that every player gets a hand of
+//! the right size that holds only tiles they might hold (see `TileKnowledge`). The tiles are chosen in proportion to their
+//! weights, which are usually the probabilities of another player holding them.
+//!
+//! The hands of the players with the fewest tiles they might hold are dealt first, since they are the most likely to run out.
+//! Even so, a deal may leave a player without enough tiles, so several deals are tried before giving up.
+
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+
+use dominoes_state::Hand;
+use crate::TileKnowledge;
+use rules::Tile;
+
+// Number of deals tried before giving up on meeting the constraints
+const MAX_ATTEMPTS: usize = 10;
+
+/// A complete deal of the hidden tiles
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    /// The hand of each player, indexed by player ID. A player who is not dealt a hand has an empty one.
+    pub hands: Vec<Hand>,
+    /// The tiles in the boneyard, in the order they are drawn
+    pub boneyard: Vec<Tile>,
+}
+
+/// Deals the hidden tiles to the other players and the boneyard (see the module documentation)
+///
+/// # Examples
+/// ```rust
+/// # use rand::SeedableRng;
+/// # use player::WorldSampler;
+/// # use rules::{GameRng, Tile, TileMask};
+///
+/// let unseen: Vec<Tile> = TileMask::all(6).iter().collect();
+/// let sampler = WorldSampler::new(&unseen, &[0, 7], 14);
+/// let world = sampler.try_sample(&mut GameRng::seed_from_u64(1)).unwrap();
+/// assert!(world.hands[0].is_empty());
+/// assert_eq!(world.hands[1].len(), 7);
+/// assert_eq!(world.boneyard.len(), 14);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WorldSampler {
+    // The tiles to deal
+    unseen: Vec<Tile>,
+    // The size of the hand dealt to each player, indexed by player ID
+    hand_sizes: Vec<usize>,
+    // The number of tiles dealt to the boneyard
+    boneyard_count: usize,
+    // The weight of each tile, indexed by the tile's ordinal, or all the same if none
+    weights: Option<Vec<f64>>,
+    // What is known about the values that the players do not hold, if anything
+    tile_knowledge: Option<TileKnowledge>,
+}
+
+impl WorldSampler {
+    /// Creates a sampler that deals the tiles without constraints, each as likely as any other
+    ///
+    /// # Arguments
+    /// * `unseen` - The tiles to deal
+    /// * `hand_sizes` - The size of the hand dealt to each player, indexed by player ID. The searching player's is 0.
+    /// * `boneyard_count` - The number of tiles dealt to the boneyard. Any tiles left over are not dealt.
+    pub fn new(unseen: &[Tile], hand_sizes: &[usize], boneyard_count: usize) -> Self {
+        Self {
+            unseen: unseen.to_vec(),
+            hand_sizes: hand_sizes.to_vec(),
+            boneyard_count,
+            weights: None,
+            tile_knowledge: None,
+        }
+    }
+
+    /// Returns the sampler with the tiles chosen for the hands in proportion to their weights
+    ///
+    /// A tile with a weight of 0 is never dealt to a hand.
+    ///
+    /// # Arguments
+    /// * `weights` - The weight of each tile, indexed by the tile's ordinal, e.g. the probability of another player holding it
+    pub fn with_weights(mut self, weights: &[f64]) -> Self {
+        self.weights = Some(weights.to_vec());
+        self
+    }
+
+    /// Returns the sampler with a player only dealt tiles that they might hold
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rand::SeedableRng;
+    /// # use dominoes_state::{Action, DominoesState};
+    /// # use player::{TileKnowledge, WorldSampler};
+    /// # use rules::{Configuration, GameRng, Tile, TileMask};
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut state = DominoesState::new(&configuration);
+    /// state.play_tile_unchecked(Tile::from((6, 6)), None);
+    /// let mut knowledge = TileKnowledge::new(2);
+    /// knowledge.observe(&Action::pass(1), &state);
+    ///
+    /// // Player 1 holds 21 tiles, so they hold every tile without a 6
+    /// let unseen: Vec<Tile> = TileMask::all(configuration.set_id()).iter().filter(|&tile| tile != Tile::from((6, 6))).collect();
+    /// let sampler = WorldSampler::new(&unseen, &[0, 21], 6).with_tile_knowledge(&knowledge);
+    /// let world = sampler.try_sample(&mut GameRng::seed_from_u64(1)).unwrap();
+    /// assert!(world.boneyard.iter().all(|tile| tile.as_tuple().1 == 6));
+    /// ```
+    pub fn with_tile_knowledge(mut self, tile_knowledge: &TileKnowledge) -> Self {
+        self.tile_knowledge = Some(tile_knowledge.clone());
+        self
+    }
+
+    /// Returns a world in which every player holds a hand of the right size that meets the constraints
+    ///
+    /// # Returns
+    /// The world, or `None` if none was found, e.g. because a player might hold too few of the tiles, or there are too few tiles
+    pub fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<World> {
+        (0..MAX_ATTEMPTS).find_map(|_| self.deal(rng, true))
+    }
+
+    /// Returns a world that meets the constraints, if possible
+    ///
+    /// If no such world is found (see `try_sample`), a player without enough tiles that they might hold is dealt a smaller hand,
+    /// and the boneyard is smaller if too few tiles are left.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> World {
+        self.try_sample(rng).unwrap_or_else(|| self.deal(rng, false).expect("a partial deal always succeeds"))
+    }
+
+    // Returns the weight of a tile in a player's hand
+    fn weight(&self, player_id: u8, tile: &Tile) -> f64 {
+        if self.tile_knowledge.as_ref().is_some_and(|known| !known.can_hold(player_id, *tile)) {
+            return 0.0;
+        }
+        self.weights.as_ref().map_or(1.0, |weights| weights[tile.ordinal as usize])
+    }
+
+    // Deals the tiles once. If strict, returns None if a hand or the boneyard cannot be filled, and otherwise they are smaller.
+    fn deal<R: Rng + ?Sized>(&self, rng: &mut R, strict: bool) -> Option<World> {
+        let mut pool = self.unseen.clone();
+        let mut hands = vec![Hand::new(); self.hand_sizes.len()];
+
+        // The players who might hold the fewest tiles are dealt first
+        let possible = |player_id: u8, pool: &[Tile]| pool.iter().filter(|tile| self.weight(player_id, tile) > 0.0).count();
+        let mut order: Vec<u8> = (0..self.hand_sizes.len() as u8).filter(|&id| self.hand_sizes[id as usize] > 0).collect();
+        order.sort_by_key(|&player_id| possible(player_id, &pool));
+
+        for player_id in order {
+            let mut size = self.hand_sizes[player_id as usize];
+            let available = possible(player_id, &pool);
+            if available < size {
+                if strict {
+                    return None;
+                }
+                size = available;
+            }
+            let hand = &mut hands[player_id as usize];
+            let sample = pool.choose_multiple_weighted(rng, size, |tile| self.weight(player_id, tile)).ok()?;
+            for &tile in sample {
+                hand.add_tile(tile);
+            }
+            pool.retain(|tile| !hand.contains(tile));
+        }
+
+        if strict && pool.len() < self.boneyard_count {
+            return None;
+        }
+        pool.shuffle(rng);
+        pool.truncate(self.boneyard_count);
+        Some(World { hands, boneyard: pool })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use dominoes_state::{Action, DominoesState};
+    use rules::{Configuration, TileMask, Variation};
+
+    #[test]
+    fn test_world_sampler_complete_deals() {
+        let configuration = Configuration::new(3, Variation::Traditional, 6, 7);
+        let unseen: Vec<Tile> = TileMask::all(configuration.set_id()).iter().collect();
+        let sampler = WorldSampler::new(&unseen, &[0, 7, 7], 14);
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        for _ in 0..20 {
+            let world = sampler.try_sample(&mut rng).unwrap();
+            let mut dealt: Vec<Tile> = world.hands.iter().flat_map(|hand| hand.tiles().iter().copied()).collect();
+            dealt.extend(&world.boneyard);
+            dealt.sort();
+            assert_eq!(dealt, unseen);
+            assert_eq!(world.hands.iter().map(Hand::len).collect::<Vec<_>>(), vec![0, 7, 7]);
+        }
+
+        // There are too few tiles
+        let sampler = WorldSampler::new(&unseen, &[0, 7, 7], 15);
+        assert_eq!(sampler.try_sample(&mut rng), None);
+        assert_eq!(sampler.sample(&mut rng).boneyard.len(), 14);
+    }
+
+    #[test]
+    fn test_world_sampler_constraints() {
+        let configuration = Configuration::new(3, Variation::Traditional, 6, 7);
+        let mut state = DominoesState::new(&configuration);
+        state.play_tile_unchecked(Tile::from((6, 6)), None);
+        let mut knowledge = TileKnowledge::new(3);
+        knowledge.observe(&Action::pass(1), &state);
+        state.play_tile_unchecked(Tile::from((5, 6)), Some(6));
+        knowledge.observe(&Action::pass(2), &state);
+        let played = [Tile::from((6, 6)), Tile::from((5, 6))];
+        let unseen: Vec<Tile> = TileMask::all(configuration.set_id()).iter().filter(|tile| !played.contains(tile)).collect();
+
+        // Player 1 holds no 6, and player 2 holds no 6 or 5, so player 2 holds all 15 of the other tiles. They are dealt first,
+        // or player 1 would take some of them.
+        let sampler = WorldSampler::new(&unseen, &[0, 5, 15], 6).with_tile_knowledge(&knowledge);
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        for _ in 0..20 {
+            let world = sampler.try_sample(&mut rng).unwrap();
+            assert!(world.hands[1].tiles().iter().all(|tile| tile.as_tuple().1 == 5));
+            assert!(world.hands[2].tiles().iter().all(|tile| tile.as_tuple().1 < 5));
+            assert_eq!(world.hands[2].len(), 15);
+            assert_eq!(world.boneyard.len(), 6);
+        }
+
+        // A player who might hold too few tiles gets a smaller hand
+        let sampler = WorldSampler::new(&unseen, &[0, 0, 16], 6).with_tile_knowledge(&knowledge);
+        assert_eq!(sampler.try_sample(&mut rng), None);
+        assert_eq!(sampler.sample(&mut rng).hands[2].len(), 15);
+    }
+
+    #[test]
+    fn test_world_sampler_weights() {
+        let unseen = vec![Tile::from((0, 0)), Tile::from((0, 1)), Tile::from((1, 1))];
+        let mut weights = vec![0.0; 3];
+        weights[Tile::from((0, 1)).ordinal as usize] = 0.5;
+        let sampler = WorldSampler::new(&unseen, &[0, 1], 2).with_weights(&weights);
+        let world = sampler.try_sample(&mut ChaCha8Rng::seed_from_u64(5)).unwrap();
+        assert_eq!(world.hands[1].tiles(), &[Tile::from((0, 1))]);
+        assert_eq!(world.boneyard.len(), 2);
+    }
+}
@@ -0,0 +1,51 @@
+//! Benchmarks of the layout
+//!
+//! Run with `cargo bench -p rules`.
+
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use rules::{Configuration, Layout, Tile, Variation};
+
+// Returns the tiles of a double-nine set in the order in which they are attached, and the parent of each one. Each tile is
+// attached to the first node with an open end that it matches, so the layout is as large as the set allows.
+fn attachments(configuration: &Configuration) -> Vec<(Tile, Option<usize>)> {
+    let mut layout = Layout::new(configuration);
+    let mut remaining: Vec<Tile> = configuration.tiles().iter().copied().filter(|tile| *tile != Tile::from((9, 9))).collect();
+    let mut attachments = vec![(Tile::from((9, 9)), None)];
+    layout.attach_unchecked(Tile::from((9, 9)), None);
+    while let Some((position, parent)) = remaining.iter().enumerate().find_map(|(position, tile)| {
+        let (a, b) = tile.as_tuple();
+        let parent = layout.nodes_with_open_end(a).next().or_else(|| layout.nodes_with_open_end(b).next());
+        parent.map(|parent| (position, parent))
+    }) {
+        let tile = remaining.remove(position);
+        layout.attach_unchecked(tile, Some(parent));
+        attachments.push((tile, Some(parent)));
+    }
+    attachments
+}
+
+fn bench_layout(c: &mut Criterion) {
+    let configuration = Configuration::new(4, Variation::Traditional, 9, 10);
+    let attachments = attachments(&configuration);
+    let mut layout = Layout::new(&configuration);
+    for (tile, parent) in &attachments {
+        layout.attach_unchecked(*tile, *parent);
+    }
+
+    c.bench_function("Layout::attach", |b| {
+        b.iter(|| {
+            let mut layout = Layout::new(&configuration);
+            for (tile, parent) in &attachments {
+                layout.attach_unchecked(*tile, *parent);
+            }
+            black_box(layout)
+        })
+    });
+    c.bench_function("Layout::clone", |b| b.iter(|| black_box(&layout).clone()));
+}
+
+criterion_group!(benches, bench_layout);
+criterion_main!(benches);
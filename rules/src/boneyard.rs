@@ -21,8 +21,10 @@
 //! }
 //! ```
 
-use crate::{Configuration, Tile};
-use rand::{seq::SliceRandom, rng};
+use crate::{Configuration, RngStream, Tile};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, de};
+use rand::{Rng, seq::SliceRandom, rng};
 
 /// A boneyard implementation.
 ///
@@ -49,17 +51,53 @@ use rand::{seq::SliceRandom, rng};
 /// }
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Boneyard {
     /// All the tiles in the boneyard
     tiles: Vec<Tile>,
     /// Index of the next tile to draw
     next: usize,
+    /// The number of tiles that must be left in the boneyard and cannot be drawn (see `Configuration::reserve`). It is not
+    /// serialized if it is 0, so older boneyards are unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_zero"))]
+    reserve: usize,
+}
+
+// Returns true if no tiles are reserved, so the reserve is not serialized
+#[cfg(feature = "serde")]
+fn is_zero(reserve: &usize) -> bool {
+    *reserve == 0
+}
+
+// The tiles that have been drawn are kept, so that draws can still be taken back after deserializing
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Boneyard {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // The fields as they are serialized
+        #[derive(Deserialize)]
+        struct Fields {
+            tiles: Vec<Tile>,
+            next: usize,
+            #[serde(default)]
+            reserve: usize,
+        }
+
+        let Fields { tiles, next, reserve } = Fields::deserialize(deserializer)?;
+        if next > tiles.len() {
+            return Err(de::Error::custom(format!("The next tile ({next}) is past the end of the boneyard ({})", tiles.len())));
+        }
+        Ok(Self { tiles, next, reserve })
+    }
 }
 
 impl Boneyard {
     /// Creates a new boneyard with the provided tiles
     ///
-    /// The tiles are automatically shuffled on creation.
+    /// The tiles are automatically shuffled on creation. If the configuration has a seed, the order is the same every time (see
+    /// `Configuration::with_seed`). The tiles reserved by the configuration cannot be drawn (see `Configuration::reserve`).
     ///
     /// # Arguments
     /// * `configuration` - The game configuration containing the rules and tile set
@@ -75,9 +113,34 @@ impl Boneyard {
     /// assert_eq!(boneyard.count(), 28); // 7*8/2 = 28 tiles
     /// ```
     pub fn new(configuration: &Configuration) -> Self {
+        Self::with_rng(configuration, &mut configuration.rng(RngStream::Deal))
+    }
+
+    /// Creates a new boneyard shuffled with the given random number generator
+    ///
+    /// The same generator state always produces the same order, so a seeded generator can be used to reproduce a game. This also
+    /// allows the boneyard to be created on platforms without a thread-local random number generator, such as WebAssembly.
+    ///
+    /// # Arguments
+    /// * `configuration` - The game configuration containing the rules and tile set
+    /// * `rng` - The random number generator used to shuffle the tiles
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Boneyard;
+    /// # use rules::Configuration;
+    /// use rand::SeedableRng;
+    /// use rand::rngs::StdRng;
+    ///
+    /// let config = Configuration::default();
+    /// let mut a = Boneyard::with_rng(&config, &mut StdRng::seed_from_u64(42));
+    /// let mut b = Boneyard::with_rng(&config, &mut StdRng::seed_from_u64(42));
+    /// assert_eq!(a.draw(), b.draw());
+    /// ```
+    pub fn with_rng<R: Rng + ?Sized>(configuration: &Configuration, rng: &mut R) -> Self {
         let mut tiles = configuration.all_tiles().to_vec();
-        tiles.shuffle(&mut rng());
-        Self { tiles, next: 0 }
+        tiles.shuffle(rng);
+        Self { tiles, next: 0, reserve: configuration.reserve() }
     }
 
     /// Creates a new boneyard with a specific set of tiles without shuffling them
@@ -104,7 +167,33 @@ impl Boneyard {
     /// assert_eq!(boneyard.draw(), None);
     /// ```
     pub fn with(tiles: Vec<Tile>) -> Self {
-        Self { tiles, next: 0 }
+        Self { tiles, next: 0, reserve: 0 }
+    }
+
+    /// Returns the boneyard with a number of tiles that must be left in it and cannot be drawn (see `Configuration::reserve`)
+    ///
+    /// # Arguments
+    /// * `reserve` - The number of tiles that cannot be drawn
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Boneyard, Tile};
+    ///
+    /// let mut boneyard = Boneyard::with(vec![Tile::from((0, 0)), Tile::from((1, 1)), Tile::from((2, 2))]).with_reserve(2);
+    /// assert_eq!(boneyard.count(), 1);
+    /// assert_eq!(boneyard.draw(), Some(Tile::from((0, 0))));
+    /// assert!(boneyard.is_empty());
+    /// assert_eq!(boneyard.draw(), None);
+    /// assert_eq!(boneyard.remaining().len(), 2);
+    /// ```
+    pub fn with_reserve(mut self, reserve: usize) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    /// Returns the number of tiles that must be left in the boneyard and cannot be drawn
+    pub fn reserve(&self) -> usize {
+        self.reserve
     }
 
     /// Shuffles the remaining tiles in the boneyard.
@@ -136,12 +225,21 @@ impl Boneyard {
     /// assert!(next == Some(Tile::from((2, 2))) || next == Some(Tile::from((3, 3))) || next == Some(Tile::from((4, 4))));
     /// ```
     pub fn shuffle(&mut self) {
-        self.tiles[self.next..].shuffle(&mut rng());
+        self.shuffle_with(&mut rng());
+    }
+
+    /// Shuffles the remaining tiles in the boneyard with the given random number generator, e.g. a seeded one (see `shuffle`)
+    ///
+    /// # Arguments
+    /// * `rng` - The random number generator used to shuffle the tiles
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.tiles[self.next..].shuffle(rng);
     }
 
     /// Draws a tile from the boneyard, removing and returning it if available
     ///
-    /// This method returns a copy of the next tile from the boneyard. If the boneyard is empty, it returns `None`.
+    /// This method returns a copy of the next tile from the boneyard. If the boneyard is empty, it returns `None`. The boneyard is
+    /// empty when only the reserved tiles are left (see `with_reserve`).
     ///
     /// # Returns
     /// * `Some(tile)` - The next tile from the boneyard if available
@@ -170,14 +268,117 @@ impl Boneyard {
     /// assert_eq!(drawn_tiles.len(), 3); // (0,0), (0,1), (1,1)
     /// ```
     pub fn draw(&mut self) -> Option<Tile> {
-        let tile = self.tiles.get(self.next).copied();
+        let tile = self.peek().copied();
         if tile.is_some() {
             self.next += 1;
         }
         tile
     }
 
-    /// Returns the number of tiles remaining in the boneyard.
+    /// Deals the starting hands
+    ///
+    /// Each player is dealt a whole hand in turn, so the hands are the same as if each player drew their tiles one after another.
+    /// Either every hand is dealt or, if there are not enough tiles, nothing is drawn. The reserved tiles cannot be dealt.
+    ///
+    /// # Arguments
+    /// * `num_players` - The number of hands to deal
+    /// * `hand_size` - The number of tiles in each hand
+    ///
+    /// # Returns
+    /// The hands, by player ID
+    ///
+    /// # Panics
+    /// Panics if the boneyard does not have enough tiles for every hand
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Boneyard, Tile};
+    ///
+    /// let mut boneyard = Boneyard::with(vec![Tile::from((0, 0)), Tile::from((0, 1)), Tile::from((1, 1)), Tile::from((0, 2))]);
+    /// let hands = boneyard.deal(2, 2);
+    /// assert_eq!(hands, vec![vec![Tile::from((0, 0)), Tile::from((0, 1))], vec![Tile::from((1, 1)), Tile::from((0, 2))]]);
+    /// assert!(boneyard.is_empty());
+    /// ```
+    pub fn deal(&mut self, num_players: usize, hand_size: usize) -> Vec<Vec<Tile>> {
+        let needed = num_players * hand_size;
+        assert!(needed <= self.count(), "The boneyard has {} tiles, but {needed} are needed to deal the hands", self.count());
+        let dealt = &self.tiles[self.next..self.next + needed];
+        let hands = (0..num_players).map(|player| dealt[player * hand_size..(player + 1) * hand_size].to_vec()).collect();
+        self.next += needed;
+        hands
+    }
+
+    /// Deals the starting hands of a round, redealing until the first tile can be played
+    ///
+    /// The hands are dealt as by `deal`. If the opening rule of the variation requires a double and nobody was dealt one (see
+    /// `Opening::can_open`), every tile of the set is shuffled again with `rng`, as in `with_rng`, and the hands are redealt.
+    ///
+    /// # Arguments
+    /// * `configuration` - The game configuration, which determines the number and size of the hands and the opening rule
+    /// * `rng` - The random number generator used to shuffle the tiles for a redeal
+    ///
+    /// # Returns
+    /// The hands, by player ID
+    ///
+    /// # Panics
+    /// Panics if the set does not have enough tiles for every hand
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Boneyard, Configuration, Variation};
+    /// use rand::SeedableRng;
+    /// use rand::rngs::StdRng;
+    ///
+    /// let configuration = Configuration::new(4, Variation::Traditional, 6, 2);
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let mut boneyard = Boneyard::with_rng(&configuration, &mut rng);
+    /// let hands = boneyard.deal_round(&configuration, &mut rng);
+    /// assert_eq!(hands.len(), 4);
+    /// assert!(hands.iter().flatten().any(|tile| tile.is_double()));
+    /// assert_eq!(boneyard.count(), 28 - 4 * 2);
+    /// ```
+    pub fn deal_round<R: Rng + ?Sized>(&mut self, configuration: &Configuration, rng: &mut R) -> Vec<Vec<Tile>> {
+        loop {
+            let hands = self.deal(configuration.num_players(), configuration.starting_hand_size());
+            if configuration.opening().can_open(&hands) {
+                return hands;
+            }
+            *self = Self::with_rng(configuration, rng);
+        }
+    }
+
+    /// Returns the last tile drawn to the boneyard
+    ///
+    /// The tile becomes the next tile to be drawn again. This is used to take back a draw.
+    ///
+    /// # Returns
+    /// * `Some(tile)` - The tile that was returned to the boneyard
+    /// * `None` - If no tiles have been drawn
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Boneyard;
+    /// # use rules::Tile;
+    ///
+    /// let mut boneyard = Boneyard::with(vec![Tile::from((0, 0)), Tile::from((1, 1))]);
+    /// assert_eq!(boneyard.undraw(), None);
+    ///
+    /// let tile = boneyard.draw();
+    /// assert_eq!(boneyard.undraw(), tile);
+    /// assert_eq!(boneyard.count(), 2);
+    /// assert_eq!(boneyard.draw(), tile);
+    /// ```
+    pub fn undraw(&mut self) -> Option<Tile> {
+        if self.next == 0 {
+            return None;
+        }
+        self.next -= 1;
+        Some(self.tiles[self.next])
+    }
+
+    /// Returns the number of tiles remaining in the boneyard that can be drawn.
+    ///
+    /// The reserved tiles are not counted (see `with_reserve`).
     ///
     /// # Returns
     /// The number of tiles remaining to be drawn
@@ -208,10 +409,10 @@ impl Boneyard {
     /// assert_eq!(boneyard.count(), 0);
     /// ```
     pub fn count(&self) -> usize {
-        self.tiles.len() - self.next
+        (self.tiles.len() - self.next).saturating_sub(self.reserve)
     }
 
-    /// Checks if the boneyard is empty and there are no more tiles to be drawn.
+    /// Checks if the boneyard is empty and there are no more tiles to be drawn, though reserved tiles may be left in it.
     ///
     /// # Returns
     /// * `true` - If there are no tiles remaining to draw
@@ -263,7 +464,7 @@ impl Boneyard {
     /// }
     /// ```
     pub fn is_empty(&self) -> bool {
-        self.next >= self.tiles.len()
+        self.count() == 0
     }
 
     /// Peeks at the next tile without removing it.
@@ -273,7 +474,7 @@ impl Boneyard {
     ///
     /// # Returns
     /// * `Some(&tile)` - A reference to the next tile that would be drawn
-    /// * `None` - If there are no tiles remaining in the boneyard
+    /// * `None` - If there are no tiles that can be drawn
     ///
     /// # Examples
     /// ```rust
@@ -307,7 +508,23 @@ impl Boneyard {
     /// The returned reference is never invalidated as long as the boneyard exists, but will no longer reference the next tile to
     /// be drawn after a call to `draw()` or `shuffle()`.
     pub fn peek(&self) -> Option<&Tile> {
-        self.tiles.get(self.next)
+        if self.is_empty() { None } else { self.tiles.get(self.next) }
+    }
+
+    /// Returns the tiles remaining in the boneyard, in the order in which they will be drawn. The reserved tiles, which cannot be
+    /// drawn, are last.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Boneyard;
+    /// # use rules::Tile;
+    ///
+    /// let mut boneyard = Boneyard::with(vec![Tile::from((1, 2)), Tile::from((3, 4))]);
+    /// boneyard.draw();
+    /// assert_eq!(boneyard.remaining(), &[Tile::from((3, 4))]);
+    /// ```
+    pub fn remaining(&self) -> &[Tile] {
+        &self.tiles[self.next..]
     }
 }
 
@@ -543,6 +760,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_boneyard_seeded() {
+        // A configuration with a seed always deals the same boneyard, and the same as a generator for its deals
+        let configuration = Configuration::default().with_seed(11);
+        let boneyard = Boneyard::new(&configuration);
+        assert_eq!(Boneyard::new(&configuration).remaining(), boneyard.remaining());
+        let dealt = Boneyard::with_rng(&configuration, &mut configuration.rng(RngStream::Deal));
+        assert_eq!(dealt.remaining(), boneyard.remaining());
+
+        // Shuffling with generators in the same state gives the same order
+        let mut a = boneyard.clone();
+        let mut b = boneyard.clone();
+        a.shuffle_with(&mut configuration.rng(RngStream::Player(0)));
+        b.shuffle_with(&mut configuration.rng(RngStream::Player(0)));
+        assert_eq!(a.remaining(), b.remaining());
+    }
+
     #[test]
     fn test_boneyard_peek_empty() {
         let tiles = vec![crate::Tile::from((0, 0))];
@@ -742,4 +976,100 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_boneyard_deal() {
+        let tiles: Vec<Tile> = (0..6).map(|value| Tile::from((value, value))).collect();
+        let mut boneyard = Boneyard::with(tiles.clone());
+        assert_eq!(boneyard.draw(), Some(tiles[0]));
+        let hands = boneyard.deal(2, 2);
+        assert_eq!(hands, vec![tiles[1..3].to_vec(), tiles[3..5].to_vec()]);
+        assert_eq!(boneyard.count(), 1);
+        assert_eq!(boneyard.deal(3, 0), vec![Vec::<Tile>::new(); 3]);
+        assert_eq!(boneyard.count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The boneyard has 3 tiles, but 4 are needed to deal the hands")]
+    fn test_boneyard_deal_not_enough_tiles() {
+        let mut boneyard = Boneyard::with(vec![Tile::from((0, 0)), Tile::from((0, 1)), Tile::from((1, 1))]);
+        boneyard.deal(2, 2);
+    }
+
+    #[test]
+    fn test_boneyard_deal_round() {
+        // Nobody holds a double, so the hands are redealt from a new shuffle
+        let configuration = Configuration::new(2, crate::Variation::Traditional, 6, 1).with_seed(7);
+        let mut boneyard = Boneyard::with(vec![Tile::from((0, 1)), Tile::from((2, 3)), Tile::from((4, 4))]);
+        let mut rng = configuration.rng(RngStream::Deal);
+        let hands = boneyard.deal_round(&configuration, &mut rng);
+        assert!(hands.iter().flatten().any(|tile| tile.is_double()));
+        assert_eq!(boneyard.count(), 28 - 2);
+
+        // Any tile can be played first in All Fives, so the hands are kept
+        let configuration = Configuration::new(2, crate::Variation::AllFives, 6, 1);
+        let mut boneyard = Boneyard::with(vec![Tile::from((0, 1)), Tile::from((2, 3))]);
+        let hands = boneyard.deal_round(&configuration, &mut rng);
+        assert_eq!(hands, vec![vec![Tile::from((0, 1))], vec![Tile::from((2, 3))]]);
+    }
+
+    #[test]
+    fn test_boneyard_undraw() {
+        let tiles = vec![Tile::from((0, 0)), Tile::from((0, 1)), Tile::from((1, 1))];
+        let mut boneyard = Boneyard::with(tiles);
+        assert_eq!(boneyard.undraw(), None);
+
+        boneyard.draw();
+        boneyard.draw();
+        assert_eq!(boneyard.undraw(), Some(Tile::from((0, 1))));
+        assert_eq!(boneyard.count(), 2);
+        assert_eq!(boneyard.peek(), Some(&Tile::from((0, 1))));
+        assert_eq!(boneyard.undraw(), Some(Tile::from((0, 0))));
+        assert_eq!(boneyard.undraw(), None);
+        assert_eq!(boneyard.count(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_boneyard_serde() {
+        let mut boneyard = Boneyard::with(vec![Tile::from((1, 2)), Tile::from((3, 4))]);
+        boneyard.draw();
+        let json = serde_json::to_string(&boneyard).unwrap();
+        assert_eq!(json, "{\"tiles\":[[1,2],[3,4]],\"next\":1}");
+
+        // A draw can still be taken back
+        let mut deserialized: Boneyard = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.remaining(), &[Tile::from((3, 4))]);
+        assert_eq!(deserialized.undraw(), Some(Tile::from((1, 2))));
+
+        assert!(serde_json::from_str::<Boneyard>(r#"{"tiles":[[1,2]],"next":2}"#).is_err());
+
+        // The reserve is kept
+        let json = serde_json::to_string(&deserialized.with_reserve(1)).unwrap();
+        assert_eq!(json, "{\"tiles\":[[1,2],[3,4]],\"next\":0,\"reserve\":1}");
+        assert_eq!(serde_json::from_str::<Boneyard>(&json).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_boneyard_reserve() {
+        let configuration = crate::Configuration::default().with_reserve(2).unwrap();
+        let mut boneyard = Boneyard::new(&configuration);
+        assert_eq!(boneyard.reserve(), 2);
+        assert_eq!(boneyard.count(), 26);
+        boneyard.deal(2, 7);
+        while boneyard.draw().is_some() {}
+        assert!(boneyard.is_empty());
+        assert_eq!(boneyard.count(), 0);
+        assert_eq!(boneyard.peek(), None);
+        assert_eq!(boneyard.remaining().len(), 2);
+
+        // A tile drawn is returned to the drawable tiles
+        let tile = boneyard.undraw();
+        assert_eq!(boneyard.count(), 1);
+        assert_eq!(boneyard.peek().copied(), tile);
+
+        // There can be more reserved tiles than are left
+        let boneyard = Boneyard::with(vec![Tile::from((0, 0))]).with_reserve(2);
+        assert!(boneyard.is_empty());
+    }
 }
@@ -1,5 +1,8 @@
 //! Configuration module
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, de};
+
 use crate::*;
 
 /// Configuration for a dominoes game session.
@@ -27,6 +30,7 @@ use crate::*;
 /// assert_eq!(default_config.starting_hand_size(), 7);
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Configuration {
     /// The game variation being played
     variation: Variation,
@@ -36,14 +40,113 @@ pub struct Configuration {
     starting_hand_size: usize,
     /// Number of players in the game
     num_players: usize,
+    /// Number of points needed to win a match. It is not serialized if it is the default, so older configurations are unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_default_target_score"))]
+    target_score: u32,
+    /// The rule for the number of tiles that can be attached to doubles, or `None` for the rule of the variation. It is not
+    /// serialized if it is `None`, so older configurations are unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    branching: Option<Branching>,
+    /// The rule for drawing from the boneyard, or `None` for the rule of the variation. It is not serialized if it is `None`, so
+    /// older configurations are unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    draw_rule: Option<DrawRule>,
+    /// Whether the points scored by a play must be claimed, under the muggins rule. It is not serialized if it is `false`, so
+    /// older configurations are unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "std::ops::Not::not"))]
+    muggins: bool,
+    /// Whether the players play as two teams of partners. It is not serialized if it is `false`, so older configurations are
+    /// unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "std::ops::Not::not"))]
+    partners: bool,
+    /// The number of tiles that must be left in the boneyard. It is not serialized if it is 0, so older configurations are
+    /// unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_zero"))]
+    reserve: usize,
+    /// The seed of every random number generator in a game, or `None` if the games are not reproducible. It is not serialized if
+    /// it is `None`, so older configurations are unchanged.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    seed: Option<u64>,
     /// Complete set of all tiles available for this game
+    #[cfg_attr(feature = "serde", serde(skip))]
     tiles: Vec<Tile>,
 }
 
+// Returns true if a target score is the default, so it is not serialized
+#[cfg(feature = "serde")]
+fn is_default_target_score(target_score: &u32) -> bool {
+    *target_score == Configuration::DEFAULT_TARGET_SCORE
+}
+
+// Returns true if no tiles are reserved, so the reserve is not serialized
+#[cfg(feature = "serde")]
+fn is_zero(reserve: &usize) -> bool {
+    *reserve == 0
+}
+
+// A configuration is deserialized with try_new, so it is valid and its tiles are generated
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Configuration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // The fields as they are serialized
+        #[derive(Deserialize)]
+        struct Fields {
+            variation: Variation,
+            set_id: u8,
+            starting_hand_size: usize,
+            num_players: usize,
+            #[serde(default)]
+            target_score: Option<u32>,
+            #[serde(default)]
+            branching: Option<Branching>,
+            #[serde(default)]
+            draw_rule: Option<DrawRule>,
+            #[serde(default)]
+            muggins: bool,
+            #[serde(default)]
+            partners: bool,
+            #[serde(default)]
+            reserve: usize,
+            #[serde(default)]
+            seed: Option<u64>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let configuration =
+            Configuration::try_new(fields.num_players, fields.variation, fields.set_id, fields.starting_hand_size)
+                .map_err(de::Error::custom)?
+                .with_muggins(fields.muggins)
+                .with_reserve(fields.reserve)
+                .map_err(de::Error::custom)?;
+        let configuration = match fields.seed {
+            Some(seed) => configuration.with_seed(seed),
+            None => configuration,
+        };
+        let configuration = match fields.target_score {
+            Some(target_score) => configuration.with_target_score(target_score),
+            None => configuration,
+        };
+        let configuration = match fields.draw_rule {
+            Some(draw_rule) => configuration.with_draw_rule(draw_rule),
+            None => configuration,
+        };
+        let configuration =
+            if fields.partners { configuration.with_partners().map_err(de::Error::custom)? } else { configuration };
+        Ok(match fields.branching {
+            Some(branching) => configuration.with_branching(branching),
+            None => configuration,
+        })
+    }
+}
+
 impl Configuration {
     pub const DEFAULT_NUM_PLAYERS: usize = 2;
     pub const DEFAULT_VARIATION: Variation = Variation::Traditional;
     pub const DEFAULT_SET_ID: u8 = 6;
+    pub const DEFAULT_TARGET_SCORE: u32 = 100;
 
     /// Creates a new configuration
     ///
@@ -85,15 +188,71 @@ impl Configuration {
             set_id,
             starting_hand_size,
             num_players,
+            target_score: Self::DEFAULT_TARGET_SCORE,
+            branching: None,
+            draw_rule: None,
+            muggins: false,
+            partners: false,
+            reserve: 0,
+            seed: None,
             tiles,
         }
     }
 
+    /// Creates a new configuration, or returns an error if a game cannot be played with it
+    ///
+    /// Unlike `new`, this also checks that the set has enough tiles to deal every player a hand.
+    ///
+    /// # Arguments
+    /// * `num_players` - Number of players
+    /// * `variation` - The game variation being played
+    /// * `set_id` - ID of the set. Same as the highest tile value.
+    /// * `starting_hand_size` - Number of tiles each player starts with
+    ///
+    /// # Examples
+    /// ```
+    /// # use rules::{Configuration, RulesError, Variation};
+    ///
+    /// assert!(Configuration::try_new(4, Variation::Traditional, 6, 7).is_ok());
+    /// assert_eq!(Configuration::try_new(2, Variation::Traditional, 22, 7).unwrap_err(), RulesError::SetTooLarge(22));
+    /// assert!(matches!(
+    ///     Configuration::try_new(4, Variation::Traditional, 3, 6),
+    ///     Err(RulesError::NotEnoughTiles { set_size: 10, .. })
+    /// ));
+    /// ```
+    pub fn try_new(
+        num_players: usize,
+        variation: Variation,
+        set_id: u8,
+        starting_hand_size: usize,
+    ) -> Result<Self, RulesError> {
+        if num_players < 2 {
+            return Err(RulesError::TooFewPlayers(num_players));
+        }
+        if set_id > MAX_PIPS {
+            return Err(RulesError::SetTooLarge(set_id));
+        }
+        if num_players * starting_hand_size > set_size(set_id) {
+            return Err(RulesError::NotEnoughTiles {
+                set_id,
+                set_size: set_size(set_id),
+                starting_hand_size,
+                num_players,
+            });
+        }
+        Ok(Self::new(num_players, variation, set_id, starting_hand_size))
+    }
+
     /// Returns the game variation being played.
     pub fn variation(&self) -> Variation {
         self.variation
     }
 
+    /// Returns the rule for the first tile of a round, which depends on the variation.
+    pub fn opening(&self) -> Opening {
+        self.variation.opening()
+    }
+
     /// Returns the ID of the dominoes set.
     pub fn set_id(&self) -> u8 {
         self.set_id
@@ -109,6 +268,209 @@ impl Configuration {
         self.num_players
     }
 
+    /// Returns the number of points needed to win a match.
+    pub fn target_score(&self) -> u32 {
+        self.target_score
+    }
+
+    /// Returns the configuration with a different number of points needed to win a match.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::AllFives, 6, 7).with_target_score(250);
+    /// assert_eq!(config.target_score(), 250);
+    /// assert_eq!(Configuration::default().target_score(), Configuration::DEFAULT_TARGET_SCORE);
+    /// ```
+    pub fn with_target_score(mut self, target_score: u32) -> Self {
+        self.target_score = target_score;
+        self
+    }
+
+    /// Returns the rule for the number of tiles that can be attached to doubles, which depends on the variation unless it is
+    /// changed with `with_branching`. In Mexican Train, the engine has an arm for each player's train and one for the Mexican
+    /// train.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Branching, Configuration, Variation};
+    ///
+    /// let config = Configuration::new(4, Variation::MexicanTrain, 12, 15);
+    /// assert_eq!(config.branching(), Branching::Spinner { arms: 5 });
+    /// ```
+    pub fn branching(&self) -> Branching {
+        self.branching.unwrap_or(match self.variation {
+            Variation::MexicanTrain => Branching::Spinner { arms: self.num_players as u8 + 1 },
+            variation => variation.branching(),
+        })
+    }
+
+    /// Returns the configuration with a different rule for the number of tiles that can be attached to doubles.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Branching, Configuration, Variation};
+    ///
+    /// let config = Configuration::new(4, Variation::Traditional, 12, 12).with_branching(Branching::ChickenFoot { arms: 4 });
+    /// assert_eq!(config.branching(), Branching::ChickenFoot { arms: 4 });
+    /// assert_eq!(Configuration::default().branching(), Branching::EveryDouble);
+    /// ```
+    pub fn with_branching(mut self, branching: Branching) -> Self {
+        self.branching = Some(branching);
+        self
+    }
+
+    /// Returns the rule for drawing from the boneyard when a player cannot play, which depends on the variation unless it is
+    /// changed with `with_draw_rule`.
+    pub fn draw_rule(&self) -> DrawRule {
+        self.draw_rule.unwrap_or(self.variation.draw_rule())
+    }
+
+    /// Returns the configuration with a different rule for drawing from the boneyard, e.g. to play a Block game.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, DrawRule, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::Traditional, 6, 7).with_draw_rule(DrawRule::NoDraw);
+    /// assert_eq!(config.draw_rule(), DrawRule::NoDraw);
+    /// assert_eq!(Configuration::default().draw_rule(), DrawRule::DrawUntilPlayable);
+    /// ```
+    pub fn with_draw_rule(mut self, draw_rule: DrawRule) -> Self {
+        self.draw_rule = Some(draw_rule);
+        self
+    }
+
+    /// Returns `true` if the points scored by a play must be claimed by the player who made it. If they are missed, another player
+    /// may call "muggins" and take them. Plays score only in some variations, e.g. All Fives.
+    pub fn muggins(&self) -> bool {
+        self.muggins
+    }
+
+    /// Returns the configuration with or without the muggins rule (see `muggins`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Variation};
+    ///
+    /// let config = Configuration::new(2, Variation::AllFives, 6, 7).with_muggins(true);
+    /// assert!(config.muggins());
+    /// assert!(!Configuration::default().muggins());
+    /// ```
+    pub fn with_muggins(mut self, muggins: bool) -> Self {
+        self.muggins = muggins;
+        self
+    }
+
+    /// Returns the number of tiles that must be left in the boneyard. Under many rules, the last two tiles cannot be drawn.
+    pub fn reserve(&self) -> usize {
+        self.reserve
+    }
+
+    /// Returns the configuration with a number of tiles that must be left in the boneyard and cannot be drawn (see `reserve`), or
+    /// an error if fewer tiles are left after the deal.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Boneyard, Configuration, RulesError};
+    ///
+    /// let config = Configuration::default().with_reserve(2).unwrap();
+    /// assert_eq!(config.reserve(), 2);
+    /// assert_eq!(Boneyard::new(&config).count(), 26);
+    /// assert_eq!(Configuration::default().reserve(), 0);
+    ///
+    /// let error = Configuration::default().with_reserve(15).unwrap_err();
+    /// assert_eq!(error, RulesError::ReserveTooLarge { reserve: 15, undealt: 14 });
+    /// ```
+    pub fn with_reserve(mut self, reserve: usize) -> Result<Self, RulesError> {
+        let undealt = self.set_size().saturating_sub(self.num_players * self.starting_hand_size);
+        if reserve > undealt {
+            return Err(RulesError::ReserveTooLarge { reserve, undealt });
+        }
+        self.reserve = reserve;
+        Ok(self)
+    }
+
+    /// Returns `true` if the players play as two teams of partners, who sit across from each other: Alice and Carol against Bob
+    /// and Dave.
+    pub fn partners(&self) -> bool {
+        self.partners
+    }
+
+    /// Returns the configuration with the players playing as two teams of partners (see `partners`), or an error if there are
+    /// not four players.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, RulesError, Variation};
+    ///
+    /// let config = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+    /// assert_eq!((config.team(0), config.team(1), config.team(2), config.team(3)), (0, 1, 0, 1));
+    /// assert_eq!(Configuration::default().with_partners().unwrap_err(), RulesError::PartnersNeedFourPlayers(2));
+    /// ```
+    pub fn with_partners(mut self) -> Result<Self, RulesError> {
+        if self.num_players != 4 {
+            return Err(RulesError::PartnersNeedFourPlayers(self.num_players));
+        }
+        self.partners = true;
+        Ok(self)
+    }
+
+    /// Returns the number of teams. Without partners, each player is a team of their own.
+    pub fn num_teams(&self) -> usize {
+        if self.partners { 2 } else { self.num_players }
+    }
+
+    /// Returns the team of a player. A team is numbered by the ID of its first player, so without partners, a player's team is
+    /// their own ID.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player
+    pub fn team(&self, player_id: u8) -> u8 {
+        Self::team_of(self.partners, player_id)
+    }
+
+    /// Returns the team of a player, for a game with or without partners (see `team`)
+    ///
+    /// # Arguments
+    /// * `partners` - Whether the players play as two teams of partners (see `partners`)
+    /// * `player_id` - The player
+    pub const fn team_of(partners: bool, player_id: u8) -> u8 {
+        if partners { player_id % 2 } else { player_id }
+    }
+
+    /// Returns the seed of every random number generator in a game, or `None` if the generators are seeded from the operating
+    /// system's source of entropy (see `rules::random`).
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns the configuration with a seed for every random number generator in a game, so the shuffles of the boneyard and the
+    /// choices of computer players are reproducible (see `rules::random`).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Boneyard, Configuration};
+    ///
+    /// let config = Configuration::default().with_seed(42);
+    /// assert_eq!(config.seed(), Some(42));
+    /// assert_eq!(Boneyard::new(&config).remaining(), Boneyard::new(&config).remaining());
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns a random number generator for a use of randomness in a game. If the configuration has a seed, generators for the
+    /// same use are identical.
+    ///
+    /// # Arguments
+    /// * `stream` - What the generator is used for
+    pub fn rng(&self, stream: RngStream) -> GameRng {
+        game_rng(self.seed, stream)
+    }
+
     /// Returns the complete set of all tiles available for this game.
     pub fn tiles(&self) -> &[Tile] {
         &self.tiles
@@ -150,10 +512,47 @@ impl Configuration {
         &self.tiles
     }
 
+    /// Returns the ID of the set usually played in a variation, which is double-twelve for Mexican Train, double-nine for Chicken
+    /// Foot, and double-six otherwise.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Variation};
+    ///
+    /// assert_eq!(Configuration::default_set_id(Variation::MexicanTrain), 12);
+    /// assert_eq!(Configuration::default_set_id(Variation::ChickenFoot), 9);
+    /// assert_eq!(Configuration::default_set_id(Variation::AllFives), Configuration::DEFAULT_SET_ID);
+    /// ```
+    pub fn default_set_id(variation: Variation) -> u8 {
+        match variation {
+            Variation::MexicanTrain => 12,
+            Variation::ChickenFoot => 9,
+            _ => Self::DEFAULT_SET_ID,
+        }
+    }
+
     /// Returns the default starting hand size for a given number of players and variation.
+    ///
+    /// The hand sizes are for the set usually played in the variation (see `default_set_id`).
     pub fn default_starting_hand_size(num_players: usize, variation: Variation) -> usize {
         match variation {
             Variation::Bergen => 6,
+            Variation::MexicanTrain => match num_players {
+                2..=4 => 15,
+                5..=6 => 12,
+                7..=8 => 10,
+                _ => 8,
+            },
+            Variation::ChickenFoot => match num_players {
+                2 => 21,
+                3 => 14,
+                4 => 11,
+                5 => 9,
+                6 => 7,
+                7 => 6,
+                8 => 5,
+                _ => 4,
+            },
             Variation::Blind => match num_players {
                 2 => 8,
                 3 => 7,
@@ -271,4 +670,96 @@ mod tests {
     fn test_configuration_new_set_id_too_large() {
         Configuration::new(2, Variation::Traditional, 22, 7);
     }
+
+    #[test]
+    fn test_configuration_try_new() {
+        let config = Configuration::try_new(3, Variation::Bergen, 6, 6).unwrap();
+        assert_eq!(config.num_players(), 3);
+        assert_eq!(config.starting_hand_size(), 6);
+
+        assert_eq!(Configuration::try_new(1, Variation::Traditional, 6, 7).unwrap_err(), RulesError::TooFewPlayers(1));
+        assert_eq!(Configuration::try_new(2, Variation::Traditional, 22, 7).unwrap_err(), RulesError::SetTooLarge(22));
+        assert_eq!(
+            Configuration::try_new(2, Variation::Traditional, 3, 7).unwrap_err(),
+            RulesError::NotEnoughTiles { set_id: 3, set_size: 10, starting_hand_size: 7, num_players: 2 }
+        );
+    }
+
+    #[test]
+    fn test_configuration_partners() {
+        // Without partners, each player is a team of their own
+        let config = Configuration::new(4, Variation::Traditional, 6, 7);
+        assert!(!config.partners());
+        assert_eq!(config.num_teams(), 4);
+        assert_eq!(config.team(3), 3);
+
+        let config = config.with_partners().unwrap();
+        assert!(config.partners());
+        assert_eq!(config.num_teams(), 2);
+        assert_eq!(config.team(3), 1);
+        let config = Configuration::new(3, Variation::Traditional, 6, 7);
+        assert_eq!(config.with_partners().unwrap_err(), RulesError::PartnersNeedFourPlayers(3));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_configuration_serde() {
+        let configuration = Configuration::new(4, Variation::AllFives, 9, 6);
+        let json = serde_json::to_string(&configuration).unwrap();
+        assert_eq!(json, r#"{"variation":"AllFives","set_id":9,"starting_hand_size":6,"num_players":4}"#);
+        let deserialized: Configuration = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.set_size(), 55);
+        assert_eq!(deserialized.num_players(), 4);
+        assert_eq!(deserialized.target_score(), Configuration::DEFAULT_TARGET_SCORE);
+
+        // A target score other than the default is kept
+        let json = serde_json::to_string(&configuration.clone().with_target_score(250)).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"target_score":250}"#));
+        assert_eq!(serde_json::from_str::<Configuration>(&json).unwrap().target_score(), 250);
+
+        // So is the muggins rule
+        let json = serde_json::to_string(&configuration.clone().with_muggins(true)).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"muggins":true}"#));
+        assert!(serde_json::from_str::<Configuration>(&json).unwrap().muggins());
+        assert!(!deserialized.muggins());
+
+        // So are partners
+        let partners = Configuration::new(4, Variation::Traditional, 6, 7).with_partners().unwrap();
+        let json = serde_json::to_string(&partners).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"partners":true}"#));
+        assert!(serde_json::from_str::<Configuration>(&json).unwrap().partners());
+        assert!(!deserialized.partners());
+        let three_partners = r#"{"variation":"Traditional","set_id":6,"starting_hand_size":7,"num_players":3,"partners":true}"#;
+        assert!(serde_json::from_str::<Configuration>(three_partners).is_err());
+
+        // So is a seed
+        let json = serde_json::to_string(&configuration.clone().with_seed(42)).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"seed":42}"#));
+        assert_eq!(serde_json::from_str::<Configuration>(&json).unwrap().seed(), Some(42));
+        assert_eq!(deserialized.seed(), None);
+
+        // So is a reserve
+        let json = serde_json::to_string(&configuration.clone().with_reserve(2).unwrap()).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"reserve":2}"#));
+        assert_eq!(serde_json::from_str::<Configuration>(&json).unwrap().reserve(), 2);
+        assert_eq!(deserialized.reserve(), 0);
+
+        // So is a rule for drawing other than the variation's
+        let json = serde_json::to_string(&configuration.clone().with_draw_rule(DrawRule::NoDraw)).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"draw_rule":"NoDraw"}"#));
+        assert_eq!(serde_json::from_str::<Configuration>(&json).unwrap().draw_rule(), DrawRule::NoDraw);
+        assert_eq!(deserialized.draw_rule(), DrawRule::DrawUntilPlayable);
+
+        // So is a rule for doubles other than the variation's
+        let json = serde_json::to_string(&configuration.with_branching(Branching::Spinner { arms: 8 })).unwrap();
+        assert!(json.ends_with(r#""num_players":4,"branching":{"Spinner":{"arms":8}}}"#));
+        assert_eq!(serde_json::from_str::<Configuration>(&json).unwrap().branching(), Branching::Spinner { arms: 8 });
+        assert_eq!(deserialized.branching(), Branching::Spinner { arms: 4 });
+
+        // The configuration must be valid
+        let too_few_players = r#"{"variation":"Traditional","set_id":6,"starting_hand_size":7,"num_players":1}"#;
+        assert!(serde_json::from_str::<Configuration>(too_few_players).is_err());
+        let not_enough_tiles = r#"{"variation":"Traditional","set_id":3,"starting_hand_size":7,"num_players":4}"#;
+        assert!(serde_json::from_str::<Configuration>(not_enough_tiles).is_err());
+    }
 }
@@ -0,0 +1,104 @@
+//! Error types
+//!
+//! This module defines the errors returned by this crate, so that callers can match on the kind of failure instead of its message.
+
+use thiserror::Error;
+
+use crate::{MAX_PIPS, Tile};
+
+/// An error in the configuration of a game
+///
+/// # Examples
+/// ```rust
+/// # use rules::{Configuration, RulesError, Variation};
+///
+/// let result = Configuration::try_new(1, Variation::Traditional, 6, 7);
+/// assert_eq!(result.unwrap_err(), RulesError::TooFewPlayers(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RulesError {
+    /// A game needs at least two players
+    #[error("A game needs at least 2 players, not {0}")]
+    TooFewPlayers(usize),
+    /// The set is larger than the largest supported set
+    #[error("A double-{0} set is not supported. The largest is double-{MAX_PIPS}.")]
+    SetTooLarge(u8),
+    /// There are not enough tiles in the set to deal every player a hand
+    #[error(
+        "A double-{set_id} set has {set_size} tiles, which is not enough to deal {starting_hand_size} to each of \
+         {num_players} players"
+    )]
+    NotEnoughTiles {
+        set_id: u8,
+        set_size: usize,
+        starting_hand_size: usize,
+        num_players: usize,
+    },
+    /// Partners play in two teams of two, so there must be four players
+    #[error("Partners need 4 players, not {0}")]
+    PartnersNeedFourPlayers(usize),
+    /// More tiles are reserved than are left in the boneyard after the deal
+    #[error("Only {undealt} tiles are left after the deal, so {reserve} cannot be reserved")]
+    ReserveTooLarge { reserve: usize, undealt: usize },
+}
+
+/// A tile written as text could not be read (see `Tile::from_str`)
+///
+/// # Examples
+/// ```rust
+/// # use rules::{ParseTileError, Tile};
+///
+/// assert_eq!("3-4".parse::<Tile>(), Err(ParseTileError("3-4".to_string())));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Expected a tile such as 3|4, found '{0}'")]
+pub struct ParseTileError(pub String);
+
+/// An error in the structure of a layout
+///
+/// # Examples
+/// ```rust
+/// # use rules::{Configuration, Layout, LayoutError, Tile};
+///
+/// let mut tree = ego_tree::Tree::new(Tile::from((6, 6)));
+/// tree.root_mut().append(Tile::from((1, 2)));
+///
+/// let result = Layout::from_tree(&Configuration::default(), &tree);
+/// assert_eq!(result.unwrap_err(), LayoutError::Mismatch { tile: Tile::from((1, 2)), parent: Tile::from((6, 6)) });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LayoutError {
+    /// A tile has a value that is larger than the set allows
+    #[error("Tile {tile} is not in a double-{set_id} set")]
+    NotInSet { tile: Tile, set_id: u8 },
+    /// A tile does not have a value in common with the tile it is attached to
+    #[error("Tile {tile} does not match {parent}")]
+    Mismatch { tile: Tile, parent: Tile },
+    /// A tile has more children than it has open ends. `max` is the number of children allowed.
+    #[error("Tile node {index} has more than {max} {}", if *max == 1 { "child" } else { "children" })]
+    TooManyChildren { index: usize, max: usize },
+    /// A tile is attached to a node that does not exist
+    #[error("There is no tile node {0}")]
+    NoSuchNode(usize),
+    /// A tile is attached to a node on an end that is not open
+    #[error("Tile node {index} does not have an open {value}")]
+    NoOpenEnd { index: usize, value: u8 },
+    /// A first tile is placed on a layout that already has one
+    #[error("The layout already has a first tile")]
+    NotEmpty,
+    /// A node's links to its parent and children do not form a tree rooted at the first node
+    #[error("Tile node {0} is not linked correctly to its parent or children")]
+    BrokenLink(usize),
+    /// A tile is detached from the layout while other tiles are attached to it
+    #[error("Tile node {0} has tiles attached to it")]
+    NotALeaf(usize),
+    /// A tile is in the layout more than once
+    #[error("Tile {0} is in the layout more than once")]
+    Duplicate(Tile),
+    /// A node's open ends are not the ones left by the tiles attached to it
+    #[error("The open ends of tile node {0} do not match the tiles attached to it")]
+    WrongOpenEnds(usize),
+    /// The number of open ends with a value is not the number left by the tiles
+    #[error("There {} {actual} open {value}, but the tiles leave {expected}", if *actual == 1 { "is" } else { "are" })]
+    WrongEndCount { value: u8, expected: u8, actual: u8 },
+}
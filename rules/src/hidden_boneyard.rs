@@ -0,0 +1,278 @@
+//! Hidden boneyard module
+//!
+//! This module provides the `HiddenBoneyard` struct, a boneyard for fair play against opponents who cannot see it, e.g. over a
+//! network. Its next tile cannot be peeked at, and the order of its tiles is committed to when it is shuffled: the commitment is a
+//! SHA-256 hash of a secret salt and the order. The commitment is given to the players before the game, and the order and the salt
+//! are revealed after it (see `Reveal`), so that the players can check that the order was not changed during the game. The salt is
+//! taken from the operating system's entropy, so it cannot be predicted even if the shuffle can, e.g. in a seeded game.
+//!
+//! # Example
+//! ```rust
+//! # use rules::{Configuration, HiddenBoneyard};
+//!
+//! let mut boneyard = HiddenBoneyard::new(&Configuration::default());
+//! let commitment = boneyard.commitment(); // Given to the players before the game
+//! let tile = boneyard.draw().unwrap();
+//!
+//! // After the game, the players check the order against the commitment and the tiles that they drew
+//! let reveal = boneyard.reveal();
+//! assert!(reveal.verify(&commitment));
+//! assert_eq!(reveal.tiles[0], tile);
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+
+use rand::rngs::OsRng;
+use rand::{Rng, TryRngCore};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Boneyard, Configuration, RngStream, Tile};
+
+/// The secret that is hashed with the order of the tiles, so that the order cannot be found from the commitment
+pub type Salt = [u8; 32];
+
+/// A commitment to the order of the tiles in a boneyard: the SHA-256 hash of a salt followed by the ordinals of the tiles
+///
+/// It is displayed in hexadecimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Commitment([u8; 32]);
+
+impl Display for Commitment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// The order of the tiles in a boneyard and the salt that it was committed to with, revealed after a game
+///
+/// # Examples
+/// ```rust
+/// # use rules::{Reveal, Tile};
+///
+/// let reveal = Reveal::new(vec![Tile::from((0, 0)), Tile::from((0, 1))], [7; 32]);
+/// let commitment = reveal.commitment();
+/// assert!(reveal.verify(&commitment));
+///
+/// // A different order does not match the commitment
+/// let changed = Reveal::new(vec![Tile::from((0, 1)), Tile::from((0, 0))], [7; 32]);
+/// assert!(!changed.verify(&commitment));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Reveal {
+    /// Every tile of the boneyard in the order in which they were to be drawn, including the tiles already drawn or dealt
+    pub tiles: Vec<Tile>,
+    /// The salt hashed with the order
+    pub salt: Salt,
+}
+
+impl Reveal {
+    /// Creates a reveal of an order of tiles and a salt
+    ///
+    /// # Arguments
+    /// * `tiles` - Every tile of the boneyard in the order in which they were to be drawn
+    /// * `salt` - The secret hashed with the order
+    pub fn new(tiles: Vec<Tile>, salt: Salt) -> Self {
+        Self { tiles, salt }
+    }
+
+    /// Returns the commitment to the order
+    pub fn commitment(&self) -> Commitment {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt);
+        hasher.update(self.tiles.iter().map(|tile| tile.ordinal).collect::<Vec<u8>>());
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        Commitment(hash)
+    }
+
+    /// Returns `true` if this is the order that was committed to
+    ///
+    /// A player should also check that the tiles they were dealt and drew are where they would be in the order.
+    ///
+    /// # Arguments
+    /// * `commitment` - The commitment given before the game
+    pub fn verify(&self, commitment: &Commitment) -> bool {
+        self.commitment() == *commitment
+    }
+}
+
+/// A boneyard whose next tile cannot be seen and whose order is committed to
+///
+/// Unlike `Boneyard`, it has no `peek` and no way to see the remaining tiles until they are revealed with `reveal`. Its debug
+/// output does not show them either.
+///
+/// # Examples
+/// ```rust
+/// # use rules::{Configuration, HiddenBoneyard};
+///
+/// let configuration = Configuration::default();
+/// let mut boneyard = HiddenBoneyard::new(&configuration);
+/// let commitment = boneyard.commitment();
+/// let hands = boneyard.deal(2, 7);
+/// assert_eq!(boneyard.count(), 14);
+///
+/// let reveal = boneyard.reveal();
+/// assert!(reveal.verify(&commitment));
+/// assert_eq!(reveal.tiles[..7], hands[0]);
+/// ```
+#[derive(Clone)]
+pub struct HiddenBoneyard {
+    /// The tiles
+    boneyard: Boneyard,
+    /// The secret hashed with the order of the tiles
+    salt: Salt,
+    /// The commitment to the order of the tiles when the boneyard was created
+    commitment: Commitment,
+}
+
+impl fmt::Debug for HiddenBoneyard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HiddenBoneyard")
+            .field("count", &self.count())
+            .field("reserve", &self.reserve())
+            .field("commitment", &self.commitment.to_string())
+            .finish()
+    }
+}
+
+impl HiddenBoneyard {
+    /// Creates a hidden boneyard with the tiles of the configuration, shuffled (see `Boneyard::new`)
+    ///
+    /// # Arguments
+    /// * `configuration` - The game configuration containing the rules and tile set
+    pub fn new(configuration: &Configuration) -> Self {
+        Self::with_rng(configuration, &mut configuration.rng(RngStream::Deal))
+    }
+
+    /// Creates a hidden boneyard shuffled with the given random number generator (see `Boneyard::with_rng`)
+    ///
+    /// The salt is not chosen with the generator (see `from_boneyard`).
+    ///
+    /// # Arguments
+    /// * `configuration` - The game configuration containing the rules and tile set
+    /// * `rng` - The random number generator used to shuffle the tiles
+    pub fn with_rng<R: Rng + ?Sized>(configuration: &Configuration, rng: &mut R) -> Self {
+        Self::from_boneyard(Boneyard::with_rng(configuration, rng))
+    }
+
+    /// Hides a boneyard and commits to the order of its remaining tiles, with a salt taken from the operating system's entropy
+    ///
+    /// # Arguments
+    /// * `boneyard` - The boneyard. The tiles already drawn from it are not part of the order.
+    ///
+    /// # Panics
+    /// Panics if the operating system cannot provide the entropy
+    pub fn from_boneyard(boneyard: Boneyard) -> Self {
+        let mut salt = Salt::default();
+        OsRng.try_fill_bytes(&mut salt).expect("The operating system must provide entropy for the salt");
+        Self::with_salt(boneyard, salt)
+    }
+
+    /// Hides a boneyard and commits to the order of its remaining tiles, with the given salt
+    ///
+    /// The order is only secret if the salt cannot be predicted, so this is meant for tests and for salts chosen elsewhere.
+    ///
+    /// # Arguments
+    /// * `boneyard` - The boneyard. The tiles already drawn from it are not part of the order.
+    /// * `salt` - The secret hashed with the order
+    pub fn with_salt(boneyard: Boneyard, salt: Salt) -> Self {
+        let boneyard = Boneyard::with(boneyard.remaining().to_vec()).with_reserve(boneyard.reserve());
+        let commitment = Reveal::new(boneyard.remaining().to_vec(), salt).commitment();
+        Self { boneyard, salt, commitment }
+    }
+
+    /// Returns the commitment to the order of the tiles, which is given to the players before the game
+    pub fn commitment(&self) -> Commitment {
+        self.commitment
+    }
+
+    /// Draws a tile, or returns `None` if there are no tiles that can be drawn (see `Boneyard::draw`)
+    pub fn draw(&mut self) -> Option<Tile> {
+        self.boneyard.draw()
+    }
+
+    /// Deals the starting hands (see `Boneyard::deal`)
+    ///
+    /// # Arguments
+    /// * `num_players` - The number of hands to deal
+    /// * `hand_size` - The number of tiles in each hand
+    ///
+    /// # Panics
+    /// Panics if the boneyard does not have enough tiles for every hand
+    pub fn deal(&mut self, num_players: usize, hand_size: usize) -> Vec<Vec<Tile>> {
+        self.boneyard.deal(num_players, hand_size)
+    }
+
+    /// Returns the number of tiles that can be drawn (see `Boneyard::count`)
+    pub fn count(&self) -> usize {
+        self.boneyard.count()
+    }
+
+    /// Returns `true` if no more tiles can be drawn (see `Boneyard::is_empty`)
+    pub fn is_empty(&self) -> bool {
+        self.boneyard.is_empty()
+    }
+
+    /// Returns the number of tiles that must be left in the boneyard and cannot be drawn
+    pub fn reserve(&self) -> usize {
+        self.boneyard.reserve()
+    }
+
+    /// Reveals the order of the tiles and the salt, e.g. after the game, so that the players can check them against the commitment
+    pub fn reveal(self) -> Reveal {
+        let mut boneyard = self.boneyard;
+        while boneyard.undraw().is_some() {}
+        Reveal::new(boneyard.remaining().to_vec(), self.salt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_boneyard() {
+        let configuration = Configuration::default().with_seed(3);
+        let mut boneyard = HiddenBoneyard::new(&configuration);
+        let commitment = boneyard.commitment();
+        assert_eq!(commitment.to_string().len(), 64);
+
+        // A seeded game shuffles the same way every time, but the salt is not seeded, so the commitment cannot be predicted
+        let other = HiddenBoneyard::new(&configuration);
+        assert_ne!(other.commitment(), commitment);
+        assert_eq!(other.clone().reveal().tiles, boneyard.clone().reveal().tiles);
+
+        let hands = boneyard.deal(2, 7);
+        let drawn = boneyard.draw().unwrap();
+        assert_eq!(boneyard.count(), 13);
+        let reveal = boneyard.reveal();
+        assert!(reveal.verify(&commitment));
+        assert_eq!(reveal.tiles.len(), 28);
+        assert_eq!(reveal.tiles[7..14], hands[1]);
+        assert_eq!(reveal.tiles[14], drawn);
+
+        // The order cannot be changed, and neither can the salt
+        let mut changed = reveal.clone();
+        changed.tiles.swap(20, 21);
+        assert!(!changed.verify(&commitment));
+        let mut changed = reveal;
+        changed.salt[0] ^= 1;
+        assert!(!changed.verify(&commitment));
+    }
+
+    #[test]
+    fn test_hidden_boneyard_with_salt() {
+        // The tiles already drawn are not part of the order, and the reserve is kept
+        let mut boneyard = Boneyard::with(vec![Tile::from((0, 0)), Tile::from((0, 1)), Tile::from((1, 1))]).with_reserve(1);
+        boneyard.draw();
+        let mut hidden = HiddenBoneyard::with_salt(boneyard, [0; 32]);
+        assert_eq!(hidden.reserve(), 1);
+        assert_eq!(hidden.draw(), Some(Tile::from((0, 1))));
+        assert!(hidden.is_empty());
+        assert_eq!(hidden.reveal(), Reveal::new(vec![Tile::from((0, 1)), Tile::from((1, 1))], [0; 32]));
+    }
+}
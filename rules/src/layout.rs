@@ -2,13 +2,18 @@
 //!
 //! This module provides the Layout struct for managing the layout of domino tiles.
 
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
 use multimap::MultiMap;
 use ego_tree;
-use serde::{Serialize, Deserialize, Deserializer, Serializer};
-use serde::de::{self, Visitor, MapAccess};
+#[cfg(feature = "serde")]
+use serde::de::{self, MapAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{Configuration, Tile};
+use crate::metrics::{self, Counter};
+use crate::{Branching, Configuration, LayoutError, MAX_PIPS, Tile};
 
 /// A node in the domino layout graph representing a single placed tile.
 ///
@@ -23,12 +28,13 @@ use crate::{Configuration, Tile};
 ///     children: vec![2, 3],  // Has children at indices 2 and 3
 /// };
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LayoutNode {
     /// The tile
     pub tile: Tile,
     /// Index of the node's parent, `None` indicates this is the root node
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub parent: Option<usize>,
     /// Indexes of the child nodes attached to this tile
     pub children: Vec<usize>,
@@ -46,21 +52,21 @@ pub struct LayoutNode {
 /// let config = Configuration::default();
 /// let mut layout = Layout::new(&config);
 ///
-/// // Place the first tile (must be a double)
+/// // Place the first tile
 /// let double_six = Tile::from((6, 6));
-/// layout.attach(double_six, None);
+/// layout.attach_unchecked(double_six, None);
 ///
 /// // Attach a second tile to the first
 /// let three_six = Tile::from((3, 6));
-/// layout.attach(three_six, Some(0));
+/// layout.attach_unchecked(three_six, Some(0));
 ///
 /// // Get string representation
-/// println!("{}", layout.to_string()); // "6|6-3|6"
+/// assert_eq!(layout.to_string(), "6|6=(6|3)");
 /// ```
 ///
 /// # Important Notes
 /// - The layout contains *copies* of tiles, not references
-/// - The first tile must be a double tile in order for serialization to work.
+/// - Any tile can be the first tile. The tiles allowed by the rules are decided by the variation (see `Opening`).
 #[derive(Debug, Clone)]
 pub struct Layout {
     /// Vector of all tiles in the layout with their connectivity information
@@ -74,9 +80,81 @@ pub struct Layout {
     ///
     /// Array where index corresponds to the domino value (0-6 for standard set) and the value at that index is the count of all
     /// open ends in the layout with that value.
-    pub end_counts: Vec<u8>,
+    pub end_counts: EndCounts,
+    /// The rule for the number of tiles that can be attached to doubles, from the configuration
+    branching: Branching,
+}
+
+/// The number of open ends with each value in a layout
+///
+/// The counts are stored in an array that is large enough for any set, so cloning a layout does not allocate them. `EndCounts`
+/// dereferences to a slice with one count for each value of the set (0 to the set ID), so it is indexed and iterated like one.
+///
+/// # Examples
+/// ```rust
+/// # use rules::EndCounts;
+///
+/// let mut counts = EndCounts::new(6);
+/// counts[6] += 2;
+/// assert_eq!(counts.len(), 7);
+/// assert_eq!(counts, vec![0, 0, 0, 0, 0, 0, 2]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct EndCounts {
+    // Counts of the values 0 to len - 1. The counts after them are always 0.
+    counts: [u8; MAX_PIPS as usize + 1],
+    len: u8,
+}
+
+impl EndCounts {
+    /// Creates a count of 0 for each value of a set
+    ///
+    /// # Arguments
+    /// * `set_id` - ID of the set, which is the highest value
+    ///
+    /// # Panics
+    /// Panics if `set_id` is larger than `MAX_PIPS`.
+    pub const fn new(set_id: u8) -> Self {
+        assert!(set_id <= MAX_PIPS, "set_id must be <= MAX_PIPS");
+        Self { counts: [0; MAX_PIPS as usize + 1], len: set_id + 1 }
+    }
+}
+
+impl Deref for EndCounts {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.counts[..self.len as usize]
+    }
+}
+
+impl DerefMut for EndCounts {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.counts[..self.len as usize]
+    }
+}
+
+impl fmt::Debug for EndCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
 }
 
+impl PartialEq for EndCounts {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for EndCounts {}
+
+impl PartialEq<Vec<u8>> for EndCounts {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        **self == other[..]
+    }
+}
+
+#[cfg(feature = "serde")]
 impl Serialize for Layout {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -84,19 +162,28 @@ impl Serialize for Layout {
     {
         use serde::ser::SerializeStruct;
 
-        let mut state = serializer.serialize_struct("Layout", 2)?;
+        let mut state = serializer.serialize_struct("Layout", 3)?;
         state.serialize_field("nodes", &self.nodes)?;
         state.serialize_field("set_id", &(self.end_counts.len().saturating_sub(1)))?; // -1 because end_counts has length set_id+1
+        // Older layouts have no rule for doubles, so it is only serialized if it differs from the rule they were played with
+        if self.branching == Branching::EveryDouble {
+            state.skip_field("branching")?;
+        } else {
+            state.serialize_field("branching", &self.branching)?;
+        }
         state.end()
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for Layout {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        enum Field { Nodes, SetId }
+        const FIELDS: &[&str] = &["nodes", "set_id", "branching"];
+
+        enum Field { Nodes, SetId, Branching }
 
         impl<'de> Deserialize<'de> for Field {
             fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
@@ -109,7 +196,7 @@ impl<'de> Deserialize<'de> for Layout {
                     type Value = Field;
 
                     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        formatter.write_str("`nodes` or `set_id`")
+                        formatter.write_str("`nodes`, `set_id`, or `branching`")
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -119,7 +206,8 @@ impl<'de> Deserialize<'de> for Layout {
                         match value {
                             "nodes" => Ok(Field::Nodes),
                             "set_id" => Ok(Field::SetId),
-                            _ => Err(de::Error::unknown_field(value, &["nodes", "set_id"])),
+                            "branching" => Ok(Field::Branching),
+                            _ => Err(de::Error::unknown_field(value, FIELDS)),
                         }
                     }
                 }
@@ -143,6 +231,7 @@ impl<'de> Deserialize<'de> for Layout {
             {
                 let mut nodes = None;
                 let mut set_id = None;
+                let mut branching = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -158,17 +247,27 @@ impl<'de> Deserialize<'de> for Layout {
                             }
                             set_id = Some(map.next_value()?);
                         }
+                        Field::Branching => {
+                            if branching.is_some() {
+                                return Err(de::Error::duplicate_field("branching"));
+                            }
+                            branching = Some(map.next_value()?);
+                        }
                     }
                 }
 
                 let nodes: Vec<LayoutNode> = nodes.ok_or_else(|| de::Error::missing_field("nodes"))?;
                 let set_id: usize = set_id.ok_or_else(|| de::Error::missing_field("set_id"))?;
+                if set_id > MAX_PIPS as usize {
+                    return Err(de::Error::custom(format!("A double-{set_id} set is not supported")));
+                }
 
                 // Reconstruct open and end_counts from nodes
                 let mut layout = Layout {
                     nodes,
                     open: MultiMap::new(),
-                    end_counts: vec![0; set_id + 1], // +1 because values are 0..set_id inclusive
+                    end_counts: EndCounts::new(set_id as u8),
+                    branching: branching.unwrap_or(Branching::EveryDouble),
                 };
 
                 layout.rebuild_open_and_end_counts().map_err(de::Error::custom)?;
@@ -176,7 +275,6 @@ impl<'de> Deserialize<'de> for Layout {
             }
         }
 
-        const FIELDS: &[&str] = &["nodes", "set_id"];
         deserializer.deserialize_struct("Layout", FIELDS, LayoutVisitor)
     }
 }
@@ -200,7 +298,8 @@ impl Layout {
         Self {
             nodes: Vec::new(),
             open: MultiMap::new(),
-            end_counts: vec![0; configuration.set_id() as usize + 1], // +1 because values are 0..set_id inclusive
+            end_counts: EndCounts::new(configuration.set_id()),
+            branching: configuration.branching(),
         }
     }
 
@@ -247,25 +346,75 @@ impl Layout {
     /// assert_eq!(layout.open_count(6), 0);
     ///
     /// // Place double-6 tile
-    /// layout.attach(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
     ///
     /// // Now there are 2 open ends with value 6
     /// assert_eq!(layout.open_count(6), 2);
     /// ```
     pub fn open_count(&self, end: u8) -> u8 {
-        self.end_counts[end as usize]
+        self.end_counts.get(end as usize).copied().unwrap_or(0)
     }
 
-    /// Attaches a domino tile to the layout.
+    /// Attaches a domino tile to the layout, or returns an error if it cannot be attached
     ///
-    /// Places a new tile either as the first tile (root) or attached to an existing tile at one of its open ends.
+    /// Places a new tile either as the first tile (root) or attached to an existing tile at one of its open ends. The tile and
+    /// index are checked, so they may come from a file or a network connection. The layout is not changed if an error is
+    /// returned.
     ///
     /// # Parameters
     /// - `tile`: The domino tile to place in the layout
     /// - `parent_index`: The index of the existing node to attach to, or `None` for the first tile
     ///
     /// # Returns
-    /// A tuple containing the new open end value and how many open ends were created.
+    /// A tuple containing the new open end value and how many open ends were created, or the reason the tile cannot be attached
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Layout, LayoutError, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// assert_eq!(layout.attach(Tile::from((6, 6)), None), Ok((6, 2)));
+    /// assert_eq!(layout.attach(Tile::from((3, 6)), None), Err(LayoutError::NotEmpty));
+    /// assert_eq!(layout.attach(Tile::from((3, 6)), Some(1)), Err(LayoutError::NoSuchNode(1)));
+    /// assert_eq!(layout.attach(Tile::from((3, 6)), Some(0)), Ok((3, 1)));
+    /// ```
+    pub fn attach(&mut self, tile: Tile, parent_index: Option<usize>) -> Result<(u8, u8), LayoutError> {
+        let set_id = self.set_id();
+        let (a, b) = tile.as_tuple();
+        if a > set_id || b > set_id {
+            return Err(LayoutError::NotInSet { tile, set_id });
+        }
+
+        match parent_index {
+            Some(parent_index) => {
+                let parent = self.nodes.get(parent_index).ok_or(LayoutError::NoSuchNode(parent_index))?.tile;
+                let (matched_value, _) = tile.matches(&parent).ok_or(LayoutError::Mismatch { tile, parent })?;
+                if !self.open.get_vec(&parent_index).is_some_and(|values| values.contains(&matched_value)) {
+                    return Err(LayoutError::NoOpenEnd { index: parent_index, value: matched_value });
+                }
+            }
+            None => {
+                if !self.nodes.is_empty() {
+                    return Err(LayoutError::NotEmpty);
+                }
+            }
+        }
+        Ok(self.attach_unchecked(tile, parent_index))
+    }
+
+    /// Attaches a domino tile to the layout, without checking it
+    ///
+    /// This is the panicking version of `attach`, for tiles and indexes that are known to be valid, such as those of the plays
+    /// made by the search.
+    ///
+    /// # Parameters
+    /// - `tile`: The domino tile to place in the layout
+    /// - `parent_index`: The index of the existing node to attach to, or `None` for the first tile
+    ///
+    /// # Returns
+    /// A tuple containing the new open end value and how many open ends were created. Both ends of the first tile are open, so if
+    /// it is not a double, the value returned is its lower end and one end with its higher value is also created.
     ///
     /// # Panics
     /// - If `parent_index` refers to a non-existent node
@@ -284,17 +433,18 @@ impl Layout {
     ///
     /// // Place first tile (e.g., 6|6 double)
     /// let double_six = Tile::from((6, 6));
-    /// layout.attach(double_six, None);
+    /// layout.attach_unchecked(double_six, None);
     ///
     /// // Attach second tile to first tile's node (index 0)
     /// let three_six = Tile::from((3, 6));
-    /// layout.attach(three_six, Some(0));
+    /// layout.attach_unchecked(three_six, Some(0));
     ///
     /// // Attach third tile to second tile's node (index 1)
     /// let one_three = Tile::from((1, 3));
-    /// layout.attach(one_three, Some(1));
+    /// layout.attach_unchecked(one_three, Some(1));
     /// ```
-    pub fn attach(&mut self, tile: Tile, parent_index: Option<usize>) -> (u8, u8) {
+    pub fn attach_unchecked(&mut self, tile: Tile, parent_index: Option<usize>) -> (u8, u8) {
+        metrics::increment(Counter::TilesAttached);
         let (end_value, created_count) = match parent_index {
             Some(parent_index) => {
                 // Normal case: tile is attached to an existing tile
@@ -304,6 +454,7 @@ impl Layout {
 
                 // Determine the matched and open values
                 let (matched_value, open_value) = tile.matches(parent).expect("Tiles should be attachable");
+                let open_count = if tile.is_double() { self.double_open_ends() } else { 1 };
 
                 // Add a new tile node to the layout
                 let tile_index = self.nodes.len(); // Index of the new tile
@@ -313,8 +464,7 @@ impl Layout {
                     children: Vec::new(),
                 });
 
-                // Add the open ends. If the tile is a double, add twice.
-                let open_count = if tile.is_double() { 2 } else { 1 };
+                // Add the open ends. A double may have more than one (see `Branching`).
                 for _ in 0..open_count {
                     self.open.insert(tile_index, open_value);
                 }
@@ -330,7 +480,6 @@ impl Layout {
             }
             None => {
                 // The first tile is a special case
-                assert!(tile.is_double());
                 assert!(self.nodes.is_empty() && self.open.is_empty());
 
                 // Add the new tile node to the layout
@@ -340,31 +489,136 @@ impl Layout {
                     children: Vec::new(),
                 });
 
-                // Both ends are open for the first tile.
-                let (a, _) = tile.as_tuple();
-                self.open.insert(0, a);
-                self.open.insert(0, a);
-                self.end_counts[a as usize] += 2;
-                (a, 2)
+                // Both ends are open for the first tile, and a double may have more open ends (see `Branching`)
+                let (a, b) = tile.as_tuple();
+                if tile.is_double() {
+                    let open_count = self.branching.open_ends(true, true);
+                    for _ in 0..open_count {
+                        self.open.insert(0, a);
+                    }
+                    self.end_counts[a as usize] += open_count;
+                    (a, open_count)
+                } else {
+                    self.open.insert(0, a);
+                    self.open.insert(0, b);
+                    self.end_counts[a as usize] += 1;
+                    self.end_counts[b as usize] += 1;
+                    (a, 1)
+                }
             }
         };
 
         (end_value, created_count)
     }
 
+    /// Removes the last tile attached to the layout
+    ///
+    /// This reverses the most recent call to `attach`, reopening the end that the tile was attached to. The last tile never has
+    /// any children because tiles are only attached to tiles already in the layout.
+    ///
+    /// # Returns
+    /// The tile that was removed, or `None` if the layout is empty
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Layout;
+    /// # use rules::{Configuration, Tile};
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+    ///
+    /// assert_eq!(layout.detach_last(), Some(Tile::from((3, 6))));
+    /// assert_eq!(layout.open_count(6), 2);
+    /// assert_eq!(layout.open_count(3), 0);
+    /// ```
+    pub fn detach_last(&mut self) -> Option<Tile> {
+        let node = self.nodes.pop()?;
+        let tile_index = self.nodes.len();
+        assert!(node.children.is_empty());
+
+        // Remove the tile's open ends
+        self.remove_open_ends(tile_index);
+        if let Some(parent_index) = node.parent {
+            let parent = &self.nodes[parent_index].tile;
+            let (matched_value, _) = node.tile.matches(parent).expect("Tiles should be attachable");
+
+            // Reopen the parent's end and detach the tile from the parent
+            self.open.insert(parent_index, matched_value);
+            self.end_counts[matched_value as usize] += 1;
+            self.nodes[parent_index].children.retain(|&child| child != tile_index);
+        }
+        Some(node.tile)
+    }
+
+    /// Removes a tile that has no tiles attached to it
+    ///
+    /// The end that the tile was attached to is reopened. Unlike `detach_last`, the tile may be anywhere in the layout, so the
+    /// indexes of the nodes after it are decremented. The layout is not changed if an error is returned.
+    ///
+    /// # Arguments
+    /// * `node_index` - The index of the node to remove
+    ///
+    /// # Returns
+    /// The tile that was removed, or the reason it cannot be removed
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Layout, LayoutError, Tile};
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+    /// layout.attach_unchecked(Tile::from((4, 6)), Some(0));
+    ///
+    /// assert_eq!(layout.detach(0), Err(LayoutError::NotALeaf(0)));
+    /// assert_eq!(layout.detach(1), Ok(Tile::from((3, 6))));
+    /// assert_eq!(layout.to_string(), "6|6=(6|4)");
+    /// assert_eq!(layout.open_count(6), 1);
+    /// assert_eq!(layout.open_count(3), 0);
+    /// ```
+    pub fn detach(&mut self, node_index: usize) -> Result<Tile, LayoutError> {
+        let node = self.nodes.get(node_index).ok_or(LayoutError::NoSuchNode(node_index))?;
+        if !node.children.is_empty() {
+            return Err(LayoutError::NotALeaf(node_index));
+        }
+        if node_index + 1 == self.nodes.len() {
+            return Ok(self.detach_last().expect("The layout is not empty"));
+        }
+
+        // The root has children unless it is the only node, so the node has a parent
+        let node = self.nodes.remove(node_index);
+        let parent_index = node.parent.expect("Only the root has no parent");
+        let (matched_value, _) = node.tile.matches(&self.nodes[parent_index].tile).expect("Tiles should be attachable");
+        self.nodes[parent_index].children.retain(|&child| child != node_index);
+
+        // Remove the tile's open ends and reopen the parent's end
+        self.remove_open_ends(node_index);
+        self.open.insert(parent_index, matched_value);
+        self.end_counts[matched_value as usize] += 1;
+
+        // Renumber the nodes after the removed node
+        let renumber = |index: usize| if index > node_index { index - 1 } else { index };
+        for node in &mut self.nodes {
+            node.parent = node.parent.map(renumber);
+            for child in &mut node.children {
+                *child = renumber(*child);
+            }
+        }
+        self.open = std::mem::take(&mut self.open).into_iter().map(|(index, values)| (renumber(index), values)).collect();
+        Ok(node.tile)
+    }
+
     /// Returns a vector of node indices that have an open end with the specified value.
     ///
     /// This function scans the layout and returns the indices of all nodes that currently have an open end matching the given
-    /// value. If there are no such nodes, an empty vector is returned.
+    /// value. If there are no such nodes, an empty vector is returned. Use `nodes_with_open_end` to avoid allocating the vector.
     ///
     /// # Arguments
     /// * `end_value` - The domino value to search for among open ends (e.g., 0-6 for double-six)
     ///
     /// # Returns
-    /// A vector of node indices.
-    ///
-    /// # Panics
-    /// Panics if `end_value` is not a valid end value.
+    /// A vector of node indices. The vector is empty if `end_value` is not a value in the set.
     ///
     /// # Examples
     /// ```rust
@@ -372,8 +626,8 @@ impl Layout {
     /// # use rules::{Configuration, Tile};
     /// let config = Configuration::default();
     /// let mut layout = Layout::new(&config);
-    /// layout.attach(Tile::from((6, 6)), None);
-    /// layout.attach(Tile::from((3, 6)), Some(0));
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
     /// // Node 0 has one open 6, node 1 has open 3
     /// let six_nodes = layout.get_nodes_with_open_end(6);
     /// assert!(six_nodes.contains(&0));
@@ -383,18 +637,34 @@ impl Layout {
     /// assert!(four_nodes.is_empty());
     /// ```
     pub fn get_nodes_with_open_end(&self, end_value: u8) -> Vec<usize> {
-        // Quick return if there are no open ends with that value
-        assert!((end_value as usize) < self.end_counts.len());
-        if self.end_counts[end_value as usize] == 0 {
-            return Vec::new();
-        }
+        self.nodes_with_open_end(end_value).collect()
+    }
 
+    /// Returns an iterator over the indices of the nodes that have an open end with the specified value.
+    ///
+    /// Each node is returned once, even if it has more than one open end with the value. The order is unspecified.
+    ///
+    /// # Arguments
+    /// * `end_value` - The domino value to search for among open ends (e.g., 0-6 for double-six)
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Layout;
+    /// # use rules::{Configuration, Tile};
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+    /// assert_eq!(layout.nodes_with_open_end(3).next(), Some(1));
+    /// assert_eq!(layout.nodes_with_open_end(4).count(), 0);
+    /// ```
+    pub fn nodes_with_open_end(&self, end_value: u8) -> impl Iterator<Item = usize> + '_ {
+        // Nothing is scanned if there are no open ends with that value
+        let any_open = self.open_count(end_value) > 0;
         self.open
-            .iter()
-            .filter_map(|(node_index, value)| {
-                (*value == end_value).then_some(*node_index)
-            })
-            .collect()
+            .iter_all()
+            .filter(move |(_, values)| any_open && values.contains(&end_value))
+            .map(|(node_index, _)| *node_index)
     }
 
     /// Creates an ego_tree representation of the layout
@@ -416,11 +686,11 @@ impl Layout {
     ///
     /// // Build a simple chain: 6|6-6|3-3|1
     /// let double_six = Tile::from((6, 6));
-    /// layout.attach(double_six, None);
+    /// layout.attach_unchecked(double_six, None);
     /// let three_six = Tile::from((3, 6));
-    /// layout.attach(three_six, Some(0));
+    /// layout.attach_unchecked(three_six, Some(0));
     /// let one_three = Tile::from((1, 3));
-    /// layout.attach(one_three, Some(1));
+    /// layout.attach_unchecked(one_three, Some(1));
     ///
     /// let tree = layout.to_tree().unwrap();
     /// assert_eq!(tree.root().value(), &double_six);
@@ -464,85 +734,267 @@ impl Layout {
         Some(tree)
     }
 
+    /// Creates a layout from a tree of tiles.
+    ///
+    /// This is the inverse of [`Layout::to_tree`]. Each child in the tree is attached to its parent.
+    ///
+    /// # Arguments
+    /// * `configuration` - The configuration determining the domino set
+    /// * `tree` - The tree of tiles. The root is the first tile and each child is connected to its parent.
+    ///
+    /// # Returns
+    /// The layout, or an error if a value is not in the set, a tile does not match its parent, a tile has too many children, or
+    /// two tiles are connected to the same end of a tile (see `LayoutError`)
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Layout, Tile};
+    ///
+    /// let mut tree = ego_tree::Tree::new(Tile::from((6, 6)));
+    /// tree.root_mut().append(Tile::from((3, 6)));
+    ///
+    /// let layout = Layout::from_tree(&Configuration::default(), &tree).unwrap();
+    /// assert_eq!(layout.nodes.len(), 2);
+    /// assert_eq!(layout.open_count(6), 1);
+    /// assert_eq!(layout.open_count(3), 1);
+    /// ```
+    pub fn from_tree(configuration: &Configuration, tree: &ego_tree::Tree<Tile>) -> Result<Self, LayoutError> {
+        let mut layout = Self::new(configuration);
+
+        // Map from ego_tree NodeId to layout node index. Nodes are added in pre-order, so a parent is always added before its
+        // children.
+        let mut indexes: std::collections::HashMap<ego_tree::NodeId, usize> = std::collections::HashMap::new();
+        for node in tree.root().descendants() {
+            let tile = *node.value();
+            let (a, b) = tile.as_tuple();
+            if a > configuration.set_id() || b > configuration.set_id() {
+                return Err(LayoutError::NotInSet { tile, set_id: configuration.set_id() });
+            }
+
+            let index = layout.nodes.len();
+            let parent = node.parent().map(|parent| indexes[&parent.id()]);
+            if let Some(parent_index) = parent {
+                let parent_node = &mut layout.nodes[parent_index];
+                if tile.matches(&parent_node.tile).is_none() {
+                    return Err(LayoutError::Mismatch { tile, parent: parent_node.tile });
+                }
+                parent_node.children.push(index);
+            }
+            layout.nodes.push(LayoutNode { tile, parent, children: Vec::new() });
+            indexes.insert(node.id(), index);
+        }
+
+        layout.rebuild_open_and_end_counts()?;
+        Ok(layout)
+    }
+
+    /// Checks that the layout is consistent, e.g. after it has been deserialized or its public fields have been changed.
+    ///
+    /// Unlike the checks made while the layout is built, every problem is reported rather than only the first.
+    ///
+    /// # Arguments
+    /// * `configuration` - The configuration determining the domino set and the rule for doubles
+    ///
+    /// # Errors
+    /// Returns every problem found: a tile that is not in the set or is in the layout more than once, a node that is not linked
+    /// into a tree, a tile that does not match its parent, a tile with too many children or with two tiles on one end, and open
+    /// ends or end counts that are not the ones left by the tiles (see `LayoutError`). The open ends are only checked if the tiles
+    /// are in the set and form a tree.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Layout, LayoutError, Tile};
+    ///
+    /// let configuration = Configuration::default();
+    /// let mut layout = Layout::new(&configuration);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+    /// assert_eq!(layout.validate(&configuration), Ok(()));
+    ///
+    /// layout.end_counts[3] = 2;
+    /// assert_eq!(layout.validate(&configuration), Err(vec![LayoutError::WrongEndCount { value: 3, expected: 1, actual: 2 }]));
+    /// ```
+    pub fn validate(&self, configuration: &Configuration) -> Result<(), Vec<LayoutError>> {
+        let mut errors = self.node_errors(configuration.set_id());
+        let sound = errors.is_empty();
+
+        let mut seen = HashSet::new();
+        for node in &self.nodes {
+            let duplicate = LayoutError::Duplicate(node.tile);
+            if !seen.insert(node.tile) && !errors.contains(&duplicate) {
+                errors.push(duplicate);
+            }
+        }
+
+        // The open ends can only be determined from tiles that are in the set and form a tree
+        if sound {
+            match self.find_open_ends(configuration.branching(), configuration.set_id()) {
+                Ok((open, end_counts)) => {
+                    let indexes: BTreeSet<usize> = self.open.keys().chain(open.keys()).copied().collect();
+                    for index in indexes {
+                        let sorted = |open: &MultiMap<usize, u8>| {
+                            let mut values = open.get_vec(&index).cloned().unwrap_or_default();
+                            values.sort_unstable();
+                            values
+                        };
+                        if sorted(&self.open) != sorted(&open) {
+                            errors.push(LayoutError::WrongOpenEnds(index));
+                        }
+                    }
+
+                    for value in 0..self.end_counts.len().max(end_counts.len()) {
+                        let expected = end_counts.get(value).copied().unwrap_or(0);
+                        let actual = self.end_counts.get(value).copied().unwrap_or(0);
+                        if expected != actual {
+                            errors.push(LayoutError::WrongEndCount { value: value as u8, expected, actual });
+                        }
+                    }
+                }
+                Err(open_end_errors) => errors.extend(open_end_errors),
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
     /// Rebuilds the `open` and `end_counts` fields from the `nodes` structure.
     ///
     /// This method is used during deserialization to reconstruct the derived state from the serialized nodes. It analyzes the tree
     /// structure to determine which ends are open and updates the counts accordingly.
-    fn rebuild_open_and_end_counts(&mut self) -> Result<(), String>{
-        self.open.clear();
-        self.end_counts.fill(0);
-
-        if self.nodes.is_empty() {
-            return Ok(());
-        }
+    fn rebuild_open_and_end_counts(&mut self) -> Result<(), LayoutError> {
+        self.check_nodes()?;
+        let (open, end_counts) = self
+            .find_open_ends(self.branching, self.set_id())
+            .map_err(|errors| errors.into_iter().next().expect("at least one error"))?;
+        self.open = open;
+        self.end_counts = end_counts;
+        Ok(())
+    }
 
-        // For each node, determine its open ends based on its connectivity
+    // Determines the open ends of the nodes and the number of open ends with each value, from the tiles and how they are
+    // connected. The nodes must have passed `check_nodes`. Every node with too many children or with two tiles on one end is
+    // reported.
+    fn find_open_ends(&self, branching: Branching, set_id: u8) -> Result<(MultiMap<usize, u8>, EndCounts), Vec<LayoutError>> {
+        let mut open = MultiMap::new();
+        let mut end_counts = EndCounts::new(set_id);
+        let mut errors = Vec::new();
+
+        // For each node, determine its open ends based on its connectivity. The nodes are in the order the tiles were played, so
+        // the first double is the one that can be a spinner.
+        let mut first_double = true;
         for (node_index, node) in self.nodes.iter().enumerate() {
-            // Validate the children count
-            // Double tiles can have up to 2 children.
-            // Non-doubles can have up to 2 children if it is the root node (otherwise only 1).
-            if node.children.len() > 2 {
-                return Err(format!("Tile node {node_index} has more than 2 children"));
+            let (a, b) = node.tile.as_tuple();
+
+            if node.tile.is_double() {
+                // A double has the open ends allowed by the rule for doubles, less those used by its children
+                let max = usize::from(branching.open_ends(first_double, node.parent.is_none()));
+                first_double = false;
+                if node.children.len() > max {
+                    errors.push(LayoutError::TooManyChildren { index: node_index, max });
+                }
+                for _ in node.children.len()..max {
+                    open.insert(node_index, a);
+                    end_counts[a as usize] += 1;
+                }
+                continue;
             }
 
-            let (a, b) = node.tile.as_tuple();
+            // A non-double can have a child on each end if it is the root, and only on the end not matching its parent otherwise
+            let max = if node.parent.is_some() { 1 } else { 2 };
+            if node.children.len() > max {
+                errors.push(LayoutError::TooManyChildren { index: node_index, max });
+                continue;
+            }
 
             // Count connections for each value
             let mut connections_a = 0;
             let mut connections_b = 0;
-
-            // Count parent connection
-            if let Some(parent_index) = node.parent {
-                let (parent_a, parent_b) = self.nodes[parent_index].tile.as_tuple();
-                if a == parent_a || a == parent_b {
+            let neighbors = node.parent.into_iter().chain(node.children.iter().copied());
+            for neighbor in neighbors {
+                let (neighbor_a, neighbor_b) = self.nodes[neighbor].tile.as_tuple();
+                if a == neighbor_a || a == neighbor_b {
                     connections_a += 1;
                 } else {
                     connections_b += 1;
                 }
-            } else {
-                // Root node, a double has an implicit parent connection, a non-double does not
-                if node.tile.is_double() {
-                    connections_a += 1; // both ends are 'a'
-                }
             }
 
-            // Count child connections
-            for &child_index in &node.children {
-                let (child_a, child_b) = self.nodes[child_index].tile.as_tuple();
-                if a == child_a || a == child_b {
-                    connections_a += 1;
-                } else {
-                    connections_b += 1;
+            // Each end is connected to at most one tile, and it is open if nothing is connected to it
+            for (value, connections) in [(a, connections_a), (b, connections_b)] {
+                if connections > 1 {
+                    errors.push(LayoutError::NoOpenEnd { index: node_index, value });
                 }
             }
+            if connections_a == 0 {
+                open.insert(node_index, a);
+                end_counts[a as usize] += 1;
+            }
+            if connections_b == 0 {
+                open.insert(node_index, b);
+                end_counts[b as usize] += 1;
+            }
+        }
 
-            // Determine open ends
-            if node.tile.is_double() {
-                // For double tiles, both ends have the same value
-                // Total connections possible = 3, used = connections_a (since a == b)
-                let total_connections = connections_a; // connections_a == connections_b for doubles
-                let remaining_connections = 3 - total_connections;
+        if errors.is_empty() { Ok((open, end_counts)) } else { Err(errors) }
+    }
 
-                for _ in 0..remaining_connections {
-                    self.open.insert(node_index, a);
-                    self.end_counts[a as usize] += 1;
-                }
-            } else {
-                if node.parent.is_some() && node.children.len() > 1 {
-                    return Err(format!("Non-double tile node {node_index} has more than 1 child"));
-                }
-                // For regular tiles, each end can have at most 1 connection
-                if connections_a == 0 {
-                    self.open.insert(node_index, a);
-                    self.end_counts[a as usize] += 1;
-                }
-                if connections_b == 0 {
-                    self.open.insert(node_index, b);
-                    self.end_counts[b as usize] += 1;
+    // Returns the number of open ends of a double attached to a tile in the layout. Only the first double can be a spinner.
+    fn double_open_ends(&self) -> u8 {
+        let first_double = !self.nodes.iter().any(|node| node.tile.is_double());
+        self.branching.open_ends(first_double, false)
+    }
+
+    // Returns the ID of the set, which is the highest value that can be on an end
+    fn set_id(&self) -> u8 {
+        (self.end_counts.len() - 1) as u8
+    }
+
+    // Checks that the tiles are in the set and that the nodes form a tree rooted at the first node, in which every tile matches
+    // its parent. Nodes that come from outside (e.g. deserialized) must pass this before the open ends can be rebuilt.
+    fn check_nodes(&self) -> Result<(), LayoutError> {
+        match self.node_errors(self.set_id()).into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    // Returns the problems found by `check_nodes` for a set, in the order of the nodes
+    fn node_errors(&self, set_id: u8) -> Vec<LayoutError> {
+        let mut errors = Vec::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let (a, b) = node.tile.as_tuple();
+            if a > set_id || b > set_id {
+                errors.push(LayoutError::NotInSet { tile: node.tile, set_id });
+            }
+
+            let linked_to_parent = match node.parent {
+                None => index == 0,
+                Some(parent) => parent < index && self.nodes[parent].children.contains(&index),
+            };
+            let linked_to_children = node
+                .children
+                .iter()
+                .all(|&child| child < self.nodes.len() && self.nodes[child].parent == Some(index));
+            if !linked_to_parent || !linked_to_children {
+                errors.push(LayoutError::BrokenLink(index));
+                continue;
+            }
+
+            if let Some(parent) = node.parent {
+                let parent = self.nodes[parent].tile;
+                if node.tile.matches(&parent).is_none() {
+                    errors.push(LayoutError::Mismatch { tile: node.tile, parent });
                 }
             }
         }
-        Ok(())
+        errors
+    }
+
+    // Removes all of the open ends of a node, which are all of its ends that tiles can be attached to if it has no children
+    fn remove_open_ends(&mut self, index: usize) {
+        for value in self.open.remove(&index).unwrap_or_default() {
+            self.end_counts[value as usize] -= 1;
+        }
     }
 
     // Removes a tile from the open list. Note that a double tile will have two entries with the same key, and only one of the
@@ -559,7 +1011,7 @@ impl Layout {
         }
     }
 
-    /// Recursive helper for formatting the layout as a string.
+    /// Recursive helper for formatting the layout as a string. `open` is the value of the node that is written first.
     fn fmt_r(&self, node: &LayoutNode, open: u8) -> String {
         let (a, b) = node.tile.as_tuple();
         let (a, b) = if open == a { (a, b) } else { (b, a) }; // Swap if necessary (tiles are appended left-to-right)
@@ -567,13 +1019,15 @@ impl Layout {
         let mut result = format!("{a}|{b}");
 
         // Add the children recursively
-        if a == b && node.children.len() >= 1 {
-            // Double tile with children
+        if !node.children.is_empty() && (a == b || node.children.len() > 1) {
+            // Double tile with children, or a first tile with a chain at each end. The chains of a first tile that is not a double
+            // are attached to its left and right values in that order.
             result.push_str("=(");
             for (i, &child) in node.children.iter().enumerate() {
                 let child_node = &self.nodes[child];
                 if i > 0 { result.push(','); }
-                result.push_str(&self.fmt_r(child_node, b));
+                let end = if i == 0 { a } else { b };
+                result.push_str(&self.fmt_r(child_node, end));
             }
             result.push(')');
         } else if node.children.len() == 1 {
@@ -592,20 +1046,20 @@ impl Layout {
 /// Generates a textual representation showing how domino tiles are connected in the layout. The format uses specific notation
 /// to represent the tree structure:
 /// - Single connections: `tile1-tile2`
-/// - Multiple branches: `tile=(branch1,branch2,branch3)`
-/// - Tiles shown as `a|b`
+/// - Branches from a double: `double=(branch1,branch2)`
+/// - Chains at both ends of a first tile that is not a double: `tile=(left chain,right chain)`
+/// - Tiles shown as `a|b`, where `a` is the value connected to the preceding tile
+///
+/// The representation is the syntax accepted by `game::layout_parser::parse_layout`, which rebuilds the same layout from it.
 ///
 /// # Returns
 /// A `String` representing the complete layout structure, or an empty string if no tiles have been placed.
 ///
 /// # Format Examples
-/// - **Linear chain**: `6|6-6|3-3|1` (each tile connected to the next)
-/// - **Branching**: `3|3=(3|2,3|5,3|4)` (multiple tiles attached to one tile)
-/// - **Complex tree**: `6|6-6|3=(3|1,3|5-5|2)`
-///
-/// # Panics
-/// Panics if the root tile (first placed) is not a double tile, as the current implementation requires this for proper string
-/// generation.
+/// - **Linear chain**: `6|6=(6|3-3|1)` (each tile connected to the next)
+/// - **Branching**: `3|3=(3|2,3|5)` (two tiles attached to a double)
+/// - **Complex tree**: `6|6=(6|3-3|3=(3|1,3|5-5|2))`
+/// - **First tile not a double**: `1|2=(1|3,2|4-4|4)`
 ///
 /// # Examples
 /// ```rust
@@ -615,33 +1069,33 @@ impl Layout {
 /// let config = Configuration::new(4, Variation::Traditional, 6, 6);
 /// let mut layout = Layout::new(&config);
 ///
-/// // Simple chain: 6|6-6|3-3|1
-/// let double_six = Tile::from((6, 6));
-/// layout.attach(double_six, None);
-/// let three_six = Tile::from((3, 6));
-/// layout.attach(three_six, Some(0));
-/// let one_three = Tile::from((1, 3));
-/// layout.attach(one_three, Some(1));
-/// // Layout string representation will show tiles as connected
+/// // Simple chain
+/// layout.attach_unchecked(Tile::from((6, 6)), None);
+/// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+/// layout.attach_unchecked(Tile::from((1, 3)), Some(1));
+/// assert_eq!(layout.to_string(), "6|6=(6|3-3|1)");
 ///
-/// // Simple branching example
-/// let mut layout2 = Layout::new(&config);
-/// let double_six = Tile::from((6, 6));
-/// layout2.attach(double_six, None);
-/// let three_six = Tile::from((3, 6));
-/// layout2.attach(three_six, Some(0));
-/// // Layout will show branching pattern
+/// // Branching
+/// layout.attach_unchecked(Tile::from((2, 6)), Some(0));
+/// assert_eq!(layout.to_string(), "6|6=(6|3-3|1,6|2)");
 /// ```
 impl Display for Layout {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        if self.nodes.is_empty() {
+        let Some(root) = self.nodes.first() else {
             return Ok(());
-        }
+        };
 
-        let root = &self.nodes[0];
+        // The value written first is the one connected to the first chain, or the one without a chain if there is only one
         let (a, b) = root.tile.as_tuple();
-        assert_eq!(a, b, "First node must be a double");
-        write!(f, "{}", self.fmt_r(root, b))
+        let open = match root.children.as_slice() {
+            [] => a,
+            [child] => {
+                let (matched, _) = self.nodes[*child].tile.matches(&root.tile).expect("Tiles should be attached");
+                if matched == a { b } else { a }
+            }
+            [first, ..] => self.nodes[*first].tile.matches(&root.tile).expect("Tiles should be attached").0,
+        };
+        write!(f, "{}", self.fmt_r(root, open))
     }
 }
 
@@ -692,7 +1146,7 @@ mod tests {
         let mut layout = Layout::new(&configuration);
         let double_six = create_tile(6, 6);
 
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         assert_eq!(layout.nodes.len(), 1);
         assert_eq!(layout.nodes[0].tile, double_six);
@@ -712,8 +1166,8 @@ mod tests {
         let double_six = create_tile(6, 6);
         let three_six = create_tile(3, 6);
 
-        layout.attach(double_six, None);
-        layout.attach(three_six, Some(0));
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, Some(0));
 
         assert_eq!(layout.nodes.len(), 2);
 
@@ -743,9 +1197,9 @@ mod tests {
         let double_three = create_tile(3, 3);
         let three_six = create_tile(3, 6);
 
-        layout.attach(double_six, None);
-        layout.attach(three_six, Some(0));
-        layout.attach(double_three, Some(1));
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, Some(0));
+        layout.attach_unchecked(double_three, Some(1));
 
         // Double tile should have two open ends with the same value
         let double_open: Vec<u8> = layout.open.get_vec(&2).unwrap().clone();
@@ -753,6 +1207,73 @@ mod tests {
         assert!(double_open.iter().all(|&x| x == 3));
     }
 
+    #[test]
+    fn test_detach_last() {
+        let configuration = crate::Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.detach_last(), None);
+
+        layout.attach_unchecked(create_tile(6, 6), None);
+        layout.attach_unchecked(create_tile(3, 6), Some(0));
+        let before = layout.clone();
+        layout.attach_unchecked(create_tile(3, 3), Some(1));
+
+        assert_eq!(layout.detach_last(), Some(create_tile(3, 3)));
+        assert_eq!(layout.nodes.len(), before.nodes.len());
+        assert!(layout.nodes[1].children.is_empty());
+        assert_eq!(layout.end_counts, before.end_counts);
+        assert_eq!(layout.open.get_vec(&1), before.open.get_vec(&1));
+        assert!(layout.open.get_vec(&2).is_none());
+
+        assert_eq!(layout.detach_last(), Some(create_tile(3, 6)));
+        assert_eq!(layout.detach_last(), Some(create_tile(6, 6)));
+        assert!(layout.is_empty());
+        assert!(layout.open.is_empty());
+        assert!(layout.end_counts.iter().all(|&count| count == 0));
+    }
+
+    #[test]
+    fn test_detach() {
+        let configuration = crate::Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.detach(0), Err(LayoutError::NoSuchNode(0)));
+
+        // 6|6 with 3|6 and 4|6 attached, and 3|3 attached to 3|6
+        layout.attach_unchecked(create_tile(6, 6), None);
+        layout.attach_unchecked(create_tile(3, 6), Some(0));
+        layout.attach_unchecked(create_tile(4, 6), Some(0));
+        layout.attach_unchecked(create_tile(3, 3), Some(1));
+        assert_eq!(layout.detach(1), Err(LayoutError::NotALeaf(1)));
+
+        // Removing 4|6 renumbers 3|3
+        let mut expected = Layout::new(&configuration);
+        expected.attach_unchecked(create_tile(6, 6), None);
+        expected.attach_unchecked(create_tile(3, 6), Some(0));
+        expected.attach_unchecked(create_tile(3, 3), Some(1));
+        assert_eq!(layout.detach(2), Ok(create_tile(4, 6)));
+        assert_eq!(layout.to_string(), expected.to_string());
+        assert_eq!(layout.end_counts, expected.end_counts);
+        assert_eq!(layout.nodes[0].children, vec![1]);
+        assert_eq!(layout.nodes[1].children, vec![2]);
+        assert_eq!(layout.nodes[2].parent, Some(1));
+        for index in 0..layout.nodes.len() {
+            let mut values = layout.open.get_vec(&index).cloned().unwrap_or_default();
+            let mut expected_values = expected.open.get_vec(&index).cloned().unwrap_or_default();
+            values.sort();
+            expected_values.sort();
+            assert_eq!(values, expected_values);
+        }
+
+        // The renumbered layout can be extended and taken apart
+        layout.attach_unchecked(create_tile(2, 3), Some(2));
+        assert_eq!(layout.detach(3), Ok(create_tile(2, 3)));
+        assert_eq!(layout.detach(2), Ok(create_tile(3, 3)));
+        assert_eq!(layout.detach(1), Ok(create_tile(3, 6)));
+        assert_eq!(layout.detach(0), Ok(create_tile(6, 6)));
+        assert!(layout.is_empty());
+        assert!(layout.end_counts.iter().all(|&count| count == 0));
+    }
+
     #[test]
     fn test_branching_layout() {
         let configuration = crate::Configuration::default();
@@ -761,9 +1282,9 @@ mod tests {
         let two_three = create_tile(2, 3);
         let three_five = create_tile(3, 5);
 
-        layout.attach(double_three, None);
-        layout.attach(two_three, Some(0));
-        layout.attach(three_five, Some(0));
+        layout.attach_unchecked(double_three, None);
+        layout.attach_unchecked(two_three, Some(0));
+        layout.attach_unchecked(three_five, Some(0));
 
         assert_eq!(layout.nodes.len(), 3);
         assert_eq!(layout.nodes[0].children, vec![1, 2]);
@@ -772,6 +1293,63 @@ mod tests {
         assert!(layout.open.get_vec(&0).is_none() || layout.open.get_vec(&0).unwrap().is_empty());
     }
 
+    #[test]
+    fn test_spinner() {
+        // In All Fives, the first double is a spinner with four arms, and other doubles continue the line
+        let configuration = crate::Configuration::new(2, crate::Variation::AllFives, 6, 7);
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.attach_unchecked(create_tile(5, 5), None), (5, 4));
+        for tile in [create_tile(1, 5), create_tile(2, 5), create_tile(3, 5)] {
+            layout.attach_unchecked(tile, Some(0));
+        }
+        assert_eq!(layout.open_count(5), 1);
+        assert_eq!(layout.attach_unchecked(create_tile(3, 3), Some(3)), (3, 1));
+        assert_eq!(layout.attach(create_tile(3, 4), Some(4)), Ok((4, 1)));
+        assert_eq!(layout.attach(create_tile(3, 6), Some(4)), Err(LayoutError::NoOpenEnd { index: 4, value: 3 }));
+
+        // Taking the tiles back reopens the spinner's arms
+        let before = layout.clone();
+        layout.attach_unchecked(create_tile(4, 5), Some(0));
+        assert_eq!(layout.open_count(5), 0);
+        layout.detach_last();
+        assert_eq!(layout.end_counts, before.end_counts);
+        while layout.detach_last().is_some() {}
+        assert!(layout.end_counts.iter().all(|&count| count == 0));
+
+        // A spinner that is not the first tile has an arm attached to the tile it is played on
+        layout.attach_unchecked(create_tile(2, 5), None);
+        assert_eq!(layout.attach_unchecked(create_tile(5, 5), Some(0)), (5, 3));
+        assert_eq!(layout.attach_unchecked(create_tile(2, 2), Some(0)), (2, 1));
+    }
+
+    #[test]
+    fn test_chicken_foot() {
+        let configuration = crate::Configuration::default().with_branching(crate::Branching::ChickenFoot { arms: 4 });
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.attach_unchecked(create_tile(6, 6), None), (6, 4));
+        layout.attach_unchecked(create_tile(2, 6), Some(0));
+        assert_eq!(layout.attach_unchecked(create_tile(2, 2), Some(1)), (2, 3));
+        assert_eq!(layout.open.get_vec(&2), Some(&vec![2, 2, 2]));
+        assert_eq!(layout.open_count(6), 3);
+
+        // The open ends are rebuilt with the same rule
+        let tree = layout.to_tree().unwrap();
+        let rebuilt = Layout::from_tree(&configuration, &tree).unwrap();
+        assert_eq!(rebuilt.end_counts, layout.end_counts);
+
+        // A double cannot have more children than open ends
+        let mut tree = ego_tree::Tree::new(create_tile(6, 6));
+        let mut root = tree.root_mut();
+        let mut double = root.append(create_tile(6, 6));
+        for tile in [create_tile(1, 6), create_tile(2, 6), create_tile(3, 6), create_tile(4, 6)] {
+            double.append(tile);
+        }
+        assert_eq!(
+            Layout::from_tree(&configuration, &tree).unwrap_err(),
+            LayoutError::TooManyChildren { index: 1, max: 3 }
+        );
+    }
+
     #[test]
     fn test_to_string_empty() {
         let configuration = crate::Configuration::default();
@@ -785,7 +1363,7 @@ mod tests {
         let mut layout = Layout::new(&configuration);
         let double_six = create_tile(6, 6);
 
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         assert_eq!(layout.to_string(), "6|6");
     }
@@ -798,9 +1376,9 @@ mod tests {
         let three_six = create_tile(3, 6);
         let one_three = create_tile(1, 3);
 
-        layout.attach(double_six, None);
-        layout.attach(three_six, Some(0));
-        layout.attach(one_three, Some(1));
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, Some(0));
+        layout.attach_unchecked(one_three, Some(1));
 
         assert_eq!(layout.to_string(), "6|6=(6|3-3|1)");
     }
@@ -813,9 +1391,9 @@ mod tests {
         let two_three = create_tile(2, 3);
         let three_five = create_tile(3, 5);
 
-        layout.attach(double_three, None);
-        layout.attach(two_three, Some(0));
-        layout.attach(three_five, Some(0));
+        layout.attach_unchecked(double_three, None);
+        layout.attach_unchecked(two_three, Some(0));
+        layout.attach_unchecked(three_five, Some(0));
 
         assert_eq!(layout.to_string(), "3|3=(3|2,3|5)");
     }
@@ -831,28 +1409,33 @@ mod tests {
         let three_five = create_tile(3, 5);
         let two_five = create_tile(2, 5);
 
-        layout.attach(double_six, None);
-        layout.attach(three_six, Some(0));
-        layout.attach(double_three, Some(1));  // Attach double-3 to the 3|6 tile
-        layout.attach(one_three, Some(2));     // Attach 1|3 to the double-3
-        layout.attach(three_five, Some(2));    // Attach 3|5 to the double-3 (creates branching)
-        layout.attach(two_five, Some(4));      // Attach 2|5 to the 3|5 tile
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, Some(0));
+        layout.attach_unchecked(double_three, Some(1));  // Attach double-3 to the 3|6 tile
+        layout.attach_unchecked(one_three, Some(2));     // Attach 1|3 to the double-3
+        layout.attach_unchecked(three_five, Some(2));    // Attach 3|5 to the double-3 (creates branching)
+        layout.attach_unchecked(two_five, Some(4));      // Attach 2|5 to the 3|5 tile
 
         assert_eq!(layout.to_string(), "6|6=(6|3-3|3=(3|1,3|5-5|2))");
     }
 
     #[test]
-    #[should_panic(expected = "First node must be a double")]
-    fn test_to_string_non_double_root_panics() {
+    fn test_to_string_non_double_root() {
         let configuration = crate::Configuration::default();
-        let mut layout = Layout::new(&configuration);
-        // Manually create invalid layout with non-double root
-        layout.nodes.push(LayoutNode {
-            tile: create_tile(3, 6),
-            parent: None,
-            children: Vec::new(),
-        });
-        layout.to_string();
+        let mut tree = ego_tree::Tree::new(create_tile(3, 6));
+        let layout = Layout::from_tree(&configuration, &tree).unwrap();
+        assert_eq!(layout.to_string(), "3|6");
+
+        // A single chain is written after the first tile, whichever end it is attached to
+        tree.root_mut().append(create_tile(1, 3));
+        let layout = Layout::from_tree(&configuration, &tree).unwrap();
+        assert_eq!(layout.to_string(), "6|3-3|1");
+
+        // Chains at both ends are written as a group, in the order they were attached
+        tree.root_mut().append(create_tile(6, 6));
+        let mut layout = Layout::from_tree(&configuration, &tree).unwrap();
+        layout.attach_unchecked(create_tile(2, 6), Some(2));
+        assert_eq!(layout.to_string(), "3|6=(3|1,6|6=(6|2))");
     }
 
     #[test]
@@ -861,7 +1444,7 @@ mod tests {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
         let tile = create_tile(3, 6);
-        layout.attach(tile, Some(5)); // Parent index 5 doesn't exist
+        layout.attach_unchecked(tile, Some(5)); // Parent index 5 doesn't exist
     }
 
     #[test]
@@ -872,8 +1455,8 @@ mod tests {
         let double_six = create_tile(6, 6);
         let three_six = create_tile(3, 6);
 
-        layout.attach(double_six, None);
-        layout.attach(three_six, None); // Should panic - trying to add first tile to non-empty layout
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, None); // Should panic - trying to add first tile to non-empty layout
     }
 
     #[test]
@@ -882,7 +1465,7 @@ mod tests {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
         let tile = create_tile(3, 6);
-        layout.attach(tile, Some(0)); // Should panic - layout is empty
+        layout.attach_unchecked(tile, Some(0)); // Should panic - layout is empty
     }
 
     #[test]
@@ -891,7 +1474,7 @@ mod tests {
         let mut layout = Layout::new(&configuration);
         let double_six = create_tile(6, 6);
 
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         // Manually test remove_from_open
         assert_eq!(layout.open.get_vec(&0).unwrap().len(), 2);
@@ -913,7 +1496,7 @@ mod tests {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
         let double_six = create_tile(6, 6);
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
         layout.remove_from_open(0, 9); // Value 9 doesn't exist for this tile
     }
 
@@ -927,12 +1510,12 @@ mod tests {
 
         // Add a tile - no longer empty
         let double_six = create_tile(6, 6);
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
         assert!(!layout.is_empty());
 
         // Add another tile - still not empty
         let three_six = create_tile(3, 6);
-        layout.attach(three_six, Some(0));
+        layout.attach_unchecked(three_six, Some(0));
         assert!(!layout.is_empty());
     }
 
@@ -948,7 +1531,7 @@ mod tests {
 
         // Place double-six
         let double_six = create_tile(6, 6);
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         // Should have 2 open ends with value 6
         assert_eq!(layout.open_count(6), 2);
@@ -958,7 +1541,7 @@ mod tests {
 
         // Attach 3-6 tile to consume one 6 end and add one 3 end
         let three_six = create_tile(3, 6);
-        layout.attach(three_six, Some(0));
+        layout.attach_unchecked(three_six, Some(0));
 
         assert_eq!(layout.open_count(6), 1);
         assert_eq!(layout.open_count(3), 1);
@@ -968,7 +1551,7 @@ mod tests {
 
         // Attach double-3 to consume 3 end and add two 3 ends
         let double_three = create_tile(3, 3);
-        layout.attach(double_three, Some(1));
+        layout.attach_unchecked(double_three, Some(1));
 
         assert_eq!(layout.open_count(6), 1);
         assert_eq!(layout.open_count(3), 2);
@@ -984,22 +1567,22 @@ mod tests {
 
         // Build: 4|4=(4|2,4|1-1|5)
         let double_four = create_tile(4, 4);
-        layout.attach(double_four, None);
+        layout.attach_unchecked(double_four, None);
         assert_eq!(layout.open_count(4), 2);
 
         let two_four = create_tile(2, 4);
-        layout.attach(two_four, Some(0));
+        layout.attach_unchecked(two_four, Some(0));
         assert_eq!(layout.open_count(4), 1);
         assert_eq!(layout.open_count(2), 1);
 
         let one_four = create_tile(1, 4);
-        layout.attach(one_four, Some(0));
+        layout.attach_unchecked(one_four, Some(0));
         assert_eq!(layout.open_count(4), 0);
         assert_eq!(layout.open_count(2), 1);
         assert_eq!(layout.open_count(1), 1);
 
         let one_five = create_tile(1, 5);
-        layout.attach(one_five, Some(2));
+        layout.attach_unchecked(one_five, Some(2));
         assert_eq!(layout.open_count(4), 0);
         assert_eq!(layout.open_count(2), 1);
         assert_eq!(layout.open_count(1), 0);
@@ -1013,19 +1596,19 @@ mod tests {
 
         // First tile (double) should return (value, 2)
         let double_six = create_tile(6, 6);
-        let (open_value, count) = layout.attach(double_six, None);
+        let (open_value, count) = layout.attach_unchecked(double_six, None);
         assert_eq!(open_value, 6);
         assert_eq!(count, 2);
 
         // Regular tile should return (new_value, 1)
         let three_six = create_tile(3, 6);
-        let (open_value, count) = layout.attach(three_six, Some(0));
+        let (open_value, count) = layout.attach_unchecked(three_six, Some(0));
         assert_eq!(open_value, 3);
         assert_eq!(count, 1);
 
         // Double tile attached should return (value, 2)
         let double_three = create_tile(3, 3);
-        let (open_value, count) = layout.attach(double_three, Some(1));
+        let (open_value, count) = layout.attach_unchecked(double_three, Some(1));
         assert_eq!(open_value, 3);
         assert_eq!(count, 2);
     }
@@ -1039,7 +1622,7 @@ mod tests {
         assert!(layout.nodes.is_empty());
 
         let double_three = create_tile(3, 3);
-        layout.attach(double_three, None);
+        layout.attach_unchecked(double_three, None);
 
         // Test that nodes field is accessible and populated
         assert_eq!(layout.nodes.len(), 1);
@@ -1068,14 +1651,14 @@ mod tests {
 
         // Start with double-4
         let double_four = create_tile(4, 4);
-        layout.attach(double_four, None);
+        layout.attach_unchecked(double_four, None);
 
         // Attach multiple tiles to the same parent
         let four_one = create_tile(1, 4);
-        layout.attach(four_one, Some(0));
+        layout.attach_unchecked(four_one, Some(0));
 
         let four_six = create_tile(4, 6);
-        layout.attach(four_six, Some(0));
+        layout.attach_unchecked(four_six, Some(0));
 
         // Parent should have both children
         assert_eq!(layout.nodes[0].children, vec![1, 2]);
@@ -1096,16 +1679,16 @@ mod tests {
 
         // Build a complex layout and verify end_counts matches actual open entries
         let double_two = create_tile(2, 2);
-        layout.attach(double_two, None);
+        layout.attach_unchecked(double_two, None);
 
         let two_five = create_tile(2, 5);
-        layout.attach(two_five, Some(0));
+        layout.attach_unchecked(two_five, Some(0));
 
         let two_six = create_tile(2, 6);
-        layout.attach(two_six, Some(0));
+        layout.attach_unchecked(two_six, Some(0));
 
         let double_five = create_tile(5, 5);
-        layout.attach(double_five, Some(1));
+        layout.attach_unchecked(double_five, Some(1));
 
         // Manually count open ends and compare with end_counts
         let mut actual_counts = vec![0u8; 7];
@@ -1129,21 +1712,29 @@ mod tests {
         let mut layout = Layout::new(&configuration);
 
         let double_six = create_tile(6, 6);
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         // Try to attach a tile that doesn't match any open end
         let one_two = create_tile(1, 2);
-        layout.attach(one_two, Some(0)); // Should panic - no 1 or 2 open on node 0
+        layout.attach_unchecked(one_two, Some(0)); // Should panic - no 1 or 2 open on node 0
     }
 
     #[test]
-    #[should_panic]
     fn test_attach_non_double_as_first_tile() {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
 
+        // Both ends of the first tile are open
         let three_six = create_tile(3, 6);
-        layout.attach(three_six, None); // Should panic - first tile must be double
+        assert_eq!(layout.attach_unchecked(three_six, None), (3, 1));
+        assert_eq!(layout.open_count(3), 1);
+        assert_eq!(layout.open_count(6), 1);
+
+        layout.attach_unchecked(create_tile(6, 6), Some(0));
+        assert_eq!(layout.open_count(6), 2);
+        assert_eq!(layout.detach_last(), Some(create_tile(6, 6)));
+        assert_eq!(layout.detach_last(), Some(three_six));
+        assert!(layout.end_counts.iter().all(|&count| count == 0));
     }
 
     #[test]
@@ -1160,7 +1751,7 @@ mod tests {
         let mut layout = Layout::new(&configuration);
         let double_six = create_tile(6, 6);
 
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         let tree = layout.to_tree().unwrap();
         assert_eq!(tree.root().value(), &double_six);
@@ -1175,9 +1766,9 @@ mod tests {
         let three_six = create_tile(3, 6);
         let one_three = create_tile(1, 3);
 
-        layout.attach(double_six, None);
-        layout.attach(three_six, Some(0));
-        layout.attach(one_three, Some(1));
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, Some(0));
+        layout.attach_unchecked(one_three, Some(1));
 
         let tree = layout.to_tree().unwrap();
 
@@ -1204,9 +1795,9 @@ mod tests {
         let two_three = create_tile(2, 3);
         let three_five = create_tile(3, 5);
 
-        layout.attach(double_three, None);
-        layout.attach(two_three, Some(0));
-        layout.attach(three_five, Some(0));
+        layout.attach_unchecked(double_three, None);
+        layout.attach_unchecked(two_three, Some(0));
+        layout.attach_unchecked(three_five, Some(0));
 
         let tree = layout.to_tree().unwrap();
 
@@ -1238,12 +1829,12 @@ mod tests {
         let two_five = create_tile(2, 5);
 
         // Build: 6|6-6|3-3|3=(3|1,3|5-5|2)
-        layout.attach(double_six, None);
-        layout.attach(three_six, Some(0));
-        layout.attach(double_three, Some(1));
-        layout.attach(one_three, Some(2));
-        layout.attach(three_five, Some(2));
-        layout.attach(two_five, Some(4));
+        layout.attach_unchecked(double_six, None);
+        layout.attach_unchecked(three_six, Some(0));
+        layout.attach_unchecked(double_three, Some(1));
+        layout.attach_unchecked(one_three, Some(2));
+        layout.attach_unchecked(three_five, Some(2));
+        layout.attach_unchecked(two_five, Some(4));
 
         let tree = layout.to_tree().unwrap();
 
@@ -1285,9 +1876,9 @@ mod tests {
         let four_six = create_tile(4, 6);
 
         // Attach children in specific order
-        layout.attach(double_four, None);
-        layout.attach(four_one, Some(0));    // First child
-        layout.attach(four_six, Some(0));    // Second child
+        layout.attach_unchecked(double_four, None);
+        layout.attach_unchecked(four_one, Some(0));    // First child
+        layout.attach_unchecked(four_six, Some(0));    // Second child
 
         let tree = layout.to_tree().unwrap();
 
@@ -1307,10 +1898,10 @@ mod tests {
         let two_six = create_tile(2, 6);
         let double_five = create_tile(5, 5);
 
-        layout.attach(double_two, None);
-        layout.attach(two_five, Some(0));
-        layout.attach(two_six, Some(0));
-        layout.attach(double_five, Some(1));
+        layout.attach_unchecked(double_two, None);
+        layout.attach_unchecked(two_five, Some(0));
+        layout.attach_unchecked(two_six, Some(0));
+        layout.attach_unchecked(double_five, Some(1));
 
         let tree = layout.to_tree().unwrap();
 
@@ -1359,9 +1950,9 @@ mod tests {
             create_tile(1, 2),
         ];
 
-        layout.attach(tiles[0], None);
-        layout.attach(tiles[1], Some(0));
-        layout.attach(tiles[2], Some(1));
+        layout.attach_unchecked(tiles[0], None);
+        layout.attach_unchecked(tiles[1], Some(0));
+        layout.attach_unchecked(tiles[2], Some(1));
 
         let tree = layout.to_tree().unwrap();
 
@@ -1375,6 +1966,121 @@ mod tests {
         assert!(tree_tiles.contains(&tiles[2]));
     }
 
+    #[test]
+    fn test_from_tree() {
+        let configuration = crate::Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(create_tile(6, 6), None);
+        layout.attach_unchecked(create_tile(3, 6), Some(0));
+        layout.attach_unchecked(create_tile(1, 3), Some(1));
+        layout.attach_unchecked(create_tile(2, 6), Some(0));
+
+        // A layout survives a round trip through a tree
+        let rebuilt = Layout::from_tree(&configuration, &layout.to_tree().unwrap()).unwrap();
+        assert_eq!(rebuilt.nodes.len(), layout.nodes.len());
+        for (original, rebuilt) in layout.nodes.iter().zip(rebuilt.nodes.iter()) {
+            assert_eq!(original.tile, rebuilt.tile);
+            assert_eq!(original.parent, rebuilt.parent);
+            assert_eq!(original.children, rebuilt.children);
+        }
+        assert_eq!(rebuilt.end_counts, layout.end_counts);
+
+        // The root does not have to be a double
+        let mut tree = ego_tree::Tree::new(create_tile(1, 2));
+        tree.root_mut().append(create_tile(2, 3));
+        let rebuilt = Layout::from_tree(&configuration, &tree).unwrap();
+        assert_eq!(rebuilt.open_count(1), 1);
+        assert_eq!(rebuilt.open_count(2), 0);
+        assert_eq!(rebuilt.open_count(3), 1);
+
+        // Tiles must match their parents and be in the set
+        let mut tree = ego_tree::Tree::new(create_tile(1, 2));
+        tree.root_mut().append(create_tile(4, 5));
+        assert_eq!(
+            Layout::from_tree(&configuration, &tree).unwrap_err(),
+            LayoutError::Mismatch { tile: create_tile(4, 5), parent: create_tile(1, 2) }
+        );
+        let tree = ego_tree::Tree::new(create_tile(7, 7));
+        assert_eq!(
+            Layout::from_tree(&configuration, &tree).unwrap_err(),
+            LayoutError::NotInSet { tile: create_tile(7, 7), set_id: 6 }
+        );
+
+        // A tile that is not the first tile cannot branch unless it is a double
+        let mut tree = ego_tree::Tree::new(create_tile(1, 1));
+        let mut root = tree.root_mut();
+        let mut branch = root.append(create_tile(1, 2));
+        branch.append(create_tile(2, 3));
+        branch.append(create_tile(2, 4));
+        assert_eq!(
+            Layout::from_tree(&configuration, &tree).unwrap_err(),
+            LayoutError::TooManyChildren { index: 1, max: 1 }
+        );
+
+        // Only one tile can be connected to each end of a tile that is not a double
+        let mut tree = ego_tree::Tree::new(create_tile(1, 2));
+        tree.root_mut().append(create_tile(2, 3));
+        tree.root_mut().append(create_tile(2, 4));
+        assert_eq!(Layout::from_tree(&configuration, &tree).unwrap_err(), LayoutError::NoOpenEnd { index: 0, value: 2 });
+        let mut tree = ego_tree::Tree::new(create_tile(1, 1));
+        tree.root_mut().append(create_tile(1, 2)).append(create_tile(1, 3));
+        assert_eq!(Layout::from_tree(&configuration, &tree).unwrap_err(), LayoutError::NoOpenEnd { index: 1, value: 1 });
+    }
+
+    #[test]
+    fn test_validate() {
+        let configuration = crate::Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.validate(&configuration), Ok(()));
+        layout.attach_unchecked(create_tile(6, 6), None);
+        layout.attach_unchecked(create_tile(3, 6), Some(0));
+        layout.attach_unchecked(create_tile(1, 3), Some(1));
+        layout.attach_unchecked(create_tile(2, 6), Some(0));
+        assert_eq!(layout.validate(&configuration), Ok(()));
+
+        // Every problem is reported
+        let mut broken = layout.clone();
+        broken.nodes[2].tile = create_tile(4, 5);
+        broken.nodes[3].tile = create_tile(1, 2);
+        assert_eq!(
+            broken.validate(&configuration),
+            Err(vec![
+                LayoutError::Mismatch { tile: create_tile(4, 5), parent: create_tile(3, 6) },
+                LayoutError::Mismatch { tile: create_tile(1, 2), parent: create_tile(6, 6) },
+            ])
+        );
+
+        // Duplicates are reported once and do not prevent the open ends from being checked
+        let mut broken = layout.clone();
+        broken.nodes[3].tile = create_tile(3, 6);
+        broken.end_counts[1] = 0;
+        assert_eq!(
+            broken.validate(&configuration),
+            Err(vec![
+                LayoutError::Duplicate(create_tile(3, 6)),
+                LayoutError::WrongOpenEnds(3),
+                LayoutError::WrongEndCount { value: 1, expected: 1, actual: 0 },
+                LayoutError::WrongEndCount { value: 2, expected: 0, actual: 1 },
+                LayoutError::WrongEndCount { value: 3, expected: 1, actual: 0 },
+            ])
+        );
+
+        // Links and child counts are checked
+        let mut broken = layout.clone();
+        broken.nodes[2].parent = Some(0);
+        assert_eq!(broken.validate(&configuration), Err(vec![LayoutError::BrokenLink(1), LayoutError::BrokenLink(2)]));
+        let mut broken = layout.clone();
+        broken.nodes[1].children.push(3);
+        broken.nodes[3].parent = Some(1);
+        broken.nodes[0].children.pop();
+        assert_eq!(broken.validate(&configuration), Err(vec![LayoutError::TooManyChildren { index: 1, max: 1 }]));
+
+        // The set is the one in the configuration
+        let configuration = crate::Configuration::new(4, crate::Variation::Traditional, 3, 5);
+        let errors = layout.validate(&configuration).unwrap_err();
+        assert!(errors.contains(&LayoutError::NotInSet { tile: create_tile(6, 6), set_id: 3 }));
+    }
+
     #[test]
     fn test_get_nodes_with_open_end() {
         let configuration = crate::Configuration::default();
@@ -1390,9 +2096,9 @@ mod tests {
         let three_six = Tile::from((3, 6));
         let one_three = Tile::from((1, 3));
 
-        layout.attach(double_six, None); // node 0
-        layout.attach(three_six, Some(0)); // node 1
-        layout.attach(one_three, Some(1)); // node 2
+        layout.attach_unchecked(double_six, None); // node 0
+        layout.attach_unchecked(three_six, Some(0)); // node 1
+        layout.attach_unchecked(one_three, Some(1)); // node 2
 
         // Node 0: open 6, Node 1: no open ends (3 was consumed), Node 2: open 1
         let six_nodes = layout.get_nodes_with_open_end(6);
@@ -1410,9 +2116,9 @@ mod tests {
         let three_two = Tile::from((2, 3));
         let three_five = Tile::from((3, 5));
 
-        layout2.attach(double_three, None); // node 0
-        layout2.attach(three_two, Some(0)); // node 1
-        layout2.attach(three_five, Some(0)); // node 2
+        layout2.attach_unchecked(double_three, None); // node 0
+        layout2.attach_unchecked(three_two, Some(0)); // node 1
+        layout2.attach_unchecked(three_five, Some(0)); // node 2
 
         // Node 1: open 2, Node 2: open 5
         // After attaching, node 0 should have no open ends
@@ -1422,6 +2128,21 @@ mod tests {
     }
 
     #[test]
+    fn test_nodes_with_open_end() {
+        // A root that is not a double has two different open ends, and both must be found
+        let config = Configuration::default();
+        let mut layout = Layout::from_tree(&config, &ego_tree::Tree::new(create_tile(2, 5))).unwrap();
+        assert_eq!(layout.nodes_with_open_end(2).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(layout.nodes_with_open_end(5).collect::<Vec<_>>(), vec![0]);
+
+        layout.attach_unchecked(create_tile(5, 5), Some(0));
+        assert_eq!(layout.nodes_with_open_end(5).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(layout.nodes_with_open_end(2).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(layout.nodes_with_open_end(6).count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
     fn test_layout_node_serialization() {
         // Test serialization of LayoutNode with parent
         let node_with_parent = LayoutNode {
@@ -1456,16 +2177,17 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_layout_serialization_simple() {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
 
         // Create a simple layout: 6|6-3|6
         let double_six = Tile::from((6, 6));
-        layout.attach(double_six, None);
+        layout.attach_unchecked(double_six, None);
 
         let three_six = Tile::from((3, 6));
-        layout.attach(three_six, Some(0));
+        layout.attach_unchecked(three_six, Some(0));
 
         // Serialize and deserialize
         let json = serde_json::to_string(&layout).expect("Serialization failed");
@@ -1495,22 +2217,23 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_layout_serialization_complex() {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
 
         // Create a more complex layout: 4|4=(2|4, 4|1-1|5)
         let double_four = Tile::from((4, 4));
-        layout.attach(double_four, None);
+        layout.attach_unchecked(double_four, None);
 
         let two_four = Tile::from((2, 4));
-        layout.attach(two_four, Some(0));
+        layout.attach_unchecked(two_four, Some(0));
 
         let one_four = Tile::from((1, 4));
-        layout.attach(one_four, Some(0));
+        layout.attach_unchecked(one_four, Some(0));
 
         let one_five = Tile::from((1, 5));
-        layout.attach(one_five, Some(2));
+        layout.attach_unchecked(one_five, Some(2));
 
         // Serialize and deserialize
         let json = serde_json::to_string(&layout).expect("Serialization failed");
@@ -1534,6 +2257,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_layout_serialization_empty() {
         let configuration = crate::Configuration::default();
         let layout = Layout::new(&configuration);
@@ -1549,21 +2273,22 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_layout_serialization_double_tiles() {
         let configuration = crate::Configuration::default();
         let mut layout = Layout::new(&configuration);
 
         // Create layout step by step and debug each step
         let double_three = Tile::from((3, 3));
-        layout.attach(double_three, None);
+        layout.attach_unchecked(double_three, None);
         println!("After attaching root 3|3: open count 3 = {}", layout.open_count(3));
 
         let another_double_three = Tile::from((3, 3));
-        layout.attach(another_double_three, Some(0));
+        layout.attach_unchecked(another_double_three, Some(0));
         println!("After attaching second 3|3: open count 3 = {}", layout.open_count(3));
 
         let three_five = Tile::from((3, 5));
-        layout.attach(three_five, Some(0));
+        layout.attach_unchecked(three_five, Some(0));
         println!("After attaching 3|5: open count 3 = {}, open count 5 = {}", layout.open_count(3), layout.open_count(5));
 
         // Check node structure
@@ -1592,4 +2317,71 @@ mod tests {
         assert_eq!(deserialized.open_count(3), layout.open_count(3));
         assert_eq!(deserialized.open_count(5), layout.open_count(5));
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_layout_serialization_spinner() {
+        let configuration = crate::Configuration::new(2, crate::Variation::AllFives, 6, 7);
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((5, 5)), None);
+        layout.attach_unchecked(Tile::from((0, 5)), Some(0));
+        let json = serde_json::to_string(&layout).unwrap();
+        assert!(json.ends_with(r#""set_id":6,"branching":{"Spinner":{"arms":4}}}"#));
+        let deserialized: Layout = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.open_count(5), 3);
+        assert_eq!(deserialized.end_counts, layout.end_counts);
+
+        // Layouts saved without a rule for doubles were played with two open ends on every double
+        let json = r#"{"nodes":[{"tile":[5,5],"children":[]}],"set_id":6}"#;
+        assert_eq!(serde_json::from_str::<Layout>(json).unwrap().open_count(5), 2);
+        assert!(!serde_json::to_string(&Layout::new(&crate::Configuration::default())).unwrap().contains("branching"));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_layout_deserialize_rejects_invalid_nodes() {
+        let invalid = [
+            // The set is too large
+            r#"{"nodes":[],"set_id":22}"#,
+            // A tile is not in the set
+            r#"{"nodes":[{"tile":[7,7],"children":[]}],"set_id":6}"#,
+            // A child does not exist
+            r#"{"nodes":[{"tile":[6,6],"children":[1]}],"set_id":6}"#,
+            // A parent does not list the child
+            r#"{"nodes":[{"tile":[6,6],"children":[]},{"tile":[3,6],"parent":0,"children":[]}],"set_id":6}"#,
+            // A node is its own parent
+            r#"{"nodes":[{"tile":[6,6],"children":[]},{"tile":[3,6],"parent":1,"children":[1]}],"set_id":6}"#,
+            // A tile does not match its parent
+            r#"{"nodes":[{"tile":[6,6],"children":[1]},{"tile":[1,2],"parent":0,"children":[]}],"set_id":6}"#,
+        ];
+        for json in invalid {
+            assert!(serde_json::from_str::<Layout>(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn test_attach_checked() {
+        let configuration = crate::Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.attach(create_tile(6, 6), Some(0)), Err(LayoutError::NoSuchNode(0)));
+        assert_eq!(
+            layout.attach(create_tile(7, 7), None),
+            Err(LayoutError::NotInSet { tile: create_tile(7, 7), set_id: 6 })
+        );
+        assert_eq!(layout.attach(create_tile(6, 6), None), Ok((6, 2)));
+        assert_eq!(layout.attach(create_tile(5, 5), None), Err(LayoutError::NotEmpty));
+        assert_eq!(
+            layout.attach(create_tile(1, 2), Some(0)),
+            Err(LayoutError::Mismatch { tile: create_tile(1, 2), parent: create_tile(6, 6) })
+        );
+        assert_eq!(layout.attach(create_tile(3, 6), Some(0)), Ok((3, 1)));
+
+        // The 3|6 is attached by its 6, so a tile matching the 6 cannot be attached to it
+        assert_eq!(layout.attach(create_tile(2, 6), Some(1)), Err(LayoutError::NoOpenEnd { index: 1, value: 6 }));
+        assert_eq!(layout.nodes.len(), 2);
+
+        // Values outside the set have no open ends
+        assert_eq!(layout.open_count(7), 0);
+        assert!(layout.get_nodes_with_open_end(u8::MAX).is_empty());
+    }
 }
@@ -17,13 +17,25 @@
 
 pub mod boneyard;
 pub mod configuration;
+pub mod error;
+pub mod hidden_boneyard;
 pub mod layout;
+pub mod metrics;
+pub mod random;
+pub mod render;
+#[cfg(feature = "svg")]
+pub mod svg;
 pub mod tile;
+pub mod tile_set;
 
 pub use boneyard::*;
 pub use configuration::*;
+pub use error::*;
+pub use hidden_boneyard::*;
 pub use layout::*;
+pub use random::*;
 pub use tile::*;
+pub use tile_set::*;
 
 /// Domino game variations
 ///
@@ -37,6 +49,7 @@ pub use tile::*;
 /// assert_eq!(Configuration::default_starting_hand_size(2, Variation::Blind), 8);
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Variation {
     Traditional,
     AllFives,
@@ -44,6 +57,8 @@ pub enum Variation {
     Bergen,
     Blind,
     FiveUp,
+    MexicanTrain,
+    ChickenFoot,
 }
 
 impl Variation {
@@ -65,10 +80,208 @@ impl Variation {
             Variation::Bergen => "Bergen",
             Variation::Blind => "Blind",
             Variation::FiveUp => "Five Up",
+            Variation::MexicanTrain => "Mexican Train",
+            Variation::ChickenFoot => "Chicken Foot",
+        }
+    }
+
+    /// Returns the rule for the first tile of a round in the variation
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Opening, Variation};
+    ///
+    /// assert_eq!(Variation::Traditional.opening(), Opening::HighestDouble);
+    /// assert_eq!(Variation::Bergen.opening(), Opening::LowestDouble);
+    /// assert_eq!(Variation::AllFives.opening(), Opening::AnyTile);
+    /// ```
+    pub const fn opening(self) -> Opening {
+        match self {
+            Variation::Traditional | Variation::MexicanTrain | Variation::ChickenFoot => Opening::HighestDouble,
+            Variation::Bergen => Opening::LowestDouble,
+            Variation::AllFives | Variation::AllSevens | Variation::Blind | Variation::FiveUp => Opening::AnyTile,
+        }
+    }
+
+    /// Returns the rule for the number of tiles that can be attached to doubles in the variation
+    ///
+    /// In Mexican Train, the engine has an arm for each player's train and one for the Mexican train, so the number of arms
+    /// depends on the number of players (see `Configuration::branching`). The rule returned here is for eight players. In Chicken
+    /// Foot, six tiles are played around the first double, and three on each of the others.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Branching, Variation};
+    ///
+    /// assert_eq!(Variation::Traditional.branching(), Branching::EveryDouble);
+    /// assert_eq!(Variation::AllFives.branching(), Branching::Spinner { arms: 4 });
+    /// assert_eq!(Variation::ChickenFoot.branching(), Branching::ChickenFoot { arms: 6 });
+    /// ```
+    pub const fn branching(self) -> Branching {
+        match self {
+            Variation::AllFives => Branching::Spinner { arms: 4 },
+            Variation::MexicanTrain => Branching::Spinner { arms: 9 },
+            Variation::ChickenFoot => Branching::ChickenFoot { arms: 6 },
+            _ => Branching::EveryDouble,
+        }
+    }
+
+    /// Returns the rule for drawing from the boneyard in the variation
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{DrawRule, Variation};
+    ///
+    /// assert_eq!(Variation::Traditional.draw_rule(), DrawRule::DrawUntilPlayable);
+    /// assert_eq!(Variation::MexicanTrain.draw_rule(), DrawRule::DrawOne);
+    /// ```
+    pub const fn draw_rule(self) -> DrawRule {
+        match self {
+            Variation::MexicanTrain => DrawRule::DrawOne,
+            _ => DrawRule::DrawUntilPlayable,
+        }
+    }
+}
+
+/// The rule for the first tile of a round
+///
+/// The rule decides who plays first and which tiles they can play. Under the rules that require a double, the player holding the
+/// double plays it, and if no player holds a double, the hands are redealt.
+///
+/// # Examples
+/// ```rust
+/// # use rules::{Opening, Tile};
+///
+/// let hand = [Tile::from((2, 2)), Tile::from((3, 5)), Tile::from((4, 4))];
+/// assert_eq!(Opening::HighestDouble.required_tile(&hand), Some(Tile::from((4, 4))));
+/// assert!(Opening::LowestDouble.allows(Tile::from((2, 2)), &hand));
+/// assert!(!Opening::LowestDouble.allows(Tile::from((4, 4)), &hand));
+/// assert!(Opening::AnyTile.allows(Tile::from((3, 5)), &hand));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opening {
+    /// The player with the highest double plays it first
+    HighestDouble,
+    /// The player with the lowest double plays it first
+    LowestDouble,
+    /// The first player plays any tile
+    AnyTile,
+}
+
+impl Opening {
+    /// Returns `true` if the first tile must be a double
+    pub const fn requires_double(self) -> bool {
+        !matches!(self, Opening::AnyTile)
+    }
+
+    /// Returns the tile that must be played first from a hand, or `None` if any tile can be played or the hand has no doubles
+    ///
+    /// # Arguments
+    /// * `hand` - The tiles held by the first player
+    pub fn required_tile(self, hand: &[Tile]) -> Option<Tile> {
+        let doubles = hand.iter().filter(|tile| tile.is_double()).copied();
+        match self {
+            Opening::HighestDouble => doubles.max(),
+            Opening::LowestDouble => doubles.min(),
+            Opening::AnyTile => None,
+        }
+    }
+
+    /// Returns `true` if the first tile can be played from one of the hands just dealt, or `false` if they must be redealt
+    ///
+    /// # Arguments
+    /// * `hands` - The hands dealt to the players
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Opening, Tile};
+    ///
+    /// let hands = [vec![Tile::from((1, 2))], vec![Tile::from((3, 4))]];
+    /// assert!(!Opening::HighestDouble.can_open(&hands));
+    /// assert!(Opening::AnyTile.can_open(&hands));
+    /// ```
+    pub fn can_open(self, hands: &[Vec<Tile>]) -> bool {
+        !self.requires_double() || hands.iter().flatten().any(|tile| tile.is_double())
+    }
+
+    /// Returns `true` if a tile can be played first from a hand
+    ///
+    /// # Arguments
+    /// * `tile` - The tile to play
+    /// * `hand` - The tiles held by the first player, including `tile`
+    pub fn allows(self, tile: Tile, hand: &[Tile]) -> bool {
+        match self {
+            Opening::AnyTile => true,
+            _ => self.required_tile(hand) == Some(tile),
         }
     }
 }
 
+/// The rule for the number of tiles that can be attached to doubles
+///
+/// A double is played across the line, so tiles can be attached to its sides as well as its ends. Each side or end that a tile
+/// can be attached to is an arm of the double, including the one that the double is attached to. A spinner played first is not
+/// attached to anything, so all of its arms are open.
+///
+/// # Examples
+/// ```rust
+/// # use rules::Branching;
+///
+/// // A double played on the line in the middle of a game
+/// assert_eq!(Branching::EveryDouble.open_ends(false, false), 2);
+/// assert_eq!(Branching::Spinner { arms: 4 }.open_ends(false, false), 1);
+/// assert_eq!(Branching::ChickenFoot { arms: 4 }.open_ends(false, false), 3);
+///
+/// // The first double, played first or on the line
+/// assert_eq!(Branching::Spinner { arms: 4 }.open_ends(true, true), 4);
+/// assert_eq!(Branching::Spinner { arms: 4 }.open_ends(true, false), 3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Branching {
+    /// Every double has two open ends when it is played, including a double played first
+    EveryDouble,
+    /// The first double played is a spinner with the given number of arms, e.g. 4 in All Fives or one for each train in Mexican
+    /// Train. Other doubles have one open end, continuing the line.
+    Spinner { arms: u8 },
+    /// The first double played is a spinner with the given number of arms, and other doubles have three open ends, like a
+    /// chicken's foot
+    ChickenFoot { arms: u8 },
+}
+
+impl Branching {
+    /// Returns the number of open ends that a double has when it is played
+    ///
+    /// # Arguments
+    /// * `first_double` - True if no other double is in the layout
+    /// * `first_tile` - True if the double is the first tile in the layout
+    pub const fn open_ends(self, first_double: bool, first_tile: bool) -> u8 {
+        match self {
+            Branching::EveryDouble => 2,
+            Branching::Spinner { arms } | Branching::ChickenFoot { arms } if first_double => {
+                if first_tile { arms } else { arms.saturating_sub(1) }
+            }
+            Branching::Spinner { .. } => 1,
+            Branching::ChickenFoot { .. } => 3,
+        }
+    }
+}
+
+/// The rule for drawing from the boneyard when a player cannot play
+///
+/// In a Block game, the tiles left after the deal are never drawn, so a player who cannot play passes. In a Draw game, a player
+/// who cannot play draws until they can, and passes only when the boneyard is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawRule {
+    /// A player who cannot play passes without drawing, as in a Block game
+    NoDraw,
+    /// A player who cannot play draws until they can, as in a Draw game
+    DrawUntilPlayable,
+    /// A player who cannot play draws one tile, and passes if they still cannot play, as in Mexican Train
+    DrawOne,
+}
+
 /// Maximum number of pips on a domino tile supported by this library
 pub const MAX_PIPS: u8 = 21;
 
@@ -159,6 +372,8 @@ const DOUBLES: [u8; 22] = [
 /// * If the input tuple is not in canonical form (i.e., if the first element is greater than the second).
 /// * If the calculated ordinal exceeds `u8::MAX` (shouldn't happen for valid tiles)
 ///
+/// Use `checked_tuple_to_ordinal` for values that have not been validated.
+///
 /// # Examples
 /// ```
 /// # use rules::tuple_to_ordinal;
@@ -179,6 +394,31 @@ pub const fn tuple_to_ordinal((a, b): (u8, u8)) -> u8 {
     ordinal as u8
 }
 
+/// Converts a domino tile tuple to its ordinal value, or returns `None` if the tuple is not a tile.
+///
+/// This is the checked version of `tuple_to_ordinal`, for values from untrusted input such as a file or a network connection.
+///
+/// # Arguments
+/// * `tile` - A domino tile where the first value &le; second value (canonical form)
+///
+/// # Returns
+/// The ordinal value for the tile, or `None` if the tuple is not in canonical form or a value is larger than `MAX_PIPS`
+///
+/// # Examples
+/// ```
+/// # use rules::checked_tuple_to_ordinal;
+///
+/// assert_eq!(checked_tuple_to_ordinal((0, 6)), Some(21));
+/// assert_eq!(checked_tuple_to_ordinal((6, 0)), None);
+/// assert_eq!(checked_tuple_to_ordinal((0, 22)), None);
+/// ```
+pub const fn checked_tuple_to_ordinal((a, b): (u8, u8)) -> Option<u8> {
+    if a > b || b > MAX_PIPS {
+        return None;
+    }
+    Some(tuple_to_ordinal((a, b)))
+}
+
 /// Converts an ordinal to its corresponding tuple.
 ///
 /// This is the inverse operation of `tuple_to_ordinal()`.
@@ -190,7 +430,7 @@ pub const fn tuple_to_ordinal((a, b): (u8, u8)) -> u8 {
 /// The corresponding domino tuple (a, b)
 ///
 /// # Panics
-/// If `ordinal` is outside the valid range (&ge; 253)
+/// If `ordinal` is outside the valid range (&ge; 253). Use `checked_ordinal_to_tuple` for values that have not been validated.
 ///
 /// # Examples
 /// ```rust
@@ -209,6 +449,30 @@ pub const fn ordinal_to_tuple(ordinal: u8) -> (u8, u8) {
     TUPLES[ordinal as usize]
 }
 
+/// Converts an ordinal to its corresponding tuple, or returns `None` if the ordinal is not a tile.
+///
+/// This is the checked version of `ordinal_to_tuple`.
+///
+/// # Arguments
+/// * `ordinal` - The unique ordinal
+///
+/// # Returns
+/// The corresponding domino tuple (a, b), or `None` if `ordinal` is outside the valid range (&ge; 253)
+///
+/// # Examples
+/// ```rust
+/// # use rules::checked_ordinal_to_tuple;
+///
+/// assert_eq!(checked_ordinal_to_tuple(27), Some((6, 6)));
+/// assert_eq!(checked_ordinal_to_tuple(253), None);
+/// ```
+pub const fn checked_ordinal_to_tuple(ordinal: u8) -> Option<(u8, u8)> {
+    if ordinal as usize >= TUPLES.len() {
+        return None;
+    }
+    Some(TUPLES[ordinal as usize])
+}
+
 /// Returns `true` if the tile is a double (both sides equal).
 ///
 /// Double tiles are important in many domino games as they often have
@@ -337,7 +601,8 @@ pub const fn set_size(n: u8) -> usize {
 /// * `set_id` - ID of the set. Same as the highest value on the tiles.
 ///
 /// # Returns
-/// A vector containing all unique tile combinations as tuples in canonical order
+/// A vector containing all unique tile combinations as tuples in canonical order. Use `tuples_in_set` to avoid allocating the
+/// vector.
 ///
 /// # Examples
 /// ```
@@ -352,9 +617,7 @@ pub const fn set_size(n: u8) -> usize {
 /// assert_eq!(double_six.len(), 28); // Standard domino set
 /// ```
 pub fn all_tiles_as_tuples(set_id: u8) -> Vec<(u8, u8)> {
-    (0..=set_id)
-        .flat_map(|b| (0..=b).map(move |a| (a, b)))
-        .collect()
+    tuples_in_set(set_id).collect()
 }
 
 /// Returns a sorted Vec containing all domino tiles for a given set
@@ -363,7 +626,7 @@ pub fn all_tiles_as_tuples(set_id: u8) -> Vec<(u8, u8)> {
 /// * `set_id` - ID of the set. Same as the highest value on the tiles.
 ///
 /// # Returns
-/// A sorted vector containing all tiles in the set
+/// A sorted vector containing all tiles in the set. Use `tiles_in_set` to avoid allocating the vector.
 ///
 /// # Examples
 /// ```
@@ -381,7 +644,7 @@ pub fn all_tiles_as_tuples(set_id: u8) -> Vec<(u8, u8)> {
 /// assert_eq!(double_six.len(), 28); // Standard domino set
 /// ```
 pub fn all_tiles_as_tiles(set_id: u8) -> Vec<Tile> {
-    (0..set_size(set_id) as u8).map(Tile::from).collect()
+    tiles_in_set(set_id).collect()
 }
 
 /// Returns a sorted vector containing all ordinal values for a given set as tuples.
@@ -390,7 +653,7 @@ pub fn all_tiles_as_tiles(set_id: u8) -> Vec<Tile> {
 /// * `set_id` - ID of the set. Same as the highest value on the tiles.
 ///
 /// # Returns
-/// A vector containing all ordinal values for the set
+/// A vector containing all ordinal values for the set. Use `ordinals_in_set` to avoid allocating the vector.
 ///
 /// # Examples
 /// ```
@@ -405,7 +668,53 @@ pub fn all_tiles_as_tiles(set_id: u8) -> Vec<Tile> {
 /// assert_eq!(double_six[27], 27);  // (6,6)
 /// ```
 pub fn all_tiles_as_ordinals(set_id: u8) -> Vec<u8> {
-    (0..set_size(set_id) as u8).collect()
+    ordinals_in_set(set_id).collect()
+}
+
+/// Returns an iterator over all domino tiles for a given set as tuples, in canonical order.
+///
+/// # Arguments
+/// * `set_id` - ID of the set. Same as the highest value on the tiles.
+///
+/// # Examples
+/// ```
+/// # use rules::tuples_in_set;
+///
+/// assert!(tuples_in_set(2).eq([(0, 0), (0, 1), (1, 1), (0, 2), (1, 2), (2, 2)]));
+/// ```
+pub fn tuples_in_set(set_id: u8) -> impl Iterator<Item = (u8, u8)> {
+    (0..=set_id).flat_map(|b| (0..=b).map(move |a| (a, b)))
+}
+
+/// Returns an iterator over all domino tiles for a given set, in canonical order.
+///
+/// # Arguments
+/// * `set_id` - ID of the set. Same as the highest value on the tiles.
+///
+/// # Examples
+/// ```
+/// # use rules::{tiles_in_set, Tile};
+///
+/// assert_eq!(tiles_in_set(6).count(), 28);
+/// assert_eq!(tiles_in_set(6).last(), Some(Tile::from((6, 6))));
+/// ```
+pub fn tiles_in_set(set_id: u8) -> impl Iterator<Item = Tile> {
+    ordinals_in_set(set_id).map(Tile::from)
+}
+
+/// Returns an iterator over the ordinal values of all domino tiles for a given set, in canonical order.
+///
+/// # Arguments
+/// * `set_id` - ID of the set. Same as the highest value on the tiles.
+///
+/// # Examples
+/// ```
+/// # use rules::ordinals_in_set;
+///
+/// assert!(ordinals_in_set(2).eq(0..6));
+/// ```
+pub fn ordinals_in_set(set_id: u8) -> impl Iterator<Item = u8> {
+    0..set_size(set_id) as u8
 }
 #[cfg(test)]
 mod tests {
@@ -418,6 +727,36 @@ mod tests {
         assert!(debug_str.contains("Traditional"));
     }
 
+    #[test]
+    fn test_opening() {
+        let hand = [Tile::from((1, 1)), Tile::from((2, 6)), Tile::from((5, 5)), Tile::from((3, 3))];
+        assert_eq!(Opening::HighestDouble.required_tile(&hand), Some(Tile::from((5, 5))));
+        assert_eq!(Opening::LowestDouble.required_tile(&hand), Some(Tile::from((1, 1))));
+        assert_eq!(Opening::AnyTile.required_tile(&hand), None);
+        assert!(!Opening::HighestDouble.allows(Tile::from((3, 3)), &hand));
+        assert!(hand.iter().all(|&tile| Opening::AnyTile.allows(tile, &hand)));
+
+        // Without a double, no tile can be played first under the rules that require one
+        let no_doubles = [Tile::from((1, 2)), Tile::from((3, 4))];
+        assert_eq!(Opening::HighestDouble.required_tile(&no_doubles), None);
+        assert!(!Opening::LowestDouble.allows(Tile::from((1, 2)), &no_doubles));
+        assert!(Opening::HighestDouble.requires_double() && !Opening::AnyTile.requires_double());
+    }
+
+    #[test]
+    fn test_branching() {
+        for (first_double, first_tile) in [(true, true), (true, false), (false, false)] {
+            assert_eq!(Branching::EveryDouble.open_ends(first_double, first_tile), 2);
+        }
+        let hub = Branching::Spinner { arms: 8 };
+        assert_eq!(hub.open_ends(true, true), 8);
+        assert_eq!(hub.open_ends(true, false), 7);
+        assert_eq!(hub.open_ends(false, false), 1);
+        assert_eq!(Branching::ChickenFoot { arms: 6 }.open_ends(true, true), 6);
+        assert_eq!(Branching::ChickenFoot { arms: 6 }.open_ends(false, false), 3);
+        assert_eq!(Branching::Spinner { arms: 0 }.open_ends(true, false), 0);
+    }
+
     #[test]
     fn test_variation_equality() {
         assert_eq!(Variation::Traditional, Variation::Traditional);
@@ -524,6 +863,17 @@ mod tests {
         assert_eq!(Configuration::default_starting_hand_size(4, Variation::Blind), 6);
         assert_eq!(Configuration::default_starting_hand_size(5, Variation::Blind), 6);
         assert_eq!(Configuration::default_starting_hand_size(8, Variation::Blind), 6);
+
+        // MexicanTrain variation (for a double-twelve set)
+        assert_eq!(Configuration::default_starting_hand_size(2, Variation::MexicanTrain), 15);
+        assert_eq!(Configuration::default_starting_hand_size(4, Variation::MexicanTrain), 15);
+        assert_eq!(Configuration::default_starting_hand_size(6, Variation::MexicanTrain), 12);
+        assert_eq!(Configuration::default_starting_hand_size(8, Variation::MexicanTrain), 10);
+
+        // ChickenFoot variation (for a double-nine set)
+        assert_eq!(Configuration::default_starting_hand_size(2, Variation::ChickenFoot), 21);
+        assert_eq!(Configuration::default_starting_hand_size(4, Variation::ChickenFoot), 11);
+        assert_eq!(Configuration::default_starting_hand_size(8, Variation::ChickenFoot), 5);
     }
     #[test]
     fn test_tuple_ordinal_conversion_comprehensive() {
@@ -559,6 +909,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_checked_conversions() {
+        for ordinal in 0..=252 {
+            let tuple = ordinal_to_tuple(ordinal);
+            assert_eq!(checked_ordinal_to_tuple(ordinal), Some(tuple));
+            assert_eq!(checked_tuple_to_ordinal(tuple), Some(ordinal));
+        }
+        for ordinal in 253..=u8::MAX {
+            assert_eq!(checked_ordinal_to_tuple(ordinal), None);
+        }
+        assert_eq!(checked_tuple_to_ordinal((1, 0)), None);
+        assert_eq!(checked_tuple_to_ordinal((21, 22)), None);
+        assert_eq!(checked_tuple_to_ordinal((u8::MAX, u8::MAX)), None);
+    }
+
     #[test]
     fn test_double_detection_comprehensive() {
         // Test tuple double detection
@@ -0,0 +1,281 @@
+//! Metrics
+//!
+//! This module counts the work done by the crates of the game, e.g. the number of states created and the number of searches made
+//! by computer players, so that a simulation or a server can report it. Metrics are only recorded when the `metrics` feature is
+//! enabled. Otherwise, recording does nothing and `snapshot` returns zeros, so default builds pay nothing for them.
+//!
+//! Metrics are global to the process and are never reset, so the work done by a part of a program is the difference between a
+//! snapshot taken before it and one taken after it.
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+// Number of buckets in a histogram. Values of 2^30 and larger go in the last one.
+const NUM_BUCKETS: usize = 32;
+
+/// A count of something that happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    /// A game state was created
+    StatesCreated,
+    /// A game state was cloned
+    StatesCloned,
+    /// A tile was attached to a layout
+    TilesAttached,
+    /// A computer player searched for an action
+    Searches,
+    /// A node of a search tree was expanded
+    NodesExpanded,
+}
+
+/// A distribution of values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Histogram {
+    /// The time taken by a search, in microseconds
+    SearchMicros,
+}
+
+/// A summary of the values recorded in a histogram
+///
+/// Bucket 0 counts the values of 0, and bucket `n` counts the values from 2^(n-1) to 2^n - 1. The last bucket also counts every
+/// larger value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Distribution {
+    /// The number of values recorded
+    pub count: u64,
+    /// The sum of the values
+    pub sum: u64,
+    /// The largest value
+    pub max: u64,
+    /// The number of values in each bucket
+    pub buckets: Vec<u64>,
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Self { count: 0, sum: 0, max: 0, buckets: vec![0; NUM_BUCKETS] }
+    }
+}
+
+impl Distribution {
+    /// Returns the mean of the values, or 0 if no values have been recorded
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum as f64 / self.count as f64 }
+    }
+}
+
+/// The values of every metric at some point in time
+///
+/// A snapshot is displayed in the Prometheus text format, one metric per line, so that it can be scraped by standard tools. With
+/// the `serde` feature, it can also be serialized.
+///
+/// # Examples
+/// ```rust
+/// # use rules::metrics::{self, Counter};
+///
+/// let before = metrics::snapshot();
+/// metrics::increment(Counter::Searches);
+/// let searches = metrics::snapshot().searches - before.searches;
+/// assert_eq!(searches, if cfg!(feature = "metrics") { 1 } else { 0 });
+/// assert!(before.to_string().contains("dominoes_searches_total"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Metrics {
+    /// The number of game states created
+    pub states_created: u64,
+    /// The number of game states cloned
+    pub states_cloned: u64,
+    /// The number of tiles attached to layouts
+    pub tiles_attached: u64,
+    /// The number of searches made by computer players
+    pub searches: u64,
+    /// The number of search tree nodes expanded
+    pub nodes_expanded: u64,
+    /// The time taken by each search, in microseconds
+    pub search_micros: Distribution,
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counters = [
+            ("states_created", self.states_created),
+            ("states_cloned", self.states_cloned),
+            ("tiles_attached", self.tiles_attached),
+            ("searches", self.searches),
+            ("nodes_expanded", self.nodes_expanded),
+        ];
+        for (name, value) in counters {
+            writeln!(f, "dominoes_{name}_total {value}")?;
+        }
+
+        // Prometheus buckets are cumulative, and are labeled with their upper bounds
+        let name = "dominoes_search_micros";
+        let mut cumulative = 0;
+        for (index, count) in self.search_micros.buckets.iter().enumerate() {
+            cumulative += count;
+            if index + 1 < self.search_micros.buckets.len() {
+                writeln!(f, "{name}_bucket{{le=\"{}\"}} {cumulative}", (1u64 << index) - 1)?;
+            } else {
+                writeln!(f, "{name}_bucket{{le=\"+Inf\"}} {cumulative}")?;
+            }
+        }
+        writeln!(f, "{name}_sum {}", self.search_micros.sum)?;
+        writeln!(f, "{name}_count {}", self.search_micros.count)
+    }
+}
+
+/// Adds 1 to a counter
+///
+/// # Arguments
+/// * `counter` - The counter
+#[inline]
+pub fn increment(counter: Counter) {
+    #[cfg(feature = "metrics")]
+    registry::COUNTERS[counter as usize].fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(feature = "metrics"))]
+    let _ = counter;
+}
+
+/// Adds a value to a histogram
+///
+/// # Arguments
+/// * `histogram` - The histogram
+/// * `value` - The value
+#[inline]
+pub fn record(histogram: Histogram, value: u64) {
+    #[cfg(feature = "metrics")]
+    registry::HISTOGRAMS[histogram as usize].record(value);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (histogram, value);
+}
+
+/// Returns the current values of every metric
+///
+/// Every value is 0 if the `metrics` feature is not enabled.
+pub fn snapshot() -> Metrics {
+    #[cfg(feature = "metrics")]
+    {
+        let counter = |counter: Counter| registry::COUNTERS[counter as usize].load(std::sync::atomic::Ordering::Relaxed);
+        Metrics {
+            states_created: counter(Counter::StatesCreated),
+            states_cloned: counter(Counter::StatesCloned),
+            tiles_attached: counter(Counter::TilesAttached),
+            searches: counter(Counter::Searches),
+            nodes_expanded: counter(Counter::NodesExpanded),
+            search_micros: registry::HISTOGRAMS[Histogram::SearchMicros as usize].distribution(),
+        }
+    }
+    #[cfg(not(feature = "metrics"))]
+    Metrics::default()
+}
+
+// Returns the index of the histogram bucket that counts a value
+#[cfg_attr(not(any(feature = "metrics", test)), allow(dead_code))]
+fn bucket(value: u64) -> usize {
+    (u64::BITS - value.leading_zeros()).min(NUM_BUCKETS as u32 - 1) as usize
+}
+
+// The storage of the metrics, which is only compiled with the feature
+#[cfg(feature = "metrics")]
+mod registry {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::{Distribution, NUM_BUCKETS, bucket};
+
+    // Number of variants of Counter
+    const NUM_COUNTERS: usize = 5;
+    // Number of variants of Histogram
+    const NUM_HISTOGRAMS: usize = 1;
+
+    pub(super) static COUNTERS: [AtomicU64; NUM_COUNTERS] = [const { AtomicU64::new(0) }; NUM_COUNTERS];
+    pub(super) static HISTOGRAMS: [AtomicHistogram; NUM_HISTOGRAMS] = [const { AtomicHistogram::new() }; NUM_HISTOGRAMS];
+
+    // A histogram that can be updated from any thread
+    pub(super) struct AtomicHistogram {
+        count: AtomicU64,
+        sum: AtomicU64,
+        max: AtomicU64,
+        buckets: [AtomicU64; NUM_BUCKETS],
+    }
+
+    impl AtomicHistogram {
+        const fn new() -> Self {
+            Self {
+                count: AtomicU64::new(0),
+                sum: AtomicU64::new(0),
+                max: AtomicU64::new(0),
+                buckets: [const { AtomicU64::new(0) }; NUM_BUCKETS],
+            }
+        }
+
+        pub(super) fn record(&self, value: u64) {
+            self.count.fetch_add(1, Ordering::Relaxed);
+            self.sum.fetch_add(value, Ordering::Relaxed);
+            self.max.fetch_max(value, Ordering::Relaxed);
+            self.buckets[bucket(value)].fetch_add(1, Ordering::Relaxed);
+        }
+
+        pub(super) fn distribution(&self) -> Distribution {
+            Distribution {
+                count: self.count.load(Ordering::Relaxed),
+                sum: self.sum.load(Ordering::Relaxed),
+                max: self.max.load(Ordering::Relaxed),
+                buckets: self.buckets.iter().map(|bucket| bucket.load(Ordering::Relaxed)).collect(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket() {
+        assert_eq!(bucket(0), 0);
+        assert_eq!(bucket(1), 1);
+        assert_eq!(bucket(2), 2);
+        assert_eq!(bucket(3), 2);
+        assert_eq!(bucket(4), 3);
+        assert_eq!(bucket(1 << 29), 30);
+        assert_eq!(bucket(1 << 30), NUM_BUCKETS - 1);
+        assert_eq!(bucket(u64::MAX), NUM_BUCKETS - 1);
+    }
+
+    #[test]
+    fn test_record() {
+        // Other tests may record metrics at the same time, so only the increase is checked
+        let before = snapshot();
+        increment(Counter::NodesExpanded);
+        record(Histogram::SearchMicros, 5);
+        let after = snapshot();
+        if cfg!(feature = "metrics") {
+            assert!(after.nodes_expanded > before.nodes_expanded);
+            assert!(after.search_micros.buckets[bucket(5)] > before.search_micros.buckets[bucket(5)]);
+            assert!(after.search_micros.max >= 5);
+        } else {
+            assert_eq!(after, Metrics::default());
+        }
+    }
+
+    #[test]
+    fn test_metrics_display() {
+        let mut metrics = Metrics { searches: 2, ..Metrics::default() };
+        metrics.search_micros.count = 2;
+        metrics.search_micros.sum = 7;
+        metrics.search_micros.buckets[bucket(2)] = 1;
+        metrics.search_micros.buckets[bucket(5)] = 1;
+        let text = metrics.to_string();
+        assert!(text.contains("dominoes_searches_total 2\n"));
+        assert!(text.contains("dominoes_search_micros_bucket{le=\"1\"} 0\n"));
+        assert!(text.contains("dominoes_search_micros_bucket{le=\"3\"} 1\n"));
+        assert!(text.contains("dominoes_search_micros_bucket{le=\"7\"} 2\n"));
+        assert!(text.contains("dominoes_search_micros_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.ends_with("dominoes_search_micros_count 2\n"));
+        assert_eq!(metrics.search_micros.mean(), 3.5);
+    }
+}
@@ -0,0 +1,83 @@
+//! Random number generators
+//!
+//! Everything random in a game, such as shuffling the boneyard, sampling the hidden hands, and choosing the actions of a rollout,
+//! uses a `GameRng` created by `Configuration::rng`. If the configuration has a seed (see `Configuration::with_seed`), every
+//! generator is derived from it, so a game played with the same seed and the same actions makes the same random choices. This
+//! makes shuffles, rollouts, and determinizations reproducible for debugging, replays, and regression tests. Without a seed,
+//! each generator is seeded from the operating system's source of entropy.
+//!
+//! Each use of randomness has its own stream (see `RngStream`), so that, for example, a player that searches longer does not
+//! change the deal of the next round.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The random number generator used in a game
+pub type GameRng = ChaCha8Rng;
+
+/// A use of randomness in a game, which has a stream of random numbers of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    /// Shuffling the boneyard for the deals. With the same seed, this is the same stream as a game created with a seed.
+    Deal,
+    /// The choices of a computer player, such as the hands that it samples and the actions of its rollouts
+    Player(u8),
+}
+
+impl RngStream {
+    // Returns the number of the stream in a ChaCha generator
+    fn id(self) -> u64 {
+        match self {
+            RngStream::Deal => 0,
+            RngStream::Player(player_id) => 1 + u64::from(player_id),
+        }
+    }
+}
+
+/// Creates a random number generator for a use of randomness
+///
+/// # Arguments
+/// * `seed` - The seed, or `None` to seed the generator from the operating system's source of entropy
+/// * `stream` - What the generator is used for
+///
+/// # Examples
+/// ```rust
+/// # use rules::{RngStream, game_rng};
+/// use rand::Rng;
+///
+/// let mut a = game_rng(Some(7), RngStream::Player(1));
+/// let mut b = game_rng(Some(7), RngStream::Player(1));
+/// assert_eq!(a.random::<u64>(), b.random::<u64>());
+/// ```
+pub fn game_rng(seed: Option<u64>, stream: RngStream) -> GameRng {
+    match seed {
+        Some(seed) => {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream.id());
+            rng
+        }
+        None => ChaCha8Rng::from_rng(&mut rand::rng()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_game_rng_streams() {
+        let draws = |seed, stream| -> Vec<u32> {
+            let mut rng = game_rng(seed, stream);
+            (0..4).map(|_| rng.random()).collect()
+        };
+        assert_eq!(draws(Some(1), RngStream::Deal), draws(Some(1), RngStream::Deal));
+        assert_ne!(draws(Some(1), RngStream::Deal), draws(Some(2), RngStream::Deal));
+        assert_ne!(draws(Some(1), RngStream::Player(0)), draws(Some(1), RngStream::Player(1)));
+        assert_ne!(draws(Some(1), RngStream::Deal), draws(Some(1), RngStream::Player(0)));
+
+        // The deal stream is the one used by a generator seeded directly
+        let mut seeded = ChaCha8Rng::seed_from_u64(1);
+        assert_eq!(draws(Some(1), RngStream::Deal), (0..4).map(|_| seeded.random()).collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,283 @@
+//! Layout rendering
+//!
+//! This module draws a layout as a picture rather than as the linear notation of `Display`, so that people playing in a terminal
+//! can see at a glance how the tiles are connected.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::Layout;
+
+impl Layout {
+    /// Draws the layout as a grid of text
+    ///
+    /// The first tile and the chains at its two ends are drawn on the first line, and each tile is drawn as `[a|b]`, with the
+    /// values that match facing each other. Other chains branch from doubles, and are drawn on lines below them, starting under
+    /// the middle of the double and joined to it by `|`. The branches are drawn from right to left, each on the highest line where
+    /// it does not overlap anything else.
+    ///
+    /// # Returns
+    /// The lines of the grid, without trailing spaces. An empty layout is drawn as an empty string.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Layout, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+    /// layout.attach_unchecked(Tile::from((2, 6)), Some(0));
+    /// layout.attach_unchecked(Tile::from((2, 2)), Some(2));
+    /// layout.attach_unchecked(Tile::from((2, 5)), Some(3));
+    /// layout.attach_unchecked(Tile::from((0, 2)), Some(3));
+    ///
+    /// assert_eq!(layout.render_ascii(), "[5|2][2|2][2|6][6|6][6|3]\n       |\n       [2|0]");
+    /// ```
+    pub fn render_ascii(&self) -> String {
+        self.drawing().grid.to_string()
+    }
+
+    // Places the tiles on a grid of text, as described by render_ascii
+    pub(crate) fn drawing(&self) -> Drawing {
+        let mut drawing = Drawing::default();
+        let Some(root) = self.nodes.first() else {
+            return drawing;
+        };
+
+        // The chains at the ends of the first tile are drawn to its left and right, and any others are branches
+        let (a, b) = root.tile.as_tuple();
+        let mut left = None;
+        let mut right = None;
+        let mut extra = Vec::new();
+        for &child in &root.children {
+            let (matched, _) = self.nodes[child].tile.matches(&root.tile).expect("Tiles should be attached");
+            if right.is_none() && matched == b {
+                right = Some(child);
+            } else if left.is_none() && matched == a {
+                left = Some(child);
+            } else {
+                extra.push(child);
+            }
+        }
+
+        let mut line: Vec<Placed> = left.map(|child| self.chain(child)).unwrap_or_default();
+        line.reverse();
+        for placed in &mut line {
+            placed.values = (placed.values.1, placed.values.0);
+            placed.text = format!("[{}|{}]", placed.values.0, placed.values.1);
+        }
+        line.push(Placed { node: 0, text: format!("[{a}|{b}]"), values: (a, b), branches: extra });
+        line.extend(right.map(|child| self.chain(child)).unwrap_or_default());
+
+        // Branches further right are drawn first, so that the longer branches to their left can be drawn below them without
+        // crossing their joins. Below the bottom of the grid, a branch always fits, though its join may have to cross a tile.
+        let mut pending = BinaryHeap::new();
+        drawing.draw_chain(0, 0, line, &mut pending);
+        while let Some((column, Reverse(row), Reverse(first))) = pending.pop() {
+            let chain = self.chain(first);
+            let width = chain.iter().map(Placed::width).sum();
+            let grid = &drawing.grid;
+            let mut branch_row = row + 2;
+            while branch_row < grid.rows.len()
+                && !(grid.is_free(branch_row, column, width) && (row + 1..branch_row).all(|r| grid.is_free(r, column, 1)))
+            {
+                branch_row += 2;
+            }
+            for r in row + 1..branch_row {
+                if drawing.grid.is_free(r, column, 1) {
+                    drawing.grid.put(r, column, "|");
+                    drawing.joins.push((r, column));
+                }
+            }
+            drawing.draw_chain(branch_row, column, chain, &mut pending);
+        }
+        drawing
+    }
+
+    // Returns the tiles of the chain that starts with a node, drawn left to right. Each tile but the last continues the chain with
+    // its first child, and any other children are branches.
+    fn chain(&self, first: usize) -> Vec<Placed> {
+        let mut chain = Vec::new();
+        let mut index = first;
+        loop {
+            let node = &self.nodes[index];
+            let parent = &self.nodes[node.parent.expect("Only the first tile has no parent")];
+            let values = node.tile.matches(&parent.tile).expect("Tiles should be attached");
+            let (next, branches) = match node.children.split_first() {
+                Some((&next, branches)) => (Some(next), branches.to_vec()),
+                None => (None, Vec::new()),
+            };
+            chain.push(Placed { node: index, text: format!("[{}|{}]", values.0, values.1), values, branches });
+            match next {
+                Some(next) => index = next,
+                None => return chain,
+            }
+        }
+    }
+}
+
+// The branches waiting to be drawn, with the column and row where they are joined to their doubles
+type Pending = BinaryHeap<(usize, Reverse<usize>, Reverse<usize>)>;
+
+// A layout placed on a grid of text. Only the grid is drawn as text, and the rest is drawn by the svg module.
+#[derive(Default)]
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+pub(crate) struct Drawing {
+    // The characters drawn
+    grid: Grid,
+    // The tiles, with the row and column where each one starts
+    pub(crate) tiles: Vec<(usize, usize, Placed)>,
+    // The cells of the joins between doubles and their branches, as (row, column)
+    pub(crate) joins: Vec<(usize, usize)>,
+}
+
+impl Drawing {
+    // Draws a chain starting at a row and column, and queues its branches to be drawn below the middle of their tiles. The
+    // branches are queued by column, row, and node.
+    fn draw_chain(&mut self, row: usize, mut column: usize, chain: Vec<Placed>, pending: &mut Pending) {
+        for placed in chain {
+            self.grid.put(row, column, &placed.text);
+            let middle = placed.middle(column);
+            pending.extend(placed.branches.iter().map(|&branch| (middle, Reverse(row), Reverse(branch))));
+            let width = placed.width();
+            self.tiles.push((row, column, placed));
+            column += width;
+        }
+    }
+}
+
+// A tile as it is drawn
+#[cfg_attr(not(feature = "svg"), allow(dead_code))]
+pub(crate) struct Placed {
+    // The node of the tile
+    pub(crate) node: usize,
+    // The text of the tile
+    text: String,
+    // The values on the left and right of the tile
+    pub(crate) values: (u8, u8),
+    // The nodes of the chains that branch from the tile
+    branches: Vec<usize>,
+}
+
+impl Placed {
+    // Returns the number of columns taken by the tile
+    pub(crate) fn width(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    // Returns the column of the line between the values of the tile, given the column where it starts
+    pub(crate) fn middle(&self, column: usize) -> usize {
+        column + 1 + digits(self.values.0)
+    }
+}
+
+// The characters drawn so far, by row and column
+#[derive(Default)]
+struct Grid {
+    rows: Vec<Vec<char>>,
+}
+
+impl Grid {
+    // Returns true if nothing has been drawn in a span of a row
+    fn is_free(&self, row: usize, column: usize, width: usize) -> bool {
+        self.rows.get(row).is_none_or(|chars| chars.iter().skip(column).take(width).all(|&c| c == ' '))
+    }
+
+    // Draws text starting at a row and column
+    fn put(&mut self, row: usize, column: usize, text: &str) {
+        if self.rows.len() <= row {
+            self.rows.resize(row + 1, Vec::new());
+        }
+        let chars = &mut self.rows[row];
+        for (offset, c) in text.chars().enumerate() {
+            if chars.len() <= column + offset {
+                chars.resize(column + offset + 1, ' ');
+            }
+            chars[column + offset] = c;
+        }
+    }
+}
+
+impl std::fmt::Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<String> = self.rows.iter().map(|chars| chars.iter().collect::<String>().trim_end().to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// Returns the number of digits in a value
+fn digits(value: u8) -> usize {
+    value.to_string().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Configuration, Tile, Variation};
+
+    #[test]
+    fn test_render_ascii_empty_and_single() {
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        assert_eq!(layout.render_ascii(), "");
+        layout.attach_unchecked(Tile::from((3, 5)), None);
+        assert_eq!(layout.render_ascii(), "[3|5]");
+    }
+
+    #[test]
+    fn test_render_ascii_chains_at_both_ends() {
+        // The chain on the 3 is drawn to the left, and the chain on the 5 to the right
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((3, 5)), None);
+        layout.attach_unchecked(Tile::from((5, 6)), Some(0));
+        layout.attach_unchecked(Tile::from((1, 3)), Some(0));
+        layout.attach_unchecked(Tile::from((1, 4)), Some(2));
+        assert_eq!(layout.render_ascii(), "[4|1][1|3][3|5][5|6]");
+    }
+
+    #[test]
+    fn test_render_ascii_branches_do_not_overlap() {
+        // Both doubles have branches, and the second one is drawn below the first
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        layout.attach_unchecked(Tile::from((4, 6)), Some(0));
+        layout.attach_unchecked(Tile::from((4, 4)), Some(1));
+        layout.attach_unchecked(Tile::from((0, 4)), Some(2));
+        layout.attach_unchecked(Tile::from((1, 4)), Some(2));
+        layout.attach_unchecked(Tile::from((0, 0)), Some(3));
+        layout.attach_unchecked(Tile::from((0, 5)), Some(5));
+        layout.attach_unchecked(Tile::from((0, 3)), Some(5));
+        layout.attach_unchecked(Tile::from((3, 5)), Some(7));
+        let expected = [
+            "[6|6][6|4][4|4][4|0][0|0][0|5]",
+            "            |         |",
+            "            [4|1]     [0|3][3|5]",
+        ];
+        assert_eq!(layout.render_ascii(), expected.join("\n"));
+
+        // A longer branch from the first double does not fit beside the second one's, so it is drawn lower
+        layout.attach_unchecked(Tile::from((1, 2)), Some(4));
+        layout.attach_unchecked(Tile::from((2, 3)), Some(9));
+        let expected = [
+            "[6|6][6|4][4|4][4|0][0|0][0|5]",
+            "            |         |",
+            "            |         [0|3][3|5]",
+            "            |",
+            "            [4|1][1|2][2|3]",
+        ];
+        assert_eq!(layout.render_ascii(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_render_ascii_wide_values() {
+        let configuration = Configuration::new(2, Variation::Traditional, 12, 7);
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((10, 10)), None);
+        layout.attach_unchecked(Tile::from((9, 10)), Some(0));
+        layout.attach_unchecked(Tile::from((10, 12)), Some(0));
+        assert_eq!(layout.render_ascii(), "[12|10][10|10][10|9]");
+    }
+}
@@ -0,0 +1,189 @@
+//! SVG rendering
+//!
+//! This module draws a layout as an SVG image, for documentation, debugging, and frontends that do not draw the tiles themselves.
+//! It is compiled only with the `svg` feature.
+
+use std::fmt::Write;
+
+use crate::Layout;
+
+// Width of a column of the grid of text used to place the tiles (see `Layout::render_ascii`), in pixels
+const COLUMN_WIDTH: f32 = 10.0;
+// Height of a row of the grid, in pixels
+const ROW_HEIGHT: f32 = 24.0;
+// Space around the tiles, in pixels
+const MARGIN: f32 = 10.0;
+// Distance between the centers of adjacent pips, in pixels
+const PIP_SPACING: f32 = 6.0;
+// Radius of a pip, in pixels
+const PIP_RADIUS: f32 = 2.0;
+
+impl Layout {
+    /// Draws the layout as an SVG image
+    ///
+    /// The tiles are placed as they are by `render_ascii`, with each character of the text drawn as a cell of the image. Values up
+    /// to 9 are drawn with pips, and larger values with numbers. Halves of tiles whose values are open ends are shaded, and
+    /// branches are joined to their doubles by lines.
+    ///
+    /// # Returns
+    /// The SVG document. An empty layout is drawn as an empty image.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::{Configuration, Layout, Tile};
+    ///
+    /// let config = Configuration::default();
+    /// let mut layout = Layout::new(&config);
+    /// layout.attach_unchecked(Tile::from((6, 6)), None);
+    /// layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+    ///
+    /// let svg = layout.to_svg();
+    /// assert!(svg.starts_with("<svg"));
+    /// assert_eq!(svg.matches("<g ").count(), 2);
+    /// assert_eq!(svg.matches("<circle ").count(), 6 + 6 + 6 + 3);
+    /// ```
+    pub fn to_svg(&self) -> String {
+        let drawing = self.drawing();
+        let rows = drawing.tiles.iter().map(|(row, _, _)| row + 1).max().unwrap_or(0);
+        let columns = drawing.tiles.iter().map(|(_, column, placed)| column + placed.width()).max().unwrap_or(0);
+        let width = columns as f32 * COLUMN_WIDTH + 2.0 * MARGIN;
+        let height = rows as f32 * ROW_HEIGHT + 2.0 * MARGIN;
+
+        let mut svg = String::new();
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}">"#)
+            .expect("Writing to a string cannot fail");
+        for &(row, column) in &drawing.joins {
+            let x = MARGIN + (column as f32 + 0.5) * COLUMN_WIDTH;
+            let y = MARGIN + row as f32 * ROW_HEIGHT;
+            writeln!(svg, r#"<line x1="{x}" y1="{y}" x2="{x}" y2="{}" stroke="black"/>"#, y + ROW_HEIGHT)
+                .expect("Writing to a string cannot fail");
+        }
+        for (row, column, placed) in &drawing.tiles {
+            let open = self.open.get_vec(&placed.node).map(Vec::as_slice).unwrap_or_default();
+            let left = MARGIN + *column as f32 * COLUMN_WIDTH;
+            let right = left + placed.width() as f32 * COLUMN_WIDTH;
+            let middle = MARGIN + (placed.middle(*column) as f32 + 0.5) * COLUMN_WIDTH;
+            let top = MARGIN + *row as f32 * ROW_HEIGHT;
+            writeln!(svg, r#"<g class="tile" data-node="{}">"#, placed.node).expect("Writing to a string cannot fail");
+            for (value, from, to) in [(placed.values.0, left, middle), (placed.values.1, middle, right)] {
+                let fill = if open.contains(&value) { "palegreen" } else { "ivory" };
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{fill}" stroke="black"/>"#,
+                    from + 0.5,
+                    top + 0.5,
+                    to - from - 1.0,
+                    ROW_HEIGHT - 1.0
+                )
+                .expect("Writing to a string cannot fail");
+                write_value(&mut svg, value, (from + to) / 2.0, top + ROW_HEIGHT / 2.0);
+            }
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Returns the positions of the pips of a value on a 3x3 grid
+///
+/// # Arguments
+/// * `value` - The value
+///
+/// # Returns
+/// The pips as (column, row), with the center of the grid at (0, 0), or `None` if the value is too large to be drawn with pips
+///
+/// # Examples
+/// ```rust
+/// # use rules::svg::pips;
+///
+/// assert_eq!(pips(1), Some(&[(0, 0)][..]));
+/// assert_eq!(pips(5).unwrap().len(), 5);
+/// assert_eq!(pips(10), None);
+/// ```
+pub fn pips(value: u8) -> Option<&'static [(i8, i8)]> {
+    Some(match value {
+        0 => &[],
+        1 => &[(0, 0)],
+        2 => &[(-1, -1), (1, 1)],
+        3 => &[(-1, -1), (0, 0), (1, 1)],
+        4 => &[(-1, -1), (1, -1), (-1, 1), (1, 1)],
+        5 => &[(-1, -1), (1, -1), (0, 0), (-1, 1), (1, 1)],
+        6 => &[(-1, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (1, 1)],
+        7 => &[(-1, -1), (1, -1), (-1, 0), (0, 0), (1, 0), (-1, 1), (1, 1)],
+        8 => &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)],
+        9 => &[(-1, -1), (0, -1), (1, -1), (-1, 0), (0, 0), (1, 0), (-1, 1), (0, 1), (1, 1)],
+        _ => return None,
+    })
+}
+
+// Writes the pips or the number of a value, centered at a point
+fn write_value(svg: &mut String, value: u8, x: f32, y: f32) {
+    match pips(value) {
+        Some(pips) => {
+            for &(column, row) in pips {
+                let (cx, cy) = (x + f32::from(column) * PIP_SPACING, y + f32::from(row) * PIP_SPACING);
+                writeln!(svg, r#"<circle cx="{cx}" cy="{cy}" r="{PIP_RADIUS}"/>"#).expect("Writing to a string cannot fail");
+            }
+        }
+        None => writeln!(
+            svg,
+            r#"<text x="{x}" y="{y}" font-size="12" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">{value}</text>"#
+        )
+        .expect("Writing to a string cannot fail"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Configuration, Tile, Variation};
+
+    #[test]
+    fn test_to_svg_empty() {
+        let configuration = Configuration::default();
+        let layout = Layout::new(&configuration);
+        let svg = layout.to_svg();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20">"#));
+        assert!(!svg.contains("<g "));
+    }
+
+    #[test]
+    fn test_to_svg_open_ends_and_branches() {
+        let configuration = Configuration::default();
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((6, 6)), None);
+        layout.attach_unchecked(Tile::from((3, 6)), Some(0));
+        layout.attach_unchecked(Tile::from((2, 6)), Some(0));
+        layout.attach_unchecked(Tile::from((2, 2)), Some(2));
+        layout.attach_unchecked(Tile::from((2, 5)), Some(3));
+        layout.attach_unchecked(Tile::from((0, 2)), Some(3));
+        let svg = layout.to_svg();
+
+        // Five tiles on the first row and one below the double two, joined to it by a line
+        assert_eq!(svg.matches("<g ").count(), 6);
+        assert_eq!(svg.matches("<line ").count(), 1);
+        assert!(svg.contains(r#"width="270" height="92""#));
+
+        // The open ends are the 5 at the left, the 3 at the right, and the 0 of the branch
+        assert_eq!(svg.matches(r#"fill="palegreen""#).count(), 3);
+    }
+
+    #[test]
+    fn test_to_svg_wide_values() {
+        let configuration = Configuration::new(2, Variation::Traditional, 12, 7);
+        let mut layout = Layout::new(&configuration);
+        layout.attach_unchecked(Tile::from((10, 12)), None);
+        let svg = layout.to_svg();
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(svg.contains(">12</text>"));
+    }
+
+    #[test]
+    fn test_pips() {
+        for value in 0..=9 {
+            assert_eq!(pips(value).unwrap().len(), value as usize);
+        }
+        assert!(pips(10).is_none());
+    }
+}
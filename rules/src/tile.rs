@@ -1,6 +1,9 @@
-use serde::{Serialize, Deserialize, Serializer, Deserializer, de};
-use serde::de::{Visitor, SeqAccess};
+#[cfg(feature = "serde")]
+use serde::de::{self, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::str::FromStr;
 
 use crate::*;
 
@@ -43,6 +46,41 @@ impl Tile {
         Self { ordinal }
     }
 
+    /// Creates a new tile from an ordinal value, or returns `None` if the ordinal is not a tile.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Tile;
+    ///
+    /// assert_eq!(Tile::checked_new(27), Some(Tile::from((6, 6))));
+    /// assert_eq!(Tile::checked_new(253), None);
+    /// ```
+    pub const fn checked_new(ordinal: u8) -> Option<Self> {
+        match checked_ordinal_to_tuple(ordinal) {
+            Some(_) => Some(Self { ordinal }),
+            None => None,
+        }
+    }
+
+    /// Creates a new tile from its values, or returns `None` if they are not a tile.
+    ///
+    /// Unlike `Tile::from`, this does not panic, so it is suitable for untrusted input. The values may be in either order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use rules::Tile;
+    ///
+    /// assert_eq!(Tile::checked_from_values(5, 3), Some(Tile::from((3, 5))));
+    /// assert_eq!(Tile::checked_from_values(3, 22), None);
+    /// ```
+    pub const fn checked_from_values(a: u8, b: u8) -> Option<Self> {
+        let values = if a <= b { (a, b) } else { (b, a) };
+        match checked_tuple_to_ordinal(values) {
+            Some(ordinal) => Some(Self { ordinal }),
+            None => None,
+        }
+    }
+
     /// Returns `true` if this tile is a double.
     ///
     /// # Examples
@@ -117,6 +155,7 @@ impl Tile {
 ///
 /// # Panics
 /// * If the input tuple is not in canonical form (i.e., if the first element is greater than the second).
+/// * If a value is larger than `MAX_PIPS`. Use `Tile::checked_from_values` for values that have not been validated.
 ///
 /// # Examples
 /// ```rust
@@ -216,6 +255,26 @@ impl fmt::Display for Tile {
     }
 }
 
+/// Enables parsing tiles written in the format "a|b", in either order.
+///
+/// # Examples
+/// ```rust
+/// # use rules::Tile;
+///
+/// assert_eq!("5|3".parse(), Ok(Tile::from((3, 5))));
+/// assert!("3|22".parse::<Tile>().is_err());
+/// ```
+impl FromStr for Tile {
+    type Err = ParseTileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('|')
+            .and_then(|(a, b)| Tile::checked_from_values(a.parse().ok()?, b.parse().ok()?))
+            .ok_or_else(|| ParseTileError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
 impl Serialize for Tile {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -226,6 +285,7 @@ impl Serialize for Tile {
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for Tile {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -250,7 +310,9 @@ impl<'de> Deserialize<'de> for Tile {
                     return Err(de::Error::invalid_length(3, &self));
                 }
                 // Create the tile, supporting non-canonical form
-                if a < b { Ok(Tile::from((a, b))) } else { Ok(Tile::from((b, a))) }
+                Tile::checked_from_values(a, b).ok_or_else(|| {
+                    de::Error::invalid_value(de::Unexpected::Unsigned(u64::from(a.max(b))), &"a value no larger than 21")
+                })
             }
         }
 
@@ -287,6 +349,13 @@ mod tests {
         assert_eq!(tile1, tile2);
         assert_eq!(tile1.as_tuple(), (1, 3));
 
+        // Test parsing
+        assert_eq!("1|3".parse(), Ok(tile1));
+        assert_eq!("3|1".parse::<Tile>().map(|tile| tile.to_string()), Ok("1|3".to_string()));
+        for text in ["", "1", "1|", "|3", "1-3", "a|3", "1|3|5", "0|22"] {
+            assert_eq!(text.parse::<Tile>(), Err(ParseTileError(text.to_string())));
+        }
+
         // Test from u8
         let tile3 = Tile::from(5u8);
         let tile4: Tile = 5u8.into();
@@ -370,6 +439,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_tile_serialize_deserialize() {
         let tile = Tile::from((3, 5));
 
@@ -391,6 +461,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_tile_deserialize_errors() {
         // Test invalid JSON formats
         assert!(serde_json::from_str::<Tile>("[]").is_err()); // Empty array
@@ -398,9 +469,21 @@ mod tests {
         assert!(serde_json::from_str::<Tile>("[1,2,3]").is_err()); // Too many elements
         assert!(serde_json::from_str::<Tile>("1").is_err()); // Not an array
         assert!(serde_json::from_str::<Tile>("{\"a\":1,\"b\":2}").is_err()); // Object instead of array
+        assert!(serde_json::from_str::<Tile>("[3,22]").is_err()); // Value too large
+        assert!(serde_json::from_str::<Tile>("[255,255]").is_err()); // Ordinal too large
+    }
+
+    #[test]
+    fn test_tile_checked_constructors() {
+        assert_eq!(Tile::checked_new(0), Some(Tile::from((0, 0))));
+        assert_eq!(Tile::checked_new(252), Some(Tile::from((21, 21))));
+        assert_eq!(Tile::checked_new(253), None);
+        assert_eq!(Tile::checked_from_values(21, 0), Some(Tile::from((0, 21))));
+        assert_eq!(Tile::checked_from_values(0, 22), None);
     }
 
     #[test]
+    #[cfg(feature = "serde")]
     fn test_tile_deserialize_non_canonical() {
         // Test that non-canonical form [b,a] where b > a is correctly converted to canonical form
 
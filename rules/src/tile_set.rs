@@ -0,0 +1,262 @@
+//! Tile sets
+//!
+//! This module provides `TileSet`, a domino set whose ID is a compile-time constant, so that code written for one set (e.g. a
+//! double-six game) can compute the size of the set and the number of end values at compile time. It also provides `TileMask`, a
+//! set of tiles stored as a bitmask, for code that tracks which tiles have been seen without hashing or searching a list.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Configuration, EndCounts, MAX_PIPS, Tile, Variation, set_size, tiles_in_set};
+
+// Number of 64-bit words needed for one bit per tile of the largest set
+const MASK_WORDS: usize = set_size(MAX_PIPS).div_ceil(64);
+
+/// A domino set whose ID is known at compile time
+///
+/// The ID of the set is the highest value on a tile. A set with an ID larger than `MAX_PIPS` fails to compile when it is used.
+///
+/// # Examples
+/// ```rust
+/// # use rules::{DoubleSix, TileSet, Variation};
+///
+/// assert_eq!(DoubleSix::SIZE, 28);
+/// assert_eq!(TileSet::<9>::NUM_VALUES, 10);
+/// assert_eq!(DoubleSix::tiles().count(), DoubleSix::SIZE);
+///
+/// let config = DoubleSix::configuration(2, Variation::Traditional);
+/// assert_eq!(config.set_id(), DoubleSix::ID);
+/// assert_eq!(DoubleSix::end_counts().len(), DoubleSix::NUM_VALUES);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TileSet<const N: u8>;
+
+/// The standard double-six set
+pub type DoubleSix = TileSet<6>;
+/// The double-nine set
+pub type DoubleNine = TileSet<9>;
+/// The double-twelve set
+pub type DoubleTwelve = TileSet<12>;
+/// The double-fifteen set
+pub type DoubleFifteen = TileSet<15>;
+/// The double-eighteen set
+pub type DoubleEighteen = TileSet<18>;
+
+impl<const N: u8> TileSet<N> {
+    /// The ID of the set, which is the highest value on a tile
+    pub const ID: u8 = {
+        assert!(N <= MAX_PIPS, "The set is larger than MAX_PIPS");
+        N
+    };
+
+    /// The number of values that can be on an end (0 to `ID`)
+    pub const NUM_VALUES: usize = Self::ID as usize + 1;
+
+    /// The number of tiles in the set
+    pub const SIZE: usize = set_size(Self::ID);
+
+    /// Returns the tiles in the set, in order of their ordinals
+    pub fn tiles() -> impl Iterator<Item = Tile> {
+        tiles_in_set(Self::ID)
+    }
+
+    /// Returns `true` if a tile is in the set
+    pub const fn contains(tile: Tile) -> bool {
+        (tile.ordinal as usize) < Self::SIZE
+    }
+
+    /// Returns a count of 0 for each end value of the set
+    pub const fn end_counts() -> EndCounts {
+        EndCounts::new(Self::ID)
+    }
+
+    /// Returns a configuration for a game played with this set and the default starting hand size
+    ///
+    /// # Arguments
+    /// * `num_players` - Number of players
+    /// * `variation` - The game variation being played
+    pub fn configuration(num_players: usize, variation: Variation) -> Configuration {
+        Configuration::new(
+            num_players,
+            variation,
+            Self::ID,
+            Configuration::default_starting_hand_size(num_players, variation),
+        )
+    }
+}
+
+/// A set of tiles stored as a bitmask indexed by ordinal
+///
+/// Adding, removing, and finding a tile take constant time, and a mask can hold tiles of any set without allocating. Tiles are
+/// iterated in order of their ordinals.
+///
+/// # Examples
+/// ```rust
+/// # use rules::{Tile, TileMask};
+///
+/// let mut unseen = TileMask::all(6);
+/// assert_eq!(unseen.len(), 28);
+/// assert!(unseen.remove(Tile::from((3, 6))));
+/// assert!(!unseen.contains(&Tile::from((3, 6))));
+/// assert_eq!(unseen.iter().next(), Some(Tile::from((0, 0))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TileMask {
+    // Bit n of word n / 64 is set if the tile with ordinal n is in the set
+    words: [u64; MASK_WORDS],
+}
+
+impl TileMask {
+    /// Creates an empty set of tiles
+    pub const fn new() -> Self {
+        Self { words: [0; MASK_WORDS] }
+    }
+
+    /// Creates a set containing every tile of a domino set
+    ///
+    /// # Arguments
+    /// * `set_id` - ID of the domino set
+    ///
+    /// # Panics
+    /// Panics if `set_id` is larger than `MAX_PIPS`.
+    pub fn all(set_id: u8) -> Self {
+        assert!(set_id <= MAX_PIPS, "set_id must be <= MAX_PIPS");
+        tiles_in_set(set_id).collect()
+    }
+
+    /// Adds a tile. Returns `true` if it was not already in the set.
+    pub fn insert(&mut self, tile: Tile) -> bool {
+        let (word, bit) = Self::position(tile);
+        let added = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        added
+    }
+
+    /// Removes a tile. Returns `true` if it was in the set.
+    pub fn remove(&mut self, tile: Tile) -> bool {
+        let (word, bit) = Self::position(tile);
+        let removed = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        removed
+    }
+
+    /// Returns `true` if a tile is in the set
+    pub fn contains(&self, tile: &Tile) -> bool {
+        let (word, bit) = Self::position(*tile);
+        self.words[word] & bit != 0
+    }
+
+    /// Returns the number of tiles in the set
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if the set has no tiles
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the tiles in the set, in order of their ordinals
+    pub fn iter(&self) -> TileMaskIter {
+        TileMaskIter { words: self.words, index: 0 }
+    }
+
+    // Returns the index of the word holding a tile's bit, and the bit
+    fn position(tile: Tile) -> (usize, u64) {
+        let ordinal = tile.ordinal as usize;
+        (ordinal / 64, 1 << (ordinal % 64))
+    }
+}
+
+impl FromIterator<Tile> for TileMask {
+    fn from_iter<I: IntoIterator<Item = Tile>>(tiles: I) -> Self {
+        let mut mask = Self::new();
+        for tile in tiles {
+            mask.insert(tile);
+        }
+        mask
+    }
+}
+
+impl IntoIterator for TileMask {
+    type Item = Tile;
+    type IntoIter = TileMaskIter;
+
+    fn into_iter(self) -> TileMaskIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &TileMask {
+    type Item = Tile;
+    type IntoIter = TileMaskIter;
+
+    fn into_iter(self) -> TileMaskIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the tiles in a `TileMask`, in order of their ordinals
+#[derive(Debug, Clone)]
+pub struct TileMaskIter {
+    // The bits of the tiles that have not been returned yet
+    words: [u64; MASK_WORDS],
+    // The index of the first word that may have bits set
+    index: usize,
+}
+
+impl Iterator for TileMaskIter {
+    type Item = Tile;
+
+    fn next(&mut self) -> Option<Tile> {
+        while self.index < MASK_WORDS {
+            let word = &mut self.words[self.index];
+            if *word != 0 {
+                let bit = word.trailing_zeros();
+                *word &= *word - 1;
+                return Some(Tile::new((self.index * 64) as u8 + bit as u8));
+            }
+            self.index += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_set_matches_configuration() {
+        let config = DoubleNine::configuration(4, Variation::Traditional);
+        assert_eq!(DoubleNine::tiles().collect::<Vec<_>>(), config.tiles());
+        assert_eq!(DoubleNine::SIZE, config.set_size());
+        assert!(DoubleNine::contains(Tile::from((9, 9))));
+        assert!(!DoubleNine::contains(Tile::from((0, 10))));
+        assert_eq!(TileSet::<0>::tiles().collect::<Vec<_>>(), vec![Tile::from((0, 0))]);
+        assert_eq!(TileSet::<{ MAX_PIPS }>::SIZE, set_size(MAX_PIPS));
+    }
+
+    #[test]
+    fn test_tile_mask() {
+        let mut mask = TileMask::new();
+        assert!(mask.is_empty());
+        let tiles = [Tile::from((0, 0)), Tile::from((6, 6)), Tile::from((10, 11)), Tile::from((MAX_PIPS, MAX_PIPS))];
+        for tile in tiles {
+            assert!(mask.insert(tile));
+        }
+        assert!(!mask.insert(Tile::from((6, 6))));
+        assert_eq!(mask.len(), 4);
+        assert_eq!(mask.iter().collect::<Vec<_>>(), tiles);
+        assert!(mask.contains(&Tile::from((10, 11))));
+        assert!(!mask.contains(&Tile::from((5, 6))));
+
+        assert!(mask.remove(Tile::from((10, 11))));
+        assert!(!mask.remove(Tile::from((10, 11))));
+        assert_eq!(mask.len(), 3);
+
+        assert_eq!(TileMask::all(6).iter().collect::<Vec<_>>(), DoubleSix::tiles().collect::<Vec<_>>());
+        assert_eq!(TileMask::all(MAX_PIPS).len(), set_size(MAX_PIPS));
+    }
+}